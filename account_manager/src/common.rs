@@ -1,6 +1,8 @@
+use account_utils::eth2_keystore::{keypair_from_secret, Keystore, KeystoreBuilder};
 use account_utils::PlainText;
 use account_utils::{read_input_from_user, strip_off_newlines};
 use eth2_wallet::bip39::{Language, Mnemonic};
+use eth2_wallet::{recover_validator_secret_from_mnemonic, KeyType, ValidatorKeystores};
 use std::fs;
 use std::path::PathBuf;
 use std::str::from_utf8;
@@ -65,3 +67,30 @@ pub fn read_wallet_name_from_cli(
         }
     }
 }
+
+/// Derive the EIP-2334 voting and withdrawal keystores of the validator at `index` from the seed
+/// of a mnemonic.
+pub fn validator_keystores_from_seed(
+    seed: &[u8],
+    index: u32,
+    voting_password: &[u8],
+    withdrawal_password: &[u8],
+) -> Result<ValidatorKeystores, String> {
+    let derive = |key_type: KeyType, password: &[u8]| -> Result<Keystore, String> {
+        let (secret, path) = recover_validator_secret_from_mnemonic(seed, index, key_type)
+            .map_err(|e| format!("Unable to recover validator keys: {:?}", e))?;
+
+        let keypair = keypair_from_secret(secret.as_bytes())
+            .map_err(|e| format!("Unable build keystore: {:?}", e))?;
+
+        KeystoreBuilder::new(&keypair, password, format!("{}", path))
+            .map_err(|e| format!("Unable build keystore: {:?}", e))?
+            .build()
+            .map_err(|e| format!("Unable build keystore: {:?}", e))
+    };
+
+    Ok(ValidatorKeystores {
+        voting: derive(KeyType::Voting, voting_password)?,
+        withdrawal: derive(KeyType::Withdrawal, withdrawal_password)?,
+    })
+}
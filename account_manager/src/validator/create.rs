@@ -1,4 +1,8 @@
-use crate::common::read_wallet_name_from_cli;
+use super::deposit_data::{write_deposit_data_file, DepositDataJson};
+use super::recover::MNEMONIC_FLAG;
+use crate::common::{
+    read_mnemonic_from_cli, read_wallet_name_from_cli, validator_keystores_from_seed,
+};
 use crate::wallet::create::STDIN_INPUTS_FLAG;
 use crate::{SECRETS_DIR_FLAG, WALLETS_DIR_FLAG};
 use account_utils::{
@@ -6,16 +10,19 @@ use account_utils::{
 };
 use clap::{App, Arg, ArgMatches};
 use directory::{
-    ensure_dir_exists, parse_path_or_default_with_flag, DEFAULT_SECRET_DIR, DEFAULT_WALLET_DIR,
+    ensure_dir_exists, get_testnet_name, parse_path_or_default_with_flag, DEFAULT_SECRET_DIR,
+    DEFAULT_WALLET_DIR,
 };
 use environment::Environment;
+use eth2_wallet::{bip39::Seed, ValidatorKeystores};
 use eth2_wallet_manager::WalletManager;
 use slashing_protection::{SlashingDatabase, SLASHING_PROTECTION_FILENAME};
 use std::ffi::OsStr;
 use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use types::EthSpec;
-use validator_dir::Builder as ValidatorDirBuilder;
+use types::{ChainSpec, EthSpec};
+use validator_dir::{Builder as ValidatorDirBuilder, ValidatorDir};
 
 pub const CMD: &str = "create";
 pub const WALLET_NAME_FLAG: &str = "wallet-name";
@@ -24,6 +31,9 @@ pub const DEPOSIT_GWEI_FLAG: &str = "deposit-gwei";
 pub const STORE_WITHDRAW_FLAG: &str = "store-withdrawal-keystore";
 pub const COUNT_FLAG: &str = "count";
 pub const AT_MOST_FLAG: &str = "at-most";
+pub const FROM_MNEMONIC_FLAG: &str = "from-mnemonic";
+pub const INDEX_RANGE_FLAG: &str = "index-range";
+pub const DEPOSIT_DATA_DIR_FLAG: &str = "deposit-data-dir";
 pub const WALLET_PASSWORD_PROMPT: &str = "Enter your wallet's password:";
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
@@ -103,6 +113,47 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .conflicts_with("count")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(FROM_MNEMONIC_FLAG)
+                .long(FROM_MNEMONIC_FLAG)
+                .help(
+                    "Derive the validators from a BIP-39 mnemonic instead of a wallet, creating \
+                    those with the key indices in --index-range.",
+                )
+                .requires(INDEX_RANGE_FLAG)
+                .conflicts_with_all(&[WALLET_NAME_FLAG, COUNT_FLAG, AT_MOST_FLAG]),
+        )
+        .arg(
+            Arg::with_name(INDEX_RANGE_FLAG)
+                .long(INDEX_RANGE_FLAG)
+                .value_name("START..END")
+                .help(
+                    "The EIP-2334 key indices of the validators to create with --from-mnemonic, \
+                    from START up to but not including END, e.g. 100..200.",
+                )
+                .requires(FROM_MNEMONIC_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(MNEMONIC_FLAG)
+                .long(MNEMONIC_FLAG)
+                .value_name("MNEMONIC_PATH")
+                .help("With --from-mnemonic, read the mnemonic from this file.")
+                .requires(FROM_MNEMONIC_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(DEPOSIT_DATA_DIR_FLAG)
+                .long(DEPOSIT_DATA_DIR_FLAG)
+                .value_name("DEPOSIT_DATA_DIR")
+                .help(
+                    "With --from-mnemonic, the directory to write a deposit_data-*.json file to, \
+                    containing the deposits of all the validators created.",
+                )
+                .requires(FROM_MNEMONIC_FLAG)
+                .default_value(".")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name(STDIN_INPUTS_FLAG)
                 .long(STDIN_INPUTS_FLAG)
@@ -144,6 +195,10 @@ pub fn cli_run<T: EthSpec>(
     eprintln!("secrets-dir path {:?}", secrets_dir);
     eprintln!("wallets-dir path {:?}", wallet_base_dir);
 
+    if matches.is_present(FROM_MNEMONIC_FLAG) {
+        return create_from_mnemonic(matches, &validator_dir, &secrets_dir, deposit_gwei, &spec);
+    }
+
     let starting_validator_count = existing_validator_count(&validator_dir)?;
 
     let n = match (count, at_most) {
@@ -180,6 +235,185 @@ pub fn cli_run<T: EthSpec>(
         .wallet_by_name(&wallet_name)
         .map_err(|e| format!("Unable to open wallet: {:?}", e))?;
 
+    let slashing_protection = open_slashing_protection(&validator_dir)?;
+
+    for i in 0..n {
+        let voting_password = random_password();
+        let withdrawal_password = random_password();
+
+        let keystores = wallet
+            .next_validator(
+                wallet_password.as_bytes(),
+                voting_password.as_bytes(),
+                withdrawal_password.as_bytes(),
+            )
+            .map_err(|e| format!("Unable to create validator keys: {:?}", e))?;
+
+        let voting_pubkey = keystores.voting.pubkey().to_string();
+
+        create_validator_dir(
+            keystores,
+            &voting_password,
+            &withdrawal_password,
+            &slashing_protection,
+            matches,
+            &validator_dir,
+            &secrets_dir,
+            deposit_gwei,
+            &spec,
+        )?;
+
+        println!("{}/{}\t0x{}", i + 1, n, voting_pubkey);
+    }
+
+    Ok(())
+}
+
+/// Create the validators with the key indices of `--index-range` from a mnemonic, and write all
+/// of their deposits to a single file.
+fn create_from_mnemonic(
+    matches: &ArgMatches,
+    validator_dir: &Path,
+    secrets_dir: &Path,
+    deposit_gwei: u64,
+    spec: &ChainSpec,
+) -> Result<(), String> {
+    let index_range = parse_index_range(&clap_utils::parse_required::<String>(
+        matches,
+        INDEX_RANGE_FLAG,
+    )?)?;
+    let mnemonic_path: Option<PathBuf> = clap_utils::parse_optional(matches, MNEMONIC_FLAG)?;
+    let deposit_data_dir: PathBuf = clap_utils::parse_required(matches, DEPOSIT_DATA_DIR_FLAG)?;
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+    let network_name = get_testnet_name(matches);
+
+    ensure_dir_exists(&deposit_data_dir)?;
+    let slashing_protection = open_slashing_protection(validator_dir)?;
+
+    let mnemonic = read_mnemonic_from_cli(mnemonic_path, stdin_inputs)?;
+    let seed = Seed::new(&mnemonic, "");
+
+    let n = index_range.len();
+    let mut deposits = Vec::with_capacity(n);
+    let mut failure = None;
+    for (i, index) in index_range.enumerate() {
+        match create_validator_from_seed(
+            &seed,
+            index,
+            &slashing_protection,
+            matches,
+            validator_dir,
+            secrets_dir,
+            deposit_gwei,
+            spec,
+            &network_name,
+        ) {
+            Ok((voting_pubkey, deposit)) => {
+                deposits.push(deposit);
+                println!("{}/{}\tIndex: {}\t0x{}", i + 1, n, index, voting_pubkey);
+            }
+            Err(e) => {
+                failure = Some(format!(
+                    "Unable to create the validator with index {}: {}",
+                    index, e
+                ));
+                break;
+            }
+        }
+    }
+
+    // Write the deposits of the validators created before any failure, so that they can still be
+    // deposited for.
+    if !deposits.is_empty() {
+        let path = write_deposit_data_file(&deposit_data_dir, &deposits)?;
+        eprintln!("Deposit data written to {:?}", path);
+    }
+
+    match failure {
+        Some(e) => Err(format!(
+            "{}. Created {} of {} validators.",
+            e,
+            deposits.len(),
+            n
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Create the validator with the key `index` derived from `seed`, returning its voting public key
+/// and deposit.
+#[allow(clippy::too_many_arguments)]
+fn create_validator_from_seed(
+    seed: &Seed,
+    index: u32,
+    slashing_protection: &SlashingDatabase,
+    matches: &ArgMatches,
+    validator_dir: &Path,
+    secrets_dir: &Path,
+    deposit_gwei: u64,
+    spec: &ChainSpec,
+    network_name: &str,
+) -> Result<(String, DepositDataJson), String> {
+    let voting_password = random_password();
+    let withdrawal_password = random_password();
+
+    let keystores = validator_keystores_from_seed(
+        seed.as_bytes(),
+        index,
+        voting_password.as_bytes(),
+        withdrawal_password.as_bytes(),
+    )?;
+    let voting_pubkey = keystores.voting.pubkey().to_string();
+
+    let validator = create_validator_dir(
+        keystores,
+        &voting_password,
+        &withdrawal_password,
+        slashing_protection,
+        matches,
+        validator_dir,
+        secrets_dir,
+        deposit_gwei,
+        spec,
+    )?;
+    let deposit_data = validator
+        .eth1_deposit_data()
+        .map_err(|e| format!("Unable to read deposit data: {:?}", e))?
+        .ok_or_else(|| format!("No deposit data for validator 0x{}", voting_pubkey))?;
+    let deposit = DepositDataJson::new(&deposit_data, spec, network_name);
+
+    Ok((voting_pubkey, deposit))
+}
+
+/// Parse a range of key indices such as `100..200`, which excludes its end.
+fn parse_index_range(range: &str) -> Result<Range<u32>, String> {
+    let invalid = || {
+        format!(
+            "Invalid --{} {:?}, expected START..END",
+            INDEX_RANGE_FLAG, range
+        )
+    };
+
+    let mut bounds = range.splitn(2, "..");
+    let start = bounds
+        .next()
+        .and_then(|start| start.parse().ok())
+        .ok_or_else(invalid)?;
+    let end = bounds
+        .next()
+        .and_then(|end| end.parse().ok())
+        .ok_or_else(invalid)?;
+
+    if start < end {
+        Ok(start..end)
+    } else {
+        Err(format!("--{} {:?} is empty", INDEX_RANGE_FLAG, range))
+    }
+}
+
+/// Open the slashing protection database of `validator_dir`, failing if the validator client is
+/// running.
+fn open_slashing_protection(validator_dir: &Path) -> Result<SlashingDatabase, String> {
     let slashing_protection_path = validator_dir.join(SLASHING_PROTECTION_FILENAME);
     let slashing_protection =
         SlashingDatabase::open_or_create(&slashing_protection_path).map_err(|e| {
@@ -198,48 +432,48 @@ pub fn cli_run<T: EthSpec>(
         )
     })?;
 
-    for i in 0..n {
-        let voting_password = random_password();
-        let withdrawal_password = random_password();
+    Ok(slashing_protection)
+}
 
-        let keystores = wallet
-            .next_validator(
-                wallet_password.as_bytes(),
-                voting_password.as_bytes(),
-                withdrawal_password.as_bytes(),
-            )
-            .map_err(|e| format!("Unable to create validator keys: {:?}", e))?;
+/// Register a new validator with slashing protection, and create its validator directory with a
+/// deposit of `deposit_gwei`.
+#[allow(clippy::too_many_arguments)]
+fn create_validator_dir(
+    keystores: ValidatorKeystores,
+    voting_password: &PlainText,
+    withdrawal_password: &PlainText,
+    slashing_protection: &SlashingDatabase,
+    matches: &ArgMatches,
+    validator_dir: &Path,
+    secrets_dir: &Path,
+    deposit_gwei: u64,
+    spec: &ChainSpec,
+) -> Result<ValidatorDir, String> {
+    let voting_pubkey = keystores.voting.public_key().ok_or_else(|| {
+        format!(
+            "Keystore public key is invalid: {}",
+            keystores.voting.pubkey()
+        )
+    })?;
 
-        let voting_pubkey = keystores.voting.public_key().ok_or_else(|| {
+    slashing_protection
+        .register_validator(&voting_pubkey)
+        .map_err(|e| {
             format!(
-                "Keystore public key is invalid: {}",
-                keystores.voting.pubkey()
+                "Error registering validator {}: {:?}",
+                voting_pubkey.to_hex_string(),
+                e
             )
         })?;
 
-        slashing_protection
-            .register_validator(&voting_pubkey)
-            .map_err(|e| {
-                format!(
-                    "Error registering validator {}: {:?}",
-                    voting_pubkey.to_hex_string(),
-                    e
-                )
-            })?;
-
-        ValidatorDirBuilder::new(validator_dir.clone())
-            .password_dir(secrets_dir.clone())
-            .voting_keystore(keystores.voting, voting_password.as_bytes())
-            .withdrawal_keystore(keystores.withdrawal, withdrawal_password.as_bytes())
-            .create_eth1_tx_data(deposit_gwei, &spec)
-            .store_withdrawal_keystore(matches.is_present(STORE_WITHDRAW_FLAG))
-            .build()
-            .map_err(|e| format!("Unable to build validator directory: {:?}", e))?;
-
-        println!("{}/{}\t{}", i + 1, n, voting_pubkey.to_hex_string());
-    }
-
-    Ok(())
+    ValidatorDirBuilder::new(validator_dir.to_path_buf())
+        .password_dir(secrets_dir)
+        .voting_keystore(keystores.voting, voting_password.as_bytes())
+        .withdrawal_keystore(keystores.withdrawal, withdrawal_password.as_bytes())
+        .create_eth1_tx_data(deposit_gwei, spec)
+        .store_withdrawal_keystore(matches.is_present(STORE_WITHDRAW_FLAG))
+        .build()
+        .map_err(|e| format!("Unable to build validator directory: {:?}", e))
 }
 
 /// Returns the number of validators that exist in the given `validator_dir`.
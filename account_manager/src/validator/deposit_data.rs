@@ -0,0 +1,318 @@
+//! Reads and writes the deposits of several validators in a single JSON file, in the format of the
+//! `deposit_data-*.json` files generated by the staking deposit CLI and accepted by the launchpad.
+use bls::{PublicKeyBytes, SignatureBytes};
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::ser::Formatter;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tree_hash::TreeHash;
+use types::{ChainSpec, DepositData, Hash256, SignedRoot};
+use validator_dir::Eth1DepositData;
+
+/// The version of the staking deposit CLI whose `deposit_data-*.json` files we reproduce.
+pub const DEPOSIT_CLI_VERSION: &str = "1.2.0";
+
+/// A deposit, with its fields hex-encoded without a `0x` prefix.
+///
+/// The fields are in the same order as in the files of the staking deposit CLI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositDataJson {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub amount: u64,
+    pub signature: String,
+    pub deposit_message_root: String,
+    pub deposit_data_root: String,
+    pub fork_version: String,
+    /// Named `eth2_network_name` by the staking deposit CLI, and `network_name` by its later
+    /// versions.
+    #[serde(default, rename = "eth2_network_name", alias = "network_name")]
+    pub network_name: String,
+    #[serde(default)]
+    pub deposit_cli_version: String,
+}
+
+impl DepositDataJson {
+    pub fn new(eth1_deposit_data: &Eth1DepositData, spec: &ChainSpec, network_name: &str) -> Self {
+        let deposit_data = &eth1_deposit_data.deposit_data;
+        Self {
+            pubkey: hex::encode(deposit_data.pubkey.as_serialized()),
+            withdrawal_credentials: hex::encode(deposit_data.withdrawal_credentials),
+            amount: deposit_data.amount,
+            signature: hex::encode(deposit_data.signature.serialize()),
+            deposit_message_root: hex::encode(deposit_data.as_deposit_message().tree_hash_root()),
+            deposit_data_root: hex::encode(eth1_deposit_data.root),
+            fork_version: hex::encode(spec.genesis_fork_version),
+            network_name: network_name.to_string(),
+            deposit_cli_version: DEPOSIT_CLI_VERSION.to_string(),
+        }
+    }
+
+    /// Check that the deposit is for the network of `spec`, that its roots and signature are
+    /// valid, and that its withdrawal credentials are BLS credentials equal to
+    /// `withdrawal_credentials`, if supplied.
+    pub fn verify(
+        &self,
+        spec: &ChainSpec,
+        withdrawal_credentials: Option<Hash256>,
+    ) -> Result<(), String> {
+        let fork_version = decode_hex("fork_version", &self.fork_version)?;
+        if fork_version != spec.genesis_fork_version {
+            return Err(format!(
+                "Fork version 0x{} is not the genesis fork version 0x{} of this network",
+                self.fork_version,
+                hex::encode(spec.genesis_fork_version)
+            ));
+        }
+
+        let deposit_data = DepositData {
+            pubkey: PublicKeyBytes::deserialize(&decode_hex("pubkey", &self.pubkey)?)
+                .map_err(|e| format!("Invalid pubkey: {:?}", e))?,
+            withdrawal_credentials: decode_hash(
+                "withdrawal_credentials",
+                &self.withdrawal_credentials,
+            )?,
+            amount: self.amount,
+            signature: SignatureBytes::deserialize(&decode_hex("signature", &self.signature)?)
+                .map_err(|e| format!("Invalid signature: {:?}", e))?,
+        };
+        let deposit_message = deposit_data.as_deposit_message();
+
+        if deposit_message.tree_hash_root()
+            != decode_hash("deposit_message_root", &self.deposit_message_root)?
+        {
+            return Err("Incorrect deposit_message_root".to_string());
+        }
+        if deposit_data.tree_hash_root()
+            != decode_hash("deposit_data_root", &self.deposit_data_root)?
+        {
+            return Err("Incorrect deposit_data_root".to_string());
+        }
+
+        if deposit_data.withdrawal_credentials.as_bytes()[0] != spec.bls_withdrawal_prefix_byte {
+            return Err(format!(
+                "Withdrawal credentials 0x{} are not BLS withdrawal credentials",
+                self.withdrawal_credentials
+            ));
+        }
+        if let Some(expected) = withdrawal_credentials {
+            if deposit_data.withdrawal_credentials != expected {
+                return Err(format!(
+                    "Withdrawal credentials 0x{} are not the expected {:?}",
+                    self.withdrawal_credentials, expected
+                ));
+            }
+        }
+
+        if self.amount < spec.min_deposit_amount || self.amount > spec.max_effective_balance {
+            return Err(format!(
+                "Amount of {} gwei is not between {} and {} gwei",
+                self.amount, spec.min_deposit_amount, spec.max_effective_balance
+            ));
+        }
+
+        let pubkey = deposit_data
+            .pubkey
+            .decompress()
+            .map_err(|e| format!("Invalid pubkey: {:?}", e))?;
+        let signature = deposit_data
+            .signature
+            .decompress()
+            .map_err(|e| format!("Invalid signature: {:?}", e))?;
+        let message = deposit_message.signing_root(spec.get_deposit_domain());
+        if !signature.verify(&pubkey, message) {
+            return Err("Invalid signature".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid hex in {}: {:?}", field, e))
+}
+
+/// Decode a hex-encoded 32 byte hash, with or without a `0x` prefix.
+pub fn decode_hash(field: &str, value: &str) -> Result<Hash256, String> {
+    let bytes = decode_hex(field, value)?;
+    if bytes.len() == Hash256::len_bytes() {
+        Ok(Hash256::from_slice(&bytes))
+    } else {
+        Err(format!("{} must be 32 bytes", field))
+    }
+}
+
+/// Formats JSON with the separators of Python's `json.dump`, as used by the staking deposit CLI,
+/// so that our files are byte-for-byte identical to its own.
+struct PythonFormatter;
+
+impl Formatter for PythonFormatter {
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
+
+/// Serialize `deposits` in the format of the staking deposit CLI.
+pub fn deposit_data_to_json_writer<W: Write>(
+    writer: W,
+    deposits: &[DepositDataJson],
+) -> Result<(), serde_json::Error> {
+    deposits.serialize(&mut serde_json::Serializer::with_formatter(
+        writer,
+        PythonFormatter,
+    ))
+}
+
+/// Write `deposits` to a new `deposit_data-{timestamp}.json` file in `dir`, returning its path.
+pub fn write_deposit_data_file(
+    dir: &Path,
+    deposits: &[DepositDataJson],
+) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Unable to read system time: {:?}", e))?
+        .as_secs();
+    let path = dir.join(format!("deposit_data-{}.json", timestamp));
+
+    let file = File::create(&path).map_err(|e| format!("Unable to create {:?}: {:?}", path, e))?;
+    deposit_data_to_json_writer(file, deposits)
+        .map_err(|e| format!("Unable to write {:?}: {:?}", path, e))?;
+
+    Ok(path)
+}
+
+/// Read the deposits of a `deposit_data-*.json` file.
+pub fn read_deposit_data_file(path: &Path) -> Result<Vec<DepositDataJson>, String> {
+    let file = File::open(path).map_err(|e| format!("Unable to open {:?}: {:?}", path, e))?;
+    serde_json::from_reader(file).map_err(|e| format!("Unable to parse {:?}: {:?}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::{get_withdrawal_credentials, Keypair};
+    use types::{EthSpec, MainnetEthSpec};
+
+    fn deposit(spec: &ChainSpec) -> DepositDataJson {
+        let keypair = Keypair::random();
+        let mut deposit_data = DepositData {
+            pubkey: keypair.pk.clone().into(),
+            withdrawal_credentials: Hash256::from_slice(&get_withdrawal_credentials(
+                &keypair.pk,
+                spec.bls_withdrawal_prefix_byte,
+            )),
+            amount: spec.max_effective_balance,
+            signature: SignatureBytes::empty(),
+        };
+        deposit_data.signature = deposit_data.create_signature(&keypair.sk, spec);
+
+        let eth1_deposit_data = Eth1DepositData {
+            rlp: vec![],
+            root: deposit_data.tree_hash_root(),
+            deposit_data,
+        };
+        DepositDataJson::new(&eth1_deposit_data, spec, "mainnet")
+    }
+
+    #[test]
+    fn verify_valid_deposit() {
+        let spec = MainnetEthSpec::default_spec();
+        let deposit = deposit(&spec);
+
+        assert_eq!(deposit.verify(&spec, None), Ok(()));
+        let withdrawal_credentials = decode_hash("", &deposit.withdrawal_credentials).unwrap();
+        assert_eq!(deposit.verify(&spec, Some(withdrawal_credentials)), Ok(()));
+    }
+
+    #[test]
+    fn verify_invalid_deposits() {
+        let spec = MainnetEthSpec::default_spec();
+        let valid = deposit(&spec);
+
+        let mut wrong_fork = spec.clone();
+        wrong_fork.genesis_fork_version = [0, 0, 0, 1];
+        assert!(valid.verify(&wrong_fork, None).is_err());
+
+        assert!(valid.verify(&spec, Some(Hash256::zero())).is_err());
+
+        let mut wrong_amount = valid.clone();
+        wrong_amount.amount -= 1;
+        assert!(wrong_amount.verify(&spec, None).is_err());
+
+        let mut wrong_signature = valid.clone();
+        wrong_signature.signature = deposit(&spec).signature;
+        assert!(wrong_signature.verify(&spec, None).is_err());
+    }
+
+    #[test]
+    fn python_json_format() {
+        let spec = MainnetEthSpec::default_spec();
+        let deposit = deposit(&spec);
+
+        let mut bytes = vec![];
+        deposit_data_to_json_writer(&mut bytes, &[deposit.clone()]).unwrap();
+        let json = String::from_utf8(bytes).unwrap();
+
+        assert!(json.starts_with(&format!(
+            "[{{\"pubkey\": \"{}\", \"withdrawal_credentials\": \"{}\", \"amount\": {}, ",
+            deposit.pubkey, deposit.withdrawal_credentials, deposit.amount
+        )));
+        assert!(json.ends_with(&format!(
+            "\"eth2_network_name\": \"mainnet\", \"deposit_cli_version\": \"{}\"}}]",
+            DEPOSIT_CLI_VERSION
+        )));
+        assert_eq!(
+            serde_json::from_str::<Vec<DepositDataJson>>(&json).unwrap(),
+            vec![deposit]
+        );
+    }
+
+    /// A file in the format of version 1.2.0 of the staking deposit CLI.
+    const GOLDEN_DEPOSIT_DATA: &str = include_str!("../../test_data/deposit_data-1606000000.json");
+
+    #[test]
+    fn golden_deposit_data_file() {
+        let deposits = serde_json::from_str::<Vec<DepositDataJson>>(GOLDEN_DEPOSIT_DATA).unwrap();
+        assert_eq!(deposits[0].network_name, "mainnet");
+        assert_eq!(deposits[0].deposit_cli_version, DEPOSIT_CLI_VERSION);
+
+        let mut bytes = vec![];
+        deposit_data_to_json_writer(&mut bytes, &deposits).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), GOLDEN_DEPOSIT_DATA);
+
+        // Files which name the network `network_name` are read too.
+        let renamed = GOLDEN_DEPOSIT_DATA.replace("\"eth2_network_name\"", "\"network_name\"");
+        assert_eq!(
+            serde_json::from_str::<Vec<DepositDataJson>>(&renamed).unwrap(),
+            deposits
+        );
+    }
+}
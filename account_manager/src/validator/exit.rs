@@ -1,22 +1,27 @@
 use crate::wallet::create::STDIN_INPUTS_FLAG;
+use account_utils::validator_definitions::{SigningDefinition, ValidatorDefinitions};
 use bls::{Keypair, PublicKey};
 use clap::{App, Arg, ArgMatches};
 use environment::Environment;
 use eth2::{
-    types::{GenesisData, StateId, ValidatorId, ValidatorStatus},
+    types::{GenesisData, StateId, ValidatorData, ValidatorId, ValidatorStatus},
     BeaconNodeHttpClient, Url,
 };
 use eth2_keystore::Keystore;
 use eth2_testnet_config::Eth2TestnetConfig;
 use safe_arith::SafeArith;
 use slot_clock::{SlotClock, SystemTimeSlotClock};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio_compat_02::FutureExt;
 use types::{ChainSpec, Epoch, EthSpec, Fork, VoluntaryExit};
 
 pub const CMD: &str = "exit";
 pub const KEYSTORE_FLAG: &str = "keystore";
+pub const VALIDATORS_FLAG: &str = "validators";
+pub const ALL_FLAG: &str = "all";
+pub const STATUS_FLAG: &str = "status";
+pub const EPOCH_FLAG: &str = "epoch";
 pub const PASSWORD_FILE_FLAG: &str = "password-file";
 pub const BEACON_SERVER_FLAG: &str = "beacon-node";
 pub const PASSWORD_PROMPT: &str = "Enter the keystore password";
@@ -28,21 +33,71 @@ pub const PROMPT: &str = "WARNING: WITHDRAWING STAKED ETH IS NOT CURRENTLY POSSI
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new("exit")
-        .about("Submits a VoluntaryExit to the beacon chain for a given validator keystore.")
+        .about(
+            "Submits a VoluntaryExit to the beacon chain for each of the given validator \
+            keystores, or validators in --validator-dir.",
+        )
         .arg(
             Arg::with_name(KEYSTORE_FLAG)
                 .long(KEYSTORE_FLAG)
                 .value_name("KEYSTORE_PATH")
-                .help("The path to the EIP-2335 voting keystore for the validator")
+                .help(
+                    "The path to the EIP-2335 voting keystore for the validator. May be supplied \
+                    multiple times to exit several validators.",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required_unless_one(&[VALIDATORS_FLAG, ALL_FLAG]),
+        )
+        .arg(
+            Arg::with_name(VALIDATORS_FLAG)
+                .long(VALIDATORS_FLAG)
+                .value_name("PUBKEYS")
+                .help(
+                    "A comma-separated list of the 0x-prefixed public keys of validators in \
+                    --validator-dir to exit.",
+                )
                 .takes_value(true)
-                .required(true),
+                .conflicts_with_all(&[KEYSTORE_FLAG, ALL_FLAG]),
+        )
+        .arg(
+            Arg::with_name(ALL_FLAG)
+                .long(ALL_FLAG)
+                .help("Exit all enabled validators in --validator-dir.")
+                .conflicts_with_all(&[KEYSTORE_FLAG, VALIDATORS_FLAG]),
+        )
+        .arg(
+            Arg::with_name(STATUS_FLAG)
+                .long(STATUS_FLAG)
+                .value_name("STATUSES")
+                .help(
+                    "A comma-separated list of validator statuses, e.g. active. Validators with \
+                    any other status are skipped rather than reported as failures.",
+                )
+                .default_value("active")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(EPOCH_FLAG)
+                .long(EPOCH_FLAG)
+                .value_name("EPOCH")
+                .help(
+                    "The epoch of the exits. If this epoch is in the future, the exits are \
+                    signed and published once it is reached. Defaults to the current epoch.",
+                )
+                .takes_value(true),
         )
         .arg(
             Arg::with_name(PASSWORD_FILE_FLAG)
                 .long(PASSWORD_FILE_FLAG)
                 .value_name("PASSWORD_FILE_PATH")
-                .help("The path to the password file which unlocks the validator voting keystore")
-                .takes_value(true),
+                .help(
+                    "The path to the password file which unlocks the validator voting keystores \
+                    given by --keystore",
+                )
+                .takes_value(true)
+                .requires(KEYSTORE_FLAG),
         )
         .arg(
             Arg::with_name(BEACON_SERVER_FLAG)
@@ -59,11 +114,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         )
 }
 
-pub fn cli_run<E: EthSpec>(matches: &ArgMatches, env: Environment<E>) -> Result<(), String> {
-    let keystore_path: PathBuf = clap_utils::parse_required(matches, KEYSTORE_FLAG)?;
+pub fn cli_run<E: EthSpec>(
+    matches: &ArgMatches,
+    env: Environment<E>,
+    validator_dir: PathBuf,
+) -> Result<(), String> {
     let password_file_path: Option<PathBuf> =
         clap_utils::parse_optional(matches, PASSWORD_FILE_FLAG)?;
     let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+    let statuses = parse_statuses(&clap_utils::parse_required::<String>(matches, STATUS_FLAG)?)?;
+    let exit_epoch: Option<Epoch> = clap_utils::parse_optional(matches, EPOCH_FLAG)?;
 
     let spec = env.eth2_config().spec.clone();
     let server_url: String = clap_utils::parse_required(matches, BEACON_SERVER_FLAG)?;
@@ -77,10 +137,38 @@ pub fn cli_run<E: EthSpec>(matches: &ArgMatches, env: Environment<E>) -> Result<
         .clone()
         .expect("network should have a valid config");
 
+    let keypairs = if let Some(keystore_paths) = matches.values_of(KEYSTORE_FLAG) {
+        keystore_paths
+            .map(|keystore_path| {
+                load_voting_keypair(
+                    &PathBuf::from(keystore_path),
+                    password_file_path.as_ref(),
+                    stdin_inputs,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        let pubkeys = matches
+            .value_of(VALIDATORS_FLAG)
+            .map(|pubkeys| {
+                pubkeys
+                    .split(',')
+                    .map(|pubkey| {
+                        pubkey
+                            .parse::<PublicKey>()
+                            .map_err(|e| format!("Invalid --{} {}: {}", VALIDATORS_FLAG, pubkey, e))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+        load_validator_dir_keypairs(&validator_dir, pubkeys.as_deref(), stdin_inputs)?
+    };
+
     env.runtime().block_on(
-        publish_voluntary_exit::<E>(
-            &keystore_path,
-            password_file_path.as_ref(),
+        publish_voluntary_exits::<E>(
+            &keypairs,
+            &statuses,
+            exit_epoch,
             &client,
             &spec,
             stdin_inputs,
@@ -92,10 +180,28 @@ pub fn cli_run<E: EthSpec>(matches: &ArgMatches, env: Environment<E>) -> Result<
     Ok(())
 }
 
-/// Gets the keypair and validator_index for every validator and calls `publish_voluntary_exit` on it.
-async fn publish_voluntary_exit<E: EthSpec>(
-    keystore_path: &PathBuf,
-    password_file_path: Option<&PathBuf>,
+/// Parse a comma-separated list of validator statuses.
+fn parse_statuses(statuses: &str) -> Result<Vec<ValidatorStatus>, String> {
+    statuses.split(',').map(|status| status.parse()).collect()
+}
+
+/// The outcome of exiting a single validator.
+#[derive(Debug, PartialEq)]
+enum ExitOutcome {
+    /// The validator has a status which was not selected by `--status`.
+    Skipped(ValidatorStatus),
+    /// The validator can't be exited, or its exit couldn't be published.
+    Failed(String),
+    /// The validator with this index will be exited.
+    Exiting(u64),
+}
+
+/// Checks the status of every validator, and publishes a voluntary exit at `exit_epoch` for each
+/// of those which have one of `statuses` and are eligible to exit.
+async fn publish_voluntary_exits<E: EthSpec>(
+    keypairs: &[Keypair],
+    statuses: &[ValidatorStatus],
+    exit_epoch: Option<Epoch>,
     client: &BeaconNodeHttpClient,
     spec: &ChainSpec,
     stdin_inputs: bool,
@@ -122,68 +228,148 @@ async fn publish_voluntary_exit<E: EthSpec>(
         return Err("Beacon node is still syncing".to_string());
     }
 
-    let keypair = load_voting_keypair(keystore_path, password_file_path, stdin_inputs)?;
-
-    let epoch = get_current_epoch::<E>(genesis_data.genesis_time, spec)
+    let slot_clock = get_slot_clock(genesis_data.genesis_time, spec);
+    let current_epoch = slot_clock
+        .now()
+        .map(|slot| slot.epoch(E::slots_per_epoch()))
         .ok_or_else(|| "Failed to get current epoch. Please check your system time".to_string())?;
-    let validator_index = get_validator_index_for_exit(client, &keypair.pk, epoch, spec).await?;
+    let epoch = exit_epoch.map_or(current_epoch, |epoch| std::cmp::max(epoch, current_epoch));
 
-    let fork = get_beacon_state_fork(client).await?;
-    let voluntary_exit = VoluntaryExit {
-        epoch,
-        validator_index,
-    };
+    let mut outcomes = Vec::with_capacity(keypairs.len());
+    for keypair in keypairs {
+        let validator_data = get_validator_data(client, &keypair.pk).await;
+        outcomes.push(select_exit(validator_data, statuses, epoch, spec));
+    }
+
+    let num_exiting = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, ExitOutcome::Exiting(_)))
+        .count();
+    if num_exiting == 0 {
+        report_outcomes(keypairs, &outcomes)?;
+        return Err("No validators are eligible to exit".to_string());
+    }
 
     eprintln!(
-        "Publishing a voluntary exit for validator: {} \n",
-        keypair.pk
+        "Publishing voluntary exits at epoch {} for {} validators:",
+        epoch, num_exiting
     );
+    for (keypair, outcome) in keypairs.iter().zip(&outcomes) {
+        if let ExitOutcome::Exiting(validator_index) = outcome {
+            eprintln!("{} (index {})", keypair.pk, validator_index);
+        }
+    }
+    eprintln!("");
     eprintln!("WARNING: THIS IS AN IRREVERSIBLE OPERATION\n");
     eprintln!("{}\n", PROMPT);
     eprintln!(
         "PLEASE VISIT {} TO MAKE SURE YOU UNDERSTAND THE IMPLICATIONS OF A VOLUNTARY EXIT.",
         WEBSITE_URL
     );
-    eprintln!("Enter the exit phrase from the above URL to confirm the voluntary exit: ");
+    eprintln!("Enter the exit phrase from the above URL to confirm the voluntary exits: ");
 
     let confirmation = account_utils::read_input_from_user(stdin_inputs)?;
-    if confirmation == CONFIRMATION_PHRASE {
-        // Sign and publish the voluntary exit to network
-        let signed_voluntary_exit = voluntary_exit.sign(
-            &keypair.sk,
-            &fork,
-            genesis_data.genesis_validators_root,
-            spec,
-        );
-        client
-            .post_beacon_pool_voluntary_exits(&signed_voluntary_exit)
-            .await
-            .map_err(|e| format!("Failed to publish voluntary exit: {}", e))?;
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await; // Provides nicer UX.
+    if confirmation != CONFIRMATION_PHRASE {
         eprintln!(
-            "Successfully validated and published voluntary exit for validator {}",
-            keypair.pk
+            "Did not publish voluntary exits. Please check that you entered the correct exit phrase."
         );
-    } else {
+        return Ok(());
+    }
+
+    if epoch > current_epoch {
+        let wait = slot_clock
+            .duration_to_slot(epoch.start_slot(E::slots_per_epoch()))
+            .ok_or_else(|| "Failed to read the slot clock".to_string())?;
         eprintln!(
-            "Did not publish voluntary exit for validator {}. Please check that you entered the correct exit phrase.",
-            keypair.pk
+            "Waiting {} seconds to publish the exits at the start of epoch {}",
+            wait.as_secs(),
+            epoch
         );
+        tokio::time::sleep(wait).await;
     }
 
-    Ok(())
+    let fork = get_beacon_state_fork(client).await?;
+    for (keypair, outcome) in keypairs.iter().zip(outcomes.iter_mut()) {
+        if let ExitOutcome::Exiting(validator_index) = *outcome {
+            let voluntary_exit = VoluntaryExit {
+                epoch,
+                validator_index,
+            };
+            // Sign and publish the voluntary exit to network
+            let signed_voluntary_exit = voluntary_exit.sign(
+                &keypair.sk,
+                &fork,
+                genesis_data.genesis_validators_root,
+                spec,
+            );
+            if let Err(e) = client
+                .post_beacon_pool_voluntary_exits(&signed_voluntary_exit)
+                .await
+            {
+                *outcome = ExitOutcome::Failed(format!("Failed to publish voluntary exit: {}", e));
+            }
+        }
+    }
+
+    report_outcomes(keypairs, &outcomes)
 }
 
-/// Get the validator index of a given the validator public key by querying the beacon node endpoint.
-///
-/// Returns an error if the beacon endpoint returns an error or given validator is not eligible for an exit.
-async fn get_validator_index_for_exit(
-    client: &BeaconNodeHttpClient,
-    validator_pubkey: &PublicKey,
+/// Decides whether to exit the validator with the given `validator_data` at `epoch`, skipping it
+/// if it doesn't have one of `statuses`.
+fn select_exit(
+    validator_data: Result<ValidatorData, String>,
+    statuses: &[ValidatorStatus],
     epoch: Epoch,
     spec: &ChainSpec,
-) -> Result<u64, String> {
-    let validator_data = client
+) -> ExitOutcome {
+    match validator_data {
+        Ok(validator_data) if !statuses.contains(&validator_data.status) => {
+            ExitOutcome::Skipped(validator_data.status)
+        }
+        Ok(validator_data) => match check_exit_eligibility(&validator_data, epoch, spec) {
+            Ok(()) => ExitOutcome::Exiting(validator_data.index),
+            Err(e) => ExitOutcome::Failed(e),
+        },
+        Err(e) => ExitOutcome::Failed(e),
+    }
+}
+
+/// Print the outcome of each exit, returning an error if any failed.
+fn report_outcomes(keypairs: &[Keypair], outcomes: &[ExitOutcome]) -> Result<(), String> {
+    let n = outcomes.len();
+    let mut num_failed = 0;
+    for (i, (keypair, outcome)) in keypairs.iter().zip(outcomes).enumerate() {
+        match outcome {
+            ExitOutcome::Skipped(status) => {
+                println!(
+                    "{}/{}\t{}\tskipped, status {}",
+                    i + 1,
+                    n,
+                    keypair.pk,
+                    status
+                )
+            }
+            ExitOutcome::Failed(e) => {
+                println!("{}/{}\t{}\tfailed: {}", i + 1, n, keypair.pk, e);
+                num_failed += 1;
+            }
+            ExitOutcome::Exiting(_) => println!("{}/{}\t{}\tpublished", i + 1, n, keypair.pk),
+        }
+    }
+
+    if num_failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("Failed to exit {} of {} validators", num_failed, n))
+    }
+}
+
+/// Get the details of the given validator by querying the beacon node endpoint.
+async fn get_validator_data(
+    client: &BeaconNodeHttpClient,
+    validator_pubkey: &PublicKey,
+) -> Result<ValidatorData, String> {
+    Ok(client
         .get_beacon_states_validator_id(
             StateId::Head,
             &ValidatorId::PublicKey(validator_pubkey.into()),
@@ -197,8 +383,16 @@ async fn get_validator_index_for_exit(
                 validator_pubkey
             )
         })?
-        .data;
+        .data)
+}
 
+/// Returns an error if the given validator is not eligible for an exit at `epoch`.
+fn check_exit_eligibility(
+    validator_data: &ValidatorData,
+    epoch: Epoch,
+    spec: &ChainSpec,
+) -> Result<(), String> {
+    let validator_pubkey = &validator_data.validator.pubkey;
     match validator_data.status {
         ValidatorStatus::Active => {
             let eligible_epoch = validator_data
@@ -206,9 +400,8 @@ async fn get_validator_index_for_exit(
                 .activation_epoch
                 .safe_add(spec.shard_committee_period)
                 .map_err(|e| format!("Failed to calculate eligible epoch, validator activation epoch too high: {:?}", e))?;
-
             if epoch >= eligible_epoch {
-                Ok(validator_data.index)
+                Ok(())
             } else {
                 Err(format!(
                     "Validator {:?} is not eligible for exit. It will become eligible on epoch {}",
@@ -252,14 +445,76 @@ async fn get_beacon_state_fork(client: &BeaconNodeHttpClient) -> Result<Fork, St
         .data)
 }
 
-/// Calculates the current epoch from the genesis time and current time.
-fn get_current_epoch<E: EthSpec>(genesis_time: u64, spec: &ChainSpec) -> Option<Epoch> {
-    let slot_clock = SystemTimeSlotClock::new(
+/// Creates a slot clock from the genesis time, to calculate the current epoch.
+fn get_slot_clock(genesis_time: u64, spec: &ChainSpec) -> SystemTimeSlotClock {
+    SystemTimeSlotClock::new(
         spec.genesis_slot,
         Duration::from_secs(genesis_time),
         Duration::from_millis(spec.milliseconds_per_slot),
-    );
-    slot_clock.now().map(|s| s.epoch(E::slots_per_epoch()))
+    )
+}
+
+/// Load the voting keypairs of the enabled validators in `validator_dir`, or only of those with
+/// the given `pubkeys`.
+fn load_validator_dir_keypairs(
+    validator_dir: &Path,
+    pubkeys: Option<&[PublicKey]>,
+    stdin_inputs: bool,
+) -> Result<Vec<Keypair>, String> {
+    let validator_definitions = ValidatorDefinitions::open(validator_dir).map_err(|e| {
+        format!(
+            "No validator definitions found in {:?}: {:?}",
+            validator_dir, e
+        )
+    })?;
+
+    let definitions = if let Some(pubkeys) = pubkeys {
+        pubkeys
+            .iter()
+            .map(|pubkey| {
+                validator_definitions
+                    .as_slice()
+                    .iter()
+                    .find(|def| def.voting_public_key == *pubkey)
+                    .ok_or_else(|| format!("Validator {} is not in {:?}", pubkey, validator_dir))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        validator_definitions
+            .as_slice()
+            .iter()
+            .filter(|def| def.enabled)
+            .collect()
+    };
+
+    definitions
+        .into_iter()
+        .map(|def| match &def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password: Some(password),
+            } if voting_keystore_password_path.is_none() => {
+                Keystore::from_json_file(voting_keystore_path)
+                    .and_then(|keystore| keystore.decrypt_keypair(password.as_ref()))
+                    .map_err(|e| {
+                        format!(
+                            "Error while decrypting keypair {:?}: {:?}",
+                            voting_keystore_path, e
+                        )
+                    })
+            }
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                ..
+            } => load_voting_keypair(
+                voting_keystore_path,
+                voting_keystore_password_path.as_ref(),
+                stdin_inputs,
+            ),
+        })
+        .collect()
 }
 
 /// Load the voting keypair by loading and decrypting the keystore.
@@ -307,10 +562,12 @@ fn load_voting_keypair(
 #[cfg(not(debug_assertions))]
 mod tests {
     use super::*;
+    use account_utils::validator_definitions::ValidatorDefinition;
     use eth2_keystore::KeystoreBuilder;
     use std::fs::File;
     use std::io::Write;
     use tempfile::{tempdir, TempDir};
+    use types::{MainnetEthSpec, Validator};
 
     const PASSWORD: &str = "cats";
     const KEYSTORE_NAME: &str = "keystore-m_12381_3600_0_0_0-1595406747.json";
@@ -349,4 +606,166 @@ mod tests {
 
         assert_eq!(expected_pk, kp.pk.into());
     }
+
+    /// Create a keystore for each of `n` validators in `dir`, with their passwords stored in the
+    /// validator definitions, and disable the last of them.
+    fn create_validator_dir(dir: &TempDir, n: usize) -> Vec<PublicKey> {
+        let mut definitions = ValidatorDefinitions::default();
+        let mut pubkeys = vec![];
+        for i in 0..n {
+            let keypair = Keypair::random();
+            let keystore = KeystoreBuilder::new(&keypair, PASSWORD.as_bytes(), "".into())
+                .unwrap()
+                .build()
+                .unwrap();
+            let keystore_path = dir.path().join(format!("keystore-{}.json", i));
+            File::create(&keystore_path)
+                .map(|mut file| keystore.to_json_writer(&mut file).unwrap())
+                .unwrap();
+
+            let mut definition = ValidatorDefinition::new_keystore_with_password(
+                &keystore_path,
+                Some(PASSWORD.to_string().into()),
+            )
+            .unwrap();
+            definition.enabled = i + 1 < n;
+            definitions.push(definition);
+            pubkeys.push(keypair.pk);
+        }
+        definitions.save(dir.path()).unwrap();
+        pubkeys
+    }
+
+    fn validator_data(status: ValidatorStatus, activation_epoch: Epoch) -> ValidatorData {
+        ValidatorData {
+            index: 42,
+            balance: 32_000_000_000,
+            status,
+            validator: Validator {
+                activation_epoch,
+                ..Validator::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_statuses() {
+        assert_eq!(parse_statuses("active"), Ok(vec![ValidatorStatus::Active]));
+        assert_eq!(
+            parse_statuses("active,exited_voluntarily"),
+            Ok(vec![
+                ValidatorStatus::Active,
+                ValidatorStatus::ExitedVoluntarily
+            ])
+        );
+        assert!(parse_statuses("active,exited").is_err());
+        assert!(parse_statuses("").is_err());
+    }
+
+    #[test]
+    fn test_check_exit_eligibility() {
+        let spec = MainnetEthSpec::default_spec();
+        let activation_epoch = Epoch::new(10);
+        let eligible_epoch = activation_epoch + spec.shard_committee_period;
+        let active = validator_data(ValidatorStatus::Active, activation_epoch);
+
+        assert_eq!(
+            check_exit_eligibility(&active, eligible_epoch, &spec),
+            Ok(())
+        );
+        assert!(check_exit_eligibility(&active, eligible_epoch - 1, &spec).is_err());
+
+        let exiting = validator_data(
+            ValidatorStatus::ActiveAwaitingVoluntaryExit,
+            activation_epoch,
+        );
+        assert!(check_exit_eligibility(&exiting, eligible_epoch, &spec).is_err());
+    }
+
+    #[test]
+    fn test_select_exit() {
+        let spec = MainnetEthSpec::default_spec();
+        let statuses = [ValidatorStatus::Active, ValidatorStatus::WaitingInQueue];
+        let epoch = Epoch::new(spec.shard_committee_period);
+
+        let eligible = validator_data(ValidatorStatus::Active, Epoch::new(0));
+        assert_eq!(
+            select_exit(Ok(eligible), &statuses, epoch, &spec),
+            ExitOutcome::Exiting(42)
+        );
+
+        let too_early = validator_data(ValidatorStatus::Active, Epoch::new(1));
+        assert!(matches!(
+            select_exit(Ok(too_early), &statuses, epoch, &spec),
+            ExitOutcome::Failed(_)
+        ));
+
+        // A selected status which can't exit is a failure, any other status is skipped.
+        let queued = validator_data(ValidatorStatus::WaitingInQueue, Epoch::new(0));
+        assert!(matches!(
+            select_exit(Ok(queued), &statuses, epoch, &spec),
+            ExitOutcome::Failed(_)
+        ));
+        let exited = validator_data(ValidatorStatus::ExitedVoluntarily, Epoch::new(0));
+        assert_eq!(
+            select_exit(Ok(exited), &statuses, epoch, &spec),
+            ExitOutcome::Skipped(ValidatorStatus::ExitedVoluntarily)
+        );
+
+        assert_eq!(
+            select_exit(Err("not found".to_string()), &statuses, epoch, &spec),
+            ExitOutcome::Failed("not found".to_string())
+        );
+    }
+
+    #[test]
+    fn test_report_outcomes() {
+        let keypairs = vec![Keypair::random(), Keypair::random(), Keypair::random()];
+
+        assert_eq!(
+            report_outcomes(
+                &keypairs,
+                &[
+                    ExitOutcome::Exiting(0),
+                    ExitOutcome::Skipped(ValidatorStatus::ExitedVoluntarily),
+                    ExitOutcome::Exiting(2),
+                ]
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            report_outcomes(
+                &keypairs,
+                &[
+                    ExitOutcome::Exiting(0),
+                    ExitOutcome::Failed("not found".to_string()),
+                    ExitOutcome::Failed("not eligible".to_string()),
+                ]
+            ),
+            Err("Failed to exit 2 of 3 validators".to_string())
+        );
+    }
+
+    #[test]
+    fn test_load_validator_dir_keypairs() {
+        let dir = tempdir().unwrap();
+        let pubkeys = create_validator_dir(&dir, 3);
+
+        // Without public keys, only the enabled validators are loaded.
+        let keypairs = load_validator_dir_keypairs(dir.path(), None, false).unwrap();
+        let loaded = keypairs.into_iter().map(|kp| kp.pk).collect::<Vec<_>>();
+        assert_eq!(loaded, pubkeys[..2].to_vec());
+
+        // Public keys select validators in the given order, including disabled ones.
+        let selected = vec![pubkeys[2].clone(), pubkeys[0].clone()];
+        let keypairs = load_validator_dir_keypairs(dir.path(), Some(&selected), false).unwrap();
+        let loaded = keypairs.into_iter().map(|kp| kp.pk).collect::<Vec<_>>();
+        assert_eq!(loaded, selected);
+
+        let unknown = vec![pubkeys[0].clone(), Keypair::random().pk];
+        assert!(load_validator_dir_keypairs(dir.path(), Some(&unknown), false).is_err());
+
+        let empty_dir = tempdir().unwrap();
+        assert!(load_validator_dir_keypairs(empty_dir.path(), None, false).is_err());
+    }
 }
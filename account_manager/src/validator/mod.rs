@@ -1,9 +1,11 @@
 pub mod create;
+pub mod deposit_data;
 pub mod exit;
 pub mod import;
 pub mod list;
 pub mod recover;
 pub mod slashing_protection;
+pub mod verify_deposit_data;
 
 use crate::VALIDATOR_DIR_FLAG;
 use clap::{App, Arg, ArgMatches};
@@ -34,6 +36,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         .subcommand(recover::cli_app())
         .subcommand(slashing_protection::cli_app())
         .subcommand(exit::cli_app())
+        .subcommand(verify_deposit_data::cli_app())
 }
 
 pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
@@ -53,7 +56,8 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<
         (slashing_protection::CMD, Some(matches)) => {
             slashing_protection::cli_run(matches, env, validator_base_dir)
         }
-        (exit::CMD, Some(matches)) => exit::cli_run(matches, env),
+        (exit::CMD, Some(matches)) => exit::cli_run(matches, env, validator_base_dir),
+        (verify_deposit_data::CMD, Some(matches)) => verify_deposit_data::cli_run(matches, env),
         (unknown, _) => Err(format!(
             "{} does not have a {} command. See --help",
             CMD, unknown
@@ -1,20 +1,19 @@
 use super::create::STORE_WITHDRAW_FLAG;
-use crate::common::read_mnemonic_from_cli;
+use crate::common::{read_mnemonic_from_cli, validator_keystores_from_seed};
 use crate::validator::create::COUNT_FLAG;
 use crate::wallet::create::STDIN_INPUTS_FLAG;
 use crate::SECRETS_DIR_FLAG;
-use account_utils::eth2_keystore::{keypair_from_secret, Keystore, KeystoreBuilder};
 use account_utils::random_password;
 use clap::{App, Arg, ArgMatches};
 use directory::ensure_dir_exists;
 use directory::{parse_path_or_default_with_flag, DEFAULT_SECRET_DIR};
 use eth2_wallet::bip39::Seed;
-use eth2_wallet::{recover_validator_secret_from_mnemonic, KeyType, ValidatorKeystores};
 use std::path::PathBuf;
 use validator_dir::Builder as ValidatorDirBuilder;
 pub const CMD: &str = "recover";
 pub const FIRST_INDEX_FLAG: &str = "first-index";
 pub const MNEMONIC_FLAG: &str = "mnemonic-path";
+pub const CONFIRMATION_PHRASE: &str = "I understand the slashing risk";
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
@@ -88,6 +87,20 @@ pub fn cli_run(matches: &ArgMatches, validator_dir: PathBuf) -> Result<(), Strin
     let mnemonic_path: Option<PathBuf> = clap_utils::parse_optional(matches, MNEMONIC_FLAG)?;
     let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
 
+    if count == 0 {
+        return Err(format!("--{} must be at least 1", COUNT_FLAG));
+    }
+    let last_index = first_index.checked_add(count - 1).ok_or_else(|| {
+        format!(
+            "--{} {} and --{} {} exceed the largest key index {}",
+            FIRST_INDEX_FLAG,
+            first_index,
+            COUNT_FLAG,
+            count,
+            u32::max_value()
+        )
+    })?;
+
     eprintln!("validator-dir path: {:?}", validator_dir);
     eprintln!("secrets-dir path: {:?}", secrets_dir);
 
@@ -97,33 +110,35 @@ pub fn cli_run(matches: &ArgMatches, validator_dir: PathBuf) -> Result<(), Strin
     eprintln!("");
     eprintln!("WARNING: KEY RECOVERY CAN LEAD TO DUPLICATING VALIDATORS KEYS, WHICH CAN LEAD TO SLASHING.");
     eprintln!("");
+    eprintln!(
+        "The keys with indices {} to {} will be recovered. They must not be running on any \
+        other validator client, now or in the future.",
+        first_index, last_index
+    );
+    eprintln!(
+        "Enter the phrase \"{}\" to confirm the key recovery: ",
+        CONFIRMATION_PHRASE
+    );
+
+    let confirmation = account_utils::read_input_from_user(stdin_inputs)?;
+    if confirmation != CONFIRMATION_PHRASE {
+        return Err("Key recovery cancelled, the confirmation phrase was not entered".into());
+    }
 
     let mnemonic = read_mnemonic_from_cli(mnemonic_path, stdin_inputs)?;
 
     let seed = Seed::new(&mnemonic, "");
 
-    for index in first_index..first_index + count {
+    for index in first_index..=last_index {
         let voting_password = random_password();
         let withdrawal_password = random_password();
 
-        let derive = |key_type: KeyType, password: &[u8]| -> Result<Keystore, String> {
-            let (secret, path) =
-                recover_validator_secret_from_mnemonic(seed.as_bytes(), index, key_type)
-                    .map_err(|e| format!("Unable to recover validator keys: {:?}", e))?;
-
-            let keypair = keypair_from_secret(secret.as_bytes())
-                .map_err(|e| format!("Unable build keystore: {:?}", e))?;
-
-            KeystoreBuilder::new(&keypair, password, format!("{}", path))
-                .map_err(|e| format!("Unable build keystore: {:?}", e))?
-                .build()
-                .map_err(|e| format!("Unable build keystore: {:?}", e))
-        };
-
-        let keystores = ValidatorKeystores {
-            voting: derive(KeyType::Voting, voting_password.as_bytes())?,
-            withdrawal: derive(KeyType::Withdrawal, withdrawal_password.as_bytes())?,
-        };
+        let keystores = validator_keystores_from_seed(
+            seed.as_bytes(),
+            index,
+            voting_password.as_bytes(),
+            withdrawal_password.as_bytes(),
+        )?;
 
         let voting_pubkey = keystores.voting.pubkey().to_string();
 
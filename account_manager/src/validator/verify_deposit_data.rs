@@ -0,0 +1,68 @@
+use super::deposit_data::{decode_hash, read_deposit_data_file};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use std::path::PathBuf;
+use types::{EthSpec, Hash256};
+
+pub const CMD: &str = "verify-deposit-data";
+pub const DEPOSIT_DATA_FLAG: &str = "deposit-data";
+pub const WITHDRAWAL_CREDENTIALS_FLAG: &str = "withdrawal-credentials";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Verifies the deposits in a deposit_data-*.json file before they are submitted, \
+            checking their signatures, withdrawal credentials and fork version.",
+        )
+        .arg(
+            Arg::with_name(DEPOSIT_DATA_FLAG)
+                .long(DEPOSIT_DATA_FLAG)
+                .value_name("DEPOSIT_DATA_PATH")
+                .help("The path to a deposit_data-*.json file.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(WITHDRAWAL_CREDENTIALS_FLAG)
+                .long(WITHDRAWAL_CREDENTIALS_FLAG)
+                .value_name("WITHDRAWAL_CREDENTIALS")
+                .help(
+                    "If present, check that every deposit has these 0x-prefixed withdrawal \
+                    credentials.",
+                )
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let spec = env.core_context().eth2_config.spec;
+    let deposit_data_path: PathBuf = clap_utils::parse_required(matches, DEPOSIT_DATA_FLAG)?;
+    let withdrawal_credentials: Option<Hash256> =
+        clap_utils::parse_optional::<String>(matches, WITHDRAWAL_CREDENTIALS_FLAG)?
+            .map(|credentials| decode_hash(WITHDRAWAL_CREDENTIALS_FLAG, &credentials))
+            .transpose()?;
+
+    let deposits = read_deposit_data_file(&deposit_data_path)?;
+
+    let n = deposits.len();
+    let mut num_invalid = 0;
+    for (i, deposit) in deposits.iter().enumerate() {
+        match deposit.verify(&spec, withdrawal_credentials) {
+            Ok(()) => println!("{}/{}\t0x{}\tvalid", i + 1, n, deposit.pubkey),
+            Err(e) => {
+                println!("{}/{}\t0x{}\tINVALID: {}", i + 1, n, deposit.pubkey, e);
+                num_invalid += 1;
+            }
+        }
+    }
+
+    if num_invalid == 0 {
+        eprintln!("All {} deposits are valid", n);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} deposits are invalid, do not submit them",
+            num_invalid, n
+        ))
+    }
+}
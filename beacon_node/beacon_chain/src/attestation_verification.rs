@@ -33,9 +33,10 @@ use crate::{
     metrics,
     observed_attestations::ObserveOutcome,
     observed_attesters::Error as ObservedAttestersError,
+    validator_pubkey_cache::ValidatorPubkeyCache,
     BeaconChain, BeaconChainError, BeaconChainTypes,
 };
-use bls::verify_signature_sets;
+use bls::{verify_signature_sets, SignatureSet};
 use proto_array::Block as ProtoBlock;
 use slog::debug;
 use slot_clock::SlotClock;
@@ -50,8 +51,8 @@ use state_processing::{
 use std::borrow::Cow;
 use tree_hash::TreeHash;
 use types::{
-    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Hash256, IndexedAttestation,
-    SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
+    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Fork, Hash256,
+    IndexedAttestation, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
 };
 
 /// Returned when an attestation was not successfully verified. It might not have been verified for
@@ -268,6 +269,21 @@ pub struct VerifiedUnaggregatedAttestation<T: BeaconChainTypes> {
     subnet_id: SubnetId,
 }
 
+/// A `SignedAggregateAndProof` which has passed all the checks that precede signature verification.
+struct IndexedAggregatedAttestation<T: BeaconChainTypes> {
+    signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    attestation_root: Hash256,
+}
+
+/// An `Attestation` which has passed all the checks that precede signature verification.
+struct IndexedUnaggregatedAttestation<T: BeaconChainTypes> {
+    attestation: Attestation<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    subnet_id: SubnetId,
+    validator_index: u64,
+}
+
 /// Custom `Clone` implementation is to avoid the restrictive trait bounds applied by the usual derive
 /// macro.
 impl<T: BeaconChainTypes> Clone for VerifiedUnaggregatedAttestation<T> {
@@ -504,12 +520,79 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
     ) -> Result<Self, AttestationSlashInfo<T, Error>> {
         use AttestationSlashInfo::*;
 
+        let indexed = IndexedAggregatedAttestation::verify(signed_aggregate, chain)?;
+
+        // Ensure that all signatures are valid.
+        if let Err(e) = verify_signed_aggregate_signatures(
+            chain,
+            &indexed.signed_aggregate,
+            &indexed.indexed_attestation,
+        )
+        .and_then(|is_valid| {
+            if !is_valid {
+                Err(Error::InvalidSignature)
+            } else {
+                Ok(())
+            }
+        }) {
+            return Err(SignatureInvalid(e));
+        }
+
+        Self::from_signature_verified(indexed, chain)
+    }
+
+    /// Run the late checks on an aggregate whose signatures have been verified.
+    fn from_signature_verified(
+        indexed: IndexedAggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, AttestationSlashInfo<T, Error>> {
+        let IndexedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+            attestation_root,
+        } = indexed;
+
+        if let Err(e) = Self::verify_late_checks(&signed_aggregate, attestation_root, chain) {
+            return Err(AttestationSlashInfo::SignatureValid(indexed_attestation, e));
+        }
+
+        Ok(VerifiedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+        })
+    }
+
+    /// A helper function to add this aggregate to `beacon_chain.op_pool`.
+    pub fn add_to_pool(self, chain: &BeaconChain<T>) -> Result<Self, Error> {
+        chain.add_to_block_inclusion_pool(self)
+    }
+
+    /// Returns the underlying `attestation` for the `signed_aggregate`.
+    pub fn attestation(&self) -> &Attestation<T::EthSpec> {
+        &self.signed_aggregate.message.aggregate
+    }
+
+    /// Returns the underlying `signed_aggregate`.
+    pub fn aggregate(&self) -> &SignedAggregateAndProof<T::EthSpec> {
+        &self.signed_aggregate
+    }
+}
+
+impl<T: BeaconChainTypes> IndexedAggregatedAttestation<T> {
+    /// Run the checks that precede signature verification, producing the indexed attestation.
+    fn verify(
+        signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, AttestationSlashInfo<T, Error>> {
+        use AttestationSlashInfo::*;
+
         let attestation = &signed_aggregate.message.aggregate;
         let aggregator_index = signed_aggregate.message.aggregator_index;
-        let attestation_root = match Self::verify_early_checks(&signed_aggregate, chain) {
-            Ok(root) => root,
-            Err(e) => return Err(SignatureNotChecked(signed_aggregate.message.aggregate, e)),
-        };
+        let attestation_root =
+            match VerifiedAggregatedAttestation::verify_early_checks(&signed_aggregate, chain) {
+                Ok(root) => root,
+                Err(e) => return Err(SignatureNotChecked(signed_aggregate.message.aggregate, e)),
+            };
 
         let indexed_attestation =
             match map_attestation_committee(chain, attestation, |(committee, _)| {
@@ -538,44 +621,12 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
                 Err(e) => return Err(SignatureNotChecked(signed_aggregate.message.aggregate, e)),
             };
 
-        // Ensure that all signatures are valid.
-        if let Err(e) =
-            verify_signed_aggregate_signatures(chain, &signed_aggregate, &indexed_attestation)
-                .and_then(|is_valid| {
-                    if !is_valid {
-                        Err(Error::InvalidSignature)
-                    } else {
-                        Ok(())
-                    }
-                })
-        {
-            return Err(SignatureInvalid(e));
-        }
-
-        if let Err(e) = Self::verify_late_checks(&signed_aggregate, attestation_root, chain) {
-            return Err(SignatureValid(indexed_attestation, e));
-        }
-
-        Ok(VerifiedAggregatedAttestation {
+        Ok(Self {
             signed_aggregate,
             indexed_attestation,
+            attestation_root,
         })
     }
-
-    /// A helper function to add this aggregate to `beacon_chain.op_pool`.
-    pub fn add_to_pool(self, chain: &BeaconChain<T>) -> Result<Self, Error> {
-        chain.add_to_block_inclusion_pool(self)
-    }
-
-    /// Returns the underlying `attestation` for the `signed_aggregate`.
-    pub fn attestation(&self) -> &Attestation<T::EthSpec> {
-        &self.signed_aggregate.message.aggregate
-    }
-
-    /// Returns the underlying `signed_aggregate`.
-    pub fn aggregate(&self) -> &SignedAggregateAndProof<T::EthSpec> {
-        &self.signed_aggregate
-    }
 }
 
 impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
@@ -721,44 +772,36 @@ impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
         subnet_id: Option<SubnetId>,
         chain: &BeaconChain<T>,
     ) -> Result<Self, AttestationSlashInfo<T, Error>> {
-        use AttestationSlashInfo::*;
+        let indexed = IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)?;
 
-        if let Err(e) = Self::verify_early_checks(&attestation, chain) {
-            return Err(SignatureNotChecked(attestation, e));
+        // The aggregate signature of the attestation is valid.
+        if let Err(e) = verify_attestation_signature(chain, &indexed.indexed_attestation) {
+            return Err(AttestationSlashInfo::SignatureInvalid(e));
         }
 
-        let (indexed_attestation, committees_per_slot) =
-            match obtain_indexed_attestation_and_committees_per_slot(chain, &attestation) {
-                Ok(x) => x,
-                Err(e) => {
-                    return Err(SignatureNotChecked(attestation, e));
-                }
-            };
+        Self::from_signature_verified(indexed, chain)
+    }
 
-        let (validator_index, expected_subnet_id) = match Self::verify_middle_checks(
-            &attestation,
-            &indexed_attestation,
-            committees_per_slot,
+    /// Run the late checks on an attestation whose signature has been verified.
+    fn from_signature_verified(
+        indexed: IndexedUnaggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, AttestationSlashInfo<T, Error>> {
+        let IndexedUnaggregatedAttestation {
+            attestation,
+            indexed_attestation,
             subnet_id,
-            chain,
-        ) {
-            Ok(t) => t,
-            Err(e) => return Err(SignatureNotCheckedIndexed(indexed_attestation, e)),
-        };
-
-        // The aggregate signature of the attestation is valid.
-        if let Err(e) = verify_attestation_signature(chain, &indexed_attestation) {
-            return Err(SignatureInvalid(e));
-        }
+            validator_index,
+        } = indexed;
 
         if let Err(e) = Self::verify_late_checks(&attestation, validator_index, chain) {
-            return Err(SignatureValid(indexed_attestation, e));
+            return Err(AttestationSlashInfo::SignatureValid(indexed_attestation, e));
         }
 
         Ok(Self {
             attestation,
             indexed_attestation,
-            subnet_id: expected_subnet_id,
+            subnet_id,
         })
     }
 
@@ -786,6 +829,48 @@ impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
     }
 }
 
+impl<T: BeaconChainTypes> IndexedUnaggregatedAttestation<T> {
+    /// Run the checks that precede signature verification, producing the indexed attestation.
+    fn verify(
+        attestation: Attestation<T::EthSpec>,
+        subnet_id: Option<SubnetId>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, AttestationSlashInfo<T, Error>> {
+        use AttestationSlashInfo::*;
+
+        if let Err(e) = VerifiedUnaggregatedAttestation::verify_early_checks(&attestation, chain) {
+            return Err(SignatureNotChecked(attestation, e));
+        }
+
+        let (indexed_attestation, committees_per_slot) =
+            match obtain_indexed_attestation_and_committees_per_slot(chain, &attestation) {
+                Ok(x) => x,
+                Err(e) => {
+                    return Err(SignatureNotChecked(attestation, e));
+                }
+            };
+
+        let (validator_index, expected_subnet_id) =
+            match VerifiedUnaggregatedAttestation::verify_middle_checks(
+                &attestation,
+                &indexed_attestation,
+                committees_per_slot,
+                subnet_id,
+                chain,
+            ) {
+                Ok(t) => t,
+                Err(e) => return Err(SignatureNotCheckedIndexed(indexed_attestation, e)),
+            };
+
+        Ok(Self {
+            attestation,
+            indexed_attestation,
+            subnet_id: expected_subnet_id,
+            validator_index,
+        })
+    }
+}
+
 /// Returns `Ok(())` if the `attestation.data.beacon_block_root` is known to this chain.
 /// You can use this `shuffling_id` to read from the shuffling cache.
 ///
@@ -976,22 +1061,41 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
         .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
 
-    let aggregator_index = signed_aggregate.message.aggregator_index;
-    if aggregator_index >= pubkey_cache.len() as u64 {
-        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
-    }
-
     let fork = chain
         .canonical_head
         .try_read_for(HEAD_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
         .map(|head| head.beacon_state.fork)?;
 
-    let signature_sets = vec![
+    let signature_sets = signed_aggregate_signature_sets(
+        chain,
+        &pubkey_cache,
+        &fork,
+        signed_aggregate,
+        indexed_attestation,
+    )?;
+
+    Ok(verify_signature_sets(signature_sets.iter()))
+}
+
+/// Returns the three signature sets verified by `verify_signed_aggregate_signatures`.
+fn signed_aggregate_signature_sets<'a, T: BeaconChainTypes>(
+    chain: &'a BeaconChain<T>,
+    pubkey_cache: &'a ValidatorPubkeyCache,
+    fork: &Fork,
+    signed_aggregate: &'a SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: &'a IndexedAttestation<T::EthSpec>,
+) -> Result<Vec<SignatureSet<'a>>, Error> {
+    let aggregator_index = signed_aggregate.message.aggregator_index;
+    if aggregator_index >= pubkey_cache.len() as u64 {
+        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
+    }
+
+    Ok(vec![
         signed_aggregate_selection_proof_signature_set(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
             &signed_aggregate,
-            &fork,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
@@ -999,7 +1103,7 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
         signed_aggregate_signature_set(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
             &signed_aggregate,
-            &fork,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
@@ -1008,14 +1112,222 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
             &indexed_attestation.signature,
             &indexed_attestation,
-            &fork,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
         .map_err(BeaconChainError::SignatureSetError)?,
-    ];
+    ])
+}
 
-    Ok(verify_signature_sets(signature_sets.iter()))
+/// Verifies a batch of unaggregated attestations, returning a result for each attestation in the
+/// same order as `attestations`.
+///
+/// The attestations are checked exactly as `VerifiedUnaggregatedAttestation::verify` would check
+/// them, except that the signatures of all the attestations which pass the preceding checks are
+/// verified together in a single BLS batch. If the batch is invalid, each signature is verified
+/// individually so that only the attestations with invalid signatures are rejected.
+///
+/// An `Err` is only returned if there was an error which prevented the batch from being verified.
+pub fn batch_verify_unaggregated_attestations<T: BeaconChainTypes>(
+    attestations: Vec<(Attestation<T::EthSpec>, Option<SubnetId>)>,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, Error>>, Error> {
+    let indexed_results = attestations
+        .into_iter()
+        .map(|(attestation, subnet_id)| {
+            IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)
+        })
+        .collect::<Vec<_>>();
+
+    let signature_results = {
+        let signature_setup_timer = metrics::start_timer(
+            &metrics::ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_SETUP_TIMES,
+        );
+
+        let pubkey_cache = chain
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+
+        let fork = chain
+            .canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
+            .map(|head| head.beacon_state.fork)?;
+
+        let signature_sets = indexed_results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|indexed| {
+                indexed_attestation_signature_set_from_pubkeys(
+                    |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+                    &indexed.indexed_attestation.signature,
+                    &indexed.indexed_attestation,
+                    &fork,
+                    chain.genesis_validators_root,
+                    &chain.spec,
+                )
+                .map(|signature_set| vec![signature_set])
+                .map_err(|e| Error::from(BeaconChainError::SignatureSetError(e)))
+            })
+            .collect::<Vec<_>>();
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let _signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_TIMES);
+
+        batch_verify_signature_sets(signature_sets)
+    };
+
+    // There is exactly one signature result for each attestation which passed the checks that
+    // precede signature verification.
+    let mut signature_results = signature_results.into_iter();
+    Ok(indexed_results
+        .into_iter()
+        .map(|indexed_result| {
+            let indexed = indexed_result?;
+            match signature_results.next() {
+                Some(Ok(())) => {
+                    VerifiedUnaggregatedAttestation::from_signature_verified(indexed, chain)
+                }
+                Some(Err(e)) => Err(AttestationSlashInfo::SignatureInvalid(e)),
+                None => Err(AttestationSlashInfo::SignatureInvalid(
+                    Error::InvalidSignature,
+                )),
+            }
+        })
+        .map(|result| {
+            result
+                .map(|verified_unaggregated| {
+                    if let Some(slasher) = chain.slasher.as_ref() {
+                        slasher
+                            .accept_attestation(verified_unaggregated.indexed_attestation.clone());
+                    }
+                    verified_unaggregated
+                })
+                .map_err(|slash_info| process_slash_info(slash_info, chain))
+        })
+        .collect())
+}
+
+/// Verifies a batch of `SignedAggregateAndProof`, returning a result for each aggregate in the
+/// same order as `signed_aggregates`.
+///
+/// As for `batch_verify_unaggregated_attestations`, but for aggregates, which each contribute the
+/// three signature sets checked by `verify_signed_aggregate_signatures` to the batch.
+pub fn batch_verify_aggregated_attestations<T: BeaconChainTypes>(
+    signed_aggregates: Vec<SignedAggregateAndProof<T::EthSpec>>,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, Error>>, Error> {
+    let indexed_results = signed_aggregates
+        .into_iter()
+        .map(|signed_aggregate| IndexedAggregatedAttestation::verify(signed_aggregate, chain))
+        .collect::<Vec<_>>();
+
+    let signature_results = {
+        let signature_setup_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_SETUP_TIMES);
+
+        let pubkey_cache = chain
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+
+        let fork = chain
+            .canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
+            .map(|head| head.beacon_state.fork)?;
+
+        let signature_sets = indexed_results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|indexed| {
+                signed_aggregate_signature_sets(
+                    chain,
+                    &pubkey_cache,
+                    &fork,
+                    &indexed.signed_aggregate,
+                    &indexed.indexed_attestation,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let _signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_TIMES);
+
+        batch_verify_signature_sets(signature_sets)
+    };
+
+    // There is exactly one signature result for each aggregate which passed the checks that
+    // precede signature verification.
+    let mut signature_results = signature_results.into_iter();
+    Ok(indexed_results
+        .into_iter()
+        .map(|indexed_result| {
+            let indexed = indexed_result?;
+            match signature_results.next() {
+                Some(Ok(())) => {
+                    VerifiedAggregatedAttestation::from_signature_verified(indexed, chain)
+                }
+                Some(Err(e)) => Err(AttestationSlashInfo::SignatureInvalid(e)),
+                None => Err(AttestationSlashInfo::SignatureInvalid(
+                    Error::InvalidSignature,
+                )),
+            }
+        })
+        .map(|result| {
+            result
+                .map(|verified_aggregate| {
+                    if let Some(slasher) = chain.slasher.as_ref() {
+                        slasher.accept_attestation(verified_aggregate.indexed_attestation.clone());
+                    }
+                    verified_aggregate
+                })
+                .map_err(|slash_info| process_slash_info(slash_info, chain))
+        })
+        .collect())
+}
+
+/// Verifies the signature sets of each item in a batch, returning `Ok(())` for each item whose
+/// signature sets are all valid.
+///
+/// The sets of all items are first verified together, which is much cheaper than verifying each
+/// item separately. Only if that fails is each item verified on its own, so that an invalid
+/// signature causes only its own item to be rejected.
+fn batch_verify_signature_sets(
+    item_signature_sets: Vec<Result<Vec<SignatureSet>, Error>>,
+) -> Vec<Result<(), Error>> {
+    let all_signature_sets = item_signature_sets
+        .iter()
+        .filter_map(|signature_sets| signature_sets.as_ref().ok())
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let batch_is_valid = if all_signature_sets.is_empty() {
+        false
+    } else if verify_signature_sets(all_signature_sets.into_iter()) {
+        true
+    } else {
+        metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES);
+        false
+    };
+
+    item_signature_sets
+        .into_iter()
+        .map(|signature_sets| {
+            let signature_sets = signature_sets?;
+            if batch_is_valid || verify_signature_sets(signature_sets.iter()) {
+                Ok(())
+            } else {
+                Err(Error::InvalidSignature)
+            }
+        })
+        .collect()
 }
 
 /// Assists in readability.
@@ -1,4 +1,5 @@
 use crate::attestation_verification::{
+    batch_verify_aggregated_attestations, batch_verify_unaggregated_attestations,
     Error as AttestationError, SignatureVerifiedAttestation, VerifiedAggregatedAttestation,
     VerifiedUnaggregatedAttestation,
 };
@@ -11,6 +12,7 @@ use crate::chain_config::ChainConfig;
 use crate::errors::{BeaconChainError as Error, BlockProductionError};
 use crate::eth1_chain::{Eth1Chain, Eth1ChainBackend};
 use crate::events::{EventHandler, EventKind};
+use crate::fork_revert::load_fork_choice;
 use crate::head_tracker::HeadTracker;
 use crate::migrate::BackgroundMigrator;
 use crate::naive_aggregation_pool::{Error as NaiveAggregationError, NaiveAggregationPool};
@@ -20,6 +22,7 @@ use crate::observed_block_producers::ObservedBlockProducers;
 use crate::observed_operations::{ObservationOutcome, ObservedOperations};
 use crate::persisted_beacon_chain::{PersistedBeaconChain, DUMMY_CANONICAL_HEAD_BLOCK_ROOT};
 use crate::persisted_fork_choice::PersistedForkChoice;
+use crate::proposer_cache::{proposer_shuffling_decision_root, ProposerCache};
 use crate::shuffling_cache::{BlockShufflingIds, ShufflingCache};
 use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
@@ -32,7 +35,7 @@ use futures::channel::mpsc::Sender;
 use itertools::process_results;
 use operation_pool::{OperationPool, PersistedOperationPool};
 use parking_lot::{Mutex, RwLock};
-use slasher::Slasher;
+use slasher::{Slasher, SlasherStatus};
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use slot_clock::SlotClock;
 use state_processing::{
@@ -41,7 +44,7 @@ use state_processing::{
     BlockSignatureStrategy, SigVerifiedOp, VerifyOperation,
 };
 use std::borrow::Cow;
-use std::cmp::Ordering;
+use std::cmp::{max, Ordering};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io::prelude::*;
@@ -151,6 +154,13 @@ pub struct HeadInfo {
     pub genesis_validators_root: Hash256,
 }
 
+/// The outcome of garbage collecting orphaned states from the hot database.
+#[derive(Debug, Default, PartialEq)]
+pub struct StateGarbageCollection {
+    pub states_deleted: usize,
+    pub bytes_reclaimed: u64,
+}
+
 pub trait BeaconChainTypes: Send + Sync + 'static {
     type HotStore: store::ItemStore<Self::EthSpec>;
     type ColdStore: store::ItemStore<Self::EthSpec>;
@@ -222,6 +232,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub(crate) snapshot_cache: TimeoutRwLock<SnapshotCache<T::EthSpec>>,
     /// Caches the shuffling for a given epoch and state root.
     pub(crate) shuffling_cache: TimeoutRwLock<ShufflingCache>,
+    /// Caches the block proposers for a given epoch and decision root.
+    pub proposer_cache: Mutex<ProposerCache>,
     /// Caches a map of `validator_index -> validator_pubkey`.
     pub(crate) validator_pubkey_cache: TimeoutRwLock<ValidatorPubkeyCache>,
     /// A list of any hard-coded forks that have been disabled.
@@ -283,6 +295,25 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         persisted_fork_choice.as_kv_store_op(FORK_CHOICE_DB_KEY)
     }
 
+    /// Persists the head tracker, fork choice and `self.op_pool` in a single atomic write, so
+    /// that a crash can't leave one of them out of step with the others.
+    pub fn persist_head_fork_choice_and_op_pool(&self) -> Result<(), Error> {
+        let mut batch = vec![];
+
+        let _head_timer = metrics::start_timer(&metrics::PERSIST_HEAD);
+        batch.push(self.persist_head_in_batch());
+
+        let _fork_choice_timer = metrics::start_timer(&metrics::PERSIST_FORK_CHOICE);
+        batch.push(self.persist_fork_choice_in_batch());
+
+        let _op_pool_timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
+        batch.push(self.persist_op_pool_in_batch());
+
+        self.store.hot_db.do_atomically(batch)?;
+
+        Ok(())
+    }
+
     /// Persists `self.op_pool` to disk.
     ///
     /// ## Notes
@@ -292,14 +323,18 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub fn persist_op_pool(&self) -> Result<(), Error> {
         let _timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
 
-        self.store.put_item(
-            &OP_POOL_DB_KEY,
-            &PersistedOperationPool::from_operation_pool(&self.op_pool),
-        )?;
+        self.store
+            .hot_db
+            .do_atomically(vec![self.persist_op_pool_in_batch()])?;
 
         Ok(())
     }
 
+    /// Return a database operation for writing `self.op_pool` to disk.
+    pub fn persist_op_pool_in_batch(&self) -> KeyValueStoreOp {
+        PersistedOperationPool::from_operation_pool(&self.op_pool).as_kv_store_op(OP_POOL_DB_KEY)
+    }
+
     /// Persists `self.eth1_chain` and its caches to disk.
     pub fn persist_eth1_cache(&self) -> Result<(), Error> {
         let _timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
@@ -881,7 +916,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .get_state(&state_root, Some(slot))?
                 .ok_or_else(|| Error::MissingBeaconState(state_root))?;
 
-            state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+            self.build_committee_cache_with_shuffling_cache(
+                &mut state,
+                beacon_block_root,
+                RelativeEpoch::Current,
+            )?;
 
             self.produce_unaggregated_attestation_for_block(
                 slot,
@@ -920,7 +959,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 // them?
                 per_slot_processing(mut_state, Some(Hash256::zero()), &self.spec)?;
             }
-            mut_state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+            self.build_committee_cache_with_shuffling_cache(
+                mut_state,
+                beacon_block_root,
+                RelativeEpoch::Current,
+            )?;
         }
 
         let committee_len = state.get_beacon_committee(slot, index)?.committee.len();
@@ -984,6 +1027,56 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
+    /// Accepts a batch of `Attestation`s from the network and attempts to verify them, returning
+    /// a result for each attestation in the same order as `attestations`.
+    ///
+    /// Each attestation is verified as per `verify_unaggregated_attestation_for_gossip`, except
+    /// that their signatures are verified together in a single batch where possible. An `Err` is
+    /// only returned if the batch as a whole could not be verified.
+    pub fn batch_verify_unaggregated_attestations_for_gossip(
+        &self,
+        attestations: Vec<(Attestation<T::EthSpec>, Option<SubnetId>)>,
+    ) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, AttestationError>>, AttestationError>
+    {
+        metrics::inc_counter_by(
+            &metrics::UNAGGREGATED_ATTESTATION_PROCESSING_REQUESTS,
+            attestations.len() as i64,
+        );
+
+        let results = batch_verify_unaggregated_attestations(attestations, self)?;
+
+        metrics::inc_counter_by(
+            &metrics::UNAGGREGATED_ATTESTATION_PROCESSING_SUCCESSES,
+            results.iter().filter(|result| result.is_ok()).count() as i64,
+        );
+
+        Ok(results)
+    }
+
+    /// Accepts a batch of `SignedAggregateAndProof`s from the network and attempts to verify
+    /// them, returning a result for each aggregate in the same order as `signed_aggregates`.
+    ///
+    /// As for `batch_verify_unaggregated_attestations_for_gossip`, but for aggregates.
+    pub fn batch_verify_aggregated_attestations_for_gossip(
+        &self,
+        signed_aggregates: Vec<SignedAggregateAndProof<T::EthSpec>>,
+    ) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, AttestationError>>, AttestationError>
+    {
+        metrics::inc_counter_by(
+            &metrics::AGGREGATED_ATTESTATION_PROCESSING_REQUESTS,
+            signed_aggregates.len() as i64,
+        );
+
+        let results = batch_verify_aggregated_attestations(signed_aggregates, self)?;
+
+        metrics::inc_counter_by(
+            &metrics::AGGREGATED_ATTESTATION_PROCESSING_SUCCESSES,
+            results.iter().filter(|result| result.is_ok()).count() as i64,
+        );
+
+        Ok(results)
+    }
+
     /// Accepts some attestation-type object and attempts to verify it in the context of fork
     /// choice. If it is valid it is applied to `self.fork_choice`.
     ///
@@ -1086,6 +1179,154 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(signed_aggregate)
     }
 
+    /// Return a summary of the slasher's progress, or `None` if there is no slasher.
+    pub fn slasher_status(&self) -> Option<SlasherStatus> {
+        self.slasher.as_ref().map(|slasher| slasher.status())
+    }
+
+    /// Pause or resume the slasher's updates, returning `false` if the slasher is disabled.
+    pub fn set_slasher_paused(&self, paused: bool) -> bool {
+        match self.slasher.as_ref() {
+            Some(slasher) if paused => slasher.pause(),
+            Some(slasher) => slasher.resume(),
+            None => return false,
+        }
+        true
+    }
+
+    /// Verify the slashings collected by the slasher and add them to the op pool, returning those
+    /// which have not been seen before so that they can be published on gossip.
+    pub fn import_slasher_slashings(
+        &self,
+    ) -> (Vec<AttesterSlashing<T::EthSpec>>, Vec<ProposerSlashing>) {
+        let slasher = match self.slasher.as_ref() {
+            Some(slasher) => slasher,
+            None => return (vec![], vec![]),
+        };
+
+        let mut attester_slashings = vec![];
+        for slashing in slasher.get_attester_slashings() {
+            match self.verify_attester_slashing_for_gossip(slashing.clone()) {
+                Ok(ObservationOutcome::New(verified_slashing)) => {
+                    if let Err(e) = self.import_attester_slashing(verified_slashing) {
+                        error!(
+                            self.log,
+                            "Attester slashing from slasher is invalid";
+                            "error" => format!("{:?}", e),
+                            "slashing" => format!("{:?}", slashing),
+                        );
+                        continue;
+                    }
+                    attester_slashings.push(slashing);
+                }
+                Ok(ObservationOutcome::AlreadyKnown) => (),
+                Err(e) => error!(
+                    self.log,
+                    "Attester slashing from slasher failed verification";
+                    "error" => format!("{:?}", e),
+                    "slashing" => format!("{:?}", slashing),
+                ),
+            }
+        }
+
+        let mut proposer_slashings = vec![];
+        for slashing in slasher.get_proposer_slashings() {
+            match self.verify_proposer_slashing_for_gossip(slashing.clone()) {
+                Ok(ObservationOutcome::New(verified_slashing)) => {
+                    self.import_proposer_slashing(verified_slashing);
+                    proposer_slashings.push(slashing);
+                }
+                Ok(ObservationOutcome::AlreadyKnown) => (),
+                Err(e) => error!(
+                    self.log,
+                    "Proposer slashing from slasher failed verification";
+                    "error" => format!("{:?}", e),
+                    "slashing" => format!("{:?}", slashing),
+                ),
+            }
+        }
+
+        (attester_slashings, proposer_slashings)
+    }
+
+    /// Supply the slasher with the blocks and attestations of the canonical chain, from
+    /// `start_epoch` up to the head, so that it can detect offences from before it was enabled.
+    ///
+    /// The committees of each attestation are computed by replaying the blocks on top of the
+    /// state prior to `start_epoch`, without verifying signatures. The slasher processes each
+    /// epoch as it is reached, so that the backlog doesn't grow without bound. Epochs which are
+    /// older than the slasher's history are skipped.
+    ///
+    /// Returns the number of blocks supplied to the slasher.
+    pub fn backfill_slasher(&self, start_epoch: Epoch) -> Result<usize, Error> {
+        let slasher = self.slasher.as_ref().ok_or(Error::SlasherNotConfigured)?;
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+        let oldest_epoch = (self.epoch()? + 1).saturating_sub(slasher.history_length() as u64);
+        let start_slot = max(start_epoch, oldest_epoch).start_slot(slots_per_epoch);
+        let block_roots = process_results(self.forwards_iter_block_roots(start_slot)?, |iter| {
+            iter.map(|(block_root, _)| block_root).collect::<Vec<_>>()
+        })?;
+
+        let mut state = self.state_at_slot(
+            start_slot.saturating_sub(1_u64),
+            StateSkipConfig::WithStateRoots,
+        )?;
+        let mut state_root = None;
+        let mut previous_block_root = None;
+        let mut num_blocks = 0;
+
+        for block_root in block_roots {
+            // Skipped slots repeat the root of the previous block.
+            if previous_block_root == Some(block_root) {
+                continue;
+            }
+            previous_block_root = Some(block_root);
+
+            let block = self
+                .get_block(&block_root)?
+                .ok_or(Error::MissingBeaconBlock(block_root))?;
+            if block.slot() <= state.slot {
+                continue;
+            }
+
+            // Process the previous epoch once the state is about to leave it.
+            if block.slot().epoch(slots_per_epoch) > state.current_epoch() {
+                slasher.process_queued(self.epoch()?)?;
+            }
+
+            while state.slot < block.slot() {
+                per_slot_processing(&mut state, state_root.take(), &self.spec)?;
+            }
+            state.build_committee_cache(RelativeEpoch::Previous, &self.spec)?;
+            state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+
+            for attestation in &block.message.body.attestations {
+                let committee =
+                    state.get_beacon_committee(attestation.data.slot, attestation.data.index)?;
+                slasher.accept_attestation(get_indexed_attestation(
+                    &committee.committee,
+                    attestation,
+                )?);
+            }
+            slasher.accept_block_header(block.signed_block_header());
+
+            per_block_processing(
+                &mut state,
+                &block,
+                Some(block_root),
+                BlockSignatureStrategy::NoVerification,
+                &self.spec,
+            )?;
+            state_root = Some(block.state_root());
+            num_blocks += 1;
+        }
+
+        slasher.process_queued(self.epoch()?)?;
+
+        Ok(num_blocks)
+    }
+
     /// Move slashings collected by the slasher into the op pool for block inclusion.
     fn ingest_slashings_to_op_pool(&self, state: &BeaconState<T::EthSpec>) {
         if let Some(slasher) = self.slasher.as_ref() {
@@ -1251,9 +1492,28 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
     /// Accept some proposer slashing and queue it for inclusion in an appropriate block.
     pub fn import_proposer_slashing(&self, proposer_slashing: SigVerifiedOp<ProposerSlashing>) {
+        let slashing = proposer_slashing.as_inner().clone();
+        self.check_monitored_validators_slashed(
+            Some(
+                proposer_slashing
+                    .as_inner()
+                    .signed_header_1
+                    .message
+                    .proposer_index,
+            ),
+            "proposer_slashing",
+            "op_pool",
+        );
+
         if self.eth1_chain.is_some() {
             self.op_pool.insert_proposer_slashing(proposer_slashing)
         }
+
+        let _ = self
+            .event_handler
+            .register(EventKind::ProposerSlashingImported {
+                slashing: Box::new(slashing),
+            });
     }
 
     /// Verify an attester slashing before allowing it to propagate on the gossip network.
@@ -1274,13 +1534,85 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         &self,
         attester_slashing: SigVerifiedOp<AttesterSlashing<T::EthSpec>>,
     ) -> Result<(), Error> {
+        let slashing = attester_slashing.as_inner().clone();
+        self.check_monitored_validators_slashed(
+            attester_slashing.as_inner().intersecting_indices(),
+            "attester_slashing",
+            "op_pool",
+        );
+
         if self.eth1_chain.is_some() {
             self.op_pool
                 .insert_attester_slashing(attester_slashing, self.head_info()?.fork)
         }
+
+        let _ = self
+            .event_handler
+            .register(EventKind::AttesterSlashingImported {
+                slashing: Box::new(slashing),
+            });
         Ok(())
     }
 
+    /// Raise a critical alarm for each of `slashed_indices` which is one of the operator's
+    /// `monitored_validators`, so that they are warned as soon as a slashing is seen.
+    fn check_monitored_validators_slashed(
+        &self,
+        slashed_indices: impl IntoIterator<Item = u64>,
+        kind: &'static str,
+        source: &'static str,
+    ) {
+        if self.config.monitored_validators.is_empty() {
+            return;
+        }
+
+        let monitored = match self
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+        {
+            Some(pubkey_cache) => self
+                .config
+                .monitored_validators
+                .iter()
+                .filter_map(|pubkey| {
+                    pubkey_cache
+                        .get_index(pubkey)
+                        .map(|index| (index as u64, pubkey))
+                })
+                .collect::<HashMap<_, _>>(),
+            None => {
+                error!(
+                    self.log,
+                    "Unable to check slashing for monitored validators";
+                    "error" => format!("{:?}", Error::ValidatorPubkeyCacheLockTimeout),
+                );
+                return;
+            }
+        };
+
+        for validator_index in slashed_indices {
+            if let Some(pubkey) = monitored.get(&validator_index) {
+                crit!(
+                    self.log,
+                    "Monitored validator slashed";
+                    "validator_index" => validator_index,
+                    "pubkey" => format!("{:?}", pubkey),
+                    "kind" => kind,
+                    "source" => source,
+                );
+                metrics::inc_counter(&metrics::MONITORED_VALIDATOR_SLASHINGS);
+                let _ = self
+                    .event_handler
+                    .register(EventKind::MonitoredValidatorSlashed {
+                        validator_index,
+                        pubkey: (*pubkey).clone(),
+                        kind: kind.to_string(),
+                        source: source.to_string(),
+                    });
+            }
+        }
+    }
+
     /// Attempt to verify and import a chain of blocks to `self`.
     ///
     /// The provided blocks _must_ each reference the previous block via `block.parent_root` (i.e.,
@@ -1487,6 +1819,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // Increment the Prometheus counter for block processing requests.
         metrics::inc_counter(&metrics::BLOCK_PROCESSING_REQUESTS);
 
+        // Blocks can't be imported into a read-only database.
+        if self.store.is_read_only() {
+            return Err(BlockError::BeaconChainError(Error::DBError(
+                DBError::ReadOnly,
+            )));
+        }
+
         // Clone the block so we can provide it to the event handler.
         let block = unverified_block.block().clone();
 
@@ -1608,7 +1947,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.validator_pubkey_cache
             .try_write_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
             .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?
-            .import_new_pubkeys(&state)?;
+            .import_new_pubkeys(&state, &self.store, &self.log)?;
 
         // For the current and next epoch of this state, ensure we have the shuffling from this
         // block in our cache.
@@ -1675,7 +2014,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             let _fork_choice_block_timer =
                 metrics::start_timer(&metrics::FORK_CHOICE_PROCESS_BLOCK_TIMES);
             fork_choice
-                .on_block(current_slot, block, block_root, &state)
+                .on_block(current_slot, block, block_root, &state, &self.spec)
                 .map_err(|e| BlockError::BeaconChainError(e.into()))?;
         }
 
@@ -1713,13 +2052,56 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         ));
         ops.push(StoreOp::PutState(block.state_root, &state));
         let txn_lock = self.store.hot_db.begin_rw_transaction();
-        self.store.do_atomically(ops)?;
+        if let Err(e) = self.store.do_atomically(ops) {
+            error!(
+                self.log,
+                "Database write failed!";
+                "msg" => "Restoring fork choice from disk",
+                "error" => ?e,
+            );
+
+            // The block has been applied to fork choice but not written to disk, so fork choice
+            // is reverted to its persisted copy, which never refers to unwritten blocks.
+            match load_fork_choice(self.store.clone()) {
+                Ok(Some(persisted_fork_choice)) => *fork_choice = persisted_fork_choice,
+                Ok(None) => crit!(
+                    self.log,
+                    "No stored fork choice found to restore from";
+                    "warning" => "The database is likely corrupt now, consider --purge-db"
+                ),
+                Err(e) => crit!(
+                    self.log,
+                    "Unable to restore fork choice from disk";
+                    "error" => e,
+                    "warning" => "The database is likely corrupt now, consider --purge-db"
+                ),
+            }
+
+            return Err(e.into());
+        }
         drop(txn_lock);
 
         // The fork choice write-lock is dropped *after* the on-disk database has been updated.
         // This prevents inconsistency between the two at the expense of concurrency.
         drop(fork_choice);
 
+        // Only alarm for the slashings of the block once it has been imported, so that a block
+        // which fails to import raises no alarm, and a block which is retried raises just one.
+        for attester_slashing in &block.body.attester_slashings {
+            self.check_monitored_validators_slashed(
+                attester_slashing.intersecting_indices(),
+                "attester_slashing",
+                "block",
+            );
+        }
+        for proposer_slashing in &block.body.proposer_slashings {
+            self.check_monitored_validators_slashed(
+                Some(proposer_slashing.signed_header_1.message.proposer_index),
+                "proposer_slashing",
+                "block",
+            );
+        }
+
         let parent_root = block.parent_root;
         let slot = block.slot;
 
@@ -1800,8 +2182,6 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             per_slot_processing(&mut state, None, &self.spec)?;
         }
 
-        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
-
         let parent_root = if state.slot > 0 {
             *state
                 .get_block_root(state.slot - 1)
@@ -1810,6 +2190,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             state.latest_block_header.canonical_root()
         };
 
+        self.build_committee_cache_with_shuffling_cache(
+            &mut state,
+            parent_root,
+            RelativeEpoch::Current,
+        )
+        .map_err(BlockProductionError::BeaconChainError)?;
+
         self.ingest_slashings_to_op_pool(&state);
         let (proposer_slashings, attester_slashings) =
             self.op_pool.get_slashings(&state, &self.spec);
@@ -2025,10 +2412,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .epoch(T::EthSpec::slots_per_epoch())
             || is_reorg
         {
-            self.persist_head_and_fork_choice()?;
             self.op_pool.prune_attestations(self.epoch()?);
             self.ingest_slashings_to_op_pool(&new_head.beacon_state);
-            self.persist_op_pool()?;
+            self.persist_head_fork_choice_and_op_pool()?;
         }
 
         let update_head_timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
@@ -2165,6 +2551,74 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Delete states from the hot database which can no longer be reached from fork choice.
+    ///
+    /// These are states prior to the split slot which the migration didn't delete, and states of
+    /// blocks that have been pruned from fork choice, which would otherwise accumulate after an
+    /// unclean shutdown. States between the split and the finalized checkpoint are left for the
+    /// migration, and temporary states are left for `HotColdDB::remove_garbage`.
+    pub fn garbage_collect_orphaned_states(&self) -> Result<StateGarbageCollection, Error> {
+        let _timer = metrics::start_timer(&metrics::STATE_GC_TIMES);
+
+        let split_slot = self.store.get_split_slot();
+        let finalized_slot = self
+            .fork_choice
+            .read()
+            .finalized_checkpoint()
+            .epoch
+            .start_slot(T::EthSpec::slots_per_epoch());
+
+        let mut candidates = vec![];
+        for result in self.store.iter_hot_state_summaries() {
+            let (state_root, summary) = result?;
+            if state_root == self.genesis_state_root
+                || self.store.load_state_temporary_flag(&state_root)?.is_some()
+            {
+                continue;
+            }
+            if summary.slot() < split_slot || summary.slot() > finalized_slot {
+                candidates.push((state_root, summary));
+            }
+        }
+
+        // Block imports update fork choice and the database under the fork choice write lock, so
+        // holding the read lock prevents the deletion of states whose blocks are being imported.
+        let fork_choice = self.fork_choice.read();
+        let orphaned = candidates
+            .into_iter()
+            .filter(|(_, summary)| {
+                summary.slot() < split_slot
+                    || !fork_choice.contains_block(&summary.latest_block_root())
+            })
+            .map(|(state_root, summary)| (state_root, summary.slot()))
+            .collect::<Vec<_>>();
+        let bytes_reclaimed = self.store.delete_hot_states(&orphaned)?;
+        drop(fork_choice);
+
+        let gc = StateGarbageCollection {
+            states_deleted: orphaned.len(),
+            bytes_reclaimed,
+        };
+        metrics::inc_counter_by(&metrics::STATE_GC_STATES_DELETED, gc.states_deleted as i64);
+        metrics::inc_counter_by(
+            &metrics::STATE_GC_BYTES_RECLAIMED,
+            gc.bytes_reclaimed as i64,
+        );
+
+        if gc.states_deleted > 0 {
+            info!(
+                self.log,
+                "Garbage collected orphaned states";
+                "states_deleted" => gc.states_deleted,
+                "bytes_reclaimed" => gc.bytes_reclaimed,
+            );
+        } else {
+            debug!(self.log, "No orphaned states to garbage collect");
+        }
+
+        Ok(gc)
+    }
+
     /// Called after `self` has had a new block finalized.
     ///
     /// Performs pruning and finality-based optimizations.
@@ -2335,6 +2789,136 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Returns the committee cache of `state` for `relative_epoch`.
+    ///
+    /// If `state` doesn't have the cache built, the shuffling is read from the
+    /// `beacon_chain.shuffling_cache` instead, or computed and added to the `shuffling_cache` if
+    /// it isn't there. This means that attestation verification, the HTTP API and block
+    /// production all share the same shuffling for some `(epoch, decision_root)`, instead of
+    /// each computing it again.
+    ///
+    /// The `block_root` should be the root of the latest block applied to `state` (see
+    /// `ShufflingId::new`).
+    pub fn committee_cache_for_state<'a>(
+        &self,
+        state: &'a BeaconState<T::EthSpec>,
+        block_root: Hash256,
+        relative_epoch: RelativeEpoch,
+    ) -> Result<Cow<'a, CommitteeCache>, Error> {
+        if state.committee_cache_is_initialized(relative_epoch) {
+            return Ok(Cow::Borrowed(state.committee_cache(relative_epoch)?));
+        }
+
+        let shuffling_id = ShufflingId::new(block_root, state, relative_epoch)?;
+
+        let cached = self
+            .shuffling_cache
+            .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .get(&shuffling_id)
+            .cloned();
+
+        if let Some(committee_cache) = cached {
+            return Ok(Cow::Owned(committee_cache));
+        }
+
+        let committee_cache = CommitteeCache::initialized(
+            state,
+            relative_epoch.into_epoch(state.current_epoch()),
+            &self.spec,
+        )?;
+
+        self.shuffling_cache
+            .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .insert(shuffling_id, &committee_cache);
+
+        Ok(Cow::Owned(committee_cache))
+    }
+
+    /// Builds the committee cache of `state` for `relative_epoch`, using the shuffling cache as
+    /// per `Self::committee_cache_for_state`.
+    pub fn build_committee_cache_with_shuffling_cache(
+        &self,
+        state: &mut BeaconState<T::EthSpec>,
+        block_root: Hash256,
+        relative_epoch: RelativeEpoch,
+    ) -> Result<(), Error> {
+        if !state.committee_cache_is_initialized(relative_epoch) {
+            let committee_cache = self
+                .committee_cache_for_state(state, block_root, relative_epoch)?
+                .into_owned();
+            state.set_committee_cache(relative_epoch, committee_cache)?;
+        }
+
+        Ok(())
+    }
+
+    /// Precomputes the shufflings which are needed soon after the start of `current_epoch`, so
+    /// that duty queries and attestation verification don't compute them on the critical path:
+    ///
+    /// - The committees of `current_epoch` and the next epoch, which are added to the
+    ///   `shuffling_cache`.
+    /// - The block proposers of `current_epoch`, which are added to the `proposer_cache`.
+    ///
+    /// The proposers of the next epoch depend upon the effective balance updates at the end of
+    /// `current_epoch`, so they can't be known in advance.
+    ///
+    /// Does nothing if the head is more than one epoch behind `current_epoch`, e.g. whilst
+    /// syncing.
+    pub fn precompute_epoch_shufflings(&self, current_epoch: Epoch) -> Result<(), Error> {
+        let _timer = metrics::start_timer(&metrics::SHUFFLING_PRECOMPUTE_TIMES);
+
+        let (head_block_root, mut state) = self.with_head(|head| {
+            Ok::<_, Error>((
+                head.beacon_block_root,
+                head.beacon_state.clone_with_only_committee_caches(),
+            ))
+        })?;
+
+        let head_epoch = state.current_epoch();
+        if head_epoch > current_epoch || head_epoch + 1 < current_epoch {
+            return Ok(());
+        }
+
+        while state.current_epoch() < current_epoch {
+            // The state roots are not useful for the shuffling, so there's no need to compute
+            // them.
+            per_slot_processing(&mut state, Some(Hash256::zero()), &self.spec)?;
+        }
+
+        for &relative_epoch in &[RelativeEpoch::Current, RelativeEpoch::Next] {
+            let shuffling_id = ShufflingId::new(head_block_root, &state, relative_epoch)?;
+            self.build_committee_cache_with_shuffling_cache(
+                &mut state,
+                head_block_root,
+                relative_epoch,
+            )?;
+            self.shuffling_cache
+                .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+                .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+                .insert(shuffling_id, state.committee_cache(relative_epoch)?);
+        }
+
+        let decision_root =
+            proposer_shuffling_decision_root(current_epoch, head_block_root, &state)?;
+        if !self
+            .proposer_cache
+            .lock()
+            .contains(current_epoch, decision_root)
+        {
+            let proposers = current_epoch
+                .slot_iter(T::EthSpec::slots_per_epoch())
+                .map(|slot| state.get_beacon_proposer_index(slot, &self.spec))
+                .collect::<Result<_, _>>()?;
+            self.proposer_cache
+                .lock()
+                .insert(current_epoch, decision_root, proposers);
+        }
+
+        Ok(())
+    }
+
     /// Returns `true` if the given block root has not been processed.
     pub fn is_new_block_root(&self, beacon_block_root: &Hash256) -> Result<bool, Error> {
         Ok(!self
@@ -2521,9 +3105,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
 impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
     fn drop(&mut self) {
+        // Nothing has changed that could be persisted to a read-only database.
+        if self.store.is_read_only() {
+            return;
+        }
+
         let drop = || -> Result<(), Error> {
-            self.persist_head_and_fork_choice()?;
-            self.persist_op_pool()?;
+            self.persist_head_fork_choice_and_op_pool()?;
             self.persist_eth1_cache()
         };
 
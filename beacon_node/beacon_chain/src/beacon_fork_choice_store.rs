@@ -166,6 +166,9 @@ pub struct BeaconForkChoiceStore<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<
     justified_checkpoint: Checkpoint,
     justified_balances: Vec<u64>,
     best_justified_checkpoint: Checkpoint,
+    /// The unrealized checkpoints are not persisted, they're recomputed as blocks are imported.
+    unrealized_justified_checkpoint: Checkpoint,
+    unrealized_finalized_checkpoint: Checkpoint,
     _phantom: PhantomData<E>,
 }
 
@@ -183,6 +186,8 @@ where
             && self.justified_checkpoint == other.justified_checkpoint
             && self.justified_balances == other.justified_balances
             && self.best_justified_checkpoint == other.best_justified_checkpoint
+            && self.unrealized_justified_checkpoint == other.unrealized_justified_checkpoint
+            && self.unrealized_finalized_checkpoint == other.unrealized_finalized_checkpoint
     }
 }
 
@@ -228,6 +233,8 @@ where
             justified_balances: anchor_state.balances.clone().into(),
             finalized_checkpoint,
             best_justified_checkpoint: justified_checkpoint,
+            unrealized_justified_checkpoint: justified_checkpoint,
+            unrealized_finalized_checkpoint: finalized_checkpoint,
             _phantom: PhantomData,
         }
     }
@@ -258,6 +265,8 @@ where
             justified_checkpoint: persisted.justified_checkpoint,
             justified_balances: persisted.justified_balances,
             best_justified_checkpoint: persisted.best_justified_checkpoint,
+            unrealized_justified_checkpoint: persisted.justified_checkpoint,
+            unrealized_finalized_checkpoint: persisted.finalized_checkpoint,
             _phantom: PhantomData,
         })
     }
@@ -304,6 +313,14 @@ where
         &self.finalized_checkpoint
     }
 
+    fn unrealized_justified_checkpoint(&self) -> &Checkpoint {
+        &self.unrealized_justified_checkpoint
+    }
+
+    fn unrealized_finalized_checkpoint(&self) -> &Checkpoint {
+        &self.unrealized_finalized_checkpoint
+    }
+
     fn set_finalized_checkpoint(&mut self, checkpoint: Checkpoint) {
         self.finalized_checkpoint = checkpoint
     }
@@ -338,6 +355,14 @@ where
     fn set_best_justified_checkpoint(&mut self, checkpoint: Checkpoint) {
         self.best_justified_checkpoint = checkpoint
     }
+
+    fn set_unrealized_justified_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.unrealized_justified_checkpoint = checkpoint
+    }
+
+    fn set_unrealized_finalized_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.unrealized_finalized_checkpoint = checkpoint
+    }
 }
 
 /// A container which allows persisting the `BeaconForkChoiceStore` to the on-disk database.
@@ -839,6 +839,7 @@ impl<'a, T: BeaconChainTypes> FullyVerifiedBlock<'a, T> {
 
         state.build_committee_cache(RelativeEpoch::Previous, &chain.spec)?;
         state.build_committee_cache(RelativeEpoch::Current, &chain.spec)?;
+        state.build_progressive_balances_cache(&chain.spec)?;
 
         metrics::stop_timer(committee_timer);
 
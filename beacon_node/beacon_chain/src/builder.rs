@@ -1,29 +1,29 @@
-use crate::beacon_chain::{
-    BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
-};
+use crate::beacon_chain::{BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, OP_POOL_DB_KEY};
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::events::NullEventHandler;
+use crate::fork_revert::{find_missing_block, load_fork_choice, reset_fork_choice_to_finalization};
 use crate::head_tracker::HeadTracker;
 use crate::migrate::{BackgroundMigrator, MigratorConfig};
 use crate::persisted_beacon_chain::PersistedBeaconChain;
-use crate::persisted_fork_choice::PersistedForkChoice;
+use crate::proposer_cache::ProposerCache;
 use crate::shuffling_cache::ShufflingCache;
-use crate::snapshot_cache::{SnapshotCache, DEFAULT_SNAPSHOT_CACHE_SIZE};
+use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::ChainConfig;
 use crate::{
-    BeaconChain, BeaconChainTypes, BeaconForkChoiceStore, BeaconSnapshot, Eth1Chain,
-    Eth1ChainBackend, EventHandler,
+    BeaconChain, BeaconChainError, BeaconChainTypes, BeaconForkChoiceStore, BeaconSnapshot,
+    Eth1Chain, Eth1ChainBackend, EventHandler,
 };
 use eth1::Config as Eth1Config;
 use fork_choice::ForkChoice;
 use futures::channel::mpsc::Sender;
 use operation_pool::{OperationPool, PersistedOperationPool};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use slasher::Slasher;
-use slog::{crit, info, Logger};
+use slog::{crit, info, warn, Logger};
 use slot_clock::{SlotClock, TestingSlotClock};
+use std::fs;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -34,6 +34,7 @@ use types::{
     Slot,
 };
 
+/// The file which held the validator pubkey cache before it was stored in the database.
 pub const PUBKEY_CACHE_FILENAME: &str = "pubkey_cache.ssz";
 
 /// An empty struct used to "witness" all the `BeaconChainTypes` traits. It has no user-facing
@@ -93,7 +94,6 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     shutdown_sender: Option<Sender<&'static str>>,
     head_tracker: Option<HeadTracker>,
     data_dir: Option<PathBuf>,
-    pubkey_cache_path: Option<PathBuf>,
     validator_pubkey_cache: Option<ValidatorPubkeyCache>,
     spec: ChainSpec,
     chain_config: ChainConfig,
@@ -133,7 +133,6 @@ where
             slot_clock: None,
             shutdown_sender: None,
             head_tracker: None,
-            pubkey_cache_path: None,
             data_dir: None,
             disabled_forks: Vec::new(),
             validator_pubkey_cache: None,
@@ -191,11 +190,10 @@ where
         self
     }
 
-    /// Sets the location to the pubkey cache file.
+    /// Sets the data directory, from which any legacy pubkey cache file is removed.
     ///
     /// Should generally be called early in the build chain.
     pub fn data_dir(mut self, path: PathBuf) -> Self {
-        self.pubkey_cache_path = Some(path.join(PUBKEY_CACHE_FILENAME));
         self.data_dir = Some(path);
         self
     }
@@ -240,11 +238,6 @@ where
             .as_ref()
             .ok_or_else(|| "resume_from_db requires a log".to_string())?;
 
-        let pubkey_cache_path = self
-            .pubkey_cache_path
-            .as_ref()
-            .ok_or_else(|| "resume_from_db requires a data_dir".to_string())?;
-
         info!(
             log,
             "Starting beacon chain";
@@ -264,20 +257,23 @@ where
                     .to_string()
             })?;
 
-        let persisted_fork_choice = store
-            .get_item::<PersistedForkChoice>(&FORK_CHOICE_DB_KEY)
-            .map_err(|e| format!("DB error when reading persisted fork choice: {:?}", e))?
+        let mut fork_choice = load_fork_choice(store.clone())?
             .ok_or_else(|| "No persisted fork choice present in database.".to_string())?;
 
-        let fc_store = BeaconForkChoiceStore::from_persisted(
-            persisted_fork_choice.fork_choice_store,
-            store.clone(),
-        )
-        .map_err(|e| format!("Unable to load ForkChoiceStore: {:?}", e))?;
-
-        let fork_choice =
-            ForkChoice::from_persisted(persisted_fork_choice.fork_choice, fc_store)
-                .map_err(|e| format!("Unable to parse persisted fork choice from disk: {:?}", e))?;
+        // A crash may have persisted fork choice without some of the blocks or states which it
+        // refers to, in which case the chain is restarted from the last finalized checkpoint.
+        if let Some(missing_block_root) = find_missing_block(&fork_choice, &store)? {
+            let finalized_checkpoint = fork_choice.finalized_checkpoint();
+            warn!(
+                log,
+                "Resetting fork choice to the finalized checkpoint";
+                "missing_block_root" => ?missing_block_root,
+                "finalized_epoch" => finalized_checkpoint.epoch,
+                "finalized_root" => ?finalized_checkpoint.root,
+            );
+            fork_choice =
+                reset_fork_choice_to_finalization(store.clone(), finalized_checkpoint, &self.spec)?;
+        }
 
         let genesis_block = store
             .get_item::<SignedBeaconBlock<TEthSpec>>(&chain.genesis_block_root)
@@ -298,16 +294,45 @@ where
                 .unwrap_or_else(OperationPool::new),
         );
 
-        let pubkey_cache = ValidatorPubkeyCache::load_from_file(pubkey_cache_path)
-            .map_err(|e| format!("Unable to open persisted pubkey cache: {:?}", e))?;
+        // The pubkey cache is now stored in the database, and is rebuilt from the head state if
+        // it's missing. A read-only database leaves the data directory untouched too.
+        if let Some(legacy_path) = self
+            .data_dir
+            .as_ref()
+            .filter(|_| !store.is_read_only())
+            .map(|data_dir| data_dir.join(PUBKEY_CACHE_FILENAME))
+            .filter(|path| path.exists())
+        {
+            fs::remove_file(&legacy_path)
+                .map_err(|e| format!("Unable to remove {:?}: {:?}", legacy_path, e))?;
+            info!(log, "Removed legacy pubkey cache file"; "path" => ?legacy_path);
+        }
+
+        let pubkey_cache = match ValidatorPubkeyCache::load_from_store(&store, &log) {
+            Ok(pubkey_cache) => Some(pubkey_cache),
+            Err(e) => {
+                warn!(
+                    log,
+                    "Rebuilding corrupt pubkey cache";
+                    "error" => ?e
+                );
+                None
+            }
+        };
 
         self.genesis_block_root = Some(chain.genesis_block_root);
         self.genesis_state_root = Some(genesis_block.state_root());
-        self.head_tracker = Some(
-            HeadTracker::from_ssz_container(&chain.ssz_head_tracker)
-                .map_err(|e| format!("Failed to decode head tracker for database: {:?}", e))?,
-        );
-        self.validator_pubkey_cache = Some(pubkey_cache);
+        let head_tracker = HeadTracker::from_ssz_container(&chain.ssz_head_tracker)
+            .map_err(|e| format!("Failed to decode head tracker for database: {:?}", e))?;
+        // Heads whose blocks were lost in a crash can't be walked back when pruning, so they're
+        // forgotten. The lost blocks are imported again by sync.
+        head_tracker.0.write().retain(|block_root, _| {
+            store
+                .item_exists::<SignedBeaconBlock<TEthSpec>>(block_root)
+                .unwrap_or(true)
+        });
+        self.head_tracker = Some(head_tracker);
+        self.validator_pubkey_cache = pubkey_cache;
         self.fork_choice = Some(fork_choice);
 
         Ok(self)
@@ -375,6 +400,171 @@ where
         Ok(self.empty_op_pool())
     }
 
+    /// Starts a new chain from a trusted checkpoint (weak subjectivity) state and block, rather
+    /// than from genesis.
+    ///
+    /// The state must be the post-state of `weak_subj_block` and lie on an epoch boundary. The
+    /// genesis state is still required in order to determine the genesis block root, but none of
+    /// the history between genesis and the checkpoint is stored.
+    pub fn weak_subjectivity_state(
+        mut self,
+        mut weak_subj_state: BeaconState<TEthSpec>,
+        weak_subj_block: SignedBeaconBlock<TEthSpec>,
+        mut genesis_state: BeaconState<TEthSpec>,
+    ) -> Result<Self, String> {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "weak_subjectivity_state requires a store")?;
+        let log = self
+            .log
+            .as_ref()
+            .ok_or_else(|| "weak_subjectivity_state requires a log")?;
+
+        let weak_subj_slot = weak_subj_state.slot;
+        if weak_subj_slot % TEthSpec::slots_per_epoch() != 0 {
+            return Err(format!(
+                "Checkpoint state at slot {} is not aligned to an epoch boundary",
+                weak_subj_slot
+            ));
+        }
+        if weak_subj_block.slot() != weak_subj_slot {
+            return Err(format!(
+                "Checkpoint block slot {} does not match checkpoint state slot {}",
+                weak_subj_block.slot(),
+                weak_subj_slot
+            ));
+        }
+
+        weak_subj_state
+            .build_all_caches(&self.spec)
+            .map_err(|e| format!("Failed to build checkpoint state caches: {:?}", e))?;
+        let weak_subj_state_root = weak_subj_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Error hashing checkpoint state: {:?}", e))?;
+        if weak_subj_block.state_root() != weak_subj_state_root {
+            return Err(format!(
+                "Checkpoint block state root {:?} does not match checkpoint state root {:?}",
+                weak_subj_block.state_root(),
+                weak_subj_state_root
+            ));
+        }
+        let weak_subj_block_root = weak_subj_block.canonical_root();
+
+        if genesis_state.genesis_validators_root != weak_subj_state.genesis_validators_root {
+            return Err(format!(
+                "Checkpoint state has genesis validators root {:?}, expected {:?}",
+                weak_subj_state.genesis_validators_root, genesis_state.genesis_validators_root
+            ));
+        }
+
+        // There are no forks after genesis yet, so a state with any other fork version belongs to
+        // a different network.
+        if weak_subj_state.fork.current_version != self.spec.genesis_fork_version {
+            return Err(format!(
+                "Checkpoint state has fork version {:?}, expected {:?}",
+                weak_subj_state.fork.current_version, self.spec.genesis_fork_version
+            ));
+        }
+
+        // Refuse to start from a checkpoint which conflicts with an earlier weak subjectivity
+        // checkpoint, since the blocks prior to the checkpoint won't be stored. The weak
+        // subjectivity checkpoint is verified against later checkpoints on every start.
+        if let Some(wss_checkpoint) = self.chain_config.weak_subjectivity_checkpoint {
+            let wss_slot = wss_checkpoint.epoch.start_slot(TEthSpec::slots_per_epoch());
+            if wss_slot < weak_subj_slot {
+                let root = *weak_subj_state.get_block_root(wss_slot).map_err(|_| {
+                    format!(
+                        "Weak subjectivity checkpoint at epoch {} is too old to be verified \
+                         against the checkpoint state at slot {}, use a more recent one",
+                        wss_checkpoint.epoch, weak_subj_slot
+                    )
+                })?;
+                if root != wss_checkpoint.root {
+                    crit!(
+                        log,
+                        "Checkpoint state conflicts with the weak subjectivity checkpoint";
+                        "weak_subjectivity_root" => format!("{:?}", wss_checkpoint.root),
+                        "checkpoint_root" => format!("{:?}", root),
+                        "weak_subjectivity_epoch" => wss_checkpoint.epoch,
+                    );
+                    return Err(format!(
+                        "Checkpoint state has block root {:?} at epoch {}, but the weak \
+                         subjectivity checkpoint is {:?}. You may be on a hostile network.",
+                        root, wss_checkpoint.epoch, wss_checkpoint.root
+                    ));
+                }
+            }
+        }
+
+        info!(
+            log,
+            "Starting beacon chain";
+            "method" => "checkpoint",
+            "slot" => weak_subj_slot,
+            "block_root" => format!("{:?}", weak_subj_block_root),
+        );
+
+        // Store the genesis state and block before moving the split point, so that they remain
+        // accessible from the hot database.
+        let genesis_block = genesis_block(&mut genesis_state, &self.spec)?;
+        let genesis_state_root = genesis_block.message.state_root;
+        let genesis_block_root = genesis_block.canonical_root();
+        store
+            .put_state(&genesis_state_root, &genesis_state)
+            .map_err(|e| format!("Failed to store genesis state: {:?}", e))?;
+        store
+            .put_item(&genesis_block_root, &genesis_block)
+            .map_err(|e| format!("Failed to store genesis block: {:?}", e))?;
+        store
+            .put_item(&Hash256::zero(), &genesis_block)
+            .map_err(|e| {
+                format!(
+                    "Failed to store genesis block under 0x00..00 alias: {:?}",
+                    e
+                )
+            })?;
+
+        store
+            .init_anchor(
+                weak_subj_slot,
+                weak_subj_state_root,
+                weak_subj_block.message.slot,
+            )
+            .map_err(|e| format!("Failed to initialize database anchor: {:?}", e))?;
+        store
+            .put_state(&weak_subj_state_root, &weak_subj_state)
+            .map_err(|e| format!("Failed to store checkpoint state: {:?}", e))?;
+        store
+            .put_item(&weak_subj_block_root, &weak_subj_block)
+            .map_err(|e| format!("Failed to store checkpoint block: {:?}", e))?;
+
+        self.genesis_state_root = Some(genesis_state_root);
+        self.genesis_block_root = Some(genesis_block_root);
+
+        let snapshot = BeaconSnapshot {
+            beacon_block_root: weak_subj_block_root,
+            beacon_block: weak_subj_block,
+            beacon_state_root: weak_subj_state_root,
+            beacon_state: weak_subj_state,
+        };
+
+        let fc_store = BeaconForkChoiceStore::get_forkchoice_store(store, &snapshot);
+
+        let fork_choice = ForkChoice::from_genesis(
+            fc_store,
+            snapshot.beacon_block_root,
+            &snapshot.beacon_block.message,
+            &snapshot.beacon_state,
+        )
+        .map_err(|e| format!("Unable to initialize ForkChoice: {:?}", e))?;
+
+        self.fork_choice = Some(fork_choice);
+        self.genesis_time = Some(genesis_state.genesis_time);
+
+        Ok(self.empty_op_pool())
+    }
+
     /// Sets the `BeaconChain` eth1 backend.
     pub fn eth1_backend(mut self, backend: Option<TEth1Backend>) -> Self {
         self.eth1_chain = backend.map(Eth1Chain::new);
@@ -509,6 +699,12 @@ where
                 && fc_finalized.root == genesis_block_root
             {
                 // This is a legal edge-case encountered during genesis.
+            } else if head_finalized.epoch <= fc_finalized.epoch
+                && fc_finalized.epoch.start_slot(TEthSpec::slots_per_epoch())
+                    == store.get_split_slot()
+            {
+                // This is a legal edge-case encountered when starting from a checkpoint state,
+                // until the chain finalizes beyond the checkpoint.
             } else {
                 return Err(format!(
                     "Database corrupt: fork choice is finalized at {:?} whilst head is finalized at \
@@ -518,14 +714,25 @@ where
             }
         }
 
-        let pubkey_cache_path = self
-            .pubkey_cache_path
-            .ok_or_else(|| "Cannot build without a pubkey cache path".to_string())?;
-
-        let validator_pubkey_cache = self.validator_pubkey_cache.map(Ok).unwrap_or_else(|| {
-            ValidatorPubkeyCache::new(&canonical_head.beacon_state, pubkey_cache_path)
-                .map_err(|e| format!("Unable to init validator pubkey cache: {:?}", e))
-        })?;
+        // Catch the pubkey cache up with the head state, or rebuild it if it has diverged.
+        let validator_pubkey_cache = match self.validator_pubkey_cache {
+            Some(mut cache) if cache.is_consistent_with(&canonical_head.beacon_state) => {
+                cache
+                    .import_new_pubkeys(&canonical_head.beacon_state, &store, &log)
+                    .map_err(|e| format!("Unable to update validator pubkey cache: {:?}", e))?;
+                cache
+            }
+            other => {
+                if other.is_some() {
+                    warn!(
+                        log,
+                        "Rebuilding pubkey cache which is inconsistent with the head"
+                    );
+                }
+                ValidatorPubkeyCache::new(&canonical_head.beacon_state, &store, &log)
+                    .map_err(|e| format!("Unable to init validator pubkey cache: {:?}", e))?
+            }
+        };
 
         let migrator_config = self.store_migrator_config.unwrap_or_default();
         let store_migrator = BackgroundMigrator::new(
@@ -535,6 +742,11 @@ where
             log.clone(),
         );
 
+        let snapshot_cache = SnapshotCache::new(
+            self.chain_config.snapshot_cache_size,
+            canonical_head.clone(),
+        );
+
         let beacon_chain = BeaconChain {
             spec: self.spec,
             config: self.chain_config,
@@ -560,7 +772,7 @@ where
             observed_attester_slashings: <_>::default(),
             eth1_chain: self.eth1_chain,
             genesis_validators_root: canonical_head.beacon_state.genesis_validators_root,
-            canonical_head: TimeoutRwLock::new(canonical_head.clone()),
+            canonical_head: TimeoutRwLock::new(canonical_head),
             genesis_block_root,
             genesis_state_root,
             fork_choice: RwLock::new(fork_choice),
@@ -568,11 +780,9 @@ where
                 .event_handler
                 .ok_or_else(|| "Cannot build without an event handler".to_string())?,
             head_tracker: Arc::new(self.head_tracker.unwrap_or_default()),
-            snapshot_cache: TimeoutRwLock::new(SnapshotCache::new(
-                DEFAULT_SNAPSHOT_CACHE_SIZE,
-                canonical_head,
-            )),
+            snapshot_cache: TimeoutRwLock::new(snapshot_cache),
             shuffling_cache: TimeoutRwLock::new(ShufflingCache::new()),
+            proposer_cache: Mutex::new(ProposerCache::new()),
             validator_pubkey_cache: TimeoutRwLock::new(validator_pubkey_cache),
             disabled_forks: self.disabled_forks,
             shutdown_sender: self
@@ -589,11 +799,44 @@ where
 
         // Only perform the check if it was configured.
         if let Some(wss_checkpoint) = beacon_chain.config.weak_subjectivity_checkpoint {
-            if let Err(e) = beacon_chain.verify_weak_subjectivity_checkpoint(
-                wss_checkpoint,
-                head.beacon_block_root,
-                &head.beacon_state,
-            ) {
+            // The finalized checkpoint of fork choice may be more recent than that of the head
+            // state, e.g. the checkpoint a database was started from.
+            let finalized_checkpoint = beacon_chain.fork_choice.read().finalized_checkpoint();
+            let wss_slot = wss_checkpoint.epoch.start_slot(TEthSpec::slots_per_epoch());
+            let anchor_slot = beacon_chain
+                .store
+                .load_anchor_slot()
+                .map_err(|e| format!("Failed to load anchor slot: {:?}", e))?;
+
+            let result = if wss_checkpoint.epoch == finalized_checkpoint.epoch {
+                if wss_checkpoint.root == finalized_checkpoint.root {
+                    Ok(())
+                } else {
+                    Err(BeaconChainError::WeakSubjectivtyVerificationFailure)
+                }
+            } else if wss_checkpoint.epoch < finalized_checkpoint.epoch
+                && anchor_slot.map_or(false, |anchor_slot| wss_slot < anchor_slot)
+            {
+                // The blocks prior to the anchor aren't stored, the checkpoint state is only
+                // available to verify against when the database is first started from it.
+                warn!(
+                    log,
+                    "Skipping weak subjectivity checkpoint verification";
+                    "info" => "it precedes the oldest block in the database, and is only \
+                               verified when starting from a checkpoint state",
+                    "weak_subjectivity_epoch" => wss_checkpoint.epoch,
+                    "anchor_slot" => anchor_slot,
+                );
+                Ok(())
+            } else {
+                beacon_chain.verify_weak_subjectivity_checkpoint(
+                    wss_checkpoint,
+                    head.beacon_block_root,
+                    &head.beacon_state,
+                )
+            };
+
+            if let Err(e) = result {
                 crit!(
                     log,
                     "Weak subjectivity checkpoint verification failed on startup!";
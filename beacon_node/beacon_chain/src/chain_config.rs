@@ -1,5 +1,6 @@
+use crate::snapshot_cache::DEFAULT_SNAPSHOT_CACHE_SIZE;
 use serde_derive::{Deserialize, Serialize};
-use types::Checkpoint;
+use types::{Checkpoint, PublicKeyBytes};
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct ChainConfig {
@@ -12,6 +13,11 @@ pub struct ChainConfig {
     ///
     /// If `None`, there is no weak subjectivity verification.
     pub weak_subjectivity_checkpoint: Option<Checkpoint>,
+    /// Maximum number of recent block post-states to keep in memory for block processing.
+    pub snapshot_cache_size: usize,
+    /// The operator's own validators, for which a critical alarm is raised if they appear in a
+    /// slashing.
+    pub monitored_validators: Vec<PublicKeyBytes>,
 }
 
 impl Default for ChainConfig {
@@ -19,6 +25,8 @@ impl Default for ChainConfig {
         Self {
             import_max_skip_slots: None,
             weak_subjectivity_checkpoint: None,
+            snapshot_cache_size: DEFAULT_SNAPSHOT_CACHE_SIZE,
+            monitored_validators: vec![],
         }
     }
 }
@@ -8,6 +8,7 @@ use crate::observed_block_producers::Error as ObservedBlockProducersError;
 use futures::channel::mpsc::TrySendError;
 use operation_pool::OpPoolError;
 use safe_arith::ArithError;
+use slasher::Error as SlasherError;
 use ssz_types::Error as SszTypesError;
 use state_processing::{
     block_signature_verifier::Error as BlockSignatureVerifierError,
@@ -76,7 +77,7 @@ pub enum BeaconChainError {
     SignatureSetError(SignatureSetError),
     BlockSignatureVerifierError(state_processing::block_signature_verifier::Error),
     DuplicateValidatorPublicKey,
-    ValidatorPubkeyCacheFileError(String),
+    ValidatorPubkeyCacheError(String),
     OpPoolError(OpPoolError),
     NaiveAggregationError(NaiveAggregationError),
     ObservedAttestationsError(ObservedAttestationsError),
@@ -90,6 +91,9 @@ pub enum BeaconChainError {
     },
     WeakSubjectivtyVerificationFailure,
     WeakSubjectivtyShutdownError(TrySendError<&'static str>),
+    BlockProcessingError(BlockProcessingError),
+    SlasherNotConfigured,
+    SlasherError(SlasherError),
 }
 
 easy_from_to!(SlotProcessingError, BeaconChainError);
@@ -106,6 +110,8 @@ easy_from_to!(ObservedBlockProducersError, BeaconChainError);
 easy_from_to!(BlockSignatureVerifierError, BeaconChainError);
 easy_from_to!(PruningError, BeaconChainError);
 easy_from_to!(ArithError, BeaconChainError);
+easy_from_to!(BlockProcessingError, BeaconChainError);
+easy_from_to!(SlasherError, BeaconChainError);
 
 #[derive(Debug)]
 pub enum BlockProductionError {
@@ -117,6 +123,7 @@ pub enum BlockProductionError {
     Eth1ChainError(Eth1ChainError),
     BeaconStateError(BeaconStateError),
     OpPoolError(OpPoolError),
+    BeaconChainError(BeaconChainError),
     /// The `BeaconChain` was explicitly configured _without_ a connection to eth1, therefore it
     /// cannot produce blocks.
     NoEth1ChainConnection,
@@ -1,10 +1,14 @@
+use crate::slashing_webhook::SlashingWebhook;
 use bus::Bus;
 use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
 use slog::{error, Logger};
 use std::marker::PhantomData;
 use std::sync::Arc;
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, SignedBeaconBlockHash};
+use types::{
+    Attestation, AttesterSlashing, Epoch, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
+    SignedBeaconBlock, SignedBeaconBlockHash,
+};
 pub use websocket_server::WebSocketSender;
 
 pub trait EventHandler<T: EthSpec>: Sized + Send + Sync {
@@ -75,6 +79,8 @@ impl<T: EthSpec> EventHandler<T> for ServerSentEvents<T> {
 pub struct TeeEventHandler<E: EthSpec> {
     websockets_handler: WebSocketSender<E>,
     sse_handler: ServerSentEvents<E>,
+    slashing_webhook: Option<SlashingWebhook<E>>,
+    log: Logger,
 }
 
 impl<E: EthSpec> TeeEventHandler<E> {
@@ -82,11 +88,14 @@ impl<E: EthSpec> TeeEventHandler<E> {
     pub fn new(
         log: Logger,
         websockets_handler: WebSocketSender<E>,
+        slashing_webhook: Option<SlashingWebhook<E>>,
     ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
-        let (sse_handler, bus) = ServerSentEvents::new(log);
+        let (sse_handler, bus) = ServerSentEvents::new(log.clone());
         let result = Self {
             websockets_handler,
             sse_handler,
+            slashing_webhook,
+            log,
         };
         Ok((result, bus))
     }
@@ -95,6 +104,12 @@ impl<E: EthSpec> TeeEventHandler<E> {
 impl<E: EthSpec> EventHandler<E> for TeeEventHandler<E> {
     fn register(&self, kind: EventKind<E>) -> Result<(), String> {
         self.websockets_handler.register(kind.clone())?;
+        if let Some(slashing_webhook) = &self.slashing_webhook {
+            // A notification which can't be queued mustn't keep the event from the other handlers.
+            if let Err(e) = slashing_webhook.register(kind.clone()) {
+                error!(self.log, "Slashing webhook error"; "error" => e);
+            }
+        }
         self.sse_handler.register(kind)?;
         Ok(())
     }
@@ -144,4 +159,18 @@ pub enum EventKind<T: EthSpec> {
         reason: String,
         attestation: Box<Attestation<T>>,
     },
+    AttesterSlashingImported {
+        slashing: Box<AttesterSlashing<T>>,
+    },
+    ProposerSlashingImported {
+        slashing: Box<ProposerSlashing>,
+    },
+    /// One of the `monitored_validators` appeared in a slashing, either in the op pool or in a
+    /// block.
+    MonitoredValidatorSlashed {
+        validator_index: u64,
+        pubkey: PublicKeyBytes,
+        kind: String,
+        source: String,
+    },
 }
@@ -0,0 +1,123 @@
+//! Recovery of fork choice from writes which did not reach the database.
+//!
+//! Fork choice is persisted separately from the blocks and states it refers to, so a crash or a
+//! failed write can leave it referring to a block which is not stored. This module detects that
+//! case and rebuilds fork choice from the database instead.
+use crate::beacon_chain::FORK_CHOICE_DB_KEY;
+use crate::persisted_fork_choice::PersistedForkChoice;
+use crate::{BeaconForkChoiceStore, BeaconSnapshot};
+use fork_choice::ForkChoice;
+use state_processing::per_slot_processing;
+use std::sync::Arc;
+use store::{HotColdDB, ItemStore};
+use types::{ChainSpec, Checkpoint, EthSpec, Hash256, SignedBeaconBlock};
+
+/// Fork choice, as it is stored in a `BeaconChain`.
+pub type BeaconForkChoice<E, Hot, Cold> = ForkChoice<BeaconForkChoiceStore<E, Hot, Cold>, E>;
+
+/// Load the copy of fork choice which was last persisted to `store`, if any.
+pub fn load_fork_choice<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+) -> Result<Option<BeaconForkChoice<E, Hot, Cold>>, String> {
+    let persisted_fork_choice = match store
+        .get_item::<PersistedForkChoice>(&FORK_CHOICE_DB_KEY)
+        .map_err(|e| format!("DB error when reading persisted fork choice: {:?}", e))?
+    {
+        Some(persisted_fork_choice) => persisted_fork_choice,
+        None => return Ok(None),
+    };
+
+    let fc_store =
+        BeaconForkChoiceStore::from_persisted(persisted_fork_choice.fork_choice_store, store)
+            .map_err(|e| format!("Unable to load ForkChoiceStore: {:?}", e))?;
+
+    ForkChoice::from_persisted(persisted_fork_choice.fork_choice, fc_store)
+        .map(Some)
+        .map_err(|e| format!("Unable to parse persisted fork choice from disk: {:?}", e))
+}
+
+/// Return the root of a block which fork choice might select as the head, but which is missing
+/// from `store` or lacks its state.
+///
+/// Blocks which conflict with finalization are ignored, as they are pruned from the database
+/// before they are pruned from fork choice.
+pub fn find_missing_block<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    fork_choice: &BeaconForkChoice<E, Hot, Cold>,
+    store: &HotColdDB<E, Hot, Cold>,
+) -> Result<Option<Hash256>, String> {
+    let split_slot = store.get_split_slot();
+
+    for node in &fork_choice.proto_array().core_proto_array().nodes {
+        if !fork_choice.is_descendant_of_finalized(node.root) {
+            continue;
+        }
+
+        let block_exists = store
+            .item_exists::<SignedBeaconBlock<E>>(&node.root)
+            .map_err(|e| format!("DB error when checking block {:?}: {:?}", node.root, e))?;
+        // The states of finalized blocks may have been moved to the freezer.
+        let state_exists = node.slot < split_slot
+            || store
+                .load_hot_state_summary(&node.state_root)
+                .map_err(|e| {
+                    format!(
+                        "DB error when checking state {:?}: {:?}",
+                        node.state_root, e
+                    )
+                })?
+                .is_some();
+
+        if !block_exists || !state_exists {
+            return Ok(Some(node.root));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Create a new fork choice which starts from the `finalized_checkpoint`, discarding the blocks
+/// that were imported after it.
+///
+/// The discarded blocks remain in the database, and are imported again by sync.
+pub fn reset_fork_choice_to_finalization<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+    finalized_checkpoint: Checkpoint,
+    spec: &ChainSpec,
+) -> Result<BeaconForkChoice<E, Hot, Cold>, String> {
+    let finalized_block_root = finalized_checkpoint.root;
+    let finalized_block = store
+        .get_block(&finalized_block_root)
+        .map_err(|e| format!("DB error when reading finalized block: {:?}", e))?
+        .ok_or_else(|| format!("Finalized block missing: {:?}", finalized_block_root))?;
+    let finalized_block_state_root = finalized_block.state_root();
+    let mut finalized_state = store
+        .get_state(&finalized_block_state_root, Some(finalized_block.slot()))
+        .map_err(|e| format!("DB error when reading finalized state: {:?}", e))?
+        .ok_or_else(|| format!("Finalized state missing: {:?}", finalized_block_state_root))?;
+
+    // Fork choice is anchored at the start of the finalized epoch, as it is when starting from a
+    // checkpoint, so the state is advanced through any skipped slots.
+    let finalized_slot = finalized_checkpoint.epoch.start_slot(E::slots_per_epoch());
+    let mut state_root = Some(finalized_block_state_root);
+    while finalized_state.slot < finalized_slot {
+        per_slot_processing(&mut finalized_state, state_root.take(), spec)
+            .map_err(|e| format!("Unable to advance finalized state: {:?}", e))?;
+    }
+
+    let snapshot = BeaconSnapshot {
+        beacon_block_root: finalized_block_root,
+        beacon_block: finalized_block,
+        beacon_state_root: finalized_block_state_root,
+        beacon_state: finalized_state,
+    };
+
+    let fc_store = BeaconForkChoiceStore::get_forkchoice_store(store, &snapshot);
+
+    ForkChoice::from_genesis(
+        fc_store,
+        snapshot.beacon_block_root,
+        &snapshot.beacon_block.message,
+        &snapshot.beacon_state,
+    )
+    .map_err(|e| format!("Unable to reset fork choice to finalization: {:?}", e))
+}
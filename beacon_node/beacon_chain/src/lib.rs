@@ -9,6 +9,7 @@ pub mod chain_config;
 mod errors;
 pub mod eth1_chain;
 pub mod events;
+mod fork_revert;
 mod head_tracker;
 mod metrics;
 pub mod migrate;
@@ -19,7 +20,9 @@ mod observed_block_producers;
 pub mod observed_operations;
 mod persisted_beacon_chain;
 mod persisted_fork_choice;
+mod proposer_cache;
 mod shuffling_cache;
+pub mod slashing_webhook;
 mod snapshot_cache;
 pub mod test_utils;
 mod timeout_rw_lock;
@@ -27,7 +30,7 @@ mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
     AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, ChainSegmentResult,
-    ForkChoiceError, StateSkipConfig,
+    ForkChoiceError, StateGarbageCollection, StateSkipConfig,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
 pub use self::chain_config::ChainConfig;
@@ -39,6 +42,7 @@ pub use eth1_chain::{Eth1Chain, Eth1ChainBackend};
 pub use events::EventHandler;
 pub use metrics::scrape_for_metrics;
 pub use parking_lot;
+pub use proposer_cache::{proposer_shuffling_decision_root, ProposerCache};
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, DepositValidationError,
@@ -172,6 +172,26 @@ lazy_static! {
         "beacon_attestation_processing_signature_seconds",
         "Time spent on the signature verification of attestation processing"
     );
+    pub static ref ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_SETUP_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_unagg_signature_setup_seconds",
+        "Time spent on setting up for the signature verification of a batch of unaggregated attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_unagg_signature_seconds",
+        "Time spent on the signature verification of a batch of unaggregated attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_SETUP_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_agg_signature_setup_seconds",
+        "Time spent on setting up for the signature verification of a batch of aggregates"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_agg_signature_seconds",
+        "Time spent on the signature verification of a batch of aggregates"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_processing_batch_signature_failures_total",
+        "Count of attestation signature batches which were invalid and verified individually"
+    );
 
     /*
      * Shuffling cache
@@ -180,6 +200,36 @@ lazy_static! {
         try_create_int_counter("beacon_shuffling_cache_hits_total", "Count of times shuffling cache fulfils request");
     pub static ref SHUFFLING_CACHE_MISSES: Result<IntCounter> =
         try_create_int_counter("beacon_shuffling_cache_misses_total", "Count of times shuffling cache fulfils request");
+    pub static ref SHUFFLING_PRECOMPUTE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_shuffling_precompute_seconds",
+        "Time taken to precompute the committees and proposers at the start of an epoch"
+    );
+
+    /*
+     * Proposer cache
+     */
+    pub static ref PROPOSER_CACHE_HITS: Result<IntCounter> =
+        try_create_int_counter("beacon_proposer_cache_hits_total", "Count of times proposer cache fulfils request");
+    pub static ref PROPOSER_CACHE_MISSES: Result<IntCounter> =
+        try_create_int_counter("beacon_proposer_cache_misses_total", "Count of times proposer cache fails to fulfil request");
+
+    /*
+     * Snapshot cache
+     */
+    pub static ref SNAPSHOT_CACHE_HITS: Result<IntCounter> =
+        try_create_int_counter("beacon_snapshot_cache_hits_total", "Count of times snapshot cache fulfils request");
+    pub static ref SNAPSHOT_CACHE_MISSES: Result<IntCounter> =
+        try_create_int_counter("beacon_snapshot_cache_misses_total", "Count of times snapshot cache fails to fulfil request");
+    pub static ref SNAPSHOT_CACHE_EVICTIONS: Result<IntCounter> =
+        try_create_int_counter("beacon_snapshot_cache_evictions_total", "Count of snapshots ejected from the full snapshot cache");
+
+    /*
+     * Monitored validators
+     */
+    pub static ref MONITORED_VALIDATOR_SLASHINGS: Result<IntCounter> = try_create_int_counter(
+        "beacon_monitored_validator_slashings_total",
+        "Count of times a monitored validator has been seen in a slashing"
+    );
 
     /*
      * Attestation Production
@@ -248,6 +298,16 @@ lazy_static! {
     pub static ref PERSIST_FORK_CHOICE: Result<Histogram> =
         try_create_histogram("beacon_persist_fork_choice", "Time taken to persist the fork choice struct");
 
+    /*
+     * Orphaned state garbage collection
+     */
+    pub static ref STATE_GC_TIMES: Result<Histogram> =
+        try_create_histogram("beacon_state_gc_seconds", "Time taken to garbage collect orphaned states");
+    pub static ref STATE_GC_STATES_DELETED: Result<IntCounter> =
+        try_create_int_counter("beacon_state_gc_states_deleted_total", "Count of orphaned states deleted from the hot database");
+    pub static ref STATE_GC_BYTES_RECLAIMED: Result<IntCounter> =
+        try_create_int_counter("beacon_state_gc_bytes_reclaimed_total", "Bytes of orphaned states deleted from the hot database");
+
     /*
      * Eth1
      */
@@ -24,13 +24,22 @@ const MAX_COMPACTION_PERIOD_SECONDS: u64 = 604800;
 const MIN_COMPACTION_PERIOD_SECONDS: u64 = 7200;
 /// Compact after a large finality gap, if we respect `MIN_COMPACTION_PERIOD_SECONDS`.
 const COMPACTION_FINALITY_DISTANCE: u64 = 1024;
+/// Reconstruct at most this many historic restore points between checks for new migrations.
+const RECONSTRUCTION_BATCH_SIZE: usize = 1;
+
+/// The next migration for the background thread to run.
+///
+/// Each finalization replaces the migration waiting here, as a migration to a later checkpoint
+/// subsumes it. This bounds the work queued behind a slow migration to a single migration.
+type PendingMigration = Arc<Mutex<Option<MigrationNotification>>>;
 
 /// The background migrator runs a thread to perform pruning and migrate state from the hot
 /// to the cold database.
 pub struct BackgroundMigrator<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     db: Arc<HotColdDB<E, Hot, Cold>>,
-    #[allow(clippy::type_complexity)]
-    tx_thread: Option<Mutex<(mpsc::Sender<MigrationNotification>, thread::JoinHandle<()>)>>,
+    pending: PendingMigration,
+    /// Wakes the background thread when a migration is pending.
+    tx_thread: Option<Mutex<(mpsc::SyncSender<()>, thread::JoinHandle<()>)>>,
     /// Genesis block root, for persisting the `PersistedBeaconChain`.
     genesis_block_root: Hash256,
     log: Logger,
@@ -88,13 +97,21 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
         genesis_block_root: Hash256,
         log: Logger,
     ) -> Self {
+        let pending = PendingMigration::default();
         let tx_thread = if config.blocking {
+            Self::run_historic_state_pruning(&db, &log);
+            Self::run_reconstruction_to_completion(&db, &log);
             None
         } else {
-            Some(Mutex::new(Self::spawn_thread(db.clone(), log.clone())))
+            Some(Mutex::new(Self::spawn_thread(
+                db.clone(),
+                pending.clone(),
+                log.clone(),
+            )))
         };
         Self {
             db,
+            pending,
             tx_thread,
             genesis_block_root,
             log,
@@ -121,11 +138,22 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
 
         // Async path, on the background thread.
         if let Some(tx_thread) = &self.tx_thread {
+            {
+                let mut pending = self.pending.lock();
+                if pending.as_ref().map_or(true, |pending| {
+                    notif.finalized_checkpoint.epoch > pending.finalized_checkpoint.epoch
+                }) {
+                    *pending = Some(notif);
+                }
+            }
+
             let (ref mut tx, ref mut thread) = *tx_thread.lock();
 
-            // Restart the background thread if it has crashed.
-            if let Err(tx_err) = tx.send(notif) {
-                let (new_tx, new_thread) = Self::spawn_thread(self.db.clone(), self.log.clone());
+            // A full channel means that the thread is yet to wake up and take the pending
+            // migration. Restart the background thread if it has crashed.
+            if let Err(mpsc::TrySendError::Disconnected(())) = tx.try_send(()) {
+                let (new_tx, new_thread) =
+                    Self::spawn_thread(self.db.clone(), self.pending.clone(), self.log.clone());
 
                 *tx = new_tx;
                 let old_thread = mem::replace(thread, new_thread);
@@ -141,12 +169,13 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
                 }
 
                 // Retry at most once, we could recurse but that would risk overflowing the stack.
-                let _ = tx.send(tx_err.0);
+                let _ = tx.try_send(());
             }
         }
         // Synchronous path, on the current thread.
         else {
-            Self::run_migration(self.db.clone(), notif, &self.log)
+            Self::run_migration(self.db.clone(), notif, &self.log);
+            Self::run_reconstruction_to_completion(&self.db, &self.log);
         }
 
         Ok(())
@@ -227,32 +256,89 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
 
     /// Spawn a new child thread to run the migration process.
     ///
-    /// Return a channel handle for sending new finalized states to the thread.
+    /// Return a channel handle for waking the thread when a migration is pending.
     fn spawn_thread(
         db: Arc<HotColdDB<E, Hot, Cold>>,
+        pending: PendingMigration,
         log: Logger,
-    ) -> (mpsc::Sender<MigrationNotification>, thread::JoinHandle<()>) {
-        let (tx, rx) = mpsc::channel();
+    ) -> (mpsc::SyncSender<()>, thread::JoinHandle<()>) {
+        let (tx, rx) = mpsc::sync_channel(1);
         let thread = thread::spawn(move || {
-            while let Ok(notif) = rx.recv() {
-                // Read the rest of the messages in the channel, ultimately choosing the `notif`
-                // with the highest finalized epoch.
-                let notif = rx
-                    .try_iter()
-                    .fold(notif, |best, other: MigrationNotification| {
-                        if other.finalized_checkpoint.epoch > best.finalized_checkpoint.epoch {
-                            other
-                        } else {
-                            best
+            Self::run_historic_state_pruning(&db, &log);
+
+            // Historic states are reconstructed in batches while there are no migrations waiting,
+            // so that reconstruction doesn't hold up the freezing of newly finalized states.
+            let mut reconstruction_enabled = db.get_config().reconstruct_historic_states;
+            let mut reconstruct = reconstruction_enabled;
+
+            loop {
+                if reconstruct {
+                    match rx.try_recv() {
+                        Ok(()) => (),
+                        Err(mpsc::TryRecvError::Empty) => {
+                            match db.reconstruct_historic_states(RECONSTRUCTION_BATCH_SIZE) {
+                                Ok(more) => reconstruct = more,
+                                Err(e) => {
+                                    error!(
+                                        log,
+                                        "Historic state reconstruction failed";
+                                        "info" => "reconstruction will be retried after a restart",
+                                        "error" => ?e
+                                    );
+                                    reconstruction_enabled = false;
+                                    reconstruct = false;
+                                }
+                            }
+                            continue;
                         }
-                    });
+                        Err(mpsc::TryRecvError::Disconnected) => break,
+                    }
+                } else if rx.recv().is_err() {
+                    break;
+                }
+
+                // The pending migration may already have been taken after an earlier wake-up.
+                let notif = match pending.lock().take() {
+                    Some(notif) => notif,
+                    None => continue,
+                };
 
                 Self::run_migration(db.clone(), notif, &log);
+
+                // Newly frozen states may allow reconstruction to continue.
+                reconstruct = reconstruction_enabled;
             }
         });
         (tx, thread)
     }
 
+    /// Delete the restore points of pruned historic states.
+    ///
+    /// No restore points are stored while pruning is enabled, so this only needs to run once on
+    /// start-up.
+    fn run_historic_state_pruning(db: &Arc<HotColdDB<E, Hot, Cold>>, log: &Logger) {
+        if let Err(e) = db.prune_historic_states() {
+            error!(log, "Historic state pruning failed"; "error" => ?e);
+        }
+    }
+
+    /// Reconstruct all of the historic states that can currently be reconstructed.
+    fn run_reconstruction_to_completion(db: &Arc<HotColdDB<E, Hot, Cold>>, log: &Logger) {
+        if !db.get_config().reconstruct_historic_states {
+            return;
+        }
+        loop {
+            match db.reconstruct_historic_states(RECONSTRUCTION_BATCH_SIZE) {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(e) => {
+                    error!(log, "Historic state reconstruction failed"; "error" => ?e);
+                    break;
+                }
+            }
+        }
+    }
+
     /// Traverses live heads and prunes blocks and states of chains that we know can't be built
     /// upon because finalization would prohibit it. This is an optimisation intended to save disk
     /// space.
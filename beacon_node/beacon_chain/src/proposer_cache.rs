@@ -0,0 +1,79 @@
+use crate::metrics;
+use lru::LruCache;
+use types::{BeaconState, BeaconStateError, Epoch, EthSpec, Hash256};
+
+/// The size of the LRU cache that stores the block proposers of an epoch.
+///
+/// Each entry is one validator index per slot of the epoch, so even a generous number of forks
+/// across a few epochs is cheap to keep around.
+const CACHE_SIZE: usize = 16;
+
+/// Provides an LRU cache of the block proposer indices for each slot of an epoch.
+///
+/// Proposers are keyed by the epoch and the "decision root", the root of the block at the last
+/// slot of the previous epoch (see `proposer_shuffling_decision_root`), since any state descended
+/// from that block will select the same proposers for the epoch.
+pub struct ProposerCache {
+    cache: LruCache<(Epoch, Hash256), Vec<usize>>,
+}
+
+impl ProposerCache {
+    pub fn new() -> Self {
+        Self {
+            cache: LruCache::new(CACHE_SIZE),
+        }
+    }
+
+    /// Returns the proposer indices for each slot of `epoch`, in slot order.
+    pub fn get(&mut self, epoch: Epoch, decision_root: Hash256) -> Option<&[usize]> {
+        let opt = self.cache.get(&(epoch, decision_root));
+
+        if opt.is_some() {
+            metrics::inc_counter(&metrics::PROPOSER_CACHE_HITS);
+        } else {
+            metrics::inc_counter(&metrics::PROPOSER_CACHE_MISSES);
+        }
+
+        opt.map(Vec::as_slice)
+    }
+
+    pub fn contains(&self, epoch: Epoch, decision_root: Hash256) -> bool {
+        self.cache.contains(&(epoch, decision_root))
+    }
+
+    pub fn insert(&mut self, epoch: Epoch, decision_root: Hash256, proposers: Vec<usize>) {
+        let key = (epoch, decision_root);
+        if !self.cache.contains(&key) {
+            self.cache.put(key, proposers);
+        }
+    }
+}
+
+impl Default for ProposerCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns a block root which can be used to key the proposers of `shuffling_epoch`, where
+/// `head_block_root` is the latest block applied to `head_block_state`.
+///
+/// This is the root of the block at the last slot of the epoch prior to `shuffling_epoch`.
+pub fn proposer_shuffling_decision_root<E: EthSpec>(
+    shuffling_epoch: Epoch,
+    head_block_root: Hash256,
+    head_block_state: &BeaconState<E>,
+) -> Result<Hash256, BeaconStateError> {
+    let decision_slot = shuffling_epoch
+        .start_slot(E::slots_per_epoch())
+        .saturating_sub(1_u64);
+
+    // If decision slot is equal to or ahead of the head, the block root is the head block root
+    if decision_slot >= head_block_state.slot {
+        Ok(head_block_root)
+    } else {
+        head_block_state
+            .get_block_root(decision_slot)
+            .map(|root| *root)
+    }
+}
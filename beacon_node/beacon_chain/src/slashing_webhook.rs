@@ -0,0 +1,389 @@
+//! Notifies operators of slashings by POSTing them as JSON to a webhook URL.
+//!
+//! Every slashing imported into the op pool is reported, whether it was found by the slasher,
+//! received on gossip or submitted to the HTTP API. Notifications are sent from a background
+//! task, and retried with an exponential backoff if the webhook can't be reached.
+use crate::events::{EventHandler, EventKind};
+use reqwest::{Client, Url};
+use serde_derive::{Deserialize, Serialize};
+use slog::{debug, error, warn, Logger};
+use std::collections::HashSet;
+use std::time::Duration;
+use task_executor::TaskExecutor;
+use tokio::sync::mpsc;
+use types::{AttesterSlashing, EthSpec, ProposerSlashing};
+
+/// The maximum number of notifications waiting to be sent before new ones are dropped.
+const QUEUE_SIZE: usize = 256;
+/// The number of times a notification is attempted before it is dropped.
+const MAX_ATTEMPTS: u32 = 5;
+/// The delay before the first retry, which doubles for each later one.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The URL which notifications are POSTed to.
+    pub url: String,
+    /// Only notify of slashings which include one of these validators, or of all slashings if
+    /// `None`.
+    pub validators: Option<Vec<u64>>,
+}
+
+/// The JSON body of a notification.
+#[derive(Debug, Clone, Serialize)]
+#[serde(bound = "T: EthSpec", rename_all = "snake_case", tag = "kind")]
+pub enum SlashingNotification<T: EthSpec> {
+    AttesterSlashing {
+        /// The slashed validators, limited to those which are monitored.
+        validator_indices: Vec<u64>,
+        slashing: AttesterSlashing<T>,
+    },
+    ProposerSlashing {
+        validator_indices: Vec<u64>,
+        slashing: ProposerSlashing,
+    },
+}
+
+/// An `EventHandler` which queues notifications for the slashing events of monitored validators.
+pub struct SlashingWebhook<T: EthSpec> {
+    tx: mpsc::Sender<SlashingNotification<T>>,
+    validators: Option<HashSet<u64>>,
+}
+
+impl<T: EthSpec> SlashingWebhook<T> {
+    /// Start the task which sends notifications to the webhook.
+    pub fn new(config: Config, executor: TaskExecutor, log: Logger) -> Result<Self, String> {
+        let url = Url::parse(&config.url)
+            .map_err(|e| format!("Invalid slashing webhook URL: {:?}", e))?;
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Unable to build slashing webhook client: {:?}", e))?;
+
+        let (tx, mut rx) = mpsc::channel(QUEUE_SIZE);
+
+        executor.spawn(
+            async move {
+                while let Some(notification) = rx.recv().await {
+                    send_notification(
+                        &client,
+                        url.clone(),
+                        &notification,
+                        INITIAL_RETRY_DELAY,
+                        &log,
+                    )
+                    .await;
+                }
+            },
+            "slashing_webhook",
+        );
+
+        Ok(Self {
+            tx,
+            validators: config
+                .validators
+                .map(|validators| validators.into_iter().collect()),
+        })
+    }
+
+    /// Return the validators of `slashed` which are monitored.
+    fn monitored(&self, slashed: impl IntoIterator<Item = u64>) -> Vec<u64> {
+        slashed
+            .into_iter()
+            .filter(|index| {
+                self.validators
+                    .as_ref()
+                    .map_or(true, |validators| validators.contains(index))
+            })
+            .collect()
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for SlashingWebhook<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let notification = match kind {
+            EventKind::AttesterSlashingImported { slashing } => {
+                SlashingNotification::AttesterSlashing {
+                    validator_indices: self.monitored(slashing.intersecting_indices()),
+                    slashing: *slashing,
+                }
+            }
+            EventKind::ProposerSlashingImported { slashing } => {
+                let slashed = Some(slashing.signed_header_1.message.proposer_index);
+
+                SlashingNotification::ProposerSlashing {
+                    validator_indices: self.monitored(slashed),
+                    slashing: *slashing,
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        let validator_indices = match &notification {
+            SlashingNotification::AttesterSlashing {
+                validator_indices, ..
+            }
+            | SlashingNotification::ProposerSlashing {
+                validator_indices, ..
+            } => validator_indices,
+        };
+        if validator_indices.is_empty() {
+            return Ok(());
+        }
+
+        self.tx
+            .try_send(notification)
+            .map_err(|e| format!("Unable to queue slashing notification: {:?}", e))
+    }
+}
+
+/// POST `notification` to `url`, retrying after `retry_delay`, doubled for each later retry,
+/// until it succeeds or `MAX_ATTEMPTS` is reached.
+async fn send_notification<T: EthSpec>(
+    client: &Client,
+    url: Url,
+    notification: &SlashingNotification<T>,
+    mut retry_delay: Duration,
+    log: &Logger,
+) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(url.clone())
+            .json(notification)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => {
+                debug!(log, "Sent slashing notification"; "attempt" => attempt);
+                return;
+            }
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!(
+                    log,
+                    "Slashing notification failed, retrying";
+                    "error" => format!("{:?}", e),
+                    "attempt" => attempt,
+                    "retry_delay" => format!("{:?}", retry_delay),
+                );
+                tokio::time::sleep(retry_delay).await;
+                retry_delay *= 2;
+            }
+            Err(e) => error!(
+                log,
+                "Unable to send slashing notification";
+                "error" => format!("{:?}", e),
+                "attempts" => attempt,
+                "notification" => format!("{:?}", notification),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use environment::{null_logger, EnvironmentBuilder};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc as std_mpsc;
+    use std::thread;
+    use tokio_compat_02::FutureExt;
+    use types::test_utils::test_random_instance;
+    use types::{Epoch, Hash256, MinimalEthSpec, VariableList};
+
+    type E = MinimalEthSpec;
+
+    fn webhook(
+        validators: Option<Vec<u64>>,
+    ) -> (SlashingWebhook<E>, mpsc::Receiver<SlashingNotification<E>>) {
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let webhook = SlashingWebhook {
+            tx,
+            validators: validators.map(|validators| validators.into_iter().collect()),
+        };
+        (webhook, rx)
+    }
+
+    fn proposer_slashing(proposer_index: u64) -> ProposerSlashing {
+        let mut slashing: ProposerSlashing = test_random_instance();
+        slashing.signed_header_1.message.proposer_index = proposer_index;
+        slashing.signed_header_2.message.proposer_index = proposer_index;
+        slashing
+    }
+
+    fn attester_slashing(indices_1: Vec<u64>, indices_2: Vec<u64>) -> AttesterSlashing<E> {
+        let mut slashing: AttesterSlashing<E> = test_random_instance();
+        slashing.attestation_1.attesting_indices = VariableList::new(indices_1).unwrap();
+        slashing.attestation_2.attesting_indices = VariableList::new(indices_2).unwrap();
+        slashing
+    }
+
+    /// Return the validators of the next queued notification, if any.
+    fn next_validators(rx: &mut mpsc::Receiver<SlashingNotification<E>>) -> Option<Vec<u64>> {
+        match futures::FutureExt::now_or_never(rx.recv()).flatten()? {
+            SlashingNotification::AttesterSlashing {
+                validator_indices, ..
+            }
+            | SlashingNotification::ProposerSlashing {
+                validator_indices, ..
+            } => Some(validator_indices),
+        }
+    }
+
+    #[test]
+    fn notifies_monitored_validators() {
+        let (webhook, mut rx) = webhook(Some(vec![2, 5]));
+
+        webhook
+            .register(EventKind::AttesterSlashingImported {
+                slashing: Box::new(attester_slashing(vec![1, 2, 3], vec![2, 3, 4])),
+            })
+            .unwrap();
+        assert_eq!(next_validators(&mut rx), Some(vec![2]));
+
+        // Slashings and events which don't concern the monitored validators are ignored.
+        webhook
+            .register(EventKind::AttesterSlashingImported {
+                slashing: Box::new(attester_slashing(vec![1, 3], vec![3, 5])),
+            })
+            .unwrap();
+        webhook
+            .register(EventKind::ProposerSlashingImported {
+                slashing: Box::new(proposer_slashing(1)),
+            })
+            .unwrap();
+        webhook
+            .register(EventKind::BeaconFinalization {
+                epoch: Epoch::new(1),
+                root: Hash256::zero(),
+            })
+            .unwrap();
+        assert_eq!(next_validators(&mut rx), None);
+
+        webhook
+            .register(EventKind::ProposerSlashingImported {
+                slashing: Box::new(proposer_slashing(5)),
+            })
+            .unwrap();
+        assert_eq!(next_validators(&mut rx), Some(vec![5]));
+    }
+
+    #[test]
+    fn notifies_all_validators() {
+        let (webhook, mut rx) = webhook(None);
+
+        webhook
+            .register(EventKind::AttesterSlashingImported {
+                slashing: Box::new(attester_slashing(vec![1, 2, 3], vec![2, 3, 4])),
+            })
+            .unwrap();
+        assert_eq!(next_validators(&mut rx), Some(vec![2, 3]));
+    }
+
+    #[test]
+    fn full_queue() {
+        let (webhook, _rx) = webhook(None);
+
+        for _ in 0..QUEUE_SIZE {
+            webhook
+                .register(EventKind::ProposerSlashingImported {
+                    slashing: Box::new(proposer_slashing(1)),
+                })
+                .unwrap();
+        }
+        assert!(webhook
+            .register(EventKind::ProposerSlashingImported {
+                slashing: Box::new(proposer_slashing(1)),
+            })
+            .is_err());
+    }
+
+    /// Serve one request with each of `statuses` in turn, sending the body of each to the
+    /// returned channel.
+    fn serve(statuses: Vec<u16>) -> (Url, std_mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let (tx, rx) = std_mpsc::channel();
+
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                tx.send(String::from_utf8(body).unwrap()).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn send(url: Url) {
+        let env = EnvironmentBuilder::minimal()
+            .multi_threaded_tokio_runtime()
+            .unwrap()
+            .null_logger()
+            .unwrap()
+            .build()
+            .unwrap();
+        let notification = SlashingNotification::<E>::ProposerSlashing {
+            validator_indices: vec![1],
+            slashing: proposer_slashing(1),
+        };
+
+        env.runtime().block_on(
+            async {
+                send_notification(
+                    &Client::new(),
+                    url,
+                    &notification,
+                    Duration::from_millis(10),
+                    &null_logger().unwrap(),
+                )
+                .await
+            }
+            .compat(),
+        );
+    }
+
+    #[test]
+    fn retries_until_sent() {
+        let (url, rx) = serve(vec![500, 503, 200]);
+        send(url);
+
+        let bodies = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(bodies.len(), 3);
+        let body: serde_json::Value = serde_json::from_str(&bodies[2]).unwrap();
+        assert_eq!(body["kind"], "proposer_slashing");
+        assert_eq!(body["validator_indices"], serde_json::json!([1]));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let (url, rx) = serve(vec![500; MAX_ATTEMPTS as usize]);
+        send(url);
+
+        assert_eq!(rx.try_iter().count(), MAX_ATTEMPTS as usize);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::metrics;
 use crate::BeaconSnapshot;
 use std::cmp;
 use types::{Epoch, EthSpec, Hash256};
@@ -56,6 +57,7 @@ impl<T: EthSpec> SnapshotCache<T> {
                 .map(|(i, _slot)| i);
 
             if let Some(i) = insert_at {
+                metrics::inc_counter(&metrics::SNAPSHOT_CACHE_EVICTIONS);
                 self.snapshots[i] = snapshot;
             }
         }
@@ -63,19 +65,27 @@ impl<T: EthSpec> SnapshotCache<T> {
 
     /// If there is a snapshot with `block_root`, remove and return it.
     pub fn try_remove(&mut self, block_root: Hash256) -> Option<BeaconSnapshot<T>> {
-        self.snapshots
+        let opt = self
+            .snapshots
             .iter()
             .position(|snapshot| snapshot.beacon_block_root == block_root)
-            .map(|i| self.snapshots.remove(i))
+            .map(|i| self.snapshots.remove(i));
+
+        inc_hit_or_miss_counter(opt.is_some());
+        opt
     }
 
     /// If there is a snapshot with `block_root`, clone it (with only the committee caches) and
     /// return the clone.
     pub fn get_cloned(&self, block_root: Hash256) -> Option<BeaconSnapshot<T>> {
-        self.snapshots
+        let opt = self
+            .snapshots
             .iter()
             .find(|snapshot| snapshot.beacon_block_root == block_root)
-            .map(|snapshot| snapshot.clone_with_only_committee_caches())
+            .map(|snapshot| snapshot.clone_with_only_committee_caches());
+
+        inc_hit_or_miss_counter(opt.is_some());
+        opt
     }
 
     /// Removes all snapshots from the queue that are less than or equal to the finalized epoch.
@@ -94,6 +104,14 @@ impl<T: EthSpec> SnapshotCache<T> {
     }
 }
 
+fn inc_hit_or_miss_counter(hit: bool) {
+    if hit {
+        metrics::inc_counter(&metrics::SNAPSHOT_CACHE_HITS);
+    } else {
+        metrics::inc_counter(&metrics::SNAPSHOT_CACHE_MISSES);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
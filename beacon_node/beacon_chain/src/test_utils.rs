@@ -17,6 +17,7 @@ use rand::rngs::StdRng;
 use rand::Rng;
 use rand_core::SeedableRng;
 use rayon::prelude::*;
+use slasher::Slasher;
 use slog::Logger;
 use slot_clock::TestingSlotClock;
 use state_processing::per_slot_processing;
@@ -24,7 +25,9 @@ use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
-use store::{config::StoreConfig, BlockReplay, HotColdDB, ItemStore, LevelDB, MemoryStore};
+use store::{
+    config::StoreConfig, BeaconNodeBackend, BlockReplay, HotColdDB, ItemStore, MemoryStore,
+};
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
 use types::{
@@ -51,7 +54,7 @@ pub type BaseHarnessType<TEthSpec, THotStore, TColdStore> = Witness<
     TColdStore,
 >;
 
-pub type DiskHarnessType<E> = BaseHarnessType<E, LevelDB<E>, LevelDB<E>>;
+pub type DiskHarnessType<E> = BaseHarnessType<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>;
 pub type EphemeralHarnessType<E> = BaseHarnessType<E, MemoryStore<E>, MemoryStore<E>>;
 
 pub type AddBlocksResult<E> = (
@@ -223,7 +226,7 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new_with_disk_store(
         eth_spec_instance: E,
-        store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+        store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
         validator_keypairs: Vec<Keypair>,
     ) -> Self {
         let data_dir = tempdir().expect("should create temporary data_dir");
@@ -268,16 +271,37 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn resume_from_disk_store(
         eth_spec_instance: E,
-        store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+        store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
         validator_keypairs: Vec<Keypair>,
         data_dir: TempDir,
+    ) -> Self {
+        Self::resume_from_disk_store_with_slasher(
+            eth_spec_instance,
+            store,
+            validator_keypairs,
+            data_dir,
+            None,
+        )
+    }
+
+    /// Resume the chain in `store`, with an optional `slasher`.
+    pub fn resume_from_disk_store_with_slasher(
+        eth_spec_instance: E,
+        store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+        validator_keypairs: Vec<Keypair>,
+        data_dir: TempDir,
+        slasher: Option<Arc<Slasher<E>>>,
     ) -> Self {
         let spec = E::default_spec();
 
         let log = test_logger();
         let (shutdown_tx, shutdown_receiver) = futures::channel::mpsc::channel(1);
 
-        let chain = BeaconChainBuilder::new(eth_spec_instance)
+        let mut builder = BeaconChainBuilder::new(eth_spec_instance);
+        if let Some(slasher) = slasher {
+            builder = builder.slasher(slasher);
+        }
+        let chain = builder
             .logger(log.clone())
             .custom_spec(spec)
             .import_max_skip_slots(None)
@@ -1,11 +1,14 @@
 use crate::errors::BeaconChainError;
-use ssz::{Decode, DecodeError, Encode};
+use rayon::prelude::*;
+use slog::{info, Logger};
+use ssz::{Decode, Encode};
 use std::collections::HashMap;
 use std::convert::TryInto;
-use std::fs::{File, OpenOptions};
-use std::io::{self, Read, Write};
-use std::path::Path;
-use types::{BeaconState, EthSpec, PublicKey, PublicKeyBytes, Validator};
+use store::{DBColumn, Error as StoreError, HotColdDB, ItemStore, KeyValueStoreOp, StoreItem};
+use types::{BeaconState, EthSpec, Hash256, PublicKey, PublicKeyBytes, Validator};
+
+/// The number of public keys to decompress between progress reports.
+const DECOMPRESSION_PROGRESS_INTERVAL: usize = 100_000;
 
 /// Provides a mapping of `validator_index -> validator_publickey`.
 ///
@@ -16,87 +19,163 @@ use types::{BeaconState, EthSpec, PublicKey, PublicKeyBytes, Validator};
 ///    keys in compressed form and they are needed in decompressed form for signature verification.
 ///    Decompression is expensive when many keys are involved.
 ///
-/// The cache has a `persistence_file` that it uses to maintain a persistent, on-disk
-/// copy of itself. This allows it to be restored between process invocations.
+/// The cache keeps a persistent copy of itself in the `PubkeyCache` column of the hot database.
+/// This allows it to be restored between process invocations.
 pub struct ValidatorPubkeyCache {
     pubkeys: Vec<PublicKey>,
     indices: HashMap<PublicKeyBytes, usize>,
-    persitence_file: ValidatorPubkeyCacheFile,
 }
 
 impl ValidatorPubkeyCache {
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, BeaconChainError> {
-        ValidatorPubkeyCacheFile::open(&path)
-            .and_then(ValidatorPubkeyCacheFile::into_cache)
-            .map_err(Into::into)
-    }
-
     /// Create a new public key cache using the keys in `state.validators`.
     ///
-    /// Also creates a new persistence file, returning an error if there is already a file at
-    /// `persistence_path`.
-    pub fn new<T: EthSpec, P: AsRef<Path>>(
-        state: &BeaconState<T>,
-        persistence_path: P,
+    /// Also replaces any copy of the cache in `store`, unless it is read-only, in which case the
+    /// cache is only held in memory.
+    pub fn new<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+        state: &BeaconState<E>,
+        store: &HotColdDB<E, Hot, Cold>,
+        log: &Logger,
     ) -> Result<Self, BeaconChainError> {
         let mut cache = Self {
-            persitence_file: ValidatorPubkeyCacheFile::create(persistence_path)?,
             pubkeys: vec![],
             indices: HashMap::new(),
         };
-
-        cache.import_new_pubkeys(state)?;
+        let mut new_pubkeys = cache.import(&state.validators, log)?;
+
+        if !store.is_read_only() {
+            let mut ops = store
+                .hot_db
+                .iter_column_keys(DBColumn::PubkeyCache)
+                .map(|key| key.map(|key| KeyValueStoreOp::DeleteKey(pubkey_cache_key(key))))
+                .collect::<Result<Vec<_>, StoreError>>()?;
+            ops.append(&mut new_pubkeys.ops);
+            store.hot_db.do_atomically(ops)?;
+        }
+        cache.extend(new_pubkeys);
 
         Ok(cache)
     }
 
+    /// Load the cache from `store`, returning an error if the stored copy is corrupt.
+    ///
+    /// A database without a stored copy yields an empty cache.
+    pub fn load_from_store<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+        store: &HotColdDB<E, Hot, Cold>,
+        log: &Logger,
+    ) -> Result<Self, BeaconChainError> {
+        let mut compressed = vec![];
+        let mut indices = HashMap::new();
+
+        while let Some(DatabasePubkey(pubkey)) =
+            store.get_item(&Hash256::from_low_u64_be(compressed.len() as u64))?
+        {
+            let index = compressed.len();
+            if indices.insert(pubkey.clone(), index).is_some() {
+                return Err(BeaconChainError::DuplicateValidatorPublicKey);
+            }
+            compressed.push(pubkey);
+        }
+
+        // Keys beyond a gap can't be reached, and indicate that the stored copy is corrupt.
+        let num_stored = store.hot_db.iter_column_keys(DBColumn::PubkeyCache).count();
+        if num_stored != compressed.len() {
+            return Err(BeaconChainError::ValidatorPubkeyCacheError(format!(
+                "{} keys are stored but only {} are contiguous",
+                num_stored,
+                compressed.len()
+            )));
+        }
+
+        let pubkeys = decompress_pubkeys(&compressed.iter().collect::<Vec<_>>(), log)?;
+
+        Ok(Self { pubkeys, indices })
+    }
+
+    /// Return `true` if every key in `self` belongs to the validator with the same index in
+    /// `state`.
+    pub fn is_consistent_with<E: EthSpec>(&self, state: &BeaconState<E>) -> bool {
+        state
+            .validators
+            .iter()
+            .take(self.len())
+            .enumerate()
+            .all(|(i, validator)| self.get_index(&validator.pubkey) == Some(i))
+    }
+
     /// Scan the given `state` and add any new validator public keys.
     ///
-    /// Does not delete any keys from `self` if they don't appear in `state`.
-    pub fn import_new_pubkeys<T: EthSpec>(
+    /// Does not delete any keys from `self` if they don't appear in `state`. The new keys are not
+    /// persisted if `store` is read-only.
+    pub fn import_new_pubkeys<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         &mut self,
-        state: &BeaconState<T>,
+        state: &BeaconState<E>,
+        store: &HotColdDB<E, Hot, Cold>,
+        log: &Logger,
     ) -> Result<(), BeaconChainError> {
         if state.validators.len() > self.pubkeys.len() {
-            self.import(&state.validators[self.pubkeys.len()..])
-        } else {
-            Ok(())
+            let mut new_pubkeys = self.import(&state.validators[self.pubkeys.len()..], log)?;
+            if !store.is_read_only() {
+                store
+                    .hot_db
+                    .do_atomically(std::mem::take(&mut new_pubkeys.ops))?;
+            }
+            self.extend(new_pubkeys);
         }
+        Ok(())
     }
 
-    /// Adds zero or more validators to `self`.
-    fn import(&mut self, validators: &[Validator]) -> Result<(), BeaconChainError> {
-        self.pubkeys.reserve(validators.len());
-        self.indices.reserve(validators.len());
-
-        for v in validators.iter() {
-            let i = self.pubkeys.len();
-
-            if self.indices.contains_key(&v.pubkey) {
+    /// Validates and decompresses the keys of zero or more validators which follow those in
+    /// `self`, returning them along with the operations to persist them.
+    ///
+    /// Nothing is added to `self`; the caller must persist the operations and only then `extend`
+    /// `self`, so that `self` never holds keys which haven't been persisted.
+    fn import(
+        &self,
+        validators: &[Validator],
+        log: &Logger,
+    ) -> Result<NewPubkeys, BeaconChainError> {
+        let mut ops = Vec::with_capacity(validators.len());
+        let mut indices = HashMap::with_capacity(validators.len());
+
+        for (offset, v) in validators.iter().enumerate() {
+            let i = self.pubkeys.len() + offset;
+
+            if self.indices.contains_key(&v.pubkey) || indices.insert(v.pubkey.clone(), i).is_some()
+            {
                 return Err(BeaconChainError::DuplicateValidatorPublicKey);
             }
 
-            // The item is written to disk (the persistence file) _before_ it is written into
-            // the local struct.
-            //
-            // This means that a pubkey cache read from disk will always be equivalent to or
-            // _later than_ the cache that was running in the previous instance of Lighthouse.
-            //
-            // The motivation behind this ordering is that we do not want to have states that
-            // reference a pubkey that is not in our cache. However, it's fine to have pubkeys
-            // that are never referenced in a state.
-            self.persitence_file.append(i, &v.pubkey)?;
-
-            self.pubkeys.push(
-                (&v.pubkey)
-                    .try_into()
-                    .map_err(BeaconChainError::InvalidValidatorPubkeyBytes)?,
+            ops.push(
+                DatabasePubkey(v.pubkey.clone()).as_kv_store_op(Hash256::from_low_u64_be(i as u64)),
             );
-
-            self.indices.insert(v.pubkey.clone(), i);
         }
 
-        Ok(())
+        let pubkeys = decompress_pubkeys(
+            &validators.iter().map(|v| &v.pubkey).collect::<Vec<_>>(),
+            log,
+        )?;
+
+        Ok(NewPubkeys {
+            ops,
+            pubkeys,
+            indices,
+        })
+    }
+
+    /// Adds keys returned by `import` to `self`, once they have been persisted.
+    fn extend(&mut self, new_pubkeys: NewPubkeys) {
+        // The keys are written to the database _before_ being added to `self`, and the caller
+        // holds its lock on `self` throughout, so no other thread can use the new keys before
+        // they are persisted.
+        //
+        // This means that a pubkey cache read from disk will always be equivalent to or _later
+        // than_ the cache that was running in the previous instance of Lighthouse.
+        //
+        // The motivation behind this ordering is that we do not want to have states that
+        // reference a pubkey that is not in our cache. However, it's fine to have pubkeys
+        // that are never referenced in a state.
+        self.pubkeys.extend(new_pubkeys.pubkeys);
+        self.indices.extend(new_pubkeys.indices);
     }
 
     /// Get the public key for a validator with index `i`.
@@ -115,122 +194,84 @@ impl ValidatorPubkeyCache {
     }
 }
 
-/// Allows for maintaining an on-disk copy of the `ValidatorPubkeyCache`. The file is raw SSZ bytes
-/// (not ASCII encoded).
-///
-/// ## Writes
-///
-/// Each entry is simply appended to the file.
-///
-/// ## Reads
-///
-/// The whole file is parsed as an SSZ "variable list" of objects.
-///
-/// This parsing method is possible because the items in the list are fixed-length SSZ objects.
-struct ValidatorPubkeyCacheFile(File);
-
-#[derive(Debug)]
-enum Error {
-    Io(io::Error),
-    Ssz(DecodeError),
-    PubkeyDecode(bls::Error),
-    /// The file read from disk does not have a contiguous list of validator public keys. The file
-    /// has become corrupted.
-    InconsistentIndex {
-        expected: Option<usize>,
-        found: usize,
-    },
+/// Keys which have been validated for a `ValidatorPubkeyCache` but not yet added to it.
+struct NewPubkeys {
+    /// The operations which persist the keys.
+    ops: Vec<KeyValueStoreOp>,
+    pubkeys: Vec<PublicKey>,
+    indices: HashMap<PublicKeyBytes, usize>,
 }
 
-impl From<Error> for BeaconChainError {
-    fn from(e: Error) -> BeaconChainError {
-        BeaconChainError::ValidatorPubkeyCacheFileError(format!("{:?}", e))
+/// Decompresses `pubkeys` across all cores, preserving their order.
+///
+/// Decompressing a large validator set takes minutes on a single core, so progress is logged
+/// every `DECOMPRESSION_PROGRESS_INTERVAL` keys.
+fn decompress_pubkeys(
+    pubkeys: &[&PublicKeyBytes],
+    log: &Logger,
+) -> Result<Vec<PublicKey>, BeaconChainError> {
+    let mut decompressed = Vec::with_capacity(pubkeys.len());
+
+    for chunk in pubkeys.chunks(DECOMPRESSION_PROGRESS_INTERVAL) {
+        decompressed.extend(
+            chunk
+                .par_iter()
+                .map(|pubkey| {
+                    (*pubkey)
+                        .try_into()
+                        .map_err(BeaconChainError::InvalidValidatorPubkeyBytes)
+                })
+                .collect::<Result<Vec<PublicKey>, _>>()?,
+        );
+
+        if pubkeys.len() > DECOMPRESSION_PROGRESS_INTERVAL {
+            info!(
+                log,
+                "Decompressing validator public keys";
+                "decompressed" => decompressed.len(),
+                "total" => pubkeys.len(),
+            );
+        }
     }
+
+    Ok(decompressed)
 }
 
-impl ValidatorPubkeyCacheFile {
-    /// Creates a file for reading and writing.
-    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .map(Self)
-            .map_err(Error::Io)
-    }
+/// The compressed public key of a validator, stored under the validator's index.
+struct DatabasePubkey(PublicKeyBytes);
 
-    /// Opens an existing file for reading and writing.
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(false)
-            .append(true)
-            .open(path)
-            .map(Self)
-            .map_err(Error::Io)
+impl StoreItem for DatabasePubkey {
+    fn db_column() -> DBColumn {
+        DBColumn::PubkeyCache
     }
 
-    /// Append a public key to file.
-    ///
-    /// The provided `index` should each be one greater than the previous and start at 0.
-    /// Otherwise, the file will become corrupted and unable to be converted into a cache .
-    pub fn append(&mut self, index: usize, pubkey: &PublicKeyBytes) -> Result<(), Error> {
-        append_to_file(&mut self.0, index, pubkey)
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
     }
 
-    /// Creates a `ValidatorPubkeyCache` by reading and parsing the underlying file.
-    pub fn into_cache(mut self) -> Result<ValidatorPubkeyCache, Error> {
-        let mut bytes = vec![];
-        self.0.read_to_end(&mut bytes).map_err(Error::Io)?;
-
-        let list: Vec<(usize, PublicKeyBytes)> = Vec::from_ssz_bytes(&bytes).map_err(Error::Ssz)?;
-
-        let mut last = None;
-        let mut pubkeys = Vec::with_capacity(list.len());
-        let mut indices = HashMap::new();
-
-        for (index, pubkey) in list {
-            let expected = last.map(|n| n + 1);
-            if expected.map_or(true, |expected| index == expected) {
-                last = Some(index);
-                pubkeys.push((&pubkey).try_into().map_err(Error::PubkeyDecode)?);
-                indices.insert(pubkey, index);
-            } else {
-                return Err(Error::InconsistentIndex {
-                    expected,
-                    found: index,
-                });
-            }
-        }
-
-        Ok(ValidatorPubkeyCache {
-            pubkeys,
-            indices,
-            persitence_file: self,
-        })
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self(PublicKeyBytes::from_ssz_bytes(bytes)?))
     }
 }
 
-fn append_to_file(file: &mut File, index: usize, pubkey: &PublicKeyBytes) -> Result<(), Error> {
-    let mut line = Vec::with_capacity(index.ssz_bytes_len() + pubkey.ssz_bytes_len());
-
-    index.ssz_append(&mut line);
-    pubkey.ssz_append(&mut line);
-
-    file.write_all(&line).map_err(Error::Io)
+/// The database key of a cache entry, given its key within the column.
+fn pubkey_cache_key(key: Hash256) -> Vec<u8> {
+    store::get_key_for_col(DBColumn::PubkeyCache.into(), key.as_bytes())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use tempfile::tempdir;
+    use crate::test_utils::test_logger;
+    use store::{MemoryStore, StoreConfig};
     use types::{
         test_utils::{generate_deterministic_keypair, TestingBeaconStateBuilder},
         BeaconState, EthSpec, Keypair, MainnetEthSpec,
     };
 
+    type Store =
+        HotColdDB<MainnetEthSpec, MemoryStore<MainnetEthSpec>, MemoryStore<MainnetEthSpec>>;
+
     fn get_state(validator_count: usize) -> (BeaconState<MainnetEthSpec>, Vec<Keypair>) {
         let spec = MainnetEthSpec::default_spec();
         let builder =
@@ -238,6 +279,15 @@ mod test {
         builder.build()
     }
 
+    fn get_store() -> Store {
+        HotColdDB::open_ephemeral(
+            StoreConfig::default(),
+            MainnetEthSpec::default_spec(),
+            test_logger(),
+        )
+        .expect("should open store")
+    }
+
     fn check_cache_get(cache: &ValidatorPubkeyCache, keypairs: &[Keypair]) {
         let validator_count = keypairs.len();
 
@@ -268,90 +318,114 @@ mod test {
     #[test]
     fn basic_operation() {
         let (state, keypairs) = get_state(8);
+        let store = get_store();
+        let log = test_logger();
 
-        let dir = tempdir().expect("should create tempdir");
-        let path = dir.path().join("cache.ssz");
-
-        let mut cache = ValidatorPubkeyCache::new(&state, path).expect("should create cache");
+        let mut cache =
+            ValidatorPubkeyCache::new(&state, &store, &log).expect("should create cache");
 
         check_cache_get(&cache, &keypairs[..]);
 
         // Try adding a state with the same number of keypairs.
         let (state, keypairs) = get_state(8);
         cache
-            .import_new_pubkeys(&state)
+            .import_new_pubkeys(&state, &store, &log)
             .expect("should import pubkeys");
         check_cache_get(&cache, &keypairs[..]);
 
         // Try adding a state with less keypairs.
         let (state, _) = get_state(1);
         cache
-            .import_new_pubkeys(&state)
+            .import_new_pubkeys(&state, &store, &log)
             .expect("should import pubkeys");
         check_cache_get(&cache, &keypairs[..]);
 
         // Try adding a state with more keypairs.
         let (state, keypairs) = get_state(12);
         cache
-            .import_new_pubkeys(&state)
+            .import_new_pubkeys(&state, &store, &log)
             .expect("should import pubkeys");
         check_cache_get(&cache, &keypairs[..]);
+        assert!(cache.is_consistent_with(&state));
     }
 
     #[test]
     fn persistence() {
         let (state, keypairs) = get_state(8);
-
-        let dir = tempdir().expect("should create tempdir");
-        let path = dir.path().join("cache.ssz");
+        let store = get_store();
+        let log = test_logger();
 
         // Create a new cache.
-        let cache = ValidatorPubkeyCache::new(&state, &path).expect("should create cache");
+        let cache = ValidatorPubkeyCache::new(&state, &store, &log).expect("should create cache");
         check_cache_get(&cache, &keypairs[..]);
         drop(cache);
 
-        // Re-init the cache from the file.
-        let mut cache = ValidatorPubkeyCache::load_from_file(&path).expect("should open cache");
+        // Re-init the cache from the store.
+        let mut cache =
+            ValidatorPubkeyCache::load_from_store(&store, &log).expect("should load cache");
         check_cache_get(&cache, &keypairs[..]);
 
         // Add some more keypairs.
         let (state, keypairs) = get_state(12);
         cache
-            .import_new_pubkeys(&state)
+            .import_new_pubkeys(&state, &store, &log)
             .expect("should import pubkeys");
         check_cache_get(&cache, &keypairs[..]);
         drop(cache);
 
-        // Re-init the cache from the file.
-        let cache = ValidatorPubkeyCache::load_from_file(&path).expect("should open cache");
+        // Re-init the cache from the store.
+        let cache = ValidatorPubkeyCache::load_from_store(&store, &log).expect("should load cache");
+        check_cache_get(&cache, &keypairs[..]);
+
+        // Re-creating the cache from a smaller state replaces the stored copy.
+        let (state, keypairs) = get_state(4);
+        ValidatorPubkeyCache::new(&state, &store, &log).expect("should create cache");
+        let cache = ValidatorPubkeyCache::load_from_store(&store, &log).expect("should load cache");
         check_cache_get(&cache, &keypairs[..]);
     }
 
     #[test]
-    fn invalid_persisted_file() {
-        let dir = tempdir().expect("should create tempdir");
-        let path = dir.path().join("cache.ssz");
-        let pubkey = generate_deterministic_keypair(0).pk.into();
-
-        let mut file = File::create(&path).expect("should create file");
-        append_to_file(&mut file, 0, &pubkey).expect("should write to file");
-        drop(file);
-
-        let cache = ValidatorPubkeyCache::load_from_file(&path).expect("should open cache");
-        drop(cache);
+    fn invalid_persisted_cache() {
+        let store = get_store();
+        let log = test_logger();
+        let pubkey: PublicKeyBytes = generate_deterministic_keypair(0).pk.into();
+        let put = |index: u64| {
+            store
+                .put_item(
+                    &Hash256::from_low_u64_be(index),
+                    &DatabasePubkey(pubkey.clone()),
+                )
+                .expect("should store pubkey")
+        };
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(&path)
-            .expect("should open file");
+        put(0);
+        ValidatorPubkeyCache::load_from_store(&store, &log).expect("should load cache");
 
-        append_to_file(&mut file, 42, &pubkey).expect("should write bad data to file");
-        drop(file);
+        // A gap in the indices.
+        put(42);
+        assert!(
+            ValidatorPubkeyCache::load_from_store(&store, &log).is_err(),
+            "should not load cache with a gap"
+        );
 
+        // A duplicate key.
+        put(1);
         assert!(
-            ValidatorPubkeyCache::load_from_file(&path).is_err(),
-            "should not parse invalid file"
+            ValidatorPubkeyCache::load_from_store(&store, &log).is_err(),
+            "should not load cache with a duplicate key"
         );
     }
+
+    #[test]
+    fn inconsistent_with_state() {
+        let (state, _) = get_state(8);
+        let store = get_store();
+        let log = test_logger();
+        let cache = ValidatorPubkeyCache::new(&state, &store, &log).expect("should create cache");
+
+        let (mut other_state, _) = get_state(8);
+        other_state.validators.swap(0, 1);
+        assert!(cache.is_consistent_with(&state));
+        assert!(!cache.is_consistent_with(&other_state));
+    }
 }
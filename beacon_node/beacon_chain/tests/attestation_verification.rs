@@ -942,3 +942,99 @@ fn attestation_that_skips_epochs() {
         .verify_unaggregated_attestation_for_gossip(attestation, Some(subnet_id))
         .expect("should gossip verify attestation that skips slots");
 }
+
+/// Ensures that verifying unaggregated attestations in a batch gives each attestation the same
+/// result as verifying it individually, even when one of them has an invalid signature.
+#[test]
+fn batch_unaggregated_gossip_verification() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let (valid_attestation, expected_validator_index, _, validator_sk, subnet_id) =
+        get_valid_unaggregated_attestation(&harness.chain);
+
+    let bad_signature_attestation = {
+        let mut a = valid_attestation.clone();
+
+        let mut agg_sig = AggregateSignature::infinity();
+        agg_sig.add_assign(&validator_sk.sign(Hash256::from_low_u64_be(42)));
+        a.signature = agg_sig;
+
+        a
+    };
+    let id: u64 = subnet_id.into();
+    let invalid_subnet_id = SubnetId::new(id + 1);
+
+    let results = harness
+        .chain
+        .batch_verify_unaggregated_attestations_for_gossip(vec![
+            (bad_signature_attestation, Some(subnet_id)),
+            (valid_attestation.clone(), Some(invalid_subnet_id)),
+            (valid_attestation.clone(), Some(subnet_id)),
+        ])
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[0], Err(AttnError::InvalidSignature)));
+    assert!(matches!(results[1], Err(AttnError::InvalidSubnetId { .. })));
+    assert!(results[2].is_ok(), "valid attestation should verify");
+
+    // The valid attestation should have been observed.
+    assert!(matches!(
+        harness
+            .chain
+            .verify_unaggregated_attestation_for_gossip(valid_attestation, Some(subnet_id)),
+        Err(AttnError::PriorAttestationKnown { validator_index, .. })
+        if validator_index == expected_validator_index as u64
+    ));
+}
+
+/// Ensures that verifying aggregates in a batch gives each aggregate the same result as verifying
+/// it individually, even when one of them has an invalid signature.
+#[test]
+fn batch_aggregated_gossip_verification() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let (valid_attestation, _, _, _, _) = get_valid_unaggregated_attestation(&harness.chain);
+    let (valid_aggregate, _, aggregator_sk) =
+        get_valid_aggregated_attestation(&harness.chain, valid_attestation);
+
+    let bad_signature_aggregate = {
+        let mut a = valid_aggregate.clone();
+        a.signature = aggregator_sk.sign(Hash256::from_low_u64_be(42));
+        a
+    };
+
+    let results = harness
+        .chain
+        .batch_verify_aggregated_attestations_for_gossip(vec![
+            bad_signature_aggregate,
+            valid_aggregate.clone(),
+        ])
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[0], Err(AttnError::InvalidSignature)));
+    assert!(results[1].is_ok(), "valid aggregate should verify");
+
+    // The valid aggregate should have been observed.
+    assert!(matches!(
+        harness
+            .chain
+            .verify_aggregated_attestation_for_gossip(valid_aggregate),
+        Err(AttnError::AttestationAlreadyKnown(_))
+    ));
+}
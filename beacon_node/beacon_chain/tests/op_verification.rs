@@ -5,13 +5,21 @@
 #[macro_use]
 extern crate lazy_static;
 
+use beacon_chain::builder::{BeaconChainBuilder, Witness};
+use beacon_chain::eth1_chain::CachingEth1Backend;
+use beacon_chain::events::{EventHandler, EventKind};
 use beacon_chain::observed_operations::ObservationOutcome;
+use beacon_chain::slot_clock::TestingSlotClock;
 use beacon_chain::test_utils::{
-    AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType, MigratorConfig,
+    HARNESS_GENESIS_TIME, HARNESS_SLOT_TIME,
 };
+use beacon_chain::{BeaconChain, ChainConfig};
+use genesis::interop_genesis_state;
+use slog::{o, Drain, Level, Logger, Never, OwnedKVList, Record};
 use sloggers::{null::NullLoggerBuilder, Build};
-use std::sync::Arc;
-use store::{LevelDB, StoreConfig};
+use std::sync::{Arc, Mutex};
+use store::{BeaconNodeBackend, MemoryStore, StoreConfig};
 use tempfile::{tempdir, TempDir};
 use types::test_utils::{
     AttesterSlashingTestTask, ProposerSlashingTestTask, TestingAttesterSlashingBuilder,
@@ -29,7 +37,15 @@ lazy_static! {
 
 type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
-type HotColdDB = store::HotColdDB<E, LevelDB<E>, LevelDB<E>>;
+type HotColdDB = store::HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>;
+type MonitoringChainType = Witness<
+    TestingSlotClock,
+    CachingEth1Backend<E>,
+    E,
+    RecordingEventHandler,
+    MemoryStore<E>,
+    MemoryStore<E>,
+>;
 
 fn get_store(db_path: &TempDir) -> Arc<HotColdDB> {
     let spec = E::default_spec();
@@ -273,3 +289,132 @@ fn attester_slashing() {
         ObservationOutcome::AlreadyKnown
     ));
 }
+
+/// Records the events of a chain.
+struct RecordingEventHandler(Arc<Mutex<Vec<EventKind<E>>>>);
+
+impl EventHandler<E> for RecordingEventHandler {
+    fn register(&self, kind: EventKind<E>) -> Result<(), String> {
+        self.0.lock().unwrap().push(kind);
+        Ok(())
+    }
+}
+
+/// Records the messages of critical logs.
+struct CritRecorder(Arc<Mutex<Vec<String>>>);
+
+impl Drain for CritRecorder {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), Never> {
+        if record.level() == Level::Critical {
+            self.0.lock().unwrap().push(record.msg().to_string());
+        }
+        Ok(())
+    }
+}
+
+fn monitored_validator_slashings() -> f64 {
+    lighthouse_metrics::gather()
+        .iter()
+        .find(|family| family.get_name() == "beacon_monitored_validator_slashings_total")
+        .map_or(0.0, |family| {
+            family.get_metric()[0].get_counter().get_value()
+        })
+}
+
+#[test]
+fn monitored_validator_slashed_in_block() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store, VALIDATOR_COUNT);
+    let spec = E::default_spec();
+
+    let monitored_index = 3;
+    let events = Arc::new(Mutex::new(vec![]));
+    let crit_logs = Arc::new(Mutex::new(vec![]));
+
+    // A second chain from the same genesis, which monitors one validator and imports the blocks
+    // produced by the harness.
+    let log = Logger::root(CritRecorder(crit_logs.clone()), o!());
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let chain: BeaconChain<MonitoringChainType> = BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(log.clone())
+        .custom_spec(spec.clone())
+        .store(Arc::new(
+            store::HotColdDB::open_ephemeral(StoreConfig::default(), spec.clone(), log).unwrap(),
+        ))
+        .store_migrator_config(MigratorConfig::default().blocking())
+        .data_dir(db_path.path().join("monitoring_chain"))
+        .genesis_state(
+            interop_genesis_state::<E>(&KEYPAIRS[..], HARNESS_GENESIS_TIME, &spec).unwrap(),
+        )
+        .unwrap()
+        .dummy_eth1_backend()
+        .unwrap()
+        .event_handler(RecordingEventHandler(events.clone()))
+        .testing_slot_clock(HARNESS_SLOT_TIME)
+        .unwrap()
+        .shutdown_sender(shutdown_tx)
+        .chain_config(ChainConfig {
+            monitored_validators: vec![PublicKeyBytes::from(&KEYPAIRS[monitored_index].pk)],
+            ..ChainConfig::default()
+        })
+        .build()
+        .unwrap();
+
+    // Include a slashing of the monitored validator and another in the next block.
+    let slashing = harness.make_attester_slashing(vec![monitored_index as u64, 5]);
+    match harness
+        .chain
+        .verify_attester_slashing_for_gossip(slashing)
+        .unwrap()
+    {
+        ObservationOutcome::New(verified) => {
+            harness.chain.import_attester_slashing(verified).unwrap()
+        }
+        ObservationOutcome::AlreadyKnown => panic!("slashing should be new"),
+    }
+    let slot = harness.get_current_slot();
+    let (block, _) = harness.make_block(harness.get_current_state(), slot);
+    assert_eq!(block.message.body.attester_slashings.len(), 1);
+
+    let slashings_before = monitored_validator_slashings();
+    chain.slot_clock.set_slot(slot.as_u64());
+    chain.process_block(block).unwrap();
+
+    let slashed_events = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            EventKind::MonitoredValidatorSlashed {
+                validator_index,
+                pubkey,
+                kind,
+                source,
+            } => Some((
+                *validator_index,
+                pubkey.clone(),
+                kind.clone(),
+                source.clone(),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        slashed_events,
+        vec![(
+            monitored_index as u64,
+            PublicKeyBytes::from(&KEYPAIRS[monitored_index].pk),
+            "attester_slashing".to_string(),
+            "block".to_string(),
+        )]
+    );
+    assert_eq!(
+        *crit_logs.lock().unwrap(),
+        vec!["Monitored validator slashed".to_string()]
+    );
+    assert_eq!(monitored_validator_slashings() - slashings_before, 1.0);
+}
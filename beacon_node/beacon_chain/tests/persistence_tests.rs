@@ -4,14 +4,22 @@
 extern crate lazy_static;
 
 use beacon_chain::{
-    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy},
-    BeaconChain, BeaconChainTypes,
+    builder::BeaconChainBuilder,
+    migrate::MigratorConfig,
+    test_utils::{test_logger, AttestationStrategy, BeaconChainHarness, BlockStrategy},
+    BeaconChain, BeaconChainError, BeaconChainTypes, ChainConfig, StateSkipConfig,
 };
+use slasher::{Config as SlasherConfig, Slasher};
 use sloggers::{null::NullLoggerBuilder, Build};
+use state_processing::common::get_indexed_attestation;
 use std::sync::Arc;
-use store::{HotColdDB, LevelDB, StoreConfig};
+use std::time::Duration;
+use store::{BeaconNodeBackend, DBColumn, HotColdDB, KeyValueStore, StoreConfig};
 use tempfile::{tempdir, TempDir};
-use types::{EthSpec, Keypair, MinimalEthSpec};
+use types::{
+    Checkpoint, Epoch, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, SignedBeaconBlock,
+    Slot,
+};
 
 type E = MinimalEthSpec;
 
@@ -23,7 +31,7 @@ lazy_static! {
     static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
 }
 
-fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     let spec = E::default_spec();
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
@@ -137,6 +145,237 @@ fn finalizes_after_resuming_from_db() {
     );
 }
 
+#[test]
+fn failed_block_write_reverts_fork_choice() {
+    let harness = BeaconChainHarness::new(MinimalEthSpec, KEYPAIRS[..].to_vec());
+
+    harness.advance_slot();
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness
+        .chain
+        .persist_head_fork_choice_and_op_pool()
+        .expect("should persist the head, fork choice and op pool");
+
+    let head = harness.chain.head().expect("should read head");
+    let slot = head.beacon_block.slot() + 1;
+    harness.set_current_slot(slot);
+    let (block, _) = harness.make_block(head.beacon_state, slot);
+    let block_root = block.canonical_root();
+
+    harness.chain.store.hot_db.set_fail_writes(true);
+    assert!(
+        harness.process_block(slot, block.clone()).is_err(),
+        "the block should not import whilst writes fail"
+    );
+    harness.chain.store.hot_db.set_fail_writes(false);
+
+    assert!(
+        !harness.chain.fork_choice.read().contains_block(&block_root),
+        "fork choice should not contain the unwritten block"
+    );
+    assert!(
+        !harness
+            .chain
+            .store
+            .item_exists::<SignedBeaconBlock<E>>(&block_root)
+            .unwrap(),
+        "the block should not be stored"
+    );
+
+    harness
+        .process_block(slot, block)
+        .expect("the block should import once writes succeed");
+    assert_eq!(
+        harness.chain.head_info().unwrap().block_root,
+        block_root,
+        "the block should become the head"
+    );
+}
+
+#[test]
+fn resets_fork_choice_after_lost_block_write() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[..].to_vec(),
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = harness.chain.head_info().expect("should read head");
+    let finalized_checkpoint = head_info.finalized_checkpoint;
+    assert!(
+        finalized_checkpoint.epoch > 0,
+        "the chain should have already finalized"
+    );
+    let latest_slot = harness.chain.slot().expect("should have a slot");
+
+    harness
+        .chain
+        .persist_head_fork_choice_and_op_pool()
+        .expect("should persist the head, fork choice and op pool");
+
+    // Simulate a crash in which the write of the head block was lost, but fork choice survived.
+    store
+        .hot_db
+        .key_delete(
+            DBColumn::BeaconBlock.into(),
+            head_info.block_root.as_bytes(),
+        )
+        .expect("should delete the head block");
+
+    let data_dir = harness.data_dir;
+    drop(harness.chain);
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[..].to_vec(),
+        data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness.chain.head_info().unwrap().block_root,
+        finalized_checkpoint.root,
+        "the head should be reset to the finalized block"
+    );
+    assert!(
+        !resumed_harness
+            .chain
+            .heads()
+            .iter()
+            .any(|(block_root, _)| *block_root == head_info.block_root),
+        "the lost block should not be a head"
+    );
+
+    resumed_harness
+        .chain
+        .slot_clock
+        .set_slot(latest_slot.as_u64() + 1);
+    resumed_harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        resumed_harness.chain.head_info().unwrap().slot,
+        latest_slot + MinimalEthSpec::slots_per_epoch(),
+        "the chain should extend from the finalized block"
+    );
+}
+
+/// Resumes a chain from `store` with the weak subjectivity checkpoint `wss_checkpoint`.
+fn resume_with_wss_checkpoint(
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+    wss_checkpoint: Checkpoint,
+) -> Result<(), String> {
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let chain_config = ChainConfig {
+        weak_subjectivity_checkpoint: Some(wss_checkpoint),
+        ..ChainConfig::default()
+    };
+
+    BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(test_logger())
+        .store(store)
+        .store_migrator_config(MigratorConfig::default().blocking())
+        .chain_config(chain_config)
+        .resume_from_db()?
+        .dummy_eth1_backend()?
+        .null_event_handler()
+        .testing_slot_clock(Duration::from_secs(1))?
+        .shutdown_sender(shutdown_tx)
+        .build()
+        .map(|_| ())
+}
+
+#[test]
+fn verifies_wss_checkpoint_on_resume() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[..].to_vec(),
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let finalized_checkpoint = harness.chain.fork_choice.read().finalized_checkpoint();
+    assert!(
+        finalized_checkpoint.epoch > 1,
+        "the chain should have already finalized"
+    );
+    let earlier_epoch = Epoch::new(1);
+    let earlier_checkpoint = Checkpoint {
+        epoch: earlier_epoch,
+        root: harness
+            .chain
+            .block_root_at_slot(earlier_epoch.start_slot(MinimalEthSpec::slots_per_epoch()))
+            .unwrap()
+            .expect("should have a block root at the earlier epoch"),
+    };
+    let later_checkpoint = Checkpoint {
+        epoch: finalized_checkpoint.epoch + 1,
+        root: Hash256::repeat_byte(0xff),
+    };
+
+    harness
+        .chain
+        .persist_head_fork_choice_and_op_pool()
+        .expect("should persist the head, fork choice and op pool");
+    drop(harness);
+
+    for checkpoint in &[finalized_checkpoint, earlier_checkpoint] {
+        assert_eq!(
+            resume_with_wss_checkpoint(store.clone(), *checkpoint),
+            Ok(()),
+            "should resume with a matching checkpoint at epoch {}",
+            checkpoint.epoch
+        );
+
+        let conflicting_checkpoint = Checkpoint {
+            root: Hash256::repeat_byte(0xff),
+            ..*checkpoint
+        };
+        assert!(
+            resume_with_wss_checkpoint(store.clone(), conflicting_checkpoint).is_err(),
+            "should refuse to resume with a conflicting checkpoint at epoch {}",
+            checkpoint.epoch
+        );
+    }
+
+    // A checkpoint which isn't finalized yet is verified once it is.
+    assert_eq!(
+        resume_with_wss_checkpoint(store, later_checkpoint),
+        Ok(()),
+        "should resume with an unfinalized checkpoint"
+    );
+}
+
 /// Checks that two chains are the same, for the purpose of this tests.
 ///
 /// Several fields that are hard/impossible to check are ignored (e.g., the store).
@@ -161,3 +400,113 @@ fn assert_chains_pretty_much_the_same<T: BeaconChainTypes>(a: &BeaconChain<T>, b
         "fork_choice heads should be equal"
     );
 }
+
+#[test]
+fn backfill_slasher_from_db() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 4;
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[..].to_vec(),
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert!(
+        matches!(
+            harness.chain.backfill_slasher(Epoch::new(0)),
+            Err(BeaconChainError::SlasherNotConfigured)
+        ),
+        "the backfill should fail without a slasher"
+    );
+
+    // Take a block and one of its attestations, to make conflicting ones from.
+    let block = harness
+        .chain
+        .block_at_slot(Slot::new(num_blocks_produced))
+        .expect("should read block")
+        .expect("the block should exist");
+    let attestation = block.message.body.attestations[0].clone();
+    let mut state = harness
+        .chain
+        .state_at_slot(attestation.data.slot, StateSkipConfig::WithStateRoots)
+        .expect("should load state");
+    state
+        .build_committee_cache(RelativeEpoch::Current, &harness.spec)
+        .unwrap();
+    let committee = state
+        .get_beacon_committee(attestation.data.slot, attestation.data.index)
+        .unwrap();
+    let indexed_attestation = get_indexed_attestation(&committee.committee, &attestation).unwrap();
+
+    let latest_slot = harness.chain.slot().expect("should have a slot");
+    harness
+        .chain
+        .persist_head_fork_choice_and_op_pool()
+        .expect("should persist the head, fork choice and op pool");
+
+    let data_dir = harness.data_dir;
+    drop(harness.chain);
+
+    // Enable a slasher, which has seen none of the blocks and attestations so far.
+    let slasher_dir = tempdir().unwrap();
+    let slasher = Arc::new(
+        Slasher::open(SlasherConfig::new(slasher_dir.path().into()), test_logger())
+            .expect("slasher should open"),
+    );
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store_with_slasher(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[..].to_vec(),
+        data_dir,
+        Some(slasher.clone()),
+    );
+    resumed_harness
+        .chain
+        .slot_clock
+        .set_slot(latest_slot.as_u64());
+
+    assert_eq!(
+        resumed_harness
+            .chain
+            .backfill_slasher(Epoch::new(0))
+            .unwrap(),
+        num_blocks_produced as usize,
+        "every block should be supplied to the slasher"
+    );
+
+    // Blocks and attestations which conflict with those of the chain are now slashable.
+    let mut block_header = block.signed_block_header();
+    block_header.message.state_root = Hash256::repeat_byte(1);
+    slasher.accept_block_header(block_header);
+
+    let mut double_vote = indexed_attestation.clone();
+    double_vote.data.beacon_block_root = Hash256::repeat_byte(1);
+    slasher.accept_attestation(double_vote);
+
+    slasher
+        .process_queued(latest_slot.epoch(MinimalEthSpec::slots_per_epoch()))
+        .unwrap();
+
+    let proposer_slashings = slasher.get_proposer_slashings();
+    assert_eq!(proposer_slashings.len(), 1);
+    assert!(proposer_slashings
+        .iter()
+        .all(|slashing| slashing.signed_header_1.message.slot == block.slot()));
+
+    let attester_slashings = slasher.get_attester_slashings();
+    assert!(!attester_slashings.is_empty());
+    assert!(attester_slashings.iter().all(|slashing| {
+        slashing.attestation_1 == indexed_attestation
+            || slashing.attestation_2 == indexed_attestation
+    }));
+}
@@ -1,20 +1,26 @@
 #![cfg(not(debug_assertions))]
 
 use beacon_chain::attestation_verification::Error as AttnError;
+use beacon_chain::builder::{BeaconChainBuilder, PUBKEY_CACHE_FILENAME};
 use beacon_chain::test_utils::{
     test_logger, AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
 };
-use beacon_chain::BeaconSnapshot;
+use beacon_chain::{BeaconChain, BeaconSnapshot, ChainConfig};
 use lazy_static::lazy_static;
 use maplit::hashset;
 use rand::Rng;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryInto;
+use std::fs;
 use std::sync::Arc;
+use std::time::Duration;
 use store::{
+    hot_cold_store::HotColdDBError,
     iter::{BlockRootsIterator, StateRootsIterator},
-    HotColdDB, LevelDB, StoreConfig,
+    BeaconNodeBackend, DBColumn, Era, Error as StoreError, HierarchyConfig, HistoryPruningSummary,
+    HotColdDB, Inconsistency, KeyValueStore, SchemaVersion, StoreConfig, CURRENT_SCHEMA_VERSION,
 };
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
@@ -33,11 +39,17 @@ lazy_static! {
 type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
 
-fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
+    get_store_with_config(db_path, StoreConfig::default())
+}
+
+fn get_store_with_config(
+    db_path: &TempDir,
+    config: StoreConfig,
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     let spec = MinimalEthSpec::default_spec();
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
-    let config = StoreConfig::default();
     let log = test_logger();
 
     Arc::new(
@@ -47,7 +59,7 @@ fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
 }
 
 fn get_harness(
-    store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
     validator_count: usize,
 ) -> TestHarness {
     let harness = BeaconChainHarness::new_with_disk_store(
@@ -78,6 +90,36 @@ fn full_participation_no_skips() {
     check_iterators(&harness);
 }
 
+#[test]
+fn small_migration_batches() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        migration_batch_size: 3,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    check_finalization(&harness, num_blocks_produced);
+    check_split_slot(&harness, store.clone());
+    check_chain_dump(&harness, num_blocks_produced + 1);
+    check_iterators(&harness);
+
+    // No states before the split are left behind in the hot database.
+    let split_slot = store.get_split_slot();
+    assert!(store
+        .iter_hot_state_summaries()
+        .map(Result::unwrap)
+        .all(|(_, summary)| summary.slot() >= split_slot));
+}
+
 #[test]
 fn randomised_skips() {
     let num_slots = E::slots_per_epoch() * 5;
@@ -252,6 +294,499 @@ fn split_slot_restore() {
     assert_eq!(store.get_split_slot(), split_slot);
 }
 
+// Check that historic states can be pruned from an existing database, and reconstructed after
+// pruning is disabled again.
+#[test]
+fn historic_state_pruning_and_reconstruction() {
+    let db_path = tempdir().unwrap();
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+
+    let restore_points = {
+        let store = get_store(&db_path);
+        let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+        let num_blocks = 3 * slots_per_restore_point;
+
+        harness.extend_chain(
+            num_blocks as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let split_slot = store.get_split_slot();
+        (1..)
+            .map(|i| Slot::new(i * slots_per_restore_point))
+            .take_while(|slot| *slot < split_slot)
+            .map(|slot| {
+                let state = store.load_cold_state_by_slot(slot).unwrap();
+                (slot, state.canonical_root())
+            })
+            .collect::<Vec<_>>()
+    };
+    assert!(restore_points.len() >= 2);
+
+    // Enabling pruning deletes every restore point except genesis.
+    {
+        let store = get_store_with_config(
+            &db_path,
+            StoreConfig {
+                prune_historic_states: Some(true),
+                ..StoreConfig::default()
+            },
+        );
+        store.prune_historic_states().unwrap();
+
+        assert!(store.load_cold_state_by_slot(Slot::new(0)).is_ok());
+        for (slot, _) in &restore_points {
+            match store.load_cold_state_by_slot(*slot) {
+                Err(StoreError::HotColdDBError(HotColdDBError::HistoricStateUnavailable(s))) => {
+                    assert_eq!(s, *slot)
+                }
+                other => panic!("state at slot {} should be unavailable: {:?}", slot, other),
+            }
+        }
+    }
+
+    // Reconstruction restores the pruned states.
+    let store = get_store_with_config(
+        &db_path,
+        StoreConfig {
+            prune_historic_states: Some(false),
+            reconstruct_historic_states: true,
+            ..StoreConfig::default()
+        },
+    );
+    while store.reconstruct_historic_states(1).unwrap() {}
+
+    assert!(store.get_historic_state_limits().is_complete());
+    for (slot, state_root) in &restore_points {
+        let state = store
+            .load_cold_state_by_slot(*slot)
+            .expect("state should be reconstructed");
+        assert_eq!(state.canonical_root(), *state_root);
+    }
+}
+
+// Check that restore points are stored as layered diffs, and load correctly.
+#[test]
+fn hierarchical_state_diffs() {
+    let db_path = tempdir().unwrap();
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+    let config = StoreConfig {
+        hierarchy: Some(HierarchyConfig {
+            exponents: vec![7, 8],
+        }),
+        ..StoreConfig::default()
+    };
+
+    let restore_points = {
+        let store = get_store_with_config(&db_path, config.clone());
+        let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+        harness.extend_chain(
+            6 * slots_per_restore_point as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let split_slot = store.get_split_slot();
+        let restore_points = (0..)
+            .map(|i| Slot::new(i * slots_per_restore_point))
+            .take_while(|slot| *slot < split_slot)
+            .map(|slot| {
+                (
+                    slot,
+                    harness.chain.state_root_at_slot(slot).unwrap().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(restore_points.len() >= 5);
+
+        // Only the top layer is stored in full.
+        for (slot, state_root) in &restore_points {
+            let column = if *slot % 256 == 0 {
+                DBColumn::BeaconState
+            } else {
+                DBColumn::BeaconStateDiff
+            };
+            assert!(
+                store
+                    .cold_db
+                    .key_exists(column.into(), state_root.as_bytes())
+                    .unwrap(),
+                "restore point at slot {} should be stored in {:?}",
+                slot,
+                column
+            );
+        }
+        restore_points
+    };
+
+    // Re-open the store and load the restore points from disk, in reverse order so that they
+    // aren't all served from the cache.
+    let store = get_store_with_config(&db_path, config);
+    for (slot, state_root) in restore_points.iter().rev() {
+        let state = store.load_cold_state_by_slot(*slot).unwrap();
+        assert_eq!(state.canonical_root(), *state_root);
+    }
+}
+
+// Check that verification passes on an intact database, and reports corrupt and missing entries.
+#[test]
+fn database_verification() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+
+    harness.extend_chain(
+        3 * slots_per_restore_point as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let report = store.verify().unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert!(report.blocks > 3 * slots_per_restore_point as usize);
+    assert!(report.hot_states > 0);
+    assert!(report.restore_points >= 2);
+    assert_eq!(report.anchor_slot, None);
+
+    // A database started from genesis has no anchor, so any missing block is reported.
+    let missing_block_root = harness
+        .chain
+        .block_root_at_slot(Slot::new(1))
+        .unwrap()
+        .unwrap();
+    let missing_block = store.get_block(&missing_block_root).unwrap().unwrap();
+    store
+        .hot_db
+        .key_delete(DBColumn::BeaconBlock.into(), missing_block_root.as_bytes())
+        .unwrap();
+
+    let report = store.verify().unwrap();
+    assert_eq!(report.anchor_slot, None);
+    assert_eq!(
+        report.inconsistencies,
+        vec![Inconsistency::MissingParentBlock {
+            block_root: harness
+                .chain
+                .block_root_at_slot(Slot::new(2))
+                .unwrap()
+                .unwrap(),
+            slot: Slot::new(2),
+            parent_root: missing_block_root,
+        }]
+    );
+    store.put_item(&missing_block_root, &missing_block).unwrap();
+
+    // Corrupt the head block and delete a restore point.
+    let head_block_root = harness.chain.head().unwrap().beacon_block_root;
+    store
+        .hot_db
+        .put_bytes(
+            DBColumn::BeaconBlock.into(),
+            head_block_root.as_bytes(),
+            &[42; 8],
+        )
+        .unwrap();
+
+    let restore_point_slot = Slot::new(slots_per_restore_point);
+    let restore_point_root = store
+        .load_cold_state_by_slot(restore_point_slot)
+        .unwrap()
+        .canonical_root();
+    store
+        .cold_db
+        .key_delete(DBColumn::BeaconState.into(), restore_point_root.as_bytes())
+        .unwrap();
+
+    let report = store.verify().unwrap();
+    assert_eq!(
+        report.inconsistencies.len(),
+        2,
+        "{:?}",
+        report.inconsistencies
+    );
+    assert!(matches!(
+        report.inconsistencies[0],
+        Inconsistency::UnreadableBlock { block_root, .. } if block_root == head_block_root
+    ));
+    assert_eq!(
+        report.inconsistencies[1],
+        Inconsistency::MissingRestorePoint {
+            slot: restore_point_slot,
+            state_root: restore_point_root,
+        }
+    );
+}
+
+// Check that pruning history leaves a database like one started from a checkpoint at the split.
+#[test]
+fn prune_history() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+
+    harness.extend_chain(
+        3 * slots_per_restore_point as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let split_slot = store.get_split_slot();
+    let restore_point_slot = Slot::new(slots_per_restore_point);
+    let pruned_block_root = harness
+        .chain
+        .block_root_at_slot(restore_point_slot)
+        .unwrap()
+        .unwrap();
+    let genesis_block_root = harness.chain.genesis_block_root;
+
+    let summary = store.prune_history().unwrap();
+    assert_eq!(summary.blocks_deleted, split_slot.as_u64() as usize - 1);
+    assert!(summary.restore_points_deleted >= 2);
+    assert_eq!(
+        summary.state_summaries_deleted,
+        split_slot.as_u64() as usize - 1
+    );
+
+    assert!(store.get_block(&pruned_block_root).unwrap().is_none());
+    assert!(store.get_block(&genesis_block_root).unwrap().is_some());
+    assert!(store.load_cold_state_by_slot(Slot::new(0)).is_ok());
+    assert!(store.load_cold_state_by_slot(restore_point_slot).is_err());
+
+    let report = store.verify().unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(report.anchor_slot, Some(split_slot));
+
+    // Pruning again has nothing to do, and the chain continues to finalize.
+    assert_eq!(
+        store.prune_history().unwrap(),
+        HistoryPruningSummary::default()
+    );
+
+    harness.extend_chain(
+        slots_per_restore_point as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    assert!(store.get_split_slot() > split_slot);
+}
+
+// Check that the restore points can be migrated to a different frequency and back again.
+#[test]
+fn migrate_slots_per_restore_point() {
+    let db_path = tempdir().unwrap();
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+    let new_slots_per_restore_point = slots_per_restore_point / 4;
+    let config = StoreConfig {
+        hierarchy: Some(HierarchyConfig {
+            exponents: vec![7, 8],
+        }),
+        ..StoreConfig::default()
+    };
+    let new_config = StoreConfig {
+        slots_per_restore_point: new_slots_per_restore_point,
+        ..config.clone()
+    };
+
+    let restore_points = {
+        let store = get_store_with_config(&db_path, config.clone());
+        let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+        harness.extend_chain(
+            4 * slots_per_restore_point as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let split_slot = store.get_split_slot();
+        (0..)
+            .map(|i| Slot::new(i * new_slots_per_restore_point))
+            .take_while(|slot| *slot < split_slot)
+            .map(|slot| {
+                (
+                    slot,
+                    harness.chain.state_root_at_slot(slot).unwrap().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Migrate to more frequent restore points.
+    get_store_with_config(&db_path, config.clone())
+        .migrate_slots_per_restore_point(new_slots_per_restore_point)
+        .unwrap();
+
+    // The old frequency no longer matches the database.
+    assert!(
+        HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+            &db_path.path().join("hot_db"),
+            &db_path.path().join("cold_db"),
+            config.clone(),
+            MinimalEthSpec::default_spec(),
+            test_logger(),
+        )
+        .is_err()
+    );
+
+    {
+        let store = get_store_with_config(&db_path, new_config);
+        for (slot, state_root) in restore_points.iter().rev() {
+            let state = store.load_cold_state_by_slot(*slot).unwrap();
+            assert_eq!(state.canonical_root(), *state_root);
+        }
+        let report = store.verify().unwrap();
+        assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+        assert_eq!(report.restore_points, restore_points.len());
+
+        // Migrate back again.
+        store
+            .migrate_slots_per_restore_point(slots_per_restore_point)
+            .unwrap();
+    }
+
+    // The restore points which are not part of the original layout are deleted.
+    let store = get_store_with_config(&db_path, config);
+    for (slot, state_root) in &restore_points {
+        if *slot % slots_per_restore_point != 0 {
+            for column in &[DBColumn::BeaconState, DBColumn::BeaconStateDiff] {
+                assert!(!store
+                    .cold_db
+                    .key_exists((*column).into(), state_root.as_bytes())
+                    .unwrap());
+            }
+        }
+
+        let state = store.load_cold_state_by_slot(*slot).unwrap();
+        assert_eq!(state.canonical_root(), *state_root);
+    }
+    let report = store.verify().unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+}
+
+// Check that eras can be exported from the freezer and verified against their historical roots.
+#[test]
+fn era_export() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let slots_per_era = E::slots_per_historical_root() as u64;
+
+    // Skip a slot, so that an era contains an empty slot.
+    harness.extend_chain(
+        slots_per_era as usize + 3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.advance_slot();
+    harness.extend_chain(
+        2 * slots_per_era as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    assert!(store.get_split_slot() > 2 * slots_per_era);
+
+    let genesis = store.load_era(0).unwrap();
+    genesis.verify().unwrap();
+    assert!(genesis.blocks.is_empty());
+    assert!(genesis
+        .file_name("minimal")
+        .unwrap()
+        .starts_with("minimal-00000-"));
+
+    let era = store.load_era(2).unwrap();
+    era.verify().unwrap();
+    assert_eq!(era.blocks.len() as u64, slots_per_era - 1);
+    assert_eq!(
+        era.state.canonical_root(),
+        harness
+            .chain
+            .state_root_at_slot(Slot::new(2 * slots_per_era))
+            .unwrap()
+            .unwrap()
+    );
+
+    let decoded = Era::<E>::from_bytes(&era.to_bytes().unwrap()).unwrap();
+    decoded.verify().unwrap();
+    assert_eq!(decoded.blocks, era.blocks);
+    assert_eq!(decoded.state.canonical_root(), era.state.canonical_root());
+
+    // A missing or substituted block is detected.
+    let mut missing_block = decoded.clone();
+    missing_block.blocks.remove(10);
+    assert!(missing_block.verify().is_err());
+
+    let mut wrong_block = decoded;
+    wrong_block.blocks[10] = store.load_era(1).unwrap().blocks[10].clone();
+    assert!(wrong_block.verify().is_err());
+
+    assert!(store.load_era(100).is_err());
+}
+
+// Check that a restore point migration interrupted before its new frequency is recorded in the
+// hot DB is completed when the database is next opened.
+#[test]
+fn interrupted_restore_point_migration() {
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        hierarchy: Some(HierarchyConfig {
+            exponents: vec![7, 8],
+        }),
+        ..StoreConfig::default()
+    };
+    let new_slots_per_restore_point = config.slots_per_restore_point / 4;
+    let new_config = StoreConfig {
+        slots_per_restore_point: new_slots_per_restore_point,
+        ..config.clone()
+    };
+
+    {
+        let store = get_store_with_config(&db_path, config.clone());
+        let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+        harness.extend_chain(
+            2 * config.slots_per_restore_point as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        // Switch the layout over without recording the new frequency.
+        store
+            .migrate_restore_point_layout(new_slots_per_restore_point)
+            .unwrap();
+    }
+
+    let open = |config: StoreConfig| {
+        HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+            &db_path.path().join("hot_db"),
+            &db_path.path().join("cold_db"),
+            config,
+            MinimalEthSpec::default_spec(),
+            test_logger(),
+        )
+    };
+
+    // A read-only database reports the new frequency without recording it.
+    let read_only = |config: StoreConfig| StoreConfig {
+        read_only: true,
+        ..config
+    };
+    assert!(open(read_only(config.clone())).is_err());
+    assert!(open(read_only(new_config.clone())).is_ok());
+
+    // Opening the database records the new frequency, even with the old one configured.
+    assert!(open(config.clone()).is_err());
+    assert!(open(read_only(config)).is_err());
+
+    let store = open(new_config).unwrap();
+    let report = store.verify().unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert!(report.restore_points > 2);
+}
+
 // Check attestation processing and `load_epoch_boundary_state` in the presence of a split DB.
 // This is a bit of a monster test in that it tests lots of different things, but until they're
 // tested elsewhere, this is as good a place as any.
@@ -1652,6 +2187,597 @@ fn garbage_collect_temp_states_from_failed_block() {
     assert_eq!(store.iter_temporary_state_roots().count(), 0);
 }
 
+#[test]
+fn garbage_collect_orphaned_states() {
+    let num_blocks_produced = E::slots_per_epoch();
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // Store the state of a block which is never imported, like those left behind by an unclean
+    // shutdown.
+    let head_state = harness.get_current_state();
+    let orphan_slot = head_state.slot + 1;
+    let (block, state) = harness.make_block(head_state, orphan_slot);
+    let orphan_state_root = block.state_root();
+    store.put_state(&orphan_state_root, &state).unwrap();
+    let num_states = store.iter_hot_state_summaries().count();
+
+    let gc = harness.chain.garbage_collect_orphaned_states().unwrap();
+    assert_eq!(gc.states_deleted, 1);
+    assert!(gc.bytes_reclaimed > 0);
+    assert_eq!(store.iter_hot_state_summaries().count(), num_states - 1);
+    assert!(store
+        .load_hot_state_summary(&orphan_state_root)
+        .unwrap()
+        .is_none());
+
+    // The canonical chain is untouched, and there's nothing left to collect.
+    check_chain_dump(&harness, num_blocks_produced + 1);
+    let gc = harness.chain.garbage_collect_orphaned_states().unwrap();
+    assert_eq!(gc.states_deleted, 0);
+}
+
+/// Store some temporary states by processing an invalid block.
+fn store_temporary_states(harness: &TestHarness) {
+    let genesis_state = harness.get_current_state();
+    let block_slot = Slot::new(E::slots_per_epoch());
+    let (mut block, state) = harness.make_block(genesis_state, block_slot);
+    block.message.state_root = Hash256::repeat_byte(0xff);
+    let proposer_index = block.message.proposer_index as usize;
+    let block = block.message.sign(
+        &harness.validator_keypairs[proposer_index].sk,
+        &state.fork,
+        state.genesis_validators_root,
+        &harness.spec,
+    );
+    harness.set_current_slot(block_slot);
+    harness.process_block_result(block).unwrap_err();
+}
+
+#[test]
+fn downgrade_and_upgrade_schema() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    store_temporary_states(&harness);
+    let num_temp_states = store.iter_temporary_state_roots().count() as u64;
+    assert!(num_temp_states > 0);
+
+    // Planning the downgrade counts the temporary states without deleting them.
+    let v1 = SchemaVersion(1);
+    let plan = store.plan_schema_migration(v1).unwrap();
+    assert_eq!(plan.from, CURRENT_SCHEMA_VERSION);
+    assert_eq!(plan.steps.len(), 1);
+    assert_eq!(plan.steps[0].entries, num_temp_states);
+    assert_eq!(
+        store.iter_temporary_state_roots().count() as u64,
+        num_temp_states
+    );
+
+    store.migrate_schema(CURRENT_SCHEMA_VERSION, v1).unwrap();
+    assert_eq!(store.schema_version().unwrap(), v1);
+    assert_eq!(store.iter_temporary_state_roots().count(), 0);
+    assert!(store.plan_schema_migration(SchemaVersion(0)).is_err());
+
+    drop(harness);
+    drop(store);
+
+    // A read-only store can plan the upgrade without changing the database, if it doesn't
+    // expect the current version.
+    let open_read_only = |migrate_schema| {
+        HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+            &db_path.path().join("hot_db"),
+            &db_path.path().join("cold_db"),
+            StoreConfig {
+                read_only: true,
+                migrate_schema,
+                ..StoreConfig::default()
+            },
+            MinimalEthSpec::default_spec(),
+            test_logger(),
+        )
+    };
+    assert!(open_read_only(true).is_err());
+    let store = open_read_only(false).unwrap();
+    let plan = store.plan_schema_migration(CURRENT_SCHEMA_VERSION).unwrap();
+    assert_eq!(plan.from, v1);
+    assert_eq!(plan.steps.len(), 1);
+    assert!(store.migrate_schema(v1, CURRENT_SCHEMA_VERSION).is_err());
+    assert_eq!(store.schema_version().unwrap(), v1);
+    drop(store);
+
+    // The downgrade is kept by a store which doesn't migrate, and undone by one which does.
+    let config = StoreConfig {
+        migrate_schema: false,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config);
+    assert_eq!(store.schema_version().unwrap(), v1);
+    drop(store);
+
+    let store = get_store(&db_path);
+    assert_eq!(store.schema_version().unwrap(), CURRENT_SCHEMA_VERSION);
+}
+
+// Check that an automatic schema migration is checked and run before anything else is written.
+#[test]
+fn schema_migration_checked_before_writes() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    // Downgrade, then leave some temporary states for the garbage collection at start-up.
+    let v1 = SchemaVersion(1);
+    store.migrate_schema(CURRENT_SCHEMA_VERSION, v1).unwrap();
+    store_temporary_states(&harness);
+    let num_temp_states = store.iter_temporary_state_roots().count();
+    assert!(num_temp_states > 0);
+    drop(harness);
+    drop(store);
+
+    let spec = MinimalEthSpec::default_spec();
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let open = |check_migration: &dyn Fn(
+        &HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>,
+    ) -> Result<(), StoreError>| {
+        HotColdDB::open_with_migration_check(
+            &hot_path,
+            &cold_path,
+            StoreConfig::default(),
+            spec.clone(),
+            test_logger(),
+            |db, plan| {
+                assert_eq!(plan.from, v1);
+                assert_eq!(plan.to, CURRENT_SCHEMA_VERSION);
+                check_migration(db)
+            },
+        )
+    };
+
+    // A refused migration leaves the database as it was found.
+    assert!(open(&|_| Err(StoreError::DBError {
+        message: "refused".into()
+    }))
+    .is_err());
+
+    // The migration is checked before the garbage collection.
+    let checked = Cell::new(false);
+    let store = open(&|db| {
+        assert_eq!(db.schema_version().unwrap(), v1);
+        assert_eq!(db.iter_temporary_state_roots().count(), num_temp_states);
+        checked.set(true);
+        Ok(())
+    })
+    .unwrap();
+    assert!(checked.get());
+    assert_eq!(store.schema_version().unwrap(), CURRENT_SCHEMA_VERSION);
+    assert_eq!(store.iter_temporary_state_roots().count(), 0);
+}
+
+// Check that a database opened read-only serves the chain but refuses all writes.
+#[test]
+fn read_only_store() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().unwrap();
+    let split_slot = store.get_split_slot();
+    assert!(split_slot > 0);
+    drop(harness);
+    drop(store);
+
+    let config = StoreConfig {
+        read_only: true,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config);
+    assert!(store.is_read_only());
+    assert_eq!(store.get_split_slot(), split_slot);
+
+    // Both the hot and the frozen parts of the chain can be read.
+    assert_eq!(
+        store.get_block(&head.beacon_block_root).unwrap(),
+        Some(head.beacon_block.clone())
+    );
+    let head_state = store
+        .get_state(&head.beacon_state_root, Some(head.beacon_state.slot))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        head_state.tree_hash_root(),
+        head.beacon_state.tree_hash_root()
+    );
+    let genesis_state = store.load_cold_state_by_slot(Slot::new(0)).unwrap();
+    assert_eq!(genesis_state.slot, Slot::new(0));
+
+    assert!(matches!(
+        store.put_block(&Hash256::zero(), head.beacon_block.clone()),
+        Err(StoreError::ReadOnly)
+    ));
+    assert!(matches!(
+        store
+            .hot_db
+            .put_bytes(DBColumn::BeaconMeta.as_str(), &[0; 32], &[]),
+        Err(StoreError::ReadOnly)
+    ));
+    assert!(matches!(
+        store.cold_db.do_atomically(vec![]),
+        Err(StoreError::ReadOnly)
+    ));
+
+    // Resuming the chain leaves any legacy pubkey cache file in place.
+    let data_dir = tempdir().unwrap();
+    let legacy_path = data_dir.path().join(PUBKEY_CACHE_FILENAME);
+    fs::write(&legacy_path, &[]).unwrap();
+    BeaconChainBuilder::<DiskHarnessType<E>>::new(MinimalEthSpec)
+        .logger(test_logger())
+        .store(store)
+        .data_dir(data_dir.path().to_path_buf())
+        .resume_from_db()
+        .unwrap();
+    assert!(legacy_path.exists());
+}
+
+// Check that a chain can be built from a read-only database whose pubkey cache is behind the
+// head, without persisting the missing keys.
+#[test]
+fn read_only_store_with_stale_pubkey_cache() {
+    let num_blocks_produced = E::slots_per_epoch() * 2;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    drop(harness);
+
+    // Drop the last key of the stored cache, as if the head had gained a validator since it was
+    // persisted.
+    let last_index = Hash256::from_low_u64_be(LOW_VALIDATOR_COUNT as u64 - 1);
+    store
+        .hot_db
+        .key_delete(DBColumn::PubkeyCache.as_str(), last_index.as_bytes())
+        .unwrap();
+    drop(store);
+
+    let config = StoreConfig {
+        read_only: true,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config);
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let chain = BeaconChainBuilder::<DiskHarnessType<E>>::new(MinimalEthSpec)
+        .logger(test_logger())
+        .store(store.clone())
+        .resume_from_db()
+        .unwrap()
+        .dummy_eth1_backend()
+        .unwrap()
+        .null_event_handler()
+        .testing_slot_clock(Duration::from_secs(1))
+        .unwrap()
+        .shutdown_sender(shutdown_tx)
+        .build()
+        .unwrap();
+
+    let last_pubkey: PublicKeyBytes = KEYPAIRS[LOW_VALIDATOR_COUNT - 1].pk.clone().into();
+    assert_eq!(
+        chain.validator_index(&last_pubkey).unwrap(),
+        Some(LOW_VALIDATOR_COUNT - 1)
+    );
+    assert_eq!(
+        store.hot_db.iter_column_keys(DBColumn::PubkeyCache).count(),
+        LOW_VALIDATOR_COUNT - 1
+    );
+}
+
+// Check that the split point can only be moved back to a full state in the hot database.
+#[test]
+fn anchor_info_and_set_split() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let split_slot = store.get_split_slot();
+    let info = store.get_anchor_info().unwrap();
+    assert_eq!(info.split_slot, split_slot);
+    assert!(info.split_state_stored);
+    let anchor_block = store
+        .get_block(&info.anchor_block_root.unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.anchor_block_slot, Some(anchor_block.slot()));
+    assert!(anchor_block.slot() <= split_slot);
+
+    // Later, unaligned and frozen states are all rejected.
+    let invalid = |slot: Slot, state_root: Hash256| {
+        matches!(
+            store.check_split(slot, state_root),
+            Err(StoreError::HotColdDBError(HotColdDBError::InvalidSplit(_)))
+        )
+    };
+    let head = harness.chain.head().unwrap();
+    assert!(invalid(head.beacon_state.slot, head.beacon_state_root));
+    assert!(invalid(split_slot - 1, Hash256::zero()));
+    assert!(invalid(Slot::new(0), harness.chain.genesis_state_root));
+    store
+        .check_split(info.split_slot, info.split_state_root)
+        .unwrap();
+
+    // Once the genesis state is stored in the hot database, the split can be moved back to it.
+    let genesis_state = store.load_cold_state_by_slot(Slot::new(0)).unwrap();
+    let mut ops = vec![];
+    store
+        .store_hot_state(&harness.chain.genesis_state_root, &genesis_state, &mut ops)
+        .unwrap();
+    store.hot_db.do_atomically(ops).unwrap();
+    store
+        .set_split(Slot::new(0), harness.chain.genesis_state_root)
+        .unwrap();
+    assert_eq!(store.get_split_slot(), 0);
+
+    drop(harness);
+    drop(store);
+
+    let store = get_store(&db_path);
+    assert_eq!(store.get_split_slot(), 0);
+}
+
+/// Builds a chain on `store` which starts from the checkpoint `state` and `block`.
+fn build_from_checkpoint(
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+    state: BeaconState<E>,
+    block: SignedBeaconBlock<E>,
+    genesis_state: BeaconState<E>,
+    chain_config: ChainConfig,
+) -> Result<BeaconChain<DiskHarnessType<E>>, String> {
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+
+    BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(test_logger())
+        .store(store)
+        .chain_config(chain_config)
+        .weak_subjectivity_state(state, block, genesis_state)?
+        .dummy_eth1_backend()?
+        .null_event_handler()
+        .testing_slot_clock(Duration::from_secs(1))?
+        .shutdown_sender(shutdown_tx)
+        .build()
+}
+
+/// Returns the finalized checkpoint block and state of `harness`, along with its genesis state.
+fn get_checkpoint(harness: &TestHarness) -> (SignedBeaconBlock<E>, BeaconState<E>, BeaconState<E>) {
+    let finalized_checkpoint = harness.chain.head_info().unwrap().finalized_checkpoint;
+    let block = harness
+        .chain
+        .get_block(&finalized_checkpoint.root)
+        .unwrap()
+        .unwrap();
+    let state = harness
+        .chain
+        .get_state(&block.state_root(), Some(block.slot()))
+        .unwrap()
+        .unwrap();
+    let genesis_state = harness
+        .chain
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .unwrap()
+        .unwrap();
+    (block, state, genesis_state)
+}
+
+#[test]
+fn checkpoint_sync_and_import_blocks() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store, LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (block, state, genesis_state) = get_checkpoint(&harness);
+    let checkpoint_slot = block.slot();
+    let checkpoint_root = block.canonical_root();
+    assert!(checkpoint_slot > 0, "the chain should have finalized");
+
+    let checkpoint_db_path = tempdir().unwrap();
+    let checkpoint_store = get_store(&checkpoint_db_path);
+    let chain = build_from_checkpoint(
+        checkpoint_store.clone(),
+        state,
+        block,
+        genesis_state,
+        ChainConfig::default(),
+    )
+    .expect("should start from the checkpoint");
+
+    assert_eq!(chain.head_info().unwrap().block_root, checkpoint_root);
+    assert_eq!(chain.genesis_block_root, harness.chain.genesis_block_root);
+    assert_eq!(
+        checkpoint_store.load_anchor_slot().unwrap(),
+        Some(checkpoint_slot)
+    );
+    assert_eq!(
+        checkpoint_store
+            .get_block(&chain.genesis_block_root)
+            .unwrap(),
+        harness
+            .chain
+            .get_block(&harness.chain.genesis_block_root)
+            .unwrap(),
+        "should store the genesis block"
+    );
+
+    // The blocks after the checkpoint can be imported, reaching the same head.
+    let mut blocks = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .into_iter()
+        .map(|snapshot| snapshot.beacon_block)
+        .filter(|block| block.slot() > checkpoint_slot)
+        .collect::<Vec<_>>();
+    blocks.sort_by_key(|block| block.slot());
+
+    chain
+        .slot_clock
+        .set_slot(harness.chain.slot().unwrap().as_u64());
+    for block in blocks {
+        chain.process_block(block).expect("should import block");
+    }
+    chain.fork_choice().unwrap();
+
+    assert_eq!(
+        chain.head_info().unwrap().block_root,
+        harness.chain.head_info().unwrap().block_root
+    );
+}
+
+#[test]
+fn checkpoint_sync_rejects_invalid_checkpoints() {
+    // End on a slot which isn't at the start of an epoch.
+    let num_blocks_produced = E::slots_per_epoch() * 6 + 1;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store, LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (block, state, genesis_state) = get_checkpoint(&harness);
+    let checkpoint_epoch = block.slot().epoch(E::slots_per_epoch());
+    let build = |block: SignedBeaconBlock<E>,
+                 state: BeaconState<E>,
+                 genesis_state: BeaconState<E>,
+                 chain_config: ChainConfig| {
+        let db_path = tempdir().unwrap();
+        build_from_checkpoint(
+            get_store(&db_path),
+            state,
+            block,
+            genesis_state,
+            chain_config,
+        )
+        .map(|_| ())
+    };
+
+    // A state which isn't at the start of an epoch.
+    let head = harness.chain.head().unwrap();
+    assert_ne!(head.beacon_state.slot % E::slots_per_epoch(), 0);
+    assert!(build(
+        head.beacon_block.clone(),
+        head.beacon_state.clone(),
+        genesis_state.clone(),
+        ChainConfig::default()
+    )
+    .is_err());
+
+    // A block at another slot.
+    let parent_block = harness
+        .chain
+        .get_block(&block.parent_root())
+        .unwrap()
+        .unwrap();
+    assert!(build(
+        parent_block,
+        state.clone(),
+        genesis_state.clone(),
+        ChainConfig::default()
+    )
+    .is_err());
+
+    // A block of another state.
+    let mut other_block = block.clone();
+    other_block.message.state_root = Hash256::repeat_byte(1);
+    assert!(build(
+        other_block,
+        state.clone(),
+        genesis_state.clone(),
+        ChainConfig::default()
+    )
+    .is_err());
+
+    // A state of another network.
+    let mut other_genesis_state = genesis_state.clone();
+    other_genesis_state.genesis_validators_root = Hash256::repeat_byte(1);
+    assert!(build(
+        block.clone(),
+        state.clone(),
+        other_genesis_state,
+        ChainConfig::default()
+    )
+    .is_err());
+
+    // A state of another fork.
+    let mut other_fork_state = state.clone();
+    other_fork_state.fork.current_version = [1, 2, 3, 4];
+    let mut other_fork_block = block.clone();
+    other_fork_block.message.state_root = other_fork_state.tree_hash_root();
+    assert!(build(
+        other_fork_block,
+        other_fork_state,
+        genesis_state.clone(),
+        ChainConfig::default()
+    )
+    .is_err());
+
+    // A state which conflicts with an earlier weak subjectivity checkpoint.
+    let conflicting_config = ChainConfig {
+        weak_subjectivity_checkpoint: Some(Checkpoint {
+            epoch: checkpoint_epoch - 1,
+            root: Hash256::repeat_byte(1),
+        }),
+        ..ChainConfig::default()
+    };
+    assert!(build(
+        block.clone(),
+        state.clone(),
+        genesis_state.clone(),
+        conflicting_config
+    )
+    .is_err());
+
+    // The unmodified checkpoint is accepted.
+    assert_eq!(
+        build(block, state, genesis_state, ChainConfig::default()),
+        Ok(())
+    );
+}
+
 /// Check that the head state's slot matches `expected_slot`.
 fn check_slot(harness: &TestHarness, expected_slot: u64) {
     let state = &harness.chain.head().expect("should get head").beacon_state;
@@ -1681,7 +2807,10 @@ fn check_finalization(harness: &TestHarness, expected_slot: u64) {
 }
 
 /// Check that the HotColdDB's split_slot is equal to the start slot of the last finalized epoch.
-fn check_split_slot(harness: &TestHarness, store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>) {
+fn check_split_slot(
+    harness: &TestHarness,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+) {
     let split_slot = store.get_split_slot();
     assert_eq!(
         harness
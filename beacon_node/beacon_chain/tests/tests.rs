@@ -5,6 +5,7 @@ extern crate lazy_static;
 
 use beacon_chain::{
     attestation_verification::Error as AttnError,
+    proposer_shuffling_decision_root,
     test_utils::{
         AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType,
         OP_POOL_DB_KEY,
@@ -15,7 +16,9 @@ use state_processing::{
     per_slot_processing, per_slot_processing::Error as SlotProcessingError, EpochProcessingError,
 };
 use store::config::StoreConfig;
-use types::{BeaconStateError, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, Slot};
+use types::{
+    BeaconStateError, CloneConfig, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, Slot,
+};
 
 // Should ideally be divisible by 3.
 pub const VALIDATOR_COUNT: usize = 24;
@@ -335,6 +338,107 @@ fn does_not_finalize_without_attestation() {
     );
 }
 
+#[test]
+fn committee_cache_for_state_matches_state() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+
+    for relative_epoch in &[RelativeEpoch::Current, RelativeEpoch::Next] {
+        let mut head_state = head.beacon_state.clone();
+        head_state
+            .build_committee_cache(*relative_epoch, &harness.chain.spec)
+            .expect("should build committee cache");
+        let expected = head_state
+            .committee_cache(*relative_epoch)
+            .expect("should get committee cache");
+
+        // The head block's shuffling was added to the shuffling cache when it was imported, so a
+        // state without any caches should be given the same committees.
+        let mut state = head.beacon_state.clone_with(CloneConfig::none());
+        assert!(!state.committee_cache_is_initialized(*relative_epoch));
+
+        let committee_cache = harness
+            .chain
+            .committee_cache_for_state(&state, head.beacon_block_root, *relative_epoch)
+            .expect("should get committee cache");
+        assert_eq!(&*committee_cache, expected);
+
+        harness
+            .chain
+            .build_committee_cache_with_shuffling_cache(
+                &mut state,
+                head.beacon_block_root,
+                *relative_epoch,
+            )
+            .expect("should build committee cache");
+        assert_eq!(
+            state
+                .committee_cache(*relative_epoch)
+                .expect("should be initialized"),
+            expected
+        );
+    }
+}
+
+#[test]
+fn precompute_epoch_shufflings_fills_proposer_cache() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let next_epoch = head.beacon_state.current_epoch() + 1;
+
+    // Skip the head state into the next epoch, as if no block had arrived yet.
+    let mut state = head.beacon_state.clone();
+    while state.current_epoch() < next_epoch {
+        per_slot_processing(&mut state, None, &harness.chain.spec).expect("should skip slot");
+    }
+    state
+        .build_committee_cache(RelativeEpoch::Current, &harness.chain.spec)
+        .expect("should build committee cache");
+    let expected = next_epoch
+        .slot_iter(MinimalEthSpec::slots_per_epoch())
+        .map(|slot| state.get_beacon_proposer_index(slot, &harness.chain.spec))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should get proposers");
+
+    let decision_root =
+        proposer_shuffling_decision_root(next_epoch, head.beacon_block_root, &state)
+            .expect("should get decision root");
+    assert!(harness
+        .chain
+        .proposer_cache
+        .lock()
+        .get(next_epoch, decision_root)
+        .is_none());
+
+    harness
+        .chain
+        .precompute_epoch_shufflings(next_epoch)
+        .expect("should precompute shufflings");
+
+    assert_eq!(
+        harness
+            .chain
+            .proposer_cache
+            .lock()
+            .get(next_epoch, decision_root),
+        Some(&expected[..])
+    );
+}
+
 #[test]
 fn roundtrip_operation_pool() {
     let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
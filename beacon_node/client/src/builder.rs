@@ -1,24 +1,33 @@
 use crate::config::{ClientGenesis, Config as ClientConfig};
+use crate::disk_space::check_disk_space;
 use crate::notifier::spawn_notifier;
 use crate::Client;
 use beacon_chain::events::TeeEventHandler;
+use beacon_chain::slashing_webhook::{Config as SlashingWebhookConfig, SlashingWebhook};
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
     slot_clock::{SlotClock, SystemTimeSlotClock},
-    store::{HotColdDB, ItemStore, LevelDB, StoreConfig},
-    BeaconChain, BeaconChainTypes, Eth1ChainBackend, EventHandler,
+    store::{
+        BeaconNodeBackend, DatabaseBackend, Error as StoreError, HotColdDB, ItemStore, StoreConfig,
+    },
+    BeaconChain, BeaconChainError, BeaconChainTypes, Eth1ChainBackend, EventHandler,
 };
 use bus::Bus;
 use environment::RuntimeContext;
 use eth1::{Config as Eth1Config, Service as Eth1Service};
-use eth2_libp2p::NetworkGlobals;
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient,
+};
+use eth2_hashing::hash;
+use eth2_libp2p::{NetworkGlobals, PubsubMessage};
 use genesis::{interop_genesis_state, Eth1GenesisService};
 use network::{NetworkConfig, NetworkMessage, NetworkService};
 use parking_lot::Mutex;
 use slasher::{Slasher, SlasherServer};
-use slog::{debug, info, warn};
-use ssz::Decode;
+use slog::{debug, error, info, warn, Logger};
+use ssz::{Decode, Encode};
 use std::net::SocketAddr;
 use std::net::TcpListener;
 use std::path::{Path, PathBuf};
@@ -27,9 +36,10 @@ use std::time::Duration;
 use timer::spawn_timer;
 use tokio::sync::{mpsc::UnboundedSender, oneshot};
 use types::{
-    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec,
-    SignedBeaconBlockHash,
+    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, DepositTreeSnapshot,
+    EthSpec, Hash256, SignedBeaconBlock, SignedBeaconBlockHash,
 };
+use url::Url;
 use websocket_server::{Config as WebSocketConfig, WebSocketSender};
 
 /// Interval between polling the eth1 node for genesis information.
@@ -57,6 +67,7 @@ pub struct ClientBuilder<T: BeaconChainTypes> {
     beacon_chain_builder: Option<BeaconChainBuilder<T>>,
     beacon_chain: Option<Arc<BeaconChain<T>>>,
     eth1_service: Option<Eth1Service>,
+    deposit_snapshot: Option<DepositTreeSnapshot>,
     event_handler: Option<T::EventHandler>,
     network_globals: Option<Arc<NetworkGlobals<T::EthSpec>>>,
     network_send: Option<UnboundedSender<NetworkMessage<T::EthSpec>>>,
@@ -91,6 +102,7 @@ where
             beacon_chain_builder: None,
             beacon_chain: None,
             eth1_service: None,
+            deposit_snapshot: None,
             event_handler: None,
             network_globals: None,
             network_send: None,
@@ -164,6 +176,10 @@ where
             .store_contains_beacon_chain()
             .unwrap_or_else(|_| false);
 
+        if config.store.read_only && !chain_exists {
+            return Err("The read-only database does not contain a beacon chain".to_string());
+        }
+
         // If the client is expect to resume but there's no beacon chain in the database,
         // use the `DepositContract` method. This scenario is quite common when the client
         // is shutdown before finding genesis via eth1.
@@ -180,6 +196,24 @@ where
             client_genesis
         };
 
+        // Syncing from genesis (as opposed to a checkpoint) is vulnerable to long-range attacks
+        // and must be explicitly permitted.
+        if !config.allow_insecure_genesis_sync
+            && matches!(
+                client_genesis,
+                ClientGenesis::SszBytes { .. }
+                    | ClientGenesis::GenesisStateUrl { .. }
+                    | ClientGenesis::DepositContract
+            )
+        {
+            return Err(
+                "Syncing from genesis is insecure and may follow a long-range attack chain. \
+                 Use --checkpoint-sync-url or --checkpoint-state to sync from a trusted \
+                 checkpoint, or --allow-insecure-genesis-sync to sync from genesis anyway."
+                    .to_string(),
+            );
+        }
+
         let (beacon_chain_builder, eth1_service_option) = match client_genesis {
             ClientGenesis::Interop {
                 validator_count,
@@ -202,6 +236,19 @@ where
 
                 builder.genesis_state(genesis_state).map(|v| (v, None))?
             }
+            ClientGenesis::GenesisStateUrl { url, checksum } => {
+                info!(
+                    context.log(),
+                    "Downloading genesis state";
+                    "url" => &url,
+                );
+
+                let genesis_state_bytes = download_genesis_state(&url, checksum).await?;
+                let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                    .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
+
+                builder.genesis_state(genesis_state).map(|v| (v, None))?
+            }
             ClientGenesis::DepositContract => {
                 info!(
                     context.log(),
@@ -297,6 +344,140 @@ where
                     .genesis_state(genesis_state)
                     .map(|v| (v, Some(genesis_service.into_core_service())))?
             }
+            ClientGenesis::CheckpointSyncUrl {
+                genesis_state_bytes,
+                url,
+            } => {
+                info!(
+                    context.log(),
+                    "Starting checkpoint sync";
+                    "remote_url" => &url,
+                );
+
+                let url = Url::parse(&url)
+                    .map_err(|e| format!("Invalid checkpoint sync URL: {:?}", e))?;
+                let remote = BeaconNodeHttpClient::new(url);
+
+                let block = remote
+                    .get_beacon_blocks::<TEthSpec>(BlockId::Finalized)
+                    .await
+                    .map_err(|e| format!("Error fetching finalized block from remote: {:?}", e))?
+                    .ok_or_else(|| "Finalized block missing from remote, it returned 404")?
+                    .data;
+
+                let slots_per_epoch = TEthSpec::slots_per_epoch();
+                if block.slot() % slots_per_epoch != 0 {
+                    return Err(format!(
+                        "Finalized block at slot {} on the remote is not at the start of an \
+                         epoch (skipped slot), retry checkpoint sync after the next epoch is \
+                         finalized",
+                        block.slot()
+                    ));
+                }
+
+                let state = remote
+                    .get_debug_beacon_states::<TEthSpec>(StateId::Root(block.state_root()))
+                    .await
+                    .map_err(|e| format!("Error fetching finalized state from remote: {:?}", e))?
+                    .ok_or_else(|| "Finalized state missing from remote, it returned 404")?
+                    .data;
+
+                // The deposit snapshot saves downloading the deposit logs prior to the checkpoint,
+                // but it is optional as the remote may not serve it.
+                self.deposit_snapshot = match remote.get_deposit_snapshot().await {
+                    Ok(Some(response)) => {
+                        let snapshot = response.data;
+                        if snapshot.matches_state(&state) {
+                            Some(snapshot)
+                        } else {
+                            warn!(
+                                context.log(),
+                                "Ignoring deposit snapshot from remote";
+                                "info" => "it does not match the checkpoint state",
+                                "deposit_root" => ?snapshot.deposit_root,
+                                "deposit_count" => snapshot.deposit_count,
+                                "eth1_deposit_index" => state.eth1_deposit_index,
+                            );
+                            None
+                        }
+                    }
+                    Ok(None) => {
+                        warn!(
+                            context.log(),
+                            "Remote does not serve a deposit snapshot";
+                            "info" => "all deposit logs will be downloaded from the eth1 node",
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        warn!(
+                            context.log(),
+                            "Unable to fetch deposit snapshot from remote";
+                            "info" => "all deposit logs will be downloaded from the eth1 node",
+                            "error" => ?e,
+                        );
+                        None
+                    }
+                };
+
+                let genesis_state = if let Some(genesis_state_bytes) = genesis_state_bytes {
+                    BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                        .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?
+                } else {
+                    remote
+                        .get_debug_beacon_states::<TEthSpec>(StateId::Genesis)
+                        .await
+                        .map_err(|e| format!("Error fetching genesis state from remote: {:?}", e))?
+                        .ok_or_else(|| "Genesis state missing from remote, it returned 404")?
+                        .data
+                };
+
+                info!(
+                    context.log(),
+                    "Loaded checkpoint block and state";
+                    "slot" => block.slot(),
+                    "block_root" => format!("{:?}", block.canonical_root()),
+                    "state_root" => format!("{:?}", block.state_root()),
+                );
+
+                builder
+                    .weak_subjectivity_state(state, block, genesis_state)
+                    .map(|v| (v, None))?
+            }
+            ClientGenesis::CheckpointSyncFiles {
+                genesis_state_bytes,
+                anchor_state_bytes,
+                anchor_block_bytes,
+            } => {
+                info!(
+                    context.log(),
+                    "Starting checkpoint sync from files";
+                );
+
+                let state = BeaconState::from_ssz_bytes(&anchor_state_bytes)
+                    .map_err(|e| format!("Unable to parse checkpoint state SSZ: {:?}", e))?;
+                let block = SignedBeaconBlock::from_ssz_bytes(&anchor_block_bytes)
+                    .map_err(|e| format!("Unable to parse checkpoint block SSZ: {:?}", e))?;
+                let genesis_state_bytes = genesis_state_bytes.ok_or_else(|| {
+                    "The genesis state of this network is unknown, so it must be synced with \
+                     --checkpoint-sync-url instead"
+                        .to_string()
+                })?;
+                let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                    .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
+
+                info!(
+                    context.log(),
+                    "Loaded checkpoint block and state";
+                    "slot" => block.slot(),
+                    "block_root" => format!("{:?}", block.canonical_root()),
+                    "state_root" => format!("{:?}", block.state_root()),
+                );
+
+                builder
+                    .weak_subjectivity_state(state, block, genesis_state)
+                    .map(|v| (v, None))?
+            }
             ClientGenesis::FromStore => builder.resume_from_db().map(|v| (v, None))?,
         };
 
@@ -380,7 +561,66 @@ where
             .slot_clock
             .clone()
             .ok_or_else(|| "slasher server requires a slot clock")?;
-        SlasherServer::run(slasher, slot_clock, &context.executor);
+
+        let log = context.log().clone();
+
+        // Publish new slashings after each update, if configured to do so and there is a network
+        // to publish them on (there is none with `--db-read-only`).
+        let broadcast = match (slasher.config().broadcast, self.network_send.clone()) {
+            (true, Some(network_send)) => {
+                let beacon_chain = self
+                    .beacon_chain
+                    .clone()
+                    .ok_or_else(|| "slasher broadcast requires a beacon chain")?;
+                Some((beacon_chain, network_send))
+            }
+            (true, None) => {
+                warn!(
+                    log,
+                    "Slasher broadcast disabled";
+                    "reason" => "no network",
+                );
+                None
+            }
+            (false, _) => None,
+        };
+        let on_update = move || {
+            if let Some((beacon_chain, network_send)) = broadcast.as_ref() {
+                broadcast_slasher_slashings(beacon_chain, network_send, &log);
+            }
+        };
+
+        if let Some(backfill_epoch) = slasher.config().backfill_epoch {
+            let beacon_chain = self
+                .beacon_chain
+                .clone()
+                .ok_or_else(|| "slasher backfill requires a beacon chain")?;
+            let log = context.log().clone();
+            context.executor.spawn_blocking(
+                move || {
+                    info!(
+                        log,
+                        "Backfilling slasher";
+                        "start_epoch" => backfill_epoch,
+                    );
+                    match beacon_chain.backfill_slasher(backfill_epoch) {
+                        Ok(num_blocks) => info!(
+                            log,
+                            "Completed slasher backfill";
+                            "num_blocks" => num_blocks,
+                        ),
+                        Err(e) => error!(
+                            log,
+                            "Slasher backfill failed";
+                            "error" => format!("{:?}", e),
+                        ),
+                    }
+                },
+                "slasher_backfill",
+            );
+        }
+
+        SlasherServer::run(slasher, slot_clock, on_update, &context.executor);
         Ok(())
     }
 
@@ -537,6 +777,36 @@ where
             .build()
             .map_err(|e| format!("Failed to build beacon chain: {}", e))?;
 
+        // Check that there is room to sync the chain up to the current slot.
+        let store_config = chain.store.get_config();
+        if let (Some(hot_path), Some(cold_path)) = (&self.db_path, &self.freezer_db_path) {
+            if store_config.backend != DatabaseBackend::Memory && !store_config.read_only {
+                let head_slot = chain
+                    .head_info()
+                    .map_err(|e| format!("Unable to read the head: {:?}", e))?
+                    .slot;
+                let slots_to_sync = chain
+                    .slot()
+                    .map_or(0, |slot| slot.saturating_sub(head_slot).as_u64());
+                let state_bytes = chain
+                    .with_head(|head| {
+                        Ok::<_, BeaconChainError>(head.beacon_state.ssz_bytes_len() as u64)
+                    })
+                    .map_err(|e| format!("Unable to read the head: {:?}", e))?;
+                let growth = chain.store.estimate_growth(slots_to_sync, state_bytes);
+
+                check_disk_space(
+                    &[
+                        (hot_path.as_path(), growth.hot_bytes),
+                        (cold_path.as_path(), growth.cold_bytes),
+                    ],
+                    "syncing the chain",
+                    store_config.check_disk_space,
+                    context.log(),
+                )?;
+            }
+        }
+
         self.beacon_chain = Some(Arc::new(chain));
         self.beacon_chain_builder = None;
         self.event_handler = None;
@@ -565,10 +835,12 @@ where
     TColdStore: ItemStore<TEthSpec> + 'static,
 {
     #[allow(clippy::type_complexity)]
-    /// Specifies that the `BeaconChain` should publish events using the WebSocket server.
+    /// Specifies that the `BeaconChain` should publish events using the WebSocket server, and
+    /// notify the slashing webhook if one is configured.
     pub fn tee_event_handler(
         mut self,
         config: WebSocketConfig,
+        slashing_webhook_config: Option<SlashingWebhookConfig>,
     ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
         let context = self
             .runtime_context
@@ -585,8 +857,19 @@ where
             (WebSocketSender::dummy(), None)
         };
 
+        let slashing_webhook = slashing_webhook_config
+            .map(|config| {
+                let context = self
+                    .runtime_context
+                    .as_ref()
+                    .ok_or_else(|| "tee_event_handler requires a runtime_context")?
+                    .service_context("slashing_webhook".into());
+                SlashingWebhook::new(config, context.executor, context.log().clone())
+            })
+            .transpose()?;
+
         self.websocket_listen_addr = listening_addr;
-        let (tee_event_handler, bus) = TeeEventHandler::new(log, sender)?;
+        let (tee_event_handler, bus) = TeeEventHandler::new(log, sender, slashing_webhook)?;
         self.event_handler = Some(tee_event_handler);
         Ok((self, bus))
     }
@@ -599,8 +882,8 @@ impl<TSlotClock, TEth1Backend, TEthSpec, TEventHandler>
             TEth1Backend,
             TEthSpec,
             TEventHandler,
-            LevelDB<TEthSpec>,
-            LevelDB<TEthSpec>,
+            BeaconNodeBackend<TEthSpec>,
+            BeaconNodeBackend<TEthSpec>,
         >,
     >
 where
@@ -629,8 +912,30 @@ where
         self.db_path = Some(hot_path.into());
         self.freezer_db_path = Some(cold_path.into());
 
-        let store = HotColdDB::open(hot_path, cold_path, config, spec, context.log().clone())
-            .map_err(|e| format!("Unable to open database: {:?}", e))?;
+        // Any schema migration is refused before it starts if there isn't room for it.
+        let enforce_disk_space = config.check_disk_space;
+        let log = context.log().clone();
+        let store = HotColdDB::open_with_migration_check(
+            hot_path,
+            cold_path,
+            config,
+            spec,
+            log.clone(),
+            |_, plan| {
+                if plan.steps.is_empty() {
+                    return Ok(());
+                }
+                check_disk_space(
+                    &[(hot_path, plan.estimated_bytes())],
+                    "the schema migration",
+                    enforce_disk_space,
+                    &log,
+                )
+                .map_err(|message| StoreError::DBError { message })
+            },
+        )
+        .map_err(|e| format!("Unable to open database: {:?}", e))?;
+
         self.store = Some(Arc::new(store));
         Ok(self)
     }
@@ -670,6 +975,7 @@ where
             .chain_spec
             .clone()
             .ok_or_else(|| "caching_eth1_backend requires a chain spec".to_string())?;
+        let deposit_snapshot = self.deposit_snapshot.take();
 
         let backend = if let Some(eth1_service_from_genesis) = self.eth1_service {
             eth1_service_from_genesis.update_config(config)?;
@@ -697,12 +1003,27 @@ where
                     )
                     .map(|chain| chain.into_backend())
                 })
-                .unwrap_or_else(|| {
-                    Ok(CachingEth1Backend::new(
+                .unwrap_or_else(|| match deposit_snapshot {
+                    Some(snapshot) => {
+                        info!(
+                            context.log(),
+                            "Initializing deposit cache from snapshot";
+                            "deposit_count" => snapshot.deposit_count,
+                            "eth1_block" => snapshot.execution_block_height,
+                        );
+                        Eth1Service::from_deposit_snapshot(
+                            config,
+                            context.log().clone(),
+                            spec.clone(),
+                            &snapshot,
+                        )
+                        .map(CachingEth1Backend::from_service)
+                    }
+                    None => Ok(CachingEth1Backend::new(
                         config,
                         context.log().clone(),
                         spec.clone(),
-                    ))
+                    )),
                 })?
         };
 
@@ -784,3 +1105,84 @@ where
         Ok(self)
     }
 }
+
+/// Download the SSZ-encoded genesis state from `url`, checking that its SHA256 hash matches
+/// `checksum`.
+async fn download_genesis_state(url: &str, checksum: Hash256) -> Result<Vec<u8>, String> {
+    let bytes = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Unable to download genesis state from {}: {:?}", url, e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Unable to download genesis state from {}: {:?}", url, e))?;
+
+    check_genesis_state_checksum(url, &bytes, checksum)?;
+    Ok(bytes.to_vec())
+}
+
+/// Check that the SHA256 hash of the genesis state `bytes` downloaded from `url` matches
+/// `checksum`.
+fn check_genesis_state_checksum(url: &str, bytes: &[u8], checksum: Hash256) -> Result<(), String> {
+    let actual_checksum = Hash256::from_slice(&hash(bytes));
+    if actual_checksum != checksum {
+        return Err(format!(
+            "Genesis state downloaded from {} has checksum {:?}, expected {:?}",
+            url, actual_checksum, checksum
+        ));
+    }
+    Ok(())
+}
+
+/// Add the new slashings found by the slasher to the op pool, and publish them on gossip.
+fn broadcast_slasher_slashings<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    network_send: &UnboundedSender<NetworkMessage<T::EthSpec>>,
+    log: &Logger,
+) {
+    let (attester_slashings, proposer_slashings) = beacon_chain.import_slasher_slashings();
+    if attester_slashings.is_empty() && proposer_slashings.is_empty() {
+        return;
+    }
+
+    info!(
+        log,
+        "Broadcasting slashings from slasher";
+        "num_attester_slashings" => attester_slashings.len(),
+        "num_proposer_slashings" => proposer_slashings.len(),
+    );
+
+    let messages = attester_slashings
+        .into_iter()
+        .map(|slashing| PubsubMessage::AttesterSlashing(Box::new(slashing)))
+        .chain(
+            proposer_slashings
+                .into_iter()
+                .map(|slashing| PubsubMessage::ProposerSlashing(Box::new(slashing))),
+        )
+        .collect();
+
+    if let Err(e) = network_send.send(NetworkMessage::Publish { messages }) {
+        warn!(
+            log,
+            "Unable to broadcast slashings";
+            "error" => format!("{:?}", e),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn genesis_state_checksum() {
+        let url = "https://example.com/genesis.ssz";
+        let bytes = [1, 2, 3];
+        let checksum = Hash256::from_slice(&hash(&bytes));
+
+        assert_eq!(check_genesis_state_checksum(url, &bytes, checksum), Ok(()));
+        assert!(check_genesis_state_checksum(url, &bytes[1..], checksum).is_err());
+        assert!(check_genesis_state_checksum(url, &bytes, Hash256::repeat_byte(1)).is_err());
+    }
+}
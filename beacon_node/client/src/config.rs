@@ -3,7 +3,7 @@ use network::NetworkConfig;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use types::Graffiti;
+use types::{Graffiti, Hash256};
 
 /// Default directory name for the freezer database under the top-level data dir.
 const DEFAULT_FREEZER_DB_DIR: &str = "freezer_db";
@@ -26,6 +26,27 @@ pub enum ClientGenesis {
     /// We include the bytes instead of the `BeaconState<E>` because the `EthSpec` type
     /// parameter would be very annoying.
     SszBytes { genesis_state_bytes: Vec<u8> },
+    /// Downloads the SSZ-encoded genesis state from `url`, checking that its SHA256 hash matches
+    /// `checksum`.
+    GenesisStateUrl { url: String, checksum: Hash256 },
+    /// Downloads the finalized state and block from the beacon node API at `url` and starts the
+    /// chain from that checkpoint.
+    ///
+    /// The genesis state is taken from `genesis_state_bytes` if known, otherwise it is also
+    /// downloaded from `url`.
+    CheckpointSyncUrl {
+        genesis_state_bytes: Option<Vec<u8>>,
+        url: String,
+    },
+    /// Starts the chain from a checkpoint state and block read from SSZ files, which were obtained
+    /// out-of-band.
+    ///
+    /// The genesis state must be known for the network, as there's nowhere to download it from.
+    CheckpointSyncFiles {
+        genesis_state_bytes: Option<Vec<u8>>,
+        anchor_state_bytes: Vec<u8>,
+        anchor_block_bytes: Vec<u8>,
+    },
 }
 
 impl Default for ClientGenesis {
@@ -56,6 +77,9 @@ pub struct Config {
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
     pub genesis: ClientGenesis,
+    /// If false, the node will refuse to start syncing from genesis when its database is empty,
+    /// requiring a checkpoint sync instead.
+    pub allow_insecure_genesis_sync: bool,
     pub store: store::StoreConfig,
     pub network: network::NetworkConfig,
     pub chain: beacon_chain::ChainConfig,
@@ -64,6 +88,7 @@ pub struct Config {
     pub http_api: http_api::Config,
     pub http_metrics: http_metrics::Config,
     pub slasher: Option<slasher::Config>,
+    pub slashing_webhook: Option<beacon_chain::slashing_webhook::Config>,
 }
 
 impl Default for Config {
@@ -74,6 +99,7 @@ impl Default for Config {
             freezer_db_path: None,
             log_file: PathBuf::from(""),
             genesis: <_>::default(),
+            allow_insecure_genesis_sync: false,
             store: <_>::default(),
             network: NetworkConfig::default(),
             chain: <_>::default(),
@@ -86,6 +112,7 @@ impl Default for Config {
             http_api: <_>::default(),
             http_metrics: <_>::default(),
             slasher: None,
+            slashing_webhook: None,
         }
     }
 }
@@ -0,0 +1,116 @@
+//! Checks that the filesystems holding the databases have room for the writes ahead, so that a
+//! sync or schema migration is refused up-front rather than failing part way through with a full
+//! disk.
+use eth2::lighthouse::MountInfo;
+use slog::{crit, debug, warn, Logger};
+use std::path::Path;
+
+/// Space which must remain free in addition to the estimated requirement, for the hot states,
+/// compactions and logs of normal operation.
+pub const DISK_SPACE_HEADROOM: u64 = 1024 * 1024 * 1024;
+
+/// Check that each filesystem holding one of `requirements`' paths has the sum of their required
+/// bytes free, plus `DISK_SPACE_HEADROOM`.
+///
+/// A shortfall is an error if `enforce` is set, and is otherwise logged as critical. If the free
+/// space of a path's filesystem can't be determined, a warning is logged and that path is skipped,
+/// while the others are still checked.
+pub fn check_disk_space(
+    requirements: &[(&Path, u64)],
+    purpose: &str,
+    enforce: bool,
+    log: &Logger,
+) -> Result<(), String> {
+    let mut mounts: Vec<(&Path, MountInfo, u64)> = vec![];
+    for &(path, required_bytes) in requirements {
+        let mount_info = match MountInfo::for_path(path) {
+            Ok(mount_info) => mount_info,
+            Err(e) => {
+                warn!(
+                    log,
+                    "Unable to check free disk space";
+                    "path" => ?path,
+                    "error" => e,
+                );
+                continue;
+            }
+        };
+
+        match mounts
+            .iter_mut()
+            .find(|(_, mount, _)| mount.device == mount_info.device)
+        {
+            Some((_, _, required)) => *required += required_bytes,
+            None => mounts.push((path, mount_info, required_bytes)),
+        }
+    }
+
+    for (path, mount_info, required_bytes) in mounts {
+        let required_bytes = required_bytes + DISK_SPACE_HEADROOM;
+        if mount_info.avail >= required_bytes {
+            debug!(
+                log,
+                "Sufficient disk space";
+                "purpose" => purpose,
+                "path" => ?path,
+                "free_mib" => to_mib(mount_info.avail),
+                "required_mib" => to_mib(required_bytes),
+            );
+            continue;
+        }
+
+        if enforce {
+            return Err(format!(
+                "Insufficient disk space for {}: {} MiB free on the filesystem holding {:?}, \
+                about {} MiB required. Free some space, or use --disable-disk-space-check to \
+                start anyway.",
+                purpose,
+                to_mib(mount_info.avail),
+                path,
+                to_mib(required_bytes)
+            ));
+        }
+        crit!(
+            log,
+            "Insufficient disk space";
+            "info" => "the database may be corrupted if the disk fills up",
+            "purpose" => purpose,
+            "path" => ?path,
+            "free_mib" => to_mib(mount_info.avail),
+            "required_mib" => to_mib(required_bytes),
+        );
+    }
+
+    Ok(())
+}
+
+fn to_mib(bytes: u64) -> u64 {
+    bytes / (1024 * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::{o, Discard};
+
+    fn logger() -> Logger {
+        Logger::root(Discard, o!())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn unreadable_mount_does_not_skip_the_others() {
+        // The empty path has no existing ancestor, so its filesystem can't be determined.
+        let requirements = [(Path::new(""), 0), (Path::new("/"), u64::max_value() / 2)];
+
+        assert!(check_disk_space(&requirements, "test", true, &logger()).is_err());
+        assert!(check_disk_space(&requirements, "test", false, &logger()).is_ok());
+    }
+
+    #[test]
+    fn unreadable_mount_passes() {
+        let requirements = [(Path::new(""), u64::max_value() / 2)];
+
+        assert!(check_disk_space(&requirements, "test", true, &logger()).is_ok());
+    }
+}
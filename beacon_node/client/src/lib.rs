@@ -1,6 +1,7 @@
 extern crate slog;
 
 pub mod config;
+mod disk_space;
 mod metrics;
 mod notifier;
 
@@ -3,7 +3,7 @@ use ssz_derive::{Decode, Encode};
 use state_processing::common::DepositDataTree;
 use std::cmp::Ordering;
 use tree_hash::TreeHash;
-use types::{Deposit, Hash256, DEPOSIT_TREE_DEPTH};
+use types::{Deposit, DepositTreeSnapshot, Hash256, DEPOSIT_TREE_DEPTH};
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -25,18 +25,46 @@ pub enum Error {
     ///
     /// E.g., you cannot request deposit 10 when the deposit count is 9.
     DepositCountInvalid { deposit_count: u64, range_end: u64 },
+    /// The requested deposits are covered by the snapshot the cache was created from, so their
+    /// proofs can't be generated.
+    DepositsFinalized {
+        finalized_deposit_count: u64,
+        range_start: u64,
+    },
     /// Error with the merkle tree for deposits.
     DepositTreeError(merkle_proof::MerkleTreeError),
     /// An unexpected condition was encountered.
     InternalError(String),
 }
 
+/// The encoding of a `SszDepositCache` prior to the addition of deposit tree snapshots.
+#[derive(Encode, Decode, Clone)]
+pub struct SszDepositCacheV1 {
+    logs: Vec<DepositLog>,
+    leaves: Vec<Hash256>,
+    deposit_contract_deploy_block: u64,
+    deposit_roots: Vec<Hash256>,
+}
+
+impl From<SszDepositCacheV1> for SszDepositCache {
+    fn from(cache: SszDepositCacheV1) -> Self {
+        Self {
+            logs: cache.logs,
+            leaves: cache.leaves,
+            deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
+            deposit_roots: cache.deposit_roots,
+            snapshot: None,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszDepositCache {
     logs: Vec<DepositLog>,
     leaves: Vec<Hash256>,
     deposit_contract_deploy_block: u64,
     deposit_roots: Vec<Hash256>,
+    snapshot: Option<DepositTreeSnapshot>,
 }
 
 impl SszDepositCache {
@@ -46,12 +74,36 @@ impl SszDepositCache {
             leaves: cache.leaves.clone(),
             deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
             deposit_roots: cache.deposit_roots.clone(),
+            snapshot: cache.snapshot.clone(),
         }
     }
 
     pub fn to_deposit_cache(&self) -> Result<DepositCache, String> {
-        let deposit_tree =
-            DepositDataTree::create(&self.leaves, self.leaves.len(), DEPOSIT_TREE_DEPTH);
+        let mut deposit_tree = match &self.snapshot {
+            Some(snapshot) => {
+                if !snapshot.is_valid() {
+                    return Err("Invalid SszDepositCache: snapshot root is invalid".into());
+                }
+                if self.deposit_roots.first() != Some(&snapshot.deposit_root) {
+                    return Err(
+                        "Invalid SszDepositCache: deposit_roots must start at the snapshot root"
+                            .into(),
+                    );
+                }
+                DepositDataTree::from_finalized_snapshot(
+                    &snapshot.finalized,
+                    snapshot.deposit_count as usize,
+                    DEPOSIT_TREE_DEPTH,
+                )
+                .map_err(|e| format!("Invalid SszDepositCache: invalid snapshot: {:?}", e))?
+            }
+            None => DepositDataTree::create(&[], 0, DEPOSIT_TREE_DEPTH),
+        };
+        for leaf in &self.leaves {
+            deposit_tree
+                .push_leaf(*leaf)
+                .map_err(|e| format!("Invalid SszDepositCache: unable to push leaf: {:?}", e))?;
+        }
         // Check for invalid SszDepositCache conditions
         if self.leaves.len() != self.logs.len() {
             return Err("Invalid SszDepositCache: logs and leaves should have equal length".into());
@@ -69,6 +121,7 @@ impl SszDepositCache {
             deposit_contract_deploy_block: self.deposit_contract_deploy_block,
             deposit_tree,
             deposit_roots: self.deposit_roots.clone(),
+            snapshot: self.snapshot.clone(),
         })
     }
 }
@@ -84,8 +137,13 @@ pub struct DepositCache {
     /// deposit contract tree.
     deposit_tree: DepositDataTree,
     /// Vector of deposit roots. `deposit_roots[i]` denotes `deposit_root` at
-    /// `deposit_index` `i`.
+    /// `deposit_index` `i + self.finalized_deposit_count()`.
     deposit_roots: Vec<Hash256>,
+    /// The snapshot this cache was created from, if any.
+    ///
+    /// Only the subtree roots of the deposits covered by the snapshot are known, so `logs` and
+    /// `leaves` start at the snapshot's `deposit_count`.
+    snapshot: Option<DepositTreeSnapshot>,
 }
 
 impl Default for DepositCache {
@@ -98,6 +156,7 @@ impl Default for DepositCache {
             deposit_contract_deploy_block: 1,
             deposit_tree,
             deposit_roots,
+            snapshot: None,
         }
     }
 }
@@ -112,6 +171,86 @@ impl DepositCache {
         }
     }
 
+    /// Create a new `DepositCache` from a snapshot of the deposit contract tree, so that only the
+    /// deposits made after the snapshot need to be downloaded.
+    ///
+    /// Proofs can only be generated for deposits made after the snapshot.
+    pub fn from_deposit_snapshot(
+        deposit_contract_deploy_block: u64,
+        snapshot: &DepositTreeSnapshot,
+    ) -> Result<Self, String> {
+        if !snapshot.is_valid() {
+            return Err(format!(
+                "Deposit snapshot root {:?} does not match its finalized hashes",
+                snapshot.deposit_root
+            ));
+        }
+        let deposit_tree = DepositDataTree::from_finalized_snapshot(
+            &snapshot.finalized,
+            snapshot.deposit_count as usize,
+            DEPOSIT_TREE_DEPTH,
+        )
+        .map_err(|e| format!("Invalid deposit snapshot: {:?}", e))?;
+
+        Ok(DepositCache {
+            logs: Vec::new(),
+            leaves: Vec::new(),
+            deposit_contract_deploy_block,
+            deposit_roots: vec![deposit_tree.root()],
+            deposit_tree,
+            snapshot: Some(snapshot.clone()),
+        })
+    }
+
+    /// Returns the snapshot this cache was created from, if any.
+    pub fn snapshot(&self) -> Option<&DepositTreeSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the number of deposits covered by the snapshot this cache was created from, which
+    /// is the index of the first log in the cache.
+    pub fn finalized_deposit_count(&self) -> u64 {
+        self.snapshot
+            .as_ref()
+            .map_or(0, |snapshot| snapshot.deposit_count)
+    }
+
+    /// Returns a snapshot of the deposit tree at `deposit_count`, which must be the number of
+    /// deposits in the deposit contract at the eth1 block with the given hash and height.
+    pub fn get_deposit_snapshot(
+        &self,
+        deposit_count: u64,
+        execution_block_hash: Hash256,
+        execution_block_height: u64,
+    ) -> Result<DepositTreeSnapshot, Error> {
+        let finalized_deposit_count = self.finalized_deposit_count();
+        if deposit_count < finalized_deposit_count {
+            return Err(Error::DepositsFinalized {
+                finalized_deposit_count,
+                range_start: deposit_count,
+            });
+        }
+        let deposit_root = *self
+            .deposit_roots
+            .get((deposit_count - finalized_deposit_count) as usize)
+            .ok_or_else(|| Error::InsufficientDeposits {
+                known_deposits: self.logs.len(),
+                requested: deposit_count,
+            })?;
+        let finalized = self
+            .deposit_tree
+            .get_finalized_hashes(deposit_count as usize)
+            .map_err(Error::DepositTreeError)?;
+
+        Ok(DepositTreeSnapshot {
+            finalized,
+            deposit_root,
+            deposit_count,
+            execution_block_hash,
+            execution_block_height,
+        })
+    }
+
     /// Returns the number of deposits available in the cache.
     pub fn len(&self) -> usize {
         self.logs.len()
@@ -140,14 +279,20 @@ impl DepositCache {
     /// Adds `log` to self.
     ///
     /// This function enforces that `logs` are imported one-by-one with no gaps between
-    /// `log.index`, starting at `log.index == 0`.
+    /// `log.index`, starting at `log.index == self.finalized_deposit_count()`. Logs of deposits
+    /// covered by the snapshot the cache was created from are ignored.
     ///
     /// ## Errors
     ///
     /// - If a log with index `log.index - 1` is not already present in `self` (ignored when empty).
     /// - If a log with `log.index` is already known, but the given `log` is distinct to it.
     pub fn insert_log(&mut self, log: DepositLog) -> Result<(), Error> {
-        match log.index.cmp(&(self.logs.len() as u64)) {
+        let finalized_deposit_count = self.finalized_deposit_count();
+        if log.index < finalized_deposit_count {
+            return Ok(());
+        }
+        let next_index = finalized_deposit_count + self.logs.len() as u64;
+        match log.index.cmp(&next_index) {
             Ordering::Equal => {
                 let deposit = log.deposit_data.tree_hash_root();
                 self.leaves.push(deposit);
@@ -159,7 +304,7 @@ impl DepositCache {
                 Ok(())
             }
             Ordering::Less => {
-                if self.logs[log.index as usize] == log {
+                if self.logs[(log.index - finalized_deposit_count) as usize] == log {
                     Ok(())
                 } else {
                     Err(Error::DuplicateDistinctLog(log.index))
@@ -167,7 +312,7 @@ impl DepositCache {
             }
             Ordering::Greater => Err(Error::NonConsecutive {
                 log_index: log.index,
-                expected: self.logs.len(),
+                expected: next_index as usize,
             }),
         }
     }
@@ -183,6 +328,7 @@ impl DepositCache {
     ///
     /// - If `deposit_count` is larger than `end`.
     /// - There are not sufficient deposits in the tree to generate the proof.
+    /// - If `start` is covered by the snapshot the cache was created from.
     pub fn get_deposits(
         &self,
         start: u64,
@@ -190,29 +336,37 @@ impl DepositCache {
         deposit_count: u64,
         tree_depth: usize,
     ) -> Result<(Hash256, Vec<Deposit>), Error> {
+        let finalized_deposit_count = self.finalized_deposit_count();
+        let known_deposits = finalized_deposit_count + self.logs.len() as u64;
         if deposit_count < end {
             // It's invalid to ask for more deposits than should exist.
             Err(Error::DepositCountInvalid {
                 deposit_count,
                 range_end: end,
             })
-        } else if end > self.logs.len() as u64 {
+        } else if end > known_deposits {
             // The range of requested deposits exceeds the deposits stored locally.
             Err(Error::InsufficientDeposits {
                 requested: end,
-                known_deposits: self.logs.len(),
+                known_deposits: known_deposits as usize,
             })
-        } else if deposit_count > self.leaves.len() as u64 {
+        } else if deposit_count > known_deposits {
             // There are not `deposit_count` known deposit roots, so we can't build the merkle tree
             // to prove into.
             Err(Error::InsufficientDeposits {
                 requested: deposit_count,
-                known_deposits: self.logs.len(),
+                known_deposits: known_deposits as usize,
+            })
+        } else if start < finalized_deposit_count {
+            // Only the subtree roots of the finalized deposits are known, so they can't be proven.
+            Err(Error::DepositsFinalized {
+                finalized_deposit_count,
+                range_start: start,
             })
         } else {
             let leaves = self
                 .leaves
-                .get(0..deposit_count as usize)
+                .get(0..(deposit_count - finalized_deposit_count) as usize)
                 .ok_or_else(|| Error::InternalError("Unable to get known leaves".into()))?;
 
             // Note: there is likely a more optimal solution than recreating the `DepositDataTree`
@@ -222,11 +376,28 @@ impl DepositCache {
             // last finalized eth1 deposit count. Then, that tree could be cloned and extended for
             // each of these calls.
 
-            let tree = DepositDataTree::create(leaves, deposit_count as usize, tree_depth);
+            let tree = match &self.snapshot {
+                Some(snapshot) => {
+                    let mut tree = DepositDataTree::from_finalized_snapshot(
+                        &snapshot.finalized,
+                        finalized_deposit_count as usize,
+                        tree_depth,
+                    )
+                    .map_err(Error::DepositTreeError)?;
+                    for leaf in leaves {
+                        tree.push_leaf(*leaf).map_err(Error::DepositTreeError)?;
+                    }
+                    tree
+                }
+                None => DepositDataTree::create(leaves, deposit_count as usize, tree_depth),
+            };
 
             let deposits = self
                 .logs
-                .get(start as usize..end as usize)
+                .get(
+                    (start - finalized_deposit_count) as usize
+                        ..(end - finalized_deposit_count) as usize,
+                )
                 .ok_or_else(|| Error::InternalError("Unable to get known log".into()))?
                 .iter()
                 .map(|deposit_log| {
@@ -264,27 +435,40 @@ impl DepositCache {
     /// Returns the number of deposits that have been observed up to and
     /// including the block at `block_number`.
     ///
-    /// Returns `None` if the `block_number` is zero or prior to contract deployment.
+    /// Returns `None` if the `block_number` is zero, prior to contract deployment or prior to the
+    /// snapshot the cache was created from.
     pub fn get_deposit_count_from_cache(&self, block_number: u64) -> Option<u64> {
         if block_number == 0 || block_number < self.deposit_contract_deploy_block {
             None
+        } else if let Some(snapshot) = &self.snapshot {
+            if block_number < snapshot.execution_block_height {
+                None
+            } else {
+                Some(snapshot.deposit_count + self.count_logs_up_to(block_number))
+            }
         } else {
-            Some(
-                self.logs
-                    .iter()
-                    .take_while(|deposit| deposit.block_number <= block_number)
-                    .count() as u64,
-            )
+            Some(self.count_logs_up_to(block_number))
         }
     }
 
+    fn count_logs_up_to(&self, block_number: u64) -> u64 {
+        self.logs
+            .iter()
+            .take_while(|deposit| deposit.block_number <= block_number)
+            .count() as u64
+    }
+
     /// Gets the deposit root at block height = block_number.
     ///
     /// Fetches the `deposit_count` on or just before the queried `block_number`
     /// and queries the `deposit_roots` map to get the corresponding `deposit_root`.
     pub fn get_deposit_root_from_cache(&self, block_number: u64) -> Option<Hash256> {
         let index = self.get_deposit_count_from_cache(block_number)?;
-        Some(*self.deposit_roots.get(index as usize)?)
+        Some(
+            *self
+                .deposit_roots
+                .get((index - self.finalized_deposit_count()) as usize)?,
+        )
     }
 }
 
@@ -444,4 +628,115 @@ pub mod tests {
         // Range higher than count.
         assert!(tree.get_deposits(0, 4, 2, TREE_DEPTH).is_err());
     }
+
+    #[test]
+    fn from_deposit_snapshot() {
+        let n = 64;
+        let finalized = 21;
+        let logs: Vec<_> = (0..n)
+            .map(|i| {
+                let mut log = example_log();
+                log.index = i;
+                log.block_number = i + 1;
+                log.deposit_data.withdrawal_credentials = Hash256::from_low_u64_be(i);
+                log
+            })
+            .collect();
+
+        let mut full_cache = DepositCache::default();
+        for log in &logs {
+            full_cache
+                .insert_log(log.clone())
+                .expect("should add consecutive logs");
+        }
+
+        let block_height = logs[finalized as usize - 1].block_number;
+        let snapshot = full_cache
+            .get_deposit_snapshot(finalized, Hash256::repeat_byte(42), block_height)
+            .expect("should get snapshot");
+        assert!(snapshot.is_valid(), "snapshot should be valid");
+        assert_eq!(
+            Some(snapshot.deposit_root),
+            full_cache.get_deposit_root_from_cache(block_height)
+        );
+
+        let mut cache =
+            DepositCache::from_deposit_snapshot(1, &snapshot).expect("should restore snapshot");
+        assert_eq!(cache.finalized_deposit_count(), finalized);
+        assert_eq!(cache.get_deposit_count_from_cache(block_height - 1), None);
+        assert_eq!(
+            cache.get_deposit_root_from_cache(block_height),
+            Some(snapshot.deposit_root)
+        );
+
+        // Logs of finalized deposits are ignored, the remainder must be consecutive.
+        for log in &logs {
+            cache
+                .insert_log(log.clone())
+                .expect("should add consecutive logs");
+        }
+        assert_eq!(cache.len(), (n - finalized) as usize);
+
+        for block_number in block_height..=n {
+            assert_eq!(
+                cache.get_deposit_root_from_cache(block_number),
+                full_cache.get_deposit_root_from_cache(block_number),
+                "should have the same root at block {}",
+                block_number
+            );
+        }
+        assert_eq!(
+            cache.get_deposits(finalized, n, n, TREE_DEPTH),
+            full_cache.get_deposits(finalized, n, n, TREE_DEPTH),
+            "should produce the same deposits and proofs"
+        );
+        assert_eq!(
+            cache.get_deposits(finalized, finalized + 4, n - 7, TREE_DEPTH),
+            full_cache.get_deposits(finalized, finalized + 4, n - 7, TREE_DEPTH),
+            "should produce the same deposits and proofs for a smaller deposit count"
+        );
+        assert_eq!(
+            cache.get_deposits(finalized - 1, n, n, TREE_DEPTH),
+            Err(Error::DepositsFinalized {
+                finalized_deposit_count: finalized,
+                range_start: finalized - 1,
+            })
+        );
+
+        // Snapshots can be taken of the restored cache at or after its own snapshot.
+        assert_eq!(
+            cache.get_deposit_snapshot(n, Hash256::zero(), n),
+            full_cache.get_deposit_snapshot(n, Hash256::zero(), n)
+        );
+        assert!(cache
+            .get_deposit_snapshot(finalized - 1, Hash256::zero(), 0)
+            .is_err());
+
+        // The snapshot survives persistence.
+        let restored = SszDepositCache::from_deposit_cache(&cache)
+            .to_deposit_cache()
+            .expect("should decode the cache");
+        assert_eq!(restored.snapshot(), Some(&snapshot));
+        assert_eq!(
+            restored.get_deposits(finalized, n, n, TREE_DEPTH),
+            full_cache.get_deposits(finalized, n, n, TREE_DEPTH),
+        );
+    }
+
+    #[test]
+    fn from_invalid_deposit_snapshot() {
+        let mut cache = DepositCache::default();
+        for i in 0..8 {
+            let mut log = example_log();
+            log.index = i;
+            log.block_number = i + 1;
+            cache.insert_log(log).expect("should add consecutive logs");
+        }
+
+        let mut snapshot = cache
+            .get_deposit_snapshot(5, Hash256::zero(), 5)
+            .expect("should get snapshot");
+        snapshot.deposit_root = Hash256::repeat_byte(1);
+        assert!(DepositCache::from_deposit_snapshot(1, &snapshot).is_err());
+    }
 }
@@ -1,12 +1,12 @@
 use crate::Config;
 use crate::{
     block_cache::{BlockCache, Eth1Block},
-    deposit_cache::{DepositCache, SszDepositCache},
+    deposit_cache::{DepositCache, SszDepositCache, SszDepositCacheV1},
 };
 use parking_lot::RwLock;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
-use types::ChainSpec;
+use types::{ChainSpec, DepositTreeSnapshot};
 
 #[derive(Default)]
 pub struct DepositUpdater {
@@ -22,6 +22,19 @@ impl DepositUpdater {
             last_processed_block: None,
         }
     }
+
+    /// Create a `DepositUpdater` whose cache starts from `snapshot`, so that deposit logs are only
+    /// downloaded from the block following the snapshot.
+    pub fn from_snapshot(
+        deposit_contract_deploy_block: u64,
+        snapshot: &DepositTreeSnapshot,
+    ) -> Result<Self, String> {
+        let cache = DepositCache::from_deposit_snapshot(deposit_contract_deploy_block, snapshot)?;
+        Ok(DepositUpdater {
+            cache,
+            last_processed_block: Some(snapshot.execution_block_height),
+        })
+    }
 }
 
 #[derive(Default)]
@@ -50,8 +63,15 @@ impl Inner {
     }
 
     /// Recover `Inner` given byte representation of eth1 deposit and block caches.
+    ///
+    /// Caches persisted prior to the addition of deposit tree snapshots are also accepted.
     pub fn from_bytes(bytes: &[u8], config: Config, spec: ChainSpec) -> Result<Self, String> {
         let ssz_cache = SszEth1Cache::from_ssz_bytes(bytes)
+            .or_else(|e| {
+                SszEth1CacheV1::from_ssz_bytes(bytes)
+                    .map(Into::into)
+                    .map_err(|_| e)
+            })
             .map_err(|e| format!("Ssz decoding error: {:?}", e))?;
         Ok(ssz_cache.to_inner(config, spec)?)
     }
@@ -62,6 +82,24 @@ impl Inner {
     }
 }
 
+/// The encoding of a `SszEth1Cache` prior to the addition of deposit tree snapshots.
+#[derive(Encode, Decode, Clone)]
+pub struct SszEth1CacheV1 {
+    block_cache: BlockCache,
+    deposit_cache: SszDepositCacheV1,
+    last_processed_block: Option<u64>,
+}
+
+impl From<SszEth1CacheV1> for SszEth1Cache {
+    fn from(cache: SszEth1CacheV1) -> Self {
+        Self {
+            block_cache: cache.block_cache,
+            deposit_cache: cache.deposit_cache.into(),
+            last_processed_block: cache.last_processed_block,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszEth1Cache {
     block_cache: BlockCache,
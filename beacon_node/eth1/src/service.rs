@@ -20,7 +20,7 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock as TRwLock;
 use tokio::time::{interval_at, Duration, Instant};
-use types::{ChainSpec, EthSpec, Unsigned};
+use types::{ChainSpec, DepositTreeSnapshot, Eth1Data, EthSpec, Unsigned};
 
 /// Indicates the default eth1 network id we use for the deposit contract.
 pub const DEFAULT_NETWORK_ID: Eth1Id = Eth1Id::Goerli;
@@ -419,6 +419,72 @@ impl Service {
         }
     }
 
+    /// Creates a new service whose deposit cache starts from `snapshot`, so that only the deposits
+    /// made after it need to be downloaded. Does not attempt to connect to the eth1 node.
+    pub fn from_deposit_snapshot(
+        config: Config,
+        log: Logger,
+        spec: ChainSpec,
+        snapshot: &DepositTreeSnapshot,
+    ) -> Result<Self, String> {
+        let deposit_updater =
+            DepositUpdater::from_snapshot(config.deposit_contract_deploy_block, snapshot)?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                block_cache: <_>::default(),
+                deposit_cache: RwLock::new(deposit_updater),
+                remote_head_block: RwLock::new(None),
+                config: RwLock::new(config),
+                spec,
+            }),
+            log,
+        })
+    }
+
+    /// Returns a snapshot of the deposit tree at the eth1 block voted for in `eth1_data`.
+    ///
+    /// The block must be in the block cache, or be the block of the snapshot the deposit cache
+    /// was created from.
+    pub fn get_deposit_snapshot(
+        &self,
+        eth1_data: &Eth1Data,
+    ) -> Result<DepositTreeSnapshot, String> {
+        let cached_block_number = self
+            .blocks()
+            .read()
+            .iter()
+            .rev()
+            .find(|block| block.hash == eth1_data.block_hash)
+            .map(|block| block.number);
+        let deposits = self.deposits().read();
+        let block_number = cached_block_number
+            .or_else(|| {
+                deposits
+                    .cache
+                    .snapshot()
+                    .filter(|snapshot| snapshot.execution_block_hash == eth1_data.block_hash)
+                    .map(|snapshot| snapshot.execution_block_height)
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Eth1 block {:?} is not in the block cache",
+                    eth1_data.block_hash
+                )
+            })?;
+
+        let snapshot = deposits
+            .cache
+            .get_deposit_snapshot(eth1_data.deposit_count, eth1_data.block_hash, block_number)
+            .map_err(|e| format!("Unable to get deposit snapshot: {:?}", e))?;
+        if snapshot.deposit_root != eth1_data.deposit_root {
+            return Err(format!(
+                "Deposit root {:?} does not match the cached root {:?}",
+                eth1_data.deposit_root, snapshot.deposit_root
+            ));
+        }
+        Ok(snapshot)
+    }
+
     /// Returns the follow distance that has been shortened to accommodate for differences in the
     /// spacing between blocks.
     ///
@@ -39,6 +39,7 @@ use std::{
     marker::PhantomData,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use types::{ChainSpec, EnrForkId, EthSpec, SignedBeaconBlock, Slot, SubnetId};
 
@@ -481,6 +482,16 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         self.peer_manager.goodbye_peer(peer_id, reason);
     }
 
+    /// Bans a peer at the request of the user, optionally for a limited duration.
+    pub fn ban_peer_manually(&mut self, peer_id: &PeerId, duration: Option<Duration>) {
+        self.peer_manager.ban_peer_manually(peer_id, duration);
+    }
+
+    /// Lifts a ban on a peer at the request of the user.
+    pub fn unban_peer_manually(&mut self, peer_id: &PeerId) -> Result<(), &'static str> {
+        self.peer_manager.unban_peer_manually(peer_id)
+    }
+
     /// Returns an iterator over all enr entries in the DHT.
     pub fn enr_entries(&mut self) -> Vec<Enr> {
         self.peer_manager.discovery_mut().table_entries_enr()
@@ -622,6 +633,13 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
                 match PubsubMessage::decode(&gs_msg.topic, gs_msg.data()) {
                     Err(e) => {
                         debug!(self.log, "Could not decode gossipsub message"; "error" => e);
+                        let topic = GossipTopic::decode(gs_msg.topic.as_str())
+                            .map(|topic| topic.kind().to_string())
+                            .unwrap_or_else(|_| "unknown".to_string());
+                        metrics::inc_counter_vec(
+                            &metrics::GOSSIP_DECODE_FAILURES_PER_TOPIC,
+                            &[&topic],
+                        );
                         //reject the message
                         if let Err(e) = self.gossipsub.report_message_validation_result(
                             &id,
@@ -986,6 +1004,12 @@ impl<TSpec: EthSpec> NetworkBehaviour for Behaviour<TSpec> {
             // If we are at our peer limit and we don't need the peer for a future validator
             // duty, send goodbye with reason TooManyPeers
             Some(GoodbyeReason::TooManyPeers)
+        } else if self
+            .peer_manager
+            .ip_limit_reached(peer_id, endpoint.get_remote_address())
+        {
+            // Too many of our peers already share this peer's IP address or subnet.
+            Some(GoodbyeReason::TooManyPeers)
         } else {
             None
         };
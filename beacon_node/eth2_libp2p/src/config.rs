@@ -1,3 +1,4 @@
+use crate::ip_filter::IpFilter;
 use crate::types::{GossipKind, MessageData};
 use crate::{Enr, PeerIdSerialized};
 use directory::{
@@ -49,9 +50,31 @@ pub struct Config {
     /// The tcp port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp_port: Option<u16>,
 
+    /// The IPv6 udp port to broadcast to peers in order to reach back for discovery.
+    pub enr_udp6_port: Option<u16>,
+
+    /// The IPv6 tcp port to broadcast to peers in order to reach back for libp2p services.
+    pub enr_tcp6_port: Option<u16>,
+
+    /// Additional key-value pairs to advertise in the local ENR.
+    pub enr_custom_fields: Vec<(String, Vec<u8>)>,
+
+    /// A SOCKS5 proxy through which all outbound libp2p connections are dialled.
+    pub socks5_proxy: Option<std::net::SocketAddr>,
+
+    /// IP ranges that inbound and outbound connections are restricted to or refused from.
+    pub ip_filter: IpFilter,
+
     /// Target number of connected peers.
     pub target_peers: usize,
 
+    /// Maximum number of connected peers sharing a single IP address. 0 disables the limit.
+    pub max_peers_per_ip: usize,
+
+    /// Maximum number of connected peers within a single /24 (IPv4) or /64 (IPv6) subnet. 0
+    /// disables the limit.
+    pub max_peers_per_subnet: usize,
+
     /// Gossipsub configuration parameters.
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
@@ -88,6 +111,10 @@ pub struct Config {
     /// runtime.
     pub import_all_attestations: bool,
 
+    /// Run in a minimal configuration which is only sufficient for publishing blocks: no
+    /// attestation subnets are joined and block ranges are not served to peers.
+    pub proposer_only: bool,
+
     /// Indicates if the user has set the network to be in private mode. Currently this
     /// prevents sending client identifying information over identify.
     pub private: bool,
@@ -182,7 +209,14 @@ impl Default for Config {
             enr_address: None,
             enr_udp_port: None,
             enr_tcp_port: None,
+            enr_udp6_port: None,
+            enr_tcp6_port: None,
+            enr_custom_fields: vec![],
+            socks5_proxy: None,
+            ip_filter: IpFilter::default(),
             target_peers: 50,
+            max_peers_per_ip: 5,
+            max_peers_per_subnet: 10,
             gs_config,
             discv5_config,
             boot_nodes_enr: vec![],
@@ -195,6 +229,7 @@ impl Default for Config {
             private: false,
             subscribe_all_subnets: false,
             import_all_attestations: false,
+            proposer_only: false,
             topics: Vec::new(),
         }
     }
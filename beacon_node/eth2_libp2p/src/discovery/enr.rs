@@ -21,6 +21,19 @@ use types::{EnrForkId, EthSpec};
 pub const ETH2_ENR_KEY: &str = "eth2";
 /// The ENR field specifying the subnet bitfield.
 pub const BITFIELD_ENR_KEY: &str = "attnets";
+/// ENR keys that are managed by Lighthouse or discv5 and cannot be set as custom fields.
+pub const RESERVED_ENR_KEYS: [&str; 10] = [
+    "id",
+    "secp256k1",
+    "ip",
+    "ip6",
+    "tcp",
+    "tcp6",
+    "udp",
+    "udp6",
+    ETH2_ENR_KEY,
+    BITFIELD_ENR_KEY,
+];
 
 /// Extension trait for ENR's within Eth2.
 pub trait Eth2Enr {
@@ -131,10 +144,19 @@ pub fn create_enr_builder_from_config<T: EnrKey>(
     if let Some(udp_port) = config.enr_udp_port {
         builder.udp(udp_port);
     }
+    if let Some(udp6_port) = config.enr_udp6_port {
+        builder.udp6(udp6_port);
+    }
     // we always give it our listening tcp port
     if enable_tcp {
         let tcp_port = config.enr_tcp_port.unwrap_or_else(|| config.libp2p_port);
         builder.tcp(tcp_port);
+        if let Some(tcp6_port) = config.enr_tcp6_port {
+            builder.tcp6(tcp6_port);
+        }
+    }
+    for (key, value) in config.enr_custom_fields.iter() {
+        builder.add_value(key, value);
     }
     builder
 }
@@ -165,8 +187,14 @@ pub fn build_enr<T: EthSpec>(
 fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
     // take preference over disk_enr address if one is not specified
     (local_enr.ip().is_none() || local_enr.ip() == disk_enr.ip())
+        && (local_enr.ip6().is_none() || local_enr.ip6() == disk_enr.ip6())
         // tcp ports must match
         && local_enr.tcp() == disk_enr.tcp()
+        && local_enr.tcp6() == disk_enr.tcp6()
+        // take preference over disk udp6 port if one is not specified
+        && (local_enr.udp6().is_none() || local_enr.udp6() == disk_enr.udp6())
+        // custom fields must match, including any that have since been removed
+        && custom_fields(local_enr).eq(custom_fields(disk_enr))
         // must match on the same fork
         && local_enr.get(ETH2_ENR_KEY) == disk_enr.get(ETH2_ENR_KEY)
         // take preference over disk udp port if one is not specified
@@ -176,6 +204,15 @@ fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
         && local_enr.get(BITFIELD_ENR_KEY) == disk_enr.get(BITFIELD_ENR_KEY)
 }
 
+/// Returns the key-value pairs of an ENR that are not in `RESERVED_ENR_KEYS`.
+fn custom_fields(enr: &Enr) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+    enr.iter().filter(|(key, _)| {
+        !RESERVED_ENR_KEYS
+            .iter()
+            .any(|k| k.as_bytes() == key.as_slice())
+    })
+}
+
 /// Saves an ENR to disk
 pub fn save_enr_to_disk(dir: &Path, enr: &Enr, log: &slog::Logger) {
     let _ = std::fs::create_dir_all(dir);
@@ -193,3 +230,76 @@ pub fn save_enr_to_disk(dir: &Path, enr: &Enr, log: &slog::Logger) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    fn build_enr_with_config(enr_key: &CombinedKey, config: &NetworkConfig) -> Enr {
+        build_enr::<E>(enr_key, config, EnrForkId::default()).unwrap()
+    }
+
+    #[test]
+    fn test_ipv6_ports_and_custom_fields() {
+        let enr_key = CombinedKey::generate_secp256k1();
+        let mut config = NetworkConfig::default();
+        config.enr_udp6_port = Some(9001);
+        config.enr_tcp6_port = Some(9002);
+        config.enr_custom_fields = vec![
+            ("client".to_string(), b"lighthouse".to_vec()),
+            ("raw".to_string(), vec![1, 2]),
+        ];
+
+        let enr = build_enr_with_config(&enr_key, &config);
+
+        assert_eq!(enr.udp6(), Some(9001));
+        assert_eq!(enr.tcp6(), Some(9002));
+        assert_eq!(
+            enr.get("client").map(|v| v.to_vec()),
+            Some(b"lighthouse".to_vec())
+        );
+        assert_eq!(enr.get("raw").map(|v| v.to_vec()), Some(vec![1, 2]));
+        assert_eq!(custom_fields(&enr).count(), 2);
+    }
+
+    #[test]
+    fn test_compare_enr_custom_fields_and_ipv6_ports() {
+        let enr_key = CombinedKey::generate_secp256k1();
+        let mut config = NetworkConfig::default();
+        config.enr_tcp6_port = Some(9002);
+        config.enr_custom_fields = vec![("client".to_string(), b"lighthouse".to_vec())];
+        let disk_enr = build_enr_with_config(&enr_key, &config);
+
+        assert!(compare_enr(
+            &build_enr_with_config(&enr_key, &config),
+            &disk_enr
+        ));
+
+        // A removed custom field requires a new ENR.
+        let mut removed_field = config.clone();
+        removed_field.enr_custom_fields = vec![];
+        assert!(!compare_enr(
+            &build_enr_with_config(&enr_key, &removed_field),
+            &disk_enr
+        ));
+
+        // A changed custom field requires a new ENR.
+        let mut changed_field = config.clone();
+        changed_field.enr_custom_fields = vec![("client".to_string(), b"other".to_vec())];
+        assert!(!compare_enr(
+            &build_enr_with_config(&enr_key, &changed_field),
+            &disk_enr
+        ));
+
+        // A changed IPv6 TCP port requires a new ENR.
+        let mut changed_port = config;
+        changed_port.enr_tcp6_port = Some(9003);
+        assert!(!compare_enr(
+            &build_enr_with_config(&enr_key, &changed_port),
+            &disk_enr
+        ));
+    }
+}
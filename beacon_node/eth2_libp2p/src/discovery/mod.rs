@@ -3,10 +3,14 @@ pub(crate) mod enr;
 pub mod enr_ext;
 
 // Allow external use of the lighthouse ENR builder
-pub use enr::{build_enr, create_enr_builder_from_config, use_or_load_enr, CombinedKey, Eth2Enr};
+pub use enr::{
+    build_enr, create_enr_builder_from_config, use_or_load_enr, CombinedKey, Eth2Enr,
+    RESERVED_ENR_KEYS,
+};
 pub use enr_ext::{peer_id_to_node_id, CombinedKeyExt, EnrExt};
 pub use libp2p::core::identity::{Keypair, PublicKey};
 
+use crate::types::{BucketInfo, DiscoveryInfo, QueryStats, TableEntryInfo};
 use crate::{config, metrics};
 use crate::{error, Enr, NetworkConfig, NetworkGlobals, SubnetDiscovery};
 use discv5::{enr::NodeId, Discv5, Discv5Event};
@@ -19,7 +23,7 @@ use slog::{crit, debug, error, info, warn};
 use ssz::{Decode, Encode};
 use ssz_types::BitVector;
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     net::{IpAddr, SocketAddr},
     path::Path,
     pin::Pin,
@@ -53,6 +57,9 @@ const FIND_NODE_QUERY_CLOSEST_PEERS: usize = 16;
 /// The threshold for updating `min_ttl` on a connected peer.
 const DURATION_DIFFERENCE: Duration = Duration::from_millis(1);
 
+/// The number of bits in a node id, which bounds the log2 distance between two nodes.
+const NODE_ID_BITS: u64 = 256;
+
 /// The events emitted by polling discovery.
 pub enum DiscoveryEvent {
     /// A query has completed. This result contains a mapping of discovered peer IDs to the `min_ttl`
@@ -85,6 +92,16 @@ enum GroupedQueryType {
     FindPeers,
 }
 
+impl GroupedQueryType {
+    /// The label used for this type of query in metrics.
+    fn metrics_label(&self) -> &'static str {
+        match self {
+            Self::Subnet(_) => "subnet",
+            Self::FindPeers => "find_peers",
+        }
+    }
+}
+
 impl QueryType {
     /// Returns true if this query has expired.
     pub fn expired(&self) -> bool {
@@ -153,6 +170,9 @@ pub struct Discovery<TSpec: EthSpec> {
     /// The discv5 event stream.
     event_stream: EventStream,
 
+    /// Running totals of the queries started and their outcomes.
+    query_stats: QueryStats,
+
     /// Indicates if the discovery service has been started. When the service is disabled, this is
     /// always false.
     pub started: bool,
@@ -276,6 +296,7 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
             active_queries: FuturesUnordered::new(),
             discv5,
             event_stream,
+            query_stats: QueryStats::default(),
             started: !config.disable_discovery,
             log,
             enr_dir,
@@ -344,6 +365,61 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
         self.discv5.table_entries_enr()
     }
 
+    /// Builds a snapshot of the routing table and query statistics, storing it in the network
+    /// globals and updating the routing table metrics.
+    pub fn update_discovery_info(&mut self) {
+        let local_node_id = self.discv5.local_enr().node_id();
+
+        let entries = self
+            .discv5
+            .table_entries_enr()
+            .into_iter()
+            .map(|enr| TableEntryInfo {
+                node_id: enr.node_id().to_string(),
+                peer_id: enr.peer_id().to_string(),
+                enr: enr.to_base64(),
+                seq: enr.seq(),
+                distance: log2_distance(&local_node_id, &enr.node_id()),
+                ip: enr.ip().map(IpAddr::V4),
+                udp: enr.udp(),
+                tcp: enr.tcp(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut bucket_occupancy: BTreeMap<u64, usize> = BTreeMap::new();
+        for distance in entries.iter().filter_map(|entry| entry.distance) {
+            *bucket_occupancy.entry(distance).or_default() += 1;
+        }
+
+        metrics::set_gauge(&metrics::DISCOVERY_TABLE_SIZE, entries.len() as i64);
+        if let Ok(gauge_vec) = &*metrics::DISCOVERY_BUCKET_ENTRIES {
+            gauge_vec.reset();
+        }
+        for (distance, count) in bucket_occupancy.iter() {
+            metrics::set_int_gauge(
+                &metrics::DISCOVERY_BUCKET_ENTRIES,
+                &[&distance.to_string()],
+                *count as i64,
+            );
+        }
+
+        let mut query_stats = self.query_stats.clone();
+        query_stats.queued = self.queued_queries.len();
+        query_stats.active = self.active_queries.len();
+
+        *self.network_globals.discovery_info.write() = DiscoveryInfo {
+            enabled: self.started,
+            local_node_id: local_node_id.to_string(),
+            table_size: entries.len(),
+            buckets: bucket_occupancy
+                .into_iter()
+                .map(|(distance, entries)| BucketInfo { distance, entries })
+                .collect(),
+            entries,
+            queries: query_stats,
+        };
+    }
+
     /// Returns the ENR of a known peer if it exists.
     pub fn enr_of_peer(&mut self, peer_id: &PeerId) -> Option<Enr> {
         // first search the local cache
@@ -707,6 +783,7 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
 
         // Generate a random target node id.
         let random_node = NodeId::random();
+        let query_label = grouped_query.metrics_label();
 
         let enr_fork_id = match self.local_enr().eth2() {
             Ok(v) => v,
@@ -730,6 +807,9 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
             .find_node_predicate(random_node, predicate, target_peers)
             .map(|v| QueryResult(grouped_query, v));
 
+        self.query_stats.started += 1;
+        metrics::inc_counter_vec(&metrics::DISCOVERY_QUERIES_STARTED, &[query_label]);
+
         // Add the future to active queries, to be executed.
         self.active_queries.push(Box::pin(query_future));
     }
@@ -739,6 +819,19 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
         &mut self,
         query_result: QueryResult,
     ) -> Option<HashMap<PeerId, Option<Instant>>> {
+        let query_label = query_result.0.metrics_label();
+        match &query_result.1 {
+            Ok(r) => {
+                self.query_stats.completed += 1;
+                self.query_stats.peers_found += r.len() as u64;
+                metrics::inc_counter_vec(&metrics::DISCOVERY_QUERIES_COMPLETED, &[query_label]);
+            }
+            Err(_) => {
+                self.query_stats.failed += 1;
+                metrics::inc_counter_vec(&metrics::DISCOVERY_QUERIES_FAILED, &[query_label]);
+            }
+        }
+
         match query_result.0 {
             GroupedQueryType::FindPeers => {
                 self.find_peer_active = false;
@@ -926,6 +1019,22 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
     }
 }
 
+/// Returns the log2 distance between two node ids, i.e. the index of the k-bucket that `other`
+/// would occupy in the routing table of `local`. Returns `None` if the ids are equal.
+fn log2_distance(local: &NodeId, other: &NodeId) -> Option<u64> {
+    let local = local.raw();
+    let other = other.raw();
+    let mut leading_zeros = 0;
+    for (a, b) in local.iter().zip(other.iter()) {
+        let xor = a ^ b;
+        leading_zeros += u64::from(xor.leading_zeros());
+        if xor != 0 {
+            return Some(NODE_ID_BITS - leading_zeros);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1053,6 +1162,22 @@ mod tests {
         assert!(discovery.process_queue());
     }
 
+    #[test]
+    fn test_log2_distance() {
+        let mut raw = [0u8; 32];
+        let local = NodeId::new(&raw);
+        assert_eq!(log2_distance(&local, &local), None);
+
+        raw[31] = 1;
+        assert_eq!(log2_distance(&local, &NodeId::new(&raw)), Some(1));
+
+        raw[31] = 0b1000_0000;
+        assert_eq!(log2_distance(&local, &NodeId::new(&raw)), Some(8));
+
+        raw[0] = 0b1000_0000;
+        assert_eq!(log2_distance(&local, &NodeId::new(&raw)), Some(256));
+    }
+
     fn make_enr(subnet_ids: Vec<usize>) -> Enr {
         let mut builder = EnrBuilder::new("v4");
         let keypair = libp2p::identity::Keypair::generate_secp256k1();
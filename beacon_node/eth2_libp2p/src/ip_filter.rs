@@ -0,0 +1,233 @@
+//! A libp2p transport wrapper which refuses inbound and outbound connections based on
+//! configurable CIDR allow and deny lists.
+//!
+//! Filtering happens at the transport level, before any protocol negotiation takes place, so
+//! rejected peers never reach the peer manager. Limits on the number of peers per IP address and
+//! per subnet depend on the set of connected peers and are enforced by the peer manager instead.
+
+use crate::metrics;
+use futures::prelude::*;
+use libp2p::core::multiaddr::{Multiaddr, Protocol};
+use libp2p::core::transport::{ListenerEvent, Transport, TransportError};
+use serde_derive::{Deserialize, Serialize};
+use std::io;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub use ipnet::IpNet;
+
+/// The prefix length under which IPv4 peers are grouped when limiting peers per subnet.
+pub const IPV4_SUBNET_PREFIX_LEN: u8 = 24;
+/// The prefix length under which IPv6 peers are grouped when limiting peers per subnet.
+pub const IPV6_SUBNET_PREFIX_LEN: u8 = 64;
+
+/// Lists of IP ranges that peers may or may not connect from, or be dialled on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IpFilter {
+    /// If non-empty, only addresses within one of these ranges are permitted.
+    pub allow: Vec<IpNet>,
+    /// Addresses within any of these ranges are never permitted, regardless of `allow`.
+    pub deny: Vec<IpNet>,
+}
+
+impl IpFilter {
+    /// Returns `true` if no ranges have been configured.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Returns `true` if connections to or from `ip` are permitted.
+    pub fn is_permitted(&self, ip: &IpAddr) -> bool {
+        if self.deny.iter().any(|net| net.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip))
+    }
+
+    /// Returns `true` if the IP addresses in `addr` are permitted.
+    ///
+    /// Addresses without an IP component (e.g. DNS names that are resolved by a proxy, or relayed
+    /// addresses) can't be checked, so they are only permitted if no ranges have been configured.
+    pub fn is_permitted_multiaddr(&self, addr: &Multiaddr) -> bool {
+        let mut ips = addr
+            .iter()
+            .filter_map(|protocol| match protocol {
+                Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+                Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+                _ => None,
+            })
+            .peekable();
+        if ips.peek().is_none() {
+            return self.is_empty();
+        }
+        ips.all(|ip| self.is_permitted(&ip))
+    }
+}
+
+/// Returns the first IP address in `addr`, if any.
+pub fn multiaddr_ip(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+/// Returns the subnet that `ip` is grouped under when limiting peers per subnet, i.e. its /24 for
+/// IPv4 or its /64 for IPv6.
+pub fn subnet_of(ip: IpAddr) -> IpNet {
+    let prefix_len = match ip {
+        IpAddr::V4(_) => IPV4_SUBNET_PREFIX_LEN,
+        IpAddr::V6(_) => IPV6_SUBNET_PREFIX_LEN,
+    };
+    IpNet::new(ip, prefix_len)
+        .expect("prefix length is valid for the address family")
+        .trunc()
+}
+
+/// Wraps a transport, refusing dials to and dropping inbound connections from addresses that are
+/// not permitted by an `IpFilter`.
+#[derive(Debug, Clone)]
+pub struct IpFilterTransport<T> {
+    inner: T,
+    filter: Arc<IpFilter>,
+}
+
+impl<T> IpFilterTransport<T> {
+    pub fn new(inner: T, filter: IpFilter) -> Self {
+        Self {
+            inner,
+            filter: Arc::new(filter),
+        }
+    }
+}
+
+impl<T> Transport for IpFilterTransport<T>
+where
+    T: Transport<Error = io::Error>,
+    T::Listener: Unpin,
+{
+    type Output = T::Output;
+    type Error = io::Error;
+    type Listener = IpFilterListener<T::Listener>;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = T::Dial;
+
+    fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+        let inner = self.inner.listen_on(addr)?;
+        Ok(IpFilterListener {
+            inner,
+            filter: self.filter,
+        })
+    }
+
+    fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        if !self.filter.is_permitted_multiaddr(&addr) {
+            metrics::inc_counter_vec(&metrics::IP_FILTER_REJECTED_CONNECTIONS, &["outbound"]);
+            return Err(TransportError::Other(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Dialling {} is not permitted by the IP filter", addr),
+            )));
+        }
+        self.inner.dial(addr)
+    }
+}
+
+/// A listener that drops inbound connections from addresses not permitted by an `IpFilter`.
+pub struct IpFilterListener<L> {
+    inner: L,
+    filter: Arc<IpFilter>,
+}
+
+impl<L, U> Stream for IpFilterListener<L>
+where
+    L: Stream<Item = Result<ListenerEvent<U, io::Error>, io::Error>> + Unpin,
+{
+    type Item = L::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(ListenerEvent::Upgrade { remote_addr, .. })))
+                    if !self.filter.is_permitted_multiaddr(&remote_addr) =>
+                {
+                    // Dropping the upgrade closes the underlying connection.
+                    metrics::inc_counter_vec(
+                        &metrics::IP_FILTER_REJECTED_CONNECTIONS,
+                        &["inbound"],
+                    );
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allow: &[&str], deny: &[&str]) -> IpFilter {
+        IpFilter {
+            allow: allow.iter().map(|net| net.parse().unwrap()).collect(),
+            deny: deny.iter().map(|net| net.parse().unwrap()).collect(),
+        }
+    }
+
+    #[test]
+    fn empty_filter_permits_everything() {
+        let filter = IpFilter::default();
+        assert!(filter.is_permitted(&"1.2.3.4".parse().unwrap()));
+        assert!(filter.is_permitted(&"::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn deny_takes_precedence_over_allow() {
+        let filter = filter(&["10.0.0.0/8"], &["10.1.0.0/16"]);
+        assert!(filter.is_permitted(&"10.2.3.4".parse().unwrap()));
+        assert!(!filter.is_permitted(&"10.1.3.4".parse().unwrap()));
+        assert!(!filter.is_permitted(&"192.168.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn multiaddr_filtering() {
+        let filter = filter(&[], &["1.2.3.0/24"]);
+        let denied: Multiaddr = "/ip4/1.2.3.4/tcp/9000".parse().unwrap();
+        let permitted: Multiaddr = "/ip4/1.2.4.4/tcp/9000".parse().unwrap();
+        assert!(!filter.is_permitted_multiaddr(&denied));
+        assert!(filter.is_permitted_multiaddr(&permitted));
+    }
+
+    #[test]
+    fn multiaddrs_without_ips() {
+        let dns: Multiaddr = "/dns4/example.com/tcp/9000".parse().unwrap();
+        let relay: Multiaddr = "/p2p-circuit".parse().unwrap();
+
+        // Only an empty filter permits addresses which can't be checked.
+        assert!(IpFilter::default().is_permitted_multiaddr(&dns));
+        assert!(IpFilter::default().is_permitted_multiaddr(&relay));
+        for filter in &[filter(&["10.0.0.0/8"], &[]), filter(&[], &["1.2.3.0/24"])] {
+            assert!(!filter.is_permitted_multiaddr(&dns));
+            assert!(!filter.is_permitted_multiaddr(&relay));
+        }
+    }
+
+    #[test]
+    fn subnets() {
+        assert_eq!(
+            subnet_of("1.2.3.4".parse().unwrap()),
+            "1.2.3.0/24".parse().unwrap()
+        );
+        assert_eq!(
+            subnet_of("2001:db8:1:2:3::4".parse().unwrap()),
+            "2001:db8:1:2::/64".parse().unwrap()
+        );
+
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/9000".parse().unwrap();
+        let dns: Multiaddr = "/dns4/example.com/tcp/9000".parse().unwrap();
+        assert_eq!(multiaddr_ip(&addr), Some("1.2.3.4".parse().unwrap()));
+        assert_eq!(multiaddr_ip(&dns), None);
+    }
+}
@@ -7,6 +7,7 @@ extern crate lazy_static;
 
 pub mod behaviour;
 mod config;
+pub mod ip_filter;
 
 #[allow(clippy::mutable_key_type)] // PeerId in hashmaps are no longer permitted by clippy
 pub mod discovery;
@@ -14,6 +15,9 @@ mod metrics;
 mod peer_manager;
 pub mod rpc;
 mod service;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+mod socks5;
 pub mod types;
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -60,7 +64,9 @@ impl<'de> Deserialize<'de> for PeerIdSerialized {
     }
 }
 
-pub use crate::types::{error, Enr, GossipTopic, NetworkGlobals, PubsubMessage, SubnetDiscovery};
+pub use crate::types::{
+    error, DiscoveryInfo, Enr, GossipTopic, NetworkGlobals, PubsubMessage, SubnetDiscovery,
+};
 pub use behaviour::{BehaviourEvent, Gossipsub, PeerRequestId, Request, Response};
 pub use config::Config as NetworkConfig;
 pub use config::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage};
@@ -34,6 +34,47 @@ lazy_static! {
         "Unsolicited discovery requests per ip per second",
         &["Addresses"]
     );
+    pub static ref DISCOVERY_TABLE_SIZE: Result<IntGauge> = try_create_int_gauge(
+        "discovery_routing_table_size",
+        "The number of entries in the discovery routing table"
+    );
+    pub static ref DISCOVERY_BUCKET_ENTRIES: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "discovery_routing_table_bucket_entries",
+        "The number of entries in each non-empty bucket of the discovery routing table",
+        &["distance"]
+    );
+    pub static ref DISCOVERY_QUERIES_STARTED: Result<IntCounterVec> = try_create_int_counter_vec(
+        "discovery_queries_started_total",
+        "Count of discovery queries started",
+        &["type"]
+    );
+    pub static ref DISCOVERY_QUERIES_COMPLETED: Result<IntCounterVec> = try_create_int_counter_vec(
+        "discovery_queries_completed_total",
+        "Count of discovery queries that completed successfully",
+        &["type"]
+    );
+    pub static ref DISCOVERY_QUERIES_FAILED: Result<IntCounterVec> = try_create_int_counter_vec(
+        "discovery_queries_failed_total",
+        "Count of discovery queries that returned an error",
+        &["type"]
+    );
+    pub static ref IP_FILTER_REJECTED_CONNECTIONS: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "libp2p_ip_filter_rejected_connections_total",
+            "Count of connections refused by the CIDR allow/deny lists",
+            &["direction"]
+        );
+    pub static ref IP_LIMIT_REJECTED_CONNECTIONS: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "libp2p_ip_limit_rejected_connections_total",
+            "Count of connections refused because too many peers share the remote IP or subnet",
+            &["limit"]
+        );
+    pub static ref PEERS_PER_SUBSCRIBED_SUBNET: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "libp2p_peers_per_subscribed_subnet",
+        "The number of good connected peers on each subscribed attestation subnet",
+        &["subnet"]
+    );
     pub static ref PEERS_PER_CLIENT: Result<IntGaugeVec> = try_create_int_gauge_vec(
         "libp2p_peers_per_client",
         "The connected peers via client implementation",
@@ -50,6 +91,12 @@ lazy_static! {
         "Failed gossip publishes",
         &["topic_hash"]
     );
+    pub static ref GOSSIP_DECODE_FAILURES_PER_TOPIC: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "gossipsub_decode_failures_per_topic_total",
+            "Count of received gossip messages rejected because they could not be decoded",
+            &["topic"]
+        );
 }
 
 pub fn scrape_discovery_metrics() {
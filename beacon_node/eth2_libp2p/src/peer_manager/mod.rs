@@ -2,8 +2,9 @@
 
 pub use self::peerdb::*;
 use crate::discovery::{subnet_predicate, Discovery, DiscoveryEvent, TARGET_SUBNET_PEERS};
+use crate::ip_filter::multiaddr_ip;
 use crate::rpc::{GoodbyeReason, MetaData, Protocol, RPCError, RPCResponseErrorCode};
-use crate::types::SyncState;
+use crate::types::{GossipKind, SyncState};
 use crate::{error, metrics, Gossipsub};
 use crate::{EnrExt, NetworkConfig, NetworkGlobals, PeerId, SubnetDiscovery};
 use futures::prelude::*;
@@ -20,7 +21,7 @@ use std::{
     task::{Context, Poll},
     time::{Duration, Instant},
 };
-use types::{EthSpec, SubnetId};
+use types::{subnet_id::subnet_id_to_string, EthSpec, SubnetId};
 
 pub use libp2p::core::{identity::Keypair, Multiaddr};
 
@@ -51,6 +52,10 @@ const PING_INTERVAL_INBOUND: u64 = 35;
 /// requests. This defines the interval in seconds.
 const HEARTBEAT_INTERVAL: u64 = 30;
 
+/// Peers on under-provisioned subscribed subnets are retained, and newly discovered peers on those
+/// subnets are dialed, for this many heartbeats after the subnet was found lacking peers.
+const SUBNET_PEER_RETENTION_HEARTBEATS: u64 = 2;
+
 /// A fraction of `PeerManager::target_peers` that we allow to connect to us in excess of
 /// `PeerManager::target_peers`. For clarity, if `PeerManager::target_peers` is 50 and
 /// PEER_EXCESS_FACTOR = 0.1 we allow 10% more nodes, i.e 55.
@@ -76,6 +81,12 @@ pub struct PeerManager<TSpec: EthSpec> {
     target_peers: usize,
     /// The maximum number of peers we allow (exceptions for subnet peers)
     max_peers: usize,
+    /// The maximum number of connected peers sharing an IP address, or 0 for no limit.
+    max_peers_per_ip: usize,
+    /// The maximum number of connected peers within a subnet, or 0 for no limit.
+    max_peers_per_subnet: usize,
+    /// Prefer peers at our head when pruning, as this node only publishes blocks.
+    proposer_only: bool,
     /// The discovery service.
     discovery: Discovery<TSpec>,
     /// The heartbeat interval to perform routine maintenance.
@@ -124,6 +135,9 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
             target_peers: config.target_peers,
             max_peers: (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize,
+            max_peers_per_ip: config.max_peers_per_ip,
+            max_peers_per_subnet: config.max_peers_per_subnet,
+            proposer_only: config.proposer_only,
             discovery,
             heartbeat,
             log: log.clone(),
@@ -201,6 +215,46 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.ban_and_unban_peers(to_ban_peers, to_unban_peers);
     }
 
+    /// Bans a peer at the request of the user, disconnecting it if it is connected.
+    ///
+    /// The ban lasts for `duration`, or until `unban_peer_manually` is called if no duration is
+    /// given. Score decay does not lift a manual ban.
+    pub fn ban_peer_manually(&mut self, peer_id: &PeerId, duration: Option<Duration>) {
+        let already_banned = {
+            let mut peer_db = self.network_globals.peers.write();
+            peer_db.add_manual_ban(peer_id, duration.map(|duration| Instant::now() + duration));
+            match peer_db.peer_info_mut(peer_id) {
+                Some(info) => {
+                    info.apply_peer_action_to_score(PeerAction::Fatal);
+                    info.is_banned()
+                }
+                // Unknown peers are refused by the manual ban alone.
+                None => true,
+            }
+        }; // end write lock
+
+        debug!(self.log, "Manually banning peer"; "peer_id" => %peer_id, "duration" => ?duration);
+        if !already_banned {
+            self.ban_peer(peer_id, GoodbyeReason::Banned);
+        }
+    }
+
+    /// Lifts a ban on a peer at the request of the user, regardless of whether it was issued
+    /// manually or due to the peer's score. The peer's score is reset.
+    pub fn unban_peer_manually(&mut self, peer_id: &PeerId) -> Result<(), &'static str> {
+        let was_manually_banned = self
+            .network_globals
+            .peers
+            .write()
+            .remove_manual_ban(peer_id);
+        let was_banned = self.lift_ban(peer_id)?;
+        if !was_manually_banned && !was_banned {
+            return Err("Unbanning peer that is not banned");
+        }
+        debug!(self.log, "Manually unbanned peer"; "peer_id" => %peer_id);
+        Ok(())
+    }
+
     /* Discovery Requests */
 
     /// Provides a reference to the underlying discovery service.
@@ -325,6 +379,40 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.network_globals.connected_or_dialing_peers() >= self.max_peers
     }
 
+    /// Reports whether a connection to `peer_id` on `addr` would exceed the number of connected
+    /// peers allowed per IP address or per subnet, in which case the connection is refused.
+    ///
+    /// Trusted peers are exempt from these limits.
+    pub fn ip_limit_reached(&self, peer_id: &PeerId, addr: &Multiaddr) -> bool {
+        let ip = match multiaddr_ip(addr) {
+            Some(ip) => ip,
+            None => return false,
+        };
+
+        let peer_db = self.network_globals.peers.read();
+        if peer_db
+            .peer_info(peer_id)
+            .map_or(false, |info| info.is_trusted)
+        {
+            return false;
+        }
+
+        let (on_ip, on_subnet) = peer_db.connected_peers_sharing_ip(peer_id, &ip);
+        let limit = if self.max_peers_per_ip > 0 && on_ip >= self.max_peers_per_ip {
+            "ip"
+        } else if self.max_peers_per_subnet > 0 && on_subnet >= self.max_peers_per_subnet {
+            "subnet"
+        } else {
+            return false;
+        };
+
+        debug!(self.log, "Refusing peer sharing an IP with too many peers";
+            "peer_id" => %peer_id, "ip" => %ip, "limit" => limit, "peers_on_ip" => on_ip,
+            "peers_on_subnet" => on_subnet);
+        metrics::inc_counter_vec(&metrics::IP_LIMIT_REJECTED_CONNECTIONS, &[limit]);
+        true
+    }
+
     /// Updates `PeerInfo` with `identify` information.
     pub fn identify(&mut self, peer_id: &PeerId, info: &IdentifyInfo) {
         if let Some(peer_info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
@@ -782,7 +870,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     fn ban_and_unban_peers(&mut self, to_ban_peers: Vec<PeerId>, to_unban_peers: Vec<PeerId>) {
         // process banning peers
         for peer_id in to_ban_peers {
-            self.ban_peer(&peer_id);
+            self.ban_peer(&peer_id, GoodbyeReason::BadScore);
         }
         // process unbanning peers
         for peer_id in to_unban_peers {
@@ -801,7 +889,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         let mut to_ban_peers = Vec::new();
         let mut to_unban_peers = Vec::new();
 
-        for (peer_id, info) in self.network_globals.peers.write().peers_mut() {
+        let mut peer_db = self.network_globals.peers.write();
+        // Manually banned peers keep their score until the ban is lifted.
+        let manually_banned = peer_db
+            .manual_bans()
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect::<Vec<_>>();
+        for (peer_id, info) in peer_db.peers_mut() {
+            if manually_banned.contains(peer_id) {
+                continue;
+            }
             let previous_state = info.score_state();
             // Update scores
             info.score_update();
@@ -816,24 +913,56 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                 &self.log,
             );
         }
+        drop(peer_db);
         self.ban_and_unban_peers(to_ban_peers, to_unban_peers);
     }
 
+    /// Lifts any manual bans which have expired.
+    fn expire_manual_bans(&mut self) {
+        let expired = self
+            .network_globals
+            .peers
+            .write()
+            .remove_expired_manual_bans();
+        for peer_id in expired {
+            debug!(self.log, "Manual peer ban expired"; "peer_id" => %peer_id);
+            if let Err(e) = self.lift_ban(&peer_id) {
+                error!(self.log, "{}", e; "peer_id" => %peer_id);
+            }
+        }
+    }
+
+    /// Resets the score of a known peer and unbans it if it is banned. Returns true if the peer
+    /// was banned.
+    fn lift_ban(&mut self, peer_id: &PeerId) -> Result<bool, &'static str> {
+        let (was_banned, connection_banned) =
+            match self.network_globals.peers.write().peer_info_mut(peer_id) {
+                Some(info) => {
+                    let was_banned = info.score_state() == ScoreState::Banned || info.is_banned();
+                    info.reset_score();
+                    (was_banned, info.is_banned())
+                }
+                None => return Ok(false),
+            };
+        if connection_banned {
+            self.unban_peer(peer_id)?;
+        }
+        Ok(was_banned)
+    }
+
     /// Bans a peer.
     ///
     /// Records updates the peers connection status and updates the peer db as well as blocks the
     /// peer from participating in discovery and removes them from the routing table.
-    fn ban_peer(&mut self, peer_id: &PeerId) {
+    fn ban_peer(&mut self, peer_id: &PeerId, reason: GoodbyeReason) {
         {
             // write lock scope
             let mut peer_db = self.network_globals.peers.write();
 
             if peer_db.disconnect_and_ban(peer_id) {
                 // The peer was currently connected, so we start a disconnection.
-                self.events.push(PeerManagerEvent::DisconnectPeer(
-                    peer_id.clone(),
-                    GoodbyeReason::BadScore,
-                ));
+                self.events
+                    .push(PeerManagerEvent::DisconnectPeer(peer_id.clone(), reason));
             }
         } // end write lock
 
@@ -869,6 +998,56 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         Ok(())
     }
 
+    /// Checks the number of peers on each attestation subnet we are subscribed to, and searches for
+    /// more peers on subnets that are below `TARGET_SUBNET_PEERS`.
+    ///
+    /// Existing peers on such subnets are protected from being pruned as excess peers until the
+    /// subnet has recovered.
+    fn maintain_subnet_peers(&mut self) {
+        let subscribed_subnets = self
+            .network_globals
+            .gossipsub_subscriptions
+            .read()
+            .iter()
+            .filter_map(|topic| match topic.kind() {
+                GossipKind::Attestation(subnet_id) => Some(*subnet_id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let min_ttl = Instant::now()
+            + Duration::from_secs(HEARTBEAT_INTERVAL * SUBNET_PEER_RETENTION_HEARTBEATS);
+        let mut under_provisioned = Vec::new();
+        {
+            // Only report the subnets which are still subscribed to.
+            if let Ok(gauge_vec) = &*metrics::PEERS_PER_SUBSCRIBED_SUBNET {
+                gauge_vec.reset();
+            }
+            let peer_db = self.network_globals.peers.read();
+            for subnet_id in subscribed_subnets {
+                let peers_on_subnet = peer_db.good_peers_on_subnet(subnet_id).count();
+                metrics::set_int_gauge(
+                    &metrics::PEERS_PER_SUBSCRIBED_SUBNET,
+                    &[subnet_id_to_string(*subnet_id)],
+                    peers_on_subnet as i64,
+                );
+                if peers_on_subnet < TARGET_SUBNET_PEERS {
+                    under_provisioned.push(SubnetDiscovery {
+                        subnet_id,
+                        min_ttl: Some(min_ttl),
+                    });
+                }
+            }
+        }
+
+        if !under_provisioned.is_empty() {
+            debug!(self.log, "Searching for peers on under-provisioned subnets";
+                "subnets" => ?under_provisioned.iter().map(|s| *s.subnet_id).collect::<Vec<_>>(),
+                "target_subnet_peers" => TARGET_SUBNET_PEERS);
+            self.discover_subnet_peers(under_provisioned);
+        }
+    }
+
     /// The Peer manager's heartbeat maintains the peer count and maintains peer reputations.
     ///
     /// It will request discovery queries if the peer count has not reached the desired number of
@@ -885,6 +1064,15 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             }
         }
 
+        // Maintain the target number of peers on each subscribed attestation subnet.
+        self.maintain_subnet_peers();
+
+        // Refresh the routing table snapshot exposed to the HTTP API.
+        self.discovery.update_discovery_info();
+
+        // Lift manual bans that have run their course.
+        self.expire_manual_bans();
+
         // Updates peer's scores.
         self.update_peer_scores();
 
@@ -893,12 +1081,18 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
         let connected_peer_count = self.network_globals.connected_peers();
         if connected_peer_count > self.target_peers {
+            let peer_db = self.network_globals.peers.read();
+            let mut worst_peers = peer_db.worst_connected_peers();
+            if self.proposer_only {
+                // Published blocks reach the network fastest through peers at our head, so prune
+                // the others first. The sort is stable, so peers remain ordered by score.
+                worst_peers.sort_by_key(|(_, info)| {
+                    info.sync_status.is_synced() || info.sync_status.is_advanced()
+                });
+            }
+
             //remove excess peers with the worst scores, but keep subnet peers
-            for (peer_id, _) in self
-                .network_globals
-                .peers
-                .read()
-                .worst_connected_peers()
+            for (peer_id, _) in worst_peers
                 .iter()
                 .filter(|(_, info)| !info.has_future_duty())
                 .take(connected_peer_count - self.target_peers)
@@ -1005,3 +1199,103 @@ enum ConnectingType {
         multiaddr: Multiaddr,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{build_enr, CombinedKey, CombinedKeyExt};
+    use crate::types::{GossipEncoding, GossipTopic};
+    use crate::Enr;
+    use slog::{o, Drain};
+    use std::collections::HashSet;
+    use std::net::UdpSocket;
+    use types::{EnrForkId, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    pub fn unused_port() -> u16 {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("should create udp socket");
+        let local_addr = socket.local_addr().expect("should read udp socket");
+        local_addr.port()
+    }
+
+    pub fn build_log(level: slog::Level, enabled: bool) -> slog::Logger {
+        let decorator = slog_term::TermDecorator::new().build();
+        let drain = slog_term::FullFormat::new(decorator).build().fuse();
+        let drain = slog_async::Async::new(drain).build().fuse();
+
+        if enabled {
+            slog::Logger::root(drain.filter_level(level).fuse(), o!())
+        } else {
+            slog::Logger::root(drain.filter(|_| false).fuse(), o!())
+        }
+    }
+
+    async fn build_peer_manager() -> PeerManager<E> {
+        let keypair = libp2p::identity::Keypair::generate_secp256k1();
+        let mut config = NetworkConfig::default();
+        config.discovery_port = unused_port();
+        let enr_key: CombinedKey = CombinedKey::from_libp2p(&keypair).unwrap();
+        let enr: Enr = build_enr::<E>(&enr_key, &config, EnrForkId::default()).unwrap();
+        let log = build_log(slog::Level::Debug, false);
+        let globals = NetworkGlobals::new(
+            enr,
+            9000,
+            9000,
+            MetaData {
+                seq_number: 0,
+                attnets: Default::default(),
+            },
+            vec![],
+            &log,
+        );
+        PeerManager::new(&keypair, &config, Arc::new(globals), &log)
+            .await
+            .unwrap()
+    }
+
+    fn subscribe_to_subnets(peer_manager: &PeerManager<E>, subnets: &[u64]) {
+        *peer_manager.network_globals.gossipsub_subscriptions.write() = subnets
+            .iter()
+            .map(|subnet| {
+                GossipTopic::new(
+                    GossipKind::Attestation(SubnetId::new(*subnet)),
+                    GossipEncoding::default(),
+                    [0; 4],
+                )
+            })
+            .collect();
+    }
+
+    /// Returns the subnets with a value in the peers per subscribed subnet gauge.
+    fn reported_subnets() -> HashSet<String> {
+        lighthouse_metrics::gather()
+            .iter()
+            .filter(|family| family.get_name() == "libp2p_peers_per_subscribed_subnet")
+            .flat_map(|family| family.get_metric())
+            .flat_map(|metric| metric.get_label())
+            .map(|label| label.get_value().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_subnet_peers_gauge_follows_subscriptions() {
+        let mut peer_manager = build_peer_manager().await;
+
+        subscribe_to_subnets(&peer_manager, &[1, 2]);
+        peer_manager.maintain_subnet_peers();
+        let expected: HashSet<String> =
+            vec!["1".to_string(), "2".to_string()].into_iter().collect();
+        assert_eq!(reported_subnets(), expected);
+
+        // Subnets which are no longer subscribed to are no longer reported.
+        subscribe_to_subnets(&peer_manager, &[2]);
+        peer_manager.maintain_subnet_peers();
+        let expected: HashSet<String> = vec!["2".to_string()].into_iter().collect();
+        assert_eq!(reported_subnets(), expected);
+
+        subscribe_to_subnets(&peer_manager, &[]);
+        peer_manager.maintain_subnet_peers();
+        assert!(reported_subnets().is_empty());
+    }
+}
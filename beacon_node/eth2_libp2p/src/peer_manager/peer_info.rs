@@ -140,10 +140,9 @@ impl<T: EthSpec> PeerInfo<T> {
         self.score.is_good_gossipsub_peer()
     }
 
-    #[cfg(test)]
     /// Resets the peers score.
     pub fn reset_score(&mut self) {
-        self.score.test_reset();
+        self.score.reset();
     }
 
     /* Peer connection status API */
@@ -1,6 +1,7 @@
 use super::peer_info::{ConnectionDirection, PeerConnectionStatus, PeerInfo};
 use super::peer_sync_status::PeerSyncStatus;
 use super::score::{Score, ScoreState};
+use crate::ip_filter::subnet_of;
 use crate::multiaddr::{Multiaddr, Protocol};
 use crate::rpc::methods::MetaData;
 use crate::Enr;
@@ -27,6 +28,9 @@ pub struct PeerDB<TSpec: EthSpec> {
     disconnected_peers: usize,
     /// Counts banned peers in total and per ip
     banned_peers_count: BannedPeersCount,
+    /// Peers banned by the user, along with the time the ban expires (if any). These peers are
+    /// not unbanned by score decay.
+    manual_bans: HashMap<PeerId, Option<Instant>>,
     /// PeerDB's logger
     log: slog::Logger,
 }
@@ -88,6 +92,7 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             log: log.clone(),
             disconnected_peers: 0,
             banned_peers_count: BannedPeersCount::new(),
+            manual_bans: HashMap::new(),
             peers,
         }
     }
@@ -162,6 +167,9 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
     ///
     /// This is used to determine if we should accept incoming connections or not.
     pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        if self.is_manually_banned(peer_id) {
+            return true;
+        }
         if let Some(peer) = self.peers.get(peer_id) {
             match peer.score_state() {
                 ScoreState::Banned => true,
@@ -184,6 +192,9 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 
     /// Returns true if the Peer is either banned or in the disconnected state.
     pub fn is_banned_or_disconnected(&self, peer_id: &PeerId) -> bool {
+        if self.is_manually_banned(peer_id) {
+            return true;
+        }
         if let Some(peer) = self.peers.get(peer_id) {
             match peer.score_state() {
                 ScoreState::Banned | ScoreState::Disconnected => true,
@@ -194,11 +205,36 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
+    /// Returns true if the peer has been banned by the user.
+    pub fn is_manually_banned(&self, peer_id: &PeerId) -> bool {
+        self.manual_bans.contains_key(peer_id)
+    }
+
+    /// Gives the ids of all manually banned peers along with the time their ban expires, if it
+    /// expires at all.
+    pub fn manual_bans(&self) -> impl Iterator<Item = (&PeerId, &Option<Instant>)> {
+        self.manual_bans.iter()
+    }
+
     /// Gives the ids and info of all known connected peers.
     pub fn connected_peers(&self) -> impl Iterator<Item = (&PeerId, &PeerInfo<TSpec>)> {
         self.peers.iter().filter(|(_, info)| info.is_connected())
     }
 
+    /// Returns the number of connected peers, other than `peer_id`, that have been seen on `ip`,
+    /// along with the number that have been seen within the same subnet as `ip`.
+    pub fn connected_peers_sharing_ip(&self, peer_id: &PeerId, ip: &IpAddr) -> (usize, usize) {
+        let subnet = subnet_of(*ip);
+        self.connected_peers()
+            .filter(|(id, _)| *id != peer_id)
+            .fold((0, 0), |(on_ip, on_subnet), (_, info)| {
+                (
+                    on_ip + info.seen_addresses().any(|seen| seen == *ip) as usize,
+                    on_subnet + info.seen_addresses().any(|seen| subnet.contains(&seen)) as usize,
+                )
+            })
+    }
+
     /// Gives the ids of all known connected peers.
     pub fn connected_peer_ids(&self) -> impl Iterator<Item = &PeerId> {
         self.peers
@@ -535,6 +571,34 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         Ok(())
     }
 
+    /// Records a ban issued by the user. The ban lasts until `until`, or until it is explicitly
+    /// removed if `until` is `None`.
+    ///
+    /// This does not change the peer's connection state, see `disconnect_and_ban`.
+    pub fn add_manual_ban(&mut self, peer_id: &PeerId, until: Option<Instant>) {
+        self.manual_bans.insert(peer_id.clone(), until);
+    }
+
+    /// Removes a ban issued by the user. Returns true if the peer was manually banned.
+    pub fn remove_manual_ban(&mut self, peer_id: &PeerId) -> bool {
+        self.manual_bans.remove(peer_id).is_some()
+    }
+
+    /// Removes and returns all manual bans which have expired.
+    pub(super) fn remove_expired_manual_bans(&mut self) -> Vec<PeerId> {
+        let now = Instant::now();
+        let expired = self
+            .manual_bans
+            .iter()
+            .filter(|(_, until)| until.map_or(false, |until| until <= now))
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect::<Vec<_>>();
+        for peer_id in &expired {
+            self.manual_bans.remove(peer_id);
+        }
+        expired
+    }
+
     /// Removes banned and disconnected peers from the DB if we have reached any of our limits.
     /// Drops the peers with the lowest reputation so that the number of
     /// disconnected peers is less than MAX_DC_PEERS
@@ -1015,6 +1079,56 @@ mod tests {
         assert!(pdb.is_banned(&p2));
     }
 
+    #[test]
+    fn test_manual_bans() {
+        let mut pdb = get_db();
+        let p0 = PeerId::random();
+        let p1 = PeerId::random();
+        pdb.connect_ingoing(&p0, "/ip4/0.0.0.0".parse().unwrap(), None);
+
+        // A manual ban applies to known and unknown peers alike.
+        pdb.add_manual_ban(&p0, None);
+        pdb.add_manual_ban(&p1, Some(Instant::now()));
+        assert!(pdb.is_banned(&p0));
+        assert!(pdb.is_banned(&p1));
+        assert!(pdb.is_banned_or_disconnected(&p1));
+
+        // Only bans with an expiry in the past are removed.
+        assert_eq!(pdb.remove_expired_manual_bans(), vec![p1.clone()]);
+        assert!(pdb.is_manually_banned(&p0));
+        assert!(!pdb.is_banned(&p1));
+
+        assert!(pdb.remove_manual_ban(&p0));
+        assert!(!pdb.remove_manual_ban(&p0));
+        assert!(!pdb.is_banned(&p0));
+        assert_eq!(pdb.manual_bans().count(), 0);
+    }
+
+    #[test]
+    fn test_connected_peers_sharing_ip() {
+        let mut pdb = get_db();
+        let ip: IpAddr = Ipv4Addr::new(1, 2, 3, 4).into();
+
+        let p0 = connect_peer_with_ips(&mut pdb, vec![ip]);
+        connect_peer_with_ips(&mut pdb, vec![ip]);
+        connect_peer_with_ips(&mut pdb, vec![Ipv4Addr::new(1, 2, 3, 5).into()]);
+        connect_peer_with_ips(&mut pdb, vec![Ipv4Addr::new(1, 2, 4, 4).into()]);
+
+        assert_eq!(
+            pdb.connected_peers_sharing_ip(&PeerId::random(), &ip),
+            (2, 3)
+        );
+        // The peer being checked is not counted.
+        assert_eq!(pdb.connected_peers_sharing_ip(&p0, &ip), (1, 2));
+
+        // Disconnected peers are not counted.
+        pdb.notify_disconnect(&p0);
+        assert_eq!(
+            pdb.connected_peers_sharing_ip(&PeerId::random(), &ip),
+            (1, 2)
+        );
+    }
+
     #[test]
     fn test_trusted_peers_score() {
         let trusted_peer = PeerId::random();
@@ -192,10 +192,9 @@ impl RealScore {
         self.add(score);
     }
 
-    #[cfg(test)]
-    // reset the score
-    pub fn test_reset(&mut self) {
-        self.set_lighthouse_score(0f64);
+    /// Resets the score to its default, clearing any ban on decay.
+    pub fn reset(&mut self) {
+        *self = RealScore::default();
     }
 
     /// Applies time-based logic such as decay rates to the score.
@@ -271,8 +270,7 @@ apply!(update);
 apply!(update_gossipsub_score, new_score: f64, ignore: bool);
 #[cfg(test)]
 apply!(test_add, score: f64);
-#[cfg(test)]
-apply!(test_reset);
+apply!(reset);
 
 impl Score {
     pub fn score(&self) -> f64 {
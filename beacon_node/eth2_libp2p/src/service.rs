@@ -2,12 +2,18 @@ use crate::behaviour::{
     save_metadata_to_disk, Behaviour, BehaviourEvent, PeerRequestId, Request, Response,
 };
 use crate::discovery::enr;
+use crate::ip_filter::{IpFilter, IpFilterTransport};
 use crate::multiaddr::Protocol;
 use crate::rpc::{GoodbyeReason, MetaData, RPCResponseErrorCode, RequestId};
+#[cfg(feature = "simulation")]
+use crate::simulation::SimulatedLink;
+use crate::socks5::Socks5Transport;
 use crate::types::{error, EnrBitfield, GossipKind};
 use crate::EnrExt;
 use crate::{NetworkConfig, NetworkGlobals, PeerAction};
 use futures::prelude::*;
+#[cfg(feature = "simulation")]
+use libp2p::core::transport::OptionalTransport;
 use libp2p::core::{
     identity::Keypair, multiaddr::Multiaddr, muxing::StreamMuxerBox, transport::Boxed,
 };
@@ -21,6 +27,7 @@ use slog::{crit, debug, info, o, trace, warn, Logger};
 use ssz::Decode;
 use std::fs::File;
 use std::io::prelude::*;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -64,6 +71,19 @@ impl<TSpec: EthSpec> Service<TSpec> {
         enr_fork_id: EnrForkId,
         log: &Logger,
         chain_spec: &ChainSpec,
+    ) -> error::Result<(Arc<NetworkGlobals<TSpec>>, Self)> {
+        Self::new_with_link(executor, config, enr_fork_id, log, chain_spec, None).await
+    }
+
+    /// As `new`, except that if `link` is provided the service listens on the link's in-memory
+    /// address instead of the configured TCP port. Used by the `simulation` harness.
+    pub(crate) async fn new_with_link(
+        executor: task_executor::TaskExecutor,
+        config: &NetworkConfig,
+        enr_fork_id: EnrForkId,
+        log: &Logger,
+        chain_spec: &ChainSpec,
+        link: Option<SimulatedLink>,
     ) -> error::Result<(Arc<NetworkGlobals<TSpec>>, Self)> {
         let log = log.new(o!("service"=> "libp2p"));
         trace!(log, "Libp2p Service starting");
@@ -101,10 +121,28 @@ impl<TSpec: EthSpec> Service<TSpec> {
         };
         debug!(log, "Attempting to open listening ports"; "address" => ?config.listen_address, "tcp_port" => config.libp2p_port, "udp_port" => discovery_string);
 
+        // listen on the specified address
+        let listen_multiaddr = match &link {
+            #[cfg(feature = "simulation")]
+            Some(link) => link.address().clone(),
+            #[cfg(not(feature = "simulation"))]
+            Some(link) => match *link {},
+            None => {
+                let mut m = Multiaddr::from(config.listen_address);
+                m.push(Protocol::Tcp(config.libp2p_port));
+                m
+            }
+        };
+
         let (mut swarm, bandwidth) = {
             // Set up the transport - tcp/ws with noise and mplex
-            let (transport, bandwidth) = build_transport(local_keypair.clone())
-                .map_err(|e| format!("Failed to build transport: {:?}", e))?;
+            let (transport, bandwidth) = build_transport(
+                local_keypair.clone(),
+                config.socks5_proxy,
+                config.ip_filter.clone(),
+                link,
+            )
+            .map_err(|e| format!("Failed to build transport: {:?}", e))?;
 
             // Lighthouse network behaviour
             let behaviour = Behaviour::new(
@@ -136,13 +174,6 @@ impl<TSpec: EthSpec> Service<TSpec> {
             )
         };
 
-        // listen on the specified address
-        let listen_multiaddr = {
-            let mut m = Multiaddr::from(config.listen_address);
-            m.push(Protocol::Tcp(config.libp2p_port));
-            m
-        };
-
         match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
             Ok(_) => {
                 let mut log_address = listen_multiaddr;
@@ -260,6 +291,16 @@ impl<TSpec: EthSpec> Service<TSpec> {
         self.swarm.goodbye_peer(peer_id, reason);
     }
 
+    /// Bans a peer at the request of the user, optionally for a limited duration.
+    pub fn ban_peer_manually(&mut self, peer_id: &PeerId, duration: Option<Duration>) {
+        self.swarm.ban_peer_manually(peer_id, duration);
+    }
+
+    /// Lifts a ban on a peer at the request of the user.
+    pub fn unban_peer_manually(&mut self, peer_id: &PeerId) -> Result<(), &'static str> {
+        self.swarm.unban_peer_manually(peer_id)
+    }
+
     /// Sends a response to a peer's request.
     pub fn send_response(&mut self, peer_id: PeerId, id: PeerRequestId, response: Response<TSpec>) {
         self.swarm.send_successful_response(peer_id, id, response);
@@ -337,18 +378,41 @@ impl<TSpec: EthSpec> Service<TSpec> {
 
 type BoxedTransport = Boxed<(PeerId, StreamMuxerBox)>;
 
+/// Simulated links only exist with the `simulation` feature, so without it none can be given.
+#[cfg(not(feature = "simulation"))]
+type SimulatedLink = void::Void;
+
 /// The implementation supports TCP/IP, WebSockets over TCP/IP, noise as the encryption layer, and
-/// mplex as the multiplexing layer.
+/// mplex as the multiplexing layer. If a simulated `link` is given, in-memory addresses are also
+/// supported.
 fn build_transport(
     local_private_key: Keypair,
+    socks5_proxy: Option<SocketAddr>,
+    ip_filter: IpFilter,
+    link: Option<SimulatedLink>,
 ) -> std::io::Result<(BoxedTransport, Arc<BandwidthSinks>)> {
-    let transport = libp2p::tcp::TokioTcpConfig::new().nodelay(true);
-    let transport = libp2p::dns::DnsConfig::new(transport)?;
+    let tcp = libp2p::tcp::TokioTcpConfig::new().nodelay(true);
+    // The IP filter sits below the DNS transport so that it sees resolved addresses.
+    let transport = if let Some(proxy) = socks5_proxy {
+        // Domain names are resolved by the proxy, so the DNS transport is not used.
+        IpFilterTransport::new(Socks5Transport::new(tcp, proxy).boxed(), ip_filter).boxed()
+    } else {
+        libp2p::dns::DnsConfig::new(IpFilterTransport::new(tcp.boxed(), ip_filter))?.boxed()
+    };
     #[cfg(feature = "libp2p-websocket")]
     let transport = {
         let trans_clone = transport.clone();
         transport.or_transport(libp2p::websocket::WsConfig::new(trans_clone))
     };
+    #[cfg(feature = "simulation")]
+    let transport = transport.or_transport(match link {
+        Some(link) => OptionalTransport::some(link.transport()),
+        None => OptionalTransport::none(),
+    });
+    #[cfg(not(feature = "simulation"))]
+    if let Some(link) = link {
+        match link {}
+    }
 
     let (transport, bandwidth) = BandwidthLogging::new(transport);
 
@@ -0,0 +1,143 @@
+//! A harness which runs a network of `Service`s within a single process.
+//!
+//! Nodes are connected over in-memory transports whose latency and connection loss can be adjusted
+//! while the network runs. This allows gossip and RPC behaviour to be exercised in tests without
+//! binding sockets or relying on live testnets.
+
+mod transport;
+
+pub use transport::{LinkConditions, SimulatedLink};
+
+use crate::service::{Libp2pEvent, Service};
+use crate::types::error;
+use crate::{NetworkConfig, NetworkGlobals};
+use libp2p::{Multiaddr, Swarm};
+use parking_lot::RwLock;
+use slog::{o, Logger};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use task_executor::TaskExecutor;
+use types::{ChainSpec, EnrForkId, EthSpec};
+
+/// The next in-memory port to allocate. Memory ports are global to the process, so they are
+/// shared between all simulated networks.
+static NEXT_MEMORY_PORT: AtomicU64 = AtomicU64::new(1);
+
+/// A node of a `SimulatedNetwork`.
+pub struct SimulatedNode<TSpec: EthSpec> {
+    /// The node's libp2p service.
+    pub service: Service<TSpec>,
+    /// The node's network globals.
+    pub network_globals: Arc<NetworkGlobals<TSpec>>,
+    /// The in-memory address the node listens on.
+    pub address: Multiaddr,
+}
+
+/// A network of nodes connected over in-memory transports.
+pub struct SimulatedNetwork<TSpec: EthSpec> {
+    nodes: Vec<SimulatedNode<TSpec>>,
+    conditions: Arc<RwLock<LinkConditions>>,
+}
+
+impl<TSpec: EthSpec> SimulatedNetwork<TSpec> {
+    /// Builds a network of `node_count` unconnected nodes.
+    ///
+    /// Each node uses a copy of `config` with discovery, UPnP and boot nodes disabled, and stores
+    /// its keys and metadata in a `node_{index}` subdirectory of `config.network_dir`. Connection
+    /// loss for node `i` is seeded with `seed + i`.
+    pub async fn new(
+        executor: TaskExecutor,
+        config: &NetworkConfig,
+        node_count: usize,
+        conditions: LinkConditions,
+        seed: u64,
+        log: &Logger,
+        chain_spec: &ChainSpec,
+    ) -> error::Result<Self> {
+        let conditions = Arc::new(RwLock::new(conditions));
+        let mut nodes = Vec::with_capacity(node_count);
+
+        for index in 0..node_count {
+            let mut node_config = config.clone();
+            node_config.network_dir = config.network_dir.join(format!("node_{}", index));
+            node_config.disable_discovery = true;
+            node_config.upnp_enabled = false;
+            node_config.boot_nodes_enr = vec![];
+            node_config.libp2p_nodes = vec![];
+
+            let link = SimulatedLink::new(
+                NEXT_MEMORY_PORT.fetch_add(1, Ordering::Relaxed),
+                conditions.clone(),
+                seed.wrapping_add(index as u64),
+            );
+            let address = link.address().clone();
+
+            let (network_globals, service) = Service::new_with_link(
+                executor.clone(),
+                &node_config,
+                EnrForkId::default(),
+                &log.new(o!("node" => index)),
+                chain_spec,
+                Some(link),
+            )
+            .await?;
+
+            nodes.push(SimulatedNode {
+                service,
+                network_globals,
+                address,
+            });
+        }
+
+        Ok(Self { nodes, conditions })
+    }
+
+    /// Changes the conditions of all links. Latency applies to data received from now on, loss to
+    /// subsequent connection attempts.
+    pub fn set_conditions(&self, conditions: LinkConditions) {
+        *self.conditions.write() = conditions;
+    }
+
+    /// The nodes of the network, in the order they were created.
+    pub fn nodes(&self) -> &[SimulatedNode<TSpec>] {
+        &self.nodes
+    }
+
+    /// Mutable access to the nodes of the network.
+    pub fn nodes_mut(&mut self) -> &mut [SimulatedNode<TSpec>] {
+        &mut self.nodes
+    }
+
+    /// Has node `from` dial node `to`.
+    pub fn dial(&mut self, from: usize, to: usize) -> error::Result<()> {
+        let address = self.nodes[to].address.clone();
+        Swarm::dial_addr(&mut self.nodes[from].service.swarm, address)
+            .map_err(|e| format!("Node {} failed to dial node {}: {:?}", from, to, e).into())
+    }
+
+    /// Dials every pair of nodes once, forming a fully connected topology.
+    pub fn dial_all(&mut self) -> error::Result<()> {
+        for from in 0..self.nodes.len() {
+            for to in from + 1..self.nodes.len() {
+                self.dial(from, to)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drives all nodes until one of them produces an event, returning the index of that node and
+    /// the event.
+    ///
+    /// ## Panics
+    ///
+    /// If the network has no nodes.
+    pub async fn next_event(&mut self) -> (usize, Libp2pEvent<TSpec>) {
+        let (event, index, _) = futures::future::select_all(
+            self.nodes
+                .iter_mut()
+                .map(|node| Box::pin(node.service.next_event())),
+        )
+        .await;
+        (index, event)
+    }
+}
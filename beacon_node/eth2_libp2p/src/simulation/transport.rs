@@ -0,0 +1,209 @@
+//! An in-memory transport with configurable latency and connection loss.
+//!
+//! Connections are made over libp2p's `MemoryTransport`, so they never leave the process. The
+//! streams between connected nodes are reliable, therefore loss is modelled at the granularity of
+//! connection attempts rather than individual packets.
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::prelude::*;
+use libp2p::core::multiaddr::{Multiaddr, Protocol};
+use libp2p::core::transport::{
+    memory::{Channel, MemoryTransport},
+    Boxed, Transport, TransportError,
+};
+use parking_lot::{Mutex, RwLock};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::time::{sleep_until, Sleep};
+
+/// The size of the chunks read from the underlying stream.
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// The conditions applied to every link of a simulated network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConditions {
+    /// The delay applied to all data received over a connection.
+    pub latency: Duration,
+    /// The probability, in `[0, 1]`, that a connection attempt is lost.
+    pub loss: f64,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_secs(0),
+            loss: 0.0,
+        }
+    }
+}
+
+/// The in-memory address of a simulated node, along with the conditions of its links.
+#[derive(Clone)]
+pub struct SimulatedLink {
+    address: Multiaddr,
+    conditions: Arc<RwLock<LinkConditions>>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl SimulatedLink {
+    /// Creates a link listening on `/memory/{port}`. Connection loss is decided by an RNG seeded
+    /// with `seed`, so that it is repeatable.
+    pub fn new(port: u64, conditions: Arc<RwLock<LinkConditions>>, seed: u64) -> Self {
+        Self {
+            address: Multiaddr::empty().with(Protocol::Memory(port)),
+            conditions,
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    /// The address the node listens on.
+    pub fn address(&self) -> &Multiaddr {
+        &self.address
+    }
+
+    /// Returns `true` if the next connection attempt should be lost.
+    fn lose_connection(&self) -> bool {
+        let loss = self.conditions.read().loss;
+        loss > 0.0 && self.rng.lock().gen_bool(loss.min(1.0))
+    }
+
+    /// Builds the unencrypted, unmultiplexed transport for this link.
+    pub(crate) fn transport(&self) -> Boxed<DelayedIo<Channel<Vec<u8>>>> {
+        let conditions = self.conditions.clone();
+        let inner = MemoryTransport::default()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+            .map(move |channel, _| DelayedIo::new(channel, conditions.clone()));
+        LossyTransport {
+            inner,
+            link: self.clone(),
+        }
+        .boxed()
+    }
+}
+
+/// Wraps a transport, failing a fraction of dials according to the link's conditions.
+#[derive(Clone)]
+struct LossyTransport<T> {
+    inner: T,
+    link: SimulatedLink,
+}
+
+impl<T> Transport for LossyTransport<T>
+where
+    T: Transport<Error = io::Error>,
+{
+    type Output = T::Output;
+    type Error = io::Error;
+    type Listener = T::Listener;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = T::Dial;
+
+    fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+        self.inner.listen_on(addr)
+    }
+
+    fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        if self.link.lose_connection() {
+            return Err(TransportError::Other(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("Simulated loss of connection to {}", addr),
+            )));
+        }
+        self.inner.dial(addr)
+    }
+}
+
+/// A stream which delays all data read from the inner stream by the link's latency.
+pub struct DelayedIo<S> {
+    inner: S,
+    conditions: Arc<RwLock<LinkConditions>>,
+    /// Data read from `inner` along with the time at which it may be delivered.
+    pending: VecDeque<(Instant, Vec<u8>)>,
+    /// Wakes the task once the first pending chunk may be delivered.
+    delay: Option<Pin<Box<Sleep>>>,
+    /// `inner` has reached the end of the stream.
+    eof: bool,
+}
+
+impl<S> DelayedIo<S> {
+    fn new(inner: S, conditions: Arc<RwLock<LinkConditions>>) -> Self {
+        Self {
+            inner,
+            conditions,
+            pending: VecDeque::new(),
+            delay: None,
+            eof: false,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DelayedIo<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Timestamp everything that has arrived so far.
+        while !this.eof {
+            let mut chunk = vec![0; READ_CHUNK_SIZE];
+            match Pin::new(&mut this.inner).poll_read(cx, &mut chunk) {
+                Poll::Ready(Ok(0)) => this.eof = true,
+                Poll::Ready(Ok(n)) => {
+                    chunk.truncate(n);
+                    let latency = this.conditions.read().latency;
+                    this.pending.push_back((Instant::now() + latency, chunk));
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => break,
+            }
+        }
+
+        let ready_at = match this.pending.front() {
+            Some((ready_at, _)) => *ready_at,
+            None if this.eof => return Poll::Ready(Ok(0)),
+            None => return Poll::Pending,
+        };
+
+        if ready_at > Instant::now() {
+            let mut delay = Box::pin(sleep_until(ready_at.into()));
+            if delay.as_mut().poll(cx).is_ready() {
+                cx.waker().wake_by_ref();
+            }
+            this.delay = Some(delay);
+            return Poll::Pending;
+        }
+
+        let (ready_at, mut chunk) = this.pending.pop_front().expect("pending is not empty");
+        let n = std::cmp::min(buf.len(), chunk.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        if n < chunk.len() {
+            this.pending.push_front((ready_at, chunk.split_off(n)));
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DelayedIo<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
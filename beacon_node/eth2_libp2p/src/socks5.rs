@@ -0,0 +1,328 @@
+//! A libp2p transport wrapper which dials all outbound connections through a SOCKS5 proxy.
+//!
+//! Only the `CONNECT` command without authentication is supported (RFC 1928). Listening is
+//! delegated to the inner transport unchanged. Domain names (`/dns`, `/dns4`, `/dns6`) are passed
+//! to the proxy unresolved so that name resolution also happens on the far side of the proxy.
+
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures::prelude::*;
+use libp2p::core::multiaddr::{Multiaddr, Protocol};
+use libp2p::core::transport::{Transport, TransportError};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+
+const SOCKS_VERSION: u8 = 0x05;
+const METHOD_NO_AUTH: u8 = 0x00;
+const CMD_CONNECT: u8 = 0x01;
+const RESERVED: u8 = 0x00;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+const REPLY_SUCCEEDED: u8 = 0x00;
+
+/// The destination of a connection requested from the proxy.
+#[derive(Debug, Clone, PartialEq)]
+enum TargetAddr {
+    Ip(SocketAddr),
+    Domain(String, u16),
+}
+
+impl TargetAddr {
+    /// Extracts a target address from a `/ip4`, `/ip6` or `/dns*` multiaddr followed by `/tcp`.
+    ///
+    /// A trailing `/p2p` component is permitted and ignored.
+    fn from_multiaddr(addr: &Multiaddr) -> Option<Self> {
+        let mut iter = addr.iter();
+        let host = iter.next()?;
+        let port = match iter.next()? {
+            Protocol::Tcp(port) => port,
+            _ => return None,
+        };
+        match iter.next() {
+            None | Some(Protocol::P2p(_)) => {}
+            _ => return None,
+        }
+
+        match host {
+            Protocol::Ip4(ip) => Some(TargetAddr::Ip(SocketAddr::new(IpAddr::V4(ip), port))),
+            Protocol::Ip6(ip) => Some(TargetAddr::Ip(SocketAddr::new(IpAddr::V6(ip), port))),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+                Some(TargetAddr::Domain(name.into_owned(), port))
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes the address in the `ATYP | DST.ADDR | DST.PORT` format of a SOCKS5 request.
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            TargetAddr::Ip(SocketAddr::V4(addr)) => {
+                buf.push(ATYP_IPV4);
+                buf.extend_from_slice(&addr.ip().octets());
+                buf.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            TargetAddr::Ip(SocketAddr::V6(addr)) => {
+                buf.push(ATYP_IPV6);
+                buf.extend_from_slice(&addr.ip().octets());
+                buf.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            TargetAddr::Domain(name, port) => {
+                if name.len() > u8::max_value() as usize {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Domain name too long for SOCKS5 request",
+                    ));
+                }
+                buf.push(ATYP_DOMAIN);
+                buf.push(name.len() as u8);
+                buf.extend_from_slice(name.as_bytes());
+                buf.extend_from_slice(&port.to_be_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wraps a transport such that every dial is made to `proxy` and then forwarded to the intended
+/// destination via a SOCKS5 `CONNECT` request.
+#[derive(Debug, Clone)]
+pub struct Socks5Transport<T> {
+    inner: T,
+    proxy: Multiaddr,
+}
+
+impl<T> Socks5Transport<T> {
+    pub fn new(inner: T, proxy: SocketAddr) -> Self {
+        let proxy = Multiaddr::from(proxy.ip()).with(Protocol::Tcp(proxy.port()));
+        Self { inner, proxy }
+    }
+}
+
+impl<T> Transport for Socks5Transport<T>
+where
+    T: Transport<Error = io::Error>,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Dial: Send + 'static,
+{
+    type Output = T::Output;
+    type Error = io::Error;
+    type Listener = T::Listener;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = Pin<Box<dyn Future<Output = io::Result<Self::Output>> + Send>>;
+
+    fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+        self.inner.listen_on(addr)
+    }
+
+    fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let target = match TargetAddr::from_multiaddr(&addr) {
+            Some(target) => target,
+            None => return Err(TransportError::MultiaddrNotSupported(addr)),
+        };
+
+        // The proxy address is always a valid TCP address, so any error here is an error
+        // connecting to the proxy rather than an unsupported destination.
+        let connect_to_proxy = self.inner.dial(self.proxy).map_err(|e| match e {
+            TransportError::MultiaddrNotSupported(proxy) => TransportError::Other(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported SOCKS5 proxy address: {}", proxy),
+            )),
+            other => other,
+        })?;
+
+        Ok(Box::pin(async move {
+            let mut stream = connect_to_proxy.await?;
+            connect(&mut stream, &target).await?;
+            Ok(stream)
+        }))
+    }
+}
+
+/// Performs the SOCKS5 handshake on `stream`, requesting a connection to `target`.
+async fn connect<S>(stream: &mut S, target: &TargetAddr) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Method negotiation: only "no authentication" is offered.
+    stream
+        .write_all(&[SOCKS_VERSION, 1, METHOD_NO_AUTH])
+        .await?;
+    stream.flush().await?;
+
+    let mut method_reply = [0; 2];
+    stream.read_exact(&mut method_reply).await?;
+    if method_reply[0] != SOCKS_VERSION {
+        return Err(protocol_error("Invalid SOCKS version in method reply"));
+    }
+    if method_reply[1] != METHOD_NO_AUTH {
+        return Err(protocol_error("SOCKS5 proxy requires authentication"));
+    }
+
+    // Connection request.
+    let mut request = vec![SOCKS_VERSION, CMD_CONNECT, RESERVED];
+    target.encode(&mut request)?;
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(protocol_error("Invalid SOCKS version in connect reply"));
+    }
+    if reply[1] != REPLY_SUCCEEDED {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!(
+                "SOCKS5 proxy refused connection: {}",
+                reply_reason(reply[1])
+            ),
+        ));
+    }
+
+    // Discard the bound address, it is not required.
+    let bound_addr_len = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => {
+            let mut len = [0; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize
+        }
+        _ => return Err(protocol_error("Invalid address type in connect reply")),
+    };
+    let mut bound_addr = vec![0; bound_addr_len + 2];
+    stream.read_exact(&mut bound_addr).await?;
+
+    Ok(())
+}
+
+fn protocol_error(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Describes a SOCKS5 reply code.
+fn reply_reason(code: u8) -> &'static str {
+    match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::{Context, Poll};
+
+    /// A stream that replays canned proxy responses and records everything written to it.
+    struct MockStream {
+        reply: futures::io::Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl AsyncRead for MockStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.reply).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for MockStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.written.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn mock_stream(reply: Vec<u8>) -> MockStream {
+        MockStream {
+            reply: futures::io::Cursor::new(reply),
+            written: vec![],
+        }
+    }
+
+    #[test]
+    fn target_from_multiaddr() {
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/9000".parse().unwrap();
+        assert_eq!(
+            TargetAddr::from_multiaddr(&addr),
+            Some(TargetAddr::Ip("1.2.3.4:9000".parse().unwrap()))
+        );
+
+        let addr: Multiaddr = "/dns4/example.com/tcp/9000".parse().unwrap();
+        assert_eq!(
+            TargetAddr::from_multiaddr(&addr),
+            Some(TargetAddr::Domain("example.com".into(), 9000))
+        );
+
+        let addr: Multiaddr = "/ip4/1.2.3.4/udp/9000".parse().unwrap();
+        assert_eq!(TargetAddr::from_multiaddr(&addr), None);
+    }
+
+    #[test]
+    fn connect_through_proxy() {
+        let reply = vec![
+            SOCKS_VERSION,
+            METHOD_NO_AUTH,
+            SOCKS_VERSION,
+            REPLY_SUCCEEDED,
+            RESERVED,
+            ATYP_IPV4,
+            127,
+            0,
+            0,
+            1,
+            0x23,
+            0x28,
+        ];
+        let mut stream = mock_stream(reply);
+        let target = TargetAddr::Domain("example.com".into(), 9000);
+
+        futures::executor::block_on(connect(&mut stream, &target)).unwrap();
+
+        let mut expected = vec![SOCKS_VERSION, 1, METHOD_NO_AUTH];
+        expected.extend_from_slice(&[SOCKS_VERSION, CMD_CONNECT, RESERVED, ATYP_DOMAIN, 11]);
+        expected.extend_from_slice(b"example.com");
+        expected.extend_from_slice(&9000u16.to_be_bytes());
+        assert_eq!(stream.written, expected);
+    }
+
+    #[test]
+    fn connect_refused_by_proxy() {
+        let reply = vec![
+            SOCKS_VERSION,
+            METHOD_NO_AUTH,
+            SOCKS_VERSION,
+            0x05,
+            RESERVED,
+            ATYP_IPV4,
+        ];
+        let mut stream = mock_stream(reply);
+        let target = TargetAddr::Ip("1.2.3.4:9000".parse().unwrap());
+
+        let err = futures::executor::block_on(connect(&mut stream, &target)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// A snapshot of the discovery routing table and query statistics.
+///
+/// This is periodically refreshed by the discovery service so that it can be read from outside
+/// the network thread (e.g., by the HTTP API).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryInfo {
+    /// Indicates if the discv5 service is running.
+    pub enabled: bool,
+    /// The node id of the local node.
+    pub local_node_id: String,
+    /// The total number of entries in the routing table.
+    pub table_size: usize,
+    /// The occupancy of each non-empty bucket in the routing table.
+    pub buckets: Vec<BucketInfo>,
+    /// The entries of the routing table.
+    pub entries: Vec<TableEntryInfo>,
+    /// Statistics on the discovery queries performed by this node.
+    pub queries: QueryStats,
+}
+
+/// The number of entries in a single k-bucket of the routing table.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BucketInfo {
+    /// The log2 distance from the local node id that this bucket represents.
+    pub distance: u64,
+    /// The number of nodes in the bucket.
+    pub entries: usize,
+}
+
+/// Details of a single ENR held in the routing table.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableEntryInfo {
+    /// The node id of the entry.
+    pub node_id: String,
+    /// The libp2p peer id derived from the ENR public key.
+    pub peer_id: String,
+    /// The base64 encoded ENR.
+    pub enr: String,
+    /// The sequence number of the ENR.
+    pub seq: u64,
+    /// The log2 distance of the entry from the local node id.
+    pub distance: Option<u64>,
+    /// The IPv4 address advertised in the ENR, if any.
+    pub ip: Option<IpAddr>,
+    /// The UDP port advertised in the ENR, if any.
+    pub udp: Option<u16>,
+    /// The TCP port advertised in the ENR, if any.
+    pub tcp: Option<u16>,
+}
+
+/// Counts of the discovery queries started and their outcomes since the node started.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryStats {
+    /// The number of queries waiting to be started.
+    pub queued: usize,
+    /// The number of queries currently in progress.
+    pub active: usize,
+    /// The total number of queries started.
+    pub started: u64,
+    /// The total number of queries that completed successfully.
+    pub completed: u64,
+    /// The total number of queries that returned an error.
+    pub failed: u64,
+    /// The total number of ENRs returned by completed queries.
+    pub peers_found: u64,
+}
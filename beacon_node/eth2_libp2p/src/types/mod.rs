@@ -1,3 +1,4 @@
+mod discovery_info;
 pub mod error;
 mod globals;
 mod pubsub;
@@ -12,6 +13,7 @@ pub type EnrBitfield<T: EthSpec> = BitVector<T::SubnetBitfieldLength>;
 
 pub type Enr = discv5::enr::Enr<discv5::enr::CombinedKey>;
 
+pub use discovery_info::{BucketInfo, DiscoveryInfo, QueryStats, TableEntryInfo};
 pub use globals::NetworkGlobals;
 pub use pubsub::{MessageData, PubsubMessage};
 pub use subnet::SubnetDiscovery;
@@ -0,0 +1,114 @@
+#![cfg(feature = "simulation")]
+use eth2_libp2p::simulation::{LinkConditions, SimulatedNetwork};
+use slog::Level;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::time::timeout;
+use types::{ChainSpec, MinimalEthSpec};
+
+mod common;
+
+type E = MinimalEthSpec;
+
+async fn build_network(
+    rt: &Arc<Runtime>,
+    log: &slog::Logger,
+    node_count: usize,
+    conditions: LinkConditions,
+) -> (SimulatedNetwork<E>, exit_future::Signal) {
+    let config = common::build_config(0, vec![]);
+    let (signal, exit) = exit_future::signal();
+    let (shutdown_tx, _) = futures::channel::mpsc::channel(1);
+    let executor =
+        task_executor::TaskExecutor::new(Arc::downgrade(rt), exit, log.clone(), shutdown_tx);
+    let network = SimulatedNetwork::new(
+        executor,
+        &config,
+        node_count,
+        conditions,
+        42,
+        log,
+        &ChainSpec::minimal(),
+    )
+    .await
+    .expect("should build simulated network");
+    (network, signal)
+}
+
+fn fully_connected(network: &SimulatedNetwork<E>) -> bool {
+    let expected = network.nodes().len() - 1;
+    network
+        .nodes()
+        .iter()
+        .all(|node| node.network_globals.connected_peers() == expected)
+}
+
+// Tests that nodes connect over links with latency
+#[test]
+fn test_simulated_network_connects() {
+    let log_level = Level::Debug;
+    let enable_logging = false;
+
+    let rt = Arc::new(Runtime::new().unwrap());
+    let log = common::build_log(log_level, enable_logging);
+
+    rt.block_on(async {
+        let conditions = LinkConditions {
+            latency: Duration::from_millis(50),
+            loss: 0.0,
+        };
+        let (mut network, _signal) = build_network(&rt, &log, 3, conditions).await;
+        network.dial_all().unwrap();
+
+        let connect = async {
+            while !fully_connected(&network) {
+                network.next_event().await;
+            }
+        };
+        timeout(Duration::from_secs(10), connect)
+            .await
+            .expect("nodes should connect");
+    });
+}
+
+// Tests that lost connection attempts do not connect, and that links recover once the
+// conditions change
+#[test]
+fn test_simulated_network_loss() {
+    let log_level = Level::Debug;
+    let enable_logging = false;
+
+    let rt = Arc::new(Runtime::new().unwrap());
+    let log = common::build_log(log_level, enable_logging);
+
+    rt.block_on(async {
+        let conditions = LinkConditions {
+            latency: Duration::from_millis(0),
+            loss: 1.0,
+        };
+        let (mut network, _signal) = build_network(&rt, &log, 2, conditions).await;
+        network.dial(0, 1).unwrap();
+
+        let run = async {
+            loop {
+                network.next_event().await;
+            }
+        };
+        let _ = timeout(Duration::from_secs(1), run).await;
+        assert_eq!(network.nodes()[0].network_globals.connected_peers(), 0);
+        assert_eq!(network.nodes()[1].network_globals.connected_peers(), 0);
+
+        network.set_conditions(LinkConditions::default());
+        network.dial(0, 1).unwrap();
+
+        let connect = async {
+            while !fully_connected(&network) {
+                network.next_event().await;
+            }
+        };
+        timeout(Duration::from_secs(10), connect)
+            .await
+            .expect("nodes should connect once loss is removed");
+    });
+}
@@ -1,5 +1,7 @@
 use crate::metrics;
-use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use beacon_chain::{
+    proposer_shuffling_decision_root, BeaconChain, BeaconChainError, BeaconChainTypes,
+};
 use eth2::types::ProposerData;
 use fork_choice::ProtoBlock;
 use slot_clock::SlotClock;
@@ -76,28 +78,45 @@ impl BeaconProposerCache {
             per_slot_processing(&mut head_state, Some(Hash256::zero()), &chain.spec)?;
         }
 
-        let proposers = current_epoch
+        let proposer_indices = current_epoch
             .slot_iter(T::EthSpec::slots_per_epoch())
-            .map(|slot| {
-                head_state
-                    .get_beacon_proposer_index(slot, &chain.spec)
-                    .map_err(BeaconChainError::from)
-                    .and_then(|i| {
-                        let pubkey = chain
-                            .validator_pubkey(i)?
-                            .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheIncomplete(i))?;
-
-                        Ok(ProposerData {
-                            pubkey: PublicKeyBytes::from(pubkey),
-                            validator_index: i as u64,
-                            slot,
-                        })
-                    })
+            .map(|slot| head_state.get_beacon_proposer_index(slot, &chain.spec))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        chain.proposer_cache.lock().insert(
+            current_epoch,
+            decision_block_root,
+            proposer_indices.clone(),
+        );
+
+        Self::from_proposer_indices(chain, current_epoch, decision_block_root, &proposer_indices)
+    }
+
+    /// Create a new cache from the proposer indices for each slot of `epoch`.
+    fn from_proposer_indices<T: BeaconChainTypes>(
+        chain: &BeaconChain<T>,
+        epoch: Epoch,
+        decision_block_root: Hash256,
+        proposer_indices: &[usize],
+    ) -> Result<Self, BeaconChainError> {
+        let proposers = epoch
+            .slot_iter(T::EthSpec::slots_per_epoch())
+            .zip(proposer_indices)
+            .map(|(slot, &i)| {
+                let pubkey = chain
+                    .validator_pubkey(i)?
+                    .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheIncomplete(i))?;
+
+                Ok(ProposerData {
+                    pubkey: PublicKeyBytes::from(pubkey),
+                    validator_index: i as u64,
+                    slot,
+                })
             })
-            .collect::<Result<_, _>>()?;
+            .collect::<Result<_, BeaconChainError>>()?;
 
         Ok(Self {
-            epoch: current_epoch,
+            epoch,
             decision_block_root,
             proposers,
         })
@@ -114,19 +133,8 @@ impl BeaconProposerCache {
         head_block_root: Hash256,
         head_block_state: &BeaconState<E>,
     ) -> Result<Hash256, BeaconChainError> {
-        let decision_slot = shuffling_epoch
-            .start_slot(E::slots_per_epoch())
-            .saturating_sub(1_u64);
-
-        // If decision slot is equal to or ahead of the head, the block root is the head block root
-        if decision_slot >= head_block_state.slot {
-            Ok(head_block_root)
-        } else {
-            head_block_state
-                .get_block_root(decision_slot)
-                .map(|root| *root)
-                .map_err(Into::into)
-        }
+        proposer_shuffling_decision_root(shuffling_epoch, head_block_root, head_block_state)
+            .map_err(Into::into)
     }
 
     /// Return the proposers for the given `Epoch`.
@@ -171,12 +179,27 @@ impl BeaconProposerCache {
             .ok_or_else(|| BeaconChainError::MissingBeaconBlock(head_block_root))
             .map_err(warp_utils::reject::beacon_chain_error)?;
 
-        // Rebuild the cache if this call causes a cache-miss.
+        // Rebuild the cache if this call causes a cache-miss, preferring the proposers which the
+        // beacon chain precomputed at the start of the epoch.
         if self.epoch != current_epoch || self.decision_block_root != head_decision_block_root {
             metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_MISSES_TOTAL);
 
-            *self = Self::for_head_block(chain, current_epoch, head_block_root, head_block)
-                .map_err(warp_utils::reject::beacon_chain_error)?;
+            let precomputed = chain
+                .proposer_cache
+                .lock()
+                .get(current_epoch, head_decision_block_root)
+                .map(<[usize]>::to_vec);
+
+            *self = match precomputed {
+                Some(proposer_indices) => Self::from_proposer_indices(
+                    chain,
+                    current_epoch,
+                    head_decision_block_root,
+                    &proposer_indices,
+                ),
+                None => Self::for_head_block(chain, current_epoch, head_block_root, head_block),
+            }
+            .map_err(warp_utils::reject::beacon_chain_error)?;
         } else {
             metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_HITS_TOTAL);
         }
@@ -28,15 +28,15 @@ use slot_clock::SlotClock;
 use ssz::Encode;
 use state_id::StateId;
 use state_processing::per_slot_processing;
-use std::borrow::Cow;
 use std::convert::TryInto;
 use std::future::Future;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::UnboundedSender;
 use types::{
-    Attestation, AttestationDuty, AttesterSlashing, CloneConfig, CommitteeCache, Epoch, EthSpec,
-    Hash256, ProposerSlashing, PublicKey, PublicKeyBytes, RelativeEpoch, SignedAggregateAndProof,
+    Attestation, AttestationDuty, AttesterSlashing, CloneConfig, Epoch, EthSpec, Hash256,
+    ProposerSlashing, PublicKey, PublicKeyBytes, RelativeEpoch, SignedAggregateAndProof,
     SignedBeaconBlock, SignedVoluntaryExit, Slot, YamlConfig,
 };
 use warp::http::StatusCode;
@@ -72,6 +72,10 @@ pub struct Config {
     pub listen_addr: Ipv4Addr,
     pub listen_port: u16,
     pub allow_origin: Option<String>,
+    /// Whether `lighthouse/database/backup` may write backups to the node's filesystem.
+    pub allow_database_backup: bool,
+    /// Whether the slasher may be paused and resumed with `lighthouse/slasher/{pause,resume}`.
+    pub allow_slasher_control: bool,
 }
 
 impl Default for Config {
@@ -81,6 +85,8 @@ impl Default for Config {
             listen_addr: Ipv4Addr::new(127, 0, 0, 1),
             listen_port: 5052,
             allow_origin: None,
+            allow_database_backup: false,
+            allow_slasher_control: false,
         }
     }
 }
@@ -381,6 +387,35 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET beacon/deposit_snapshot
+    let get_beacon_deposit_snapshot = eth1_v1
+        .and(warp::path("beacon"))
+        .and(warp::path("deposit_snapshot"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and(eth1_service_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, eth1_service: eth1::Service| {
+            blocking_json_task(move || {
+                let (eth1_data, eth1_deposit_index) = StateId::finalized()
+                    .map_state(&chain, |state| {
+                        Ok((state.eth1_data.clone(), state.eth1_deposit_index))
+                    })?;
+
+                // A snapshot can't be used to produce blocks while its deposits are pending.
+                if eth1_deposit_index < eth1_data.deposit_count {
+                    return Err(warp_utils::reject::custom_not_found(format!(
+                        "finalized state has pending deposits ({} of {} processed)",
+                        eth1_deposit_index, eth1_data.deposit_count
+                    )));
+                }
+
+                eth1_service
+                    .get_deposit_snapshot(&eth1_data)
+                    .map_err(warp_utils::reject::custom_server_error)
+                    .map(api_types::GenericResponse::from)
+            })
+        });
+
     /*
      * beacon/states/{state_id}
      */
@@ -611,20 +646,17 @@ pub fn serve<T: BeaconChainTypes>(
                 });
 
                 blocking_json_task(move || {
+                    let state_root = query_state_id.root(&chain)?;
                     query_state_id.map_state(&chain, |state| {
                         let epoch = state.slot.epoch(T::EthSpec::slots_per_epoch());
 
-                        let committee_cache = if state
-                            .committee_cache_is_initialized(RelativeEpoch::Current)
-                        {
-                            state
-                                .committee_cache(RelativeEpoch::Current)
-                                .map(Cow::Borrowed)
-                        } else {
-                            CommitteeCache::initialized(state, epoch, &chain.spec).map(Cow::Owned)
-                        }
-                        .map_err(BeaconChainError::BeaconStateError)
-                        .map_err(warp_utils::reject::beacon_chain_error)?;
+                        let committee_cache = chain
+                            .committee_cache_for_state(
+                                state,
+                                state.get_latest_block_root(state_root),
+                                RelativeEpoch::Current,
+                            )
+                            .map_err(warp_utils::reject::beacon_chain_error)?;
 
                         // Use either the supplied slot or all slots in the epoch.
                         let slots = query.slot.map(|slot| vec![slot]).unwrap_or_else(|| {
@@ -1773,13 +1805,14 @@ pub fn serve<T: BeaconChainTypes>(
                             .collect::<Result<Vec<_>, warp::Rejection>>()?
                     } else {
                         // If the head state is equal to or earlier than the request epoch, use it.
-                        let mut state = chain
+                        let (mut state, block_root) = chain
                             .with_head(|head| {
                                 if head.beacon_state.current_epoch() <= epoch {
-                                    Ok(Some(
+                                    Ok(Some((
                                         head.beacon_state
                                             .clone_with(CloneConfig::committee_caches_only()),
-                                    ))
+                                        head.beacon_block_root,
+                                    )))
                                 } else {
                                     Ok(None)
                                 }
@@ -1787,8 +1820,12 @@ pub fn serve<T: BeaconChainTypes>(
                             .map_err(warp_utils::reject::beacon_chain_error)?
                             .map(Result::Ok)
                             .unwrap_or_else(|| {
-                                StateId::slot(epoch.start_slot(T::EthSpec::slots_per_epoch()))
-                                    .state(&chain)
+                                let state_id =
+                                    StateId::slot(epoch.start_slot(T::EthSpec::slots_per_epoch()));
+                                let state_root = state_id.root(&chain)?;
+                                let state = state_id.state(&chain)?;
+                                let block_root = state.get_latest_block_root(state_root);
+                                Ok((state, block_root))
                             })?;
 
                         // Only skip forward to the epoch prior to the request, since we have a
@@ -1814,9 +1851,13 @@ pub fn serve<T: BeaconChainTypes>(
                                 },
                             )?;
 
-                        state
-                            .build_committee_cache(relative_epoch, &chain.spec)
-                            .map_err(warp_utils::reject::beacon_state_error)?;
+                        chain
+                            .build_committee_cache_with_shuffling_cache(
+                                &mut state,
+                                block_root,
+                                relative_epoch,
+                            )
+                            .map_err(warp_utils::reject::beacon_chain_error)?;
                         pubkeys
                             .into_iter()
                             .filter_map(|(i, pubkey)| {
@@ -2006,7 +2047,7 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("peers"))
         .and(warp::path("connected"))
         .and(warp::path::end())
-        .and(network_globals)
+        .and(network_globals.clone())
         .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
             blocking_json_task(move || {
                 Ok(network_globals
@@ -2021,6 +2062,113 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/peers/banned
+    let get_lighthouse_peers_banned = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("banned"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+            blocking_json_task(move || {
+                let peer_db = network_globals.peers.read();
+                let now = Instant::now();
+
+                let mut banned = peer_db
+                    .manual_bans()
+                    .map(|(peer_id, until)| eth2::lighthouse::BannedPeer {
+                        peer_id: peer_id.to_string(),
+                        manual: true,
+                        expires_in_secs: until
+                            .map(|until| until.saturating_duration_since(now).as_secs()),
+                    })
+                    .collect::<Vec<_>>();
+                banned.extend(
+                    peer_db
+                        .banned_peers()
+                        .filter(|peer_id| !peer_db.is_manually_banned(peer_id))
+                        .map(|peer_id| eth2::lighthouse::BannedPeer {
+                            peer_id: peer_id.to_string(),
+                            manual: false,
+                            expires_in_secs: None,
+                        }),
+                );
+
+                Ok(api_types::GenericResponse::from(banned))
+            })
+        });
+
+    // POST lighthouse/peers/{peer_id}/ban
+    let post_lighthouse_peers_ban = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("ban"))
+        .and(warp::path::end())
+        .and(warp::query::<eth2::lighthouse::BanPeerQuery>())
+        .and(network_tx_filter.clone())
+        .and_then(
+            |peer_id: String,
+             query: eth2::lighthouse::BanPeerQuery,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
+                blocking_json_task(move || {
+                    let peer_id = parse_peer_id(&peer_id)?;
+                    publish_network_message(
+                        &network_tx,
+                        NetworkMessage::BanPeer {
+                            peer_id,
+                            duration: query.duration_secs.map(Duration::from_secs),
+                        },
+                    )
+                })
+            },
+        );
+
+    // POST lighthouse/peers/{peer_id}/unban
+    let post_lighthouse_peers_unban = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("unban"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and(network_tx_filter.clone())
+        .and_then(
+            |peer_id: String,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
+                blocking_json_task(move || {
+                    let peer_id = parse_peer_id(&peer_id)?;
+
+                    let is_banned = {
+                        let peer_db = network_globals.peers.read();
+                        peer_db.is_manually_banned(&peer_id)
+                            || peer_db
+                                .peer_info(&peer_id)
+                                .map_or(false, |info| info.is_banned())
+                    };
+                    if !is_banned {
+                        return Err(warp_utils::reject::custom_not_found(format!(
+                            "peer {} is not banned",
+                            peer_id
+                        )));
+                    }
+
+                    publish_network_message(&network_tx, NetworkMessage::UnbanPeer { peer_id })
+                })
+            },
+        );
+
+    // GET lighthouse/discovery
+    let get_lighthouse_discovery = warp::path("lighthouse")
+        .and(warp::path("discovery"))
+        .and(warp::path::end())
+        .and(network_globals)
+        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+            blocking_json_task(move || {
+                Ok(api_types::GenericResponse::from(
+                    network_globals.discovery_info(),
+                ))
+            })
+        });
+
     // GET lighthouse/proto_array
     let get_lighthouse_proto_array = warp::path("lighthouse")
         .and(warp::path("proto_array"))
@@ -2160,6 +2308,186 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/database/info
+    let get_lighthouse_database_info = warp::path("lighthouse")
+        .and(warp::path("database"))
+        .and(warp::path("info"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || {
+                let freezer_compression = chain.store.freezer_compression_stats().map(|stats| {
+                    eth2::lighthouse::CompressionInfo {
+                        codec: stats.compression.codec().to_string(),
+                        level: stats.compression.level(),
+                        uncompressed_bytes: stats.uncompressed_bytes,
+                        compressed_bytes: stats.compressed_bytes,
+                        ratio: stats.ratio(),
+                    }
+                });
+                Ok(api_types::GenericResponse::from(
+                    eth2::lighthouse::DatabaseInfo {
+                        split_slot: chain.store.get_split_slot(),
+                        freezer_compression,
+                    },
+                ))
+            })
+        });
+
+    // POST lighthouse/database/backup
+    let allow_database_backup = config.allow_database_backup;
+    let backup_lock = Arc::new(Mutex::new(()));
+    let post_lighthouse_database_backup = warp::path("lighthouse")
+        .and(warp::path("database"))
+        .and(warp::path("backup"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(chain_filter.clone())
+        .and_then(
+            move |request: eth2::lighthouse::DatabaseBackupRequest, chain: Arc<BeaconChain<T>>| {
+                let backup_lock = backup_lock.clone();
+                blocking_json_task(move || {
+                    if !allow_database_backup {
+                        return Err(warp_utils::reject::custom_bad_request(
+                            "database backups are disabled, see --http-allow-database-backup"
+                                .to_string(),
+                        ));
+                    }
+                    let _backup_guard = backup_lock.try_lock().ok_or_else(|| {
+                        warp_utils::reject::custom_bad_request(
+                            "a database backup is already in progress".to_string(),
+                        )
+                    })?;
+
+                    let path = request.path;
+                    if !path.is_absolute() {
+                        return Err(warp_utils::reject::custom_bad_request(format!(
+                            "backup path {} is not absolute",
+                            path.display()
+                        )));
+                    }
+                    if path.exists() {
+                        return Err(warp_utils::reject::custom_bad_request(format!(
+                            "backup path {} already exists",
+                            path.display()
+                        )));
+                    }
+                    std::fs::create_dir_all(&path).map_err(|e| {
+                        warp_utils::reject::custom_server_error(format!(
+                            "unable to create {}: {:?}",
+                            path.display(),
+                            e
+                        ))
+                    })?;
+
+                    // Mirror the layout of the beacon node's data directory, so that the backup
+                    // can be restored by copying it into place.
+                    let summary = chain
+                        .store
+                        .backup(&path.join("chain_db"), &path.join("freezer_db"))
+                        .map_err(|e| {
+                            let _ = std::fs::remove_dir(&path);
+                            warp_utils::reject::custom_server_error(format!(
+                                "database backup failed: {:?}",
+                                e
+                            ))
+                        })?;
+
+                    Ok(api_types::GenericResponse::from(
+                        eth2::lighthouse::DatabaseBackup {
+                            hot_db_entries: summary.hot_db_entries,
+                            freezer_db_entries: summary.cold_db_entries,
+                        },
+                    ))
+                })
+            },
+        );
+
+    // POST lighthouse/database/gc
+    let post_lighthouse_database_gc = warp::path("lighthouse")
+        .and(warp::path("database"))
+        .and(warp::path("gc"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || {
+                let gc = chain.garbage_collect_orphaned_states().map_err(|e| {
+                    warp_utils::reject::custom_server_error(format!(
+                        "state garbage collection failed: {:?}",
+                        e
+                    ))
+                })?;
+
+                Ok(api_types::GenericResponse::from(
+                    eth2::lighthouse::DatabaseGc {
+                        states_deleted: gc.states_deleted,
+                        bytes_reclaimed: gc.bytes_reclaimed,
+                    },
+                ))
+            })
+        });
+
+    // GET lighthouse/slasher/status
+    let get_lighthouse_slasher_status = warp::path("lighthouse")
+        .and(warp::path("slasher"))
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || {
+                let status = chain.slasher_status().ok_or_else(|| {
+                    warp_utils::reject::custom_not_found(
+                        "The slasher is disabled. See the --slasher CLI flag.".to_string(),
+                    )
+                })?;
+                let epochs = status
+                    .epochs
+                    .iter()
+                    .map(|stats| eth2::lighthouse::SlasherEpochStats {
+                        epoch: stats.epoch,
+                        attestations_processed: stats.attestations_processed,
+                        attestations_dropped: stats.attestations_dropped,
+                        blocks_processed: stats.blocks_processed,
+                    })
+                    .collect();
+                Ok(api_types::GenericResponse::from(
+                    eth2::lighthouse::SlasherStatus {
+                        database_size: status.database_size,
+                        current_epoch: status.current_epoch,
+                        oldest_epoch: status.oldest_epoch,
+                        history_length: status.history_length,
+                        paused: status.paused,
+                        attestation_queue_len: status.attestation_queue_len,
+                        block_queue_len: status.block_queue_len,
+                        attester_slashings_found: status.attester_slashings_found,
+                        proposer_slashings_found: status.proposer_slashings_found,
+                        epochs,
+                    },
+                ))
+            })
+        });
+
+    // POST lighthouse/slasher/pause
+    let allow_slasher_control = config.allow_slasher_control;
+    let post_lighthouse_slasher_pause = warp::path("lighthouse")
+        .and(warp::path("slasher"))
+        .and(warp::path("pause"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(move |chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || set_slasher_paused(&chain, allow_slasher_control, true))
+        });
+
+    // POST lighthouse/slasher/resume
+    let post_lighthouse_slasher_resume = warp::path("lighthouse")
+        .and(warp::path("slasher"))
+        .and(warp::path("resume"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(move |chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || set_slasher_paused(&chain, allow_slasher_control, false))
+        });
+
     // GET lighthouse/staking
     let get_lighthouse_staking = warp::path("lighthouse")
         .and(warp::path("staking"))
@@ -2184,6 +2512,7 @@ pub fn serve<T: BeaconChainTypes>(
         .and(
             get_beacon_genesis
                 .boxed()
+                .or(get_beacon_deposit_snapshot.boxed())
                 .or(get_beacon_state_root.boxed())
                 .or(get_beacon_state_fork.boxed())
                 .or(get_beacon_state_finality_checkpoints.boxed())
@@ -2220,6 +2549,8 @@ pub fn serve<T: BeaconChainTypes>(
                 .or(get_lighthouse_syncing.boxed())
                 .or(get_lighthouse_peers.boxed())
                 .or(get_lighthouse_peers_connected.boxed())
+                .or(get_lighthouse_peers_banned.boxed())
+                .or(get_lighthouse_discovery.boxed())
                 .or(get_lighthouse_proto_array.boxed())
                 .or(get_lighthouse_validator_inclusion_global.boxed())
                 .or(get_lighthouse_validator_inclusion.boxed())
@@ -2227,6 +2558,8 @@ pub fn serve<T: BeaconChainTypes>(
                 .or(get_lighthouse_eth1_block_cache.boxed())
                 .or(get_lighthouse_eth1_deposit_cache.boxed())
                 .or(get_lighthouse_beacon_states_ssz.boxed())
+                .or(get_lighthouse_database_info.boxed())
+                .or(get_lighthouse_slasher_status.boxed())
                 .or(get_lighthouse_staking.boxed()),
         )
         .or(warp::post().and(
@@ -2238,7 +2571,13 @@ pub fn serve<T: BeaconChainTypes>(
                 .or(post_beacon_pool_voluntary_exits.boxed())
                 .or(post_validator_duties_attester.boxed())
                 .or(post_validator_aggregate_and_proofs.boxed())
-                .or(post_validator_beacon_committee_subscriptions.boxed()),
+                .or(post_validator_beacon_committee_subscriptions.boxed())
+                .or(post_lighthouse_peers_ban.boxed())
+                .or(post_lighthouse_peers_unban.boxed())
+                .or(post_lighthouse_database_backup.boxed())
+                .or(post_lighthouse_database_gc.boxed())
+                .or(post_lighthouse_slasher_pause.boxed())
+                .or(post_lighthouse_slasher_resume.boxed()),
         ))
         .recover(warp_utils::reject::handle_rejection)
         .with(slog_logging(log.clone()))
@@ -2265,6 +2604,33 @@ pub fn serve<T: BeaconChainTypes>(
     Ok((listening_socket, server))
 }
 
+/// Pause or resume the slasher, if the endpoints to do so are enabled.
+fn set_slasher_paused<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    allow_slasher_control: bool,
+    paused: bool,
+) -> Result<(), warp::Rejection> {
+    if !allow_slasher_control {
+        return Err(warp_utils::reject::custom_bad_request(
+            "slasher control is disabled, see --http-allow-slasher-control".to_string(),
+        ));
+    }
+    if chain.set_slasher_paused(paused) {
+        Ok(())
+    } else {
+        Err(warp_utils::reject::custom_not_found(
+            "The slasher is disabled. See the --slasher CLI flag.".to_string(),
+        ))
+    }
+}
+
+/// Parses a base58-encoded peer id provided by the user.
+fn parse_peer_id(peer_id: &str) -> Result<PeerId, warp::Rejection> {
+    peer_id.parse().map_err(|_| {
+        warp_utils::reject::custom_bad_request(format!("invalid peer id: {}", peer_id))
+    })
+}
+
 /// Publish a message to the libp2p pubsub network.
 fn publish_pubsub_message<T: EthSpec>(
     network_tx: &UnboundedSender<NetworkMessage<T>>,
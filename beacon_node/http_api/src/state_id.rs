@@ -12,6 +12,10 @@ impl StateId {
         Self(CoreStateId::Head)
     }
 
+    pub fn finalized() -> Self {
+        Self(CoreStateId::Finalized)
+    }
+
     pub fn slot(slot: Slot) -> Self {
         Self(CoreStateId::Slot(slot))
     }
@@ -12,7 +12,7 @@ use eth2::{types::*, BeaconNodeHttpClient, Url};
 use eth2_libp2p::{
     rpc::methods::MetaData,
     types::{EnrBitfield, SyncState},
-    Enr, EnrExt, NetworkGlobals, PeerId,
+    DiscoveryInfo, Enr, EnrExt, NetworkGlobals, PeerId,
 };
 use http_api::{Config, Context};
 use network::NetworkMessage;
@@ -176,6 +176,8 @@ impl ApiTester {
                 listen_addr: Ipv4Addr::new(127, 0, 0, 1),
                 listen_port: 0,
                 allow_origin: None,
+                allow_database_backup: false,
+                allow_slasher_control: false,
             },
             chain: Some(chain.clone()),
             network_tx: Some(network_tx),
@@ -1748,6 +1750,58 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_discovery(self) -> Self {
+        let result = self.client.get_lighthouse_discovery().await.unwrap().data;
+
+        // The tester has no discovery service, so the snapshot is never populated.
+        assert_eq!(result, DiscoveryInfo::default());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_peers_ban(mut self) -> Self {
+        let peer_id = self.external_peer_id.to_string();
+
+        self.client
+            .post_lighthouse_peers_ban(&peer_id, Some(60))
+            .await
+            .unwrap();
+
+        assert!(
+            self.network_rx.try_recv().is_ok(),
+            "ban should be sent to network"
+        );
+
+        // The tester has no network service, so the ban is never applied.
+        let banned = self
+            .client
+            .get_lighthouse_peers_banned()
+            .await
+            .unwrap()
+            .data;
+        assert!(banned.is_empty());
+
+        let err = self
+            .client
+            .post_lighthouse_peers_unban(&peer_id)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(
+            self.network_rx.try_recv().is_err(),
+            "unbanning a peer that is not banned should not be sent to network"
+        );
+
+        let err = self
+            .client
+            .post_lighthouse_peers_ban("invalid", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+
+        self
+    }
+
     pub async fn test_get_lighthouse_proto_array(self) -> Self {
         self.client.get_lighthouse_proto_array().await.unwrap();
 
@@ -1817,6 +1871,84 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_database_info(self) -> Self {
+        let info = self
+            .client
+            .get_lighthouse_database_info()
+            .await
+            .unwrap()
+            .data;
+
+        assert_eq!(info.split_slot, self.chain.store.get_split_slot());
+        assert_eq!(
+            info.freezer_compression.is_some(),
+            self.chain.store.freezer_compression_stats().is_some()
+        );
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_database_backup_disabled(self) -> Self {
+        let request = eth2::lighthouse::DatabaseBackupRequest {
+            path: std::env::temp_dir().join("lighthouse_http_api_test_backup"),
+        };
+
+        let err = self
+            .client
+            .post_lighthouse_database_backup(&request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        assert!(!request.path.exists());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_database_gc(self) -> Self {
+        self.client.post_lighthouse_database_gc().await.unwrap();
+
+        // The first collection leaves nothing for the second.
+        let gc = self
+            .client
+            .post_lighthouse_database_gc()
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(gc.states_deleted, 0);
+        assert_eq!(gc.bytes_reclaimed, 0);
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_slasher_status_disabled(self) -> Self {
+        let err = self
+            .client
+            .get_lighthouse_slasher_status()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_slasher_pause_disabled(self) -> Self {
+        let err = self
+            .client
+            .post_lighthouse_slasher_pause()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+
+        let err = self
+            .client
+            .post_lighthouse_slasher_resume()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+
+        self
+    }
+
     pub async fn test_get_lighthouse_staking(self) -> Self {
         let result = self.client.get_lighthouse_staking().await.unwrap();
 
@@ -2135,6 +2267,12 @@ async fn lighthouse_endpoints() {
         .test_get_lighthouse_syncing()
         .compat()
         .await
+        .test_get_lighthouse_discovery()
+        .compat()
+        .await
+        .test_post_lighthouse_peers_ban()
+        .compat()
+        .await
         .test_get_lighthouse_proto_array()
         .compat()
         .await
@@ -2156,6 +2294,21 @@ async fn lighthouse_endpoints() {
         .test_get_lighthouse_beacon_states_ssz()
         .compat()
         .await
+        .test_get_lighthouse_database_info()
+        .compat()
+        .await
+        .test_post_lighthouse_database_backup_disabled()
+        .compat()
+        .await
+        .test_post_lighthouse_database_gc()
+        .compat()
+        .await
+        .test_get_lighthouse_slasher_status_disabled()
+        .compat()
+        .await
+        .test_post_lighthouse_slasher_pause_disabled()
+        .compat()
+        .await
         .test_get_lighthouse_staking()
         .compat()
         .await;
@@ -1,22 +1,24 @@
 //! This service keeps track of which shard subnet the beacon node should be subscribed to at any
 //! given time. It schedules subscriptions to shard subnets, requests peer discoveries and
 //! determines whether attestations should be aggregated and/or passed to the beacon node.
+//!
+//! Long-lived subnet subscriptions (the subnet "backbone") are derived deterministically from our
+//! node id and the current epoch, independently of the number of connected validators.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use futures::prelude::*;
-use rand::seq::SliceRandom;
 use slog::{debug, error, o, trace, warn};
 
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{NetworkConfig, SubnetDiscovery};
 use hashset_delay::HashSetDelay;
 use slot_clock::SlotClock;
-use types::{Attestation, EthSpec, Slot, SubnetId, ValidatorSubscription};
+use types::{Attestation, EthSpec, Slot, SubnetId, Uint256, ValidatorSubscription};
 
 use crate::metrics;
 
@@ -26,14 +28,6 @@ mod tests;
 /// slot is less than this number, skip the peer discovery process.
 /// Subnet discovery query takes atmost 30 secs, 2 slots take 24s.
 const MIN_PEER_DISCOVERY_SLOT_LOOK_AHEAD: u64 = 2;
-/// The time (in slots) before a last seen validator is considered absent and we unsubscribe from the random
-/// gossip topics that we subscribed to due to the validator connection.
-const LAST_SEEN_VALIDATOR_TIMEOUT: u32 = 150;
-// 30 mins at a 12s slot time
-/// The fraction of a slot that we subscribe to a subnet before the required slot.
-///
-/// Note: The time is calculated as `time = milliseconds_per_slot / ADVANCE_SUBSCRIPTION_TIME`.
-const ADVANCE_SUBSCRIBE_TIME: u32 = 3;
 /// The default number of slots before items in hash delay sets used by this class should expire.
 ///  36s at 12s slot time
 const DEFAULT_EXPIRATION_TIMEOUT: u32 = 3;
@@ -94,8 +88,15 @@ pub struct AttestationService<T: BeaconChainTypes> {
     /// A reference to the beacon chain to process received attestations.
     beacon_chain: Arc<BeaconChain<T>>,
 
-    /// The collection of currently subscribed random subnets mapped to their expiry deadline.
-    random_subnets: HashSetDelay<SubnetId>,
+    /// The collection of long-lived subnets we are subscribed to, as determined by our node id and
+    /// the current epoch.
+    long_lived_subscriptions: HashSet<SubnetId>,
+
+    /// A timer that fires when the long-lived subnet subscriptions need to be re-computed.
+    next_long_lived_subscription_event: Pin<Box<tokio::time::Sleep>>,
+
+    /// Our discovery node id, which determines the long-lived subnets we subscribe to.
+    node_id: Uint256,
 
     /// The collection of all currently subscribed subnets (long-lived **and** short-lived).
     subscriptions: HashSet<SubnetId>,
@@ -106,12 +107,6 @@ pub struct AttestationService<T: BeaconChainTypes> {
     /// A collection timeouts to track the existence of aggregate validator subscriptions at an `ExactSubnet`.
     aggregate_validators_on_subnet: HashSetDelay<ExactSubnet>,
 
-    /// A collection of seen validators. These dictate how many random subnets we should be
-    /// subscribed to. As these time out, we unsubscribe for the required random subnets and update
-    /// our ENR.
-    /// This is a set of validator indices.
-    known_validators: HashSetDelay<u64>,
-
     /// The waker for the current thread.
     waker: Option<std::task::Waker>,
 
@@ -124,6 +119,9 @@ pub struct AttestationService<T: BeaconChainTypes> {
     /// We process and aggregate all attestations on subscribed subnets.
     import_all_attestations: bool,
 
+    /// We only publish blocks, so we join no subnets.
+    proposer_only: bool,
+
     /// The logger for the attestation service.
     log: slog::Logger,
 }
@@ -133,41 +131,41 @@ impl<T: BeaconChainTypes> AttestationService<T> {
 
     pub fn new(
         beacon_chain: Arc<BeaconChain<T>>,
+        node_id: Uint256,
         config: &NetworkConfig,
         log: &slog::Logger,
     ) -> Self {
         let log = log.new(o!("service" => "attestation_service"));
 
-        // calculate the random subnet duration from the spec constants
-        let spec = &beacon_chain.spec;
         let slot_duration = beacon_chain.slot_clock.slot_duration();
-        let random_subnet_duration_millis = spec
-            .epochs_per_random_subnet_subscription
-            .saturating_mul(T::EthSpec::slots_per_epoch())
-            .saturating_mul(slot_duration.as_millis() as u64);
-
-        // Panics on overflow. Ensure LAST_SEEN_VALIDATOR_TIMEOUT is not too large.
-        let last_seen_val_timeout = slot_duration
-            .checked_mul(LAST_SEEN_VALIDATOR_TIMEOUT)
-            .expect("LAST_SEEN_VALIDATOR_TIMEOUT must not be ridiculously large");
         let default_timeout = slot_duration
             .checked_mul(DEFAULT_EXPIRATION_TIMEOUT)
             .expect("DEFAULT_EXPIRATION_TIMEOUT must not be ridiculoustly large");
 
-        AttestationService {
+        let mut service = AttestationService {
             events: VecDeque::with_capacity(10),
             beacon_chain,
-            random_subnets: HashSetDelay::new(Duration::from_millis(random_subnet_duration_millis)),
+            long_lived_subscriptions: HashSet::new(),
+            next_long_lived_subscription_event: Box::pin(tokio::time::sleep(slot_duration)),
+            node_id,
             subscriptions: HashSet::new(),
             unsubscriptions: HashSetDelay::new(default_timeout),
             aggregate_validators_on_subnet: HashSetDelay::new(default_timeout),
-            known_validators: HashSetDelay::new(last_seen_val_timeout),
             waker: None,
             subscribe_all_subnets: config.subscribe_all_subnets,
             import_all_attestations: config.import_all_attestations,
+            proposer_only: config.proposer_only,
             discovery_disabled: config.disable_discovery,
             log,
+        };
+
+        // If we are subscribed to all subnets there is no need to maintain a backbone, and a
+        // proposer-only node doesn't join one.
+        if !service.subscribe_all_subnets && !service.proposer_only {
+            service.recompute_long_lived_subnets();
         }
+
+        service
     }
 
     /// Return count of all currently subscribed subnets (long-lived **and** short-lived).
@@ -183,9 +181,6 @@ impl<T: BeaconChainTypes> AttestationService<T> {
     /// Processes a list of validator subscriptions.
     ///
     /// This will:
-    /// - Register new validators as being known.
-    /// - Subscribe to the required number of random subnets.
-    /// - Update the local ENR for new random subnets due to seeing new validators.
     /// - Search for peers for required subnets.
     /// - Request subscriptions for subnets on specific slots when required.
     /// - Build the timeouts for each of these events.
@@ -196,19 +191,21 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         &mut self,
         subscriptions: Vec<ValidatorSubscription>,
     ) -> Result<(), String> {
+        // A proposer-only node doesn't serve attestation duties.
+        if self.proposer_only {
+            return Ok(());
+        }
+
         // Maps each subnet_id subscription to it's highest slot
         let mut subnets_to_discover: HashMap<SubnetId, Slot> = HashMap::new();
         for subscription in subscriptions {
             metrics::inc_counter(&metrics::SUBNET_SUBSCRIPTION_REQUESTS);
             //NOTE: We assume all subscriptions have been verified before reaching this service
 
-            // Registers the validator with the attestation service.
-            // This will subscribe to long-lived random subnets if required.
             trace!(self.log,
                 "Validator subscription";
                 "subscription" => ?subscription,
             );
-            self.add_known_validator(subscription.validator_index);
 
             let subnet_id = match SubnetId::compute_subnet::<T::EthSpec>(
                 subscription.slot,
@@ -303,6 +300,122 @@ impl<T: BeaconChainTypes> AttestationService<T> {
 
     /* Internal private functions */
 
+    /// Re-computes the long-lived subnets for the current epoch and schedules the next
+    /// re-computation.
+    ///
+    /// If the subnets cannot be computed (e.g. the slot clock is not yet available) we retry after
+    /// a slot.
+    fn recompute_long_lived_subnets(&mut self) {
+        let next_subscription_event = self
+            .recompute_long_lived_subnets_inner()
+            .unwrap_or_else(|_| self.beacon_chain.slot_clock.slot_duration());
+
+        debug!(self.log,
+            "Recomputed long-lived subnets";
+            "subnets" => ?self.long_lived_subscriptions,
+            "next_update" => ?next_subscription_event
+        );
+
+        self.next_long_lived_subscription_event =
+            Box::pin(tokio::time::sleep(next_subscription_event));
+
+        if let Some(waker) = &self.waker {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Computes and applies the long-lived subnets for the current epoch, returning the time
+    /// until they must next be re-computed.
+    fn recompute_long_lived_subnets_inner(&mut self) -> Result<Duration, ()> {
+        let current_epoch = self.beacon_chain.epoch().map_err(|e| {
+            warn!(self.log,
+                "Failed to get the current epoch from clock";
+                "error" => ?e
+            )
+        })?;
+
+        let (subnets, next_subscription_epoch) = SubnetId::compute_subnets_for_epoch::<T::EthSpec>(
+            self.node_id,
+            current_epoch,
+            &self.beacon_chain.spec,
+        )
+        .map_err(|e| error!(self.log, "Could not compute long-lived subnets"; "error" => e))?;
+
+        let next_subscription_slot =
+            next_subscription_epoch.start_slot(T::EthSpec::slots_per_epoch());
+        let next_subscription_event = self
+            .beacon_chain
+            .slot_clock
+            .duration_to_slot(next_subscription_slot)
+            .ok_or_else(|| {
+                warn!(self.log,
+                    "Failed to determine the duration to the next long-lived subnet update";
+                    "next_subscription_slot" => next_subscription_slot
+                )
+            })?;
+
+        self.update_long_lived_subnets(subnets.collect());
+
+        Ok(next_subscription_event)
+    }
+
+    /// Replaces the current set of long-lived subnets with `subnets`.
+    ///
+    /// New subnets are subscribed to, added to the ENR bitfield and searched for peers. Subnets
+    /// that are no longer long-lived are removed from the ENR and unsubscribed from, unless a
+    /// short-lived subscription still requires them (in which case the pending unsubscription
+    /// event will handle it).
+    fn update_long_lived_subnets(&mut self, mut subnets: HashSet<SubnetId>) {
+        for subnet_id in &subnets {
+            if self.long_lived_subscriptions.contains(subnet_id) {
+                continue;
+            }
+
+            if !self.subscriptions.contains(subnet_id) {
+                self.subscriptions.insert(*subnet_id);
+                debug!(self.log, "Subscribing to long-lived subnet"; "subnet_id" => ?subnet_id);
+                self.events
+                    .push_back(AttServiceMessage::Subscribe(*subnet_id));
+            }
+
+            // add the subnet to the ENR bitfield
+            self.events.push_back(AttServiceMessage::EnrAdd(*subnet_id));
+
+            if !self.discovery_disabled {
+                self.events
+                    .push_back(AttServiceMessage::DiscoverPeers(vec![SubnetDiscovery {
+                        subnet_id: *subnet_id,
+                        min_ttl: None,
+                    }]));
+            }
+        }
+
+        // `subnets` now contains the previous long-lived subnets.
+        std::mem::swap(&mut self.long_lived_subscriptions, &mut subnets);
+
+        for subnet_id in subnets {
+            if self.long_lived_subscriptions.contains(&subnet_id) {
+                continue;
+            }
+
+            // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
+            if !self
+                .unsubscriptions
+                .keys()
+                .any(|exact_subnet| exact_subnet.subnet_id == subnet_id)
+            {
+                debug!(self.log, "Unsubscribing from long-lived subnet"; "subnet_id" => ?subnet_id);
+                self.subscriptions.remove(&subnet_id);
+                self.events
+                    .push_back(AttServiceMessage::Unsubscribe(subnet_id));
+            }
+
+            // remove the subnet from the ENR bitfield
+            self.events
+                .push_back(AttServiceMessage::EnrRemove(subnet_id));
+        }
+    }
+
     /// Checks if there are currently queued discovery requests and the time required to make the
     /// request.
     ///
@@ -353,7 +466,7 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         Ok(())
     }
 
-    /// Checks the current random subnets and subscriptions to determine if a new subscription for this
+    /// Checks the current long-lived subnets and subscriptions to determine if a new subscription for this
     /// subnet is required for the given slot.
     ///
     /// If required, adds a subscription event and an associated unsubscription event.
@@ -391,10 +504,11 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             .insert_at(exact_subnet.clone(), expected_end_subscription_duration);
 
         // Checks on current subscriptions
-        // Note: We may be connected to a long-lived random subnet. In this case we still add the
-        // subscription timeout and check this case when the timeout fires. This is because a
-        // long-lived random subnet can be unsubscribed at any time when a validator becomes
-        // in-active. This case is checked on the subscription event (see `handle_subscriptions`).
+        // Note: We may be connected to a long-lived subnet. In this case we still add the
+        // subscription timeout and check this case when the timeout fires. This is because the
+        // long-lived subnets can change at an epoch boundary before the short-lived subscription
+        // has finished. This case is checked on the unsubscription event (see
+        // `handle_unsubscriptions`).
 
         // Return if we already have a subscription for this subnet_id and slot
         if self.unsubscriptions.contains(&exact_subnet) || self.subscribe_all_subnets {
@@ -416,126 +530,36 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         Ok(())
     }
 
-    /// Updates the `known_validators` mapping and subscribes to a set of random subnets if required.
-    ///
-    /// This also updates the ENR to indicate our long-lived subscription to the subnet
-    fn add_known_validator(&mut self, validator_index: u64) {
-        if self.known_validators.get(&validator_index).is_none() && !self.subscribe_all_subnets {
-            // New validator has subscribed
-            // Subscribe to random topics and update the ENR if needed.
-
-            let spec = &self.beacon_chain.spec;
-
-            if self.random_subnets.len() < spec.attestation_subnet_count as usize {
-                // Still room for subscriptions
-                self.subscribe_to_random_subnets(
-                    self.beacon_chain.spec.random_subnets_per_validator as usize,
-                );
-            }
-        }
-        // add the new validator or update the current timeout for a known validator
-        self.known_validators.insert(validator_index);
-    }
-
-    /// Subscribe to long-lived random subnets and update the local ENR bitfield.
-    fn subscribe_to_random_subnets(&mut self, no_subnets_to_subscribe: usize) {
-        let subnet_count = self.beacon_chain.spec.attestation_subnet_count;
-
-        // Build a list of random subnets that we are not currently subscribed to.
-        let available_subnets = (0..subnet_count)
-            .map(SubnetId::new)
-            .filter(|subnet_id| self.random_subnets.get(subnet_id).is_none())
-            .collect::<Vec<_>>();
-
-        let to_subscribe_subnets = {
-            if available_subnets.len() < no_subnets_to_subscribe {
-                debug!(self.log, "Reached maximum random subnet subscriptions");
-                available_subnets
-            } else {
-                // select a random sample of available subnets
-                available_subnets
-                    .choose_multiple(&mut rand::thread_rng(), no_subnets_to_subscribe)
-                    .cloned()
-                    .collect::<Vec<_>>()
-            }
-        };
-
-        for subnet_id in to_subscribe_subnets {
-            // remove this subnet from any immediate un-subscription events
-            self.unsubscriptions
-                .retain(|exact_subnet| exact_subnet.subnet_id != subnet_id);
-
-            // insert a new random subnet
-            self.random_subnets.insert(subnet_id);
-
-            // send discovery request
-            // Note: it's wasteful to send a DiscoverPeers request if we already have peers for this subnet.
-            // However, subscribing to random subnets ideally shouldn't happen very often (once in ~27 hours) and
-            // this makes it easier to deterministically test the attestations service.
-            self.events
-                .push_back(AttServiceMessage::DiscoverPeers(vec![SubnetDiscovery {
-                    subnet_id,
-                    min_ttl: None,
-                }]));
-
-            // if we are not already subscribed, then subscribe
-            if !self.subscriptions.contains(&subnet_id) {
-                self.subscriptions.insert(subnet_id);
-                debug!(self.log, "Subscribing to random subnet"; "subnet_id" => ?subnet_id);
-                self.events
-                    .push_back(AttServiceMessage::Subscribe(subnet_id));
-            }
-
-            // add the subnet to the ENR bitfield
-            self.events.push_back(AttServiceMessage::EnrAdd(subnet_id));
-        }
-    }
-
     /* A collection of functions that handle the various timeouts */
 
     /// A queued subscription is ready.
     ///
-    /// We add subscriptions events even if we are already subscribed to a random subnet (as these
-    /// can be unsubscribed at any time by inactive validators). If we are
-    /// still subscribed at the time the event fires, we don't re-subscribe.
+    /// We add subscriptions events even if we are already subscribed to a long-lived subnet (as
+    /// these can change at an epoch boundary). If we are still subscribed at the time the event
+    /// fires, we don't re-subscribe.
     fn handle_subscriptions(&mut self, exact_subnet: ExactSubnet) {
-        // Check if the subnet currently exists as a long-lasting random subnet
-        if let Some(expiry) = self.random_subnets.get(&exact_subnet.subnet_id) {
-            // we are subscribed via a random subnet, if this is to expire during the time we need
-            // to be subscribed, just extend the expiry
-            let slot_duration = self.beacon_chain.slot_clock.slot_duration();
-            let advance_subscription_duration = slot_duration
-                .checked_div(ADVANCE_SUBSCRIBE_TIME)
-                .expect("ADVANCE_SUBSCRIPTION_TIME cannot be too large");
-            // we require the subnet subscription for at least a slot on top of the initial
-            // subscription time
-            let expected_end_subscription_duration = slot_duration + advance_subscription_duration;
-
-            if expiry < &(Instant::now() + expected_end_subscription_duration) {
-                self.random_subnets
-                    .update_timeout(&exact_subnet.subnet_id, expected_end_subscription_duration);
-            }
-        } else {
-            // we are also not un-subscribing from a subnet if the next slot requires us to be
-            // subscribed. Therefore there could be the case that we are already still subscribed
-            // to the required subnet. In which case we do not issue another subscription request.
-            if !self.subscriptions.contains(&exact_subnet.subnet_id) {
-                // we are not already subscribed
-                debug!(self.log, "Subscribing to subnet"; "subnet" => *exact_subnet.subnet_id, "target_slot" => exact_subnet.slot.as_u64());
-                self.subscriptions.insert(exact_subnet.subnet_id);
-                self.events
-                    .push_back(AttServiceMessage::Subscribe(exact_subnet.subnet_id));
-            }
+        // we are also not un-subscribing from a subnet if the next slot requires us to be
+        // subscribed. Therefore there could be the case that we are already still subscribed
+        // to the required subnet. In which case we do not issue another subscription request.
+        if !self.subscriptions.contains(&exact_subnet.subnet_id) {
+            // we are not already subscribed
+            debug!(self.log, "Subscribing to subnet"; "subnet" => *exact_subnet.subnet_id, "target_slot" => exact_subnet.slot.as_u64());
+            self.subscriptions.insert(exact_subnet.subnet_id);
+            self.events
+                .push_back(AttServiceMessage::Subscribe(exact_subnet.subnet_id));
         }
     }
 
     /// A queued unsubscription is ready.
     ///
-    /// Unsubscription events are added, even if we are subscribed to long-lived random subnets. If
-    /// a random subnet is present, we do not unsubscribe from it.
+    /// Unsubscription events are added, even if we are subscribed to long-lived subnets. If the
+    /// subnet is currently long-lived, we do not unsubscribe from it.
     fn handle_unsubscriptions(&mut self, exact_subnet: ExactSubnet) {
-        // Check if the subnet currently exists as a long-lasting random subnet
-        if self.random_subnets.contains(&exact_subnet.subnet_id) {
+        // Check if the subnet currently exists as a long-lived subnet
+        if self
+            .long_lived_subscriptions
+            .contains(&exact_subnet.subnet_id)
+        {
             return;
         }
 
@@ -545,78 +569,6 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         self.events
             .push_back(AttServiceMessage::Unsubscribe(exact_subnet.subnet_id));
     }
-
-    /// A random subnet has expired.
-    ///
-    /// This function selects a new subnet to join, or extends the expiry if there are no more
-    /// available subnets to choose from.
-    fn handle_random_subnet_expiry(&mut self, subnet_id: SubnetId) {
-        let subnet_count = self.beacon_chain.spec.attestation_subnet_count;
-        if self.random_subnets.len() == (subnet_count - 1) as usize {
-            // We are at capacity, simply increase the timeout of the current subnet
-            self.random_subnets.insert(subnet_id);
-            return;
-        }
-        // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
-        if self
-            .unsubscriptions
-            .keys()
-            .find(|s| s.subnet_id == subnet_id)
-            .is_none()
-        {
-            // we are not at capacity, unsubscribe from the current subnet.
-            debug!(self.log, "Unsubscribing from random subnet"; "subnet_id" => *subnet_id);
-            self.events
-                .push_back(AttServiceMessage::Unsubscribe(subnet_id));
-        }
-
-        // Remove the ENR bitfield bit and choose a new random on from the available subnets
-        self.events
-            .push_back(AttServiceMessage::EnrRemove(subnet_id));
-        // Subscribe to a new random subnet
-        self.subscribe_to_random_subnets(1);
-    }
-
-    /// A known validator has not sent a subscription in a while. They are considered offline and the
-    /// beacon node no longer needs to be subscribed to the allocated random subnets.
-    ///
-    /// We don't keep track of a specific validator to random subnet, rather the ratio of active
-    /// validators to random subnets. So when a validator goes offline, we can simply remove the
-    /// allocated amount of random subnets.
-    fn handle_known_validator_expiry(&mut self) -> Result<(), ()> {
-        let spec = &self.beacon_chain.spec;
-        let subnet_count = spec.attestation_subnet_count;
-        let random_subnets_per_validator = spec.random_subnets_per_validator;
-        if self.known_validators.len() as u64 * random_subnets_per_validator >= subnet_count {
-            // have too many validators, ignore
-            return Ok(());
-        }
-
-        let subscribed_subnets = self.random_subnets.keys().cloned().collect::<Vec<_>>();
-        let to_remove_subnets = subscribed_subnets.choose_multiple(
-            &mut rand::thread_rng(),
-            random_subnets_per_validator as usize,
-        );
-
-        for subnet_id in to_remove_subnets {
-            // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
-            if self
-                .unsubscriptions
-                .keys()
-                .find(|s| s.subnet_id == *subnet_id)
-                .is_none()
-            {
-                self.events
-                    .push_back(AttServiceMessage::Unsubscribe(*subnet_id));
-            }
-            // as the long lasting subnet subscription is being removed, remove the subnet_id from
-            // the ENR bitfield
-            self.events
-                .push_back(AttServiceMessage::EnrRemove(*subnet_id));
-            self.random_subnets.remove(subnet_id);
-        }
-        Ok(())
-    }
 }
 
 impl<T: BeaconChainTypes> Stream for AttestationService<T> {
@@ -641,24 +593,16 @@ impl<T: BeaconChainTypes> Stream for AttestationService<T> {
             Poll::Ready(None) | Poll::Pending => {}
         }
 
-        // process any random subnet expiries
-        match self.random_subnets.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(subnet))) => self.handle_random_subnet_expiry(subnet),
-            Poll::Ready(Some(Err(e))) => {
-                error!(self.log, "Failed to check for random subnet cycles"; "error"=> e);
-            }
-            Poll::Ready(None) | Poll::Pending => {}
-        }
-
-        // process any known validator expiries
-        match self.known_validators.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(_validator_index))) => {
-                let _ = self.handle_known_validator_expiry();
-            }
-            Poll::Ready(Some(Err(e))) => {
-                error!(self.log, "Failed to check for random subnet cycles"; "error"=> e);
-            }
-            Poll::Ready(None) | Poll::Pending => {}
+        // re-compute the long-lived subnets if required
+        if !self.subscribe_all_subnets
+            && !self.proposer_only
+            && self
+                .next_long_lived_subscription_event
+                .as_mut()
+                .poll(cx)
+                .is_ready()
+        {
+            self.recompute_long_lived_subnets();
         }
         // poll to remove entries on expiration, no need to act on expiration events
         if let Poll::Ready(Some(Err(e))) = self.aggregate_validators_on_subnet.poll_next_unpin(cx) {
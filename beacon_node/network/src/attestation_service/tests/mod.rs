@@ -17,7 +17,7 @@ mod tests {
     use store::config::StoreConfig;
     use store::{HotColdDB, MemoryStore};
     use tempfile::tempdir;
-    use types::{CommitteeIndex, EthSpec, MinimalEthSpec};
+    use types::{CommitteeIndex, EthSpec, MinimalEthSpec, Uint256};
 
     const SLOT_DURATION_MILLIS: u64 = 400;
 
@@ -91,12 +91,38 @@ mod tests {
     }
 
     fn get_attestation_service() -> AttestationService<TestBeaconChainType> {
+        get_attestation_service_with_config(NetworkConfig::default())
+    }
+
+    fn get_attestation_service_with_config(
+        config: NetworkConfig,
+    ) -> AttestationService<TestBeaconChainType> {
         let log = get_logger();
-        let config = NetworkConfig::default();
 
         let beacon_chain = CHAIN.chain.clone();
 
-        AttestationService::new(beacon_chain, &config, &log)
+        AttestationService::new(beacon_chain, test_node_id(), &config, &log)
+    }
+
+    fn test_node_id() -> Uint256 {
+        Uint256::from_big_endian(&[42; 32])
+    }
+
+    /// Returns the long-lived subnets the test node should be subscribed to in the current epoch.
+    fn get_long_lived_subnets(
+        attestation_service: &AttestationService<TestBeaconChainType>,
+    ) -> HashSet<SubnetId> {
+        let current_epoch = attestation_service
+            .beacon_chain
+            .epoch()
+            .expect("Could not get current epoch");
+        let (subnets, _) = SubnetId::compute_subnets_for_epoch::<MinimalEthSpec>(
+            test_node_id(),
+            current_epoch,
+            &attestation_service.beacon_chain.spec,
+        )
+        .expect("Should compute long-lived subnets");
+        subnets.collect()
     }
 
     fn get_subscription(
@@ -204,20 +230,30 @@ mod tests {
             AttServiceMessage::Unsubscribe(subnet_id),
         ];
 
+        let subnets_per_node = attestation_service.beacon_chain.spec.subnets_per_node as usize;
+        let long_lived_events = 3 * subnets_per_node;
+
         // Wait for 1 slot duration to get the unsubscription event
         let events = get_events(&mut attestation_service, None, 1).await;
         assert_matches!(
             events[..3],
-            [AttServiceMessage::DiscoverPeers(_), AttServiceMessage::Subscribe(_any1), AttServiceMessage::EnrAdd(_any3)]
+            [
+                AttServiceMessage::Subscribe(_any1),
+                AttServiceMessage::EnrAdd(_any2),
+                AttServiceMessage::DiscoverPeers(_)
+            ]
         );
 
         // If the long lived and short lived subnets are the same, there should be no more events
         // as we don't resubscribe already subscribed subnets.
-        if !attestation_service.random_subnets.contains(&subnet_id) {
-            assert_eq!(expected[..], events[3..]);
+        if !attestation_service
+            .long_lived_subscriptions
+            .contains(&subnet_id)
+        {
+            assert_eq!(expected[..], events[long_lived_events..]);
         }
-        // Should be subscribed to only 1 long lived subnet after unsubscription.
-        assert_eq!(attestation_service.subscription_count(), 1);
+        // Should be subscribed to only the long lived subnets after unsubscription.
+        assert_eq!(attestation_service.subscription_count(), subnets_per_node);
     }
 
     /// Test to verify that we are not unsubscribing to a subnet before a required subscription.
@@ -281,108 +317,125 @@ mod tests {
             .validator_subscriptions(vec![sub1, sub2])
             .unwrap();
 
+        let subnets_per_node = attestation_service.beacon_chain.spec.subnets_per_node as usize;
+        let long_lived_events = 3 * subnets_per_node;
+
         // Unsubscription event should happen at slot 2 (since subnet id's are the same, unsubscription event should be at higher slot + 1)
         // Get all events for 1 slot duration (unsubscription event should happen after 2 slot durations).
         let events = get_events(&mut attestation_service, None, 1).await;
         assert_matches!(
             events[..3],
-            [AttServiceMessage::DiscoverPeers(_), AttServiceMessage::Subscribe(_any1), AttServiceMessage::EnrAdd(_any3)]
+            [
+                AttServiceMessage::Subscribe(_any1),
+                AttServiceMessage::EnrAdd(_any2),
+                AttServiceMessage::DiscoverPeers(_)
+            ]
         );
 
         let expected = AttServiceMessage::Subscribe(subnet_id1);
 
-        // Should be still subscribed to 1 long lived and 1 short lived subnet if both are different.
-        if !attestation_service.random_subnets.contains(&subnet_id1) {
-            assert_eq!(expected, events[3]);
-            assert_eq!(attestation_service.subscription_count(), 2);
+        // Should be still subscribed to the long lived subnets and 1 short lived subnet if they
+        // are different.
+        if !attestation_service
+            .long_lived_subscriptions
+            .contains(&subnet_id1)
+        {
+            assert_eq!(expected, events[long_lived_events]);
+            assert_eq!(
+                attestation_service.subscription_count(),
+                subnets_per_node + 1
+            );
         } else {
-            assert_eq!(attestation_service.subscription_count(), 1);
+            assert_eq!(attestation_service.subscription_count(), subnets_per_node);
         }
 
         // Get event for 1 more slot duration, we should get the unsubscribe event now.
         let unsubscribe_event = get_events(&mut attestation_service, None, 1).await;
 
         // If the long lived and short lived subnets are different, we should get an unsubscription event.
-        if !attestation_service.random_subnets.contains(&subnet_id1) {
+        if !attestation_service
+            .long_lived_subscriptions
+            .contains(&subnet_id1)
+        {
             assert_eq!(
                 [AttServiceMessage::Unsubscribe(subnet_id1)],
                 unsubscribe_event[..]
             );
         }
 
-        // Should be subscribed to only 1 long lived subnet after unsubscription.
-        assert_eq!(attestation_service.subscription_count(), 1);
+        // Should be subscribed to only the long lived subnets after unsubscription.
+        assert_eq!(attestation_service.subscription_count(), subnets_per_node);
     }
 
     #[tokio::test]
-    async fn subscribe_all_random_subnets() {
-        let attestation_subnet_count = MinimalEthSpec::default_spec().attestation_subnet_count;
-        let subscription_slot = 10;
-        let subscription_count = attestation_subnet_count;
-        let committee_count = 1;
-
-        // create the attestation service and subscriptions
+    async fn subscribe_to_long_lived_subnets_on_startup() {
         let mut attestation_service = get_attestation_service();
+        let subnets_per_node = attestation_service.beacon_chain.spec.subnets_per_node as usize;
+        let long_lived_subnets = get_long_lived_subnets(&attestation_service);
+        assert_eq!(long_lived_subnets.len(), subnets_per_node);
+
+        let events = get_events(&mut attestation_service, None, 1).await;
+
+        let mut subscribed = HashSet::new();
+        let mut enr_added = HashSet::new();
+        let mut discover_peer_count = 0;
+        for event in &events {
+            match event {
+                AttServiceMessage::Subscribe(subnet_id) => {
+                    subscribed.insert(*subnet_id);
+                }
+                AttServiceMessage::EnrAdd(subnet_id) => {
+                    enr_added.insert(*subnet_id);
+                }
+                AttServiceMessage::DiscoverPeers(_) => discover_peer_count += 1,
+                _ => panic!("Unexpected event {:?}", event),
+            }
+        }
+
+        assert_eq!(subscribed, long_lived_subnets);
+        assert_eq!(enr_added, long_lived_subnets);
+        assert_eq!(discover_peer_count, subnets_per_node);
+        assert_eq!(
+            attestation_service.long_lived_subscriptions,
+            long_lived_subnets
+        );
+        assert_eq!(attestation_service.subscription_count(), subnets_per_node);
+    }
+
+    #[tokio::test]
+    async fn proposer_only_joins_no_subnets() {
+        let config = NetworkConfig {
+            proposer_only: true,
+            ..NetworkConfig::default()
+        };
+        let mut attestation_service = get_attestation_service_with_config(config);
         let current_slot = attestation_service
             .beacon_chain
             .slot_clock
             .now()
             .expect("Could not get current slot");
 
-        let subscriptions = get_subscriptions(
-            subscription_count,
-            current_slot + subscription_slot,
-            committee_count,
-        );
-
-        // submit the subscriptions
+        let subscriptions = get_subscriptions(4, current_slot + 2, 1);
         attestation_service
             .validator_subscriptions(subscriptions)
             .unwrap();
 
         let events = get_events(&mut attestation_service, None, 3).await;
-        let mut discover_peer_count = 0;
-        let mut enr_add_count = 0;
-        let mut unexpected_msg_count = 0;
-
-        for event in &events {
-            match event {
-                AttServiceMessage::DiscoverPeers(_) => {
-                    discover_peer_count = discover_peer_count + 1
-                }
-                AttServiceMessage::Subscribe(_any_subnet) => {}
-                AttServiceMessage::EnrAdd(_any_subnet) => enr_add_count = enr_add_count + 1,
-                _ => unexpected_msg_count = unexpected_msg_count + 1,
-            }
-        }
-
-        // The bulk discovery request length should be equal to validator_count
-        let bulk_discovery_event = events.last().unwrap();
-        if let AttServiceMessage::DiscoverPeers(d) = bulk_discovery_event {
-            assert_eq!(d.len(), attestation_subnet_count as usize);
-        } else {
-            panic!("Unexpected event {:?}", bulk_discovery_event);
-        }
-
-        // 64 `DiscoverPeer` requests of length 1 corresponding to random subnets
-        // and 1 `DiscoverPeer` request corresponding to bulk subnet discovery.
-        assert_eq!(discover_peer_count, subscription_count + 1);
-        assert_eq!(attestation_service.subscription_count(), 64);
-        assert_eq!(enr_add_count, 64);
-        assert_eq!(unexpected_msg_count, 0);
-        // test completed successfully
+        assert!(events.is_empty(), "Unexpected events {:?}", events);
+        assert!(attestation_service.long_lived_subscriptions.is_empty());
+        assert_eq!(attestation_service.subscription_count(), 0);
     }
 
     #[tokio::test]
-    async fn subscribe_all_random_subnets_plus_one() {
+    async fn long_lived_subnets_independent_of_validator_count() {
         let attestation_subnet_count = MinimalEthSpec::default_spec().attestation_subnet_count;
         let subscription_slot = 10;
-        // the 65th subscription should result in no more messages than the previous scenario
-        let subscription_count = attestation_subnet_count + 1;
+        let subscription_count = attestation_subnet_count;
         let committee_count = 1;
 
         // create the attestation service and subscriptions
         let mut attestation_service = get_attestation_service();
+        let subnets_per_node = attestation_service.beacon_chain.spec.subnets_per_node as usize;
         let current_slot = attestation_service
             .beacon_chain
             .slot_clock
@@ -416,19 +469,21 @@ mod tests {
             }
         }
 
-        // The bulk discovery request length shouldn't exceed max attestation_subnet_count
+        // The bulk discovery request length should be equal to validator_count
         let bulk_discovery_event = events.last().unwrap();
         if let AttServiceMessage::DiscoverPeers(d) = bulk_discovery_event {
             assert_eq!(d.len(), attestation_subnet_count as usize);
         } else {
             panic!("Unexpected event {:?}", bulk_discovery_event);
         }
-        // 64 `DiscoverPeer` requests of length 1 corresponding to random subnets
-        // and 1 `DiscoverPeer` request corresponding to the bulk subnet discovery.
-        // For the 65th subscription, the call to `subscribe_to_random_subnets` is not made because we are at capacity.
-        assert_eq!(discover_peer_count, 64 + 1);
+
+        // 1 `DiscoverPeer` request of length 1 for each long-lived subnet and 1 `DiscoverPeer`
+        // request corresponding to the bulk subnet discovery.
+        assert_eq!(discover_peer_count, subnets_per_node + 1);
+        // Only the long-lived subnets are advertised in our ENR, regardless of the number of
+        // validators.
+        assert_eq!(enr_add_count, subnets_per_node);
         assert_eq!(attestation_service.subscription_count(), 64);
-        assert_eq!(enr_add_count, 64);
         assert_eq!(unexpected_msg_count, 0);
     }
 }
@@ -42,6 +42,7 @@ use eth2_libp2p::{
     MessageId, NetworkGlobals, PeerId, PeerRequestId,
 };
 use slog::{crit, debug, error, trace, warn, Logger};
+use std::cmp;
 use std::collections::VecDeque;
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
@@ -52,7 +53,7 @@ use types::{
     SignedBeaconBlock, SignedVoluntaryExit, SubnetId,
 };
 
-use worker::Worker;
+use worker::{GossipAggregatePackage, GossipAttestationPackage, Worker};
 
 mod worker;
 
@@ -77,6 +78,14 @@ const MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN: usize = 16_384;
 /// start dropping them.
 const MAX_AGGREGATED_ATTESTATION_QUEUE_LEN: usize = 1_024;
 
+/// The maximum number of queued `Attestation` objects that will be verified together in a single
+/// batch.
+const MAX_GOSSIP_ATTESTATION_BATCH_SIZE: usize = 64;
+
+/// The maximum number of queued `SignedAggregateAndProof` objects that will be verified together
+/// in a single batch.
+const MAX_GOSSIP_AGGREGATE_BATCH_SIZE: usize = 64;
+
 /// The maximum number of queued `SignedBeaconBlock` objects received on gossip that will be stored
 /// before we start dropping them.
 const MAX_GOSSIP_BLOCK_QUEUE_LEN: usize = 1_024;
@@ -392,11 +401,17 @@ pub enum Work<E: EthSpec> {
         subnet_id: SubnetId,
         should_import: bool,
     },
+    GossipAttestationBatch {
+        packages: Vec<GossipAttestationPackage<E>>,
+    },
     GossipAggregate {
         message_id: MessageId,
         peer_id: PeerId,
         aggregate: Box<SignedAggregateAndProof<E>>,
     },
+    GossipAggregateBatch {
+        packages: Vec<GossipAggregatePackage<E>>,
+    },
     GossipBlock {
         message_id: MessageId,
         peer_id: PeerId,
@@ -446,7 +461,9 @@ impl<E: EthSpec> Work<E> {
     fn str_id(&self) -> &'static str {
         match self {
             Work::GossipAttestation { .. } => "gossip_attestation",
+            Work::GossipAttestationBatch { .. } => "gossip_attestation_batch",
             Work::GossipAggregate { .. } => "gossip_aggregate",
+            Work::GossipAggregateBatch { .. } => "gossip_aggregate_batch",
             Work::GossipBlock { .. } => "gossip_block",
             Work::GossipVoluntaryExit { .. } => "gossip_voluntary_exit",
             Work::GossipProposerSlashing { .. } => "gossip_proposer_slashing",
@@ -618,8 +635,71 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         // Check the aggregates, *then* the unaggregates since we assume that
                         // aggregates are more valuable to local validators and effectively give us
                         // more information with less signature verification time.
+                        //
+                        // If more than one is queued, verify them in a batch since their
+                        // signatures are cheaper to verify together.
+                        } else if aggregate_queue.len() > 1 {
+                            let batch_size =
+                                cmp::min(aggregate_queue.len(), MAX_GOSSIP_AGGREGATE_BATCH_SIZE);
+                            let mut packages = Vec::with_capacity(batch_size);
+                            for _ in 0..batch_size {
+                                match aggregate_queue.pop() {
+                                    Some(Work::GossipAggregate {
+                                        message_id,
+                                        peer_id,
+                                        aggregate,
+                                    }) => packages.push(GossipAggregatePackage {
+                                        message_id,
+                                        peer_id,
+                                        aggregate,
+                                    }),
+                                    Some(work) => error!(
+                                        self.log,
+                                        "Invalid item in aggregate queue";
+                                        "work" => work.str_id(),
+                                    ),
+                                    None => break,
+                                }
+                            }
+                            self.spawn_worker(
+                                idle_tx.clone(),
+                                Work::GossipAggregateBatch { packages },
+                            );
                         } else if let Some(item) = aggregate_queue.pop() {
                             self.spawn_worker(idle_tx.clone(), item);
+                        } else if attestation_queue.len() > 1 {
+                            let batch_size = cmp::min(
+                                attestation_queue.len(),
+                                MAX_GOSSIP_ATTESTATION_BATCH_SIZE,
+                            );
+                            let mut packages = Vec::with_capacity(batch_size);
+                            for _ in 0..batch_size {
+                                match attestation_queue.pop() {
+                                    Some(Work::GossipAttestation {
+                                        message_id,
+                                        peer_id,
+                                        attestation,
+                                        subnet_id,
+                                        should_import,
+                                    }) => packages.push(GossipAttestationPackage {
+                                        message_id,
+                                        peer_id,
+                                        attestation,
+                                        subnet_id,
+                                        should_import,
+                                    }),
+                                    Some(work) => error!(
+                                        self.log,
+                                        "Invalid item in attestation queue";
+                                        "work" => work.str_id(),
+                                    ),
+                                    None => break,
+                                }
+                            }
+                            self.spawn_worker(
+                                idle_tx.clone(),
+                                Work::GossipAttestationBatch { packages },
+                            );
                         } else if let Some(item) = attestation_queue.pop() {
                             self.spawn_worker(idle_tx.clone(), item);
                         // Check RPC methods next. Status messages are needed for sync so
@@ -679,6 +759,13 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                             _ if can_spawn => self.spawn_worker(idle_tx.clone(), work),
                             Work::GossipAttestation { .. } => attestation_queue.push(work),
                             Work::GossipAggregate { .. } => aggregate_queue.push(work),
+                            // Batches are only formed from the queues, when a worker is free.
+                            Work::GossipAttestationBatch { .. }
+                            | Work::GossipAggregateBatch { .. } => crit!(
+                                self.log,
+                                "Unsupported beacon processor work";
+                                "work" => work_id,
+                            ),
                             Work::GossipBlock { .. } => {
                                 gossip_block_queue.push(work, work_id, &self.log)
                             }
@@ -840,6 +927,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         subnet_id,
                         should_import,
                     ),
+                    /*
+                     * Batched unaggregated attestation verification.
+                     */
+                    Work::GossipAttestationBatch { packages } => {
+                        worker.process_gossip_attestation_batch(packages)
+                    }
                     /*
                      * Aggregated attestation verification.
                      */
@@ -848,6 +941,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         peer_id,
                         aggregate,
                     } => worker.process_gossip_aggregate(message_id, peer_id, *aggregate),
+                    /*
+                     * Batched aggregated attestation verification.
+                     */
+                    Work::GossipAggregateBatch { packages } => {
+                        worker.process_gossip_aggregate_batch(packages)
+                    }
                     /*
                      * Verification for beacon blocks received on gossip.
                      */
@@ -1,18 +1,39 @@
 use crate::{metrics, service::NetworkMessage, sync::SyncMessage};
 use beacon_chain::{
-    attestation_verification::Error as AttnError, observed_operations::ObservationOutcome,
+    attestation_verification::{
+        Error as AttnError, VerifiedAggregatedAttestation, VerifiedUnaggregatedAttestation,
+    },
+    observed_operations::ObservationOutcome,
     BeaconChainError, BeaconChainTypes, BlockError, ForkChoiceError,
 };
-use eth2_libp2p::{MessageAcceptance, MessageId, PeerAction, PeerId};
+use eth2_libp2p::{types::GossipKind, MessageAcceptance, MessageId, PeerAction, PeerId};
 use slog::{debug, error, info, trace, warn};
 use ssz::Encode;
 use types::{
-    Attestation, AttesterSlashing, Hash256, ProposerSlashing, SignedAggregateAndProof,
+    Attestation, AttesterSlashing, EthSpec, Hash256, ProposerSlashing, SignedAggregateAndProof,
     SignedBeaconBlock, SignedVoluntaryExit, SubnetId,
 };
 
 use super::Worker;
 
+/// An unaggregated attestation received on gossip, awaiting verification as part of a batch.
+#[derive(Debug)]
+pub struct GossipAttestationPackage<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub attestation: Box<Attestation<E>>,
+    pub subnet_id: SubnetId,
+    pub should_import: bool,
+}
+
+/// An aggregate received on gossip, awaiting verification as part of a batch.
+#[derive(Debug)]
+pub struct GossipAggregatePackage<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub aggregate: Box<SignedAggregateAndProof<E>>,
+}
+
 impl<T: BeaconChainTypes> Worker<T> {
     /* Auxiliary functions */
 
@@ -40,6 +61,26 @@ impl<T: BeaconChainTypes> Worker<T> {
         })
     }
 
+    /// Propagates the result of the validation of a message received on the gossip `topic`,
+    /// recording per-topic metrics.
+    ///
+    /// The `reason` is only recorded for messages that are ignored or rejected.
+    fn propagate_gossip_validation_result(
+        &self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        topic: &GossipKind,
+        validation_result: MessageAcceptance,
+        reason: &str,
+    ) {
+        match validation_result {
+            MessageAcceptance::Accept => metrics::register_gossip_accepted(topic),
+            MessageAcceptance::Ignore => metrics::register_gossip_ignored(topic, reason),
+            MessageAcceptance::Reject => metrics::register_gossip_rejected(topic, reason),
+        }
+        self.propagate_validation_result(message_id, propagation_source, validation_result)
+    }
+
     /* Processing functions */
 
     /// Process the unaggregated attestation received from the gossip network and:
@@ -58,17 +99,94 @@ impl<T: BeaconChainTypes> Worker<T> {
         should_import: bool,
     ) {
         let beacon_block_root = attestation.data.beacon_block_root;
+        let topic = &GossipKind::Attestation(subnet_id);
+
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self
+            .chain
+            .verify_unaggregated_attestation_for_gossip(attestation, Some(subnet_id));
+        metrics::stop_timer(validation_timer);
 
-        let attestation = match self
+        self.process_gossip_attestation_result(
+            verification_result,
+            message_id,
+            peer_id,
+            subnet_id,
+            beacon_block_root,
+            should_import,
+        );
+    }
+
+    /// Process a batch of unaggregated attestations received from the gossip network, verifying
+    /// their signatures together and then handling each as per `process_gossip_attestation`.
+    pub fn process_gossip_attestation_batch(
+        self,
+        packages: Vec<GossipAttestationPackage<T::EthSpec>>,
+    ) {
+        let mut attestations = Vec::with_capacity(packages.len());
+        let mut metadata = Vec::with_capacity(packages.len());
+        for package in packages {
+            metadata.push((
+                package.message_id,
+                package.peer_id,
+                package.subnet_id,
+                package.attestation.data.beacon_block_root,
+                package.should_import,
+            ));
+            attestations.push((*package.attestation, Some(package.subnet_id)));
+        }
+
+        let verification_results = match self
             .chain
-            .verify_unaggregated_attestation_for_gossip(attestation, Some(subnet_id))
+            .batch_verify_unaggregated_attestations_for_gossip(attestations)
         {
+            Ok(results) => results,
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Batch attestation verification failed";
+                    "batch_size" => metadata.len(),
+                    "error" => ?e,
+                );
+                return;
+            }
+        };
+
+        for ((message_id, peer_id, subnet_id, beacon_block_root, should_import), result) in
+            metadata.into_iter().zip(verification_results)
+        {
+            self.process_gossip_attestation_result(
+                result,
+                message_id,
+                peer_id,
+                subnet_id,
+                beacon_block_root,
+                should_import,
+            );
+        }
+    }
+
+    /// Handle the result of verifying an unaggregated attestation received from the gossip
+    /// network, either individually or as part of a batch.
+    fn process_gossip_attestation_result(
+        &self,
+        verification_result: Result<VerifiedUnaggregatedAttestation<T>, AttnError>,
+        message_id: MessageId,
+        peer_id: PeerId,
+        subnet_id: SubnetId,
+        beacon_block_root: Hash256,
+        should_import: bool,
+    ) {
+        let topic = &GossipKind::Attestation(subnet_id);
+
+        let attestation = match verification_result {
             Ok(attestation) => attestation,
             Err(e) => {
                 self.handle_attestation_verification_failure(
                     peer_id,
                     message_id,
                     beacon_block_root,
+                    topic,
                     "unaggregated",
                     e,
                 );
@@ -78,7 +196,13 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         // Indicate to the `Network` service that this message is valid and can be
         // propagated on the gossip network.
-        self.propagate_validation_result(message_id, peer_id.clone(), MessageAcceptance::Accept);
+        self.propagate_gossip_validation_result(
+            message_id,
+            peer_id.clone(),
+            topic,
+            MessageAcceptance::Accept,
+            "valid",
+        );
 
         if !should_import {
             return;
@@ -134,11 +258,71 @@ impl<T: BeaconChainTypes> Worker<T> {
         aggregate: SignedAggregateAndProof<T::EthSpec>,
     ) {
         let beacon_block_root = aggregate.message.aggregate.data.beacon_block_root;
+        let topic = &GossipKind::BeaconAggregateAndProof;
+
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self
+            .chain
+            .verify_aggregated_attestation_for_gossip(aggregate);
+        metrics::stop_timer(validation_timer);
+
+        self.process_gossip_aggregate_result(
+            verification_result,
+            message_id,
+            peer_id,
+            beacon_block_root,
+        );
+    }
 
-        let aggregate = match self
+    /// Process a batch of aggregates received from the gossip network, verifying their signatures
+    /// together and then handling each as per `process_gossip_aggregate`.
+    pub fn process_gossip_aggregate_batch(self, packages: Vec<GossipAggregatePackage<T::EthSpec>>) {
+        let mut aggregates = Vec::with_capacity(packages.len());
+        let mut metadata = Vec::with_capacity(packages.len());
+        for package in packages {
+            metadata.push((
+                package.message_id,
+                package.peer_id,
+                package.aggregate.message.aggregate.data.beacon_block_root,
+            ));
+            aggregates.push(*package.aggregate);
+        }
+
+        let verification_results = match self
             .chain
-            .verify_aggregated_attestation_for_gossip(aggregate)
+            .batch_verify_aggregated_attestations_for_gossip(aggregates)
         {
+            Ok(results) => results,
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Batch aggregate verification failed";
+                    "batch_size" => metadata.len(),
+                    "error" => ?e,
+                );
+                return;
+            }
+        };
+
+        for ((message_id, peer_id, beacon_block_root), result) in
+            metadata.into_iter().zip(verification_results)
+        {
+            self.process_gossip_aggregate_result(result, message_id, peer_id, beacon_block_root);
+        }
+    }
+
+    /// Handle the result of verifying an aggregate received from the gossip network, either
+    /// individually or as part of a batch.
+    fn process_gossip_aggregate_result(
+        &self,
+        verification_result: Result<VerifiedAggregatedAttestation<T>, AttnError>,
+        message_id: MessageId,
+        peer_id: PeerId,
+        beacon_block_root: Hash256,
+    ) {
+        let topic = &GossipKind::BeaconAggregateAndProof;
+
+        let aggregate = match verification_result {
             Ok(aggregate) => aggregate,
             Err(e) => {
                 // Report the failure to gossipsub
@@ -146,6 +330,7 @@ impl<T: BeaconChainTypes> Worker<T> {
                     peer_id,
                     message_id,
                     beacon_block_root,
+                    topic,
                     "aggregated",
                     e,
                 );
@@ -155,7 +340,13 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         // Indicate to the `Network` service that this message is valid and can be
         // propagated on the gossip network.
-        self.propagate_validation_result(message_id, peer_id.clone(), MessageAcceptance::Accept);
+        self.propagate_gossip_validation_result(
+            message_id,
+            peer_id.clone(),
+            topic,
+            MessageAcceptance::Accept,
+            "valid",
+        );
 
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_AGGREGATED_ATTESTATION_VERIFIED_TOTAL);
 
@@ -206,7 +397,13 @@ impl<T: BeaconChainTypes> Worker<T> {
         peer_id: PeerId,
         block: SignedBeaconBlock<T::EthSpec>,
     ) {
-        let verified_block = match self.chain.verify_block_for_gossip(block) {
+        let topic = &GossipKind::BeaconBlock;
+
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self.chain.verify_block_for_gossip(block);
+        metrics::stop_timer(validation_timer);
+
+        let verified_block = match verification_result {
             Ok(verified_block) => {
                 info!(
                     self.log,
@@ -214,10 +411,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "slot" => verified_block.block.slot(),
                     "hash" => %verified_block.block_root
                 );
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Accept,
+                    "valid",
                 );
                 verified_block
             }
@@ -235,7 +434,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                             "error" => %e);
                 // Prevent recurring behaviour by penalizing the peer slightly.
                 self.penalize_peer(peer_id.clone(), PeerAction::HighToleranceError);
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    metrics::block_error_reason(&e),
+                );
                 return;
             }
             Err(e @ BlockError::StateRootMismatch { .. })
@@ -253,10 +458,12 @@ impl<T: BeaconChainTypes> Worker<T> {
             | Err(e @ BlockError::GenesisBlock) => {
                 warn!(self.log, "Could not verify block for gossip, rejecting the block";
                             "error" => %e);
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    metrics::block_error_reason(&e),
                 );
                 self.penalize_peer(peer_id, PeerAction::LowToleranceError);
                 return;
@@ -328,14 +535,21 @@ impl<T: BeaconChainTypes> Worker<T> {
         voluntary_exit: SignedVoluntaryExit,
     ) {
         let validator_index = voluntary_exit.message.validator_index;
+        let topic = &GossipKind::VoluntaryExit;
 
-        let exit = match self.chain.verify_voluntary_exit_for_gossip(voluntary_exit) {
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self.chain.verify_voluntary_exit_for_gossip(voluntary_exit);
+        metrics::stop_timer(validation_timer);
+
+        let exit = match verification_result {
             Ok(ObservationOutcome::New(exit)) => exit,
             Ok(ObservationOutcome::AlreadyKnown) => {
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    "already_known",
                 );
                 debug!(
                     self.log,
@@ -355,10 +569,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                 );
                 // These errors occur due to a fault in the beacon chain. It is not necessarily
                 // the fault on the peer.
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    "invalid",
                 );
                 // We still penalize a peer slightly to prevent overuse of invalids.
                 self.penalize_peer(peer_id, PeerAction::HighToleranceError);
@@ -368,7 +584,13 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_EXIT_VERIFIED_TOTAL);
 
-        self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Accept);
+        self.propagate_gossip_validation_result(
+            message_id,
+            peer_id,
+            topic,
+            MessageAcceptance::Accept,
+            "valid",
+        );
 
         self.chain.import_voluntary_exit(exit);
         debug!(self.log, "Successfully imported voluntary exit");
@@ -383,11 +605,15 @@ impl<T: BeaconChainTypes> Worker<T> {
         proposer_slashing: ProposerSlashing,
     ) {
         let validator_index = proposer_slashing.signed_header_1.message.proposer_index;
+        let topic = &GossipKind::ProposerSlashing;
 
-        let slashing = match self
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self
             .chain
-            .verify_proposer_slashing_for_gossip(proposer_slashing)
-        {
+            .verify_proposer_slashing_for_gossip(proposer_slashing);
+        metrics::stop_timer(validation_timer);
+
+        let slashing = match verification_result {
             Ok(ObservationOutcome::New(slashing)) => slashing,
             Ok(ObservationOutcome::AlreadyKnown) => {
                 debug!(
@@ -397,7 +623,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "validator_index" => validator_index,
                     "peer" => %peer_id
                 );
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    "already_known",
+                );
                 return;
             }
             Err(e) => {
@@ -410,10 +642,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "peer" => %peer_id,
                     "error" => ?e
                 );
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    "invalid",
                 );
 
                 // Penalize peer slightly for invalids.
@@ -424,7 +658,13 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_PROPOSER_SLASHING_VERIFIED_TOTAL);
 
-        self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Accept);
+        self.propagate_gossip_validation_result(
+            message_id,
+            peer_id,
+            topic,
+            MessageAcceptance::Accept,
+            "valid",
+        );
 
         self.chain.import_proposer_slashing(slashing);
         debug!(self.log, "Successfully imported proposer slashing");
@@ -438,10 +678,15 @@ impl<T: BeaconChainTypes> Worker<T> {
         peer_id: PeerId,
         attester_slashing: AttesterSlashing<T::EthSpec>,
     ) {
-        let slashing = match self
+        let topic = &GossipKind::AttesterSlashing;
+
+        let validation_timer = metrics::start_gossip_validation_timer(topic);
+        let verification_result = self
             .chain
-            .verify_attester_slashing_for_gossip(attester_slashing)
-        {
+            .verify_attester_slashing_for_gossip(attester_slashing);
+        metrics::stop_timer(validation_timer);
+
+        let slashing = match verification_result {
             Ok(ObservationOutcome::New(slashing)) => slashing,
             Ok(ObservationOutcome::AlreadyKnown) => {
                 debug!(
@@ -450,7 +695,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "reason" => "Slashings already known for all slashed validators",
                     "peer" => %peer_id
                 );
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    "already_known",
+                );
                 return;
             }
             Err(e) => {
@@ -460,10 +711,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "peer" => %peer_id,
                     "error" => ?e
                 );
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    "invalid",
                 );
                 // Penalize peer slightly for invalids.
                 self.penalize_peer(peer_id, PeerAction::HighToleranceError);
@@ -473,7 +726,13 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_ATTESTER_SLASHING_VERIFIED_TOTAL);
 
-        self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Accept);
+        self.propagate_gossip_validation_result(
+            message_id,
+            peer_id,
+            topic,
+            MessageAcceptance::Accept,
+            "valid",
+        );
 
         if let Err(e) = self.chain.import_attester_slashing(slashing) {
             debug!(self.log, "Error importing attester slashing"; "error" => ?e);
@@ -491,10 +750,12 @@ impl<T: BeaconChainTypes> Worker<T> {
         peer_id: PeerId,
         message_id: MessageId,
         beacon_block_root: Hash256,
+        topic: &GossipKind,
         attestation_type: &str,
         error: AttnError,
     ) {
         metrics::register_attestation_error(&error);
+        let reason = metrics::attestation_error_reason(&error);
         match &error {
             AttnError::FutureEpoch { .. }
             | AttnError::PastEpoch { .. }
@@ -519,10 +780,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
 
                 // Do not propagate these messages.
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    reason,
                 );
             }
             AttnError::InvalidSelectionProof { .. } | AttnError::InvalidSignature => {
@@ -531,10 +794,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -546,10 +811,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  * violation of the spec nor indication of fault.
                  *
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -567,10 +834,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -588,10 +857,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -609,7 +880,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "block" => %beacon_block_root,
                     "type" => ?attestation_type,
                 );
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    reason,
+                );
                 return;
             }
             AttnError::AggregatorAlreadyKnown(_) => {
@@ -630,7 +907,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                 // behaviour.
                 self.penalize_peer(peer_id.clone(), PeerAction::HighToleranceError);
 
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    reason,
+                );
 
                 return;
             }
@@ -651,7 +934,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                 // behaviour.
                 self.penalize_peer(peer_id.clone(), PeerAction::HighToleranceError);
 
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    reason,
+                );
 
                 return;
             }
@@ -662,10 +951,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -699,7 +990,13 @@ impl<T: BeaconChainTypes> Worker<T> {
                 // behaviour.
                 self.penalize_peer(peer_id.clone(), PeerAction::HighToleranceError);
 
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+                self.propagate_gossip_validation_result(
+                    message_id,
+                    peer_id,
+                    topic,
+                    MessageAcceptance::Ignore,
+                    reason,
+                );
                 return;
             }
             AttnError::UnknownTargetRoot(_) => {
@@ -719,10 +1016,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -733,10 +1032,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -746,10 +1047,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -759,10 +1062,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -772,10 +1077,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -790,10 +1097,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "expected" => ?expected,
                     "received" => ?received,
                 );
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -803,10 +1112,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -816,10 +1127,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -829,10 +1142,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
@@ -853,10 +1168,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                 );
                 // In this case we wish to penalize gossipsub peers that do this to avoid future
                 // attestations that have too many skip slots.
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Reject,
+                    reason,
                 );
                 self.penalize_peer(peer_id.clone(), PeerAction::MidToleranceError);
             }
@@ -874,10 +1191,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                     "peer_id" => %peer_id,
                     "error" => ?e,
                 );
-                self.propagate_validation_result(
+                self.propagate_gossip_validation_result(
                     message_id,
                     peer_id.clone(),
+                    topic,
                     MessageAcceptance::Ignore,
+                    reason,
                 );
                 // Penalize the peer slightly
                 self.penalize_peer(peer_id.clone(), PeerAction::HighToleranceError);
@@ -8,6 +8,7 @@ mod gossip_methods;
 mod rpc_methods;
 mod sync_methods;
 
+pub use gossip_methods::{GossipAggregatePackage, GossipAttestationPackage};
 pub use sync_methods::ProcessId;
 
 pub(crate) const FUTURE_SLOT_TOLERANCE: u64 = 1;
@@ -1,4 +1,5 @@
 use beacon_chain::attestation_verification::Error as AttnError;
+use beacon_chain::BlockError;
 use eth2_libp2p::PubsubMessage;
 use eth2_libp2p::{
     types::GossipKind, BandwidthSinks, GossipTopic, Gossipsub, NetworkGlobals, TopicHash,
@@ -150,6 +151,40 @@ lazy_static! {
         "Count of gossip aggregated attestations transmitted"
     );
 
+    /*
+     * Gossip per-topic
+     */
+    pub static ref GOSSIP_MESSAGES_RX_PER_TOPIC: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_messages_rx_per_topic_total",
+        "Count of gossip messages received per topic",
+        &["topic"]
+    );
+    pub static ref GOSSIP_MESSAGES_TX_PER_TOPIC: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_messages_tx_per_topic_total",
+        "Count of gossip messages published per topic",
+        &["topic"]
+    );
+    pub static ref GOSSIP_MESSAGES_ACCEPTED_PER_TOPIC: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_messages_accepted_per_topic_total",
+        "Count of received gossip messages that passed validation and were propagated, per topic",
+        &["topic"]
+    );
+    pub static ref GOSSIP_MESSAGES_IGNORED_PER_TOPIC: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_messages_ignored_per_topic_total",
+        "Count of received gossip messages that were ignored, per topic and reason",
+        &["topic", "reason"]
+    );
+    pub static ref GOSSIP_MESSAGES_REJECTED_PER_TOPIC: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_messages_rejected_per_topic_total",
+        "Count of received gossip messages that were rejected, per topic and reason",
+        &["topic", "reason"]
+    );
+    pub static ref GOSSIP_MESSAGE_VALIDATION_SECONDS_PER_TOPIC: Result<HistogramVec> = try_create_histogram_vec(
+        "gossipsub_message_validation_seconds_per_topic",
+        "Time taken to validate a gossip message for propagation, per topic",
+        &["topic"]
+    );
+
     /*
      * Attestation subnet subscriptions
      */
@@ -448,7 +483,19 @@ lazy_static! {
         "Number of Syncing chains in range, per range type",
         &["range_type"]
     );
-
+    pub static ref SYNC_RANGE_PART_DOWNLOAD_TIMES: Result<Histogram> = try_create_histogram(
+        "sync_range_part_download_seconds",
+        "Time taken by a peer to serve a part of a range sync batch"
+    );
+    pub static ref SYNC_RANGE_DOWNLOADED_BYTES: Result<IntCounterVec> = try_create_int_counter_vec(
+        "sync_range_downloaded_bytes_total",
+        "Total SSZ bytes of blocks downloaded by range sync, per client",
+        &["client"]
+    );
+    pub static ref SYNC_RANGE_SLOW_PARTS_REPLACED: Result<IntCounter> = try_create_int_counter(
+        "sync_range_slow_parts_replaced_total",
+        "Count of range sync batch parts re-requested from another peer due to a slow download"
+    );
 }
 
 pub fn register_attestation_error(error: &AttnError) {
@@ -515,9 +562,100 @@ pub fn register_attestation_error(error: &AttnError) {
     }
 }
 
+/// Returns the reason label used when an `Attestation` or `SignedAggregateAndProof` fails gossip
+/// verification.
+pub fn attestation_error_reason(error: &AttnError) -> &'static str {
+    match error {
+        AttnError::FutureEpoch { .. } => "future_epoch",
+        AttnError::PastEpoch { .. } => "past_epoch",
+        AttnError::FutureSlot { .. } => "future_slot",
+        AttnError::PastSlot { .. } => "past_slot",
+        AttnError::InvalidSelectionProof { .. } => "invalid_selection_proof",
+        AttnError::InvalidSignature => "invalid_signature",
+        AttnError::EmptyAggregationBitfield => "empty_aggregation_bitfield",
+        AttnError::AggregatorPubkeyUnknown(_) => "aggregator_pubkey_unknown",
+        AttnError::AggregatorNotInCommittee { .. } => "aggregator_not_in_committee",
+        AttnError::AttestationAlreadyKnown { .. } => "attestation_already_known",
+        AttnError::AggregatorAlreadyKnown(_) => "aggregator_already_known",
+        AttnError::PriorAttestationKnown { .. } => "prior_attestation_known",
+        AttnError::ValidatorIndexTooHigh(_) => "validator_index_too_high",
+        AttnError::UnknownHeadBlock { .. } => "unknown_head_block",
+        AttnError::UnknownTargetRoot(_) => "unknown_target_root",
+        AttnError::BadTargetEpoch => "bad_target_epoch",
+        AttnError::NoCommitteeForSlotAndIndex { .. } => "no_committee_for_slot_and_index",
+        AttnError::NotExactlyOneAggregationBitSet(_) => "not_exactly_one_aggregation_bit_set",
+        AttnError::AttestsToFutureBlock { .. } => "attests_to_future_block",
+        AttnError::InvalidSubnetId { .. } => "invalid_subnet_id",
+        AttnError::Invalid(_) => "invalid_state_processing",
+        AttnError::InvalidTargetRoot { .. } => "invalid_target_root",
+        AttnError::InvalidTargetEpoch { .. } => "invalid_target_epoch",
+        AttnError::TooManySkippedSlots { .. } => "too_many_skipped_slots",
+        AttnError::BeaconChainError(_) => "beacon_chain_error",
+    }
+}
+
+/// Returns the reason label used when a gossip block fails verification.
+pub fn block_error_reason<T: EthSpec>(error: &BlockError<T>) -> &'static str {
+    match error {
+        BlockError::ParentUnknown(_) => "parent_unknown",
+        BlockError::TooManySkippedSlots { .. } => "too_many_skipped_slots",
+        BlockError::FutureSlot { .. } => "future_slot",
+        BlockError::StateRootMismatch { .. } => "state_root_mismatch",
+        BlockError::GenesisBlock => "genesis_block",
+        BlockError::WouldRevertFinalizedSlot { .. } => "would_revert_finalized_slot",
+        BlockError::NotFinalizedDescendant { .. } => "not_finalized_descendant",
+        BlockError::BlockIsAlreadyKnown => "block_is_already_known",
+        BlockError::RepeatProposal { .. } => "repeat_proposal",
+        BlockError::BlockSlotLimitReached => "block_slot_limit_reached",
+        BlockError::IncorrectBlockProposer { .. } => "incorrect_block_proposer",
+        BlockError::ProposalSignatureInvalid => "proposal_signature_invalid",
+        BlockError::UnknownValidator(_) => "unknown_validator",
+        BlockError::InvalidSignature => "invalid_signature",
+        BlockError::BlockIsNotLaterThanParent { .. } => "block_is_not_later_than_parent",
+        BlockError::NonLinearParentRoots => "non_linear_parent_roots",
+        BlockError::NonLinearSlots => "non_linear_slots",
+        BlockError::PerBlockProcessingError(_) => "per_block_processing_error",
+        BlockError::BeaconChainError(_) => "beacon_chain_error",
+        BlockError::WeakSubjectivityConflict => "weak_subjectivity_conflict",
+    }
+}
+
+/// Starts a timer measuring the time taken to validate a gossip message on `topic`.
+pub fn start_gossip_validation_timer(topic: &GossipKind) -> Option<HistogramTimer> {
+    start_timer_vec(
+        &GOSSIP_MESSAGE_VALIDATION_SECONDS_PER_TOPIC,
+        &[&topic.to_string()],
+    )
+}
+
+/// Records that a gossip message on `topic` passed validation and will be propagated.
+pub fn register_gossip_accepted(topic: &GossipKind) {
+    inc_counter_vec(&GOSSIP_MESSAGES_ACCEPTED_PER_TOPIC, &[&topic.to_string()]);
+}
+
+/// Records that a gossip message on `topic` was ignored for the given `reason`.
+pub fn register_gossip_ignored(topic: &GossipKind, reason: &str) {
+    inc_counter_vec(
+        &GOSSIP_MESSAGES_IGNORED_PER_TOPIC,
+        &[&topic.to_string(), reason],
+    );
+}
+
+/// Records that a gossip message on `topic` was rejected for the given `reason`.
+pub fn register_gossip_rejected(topic: &GossipKind, reason: &str) {
+    inc_counter_vec(
+        &GOSSIP_MESSAGES_REJECTED_PER_TOPIC,
+        &[&topic.to_string(), reason],
+    );
+}
+
 /// Inspects the `messages` that were being sent to the network and updates Prometheus metrics.
 pub fn expose_publish_metrics<T: EthSpec>(messages: &[PubsubMessage<T>]) {
     for message in messages {
+        inc_counter_vec(
+            &GOSSIP_MESSAGES_TX_PER_TOPIC,
+            &[&message.kind().to_string()],
+        );
         match message {
             PubsubMessage::BeaconBlock(_) => inc_counter(&GOSSIP_BLOCKS_TX),
             PubsubMessage::Attestation(subnet_id) => {
@@ -537,6 +675,10 @@ pub fn expose_publish_metrics<T: EthSpec>(messages: &[PubsubMessage<T>]) {
 
 /// Inspects a `message` received from the network and updates Prometheus metrics.
 pub fn expose_receive_metrics<T: EthSpec>(message: &PubsubMessage<T>) {
+    inc_counter_vec(
+        &GOSSIP_MESSAGES_RX_PER_TOPIC,
+        &[&message.kind().to_string()],
+    );
     match message {
         PubsubMessage::BeaconBlock(_) => inc_counter(&GOSSIP_BLOCKS_RX),
         PubsubMessage::Attestation(_) => inc_counter(&GOSSIP_UNAGGREGATED_ATTESTATIONS_RX),
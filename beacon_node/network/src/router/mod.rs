@@ -74,6 +74,7 @@ impl<T: BeaconChainTypes> Router<T> {
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
         executor: task_executor::TaskExecutor,
+        proposer_only: bool,
         log: slog::Logger,
     ) -> error::Result<mpsc::UnboundedSender<RouterMessage<T::EthSpec>>> {
         let message_handler_log = log.new(o!("service"=> "router"));
@@ -87,6 +88,7 @@ impl<T: BeaconChainTypes> Router<T> {
             beacon_chain,
             network_globals.clone(),
             network_send,
+            proposer_only,
             &log,
         );
 
@@ -28,6 +28,8 @@ pub struct Processor<T: BeaconChainTypes> {
     network: HandlerNetworkContext<T::EthSpec>,
     /// A multi-threaded, non-blocking processor for applying messages to the beacon chain.
     beacon_processor_send: mpsc::Sender<BeaconWorkEvent<T::EthSpec>>,
+    /// Decline `BlocksByRange` requests, as this node only publishes blocks.
+    proposer_only: bool,
     /// The `RPCHandler` logger.
     log: slog::Logger,
 }
@@ -39,6 +41,7 @@ impl<T: BeaconChainTypes> Processor<T> {
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+        proposer_only: bool,
         log: &slog::Logger,
     ) -> Self {
         let sync_logger = log.new(o!("service"=> "sync"));
@@ -72,6 +75,7 @@ impl<T: BeaconChainTypes> Processor<T> {
             sync_send,
             network: HandlerNetworkContext::new(network_send, log.clone()),
             beacon_processor_send,
+            proposer_only,
             log: log.new(o!("service" => "router")),
         }
     }
@@ -163,6 +167,22 @@ impl<T: BeaconChainTypes> Processor<T> {
         request_id: PeerRequestId,
         req: BlocksByRangeRequest,
     ) {
+        if self.proposer_only {
+            debug!(
+                self.log,
+                "Declining BlocksByRange request";
+                "peer" => %peer_id,
+                "reason" => "proposer-only node",
+            );
+            self.network.send_error_response(
+                peer_id,
+                request_id,
+                RPCResponseErrorCode::ServerError,
+                "Block ranges are not served by this node".to_string(),
+            );
+            return;
+        }
+
         self.send_beacon_processor_work(BeaconWorkEvent::blocks_by_range_request(
             peer_id, request_id, req,
         ))
@@ -390,7 +410,7 @@ impl<T: EthSpec> HandlerNetworkContext<T> {
     }
 
     /// Sends an error response to the network task.
-    pub fn _send_error_response(
+    pub fn send_error_response(
         &mut self,
         peer_id: PeerId,
         id: PeerRequestId,
@@ -18,7 +18,7 @@ use std::{net::SocketAddr, sync::Arc, time::Duration};
 use store::HotColdDB;
 use tokio::sync::mpsc;
 use tokio::time::Sleep;
-use types::{EthSpec, RelativeEpoch, SubnetId, Unsigned, ValidatorSubscription};
+use types::{EthSpec, RelativeEpoch, SubnetId, Uint256, Unsigned, ValidatorSubscription};
 
 mod tests;
 
@@ -49,8 +49,6 @@ pub enum NetworkMessage<T: EthSpec> {
     },
     /// Respond to a peer's request with an error.
     SendError {
-        // NOTE: Currently this is never used, we just say goodbye without nicely closing the
-        // stream assigned to the request
         peer_id: PeerId,
         error: RPCResponseErrorCode,
         reason: String,
@@ -81,6 +79,13 @@ pub enum NetworkMessage<T: EthSpec> {
         peer_id: PeerId,
         reason: GoodbyeReason,
     },
+    /// Ban a peer at the request of the user, optionally for a limited duration.
+    BanPeer {
+        peer_id: PeerId,
+        duration: Option<Duration>,
+    },
+    /// Lift a ban on a peer at the request of the user.
+    UnbanPeer { peer_id: PeerId },
 }
 
 /// Service that handles communication between internal services and the `eth2_libp2p` network service.
@@ -182,12 +187,14 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             network_globals.clone(),
             network_send.clone(),
             executor.clone(),
+            config.proposer_only,
             network_log.clone(),
         )?;
 
         // attestation service
+        let node_id = Uint256::from_big_endian(&network_globals.local_enr().node_id().raw());
         let attestation_service =
-            AttestationService::new(beacon_chain.clone(), &config, &network_log);
+            AttestationService::new(beacon_chain.clone(), node_id, &config, &network_log);
 
         // create a timer for updating network metrics
         let metrics_update = tokio::time::interval(Duration::from_secs(METRIC_UPDATE_INTERVAL));
@@ -383,6 +390,12 @@ fn spawn_service<T: BeaconChainTypes>(
                         }
                         NetworkMessage::ReportPeer { peer_id, action } => service.libp2p.report_peer(&peer_id, action),
                         NetworkMessage::GoodbyePeer { peer_id, reason } => service.libp2p.goodbye_peer(&peer_id, reason),
+                        NetworkMessage::BanPeer { peer_id, duration } => service.libp2p.ban_peer_manually(&peer_id, duration),
+                        NetworkMessage::UnbanPeer { peer_id } => {
+                            if let Err(e) = service.libp2p.unban_peer_manually(&peer_id) {
+                                warn!(service.log, "Could not unban peer"; "peer_id" => %peer_id, "error" => e);
+                            }
+                        }
                         NetworkMessage::Subscribe { subscriptions } => {
                             if let Err(e) = service
                                 .attestation_service
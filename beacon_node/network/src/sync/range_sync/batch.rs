@@ -5,15 +5,20 @@ use ssz::Encode;
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::ops::Sub;
+use std::time::{Duration, Instant};
 use types::{Epoch, EthSpec, SignedBeaconBlock, Slot};
 
-/// The number of times to retry a batch before it is considered failed.
+/// The number of times to retry a part of a batch before the batch is considered failed.
 const MAX_BATCH_DOWNLOAD_ATTEMPTS: u8 = 5;
 
 /// Invalid batches are attempted to be re-downloaded from other peers. If a batch cannot be processed
 /// after `MAX_BATCH_PROCESSING_ATTEMPTS` times, it is considered faulty.
 const MAX_BATCH_PROCESSING_ATTEMPTS: u8 = 3;
 
+/// Batches are split into parts of (at most) this many slots. Each part is requested from a
+/// single peer, allowing a batch to be downloaded from several peers in parallel.
+pub const SLOTS_PER_BATCH_PART: u64 = 16;
+
 /// Error type of a batch in a wrong state.
 // Such errors should never be encountered.
 pub struct WrongState(pub(super) String);
@@ -29,8 +34,8 @@ pub struct BatchInfo<T: EthSpec> {
     end_slot: Slot,
     /// The `Attempts` that have been made and failed to send us this batch.
     failed_processing_attempts: Vec<Attempt>,
-    /// The number of download retries this batch has undergone due to a failed request.
-    failed_download_attempts: Vec<PeerId>,
+    /// The peers of each failed download of a part of this batch, indexed by part.
+    failed_download_attempts: Vec<Vec<PeerId>>,
     /// State of the batch.
     state: BatchState<T>,
 }
@@ -39,10 +44,10 @@ pub struct BatchInfo<T: EthSpec> {
 pub enum BatchState<T: EthSpec> {
     /// The batch has failed either downloading or processing, but can be requested again.
     AwaitingDownload,
-    /// The batch is being downloaded.
-    Downloading(PeerId, Vec<SignedBeaconBlock<T>>, RequestId),
+    /// At least one part of the batch is being downloaded, or has been downloaded.
+    Downloading(Vec<BatchPart<T>>),
     /// The batch has been completely downloaded and is ready for processing.
-    AwaitingProcessing(PeerId, Vec<SignedBeaconBlock<T>>),
+    AwaitingProcessing(Vec<DownloadedPart<T>>),
     /// The batch is being processed.
     Processing(Attempt),
     /// The batch was successfully processed and is waiting to be validated.
@@ -58,6 +63,41 @@ pub enum BatchState<T: EthSpec> {
     Failed,
 }
 
+/// The download state of a single part of a batch.
+pub enum BatchPart<T: EthSpec> {
+    /// The part has not been requested, or a previous request failed.
+    AwaitingDownload,
+    /// The part has been requested from a peer.
+    Downloading {
+        peer: PeerId,
+        request_id: RequestId,
+        blocks: Vec<SignedBeaconBlock<T>>,
+        started: Instant,
+    },
+    /// The part has been completely downloaded.
+    Downloaded(DownloadedPart<T>),
+}
+
+/// The blocks of a part of a batch, and the peer that sent them.
+pub struct DownloadedPart<T: EthSpec> {
+    pub peer: PeerId,
+    pub blocks: Vec<SignedBeaconBlock<T>>,
+}
+
+/// The outcome of successfully downloading a part of a batch.
+pub struct PartDownload {
+    /// The peer that served the part.
+    pub peer: PeerId,
+    /// The number of blocks received for the part.
+    pub blocks: usize,
+    /// The total SSZ-encoded size of the received blocks.
+    pub bytes: usize,
+    /// The time taken to serve the request.
+    pub duration: Duration,
+    /// True if this was the last part of the batch to be downloaded.
+    pub batch_completed: bool,
+}
+
 impl<T: EthSpec> BatchState<T> {
     /// Helper function for poisoning a state.
     pub fn poison(&mut self) -> BatchState<T> {
@@ -85,56 +125,137 @@ impl<T: EthSpec> BatchInfo<T> {
     pub fn new(start_epoch: &Epoch, num_of_epochs: u64) -> Self {
         let start_slot = start_epoch.start_slot(T::slots_per_epoch()) + 1;
         let end_slot = start_slot + num_of_epochs * T::slots_per_epoch();
+        let num_parts = (num_of_epochs * T::slots_per_epoch() + SLOTS_PER_BATCH_PART - 1)
+            / SLOTS_PER_BATCH_PART;
         BatchInfo {
             start_slot,
             end_slot,
             failed_processing_attempts: Vec::new(),
-            failed_download_attempts: Vec::new(),
+            failed_download_attempts: vec![Vec::new(); num_parts as usize],
             state: BatchState::AwaitingDownload,
         }
     }
 
+    /// The number of parts this batch is split into.
+    pub fn num_parts(&self) -> usize {
+        self.failed_download_attempts.len()
+    }
+
+    /// The slot range `[start, end)` of a part of the batch.
+    fn part_range(&self, part: usize) -> (Slot, Slot) {
+        let start = self.start_slot + part as u64 * SLOTS_PER_BATCH_PART;
+        let end = std::cmp::min(start + SLOTS_PER_BATCH_PART, self.end_slot);
+        (start, end)
+    }
+
     /// Gives a list of peers from which this batch has had a failed download or processing
     /// attempt.
     pub fn failed_peers(&self) -> HashSet<PeerId> {
-        let mut peers = HashSet::with_capacity(
-            self.failed_processing_attempts.len() + self.failed_download_attempts.len(),
-        );
+        let mut peers = HashSet::new();
 
         for attempt in &self.failed_processing_attempts {
-            peers.insert(attempt.peer_id.clone());
+            for part in &attempt.parts {
+                peers.insert(part.peer_id.clone());
+            }
         }
 
-        for download in &self.failed_download_attempts {
-            peers.insert(download.clone());
+        for downloads in &self.failed_download_attempts {
+            for peer in downloads {
+                peers.insert(peer.clone());
+            }
         }
 
         peers
     }
 
+    /// Returns the index of the part that a response from `peer_id` for `request_id` belongs to,
+    /// if any.
+    pub fn part_for_request(&self, peer_id: &PeerId, request_id: &RequestId) -> Option<usize> {
+        if let BatchState::Downloading(parts) = &self.state {
+            return parts.iter().position(|part| match part {
+                BatchPart::Downloading {
+                    peer,
+                    request_id: expected_id,
+                    ..
+                } => peer == peer_id && expected_id == request_id,
+                _ => false,
+            });
+        }
+        None
+    }
+
     /// Verifies if an incomming block belongs to this batch.
     pub fn is_expecting_block(&self, peer_id: &PeerId, request_id: &RequestId) -> bool {
-        if let BatchState::Downloading(expected_peer, _, expected_id) = &self.state {
-            return peer_id == expected_peer && expected_id == request_id;
+        self.part_for_request(peer_id, request_id).is_some()
+    }
+
+    /// Returns true if any part of this batch is being downloaded from `peer_id`.
+    pub fn is_downloading_from(&self, peer_id: &PeerId) -> bool {
+        self.downloading_parts()
+            .iter()
+            .any(|(_, peer, _)| peer == peer_id)
+    }
+
+    /// Returns the index, peer and elapsed download time of each part that is being downloaded.
+    pub fn downloading_parts(&self) -> Vec<(usize, PeerId, Duration)> {
+        match &self.state {
+            BatchState::Downloading(parts) => parts
+                .iter()
+                .enumerate()
+                .filter_map(|(index, part)| match part {
+                    BatchPart::Downloading { peer, started, .. } => {
+                        Some((index, peer.clone(), started.elapsed()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the indices of the parts that need to be requested.
+    pub fn parts_awaiting_download(&self) -> Vec<usize> {
+        match &self.state {
+            BatchState::AwaitingDownload => (0..self.num_parts()).collect(),
+            BatchState::Downloading(parts) => parts
+                .iter()
+                .enumerate()
+                .filter(|(_, part)| matches!(part, BatchPart::AwaitingDownload))
+                .map(|(index, _)| index)
+                .collect(),
+            _ => Vec::new(),
         }
-        false
     }
 
-    pub fn current_peer(&self) -> Option<&PeerId> {
+    /// Returns the peers that sent (or are sending) the blocks of this batch.
+    pub fn current_peers(&self) -> Vec<PeerId> {
         match &self.state {
-            BatchState::AwaitingDownload | BatchState::Failed => None,
-            BatchState::Downloading(peer_id, _, _)
-            | BatchState::AwaitingProcessing(peer_id, _)
-            | BatchState::Processing(Attempt { peer_id, .. })
-            | BatchState::AwaitingValidation(Attempt { peer_id, .. }) => Some(&peer_id),
+            BatchState::AwaitingDownload | BatchState::Failed => Vec::new(),
+            BatchState::Downloading(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    BatchPart::AwaitingDownload => None,
+                    BatchPart::Downloading { peer, .. }
+                    | BatchPart::Downloaded(DownloadedPart { peer, .. }) => Some(peer.clone()),
+                })
+                .collect(),
+            BatchState::AwaitingProcessing(parts) => {
+                parts.iter().map(|part| part.peer.clone()).collect()
+            }
+            BatchState::Processing(attempt) | BatchState::AwaitingValidation(attempt) => attempt
+                .parts
+                .iter()
+                .map(|part| part.peer_id.clone())
+                .collect(),
             BatchState::Poisoned => unreachable!("Poisoned batch"),
         }
     }
 
-    pub fn to_blocks_by_range_request(&self) -> BlocksByRangeRequest {
+    pub fn to_blocks_by_range_request(&self, part: usize) -> BlocksByRangeRequest {
+        let (start_slot, end_slot) = self.part_range(part);
         BlocksByRangeRequest {
-            start_slot: self.start_slot.into(),
-            count: self.end_slot.sub(self.start_slot).into(),
+            start_slot: start_slot.into(),
+            count: end_slot.sub(start_slot).into(),
             step: 1,
         }
     }
@@ -147,131 +268,262 @@ impl<T: EthSpec> BatchInfo<T> {
         &self.failed_processing_attempts
     }
 
-    /// Adds a block to a downloading batch.
-    pub fn add_block(&mut self, block: SignedBeaconBlock<T>) -> Result<(), WrongState> {
-        match self.state.poison() {
-            BatchState::Downloading(peer, mut blocks, req_id) => {
+    /// Adds a block to the downloading part of the batch that matches the request.
+    pub fn add_block(
+        &mut self,
+        peer_id: &PeerId,
+        request_id: &RequestId,
+        block: SignedBeaconBlock<T>,
+    ) -> Result<(), WrongState> {
+        let index = self.part_for_request(peer_id, request_id).ok_or_else(|| {
+            WrongState(format!(
+                "Add block for batch in wrong state {:?}",
+                self.state
+            ))
+        })?;
+        if let BatchState::Downloading(parts) = &mut self.state {
+            if let BatchPart::Downloading { blocks, .. } = &mut parts[index] {
                 blocks.push(block);
-                self.state = BatchState::Downloading(peer, blocks, req_id);
-                Ok(())
-            }
-            BatchState::Poisoned => unreachable!("Poisoned batch"),
-            other => {
-                self.state = other;
-                Err(WrongState(format!(
-                    "Add block for batch in wrong state {:?}",
-                    self.state
-                )))
             }
         }
+        Ok(())
     }
 
-    /// Marks the batch as ready to be processed if the blocks are in the range. The number of
-    /// received blocks is returned, or the wrong batch end on failure
+    /// Marks the part of the batch matching the request as downloaded if the blocks are in the
+    /// part's range. Once all parts are downloaded the batch is ready to be processed.
+    ///
+    /// Information about the download is returned, or the wrong part end on failure.
     #[must_use = "Batch may have failed"]
     pub fn download_completed(
         &mut self,
-    ) -> Result<usize /* Received blocks */, Result<(Slot, Slot, IsFailed), WrongState>> {
-        match self.state.poison() {
-            BatchState::Downloading(peer, blocks, _request_id) => {
-                // verify that blocks are in range
-                if let Some(last_slot) = blocks.last().map(|b| b.slot()) {
-                    // the batch is non-empty
-                    let first_slot = blocks[0].slot();
-
-                    let failed_range = if first_slot < self.start_slot {
-                        Some((self.start_slot, first_slot))
-                    } else if self.end_slot < last_slot {
-                        Some((self.end_slot, last_slot))
-                    } else {
-                        None
-                    };
-
-                    if let Some((expected, received)) = failed_range {
-                        // this is a failed download, register the attempt and check if the batch
-                        // can be tried again
-                        self.failed_download_attempts.push(peer);
-                        self.state = if self.failed_download_attempts.len()
-                            >= MAX_BATCH_DOWNLOAD_ATTEMPTS as usize
-                        {
-                            BatchState::Failed
-                        } else {
-                            // drop the blocks
-                            BatchState::AwaitingDownload
-                        };
-
-                        return Err(Ok((expected, received, self.state.is_failed())));
-                    }
-                }
-
-                let received = blocks.len();
-                self.state = BatchState::AwaitingProcessing(peer, blocks);
-                Ok(received)
-            }
-            BatchState::Poisoned => unreachable!("Poisoned batch"),
-            other => {
-                self.state = other;
-                Err(Err(WrongState(format!(
-                    "Download completed for batch in wrong state {:?}",
-                    self.state
-                ))))
+        peer_id: &PeerId,
+        request_id: &RequestId,
+    ) -> Result<PartDownload, Result<(Slot, Slot, IsFailed), WrongState>> {
+        let index = self.part_for_request(peer_id, request_id).ok_or_else(|| {
+            Err(WrongState(format!(
+                "Download completed for batch in wrong state {:?}",
+                self.state
+            )))
+        })?;
+        let (part_start, part_end) = self.part_range(index);
+
+        let mut parts = match self.state.poison() {
+            BatchState::Downloading(parts) => parts,
+            _ => unreachable!("part_for_request only matches downloading batches"),
+        };
+
+        let (peer, blocks, started) =
+            match std::mem::replace(&mut parts[index], BatchPart::AwaitingDownload) {
+                BatchPart::Downloading {
+                    peer,
+                    blocks,
+                    started,
+                    ..
+                } => (peer, blocks, started),
+                _ => unreachable!("part_for_request only matches downloading parts"),
+            };
+
+        // verify that blocks are in range
+        if let Some(last_slot) = blocks.last().map(|b| b.slot()) {
+            // the part is non-empty
+            let first_slot = blocks[0].slot();
+
+            let failed_range = if first_slot < part_start {
+                Some((part_start, first_slot))
+            } else if part_end < last_slot {
+                Some((part_end, last_slot))
+            } else {
+                None
+            };
+
+            if let Some((expected, received)) = failed_range {
+                // this is a failed download, register the attempt and check if the batch
+                // can be tried again. The blocks are dropped.
+                self.register_failed_download(parts, index, peer);
+                return Err(Ok((expected, received, self.state.is_failed())));
             }
         }
+
+        let download = PartDownload {
+            peer: peer.clone(),
+            blocks: blocks.len(),
+            bytes: blocks.iter().map(|block| block.ssz_bytes_len()).sum(),
+            duration: started.elapsed(),
+            batch_completed: false,
+        };
+        parts[index] = BatchPart::Downloaded(DownloadedPart { peer, blocks });
+
+        if parts
+            .iter()
+            .all(|part| matches!(part, BatchPart::Downloaded(_)))
+        {
+            let downloaded = parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    BatchPart::Downloaded(downloaded) => Some(downloaded),
+                    _ => None,
+                })
+                .collect();
+            self.state = BatchState::AwaitingProcessing(downloaded);
+            Ok(PartDownload {
+                batch_completed: true,
+                ..download
+            })
+        } else {
+            self.state = BatchState::Downloading(parts);
+            Ok(download)
+        }
     }
 
+    /// Registers a failed download for every part of the batch being downloaded from `peer_id`,
+    /// or only the part matching `request_id` if given. Those parts can then be requested again.
     #[must_use = "Batch may have failed"]
-    pub fn download_failed(&mut self) -> Result<IsFailed, WrongState> {
-        match self.state.poison() {
-            BatchState::Downloading(peer, _, _request_id) => {
-                // register the attempt and check if the batch can be tried again
-                self.failed_download_attempts.push(peer);
-                self.state = if self.failed_download_attempts.len()
-                    >= MAX_BATCH_DOWNLOAD_ATTEMPTS as usize
-                {
-                    BatchState::Failed
-                } else {
-                    // drop the blocks
-                    BatchState::AwaitingDownload
-                };
-                Ok(self.state.is_failed())
-            }
+    pub fn download_failed(
+        &mut self,
+        peer_id: &PeerId,
+        request_id: Option<&RequestId>,
+    ) -> Result<IsFailed, WrongState> {
+        let failed_parts = match request_id {
+            Some(request_id) => self
+                .part_for_request(peer_id, request_id)
+                .into_iter()
+                .collect(),
+            None => self
+                .downloading_parts()
+                .into_iter()
+                .filter(|(_, peer, _)| peer == peer_id)
+                .map(|(index, _, _)| index)
+                .collect::<Vec<_>>(),
+        };
+
+        if failed_parts.is_empty() {
+            return Err(WrongState(format!(
+                "Download failed for batch in wrong state {:?}",
+                self.state
+            )));
+        }
+
+        for index in failed_parts {
+            let mut parts = match self.state.poison() {
+                BatchState::Downloading(parts) => parts,
+                // a previous part has failed the batch
+                other => {
+                    self.state = other;
+                    break;
+                }
+            };
+            parts[index] = BatchPart::AwaitingDownload;
+            self.register_failed_download(parts, index, peer_id.clone());
+        }
+        Ok(self.state.is_failed())
+    }
+
+    /// Registers a failed download of a part whose request could not be sent to `peer`.
+    #[must_use = "Batch may have failed"]
+    pub fn part_request_failed(
+        &mut self,
+        part: usize,
+        peer: PeerId,
+    ) -> Result<IsFailed, WrongState> {
+        let parts = match self.state.poison() {
+            BatchState::AwaitingDownload => (0..self.num_parts())
+                .map(|_| BatchPart::AwaitingDownload)
+                .collect(),
+            BatchState::Downloading(parts) => parts,
             BatchState::Poisoned => unreachable!("Poisoned batch"),
             other => {
                 self.state = other;
-                Err(WrongState(format!(
-                    "Download failed for batch in wrong state {:?}",
+                return Err(WrongState(format!(
+                    "Request failed for batch in wrong state {:?}",
                     self.state
-                )))
+                )));
             }
+        };
+        if part >= parts.len() {
+            self.state = BatchState::Downloading(parts);
+            return Err(WrongState(format!(
+                "Request failed for unknown part {}",
+                part
+            )));
         }
+        self.register_failed_download(parts, part, peer);
+        Ok(self.state.is_failed())
     }
 
+    /// Records a failed download of a part and sets the new state of the batch.
+    fn register_failed_download(&mut self, parts: Vec<BatchPart<T>>, index: usize, peer: PeerId) {
+        self.failed_download_attempts[index].push(peer);
+        self.state =
+            if self.failed_download_attempts[index].len() >= MAX_BATCH_DOWNLOAD_ATTEMPTS as usize {
+                BatchState::Failed
+            } else {
+                BatchState::Downloading(parts)
+            };
+    }
+
+    /// Registers a new request for a part of the batch that is awaiting download.
     pub fn start_downloading_from_peer(
         &mut self,
+        part: usize,
         peer: PeerId,
         request_id: RequestId,
     ) -> Result<(), WrongState> {
-        match self.state.poison() {
-            BatchState::AwaitingDownload => {
-                self.state = BatchState::Downloading(peer, Vec::new(), request_id);
-                Ok(())
-            }
+        let mut parts = match self.state.poison() {
+            BatchState::AwaitingDownload => (0..self.num_parts())
+                .map(|_| BatchPart::AwaitingDownload)
+                .collect(),
+            BatchState::Downloading(parts) => parts,
             BatchState::Poisoned => unreachable!("Poisoned batch"),
             other => {
                 self.state = other;
-                Err(WrongState(format!(
+                return Err(WrongState(format!(
                     "Starting download for batch in wrong state {:?}",
                     self.state
-                )))
+                )));
+            }
+        };
+
+        if !matches!(parts.get(part), Some(BatchPart::AwaitingDownload)) {
+            self.state = BatchState::Downloading(parts);
+            return Err(WrongState(format!(
+                "Starting download of part {} for batch in wrong state {:?}",
+                part, self.state
+            )));
+        }
+
+        parts[part] = BatchPart::Downloading {
+            peer,
+            request_id,
+            blocks: Vec::new(),
+            started: Instant::now(),
+        };
+        self.state = BatchState::Downloading(parts);
+        Ok(())
+    }
+
+    /// Abandons the in-progress download of a part so that it can be requested from a different
+    /// peer. Unlike a failed download, this is not counted against the batch.
+    pub fn abandon_part_download(&mut self, part: usize) -> Result<PeerId, WrongState> {
+        if let BatchState::Downloading(parts) = &mut self.state {
+            if let Some(BatchPart::Downloading { .. }) = parts.get(part) {
+                if let BatchPart::Downloading { peer, .. } =
+                    std::mem::replace(&mut parts[part], BatchPart::AwaitingDownload)
+                {
+                    return Ok(peer);
+                }
             }
         }
+        Err(WrongState(format!(
+            "Abandoning download of part {} for batch in wrong state {:?}",
+            part, self.state
+        )))
     }
 
     pub fn start_processing(&mut self) -> Result<Vec<SignedBeaconBlock<T>>, WrongState> {
         match self.state.poison() {
-            BatchState::AwaitingProcessing(peer, blocks) => {
-                self.state = BatchState::Processing(Attempt::new(peer, &blocks));
-                Ok(blocks)
+            BatchState::AwaitingProcessing(parts) => {
+                self.state = BatchState::Processing(Attempt::new(&parts));
+                Ok(parts.into_iter().flat_map(|part| part.blocks).collect())
             }
             BatchState::Poisoned => unreachable!("Poisoned batch"),
             other => {
@@ -344,24 +596,38 @@ impl<T: EthSpec> BatchInfo<T> {
     }
 }
 
-/// Represents a peer's attempt and providing the result for this batch.
+/// Represents the peers' attempt at providing the result for this batch.
 ///
 /// Invalid attempts will downscore a peer.
 #[derive(PartialEq, Debug)]
 pub struct Attempt {
-    /// The peer that made the attempt.
+    /// The peer and hash of the blocks of each part of the attempt.
+    pub parts: Vec<PartAttempt>,
+}
+
+/// A single peer's contribution to an `Attempt`.
+#[derive(PartialEq, Debug)]
+pub struct PartAttempt {
+    /// The peer that sent the part.
     pub peer_id: PeerId,
-    /// The hash of the blocks of the attempt.
+    /// The hash of the blocks of the part.
     pub hash: u64,
 }
 
 impl Attempt {
-    #[allow(clippy::ptr_arg)]
-    fn new<T: EthSpec>(peer_id: PeerId, blocks: &Vec<SignedBeaconBlock<T>>) -> Self {
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        blocks.as_ssz_bytes().hash(&mut hasher);
-        let hash = hasher.finish();
-        Attempt { peer_id, hash }
+    fn new<T: EthSpec>(parts: &[DownloadedPart<T>]) -> Self {
+        let parts = parts
+            .iter()
+            .map(|part| {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                part.blocks.as_ssz_bytes().hash(&mut hasher);
+                PartAttempt {
+                    peer_id: part.peer.clone(),
+                    hash: hasher.finish(),
+                }
+            })
+            .collect();
+        Attempt { parts }
     }
 }
 
@@ -389,7 +655,10 @@ impl<T: EthSpec> slog::KV for BatchInfo<T> {
             "end_slot",
             serializer,
         )?;
-        serializer.emit_usize("downloaded", self.failed_download_attempts.len())?;
+        serializer.emit_usize(
+            "downloaded",
+            self.failed_download_attempts.iter().map(Vec::len).sum(),
+        )?;
         serializer.emit_usize("processed", self.failed_processing_attempts.len())?;
         serializer.emit_arguments("state", &format_args!("{:?}", self.state))?;
         slog::Result::Ok(())
@@ -399,27 +668,119 @@ impl<T: EthSpec> slog::KV for BatchInfo<T> {
 impl<T: EthSpec> std::fmt::Debug for BatchState<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BatchState::Processing(Attempt {
-                ref peer_id,
-                hash: _,
-            }) => write!(f, "Processing({})", peer_id),
-            BatchState::AwaitingValidation(Attempt {
-                ref peer_id,
-                hash: _,
-            }) => write!(f, "AwaitingValidation({})", peer_id),
+            BatchState::Processing(attempt) => {
+                write!(f, "Processing({} parts)", attempt.parts.len())
+            }
+            BatchState::AwaitingValidation(attempt) => {
+                write!(f, "AwaitingValidation({} parts)", attempt.parts.len())
+            }
             BatchState::AwaitingDownload => f.write_str("AwaitingDownload"),
             BatchState::Failed => f.write_str("Failed"),
-            BatchState::AwaitingProcessing(ref peer, ref blocks) => {
-                write!(f, "AwaitingProcessing({}, {} blocks)", peer, blocks.len())
-            }
-            BatchState::Downloading(peer, blocks, request_id) => write!(
+            BatchState::AwaitingProcessing(ref parts) => write!(
                 f,
-                "Downloading({}, {} blocks, {})",
-                peer,
-                blocks.len(),
-                request_id
+                "AwaitingProcessing({} parts, {} blocks)",
+                parts.len(),
+                parts.iter().map(|part| part.blocks.len()).sum::<usize>()
             ),
+            BatchState::Downloading(parts) => {
+                let downloaded = parts
+                    .iter()
+                    .filter(|part| matches!(part, BatchPart::Downloaded(_)))
+                    .count();
+                let downloading = parts
+                    .iter()
+                    .filter(|part| matches!(part, BatchPart::Downloading { .. }))
+                    .count();
+                write!(
+                    f,
+                    "Downloading({}/{} parts downloaded, {} in progress)",
+                    downloaded,
+                    parts.len(),
+                    downloading
+                )
+            }
             BatchState::Poisoned => f.write_str("Poisoned"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MainnetEthSpec;
+
+    type E = MainnetEthSpec;
+
+    fn peer() -> PeerId {
+        PeerId::random()
+    }
+
+    #[test]
+    fn batch_is_split_into_parts() {
+        let batch = BatchInfo::<E>::new(&Epoch::new(0), 2);
+        assert_eq!(batch.num_parts(), 4);
+
+        let request = batch.to_blocks_by_range_request(0);
+        assert_eq!(request.start_slot, 1);
+        assert_eq!(request.count, SLOTS_PER_BATCH_PART);
+
+        let request = batch.to_blocks_by_range_request(3);
+        assert_eq!(request.start_slot, 1 + 3 * SLOTS_PER_BATCH_PART);
+        assert_eq!(request.count, SLOTS_PER_BATCH_PART);
+    }
+
+    #[test]
+    fn batch_completes_when_all_parts_downloaded() {
+        let mut batch = BatchInfo::<E>::new(&Epoch::new(0), 2);
+        let peers = (0..batch.num_parts()).map(|_| peer()).collect::<Vec<_>>();
+
+        for (part, peer) in peers.iter().enumerate() {
+            batch
+                .start_downloading_from_peer(part, peer.clone(), part)
+                .ok()
+                .unwrap();
+        }
+        assert!(batch.parts_awaiting_download().is_empty());
+
+        for (part, peer) in peers.iter().enumerate() {
+            let download = batch.download_completed(peer, &part).ok().unwrap();
+            assert_eq!(download.batch_completed, part == peers.len() - 1);
+        }
+        assert!(matches!(batch.state(), BatchState::AwaitingProcessing(_)));
+    }
+
+    #[test]
+    fn failed_part_is_retried_alone() {
+        let mut batch = BatchInfo::<E>::new(&Epoch::new(0), 2);
+        let (slow, fast) = (peer(), peer());
+
+        batch
+            .start_downloading_from_peer(0, slow.clone(), 0)
+            .ok()
+            .unwrap();
+        batch
+            .start_downloading_from_peer(1, fast.clone(), 1)
+            .ok()
+            .unwrap();
+
+        assert!(!batch.download_failed(&slow, Some(&0)).ok().unwrap());
+        assert_eq!(batch.parts_awaiting_download(), vec![0, 2, 3]);
+        assert!(batch.is_downloading_from(&fast));
+        assert!(!batch.is_downloading_from(&slow));
+        assert!(batch.failed_peers().contains(&slow));
+    }
+
+    #[test]
+    fn abandoned_part_is_not_counted_as_failure() {
+        let mut batch = BatchInfo::<E>::new(&Epoch::new(0), 2);
+        let slow = peer();
+
+        batch
+            .start_downloading_from_peer(0, slow.clone(), 0)
+            .ok()
+            .unwrap();
+        assert_eq!(batch.abandon_part_download(0).ok(), Some(slow.clone()));
+        assert!(!batch.is_expecting_block(&slow, &0));
+        assert!(batch.failed_peers().is_empty());
+    }
+}
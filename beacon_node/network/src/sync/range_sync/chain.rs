@@ -1,14 +1,17 @@
-use super::batch::{BatchInfo, BatchState};
+use super::batch::{BatchInfo, BatchState, PartDownload};
 use crate::beacon_processor::ProcessId;
 use crate::beacon_processor::WorkEvent as BeaconWorkEvent;
+use crate::metrics;
 use crate::sync::{network_context::SyncNetworkContext, BatchProcessResult, RequestId};
 use beacon_chain::BeaconChainTypes;
 use eth2_libp2p::{PeerAction, PeerId};
 use fnv::FnvHashMap;
 use rand::seq::SliceRandom;
 use slog::{crit, debug, o, warn};
-use std::collections::{btree_map::Entry, BTreeMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet};
 use std::hash::{Hash, Hasher};
+use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 use types::{Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
 
@@ -23,6 +26,10 @@ pub const EPOCHS_PER_BATCH: u64 = 2;
 /// The maximum number of batches to queue before requesting more.
 const BATCH_BUFFER_SIZE: u8 = 5;
 
+/// A part of a batch that has been downloading for longer than this is re-requested from an idle
+/// peer. The slow peer is not penalized, its response is simply ignored.
+const SLOW_PART_DOWNLOAD_THRESHOLD: Duration = Duration::from_secs(10);
+
 /// A return type for functions that act on a `Chain` which informs the caller whether the chain
 /// has been completed and should be removed or to be kept if further processing is
 /// required.
@@ -46,6 +53,8 @@ pub struct KeepChain;
 /// A chain identifier
 pub type ChainId = u64;
 pub type BatchId = Epoch;
+/// Identifies a part of a batch, by the batch id and the index of the part within the batch.
+pub type PartId = (BatchId, usize);
 
 /// A chain of blocks that need to be downloaded. Peers who claim to contain the target head
 /// root are grouped into the peer pool and queried for batches when downloading the
@@ -67,9 +76,12 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     batches: BTreeMap<BatchId, BatchInfo<T::EthSpec>>,
 
     /// The peers that agree on the `target_head_slot` and `target_head_root` as a canonical chain
-    /// and thus available to download this chain from, as well as the batches we are currently
-    /// requesting.
-    peers: FnvHashMap<PeerId, HashSet<BatchId>>,
+    /// and thus available to download this chain from, as well as the batch parts we are
+    /// currently requesting.
+    peers: FnvHashMap<PeerId, HashSet<PartId>>,
+
+    /// The amount of data served by each peer of the chain, used to prefer faster peers.
+    download_stats: FnvHashMap<PeerId, PeerDownloadStats>,
 
     /// Starting epoch of the next batch that needs to be downloaded.
     to_be_downloaded: BatchId,
@@ -102,6 +114,27 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     log: slog::Logger,
 }
 
+/// Bandwidth accounting of the batch parts a peer has served.
+#[derive(Default, Debug)]
+struct PeerDownloadStats {
+    /// Total SSZ bytes of the blocks received.
+    bytes: u64,
+    /// Total time spent serving the requests.
+    duration: Duration,
+}
+
+impl PeerDownloadStats {
+    /// The observed throughput of the peer in bytes per second, if it has served any request.
+    fn throughput(&self) -> Option<u64> {
+        let millis = self.duration.as_millis() as u64;
+        if millis == 0 {
+            None
+        } else {
+            Some(self.bytes.saturating_mul(1_000) / millis)
+        }
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub enum ChainSyncingState {
     /// The chain is not being synced.
@@ -138,6 +171,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             target_head_root,
             batches: BTreeMap::new(),
             peers,
+            download_stats: FnvHashMap::default(),
             to_be_downloaded: start_epoch,
             processing_target: start_epoch,
             optimistic_start: None,
@@ -171,17 +205,25 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
     }
 
     /// Removes a peer from the chain.
-    /// If the peer has active batches, those are considered failed and re-requested.
+    /// If the peer has active batch parts, those are considered failed and re-requested.
     pub fn remove_peer(
         &mut self,
         peer_id: &PeerId,
         network: &mut SyncNetworkContext<T::EthSpec>,
     ) -> ProcessingResult {
-        if let Some(batch_ids) = self.peers.remove(peer_id) {
-            // fail the batches
+        self.download_stats.remove(peer_id);
+        if let Some(parts) = self.peers.remove(peer_id) {
+            let batch_ids = parts
+                .into_iter()
+                .map(|(batch_id, _)| batch_id)
+                .collect::<BTreeSet<_>>();
+            // fail the parts of each batch
             for id in batch_ids {
                 if let Some(batch) = self.batches.get_mut(&id) {
-                    if batch.download_failed()? {
+                    if !batch.is_downloading_from(peer_id) {
+                        continue;
+                    }
+                    if batch.download_failed(peer_id, None)? {
                         return Err(RemoveChain::ChainFailed(id));
                     }
                     self.retry_batch_download(network, id)?;
@@ -238,21 +280,27 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
 
         if let Some(block) = beacon_block {
             // This is not a stream termination, simply add the block to the request
-            batch.add_block(block)?;
+            batch.add_block(peer_id, &request_id, block)?;
             Ok(KeepChain)
         } else {
-            // A stream termination has been sent. This batch has ended. Process a completed batch.
-            // Remove the request from the peer's active batches
-            self.peers
-                .get_mut(peer_id)
-                .map(|active_requests| active_requests.remove(&batch_id));
-
-            match batch.download_completed() {
-                Ok(received) => {
-                    let awaiting_batches = batch_id
-                        .saturating_sub(self.optimistic_start.unwrap_or(self.processing_target))
-                        / EPOCHS_PER_BATCH;
-                    debug!(self.log, "Completed batch received"; "epoch" => batch_id, "blocks" => received, "awaiting_batches" => awaiting_batches);
+            // A stream termination has been sent. This part has ended. Process the batch if it is
+            // now complete.
+            // Remove the request from the peer's active parts
+            if let Some(part) = batch.part_for_request(peer_id, &request_id) {
+                self.peers
+                    .get_mut(peer_id)
+                    .map(|active_requests| active_requests.remove(&(batch_id, part)));
+            }
+
+            match batch.download_completed(peer_id, &request_id) {
+                Ok(download) => {
+                    self.register_download(network, &download);
+                    if download.batch_completed {
+                        let awaiting_batches = batch_id.saturating_sub(
+                            self.optimistic_start.unwrap_or(self.processing_target),
+                        ) / EPOCHS_PER_BATCH;
+                        debug!(self.log, "Completed batch received"; "epoch" => batch_id, "awaiting_batches" => awaiting_batches);
+                    }
 
                     // pre-emptively request more blocks from peers whilst we process current blocks,
                     self.request_batches(network)?;
@@ -266,7 +314,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     if is_failed {
                         return Err(RemoveChain::ChainFailed(batch_id));
                     }
-                    // this batch can't be used, so we need to request it again.
+                    // this part can't be used, so we need to request it again.
                     self.retry_batch_download(network, batch_id)
                 }
             }
@@ -495,14 +543,19 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                         batch_id
                     ))
                 })?;
-                let peer = batch.current_peer().cloned().ok_or_else(|| {
-                    RemoveChain::WrongBatchState(format!(
+                let peers = batch.current_peers();
+                if peers.is_empty() {
+                    return Err(RemoveChain::WrongBatchState(format!(
                         "Processing target is in wrong state: {:?}",
                         batch.state(),
-                    ))
-                })?;
+                    )));
+                }
+                let peers = peers
+                    .iter()
+                    .map(|peer| format!("{} ({})", peer, network.client_type(peer)))
+                    .collect::<Vec<_>>();
                 debug!(self.log, "Batch processing failed"; "imported_blocks" => imported_blocks,
-                    "batch_epoch" => batch_id, "peer" => %peer, "client" => %network.client_type(&peer));
+                    "batch_epoch" => batch_id, "peers" => ?peers);
                 if batch.processing_completed(false)? {
                     // check that we have not exceeded the re-process retry counter
                     // If a batch has exceeded the invalid batch lookup attempts limit, it means
@@ -594,12 +647,15 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             // right, and thus, that any different attempt is wrong
             match batch.state() {
                 BatchState::AwaitingValidation(ref processed_attempt) => {
-                    for attempt in batch.attempts() {
-                        // The validated batch has been re-processed
+                    let attempted_parts = batch.attempts().iter().flat_map(|attempt| {
+                        attempt.parts.iter().zip(processed_attempt.parts.iter())
+                    });
+                    for (attempt, processed_attempt) in attempted_parts {
+                        // The validated part has been re-processed
                         if attempt.hash != processed_attempt.hash {
                             // The re-downloaded version was different
                             if processed_attempt.peer_id != attempt.peer_id {
-                                // A different peer sent the correct batch, the previous peer did not
+                                // A different peer sent the correct part, the previous peer did not
                                 // We negatively score the original peer.
                                 let action = PeerAction::LowToleranceError;
                                 debug!(self.log, "Re-processed batch validated. Scoring original peer";
@@ -620,10 +676,12 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                         }
                     }
                 }
-                BatchState::Downloading(peer, ..) => {
-                    // remove this batch from the peer's active requests
-                    if let Some(active_batches) = self.peers.get_mut(peer) {
-                        active_batches.remove(&id);
+                BatchState::Downloading(..) => {
+                    // remove the parts of this batch from the peers' active requests
+                    for (part, peer, _) in batch.downloading_parts() {
+                        if let Some(active_parts) = self.peers.get_mut(&peer) {
+                            active_parts.remove(&(id, part));
+                        }
                     }
                 }
                 BatchState::Failed | BatchState::Poisoned | BatchState::AwaitingDownload => crit!(
@@ -787,14 +845,15 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             // A batch could be retried without the peer failing the request (disconnecting/
             // sending an error /timeout) if the peer is removed from the chain for other
             // reasons. Check that this block belongs to the expected peer
-            if !batch.is_expecting_block(peer_id, &request_id) {
-                return Ok(KeepChain);
-            }
-            debug!(self.log, "Batch failed. RPC Error"; "batch_epoch" => batch_id);
+            let part = match batch.part_for_request(peer_id, &request_id) {
+                Some(part) => part,
+                None => return Ok(KeepChain),
+            };
+            debug!(self.log, "Batch part failed. RPC Error"; "batch_epoch" => batch_id, "part" => part);
             if let Some(active_requests) = self.peers.get_mut(peer_id) {
-                active_requests.remove(&batch_id);
+                active_requests.remove(&(batch_id, part));
             }
-            if batch.download_failed()? {
+            if batch.download_failed(peer_id, Some(&request_id))? {
                 return Err(RemoveChain::ChainFailed(batch_id));
             }
             self.retry_batch_download(network, batch_id)
@@ -804,67 +863,88 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         }
     }
 
-    /// Sends and registers the request of a batch awaiting download.
+    /// Sends and registers the requests of the parts of a batch awaiting download, spreading
+    /// them across the peers of the chain.
     pub fn retry_batch_download(
         &mut self,
         network: &mut SyncNetworkContext<T::EthSpec>,
         batch_id: BatchId,
     ) -> ProcessingResult {
-        let batch = match self.batches.get_mut(&batch_id) {
-            Some(batch) => batch,
-            None => return Ok(KeepChain),
-        };
-
-        // Find a peer to request the batch
-        let failed_peers = batch.failed_peers();
-
-        let new_peer = {
-            let mut priorized_peers = self
-                .peers
-                .iter()
-                .map(|(peer, requests)| (failed_peers.contains(peer), requests.len(), peer))
-                .collect::<Vec<_>>();
-            // Sort peers prioritizing unrelated peers with less active requests.
-            priorized_peers.sort_unstable();
-            priorized_peers.get(0).map(|&(_, _, peer)| peer.clone())
-        };
-
-        if let Some(peer) = new_peer {
-            self.send_batch(network, batch_id, peer)
-        } else {
-            // If we are here the chain has no more peers
-            Err(RemoveChain::EmptyPeerPool)
+        loop {
+            let batch = match self.batches.get(&batch_id) {
+                Some(batch) => batch,
+                None => return Ok(KeepChain),
+            };
+
+            let part = match batch.parts_awaiting_download().first() {
+                Some(part) => *part,
+                None => return Ok(KeepChain),
+            };
+
+            // Find a peer to request the part
+            let failed_peers = batch.failed_peers();
+            if let Some(peer) = self.select_peer(&failed_peers) {
+                self.send_part(network, batch_id, part, peer)?;
+            } else {
+                // If we are here the chain has no more peers
+                return Err(RemoveChain::EmptyPeerPool);
+            }
         }
     }
 
-    /// Requests the batch asigned to the given id from a given peer.
-    pub fn send_batch(
+    /// Selects the peer to request a part from, prioritizing peers unrelated to previous
+    /// failures, with less active requests and higher observed throughput. Peers without a
+    /// throughput measurement are tried before slower, known peers.
+    fn select_peer(&self, failed_peers: &HashSet<PeerId>) -> Option<PeerId> {
+        // randomize the peers to break ties for load balancing
+        let mut peers = self.peers.iter().collect::<Vec<_>>();
+        peers.shuffle(&mut rand::thread_rng());
+        peers
+            .into_iter()
+            .min_by_key(|(peer, requests)| {
+                let throughput = self
+                    .download_stats
+                    .get(peer)
+                    .and_then(PeerDownloadStats::throughput)
+                    .unwrap_or(u64::max_value());
+                (
+                    failed_peers.contains(peer),
+                    requests.len(),
+                    Reverse(throughput),
+                )
+            })
+            .map(|(peer, _)| peer.clone())
+    }
+
+    /// Requests a part of the batch asigned to the given id from a given peer.
+    fn send_part(
         &mut self,
         network: &mut SyncNetworkContext<T::EthSpec>,
         batch_id: BatchId,
+        part: usize,
         peer: PeerId,
     ) -> ProcessingResult {
         if let Some(batch) = self.batches.get_mut(&batch_id) {
-            let request = batch.to_blocks_by_range_request();
+            let request = batch.to_blocks_by_range_request(part);
             match network.blocks_by_range_request(peer.clone(), request, self.id, batch_id) {
                 Ok(request_id) => {
                     // inform the batch about the new request
-                    batch.start_downloading_from_peer(peer.clone(), request_id)?;
+                    batch.start_downloading_from_peer(part, peer.clone(), request_id)?;
                     if self
                         .optimistic_start
                         .map(|epoch| epoch == batch_id)
                         .unwrap_or(false)
                     {
-                        debug!(self.log, "Requesting optimistic batch"; "epoch" => batch_id, &batch);
+                        debug!(self.log, "Requesting optimistic batch part"; "epoch" => batch_id, "part" => part, "peer" => %peer, &batch);
                     } else {
-                        debug!(self.log, "Requesting batch"; "epoch" => batch_id, &batch);
+                        debug!(self.log, "Requesting batch part"; "epoch" => batch_id, "part" => part, "peer" => %peer, &batch);
                     }
-                    // register the batch for this peer
+                    // register the part for this peer
                     return self
                         .peers
                         .get_mut(&peer)
                         .map(|requests| {
-                            requests.insert(batch_id);
+                            requests.insert((batch_id, part));
                             Ok(KeepChain)
                         })
                         .unwrap_or_else(|| {
@@ -877,16 +957,10 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                 Err(e) => {
                     // NOTE: under normal conditions this shouldn't happen but we handle it anyway
                     warn!(self.log, "Could not send batch request";
-                        "batch_id" => batch_id, "error" => e, &batch);
+                        "batch_id" => batch_id, "part" => part, "error" => e, &batch);
                     // register the failed download and check if the batch can be retried
-                    batch.start_downloading_from_peer(peer.clone(), 1)?; // fake request_id is not relevant
-                    self.peers
-                        .get_mut(&peer)
-                        .map(|request| request.remove(&batch_id));
-                    if batch.download_failed()? {
+                    if batch.part_request_failed(part, peer)? {
                         return Err(RemoveChain::ChainFailed(batch_id));
-                    } else {
-                        return self.retry_batch_download(network, batch_id);
                     }
                 }
             }
@@ -895,6 +969,88 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         Ok(KeepChain)
     }
 
+    /// Records the download of a part in the bandwidth accounting of the peer that served it.
+    fn register_download(
+        &mut self,
+        network: &SyncNetworkContext<T::EthSpec>,
+        download: &PartDownload,
+    ) {
+        let stats = self
+            .download_stats
+            .entry(download.peer.clone())
+            .or_default();
+        stats.bytes = stats.bytes.saturating_add(download.bytes as u64);
+        stats.duration += download.duration;
+
+        metrics::observe(
+            &metrics::SYNC_RANGE_PART_DOWNLOAD_TIMES,
+            download.duration.as_secs_f64(),
+        );
+        metrics::inc_counter_vec_by(
+            &metrics::SYNC_RANGE_DOWNLOADED_BYTES,
+            &[&network.client_type(&download.peer).kind.to_string()],
+            download.bytes as i64,
+        );
+        debug!(self.log, "Batch part downloaded"; "peer" => %download.peer,
+            "blocks" => download.blocks, "bytes" => download.bytes,
+            "duration_ms" => download.duration.as_millis() as u64,
+            "peer_throughput" => ?stats.throughput());
+    }
+
+    /// Re-requests parts that have been downloading for longer than `threshold` from idle peers.
+    fn replace_slow_parts(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        threshold: Duration,
+    ) -> ProcessingResult {
+        let mut idle_peers = self
+            .peers
+            .iter()
+            .filter(|(_, requests)| requests.is_empty())
+            .map(|(peer, _)| peer.clone())
+            .collect::<Vec<_>>();
+        if idle_peers.is_empty() {
+            return Ok(KeepChain);
+        }
+        idle_peers.shuffle(&mut rand::thread_rng());
+
+        let slow_parts = self
+            .batches
+            .iter()
+            .flat_map(|(id, batch)| {
+                batch
+                    .downloading_parts()
+                    .into_iter()
+                    .filter(|(_, _, elapsed)| *elapsed > threshold)
+                    .map(move |(part, peer, _)| (*id, part, peer))
+            })
+            .collect::<Vec<_>>();
+
+        for (batch_id, part, slow_peer) in slow_parts {
+            let new_peer = match idle_peers.pop() {
+                Some(peer) => peer,
+                None => break,
+            };
+            if let Some(batch) = self.batches.get_mut(&batch_id) {
+                batch.abandon_part_download(part)?;
+            }
+            if let Some(active_requests) = self.peers.get_mut(&slow_peer) {
+                active_requests.remove(&(batch_id, part));
+            }
+            metrics::inc_counter(&metrics::SYNC_RANGE_SLOW_PARTS_REPLACED);
+            debug!(self.log, "Replacing slow peer for batch part"; "epoch" => batch_id,
+                "part" => part, "slow_peer" => %slow_peer, "new_peer" => %new_peer);
+            if self.peers.contains_key(&new_peer) {
+                self.send_part(network, batch_id, part, new_peer)?;
+            }
+            // If the part could not be sent to the new peer it is awaiting download again, so
+            // request it from any peer of the chain.
+            self.retry_batch_download(network, batch_id)?;
+        }
+
+        Ok(KeepChain)
+    }
+
     /// Returns true if this chain is currently syncing.
     pub fn is_syncing(&self) -> bool {
         match self.state {
@@ -913,40 +1069,29 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             return Ok(KeepChain);
         }
 
-        // find the next pending batch and request it from the peer
+        // parts that are taking too long are requested again before any new batch
+        self.replace_slow_parts(network, SLOW_PART_DOWNLOAD_THRESHOLD)?;
 
-        // randomize the peers for load balancing
-        let mut rng = rand::thread_rng();
-        let mut idle_peers = self
-            .peers
-            .iter()
-            .filter_map(|(peer, requests)| {
-                if requests.is_empty() {
-                    Some(peer.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        idle_peers.shuffle(&mut rng);
+        let has_idle_peers = |peers: &FnvHashMap<PeerId, HashSet<PartId>>| {
+            peers.values().any(|requests| requests.is_empty())
+        };
 
         // check if we have the batch for our optimistic start. If not, request it first.
         // We wait for this batch before requesting any other batches.
         if let Some(epoch) = self.optimistic_start {
-            if !self.batches.contains_key(&epoch) {
-                if let Some(peer) = idle_peers.pop() {
-                    let optimistic_batch = BatchInfo::new(&epoch, EPOCHS_PER_BATCH);
-                    self.batches.insert(epoch, optimistic_batch);
-                    self.send_batch(network, epoch, peer)?;
-                }
+            if !self.batches.contains_key(&epoch) && has_idle_peers(&self.peers) {
+                let optimistic_batch = BatchInfo::new(&epoch, EPOCHS_PER_BATCH);
+                self.batches.insert(epoch, optimistic_batch);
+                self.retry_batch_download(network, epoch)?;
             }
             return Ok(KeepChain);
         }
 
-        while let Some(peer) = idle_peers.pop() {
+        // find the next pending batch and split it across the peers while some are idle
+        while has_idle_peers(&self.peers) {
             if let Some(batch_id) = self.include_next_batch() {
-                // send the batch
-                self.send_batch(network, batch_id, peer)?;
+                // send the parts of the batch
+                self.retry_batch_download(network, batch_id)?;
             } else {
                 // No more batches, simply stop
                 return Ok(KeepChain);
@@ -1058,3 +1203,89 @@ impl RemoveChain {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::{
+        builder::Witness, eth1_chain::CachingEth1Backend, events::NullEventHandler,
+    };
+    use eth2_libp2p::discv5::enr::{CombinedKey, EnrBuilder};
+    use eth2_libp2p::{rpc::methods::MetaData, types::EnrBitfield, NetworkGlobals};
+    use slog::Logger;
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use slot_clock::SystemTimeSlotClock;
+    use std::sync::Arc;
+    use store::MemoryStore;
+    use tokio::sync::mpsc;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    type TestBeaconChainType = Witness<
+        SystemTimeSlotClock,
+        CachingEth1Backend<E>,
+        E,
+        NullEventHandler<E>,
+        MemoryStore<E>,
+        MemoryStore<E>,
+    >;
+
+    fn get_logger() -> Logger {
+        NullLoggerBuilder.build().expect("logger should build")
+    }
+
+    /// A network context whose requests fail, as the network service has stopped.
+    fn failing_network(log: &Logger) -> SyncNetworkContext<E> {
+        let meta_data = MetaData {
+            seq_number: 0,
+            attnets: EnrBitfield::<E>::default(),
+        };
+        let enr = EnrBuilder::new("v4")
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        let globals = NetworkGlobals::new(enr, 9000, 9000, meta_data, vec![], log);
+        let (network_tx, _) = mpsc::unbounded_channel();
+        SyncNetworkContext::new(network_tx, Arc::new(globals), log.clone())
+    }
+
+    #[test]
+    fn failed_replacement_of_slow_part_is_retried() {
+        let log = get_logger();
+        let mut network = failing_network(&log);
+        let (beacon_processor_send, _beacon_processor_recv) = mpsc::channel(1);
+        let (slow_peer, idle_peer) = (PeerId::random(), PeerId::random());
+        let batch_id = Epoch::new(0);
+
+        let mut chain = SyncingChain::<TestBeaconChainType>::new(
+            batch_id,
+            Slot::new(1000),
+            Hash256::zero(),
+            slow_peer.clone(),
+            beacon_processor_send,
+            &log,
+        );
+        chain.peers.insert(idle_peer, Default::default());
+
+        // Every part of the batch is being downloaded from the slow peer.
+        let mut batch = BatchInfo::<E>::new(&batch_id, EPOCHS_PER_BATCH);
+        for part in 0..batch.num_parts() {
+            batch
+                .start_downloading_from_peer(part, slow_peer.clone(), part)
+                .ok()
+                .unwrap();
+            chain
+                .peers
+                .get_mut(&slow_peer)
+                .unwrap()
+                .insert((batch_id, part));
+        }
+        chain.batches.insert(batch_id, batch);
+        std::thread::sleep(Duration::from_millis(1));
+
+        // The request to the idle peer fails, so the part is re-requested from the peers of the
+        // chain until the batch runs out of attempts, rather than being left awaiting download.
+        let result = chain.replace_slow_parts(&mut network, Duration::from_secs(0));
+        assert!(matches!(result, Err(RemoveChain::ChainFailed(id)) if id == batch_id));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::max_cover::MaxCover;
 use state_processing::common::{get_attesting_indices, get_base_reward};
 use std::collections::HashMap;
-use types::{Attestation, BeaconState, BitList, ChainSpec, EthSpec};
+use types::{Attestation, BeaconState, BitList, ChainSpec, EthSpec, Slot};
 
+#[derive(Clone)]
 pub struct AttMaxCover<'a, T: EthSpec> {
     /// Underlying attestation.
     att: &'a Attestation<T>,
@@ -38,6 +39,14 @@ impl<'a, T: EthSpec> AttMaxCover<'a, T> {
             fresh_validators_rewards,
         })
     }
+
+    /// The slot and index of the attestation's committee.
+    ///
+    /// Including an attestation in a solution only affects the covering sets of attestations from
+    /// the same committee (see `update_covering_set`).
+    pub fn committee_key(&self) -> (Slot, u64) {
+        (self.att.data.slot, self.att.data.index)
+    }
 }
 
 impl<'a, T: EthSpec> MaxCover for AttMaxCover<'a, T> {
@@ -9,7 +9,7 @@ pub use persistence::PersistedOperationPool;
 use attestation::AttMaxCover;
 use attestation_id::AttestationId;
 use attester_slashing::AttesterSlashingMaxCover;
-use max_cover::maximum_cover;
+use max_cover::{maximum_cover, maximum_cover_grouped};
 use parking_lot::RwLock;
 use state_processing::per_block_processing::errors::AttestationValidationError;
 use state_processing::per_block_processing::{
@@ -20,11 +20,16 @@ use state_processing::SigVerifiedOp;
 use std::collections::{hash_map, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::ptr;
+use std::time::{Duration, Instant};
 use types::{
     typenum::Unsigned, Attestation, AttesterSlashing, BeaconState, BeaconStateError, ChainSpec,
     Epoch, EthSpec, Fork, ForkVersion, Hash256, ProposerSlashing, RelativeEpoch,
     SignedVoluntaryExit, Validator,
 };
+/// The time allowed for searching for better attestation packings than the greedy one, during
+/// block production.
+pub const ATTESTATION_PACKING_TIME_BUDGET: Duration = Duration::from_millis(50);
+
 #[derive(Default, Debug)]
 pub struct OperationPool<T: EthSpec + Default> {
     /// Map from attestation ID (see below) to vectors of attestations.
@@ -102,12 +107,17 @@ impl<T: EthSpec> OperationPool<T> {
     /// before an approximately optimal bundle is constructed. We use it to provide access
     /// to the fork choice data from the `BeaconChain` struct that doesn't logically belong
     /// in the operation pool.
+    ///
+    /// The bundle is built by `maximum_cover_grouped`, which spends roughly
+    /// `ATTESTATION_PACKING_TIME_BUDGET` searching for better packings than the greedy one.
     pub fn get_attestations(
         &self,
         state: &BeaconState<T>,
         validity_filter: impl FnMut(&&Attestation<T>) -> bool,
         spec: &ChainSpec,
     ) -> Result<Vec<Attestation<T>>, OpPoolError> {
+        let deadline = Instant::now() + ATTESTATION_PACKING_TIME_BUDGET;
+
         // Attestations for the current fork, which may be from the current or previous epoch.
         let prev_epoch = state.previous_epoch();
         let current_epoch = state.current_epoch();
@@ -150,9 +160,11 @@ impl<T: EthSpec> OperationPool<T> {
             .filter(validity_filter)
             .flat_map(|att| AttMaxCover::new(att, state, total_active_balance, spec));
 
-        Ok(maximum_cover(
+        Ok(maximum_cover_grouped(
             valid_attestations,
             T::MaxAttestations::to_usize(),
+            AttMaxCover::committee_key,
+            deadline,
         ))
     }
 
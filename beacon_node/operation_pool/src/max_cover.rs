@@ -1,3 +1,11 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+
+/// Groups with at most this many items have their optimal covers found by exhaustive search.
+/// Larger groups use the greedy covers.
+pub const EXACT_COVER_MAX_GROUP_SIZE: usize = 8;
+
 /// Trait for types that we can compute a maximum cover for.
 ///
 /// Terminology:
@@ -83,11 +91,174 @@ where
     result
 }
 
+/// Compute a maximum cover of items which are partitioned into groups, such that including an
+/// item in the solution only affects the covering sets of items in the same group.
+///
+/// For each group and each number of items to take from it, the best cover is found, either by
+/// exhaustive search (for groups of up to `EXACT_COVER_MAX_GROUP_SIZE` items, until `deadline`
+/// passes) or greedily. The number of items taken from each group is then chosen optimally, so
+/// the result is never worse than that of `maximum_cover`, and is optimal if the deadline isn't
+/// reached. The objects are returned in greedy order, i.e. in order of decreasing marginal score.
+///
+/// * Time complexity: `O(limit * items_iter.len() + groups * limit^2)`, plus `O(2^group_size)`
+///   per exhaustively searched group
+/// * Space complexity: `O(groups * limit + item_iter.len())`
+pub fn maximum_cover_grouped<I, T, K, F>(
+    items_iter: I,
+    limit: usize,
+    group_key: F,
+    deadline: Instant,
+) -> Vec<T::Object>
+where
+    I: IntoIterator<Item = T>,
+    T: MaxCover + Clone,
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    // Partition the items into groups, preserving the order in which the groups are first seen.
+    let mut group_indices = HashMap::new();
+    let mut groups: Vec<Vec<T>> = vec![];
+    for item in items_iter.into_iter().filter(|x| x.score() != 0) {
+        let index = *group_indices.entry(group_key(&item)).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[index].push(item);
+    }
+
+    // The best cover of each size for each group, as `covers[group][size] = (score, items)`.
+    let covers = groups
+        .iter()
+        .map(|group| {
+            let mut covers = greedy_covers(group, limit);
+            if group.len() <= EXACT_COVER_MAX_GROUP_SIZE && Instant::now() < deadline {
+                improve_covers_exhaustively(group, &mut covers);
+            }
+            covers
+        })
+        .collect::<Vec<_>>();
+
+    // Choose how many items to take from each group, maximising the total score subject to the
+    // limit. `best[j]` is the best total score using at most `j` items from the groups so far, and
+    // `choices[g][j]` is the number of items taken from group `g` to achieve `best[j]`.
+    let mut best = vec![0; limit + 1];
+    let mut choices = Vec::with_capacity(covers.len());
+    for group_covers in &covers {
+        let mut next_best = best.clone();
+        let mut group_choices = vec![0; limit + 1];
+        for j in 0..=limit {
+            for (size, (score, _)) in group_covers.iter().enumerate().take(j + 1).skip(1) {
+                let total = best[j - size] + score;
+                if total > next_best[j] {
+                    next_best[j] = total;
+                    group_choices[j] = size;
+                }
+            }
+        }
+        best = next_best;
+        choices.push(group_choices);
+    }
+
+    // Walk back through the choices to find the selected items.
+    let mut selected = vec![];
+    let mut remaining = limit;
+    for (group_index, group_choices) in choices.iter().enumerate().rev() {
+        let size = group_choices[remaining];
+        if size > 0 {
+            let (_, items) = &covers[group_index][size];
+            selected.extend(
+                items
+                    .iter()
+                    .map(|&item_index| groups[group_index][item_index].clone()),
+            );
+            remaining -= size;
+        }
+    }
+
+    maximum_cover(selected, limit)
+}
+
+/// Return the greedy cover of each size for the items of a group, as `covers[size] = (score,
+/// items)`, up to the size at which the greedy algorithm runs out of items with non-zero score.
+fn greedy_covers<T: MaxCover + Clone>(group: &[T], limit: usize) -> Vec<(usize, Vec<usize>)> {
+    let mut items = group
+        .iter()
+        .cloned()
+        .map(MaxCoverItem::new)
+        .collect::<Vec<_>>();
+    let mut covers = vec![(0, vec![])];
+
+    for _ in 0..limit {
+        let (index, score) = match items
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.available && x.item.score() != 0)
+            .max_by_key(|(_, x)| x.item.score())
+        {
+            Some((index, x)) => (index, x.item.score()),
+            None => break,
+        };
+        items[index].available = false;
+        let best_item = items[index].item.object();
+        let best_cover = items[index].item.covering_set().clone();
+
+        items
+            .iter_mut()
+            .filter(|x| x.available && x.item.score() != 0)
+            .for_each(|x| x.item.update_covering_set(&best_item, &best_cover));
+
+        let (previous_score, previous_items) = &covers[covers.len() - 1];
+        let mut cover_items = previous_items.clone();
+        cover_items.push(index);
+        covers.push((previous_score + score, cover_items));
+    }
+
+    covers
+}
+
+/// Replace the covers of a group with the best covers of each size, found by scoring every subset
+/// of its items.
+fn improve_covers_exhaustively<T: MaxCover + Clone>(
+    group: &[T],
+    covers: &mut [(usize, Vec<usize>)],
+) {
+    let objects = group.iter().map(MaxCover::object).collect::<Vec<_>>();
+
+    for subset in 1_usize..(1 << group.len()) {
+        let size = subset.count_ones() as usize;
+        if size >= covers.len() {
+            // Either the limit is reached, or the largest greedy cover already covers every
+            // element, in which case it's optimal for all larger sizes too.
+            continue;
+        }
+
+        let items = (0..group.len())
+            .filter(|i| subset & (1 << i) != 0)
+            .collect::<Vec<_>>();
+        let score = items
+            .iter()
+            .enumerate()
+            .map(|(position, &i)| {
+                let mut item = group[i].clone();
+                for &j in &items[..position] {
+                    item.update_covering_set(&objects[j], group[j].covering_set());
+                }
+                item.score()
+            })
+            .sum();
+
+        if score > covers[size].0 {
+            covers[size] = (score, items);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::collections::HashSet;
     use std::iter::FromIterator;
-    use std::{collections::HashSet, hash::Hash};
+    use std::time::Duration;
 
     impl<T> MaxCover for HashSet<T>
     where
@@ -186,4 +357,59 @@ mod test {
         assert_eq!(quality(&cover), 19);
         assert_eq!(cover.len(), 5);
     }
+
+    fn suboptimal_sets() -> Vec<HashSet<usize>> {
+        vec![
+            HashSet::from_iter(vec![0, 1, 8, 11, 14]),
+            HashSet::from_iter(vec![2, 3, 7, 9, 10]),
+            HashSet::from_iter(vec![4, 5, 6, 12, 13]),
+            HashSet::from_iter(vec![9, 10]),
+            HashSet::from_iter(vec![5, 6, 7, 8]),
+            HashSet::from_iter(vec![0, 1, 2, 3, 4]),
+        ]
+    }
+
+    fn far_future() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    // Unlike the greedy algorithm, the exhaustive search finds the optimal solution.
+    #[test]
+    fn grouped_optimal() {
+        let cover = maximum_cover_grouped(suboptimal_sets(), 3, |_| (), far_future());
+        assert_eq!(quality(&cover), 15);
+        assert_eq!(cover.len(), 3);
+    }
+
+    // Once the deadline has passed, the greedy solution is used.
+    #[test]
+    fn grouped_deadline_passed() {
+        let cover = maximum_cover_grouped(suboptimal_sets(), 3, |_| (), Instant::now());
+        assert_eq!(quality(&cover), 11);
+    }
+
+    // The limit is shared optimally between the groups, and the solution is in greedy order.
+    #[test]
+    fn grouped_limit_shared() {
+        let mut sets = suboptimal_sets();
+        sets.push(HashSet::from_iter(vec![100, 101, 102, 103, 104]));
+        sets.push(HashSet::from_iter(vec![105, 106, 107, 108]));
+
+        assert_eq!(quality(&maximum_cover(sets.clone(), 4)), 18);
+
+        let cover = maximum_cover_grouped(sets, 4, |set| set.contains(&100), far_future());
+        assert_eq!(quality(&cover), 20);
+        assert_eq!(cover.len(), 4);
+        assert!(cover.windows(2).all(|w| w[0].len() >= w[1].len()));
+    }
+
+    #[test]
+    fn grouped_exclude_zero_score() {
+        let sets = example_system();
+        for k in 2..10 {
+            let cover = maximum_cover_grouped(sets.clone(), k, |_| (), far_future());
+            assert_eq!(cover.len(), 2);
+            assert_eq!(quality(&cover), 5);
+        }
+    }
 }
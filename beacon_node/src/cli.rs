@@ -45,6 +45,17 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        --subscribe-all-subnets to ensure all attestations are received for import.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("proposer-only")
+                .long("proposer-only")
+                .help("Run the beacon node in a minimal configuration that is only sufficient for \
+                       publishing blocks. No attestation subnets are joined, peers which aren't at \
+                       our head are pruned first, and block ranges are not served to peers. \
+                       Intended for a dedicated beacon node used only for block proposals.")
+                .conflicts_with("subscribe-all-subnets")
+                .conflicts_with("import-all-attestations")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("zero-ports")
                 .long("zero-ports")
@@ -118,6 +129,31 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     The --port flag is used if this is not set.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("enr-udp6-port")
+                .long("enr-udp6-port")
+                .value_name("PORT")
+                .help("The IPv6 UDP port of the local ENR. Set this only if you are sure other nodes can connect to your local node on this port.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("enr-tcp6-port")
+                .long("enr-tcp6-port")
+                .value_name("PORT")
+                .help("The IPv6 TCP port of the local ENR. Set this only if you are sure other nodes can connect to your local node on this port.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("enr-field")
+                .long("enr-field")
+                .value_name("KEY=VALUE")
+                .help("Adds a custom key-value pair to the local ENR. Values prefixed with 0x are \
+                decoded as hex, all other values are used as UTF-8 bytes. Keys used by Lighthouse or \
+                discovery (e.g. ip, udp, eth2, attnets) cannot be overridden. May be supplied multiple times.")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("enr-address")
                 .long("enr-address")
@@ -159,6 +195,49 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Disables the discv5 discovery protocol. The node will not search for new peers or participate in the discovery protocol.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("socks5-proxy")
+                .long("socks5-proxy")
+                .value_name("ADDRESS:PORT")
+                .help("Dials all outbound libp2p connections through the given SOCKS5 proxy (e.g. a local Tor \
+                daemon at 127.0.0.1:9050). Discovery and UPnP are disabled when this is set as they cannot \
+                be tunnelled through the proxy, so peers must be supplied via --libp2p-addresses.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("peer-allow-cidr")
+                .long("peer-allow-cidr")
+                .value_name("CIDRS")
+                .help("One or more comma-delimited IP ranges (e.g. 10.0.0.0/8,2001:db8::/32). If set, \
+                inbound and outbound libp2p connections are only permitted to and from these ranges.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("peer-deny-cidr")
+                .long("peer-deny-cidr")
+                .value_name("CIDRS")
+                .help("One or more comma-delimited IP ranges (e.g. 192.0.2.0/24). Inbound and outbound \
+                libp2p connections to and from these ranges are refused. Takes precedence over --peer-allow-cidr.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-peers-per-ip")
+                .long("max-peers-per-ip")
+                .value_name("COUNT")
+                .help("The maximum number of connected peers that may share a single IP address. \
+                Set to 0 to disable the limit.")
+                .default_value("5")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-peers-per-subnet")
+                .long("max-peers-per-subnet")
+                .value_name("COUNT")
+                .help("The maximum number of connected peers that may share a single /24 (IPv4) or \
+                /64 (IPv6) subnet. Set to 0 to disable the limit.")
+                .default_value("10")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("trusted-peers")
                 .long("trusted-peers")
@@ -199,6 +278,21 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     address of this server (e.g., http://localhost:5052).")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-allow-database-backup")
+                .long("http-allow-database-backup")
+                .help("Allow the lighthouse/database/backup endpoint (used by `lighthouse db \
+                    backup`) to write a copy of the database to a path on this machine. \
+                    Disabled by default.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("http-allow-slasher-control")
+                .long("http-allow-slasher-control")
+                .help("Allow the lighthouse/slasher/pause and lighthouse/slasher/resume endpoints \
+                    to suspend and restart the slasher's processing. Disabled by default.")
+                .takes_value(false),
+        )
         /* Prometheus metrics HTTP server related arguments */
         .arg(
             Arg::with_name("metrics")
@@ -329,6 +423,124 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many blocks the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("historic-state-cache-size")
+                .long("historic-state-cache-size")
+                .value_name("SIZE")
+                .help("Specifies how many freezer restore points the database should cache in \
+                       memory, to speed up loading nearby historic states [default: 4]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("freezer-migration-batch-size")
+                .long("freezer-migration-batch-size")
+                .value_name("NUM_STATES")
+                .help("Specifies how many states are written to or deleted from the database in \
+                       each batch when moving finalized states to the freezer database. Smaller \
+                       batches interfere less with block import [default: 512]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("snapshot-cache-size")
+                .long("snapshot-cache-size")
+                .value_name("SIZE")
+                .help("Specifies how many recent block post-states should be kept in memory for \
+                       block processing. Larger values use more memory but reduce the number of \
+                       states loaded from the database when processing forks [default: 4]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("hierarchy-exponents")
+                .long("hierarchy-exponents")
+                .value_name("EXPONENTS")
+                .help("Comma-separated list of the powers of two at which freezer restore points \
+                       are layered. Only the last layer stores full states, and the other \
+                       restore points are stored as diffs against the layer above. Only applies \
+                       when the database is created. [default: 13,16,18,21]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("freezer-compression")
+                .long("freezer-compression")
+                .value_name("CODEC")
+                .help("Set the compression of the freezer database. Only applies when the \
+                       database is created, existing databases keep their original compression. \
+                       [default: none]")
+                .possible_values(&["none", "snappy", "zstd"])
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("freezer-compression-level")
+                .long("freezer-compression-level")
+                .value_name("LEVEL")
+                .help("Set the level of zstd freezer compression, from 1 (fastest) to 22 \
+                       (smallest). [default: 3]")
+                .requires("freezer-compression")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("prune-historic-states")
+                .long("prune-historic-states")
+                .help("Delete the historic states stored in the freezer database, except genesis, \
+                       and stop storing new ones. Block and state roots are kept. Can be enabled \
+                       or disabled on any restart, states pruned while enabled are only available \
+                       again after --reconstruct-historic-states.")
+                .conflicts_with("reconstruct-historic-states")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("reconstruct-historic-states")
+                .long("reconstruct-historic-states")
+                .help("Reconstruct historic states which were deleted by \
+                       --prune-historic-states, by replaying blocks in the background.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("beacon-node-backend")
+                .long("beacon-node-backend")
+                .value_name("BACKEND")
+                .help("Set the key-value store used for the hot and freezer databases. An existing \
+                       database written by a different backend will not be opened unless \
+                       --migrate-db-backend is also supplied. The redb backend is only available \
+                       if Lighthouse was built with the redb feature.")
+                .possible_values(&["leveldb", "redb"])
+                .default_value("leveldb")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("migrate-db-backend")
+                .long("migrate-db-backend")
+                .help("If present, convert existing databases to the backend selected by \
+                       --beacon-node-backend on start-up. Requires enough free disk space for a \
+                       second copy of each database.")
+        )
+        .arg(
+            Arg::with_name("ephemeral-db")
+                .long("ephemeral-db")
+                .help("If present, keep the hot and freezer databases in memory instead of on \
+                       disk, overriding --beacon-node-backend. All chain data is lost when the \
+                       beacon node stops, so this is only suitable for tests and short-lived \
+                       local devnets. Use a --datadir which isn't shared with a persistent node.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("db-read-only")
+                .long("db-read-only")
+                .help("If present, open the existing hot and freezer databases without write \
+                       access and serve them over the HTTP API, without networking, eth1 or \
+                       block import. The database must not be in use by another beacon node, \
+                       so point this at a copy of a live node's datadir.")
+                .conflicts_with_all(&["ephemeral-db", "purge-db", "migrate-db-backend", "compact-db"])
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("disable-disk-space-check")
+                .long("disable-disk-space-check")
+                .help("If present, start even if the filesystems holding the databases appear to \
+                       lack the space needed to sync the chain or migrate the database schema, \
+                       logging a critical warning instead of refusing to start.")
+                .takes_value(false)
+        )
 
         /*
          * Database purging and compaction.
@@ -424,7 +636,9 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("slasher-max-db-size")
                 .long("slasher-max-db-size")
                 .help(
-                    "Maximum size of the LMDB database used by the slasher."
+                    "Maximum size of the database used by the slasher. If it is reached, the \
+                     slasher prunes its oldest epochs, keeping less history than \
+                     --slasher-history-length."
                 )
                 .value_name("GIGABYTES")
                 .requires("slasher")
@@ -450,6 +664,94 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .requires("slasher")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("slasher-backend")
+                .long("slasher-backend")
+                .help(
+                    "Set the key-value store used by the slasher's database. LMDB memory-maps \
+                     its database, while redb uses ordinary file I/O. An existing database must be deleted to change its \
+                     backend. The redb backend is only available if Lighthouse was built with the \
+                     redb feature. [default: lmdb]"
+                )
+                .value_name("BACKEND")
+                .possible_values(&["lmdb", "redb"])
+                .requires("slasher")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("slasher-memory-budget")
+                .long("slasher-memory-budget")
+                .help(
+                    "Limit the memory used by the slasher's queues of attestations and block \
+                     headers, in megabytes. Attestations beyond the limit of each batch are \
+                     deferred to the next batch, and once the queues are full new attestations \
+                     are dropped. [default: unlimited]"
+                )
+                .value_name("MEGABYTES")
+                .requires("slasher")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("slasher-backfill-epoch")
+                .long("slasher-backfill-epoch")
+                .help(
+                    "Supply the slasher with the blocks and attestations stored in the database \
+                     from this epoch onwards when it starts, so that it can detect offences from \
+                     before it was enabled. Epochs older than the slasher's history length are \
+                     skipped."
+                )
+                .value_name("EPOCH")
+                .requires("slasher")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("slasher-broadcast")
+                .long("slasher-broadcast")
+                .help(
+                    "Publish the slashings found by the slasher on gossip and add them to the op \
+                     pool as soon as they are found, rather than waiting until this node proposes \
+                     a block. [default: true]"
+                )
+                .value_name("BOOLEAN")
+                .possible_values(&["true", "false"])
+                .requires("slasher")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("slashing-webhook")
+                .long("slashing-webhook")
+                .help(
+                    "POST a JSON notification to this URL for each slashing added to the op pool, \
+                     whether it was found by the slasher, received on gossip or submitted to the \
+                     HTTP API. Failed notifications are retried."
+                )
+                .value_name("URL")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("slashing-webhook-validators")
+                .long("slashing-webhook-validators")
+                .help(
+                    "Only notify the slashing webhook of slashings which include one of these \
+                     validators. [default: all validators]"
+                )
+                .value_name("INDICES")
+                .use_delimiter(true)
+                .requires("slashing-webhook")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("monitored-validators")
+                .long("monitored-validators")
+                .help(
+                    "The public keys of the operator's own validators. A critical error is logged \
+                     if any of them appears in a slashing on gossip or in a block, so that the \
+                     operator can stop them before they are slashed again."
+                )
+                .value_name("PUBKEYS")
+                .use_delimiter(true)
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("wss-checkpoint")
                 .long("wss-checkpoint")
@@ -460,4 +762,49 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("WSS_CHECKPOINT")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("checkpoint-sync-url")
+                .long("checkpoint-sync-url")
+                .help(
+                    "Start syncing from the latest finalized checkpoint of the beacon node API at \
+                     this URL, rather than from genesis. The finalized state and block are \
+                     downloaded at startup. Only used when the database is empty."
+                )
+                .value_name("BEACON_NODE")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("checkpoint-state")
+                .long("checkpoint-state")
+                .help(
+                    "Start syncing from the finalized checkpoint state in this SSZ file, rather \
+                     than from genesis. Requires --checkpoint-block. Only used when the database \
+                     is empty."
+                )
+                .value_name("STATE_SSZ")
+                .requires("checkpoint-block")
+                .conflicts_with("checkpoint-sync-url")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("checkpoint-block")
+                .long("checkpoint-block")
+                .help(
+                    "The block of the checkpoint state given by --checkpoint-state, as an SSZ file."
+                )
+                .value_name("BLOCK_SSZ")
+                .requires("checkpoint-state")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("allow-insecure-genesis-sync")
+                .long("allow-insecure-genesis-sync")
+                .help(
+                    "Permit syncing from genesis when starting with an empty database. On \
+                     mainnet this is refused by default, since genesis sync is slow and may \
+                     follow a long-range attack chain; use --checkpoint-sync-url instead."
+                )
+                .conflicts_with_all(&["checkpoint-sync-url", "checkpoint-state"])
+                .takes_value(false)
+        )
 }
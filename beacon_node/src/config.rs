@@ -1,18 +1,22 @@
 use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
+use beacon_chain::slashing_webhook::Config as SlashingWebhookConfig;
+use beacon_chain::store::{Compression, DatabaseBackend};
 use clap::ArgMatches;
 use clap_utils::BAD_TESTNET_DIR_MESSAGE;
 use client::{ClientConfig, ClientGenesis};
 use directory::{DEFAULT_BEACON_NODE_DIR, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR};
+use eth2_libp2p::discovery::RESERVED_ENR_KEYS;
+use eth2_libp2p::ip_filter::IpNet;
 use eth2_libp2p::{multiaddr::Protocol, Enr, Multiaddr, NetworkConfig, PeerIdSerialized};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{info, warn, Logger};
 use std::cmp;
 use std::cmp::max;
 use std::fs;
-use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
 use std::net::{TcpListener, UdpSocket};
-use std::path::PathBuf;
-use types::{ChainSpec, Checkpoint, Epoch, EthSpec, Hash256, GRAFFITI_BYTES_LEN};
+use std::path::{Path, PathBuf};
+use types::{ChainSpec, Checkpoint, Epoch, EthSpec, Hash256, PublicKeyBytes, GRAFFITI_BYTES_LEN};
 
 /// Gets the fully-initialized global client.
 ///
@@ -116,6 +120,11 @@ pub fn get_config<E: EthSpec>(
         client_config.http_api.allow_origin = Some(allow_origin.to_string());
     }
 
+    client_config.http_api.allow_database_backup =
+        cli_args.is_present("http-allow-database-backup");
+    client_config.http_api.allow_slasher_control =
+        cli_args.is_present("http-allow-slasher-control");
+
     /*
      * Prometheus metrics HTTP server
      */
@@ -228,6 +237,58 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(historic_state_cache_size) = cli_args.value_of("historic-state-cache-size") {
+        client_config.store.historic_state_cache_size = historic_state_cache_size
+            .parse()
+            .map_err(|_| "historic-state-cache-size is not a valid integer".to_string())?;
+    }
+
+    if let Some(migration_batch_size) = cli_args.value_of("freezer-migration-batch-size") {
+        client_config.store.migration_batch_size = migration_batch_size
+            .parse()
+            .map_err(|_| "freezer-migration-batch-size is not a valid integer".to_string())?;
+    }
+
+    if let Some(snapshot_cache_size) = cli_args.value_of("snapshot-cache-size") {
+        client_config.chain.snapshot_cache_size = snapshot_cache_size
+            .parse()
+            .map_err(|_| "snapshot-cache-size is not a valid integer".to_string())?;
+    }
+
+    if let Some(pubkeys) = cli_args.values_of("monitored-validators") {
+        client_config.chain.monitored_validators = pubkeys
+            .map(|pubkey| {
+                pubkey
+                    .parse()
+                    .map_err(|_| format!("Invalid monitored validator pubkey: {}", pubkey))
+            })
+            .collect::<Result<Vec<PublicKeyBytes>, _>>()?;
+    }
+
+    if let Some(hierarchy) = cli_args.value_of("hierarchy-exponents") {
+        client_config.store.hierarchy = Some(hierarchy.parse()?);
+    }
+
+    if let Some(backend) = cli_args.value_of("beacon-node-backend") {
+        client_config.store.backend = backend.parse()?;
+    }
+    if cli_args.is_present("ephemeral-db") {
+        client_config.store.backend = DatabaseBackend::Memory;
+    }
+    client_config.store.migrate_backend = cli_args.is_present("migrate-db-backend");
+    client_config.store.read_only = cli_args.is_present("db-read-only");
+    client_config.store.check_disk_space = !cli_args.is_present("disable-disk-space-check");
+
+    if let Some(codec) = cli_args.value_of("freezer-compression") {
+        let level = clap_utils::parse_optional(cli_args, "freezer-compression-level")?;
+        client_config.store.freezer_compression =
+            Some(Compression::from_codec_and_level(codec, level)?);
+    }
+
+    client_config.store.prune_historic_states = Some(cli_args.is_present("prune-historic-states"));
+    client_config.store.reconstruct_historic_states =
+        cli_args.is_present("reconstruct-historic-states");
+
     client_config.store.compact_on_init = cli_args.is_present("compact-db");
     if let Some(compact_on_prune) = cli_args.value_of("auto-compact-db") {
         client_config.store.compact_on_prune = compact_on_prune
@@ -285,13 +346,42 @@ pub fn get_config<E: EthSpec>(
         client_config.network.boot_nodes_enr.append(&mut boot_nodes)
     }
 
-    if let Some(genesis_state_bytes) = eth2_testnet_config.genesis_state_bytes {
+    // Genesis sync must be explicitly permitted on mainnet. Other networks are short-lived enough
+    // that syncing from genesis is permitted by default.
+    let is_mainnet =
+        !cli_args.is_present("testnet-dir") && cli_args.value_of("network") == Some("mainnet");
+    client_config.allow_insecure_genesis_sync =
+        cli_args.is_present("allow-insecure-genesis-sync") || !is_mainnet;
+
+    if let Some(url) = cli_args.value_of("checkpoint-sync-url") {
+        client_config.genesis = ClientGenesis::CheckpointSyncUrl {
+            genesis_state_bytes: eth2_testnet_config.genesis_state_bytes,
+            url: url.to_string(),
+        };
+    } else if let (Some(state_path), Some(block_path)) = (
+        cli_args.value_of("checkpoint-state"),
+        cli_args.value_of("checkpoint-block"),
+    ) {
+        let read = |path: &str| {
+            fs::read(path).map_err(|e| format!("Unable to read checkpoint file {}: {:?}", path, e))
+        };
+        client_config.genesis = ClientGenesis::CheckpointSyncFiles {
+            genesis_state_bytes: eth2_testnet_config.genesis_state_bytes,
+            anchor_state_bytes: read(state_path)?,
+            anchor_block_bytes: read(block_path)?,
+        };
+    } else if let Some(genesis_state_bytes) = eth2_testnet_config.genesis_state_bytes {
         // Note: re-serializing the genesis state is not so efficient, however it avoids adding
         // trait bounds to the `ClientGenesis` enum. This would have significant flow-on
         // effects.
         client_config.genesis = ClientGenesis::SszBytes {
             genesis_state_bytes,
         };
+    } else if let Some(genesis_state_url) = eth2_testnet_config.genesis_state_url {
+        client_config.genesis = ClientGenesis::GenesisStateUrl {
+            url: genesis_state_url.url,
+            checksum: genesis_state_url.checksum,
+        };
     } else {
         client_config.genesis = ClientGenesis::DepositContract;
     }
@@ -361,45 +451,91 @@ pub fn get_config<E: EthSpec>(
     }
 
     if cli_args.is_present("slasher") {
-        let slasher_dir = if let Some(slasher_dir) = cli_args.value_of("slasher-dir") {
-            PathBuf::from(slasher_dir)
-        } else {
-            client_config.data_dir.join("slasher_db")
-        };
+        client_config.slasher = Some(get_slasher_config(cli_args, &client_config.data_dir)?);
+    }
+
+    if let Some(url) = cli_args.value_of("slashing-webhook") {
+        let validators = cli_args
+            .values_of("slashing-webhook-validators")
+            .map(|indices| {
+                indices
+                    .map(|index| {
+                        index
+                            .parse()
+                            .map_err(|_| format!("Invalid validator index: {}", index))
+                    })
+                    .collect::<Result<Vec<u64>, _>>()
+            })
+            .transpose()?;
 
-        let mut slasher_config = slasher::Config::new(slasher_dir);
+        client_config.slashing_webhook = Some(SlashingWebhookConfig {
+            url: url.to_string(),
+            validators,
+        });
+    }
 
-        if let Some(update_period) = clap_utils::parse_optional(cli_args, "slasher-update-period")?
-        {
-            slasher_config.update_period = update_period;
-        }
+    Ok(client_config)
+}
 
-        if let Some(history_length) =
-            clap_utils::parse_optional(cli_args, "slasher-history-length")?
-        {
-            slasher_config.history_length = history_length;
-        }
+/// Gets the slasher config from the `slasher-*` command line arguments, keeping the database in
+/// `data_dir` unless `--slasher-dir` is supplied.
+///
+/// Also used by the standalone slasher, which accepts the same arguments.
+pub fn get_slasher_config(
+    cli_args: &ArgMatches,
+    data_dir: &Path,
+) -> Result<slasher::Config, String> {
+    let slasher_dir = if let Some(slasher_dir) = cli_args.value_of("slasher-dir") {
+        PathBuf::from(slasher_dir)
+    } else {
+        data_dir.join("slasher_db")
+    };
 
-        if let Some(max_db_size_gbs) =
-            clap_utils::parse_optional::<usize>(cli_args, "slasher-max-db-size")?
-        {
-            slasher_config.max_db_size_mbs = max_db_size_gbs * 1024;
-        }
+    let mut slasher_config = slasher::Config::new(slasher_dir);
 
-        if let Some(chunk_size) = clap_utils::parse_optional(cli_args, "slasher-chunk-size")? {
-            slasher_config.chunk_size = chunk_size;
-        }
+    if let Some(update_period) = clap_utils::parse_optional(cli_args, "slasher-update-period")? {
+        slasher_config.update_period = update_period;
+    }
 
-        if let Some(validator_chunk_size) =
-            clap_utils::parse_optional(cli_args, "slasher-validator-chunk-size")?
-        {
-            slasher_config.validator_chunk_size = validator_chunk_size;
-        }
+    if let Some(history_length) = clap_utils::parse_optional(cli_args, "slasher-history-length")? {
+        slasher_config.history_length = history_length;
+    }
 
-        client_config.slasher = Some(slasher_config);
+    if let Some(max_db_size_gbs) =
+        clap_utils::parse_optional::<usize>(cli_args, "slasher-max-db-size")?
+    {
+        slasher_config.max_db_size_mbs = max_db_size_gbs * 1024;
     }
 
-    Ok(client_config)
+    if let Some(chunk_size) = clap_utils::parse_optional(cli_args, "slasher-chunk-size")? {
+        slasher_config.chunk_size = chunk_size;
+    }
+
+    if let Some(validator_chunk_size) =
+        clap_utils::parse_optional(cli_args, "slasher-validator-chunk-size")?
+    {
+        slasher_config.validator_chunk_size = validator_chunk_size;
+    }
+
+    if let Some(backend) = clap_utils::parse_optional(cli_args, "slasher-backend")? {
+        slasher_config.backend = backend;
+    }
+
+    if let Some(backfill_epoch) =
+        clap_utils::parse_optional::<u64>(cli_args, "slasher-backfill-epoch")?
+    {
+        slasher_config.backfill_epoch = Some(Epoch::new(backfill_epoch));
+    }
+
+    if let Some(memory_budget) = clap_utils::parse_optional(cli_args, "slasher-memory-budget")? {
+        slasher_config.memory_budget_mbs = Some(memory_budget);
+    }
+
+    if let Some(broadcast) = clap_utils::parse_optional(cli_args, "slasher-broadcast")? {
+        slasher_config.broadcast = broadcast;
+    }
+
+    Ok(slasher_config)
 }
 
 /// Sets the network config from the command line arguments
@@ -425,6 +561,15 @@ pub fn set_network_config(
         config.import_all_attestations = true;
     }
 
+    if cli_args.is_present("proposer-only") {
+        config.proposer_only = true;
+        warn!(
+            log,
+            "Proposer-only mode enabled";
+            "info" => "this node will not support validators performing attestation duties"
+        );
+    }
+
     if let Some(listen_address_str) = cli_args.value_of("listen-address") {
         let listen_address = listen_address_str
             .parse()
@@ -438,7 +583,24 @@ pub fn set_network_config(
             .map_err(|_| format!("Invalid number of target peers: {}", target_peers_str))?;
     }
 
-    if let Some(port_str) = cli_args.value_of("port") {
+    if let Some(max_str) = cli_args.value_of("max-peers-per-ip") {
+        config.max_peers_per_ip = max_str
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid number of peers per IP: {}", max_str))?;
+    }
+
+    if let Some(max_str) = cli_args.value_of("max-peers-per-subnet") {
+        config.max_peers_per_subnet = max_str
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid number of peers per subnet: {}", max_str))?;
+    }
+
+    // The boot node also accepts its port and ENR address as positional arguments, which take
+    // precedence over the flags.
+    if let Some(port_str) = cli_args
+        .value_of("positional-port")
+        .or_else(|| cli_args.value_of("port"))
+    {
         let port = port_str
             .parse::<u16>()
             .map_err(|_| format!("Invalid port: {}", port_str))?;
@@ -516,6 +678,28 @@ pub fn set_network_config(
         );
     }
 
+    if let Some(enr_udp6_port_str) = cli_args.value_of("enr-udp6-port") {
+        config.enr_udp6_port = Some(
+            enr_udp6_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR UDP6 port: {}", enr_udp6_port_str))?,
+        );
+    }
+
+    if let Some(enr_tcp6_port_str) = cli_args.value_of("enr-tcp6-port") {
+        config.enr_tcp6_port = Some(
+            enr_tcp6_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR TCP6 port: {}", enr_tcp6_port_str))?,
+        );
+    }
+
+    if let Some(enr_fields) = cli_args.values_of("enr-field") {
+        config.enr_custom_fields = enr_fields
+            .map(parse_enr_field)
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+
     if cli_args.is_present("enr-match") {
         // set the enr address to localhost if the address is 0.0.0.0
         if config.listen_address == "0.0.0.0".parse::<IpAddr>().expect("valid ip addr") {
@@ -526,7 +710,10 @@ pub fn set_network_config(
         config.enr_udp_port = Some(config.discovery_port);
     }
 
-    if let Some(enr_address) = cli_args.value_of("enr-address") {
+    if let Some(enr_address) = cli_args
+        .value_of("positional-enr-address")
+        .or_else(|| cli_args.value_of("enr-address"))
+    {
         let resolved_addr = match enr_address.parse::<IpAddr>() {
             Ok(addr) => addr, // // Input is an IpAddr
             Err(_) => {
@@ -583,9 +770,73 @@ pub fn set_network_config(
         config.private = true;
     }
 
+    if let Some(allow_str) = cli_args.value_of("peer-allow-cidr") {
+        config.ip_filter.allow = parse_cidrs(allow_str)?;
+    }
+
+    if let Some(deny_str) = cli_args.value_of("peer-deny-cidr") {
+        config.ip_filter.deny = parse_cidrs(deny_str)?;
+    }
+
+    if let Some(proxy_str) = cli_args.value_of("socks5-proxy") {
+        let proxy = proxy_str
+            .parse::<SocketAddr>()
+            .map_err(|_| format!("Invalid socks5-proxy address: {}", proxy_str))?;
+        config.socks5_proxy = Some(proxy);
+        // Discovery uses UDP and UPnP contacts the local gateway, neither of which can be
+        // tunnelled through a SOCKS5 proxy.
+        config.disable_discovery = true;
+        config.upnp_enabled = false;
+        warn!(
+            log,
+            "Dialling peers through SOCKS5 proxy";
+            "proxy" => %proxy,
+            "info" => "discovery and UPnP are disabled"
+        );
+    }
+
     Ok(())
 }
 
+/// Parses a comma-delimited list of IP ranges in CIDR notation.
+fn parse_cidrs(cidrs: &str) -> Result<Vec<IpNet>, String> {
+    cidrs
+        .split(',')
+        .map(|cidr| {
+            cidr.trim()
+                .parse::<IpNet>()
+                .map_err(|_| format!("Invalid CIDR: {}", cidr))
+        })
+        .collect()
+}
+
+/// Parses a custom ENR field of the form `KEY=VALUE`.
+///
+/// Values prefixed with `0x` are decoded as hex, otherwise the UTF-8 bytes of the value are used.
+fn parse_enr_field(field: &str) -> Result<(String, Vec<u8>), String> {
+    let mut split = field.splitn(2, '=');
+    let key = split
+        .next()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| format!("Invalid enr-field, expected KEY=VALUE: {}", field))?;
+    let value = split
+        .next()
+        .ok_or_else(|| format!("Invalid enr-field, expected KEY=VALUE: {}", field))?;
+
+    if RESERVED_ENR_KEYS.contains(&key) {
+        return Err(format!("The ENR key {} is reserved and cannot be set", key));
+    }
+
+    let value = if let Some(hex_value) = value.strip_prefix("0x") {
+        hex::decode(hex_value)
+            .map_err(|e| format!("Invalid hex value for enr-field {}: {:?}", key, e))?
+    } else {
+        value.as_bytes().to_vec()
+    };
+
+    Ok((key.to_string(), value))
+}
+
 /// Gets the datadir which should be used.
 pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
     // Read the `--datadir` flag.
@@ -7,11 +7,13 @@ mod config;
 pub use beacon_chain;
 pub use cli::cli_app;
 pub use client::{Client, ClientBuilder, ClientConfig, ClientGenesis};
-pub use config::{get_config, get_data_dir, get_eth2_testnet_config, set_network_config};
+pub use config::{
+    get_config, get_data_dir, get_eth2_testnet_config, get_slasher_config, set_network_config,
+};
 pub use eth2_config::Eth2Config;
 
 use beacon_chain::events::TeeEventHandler;
-use beacon_chain::store::LevelDB;
+use beacon_chain::store::{BeaconNodeBackend, DatabaseBackend};
 use beacon_chain::{
     builder::Witness, eth1_chain::CachingEth1Backend, slot_clock::SystemTimeSlotClock,
 };
@@ -30,8 +32,8 @@ pub type ProductionClient<E> = Client<
         CachingEth1Backend<E>,
         E,
         TeeEventHandler<E>,
-        LevelDB<E>,
-        LevelDB<E>,
+        BeaconNodeBackend<E>,
+        BeaconNodeBackend<E>,
     >,
 >;
 
@@ -72,20 +74,59 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
         let client_genesis = client_config.genesis.clone();
         let store_config = client_config.store.clone();
         let log = context.log().clone();
-        let db_path = client_config.create_db_path()?;
-        let freezer_db_path_res = client_config.create_freezer_db_path();
         let executor = context.executor.clone();
 
+        if store_config.read_only {
+            info!(
+                log,
+                "Using a read-only database";
+                "info" => "networking, eth1 and block import are disabled"
+            );
+        } else if store_config.backend == DatabaseBackend::Memory {
+            warn!(
+                log,
+                "Using an ephemeral database";
+                "info" => "all chain data will be lost when the beacon node stops"
+            );
+        }
+
+        // An ephemeral database leaves nothing on disk, and a read-only database must already
+        // exist, so their directories aren't created.
+        let (db_path, freezer_db_path) =
+            if store_config.backend == DatabaseBackend::Memory || store_config.read_only {
+                (
+                    client_config
+                        .get_db_path()
+                        .ok_or("Unable to locate user home directory")?,
+                    client_config
+                        .get_freezer_db_path()
+                        .ok_or("Unable to locate user home directory")?,
+                )
+            } else {
+                (
+                    client_config.create_db_path()?,
+                    client_config.create_freezer_db_path()?,
+                )
+            };
+
         let builder = ClientBuilder::new(context.eth_spec_instance.clone())
             .runtime_context(context)
             .chain_spec(spec)
             .http_api_config(client_config.http_api.clone())
-            .disk_store(&db_path, &freezer_db_path_res?, store_config)?;
+            .disk_store(&db_path, &freezer_db_path, store_config)?;
 
         let builder = if let Some(slasher_config) = client_config.slasher.clone() {
             let slasher = Arc::new(
-                Slasher::open(slasher_config, log.new(slog::o!("service" => "slasher")))
-                    .map_err(|e| format!("Slasher open error: {:?}", e))?,
+                Slasher::open(slasher_config, log.new(slog::o!("service" => "slasher"))).map_err(
+                    |e| match e {
+                        slasher::Error::SchemaMigrationRequired { .. } => format!(
+                            "Slasher open error: {:?}. Stop the beacon node and run \
+                            `lighthouse slasher migrate` to upgrade the slasher database",
+                            e
+                        ),
+                        e => format!("Slasher open error: {:?}", e),
+                    },
+                )?,
             );
             builder.slasher(slasher)
         } else {
@@ -95,7 +136,9 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
         let builder = builder
             .beacon_chain_builder(client_genesis, client_config_1)
             .await?;
-        let builder = if client_config.sync_eth1_chain && !client_config.dummy_eth1_backend {
+        let builder = if client_config.store.read_only {
+            builder.no_eth1_backend()?
+        } else if client_config.sync_eth1_chain && !client_config.dummy_eth1_backend {
             info!(
                 log,
                 "Block production enabled";
@@ -121,19 +164,25 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
             builder.no_eth1_backend()?
         };
 
-        let (builder, _events) = builder
-            .system_time_slot_clock()?
-            .tee_event_handler(client_config.websocket_server.clone())?;
+        let (builder, _events) = builder.system_time_slot_clock()?.tee_event_handler(
+            client_config.websocket_server.clone(),
+            client_config.slashing_webhook.clone(),
+        )?;
 
         // Inject the executor into the discv5 network config.
         let discv5_executor = Discv5Executor(executor);
         client_config.network.discv5_config.executor = Some(Box::new(discv5_executor));
 
+        let builder = builder.build_beacon_chain()?;
+
+        // A read-only node only serves what is already in its database.
+        let builder = if client_config.store.read_only {
+            builder
+        } else {
+            builder.network(&client_config.network).await?.notifier()?
+        };
+
         builder
-            .build_beacon_chain()?
-            .network(&client_config.network)
-            .await?
-            .notifier()?
             .http_metrics_config(client_config.http_metrics.clone())
             .build()
             .map(Self)
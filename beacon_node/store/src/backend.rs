@@ -0,0 +1,696 @@
+//! Run-time selection of the key-value store that backs the on-disk `HotColdDB`.
+//!
+//! A database written by one backend cannot be read by another, so the backend of an existing
+//! database is detected on start-up. If it differs from the configured backend the database can
+//! be migrated by copying every key-value pair into a fresh database of the configured type.
+//!
+//! Values may also be compressed, which is used to shrink the freezer database. The codec must be
+//! the same for the lifetime of a database, so it is recorded when the database is created.
+//!
+//! The memory backend keeps nothing on disk, and is intended for tests and short-lived devnets.
+use crate::config::DatabaseBackend;
+use crate::metrics;
+#[cfg(feature = "redb")]
+use crate::Redb;
+use crate::{
+    Compression, CompressionStats, DBColumn, Error, ItemStore, KeyValueStore, KeyValueStoreOp,
+    LevelDB, MemoryStore,
+};
+use parking_lot::MutexGuard;
+use slog::{info, warn, Logger};
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use types::{EthSpec, Hash256};
+
+/// The number of key-value pairs written per transaction when migrating between backends.
+const MIGRATION_BATCH_SIZE: usize = 4096;
+
+/// A file which is present in every LevelDB database directory.
+const LEVELDB_CURRENT_FILENAME: &str = "CURRENT";
+
+/// The name of the redb database file within the database directory.
+pub(crate) const REDB_FILENAME: &str = "lighthouse.redb";
+
+impl DatabaseBackend {
+    /// Return the backend of the database in the directory at `path`, or `None` if there is no
+    /// database there.
+    ///
+    /// Memory databases are never detected, as they leave nothing on disk.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if path.join(REDB_FILENAME).exists() {
+            Some(DatabaseBackend::Redb)
+        } else if path.join(LEVELDB_CURRENT_FILENAME).exists() {
+            Some(DatabaseBackend::LevelDb)
+        } else {
+            None
+        }
+    }
+}
+
+/// The key-value store implementations.
+enum Backend<E: EthSpec> {
+    LevelDb(LevelDB<E>),
+    #[cfg(feature = "redb")]
+    Redb(Redb<E>),
+    Memory(MemoryStore<E>),
+}
+
+/// A key-value store whose implementation is chosen at run-time, and which compresses all values
+/// written to it with a fixed codec.
+pub struct BeaconNodeBackend<E: EthSpec> {
+    db: Backend<E>,
+    compression: Compression,
+    /// The total size of the values passed to `compression`.
+    uncompressed_bytes: AtomicU64,
+    /// The total size of the values produced by `compression`.
+    compressed_bytes: AtomicU64,
+    /// Whether all writes are refused.
+    read_only: bool,
+}
+
+impl<E: EthSpec> BeaconNodeBackend<E> {
+    /// Open a `backend` database at `path`, creating a new database if one does not already
+    /// exist. A memory database is always new, and ignores `path`.
+    ///
+    /// Values are compressed with `compression`, which must match the codec used to write any
+    /// existing values.
+    pub fn open(
+        backend: DatabaseBackend,
+        path: &Path,
+        compression: Compression,
+    ) -> Result<Self, Error> {
+        let db = match backend {
+            DatabaseBackend::LevelDb => Backend::LevelDb(LevelDB::open(path)?),
+            #[cfg(feature = "redb")]
+            DatabaseBackend::Redb => Backend::Redb(Redb::open(path)?),
+            #[cfg(not(feature = "redb"))]
+            DatabaseBackend::Redb => {
+                return Err(Error::DBError {
+                    message: "Lighthouse was compiled without the redb backend, rebuild it with \
+                              --features redb"
+                        .into(),
+                })
+            }
+            DatabaseBackend::Memory => Backend::Memory(MemoryStore::open()),
+        };
+        Ok(Self {
+            db,
+            compression,
+            uncompressed_bytes: AtomicU64::new(0),
+            compressed_bytes: AtomicU64::new(0),
+            read_only: false,
+        })
+    }
+
+    /// Open the existing database at `path` with whichever backend wrote it, refusing all writes.
+    ///
+    /// The underlying store may still lock or recover the files of the database, so a database
+    /// which is in use by another process must be copied first.
+    pub fn open_read_only(path: &Path, compression: Compression) -> Result<Self, Error> {
+        let backend = DatabaseBackend::detect(path).ok_or_else(|| Error::DBError {
+            message: format!("No database found at {}", path.display()),
+        })?;
+        Ok(Self {
+            read_only: true,
+            ..Self::open(backend, path, compression)?
+        })
+    }
+
+    /// Return `true` if the database refuses all writes.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_write(&self) -> Result<(), Error> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// As `open`, except that an existing database written by a different backend is migrated to
+    /// `backend` if `migrate` is `true`, and rejected otherwise.
+    pub fn open_or_migrate(
+        backend: DatabaseBackend,
+        path: &Path,
+        compression: Compression,
+        migrate: bool,
+        log: &Logger,
+    ) -> Result<Self, Error> {
+        if backend == DatabaseBackend::Memory {
+            return Self::open(backend, path, compression);
+        }
+
+        Self::recover_migration(path, log)?;
+
+        if let Some(existing) = DatabaseBackend::detect(path) {
+            if existing != backend {
+                if !migrate {
+                    return Err(Error::DBError {
+                        message: format!(
+                            "The database at {} uses the {} backend but {} is configured. \
+                             Run with --migrate-db-backend to convert it, or select {}.",
+                            path.display(),
+                            existing,
+                            backend,
+                            existing
+                        ),
+                    });
+                }
+                Self::migrate(existing, backend, path, log)?;
+            }
+        }
+
+        Self::open(backend, path, compression)
+    }
+
+    /// Convert the `from` database at `path` into a `to` database.
+    ///
+    /// The new database is built alongside the old one and only swapped into place once it is
+    /// complete, so an interrupted migration leaves the original database intact. The original is
+    /// moved aside before the swap and only deleted after it, so that `recover_migration` can
+    /// restore it if the swap itself is interrupted. Values are copied verbatim, so any compression
+    /// is preserved.
+    fn migrate(
+        from: DatabaseBackend,
+        to: DatabaseBackend,
+        path: &Path,
+        log: &Logger,
+    ) -> Result<(), Error> {
+        let migration_path = migration_path(path);
+        let backup_path = migration_backup_path(path);
+
+        // Discard the remains of any previously interrupted migration.
+        if migration_path.exists() {
+            fs::remove_dir_all(&migration_path).map_err(|e| io_error(&migration_path, e))?;
+        }
+
+        info!(
+            log,
+            "Migrating database backend";
+            "path" => %path.display(),
+            "from" => %from,
+            "to" => %to,
+        );
+
+        let entries = {
+            let source = Self::open(from, path, Compression::None)?;
+            let dest = Self::open(to, &migration_path, Compression::None)?;
+            let entries = copy_entries(&source, &dest)?;
+            dest.sync()?;
+            entries
+        };
+
+        fs::rename(path, &backup_path).map_err(|e| io_error(path, e))?;
+        fs::rename(&migration_path, path).map_err(|e| io_error(&migration_path, e))?;
+        fs::remove_dir_all(&backup_path).map_err(|e| io_error(&backup_path, e))?;
+
+        info!(
+            log,
+            "Database backend migration complete";
+            "path" => %path.display(),
+            "entries" => entries,
+        );
+
+        Ok(())
+    }
+
+    /// Finish or undo a backend migration of the database at `path` which was interrupted while
+    /// swapping the new database into place.
+    ///
+    /// If the original database was moved aside but the new one is not yet in place, the original
+    /// is restored, and the migration will run again if it is still configured. If the new
+    /// database is in place, the original is deleted.
+    fn recover_migration(path: &Path, log: &Logger) -> Result<(), Error> {
+        let backup_path = migration_backup_path(path);
+        if !backup_path.exists() {
+            return Ok(());
+        }
+
+        if path.exists() {
+            fs::remove_dir_all(&backup_path).map_err(|e| io_error(&backup_path, e))?;
+        } else {
+            warn!(
+                log,
+                "Restoring database after interrupted backend migration";
+                "path" => %path.display(),
+            );
+            fs::rename(&backup_path, path).map_err(|e| io_error(&backup_path, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// The implementation backing this store.
+    pub fn backend(&self) -> DatabaseBackend {
+        match self.db {
+            Backend::LevelDb(_) => DatabaseBackend::LevelDb,
+            #[cfg(feature = "redb")]
+            Backend::Redb(_) => DatabaseBackend::Redb,
+            Backend::Memory(_) => DatabaseBackend::Memory,
+        }
+    }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    ///
+    /// Values are passed to `f` as they are stored, without being decompressed.
+    pub fn for_each_entry(
+        &self,
+        f: impl FnMut(Vec<u8>, Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        match &self.db {
+            Backend::LevelDb(db) => db.for_each_entry(f),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.for_each_entry(f),
+            Backend::Memory(db) => db.for_each_entry(f),
+        }
+    }
+
+    fn compress(&self, value: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.compression == Compression::None {
+            return Ok(value.to_vec());
+        }
+
+        let compressed = self.compression.compress(value)?;
+        self.uncompressed_bytes
+            .fetch_add(value.len() as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed.len() as u64, Ordering::Relaxed);
+        metrics::inc_counter_by(
+            &metrics::DISK_DB_COMPRESSION_INPUT_BYTES,
+            value.len() as i64,
+        );
+        metrics::inc_counter_by(
+            &metrics::DISK_DB_COMPRESSION_OUTPUT_BYTES,
+            compressed.len() as i64,
+        );
+        Ok(compressed)
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for BeaconNodeBackend<E> {
+    fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let value = match &self.db {
+            Backend::LevelDb(db) => db.get_bytes(column, key)?,
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.get_bytes(column, key)?,
+            Backend::Memory(db) => db.get_bytes(column, key)?,
+        };
+        match value {
+            Some(value) if self.compression != Compression::None => {
+                self.compression.decompress(&value).map(Some)
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn put_bytes(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.check_write()?;
+        let value = self.compress(value)?;
+        match &self.db {
+            Backend::LevelDb(db) => db.put_bytes(column, key, &value),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.put_bytes(column, key, &value),
+            Backend::Memory(db) => db.put_bytes(column, key, &value),
+        }
+    }
+
+    fn put_bytes_sync(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.check_write()?;
+        let value = self.compress(value)?;
+        match &self.db {
+            Backend::LevelDb(db) => db.put_bytes_sync(column, key, &value),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.put_bytes_sync(column, key, &value),
+            Backend::Memory(db) => db.put_bytes_sync(column, key, &value),
+        }
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        match &self.db {
+            Backend::LevelDb(db) => db.sync(),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.sync(),
+            Backend::Memory(db) => db.sync(),
+        }
+    }
+
+    fn key_exists(&self, column: &str, key: &[u8]) -> Result<bool, Error> {
+        match &self.db {
+            Backend::LevelDb(db) => db.key_exists(column, key),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.key_exists(column, key),
+            Backend::Memory(db) => db.key_exists(column, key),
+        }
+    }
+
+    fn key_delete(&self, column: &str, key: &[u8]) -> Result<(), Error> {
+        self.check_write()?;
+        match &self.db {
+            Backend::LevelDb(db) => db.key_delete(column, key),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.key_delete(column, key),
+            Backend::Memory(db) => db.key_delete(column, key),
+        }
+    }
+
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        self.check_write()?;
+        let batch = if self.compression == Compression::None {
+            batch
+        } else {
+            batch
+                .into_iter()
+                .map(|op| match op {
+                    KeyValueStoreOp::PutKeyValue(key, value) => {
+                        Ok(KeyValueStoreOp::PutKeyValue(key, self.compress(&value)?))
+                    }
+                    op @ KeyValueStoreOp::DeleteKey(_) => Ok(op),
+                })
+                .collect::<Result<_, Error>>()?
+        };
+        match &self.db {
+            Backend::LevelDb(db) => db.do_atomically(batch),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.do_atomically(batch),
+            Backend::Memory(db) => db.do_atomically(batch),
+        }
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        match &self.db {
+            Backend::LevelDb(db) => db.begin_rw_transaction(),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.begin_rw_transaction(),
+            Backend::Memory(db) => db.begin_rw_transaction(),
+        }
+    }
+
+    fn compact(&self) -> Result<(), Error> {
+        self.check_write()?;
+        match &self.db {
+            Backend::LevelDb(db) => db.compact(),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.compact(),
+            Backend::Memory(db) => db.compact(),
+        }
+    }
+
+    fn iter_column_keys<'a>(
+        &'a self,
+        column: DBColumn,
+    ) -> Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a> {
+        match &self.db {
+            Backend::LevelDb(db) => db.iter_column_keys(column),
+            #[cfg(feature = "redb")]
+            Backend::Redb(db) => db.iter_column_keys(column),
+            Backend::Memory(db) => db.iter_column_keys(column),
+        }
+    }
+
+    fn compression_stats(&self) -> Option<CompressionStats> {
+        Some(CompressionStats {
+            compression: self.compression,
+            uncompressed_bytes: self.uncompressed_bytes.load(Ordering::Relaxed),
+            compressed_bytes: self.compressed_bytes.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Both on-disk backends iterate over a single point-in-time view of the database (a LevelDB
+    /// iterator's implicit snapshot, or a redb read transaction), so writes made while the backup
+    /// is running are not included. Values are copied verbatim, so any compression is preserved.
+    fn backup(&self, dest: &Path) -> Result<usize, Error> {
+        if self.backend() == DatabaseBackend::Memory {
+            return Err(Error::DBError {
+                message: "An in-memory database cannot be backed up".to_string(),
+            });
+        }
+        if dest.exists() {
+            return Err(Error::DBError {
+                message: format!("Backup destination {} already exists", dest.display()),
+            });
+        }
+
+        let copy = || -> Result<usize, Error> {
+            let dest_db = Self::open(self.backend(), dest, Compression::None)?;
+            let entries = copy_entries(self, &dest_db)?;
+            dest_db.sync()?;
+            Ok(entries)
+        };
+        copy().map_err(|e| {
+            // Don't leave behind an incomplete database that could be mistaken for a backup.
+            let _ = fs::remove_dir_all(dest);
+            e
+        })
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for BeaconNodeBackend<E> {}
+
+/// Copy every key-value pair in `source` to `dest`, returning the number of pairs copied.
+fn copy_entries<E: EthSpec>(
+    source: &BeaconNodeBackend<E>,
+    dest: &BeaconNodeBackend<E>,
+) -> Result<usize, Error> {
+    let mut batch = Vec::with_capacity(MIGRATION_BATCH_SIZE);
+    let mut entries = 0;
+
+    source.for_each_entry(|key, value| {
+        batch.push(KeyValueStoreOp::PutKeyValue(key, value));
+        entries += 1;
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            dest.do_atomically(mem::take(&mut batch))?;
+        }
+        Ok(())
+    })?;
+    dest.do_atomically(batch)?;
+
+    Ok(entries)
+}
+
+/// The directory in which a new database is built during a backend migration.
+fn migration_path(path: &Path) -> PathBuf {
+    sibling_path(path, "migration")
+}
+
+/// The directory to which the original database is moved while a backend migration swaps the new
+/// database into place.
+fn migration_backup_path(path: &Path) -> PathBuf {
+    sibling_path(path, "pre_migration")
+}
+
+/// Return a path alongside `path`, with `suffix` appended to its final component.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!("_{}", suffix));
+    path.with_file_name(file_name)
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::DBError {
+        message: format!("Database migration failed at {}: {:?}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_key_for_col;
+    use tempfile::tempdir;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    fn test_logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    #[test]
+    #[cfg(feature = "redb")]
+    fn migrate_leveldb_to_redb() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("chain_db");
+        let column = DBColumn::BeaconStateTemporary;
+        let keys = (0..10_000)
+            .map(Hash256::from_low_u64_be)
+            .collect::<Vec<_>>();
+
+        {
+            let db =
+                BeaconNodeBackend::<E>::open(DatabaseBackend::LevelDb, &path, Compression::None)
+                    .unwrap();
+            let ops = keys
+                .iter()
+                .map(|key| {
+                    let db_key = get_key_for_col(column.as_str(), key.as_bytes());
+                    KeyValueStoreOp::PutKeyValue(db_key, key.as_bytes().to_vec())
+                })
+                .collect();
+            db.do_atomically(ops).unwrap();
+        }
+        assert_eq!(
+            DatabaseBackend::detect(&path),
+            Some(DatabaseBackend::LevelDb)
+        );
+
+        // A mismatched backend is rejected unless migration is enabled.
+        let log = test_logger();
+        let compression = Compression::None;
+        assert!(BeaconNodeBackend::<E>::open_or_migrate(
+            DatabaseBackend::Redb,
+            &path,
+            compression,
+            false,
+            &log
+        )
+        .is_err());
+        let db = BeaconNodeBackend::<E>::open_or_migrate(
+            DatabaseBackend::Redb,
+            &path,
+            compression,
+            true,
+            &log,
+        )
+        .unwrap();
+
+        assert_eq!(DatabaseBackend::detect(&path), Some(DatabaseBackend::Redb));
+        assert_eq!(db.backend(), DatabaseBackend::Redb);
+        for key in &keys {
+            assert_eq!(
+                db.get_bytes(column.as_str(), key.as_bytes()).unwrap(),
+                Some(key.as_bytes().to_vec())
+            );
+        }
+        let migrated_keys = db
+            .iter_column_keys(column)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(migrated_keys, keys);
+    }
+
+    #[test]
+    fn recover_interrupted_migration() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("chain_db");
+        let column = DBColumn::BeaconStateTemporary;
+        let key = Hash256::repeat_byte(1);
+        let log = test_logger();
+
+        {
+            let db =
+                BeaconNodeBackend::<E>::open(DatabaseBackend::LevelDb, &path, Compression::None)
+                    .unwrap();
+            db.put_bytes(column.as_str(), key.as_bytes(), &[42])
+                .unwrap();
+        }
+
+        // Simulate a crash after the original database was moved aside, but before the new one
+        // was swapped into place.
+        fs::rename(&path, migration_backup_path(&path)).unwrap();
+
+        let db = BeaconNodeBackend::<E>::open_or_migrate(
+            DatabaseBackend::LevelDb,
+            &path,
+            Compression::None,
+            false,
+            &log,
+        )
+        .unwrap();
+        assert_eq!(
+            db.get_bytes(column.as_str(), key.as_bytes()).unwrap(),
+            Some(vec![42])
+        );
+        assert!(!migration_backup_path(&path).exists());
+        drop(db);
+
+        // Simulate a crash after the swap, but before the original database was deleted.
+        fs::create_dir(migration_backup_path(&path)).unwrap();
+
+        BeaconNodeBackend::<E>::open_or_migrate(
+            DatabaseBackend::LevelDb,
+            &path,
+            Compression::None,
+            false,
+            &log,
+        )
+        .unwrap();
+        assert!(!migration_backup_path(&path).exists());
+    }
+
+    #[test]
+    fn memory_backend() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("chain_db");
+        let column = DBColumn::BeaconStateTemporary;
+        let keys = (0..100).map(Hash256::from_low_u64_be).collect::<Vec<_>>();
+
+        let db = BeaconNodeBackend::<E>::open_or_migrate(
+            DatabaseBackend::Memory,
+            &path,
+            Compression::Snappy,
+            false,
+            &test_logger(),
+        )
+        .unwrap();
+        for key in keys.iter().rev() {
+            db.put_bytes(column.as_str(), key.as_bytes(), &[42; 256])
+                .unwrap();
+        }
+
+        assert_eq!(db.backend(), DatabaseBackend::Memory);
+        assert_eq!(
+            db.get_bytes(column.as_str(), keys[0].as_bytes()).unwrap(),
+            Some(vec![42; 256])
+        );
+        let stored_keys = db
+            .iter_column_keys(column)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(stored_keys, keys);
+
+        // Nothing is written to disk, and there is nothing to back up.
+        assert!(!path.exists());
+        assert_eq!(DatabaseBackend::detect(&path), None);
+        assert!(db.backup(&dir.path().join("backup")).is_err());
+    }
+
+    #[test]
+    fn backup() {
+        let dir = tempdir().unwrap();
+        let column = DBColumn::BeaconStateTemporary;
+        let keys = (0..10_000)
+            .map(Hash256::from_low_u64_be)
+            .collect::<Vec<_>>();
+
+        for backend in &[
+            DatabaseBackend::LevelDb,
+            #[cfg(feature = "redb")]
+            DatabaseBackend::Redb,
+        ] {
+            let path = dir.path().join(format!("{}_db", backend));
+            let backup_path = dir.path().join(format!("{}_backup", backend));
+            let compression = Compression::Snappy;
+
+            let db = BeaconNodeBackend::<E>::open(*backend, &path, compression).unwrap();
+            for key in &keys {
+                db.put_bytes(column.as_str(), key.as_bytes(), &[42; 256])
+                    .unwrap();
+            }
+
+            assert_eq!(db.backup(&backup_path).unwrap(), keys.len());
+            // An existing backup is never overwritten.
+            assert!(db.backup(&backup_path).is_err());
+
+            let backup = BeaconNodeBackend::<E>::open(*backend, &backup_path, compression).unwrap();
+            for key in &keys {
+                assert_eq!(
+                    backup.get_bytes(column.as_str(), key.as_bytes()).unwrap(),
+                    Some(vec![42; 256])
+                );
+            }
+        }
+    }
+}
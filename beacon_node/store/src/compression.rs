@@ -0,0 +1,197 @@
+//! Compression of the values written to a key-value store.
+use crate::{DBColumn, Error, StoreItem};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// The zstd level used if none is specified.
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+/// The range of supported zstd levels.
+pub const ZSTD_LEVELS: RangeInclusive<i32> = 1..=22;
+
+/// The codec used to compress values, fixed when a database is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Snappy,
+    Zstd { level: i32 },
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::None
+    }
+}
+
+impl Compression {
+    /// Parse a codec name (`none`, `snappy` or `zstd`) and an optional level, which is only
+    /// accepted for zstd.
+    pub fn from_codec_and_level(codec: &str, level: Option<i32>) -> Result<Self, String> {
+        match (codec.parse()?, level) {
+            (Compression::Zstd { .. }, Some(level)) => {
+                if ZSTD_LEVELS.contains(&level) {
+                    Ok(Compression::Zstd { level })
+                } else {
+                    Err(format!(
+                        "zstd compression level must be between {} and {}",
+                        ZSTD_LEVELS.start(),
+                        ZSTD_LEVELS.end()
+                    ))
+                }
+            }
+            (compression, None) => Ok(compression),
+            (compression, Some(_)) => Err(format!(
+                "A compression level cannot be set for {}",
+                compression.codec()
+            )),
+        }
+    }
+
+    /// The name of the codec, without its level.
+    pub fn codec(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Snappy => "snappy",
+            Compression::Zstd { .. } => "zstd",
+        }
+    }
+
+    /// The compression level, if the codec has one.
+    pub fn level(&self) -> Option<i32> {
+        match self {
+            Compression::Zstd { level } => Some(*level),
+            Compression::None | Compression::Snappy => None,
+        }
+    }
+
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Snappy => snap::raw::Encoder::new()
+                .compress_vec(bytes)
+                .map_err(|e| Error::CompressionError(format!("{:?}", e))),
+            Compression::Zstd { level } => zstd::stream::encode_all(bytes, *level)
+                .map_err(|e| Error::CompressionError(format!("{:?}", e))),
+        }
+    }
+
+    pub fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Snappy => snap::raw::Decoder::new()
+                .decompress_vec(bytes)
+                .map_err(|e| Error::CompressionError(format!("{:?}", e))),
+            Compression::Zstd { .. } => zstd::stream::decode_all(bytes)
+                .map_err(|e| Error::CompressionError(format!("{:?}", e))),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "snappy" => Ok(Compression::Snappy),
+            "zstd" => Ok(Compression::Zstd {
+                level: DEFAULT_ZSTD_LEVEL,
+            }),
+            other => Err(format!("Unknown compression codec: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.level() {
+            Some(level) => write!(f, "{}:{}", self.codec(), level),
+            None => write!(f, "{}", self.codec()),
+        }
+    }
+}
+
+impl StoreItem for Compression {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let invalid = || Error::CompressionError(format!("Invalid codec: {:?}", bytes));
+        let s = std::str::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = s.splitn(2, ':');
+        let codec = parts.next().ok_or_else(invalid)?;
+        let level = parts
+            .next()
+            .map(|level| level.parse().map_err(|_| invalid()))
+            .transpose()?;
+        Compression::from_codec_and_level(codec, level).map_err(Error::CompressionError)
+    }
+}
+
+/// The number of bytes passed through a store's compression since it was opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CompressionStats {
+    pub compression: Compression,
+    pub uncompressed_bytes: u64,
+    pub compressed_bytes: u64,
+}
+
+impl CompressionStats {
+    /// The ratio of uncompressed to compressed bytes, if any bytes have been compressed.
+    pub fn ratio(&self) -> Option<f64> {
+        if self.compressed_bytes == 0 {
+            None
+        } else {
+            Some(self.uncompressed_bytes as f64 / self.compressed_bytes as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = vec![42; 4096];
+        for compression in &[
+            Compression::None,
+            Compression::Snappy,
+            Compression::Zstd { level: 1 },
+            Compression::Zstd { level: 19 },
+        ] {
+            let compressed = compression.compress(&bytes).unwrap();
+            if *compression != Compression::None {
+                assert!(compressed.len() < bytes.len());
+            }
+            assert_eq!(compression.decompress(&compressed).unwrap(), bytes);
+
+            let stored = Compression::from_store_bytes(&compression.as_store_bytes()).unwrap();
+            assert_eq!(stored, *compression);
+        }
+    }
+
+    #[test]
+    fn parse_level() {
+        assert_eq!(
+            Compression::from_codec_and_level("zstd", Some(9)),
+            Ok(Compression::Zstd { level: 9 })
+        );
+        assert_eq!(
+            Compression::from_codec_and_level("zstd", None),
+            Ok(Compression::Zstd {
+                level: DEFAULT_ZSTD_LEVEL
+            })
+        );
+        assert!(Compression::from_codec_and_level("snappy", Some(9)).is_err());
+        assert!(Compression::from_codec_and_level("zstd", Some(100)).is_err());
+        assert!(Compression::from_codec_and_level("lz4", None).is_err());
+    }
+}
@@ -1,11 +1,15 @@
-use crate::{DBColumn, Error, StoreItem};
+use crate::{Compression, DBColumn, Error, HierarchyConfig, StoreItem};
 use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::str::FromStr;
 use types::{EthSpec, MinimalEthSpec};
 
 pub const DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 2048;
 pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 5;
+pub const DEFAULT_HISTORIC_STATE_CACHE_SIZE: usize = 4;
+pub const DEFAULT_MIGRATION_BATCH_SIZE: usize = 512;
 
 /// Database configuration parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,10 +18,82 @@ pub struct StoreConfig {
     pub slots_per_restore_point: u64,
     /// Maximum number of blocks to store in the in-memory block cache.
     pub block_cache_size: usize,
+    /// Maximum number of restore points to keep in memory for replaying historic states.
+    pub historic_state_cache_size: usize,
     /// Whether to compact the database on initialization.
     pub compact_on_init: bool,
     /// Whether to compact the database during database pruning.
     pub compact_on_prune: bool,
+    /// The key-value store used for the hot and freezer databases.
+    pub backend: DatabaseBackend,
+    /// Whether to convert databases written by a different backend to `backend` on start-up.
+    pub migrate_backend: bool,
+    /// Whether to migrate an existing database to the current schema version on start-up.
+    ///
+    /// Offline tools disable this in order to inspect or migrate the schema explicitly. A
+    /// read-only database must already have the current version unless this is disabled.
+    pub migrate_schema: bool,
+    /// The compression to apply to the freezer DB if it is being created. Existing databases
+    /// keep the compression they were created with.
+    pub freezer_compression: Option<Compression>,
+    /// The layout of the restore points in the freezer DB if it is being created. Existing
+    /// databases keep the layout they were created with.
+    pub hierarchy: Option<HierarchyConfig>,
+    /// Whether to delete the restore points of historic states, and stop storing new ones.
+    ///
+    /// `None` keeps the policy recorded in the database, which is used by offline tools.
+    pub prune_historic_states: Option<bool>,
+    /// Whether to reconstruct historic states which are unavailable due to past pruning.
+    pub reconstruct_historic_states: bool,
+    /// Maximum number of states to write to or delete from each database in a single batch when
+    /// migrating finalized states to the freezer database.
+    pub migration_batch_size: usize,
+    /// Whether to open an existing database without write access, whatever its backend.
+    ///
+    /// The database must have the current schema version, and all writes to it fail.
+    pub read_only: bool,
+    /// Whether to refuse to start when the filesystems holding the databases lack the space
+    /// estimated for syncing the chain or migrating the schema, rather than warning.
+    pub check_disk_space: bool,
+}
+
+/// The key-value stores available to back the hot and freezer databases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    LevelDb,
+    Redb,
+    /// Volatile storage in memory, which is discarded when the store is dropped.
+    Memory,
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self {
+        DatabaseBackend::LevelDb
+    }
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leveldb" => Ok(DatabaseBackend::LevelDb),
+            "redb" => Ok(DatabaseBackend::Redb),
+            "memory" => Ok(DatabaseBackend::Memory),
+            other => Err(format!("Unknown database backend: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseBackend::LevelDb => write!(f, "leveldb"),
+            DatabaseBackend::Redb => write!(f, "redb"),
+            DatabaseBackend::Memory => write!(f, "memory"),
+        }
+    }
 }
 
 /// Variant of `StoreConfig` that gets written to disk. Contains immutable configuration params.
@@ -39,8 +115,19 @@ impl Default for StoreConfig {
             // Safe default for tests, shouldn't ever be read by a CLI node.
             slots_per_restore_point: MinimalEthSpec::slots_per_historical_root() as u64,
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
+            historic_state_cache_size: DEFAULT_HISTORIC_STATE_CACHE_SIZE,
             compact_on_init: false,
             compact_on_prune: true,
+            backend: DatabaseBackend::default(),
+            migrate_backend: false,
+            migrate_schema: true,
+            freezer_compression: None,
+            hierarchy: None,
+            prune_historic_states: None,
+            reconstruct_historic_states: false,
+            migration_batch_size: DEFAULT_MIGRATION_BATCH_SIZE,
+            read_only: false,
+            check_disk_space: true,
         }
     }
 }
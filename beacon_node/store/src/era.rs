@@ -0,0 +1,317 @@
+//! Era files, which archive the canonical chain one historical root at a time.
+//!
+//! Era `n` holds the blocks of the `SLOTS_PER_HISTORICAL_ROOT` slots preceding slot
+//! `n * SLOTS_PER_HISTORICAL_ROOT`, and the state at that slot. Its contents can be checked
+//! against the historical root which the state holds for them, so an era file can be trusted by
+//! anyone who knows the state's root.
+//!
+//! Era files use the e2store format: a sequence of records, each with an 8 byte header of a
+//! 2 byte type, 4 byte little-endian length and 2 reserved bytes. Blocks and the state are SSZ
+//! encoded and compressed with framed snappy, and slot index records at the end give the offset of
+//! each block (zero for empty slots) and of the state, relative to the index record.
+use crate::hot_cold_store::HotColdDB;
+use crate::{Error, ItemStore};
+use snap::read::FrameDecoder;
+use snap::write::FrameEncoder;
+use ssz::{Decode, Encode};
+use std::io::{Read, Write};
+use tree_hash::TreeHash;
+use types::{BeaconState, EthSpec, Hash256, HistoricalBatch, SignedBeaconBlock, Slot, Unsigned};
+
+const VERSION: [u8; 2] = [0x65, 0x32];
+const COMPRESSED_SIGNED_BEACON_BLOCK: [u8; 2] = [0x01, 0x00];
+const COMPRESSED_BEACON_STATE: [u8; 2] = [0x02, 0x00];
+const SLOT_INDEX: [u8; 2] = [0x69, 0x32];
+const HEADER_SIZE: usize = 8;
+
+/// The contents of an era file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Era<E: EthSpec> {
+    /// The blocks of the era, in slot order.
+    pub blocks: Vec<SignedBeaconBlock<E>>,
+    /// The state at the end of the era.
+    pub state: BeaconState<E>,
+}
+
+impl<E: EthSpec> Era<E> {
+    /// The number of the era, which is the number of historical roots preceding its state.
+    pub fn number(&self) -> u64 {
+        self.state.slot.as_u64() / E::SlotsPerHistoricalRoot::to_u64()
+    }
+
+    /// The first slot whose block belongs to the era.
+    fn start_slot(&self) -> Slot {
+        let slots_per_era = E::SlotsPerHistoricalRoot::to_u64();
+        Slot::new(self.number().saturating_sub(1) * slots_per_era)
+    }
+
+    /// The conventional file name of the era, `<config-name>-<era-number>-<short-root>.era`.
+    ///
+    /// The short root is the first 4 bytes of the historical root of the era, or of the genesis
+    /// validators root for era 0.
+    pub fn file_name(&self, config_name: &str) -> Result<String, Error> {
+        let root = match self.number() {
+            0 => self.state.genesis_validators_root,
+            number => self.historical_root(number)?,
+        };
+        let short_root = root.as_bytes()[..4]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        Ok(format!(
+            "{}-{:05}-{}.era",
+            config_name,
+            self.number(),
+            short_root
+        ))
+    }
+
+    fn historical_root(&self, number: u64) -> Result<Hash256, Error> {
+        self.state
+            .historical_roots
+            .get(number as usize - 1)
+            .copied()
+            .ok_or_else(|| Error::EraError(format!("Era {} has no historical root", number)))
+    }
+
+    /// Check that the state lies on an era boundary, that the blocks are exactly those of the
+    /// canonical chain during the era, and that they match the era's historical root.
+    pub fn verify(&self) -> Result<(), Error> {
+        let invalid = |message: String| Err(Error::EraError(message));
+        let slots_per_era = E::SlotsPerHistoricalRoot::to_u64();
+        if self.state.slot % slots_per_era != 0 {
+            return invalid(format!(
+                "State at slot {} is not on an era boundary",
+                self.state.slot
+            ));
+        }
+
+        let number = self.number();
+        if number == 0 {
+            if !self.blocks.is_empty() {
+                return invalid("Era 0 must not contain any blocks".to_string());
+            }
+            return Ok(());
+        }
+
+        let batch = HistoricalBatch::<E> {
+            block_roots: self.state.block_roots.clone(),
+            state_roots: self.state.state_roots.clone(),
+        };
+        if batch.tree_hash_root() != self.historical_root(number)? {
+            return invalid(format!("Historical root of era {} does not match", number));
+        }
+
+        // Each block must have the root recorded for its slot, and every other slot must repeat
+        // the root of the slot before it.
+        let mut blocks = self.blocks.iter().peekable();
+        let mut previous_root = None;
+        for slot in (self.start_slot().as_u64()..self.state.slot.as_u64()).map(Slot::new) {
+            let root = *self.state.get_block_root(slot)?;
+            match blocks.peek() {
+                Some(block) if block.slot() == slot => {
+                    if block.canonical_root() != root {
+                        return invalid(format!("Block at slot {} is not canonical", slot));
+                    }
+                    blocks.next();
+                }
+                _ if previous_root.map_or(false, |previous_root| previous_root != root) => {
+                    return invalid(format!("Block at slot {} is missing", slot));
+                }
+                _ => (),
+            }
+            previous_root = Some(root);
+        }
+        if let Some(block) = blocks.next() {
+            return invalid(format!(
+                "Block at slot {} is out of order or outside the era",
+                block.slot()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Encode the era as an e2store file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        write_record(&mut bytes, VERSION, &[]);
+
+        let start_slot = self.start_slot();
+        let mut block_positions = vec![None; E::SlotsPerHistoricalRoot::to_usize()];
+        for block in &self.blocks {
+            let position = block
+                .slot()
+                .as_u64()
+                .checked_sub(start_slot.as_u64())
+                .and_then(|i| block_positions.get_mut(i as usize))
+                .ok_or_else(|| {
+                    Error::EraError(format!("Block at slot {} is outside the era", block.slot()))
+                })?;
+            *position = Some(bytes.len());
+            let data = compress(&block.as_ssz_bytes())?;
+            write_record(&mut bytes, COMPRESSED_SIGNED_BEACON_BLOCK, &data);
+        }
+
+        let state_position = bytes.len();
+        write_record(
+            &mut bytes,
+            COMPRESSED_BEACON_STATE,
+            &compress(&self.state.as_ssz_bytes())?,
+        );
+
+        if self.number() > 0 {
+            write_slot_index(&mut bytes, start_slot, &block_positions);
+        }
+        write_slot_index(&mut bytes, self.state.slot, &[Some(state_position)]);
+
+        Ok(bytes)
+    }
+
+    /// Decode an era from an e2store file.
+    ///
+    /// Records of unknown types are ignored, and the slot indices are not checked, as the blocks
+    /// are read in order.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let mut blocks = vec![];
+        let mut state = None;
+        let mut is_first = true;
+
+        while !bytes.is_empty() {
+            if bytes.len() < HEADER_SIZE {
+                return Err(Error::EraError("Truncated record header".to_string()));
+            }
+            let record_type = [bytes[0], bytes[1]];
+            let length = u32::from_le_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]) as usize;
+            let data = bytes
+                .get(HEADER_SIZE..HEADER_SIZE + length)
+                .ok_or_else(|| Error::EraError("Truncated record".to_string()))?;
+            bytes = &bytes[HEADER_SIZE + length..];
+
+            if is_first && record_type != VERSION {
+                return Err(Error::EraError("Missing version record".to_string()));
+            }
+            is_first = false;
+
+            match record_type {
+                COMPRESSED_SIGNED_BEACON_BLOCK => {
+                    if state.is_some() {
+                        return Err(Error::EraError("Block follows the state".to_string()));
+                    }
+                    blocks.push(SignedBeaconBlock::from_ssz_bytes(&decompress(data)?)?);
+                }
+                COMPRESSED_BEACON_STATE => {
+                    if state.is_some() {
+                        return Err(Error::EraError("More than one state".to_string()));
+                    }
+                    state = Some(BeaconState::from_ssz_bytes(&decompress(data)?)?);
+                }
+                _ => (),
+            }
+        }
+
+        let state = state.ok_or_else(|| Error::EraError("Missing state".to_string()))?;
+        Ok(Self { blocks, state })
+    }
+}
+
+impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold> {
+    /// Load the contents of era `number` from the freezer database.
+    ///
+    /// The era's state must be frozen and available, and its blocks must be stored, which is
+    /// not the case before the anchor of a database started from a checkpoint.
+    pub fn load_era(&self, number: u64) -> Result<Era<E>, Error> {
+        let slots_per_era = E::SlotsPerHistoricalRoot::to_u64();
+        let state_slot = Slot::new(number * slots_per_era);
+        if state_slot >= self.get_split_slot() {
+            return Err(Error::EraError(format!("Era {} is not finalized", number)));
+        }
+
+        let state = self.load_cold_state_by_slot(state_slot)?;
+
+        // Load the block of each slot whose root differs from the slot before, skipping the
+        // first slot's block if it precedes the era.
+        let mut blocks = vec![];
+        let mut previous_root = None;
+        let start_slot = Slot::new(number.saturating_sub(1) * slots_per_era);
+        for slot in (start_slot.as_u64()..state_slot.as_u64()).map(Slot::new) {
+            let root = *state.get_block_root(slot)?;
+            if previous_root == Some(root) {
+                continue;
+            }
+            previous_root = Some(root);
+
+            let block = self.get_block(&root)?.ok_or(Error::BlockNotFound(root))?;
+            if block.slot() == slot {
+                blocks.push(block);
+            }
+        }
+
+        Ok(Era { blocks, state })
+    }
+}
+
+fn write_record(bytes: &mut Vec<u8>, record_type: [u8; 2], data: &[u8]) {
+    bytes.extend_from_slice(&record_type);
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0, 0]);
+    bytes.extend_from_slice(data);
+}
+
+/// Write a slot index for the records at `positions`, which start at `start_slot`.
+fn write_slot_index(bytes: &mut Vec<u8>, start_slot: Slot, positions: &[Option<usize>]) {
+    let index_position = bytes.len() as i64;
+    let mut data = Vec::with_capacity(8 * (positions.len() + 2));
+    data.extend_from_slice(&(start_slot.as_u64() as i64).to_le_bytes());
+    for position in positions {
+        let offset = position.map_or(0, |position| position as i64 - index_position);
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&(positions.len() as i64).to_le_bytes());
+    write_record(bytes, SLOT_INDEX, &data);
+}
+
+fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = FrameEncoder::new(vec![]);
+    encoder
+        .write_all(bytes)
+        .map_err(|e| Error::CompressionError(format!("{:?}", e)))?;
+    encoder
+        .into_inner()
+        .map_err(|e| Error::CompressionError(format!("{:?}", e)))
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut decompressed = vec![];
+    FrameDecoder::new(bytes)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| Error::CompressionError(format!("{:?}", e)))?;
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn slot_index() {
+        let mut bytes = vec![0; 24];
+        write_slot_index(&mut bytes, Slot::new(64), &[Some(8), None, Some(16)]);
+
+        let data = &bytes[24 + HEADER_SIZE..];
+        let read = |i: usize| i64::from_le_bytes(data[i * 8..(i + 1) * 8].try_into().unwrap());
+        assert_eq!(bytes[24..26], SLOT_INDEX);
+        assert_eq!(data.len(), 5 * 8);
+        assert_eq!(
+            (0..5).map(read).collect::<Vec<_>>(),
+            vec![64, -16, 0, -8, 3]
+        );
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        let bytes = (0..10_000).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+        assert_eq!(decompress(&compress(&bytes).unwrap()).unwrap(), bytes);
+    }
+}
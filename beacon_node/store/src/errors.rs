@@ -19,6 +19,9 @@ pub enum Error {
     NoContinuationData,
     SplitPointModified(Slot, Slot),
     ConfigError(StoreConfigError),
+    CompressionError(String),
+    EraError(String),
+    ReadOnly,
 }
 
 impl From<DecodeError> for Error {
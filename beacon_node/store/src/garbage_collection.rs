@@ -1,10 +1,10 @@
-//! Garbage collection process that runs at start-up to clean up the database.
-use crate::hot_cold_store::HotColdDB;
-use crate::{Error, LevelDB, StoreOp};
+//! Garbage collection of states which are no longer needed by the database.
+use crate::hot_cold_store::{HotColdDB, HotColdDBError, HotStateSummary};
+use crate::{BeaconNodeBackend, DBColumn, Error, ItemStore, StoreOp};
 use slog::debug;
-use types::EthSpec;
+use types::{EthSpec, Hash256, Slot};
 
-impl<E> HotColdDB<E, LevelDB<E>, LevelDB<E>>
+impl<E> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>
 where
     E: EthSpec,
 {
@@ -37,3 +37,40 @@ where
         Ok(())
     }
 }
+
+impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold> {
+    /// Return an iterator over the roots and summaries of all states in the hot database.
+    pub fn iter_hot_state_summaries<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = Result<(Hash256, HotStateSummary), Error>> + 'a {
+        self.hot_db
+            .iter_column_keys(DBColumn::BeaconStateSummary)
+            .map(move |state_root| {
+                let state_root = state_root?;
+                let summary = self
+                    .load_hot_state_summary(&state_root)?
+                    .ok_or(HotColdDBError::MissingHotStateSummary(state_root))?;
+                Ok((state_root, summary))
+            })
+    }
+
+    /// Delete the given states from the hot database, returning the number of bytes reclaimed.
+    pub fn delete_hot_states(&self, states: &[(Hash256, Slot)]) -> Result<u64, Error> {
+        let mut bytes = 0;
+        let mut ops = Vec::with_capacity(states.len());
+        for (state_root, slot) in states {
+            for column in &[DBColumn::BeaconState, DBColumn::BeaconStateSummary] {
+                bytes += self
+                    .hot_db
+                    .get_bytes((*column).into(), state_root.as_bytes())?
+                    .map_or(0, |bytes| bytes.len() as u64);
+            }
+            ops.push(StoreOp::DeleteState(*state_root, Some(*slot)));
+        }
+
+        if !ops.is_empty() {
+            self.do_atomically(ops)?;
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,280 @@
+//! Hierarchical diffs between the restore points of the freezer database.
+//!
+//! Restore points are arranged in layers with exponentially increasing spacing. Only the top
+//! layer stores full snapshots, while every other restore point is stored as a diff against the
+//! nearest preceding restore point of the layer above. Loading any restore point therefore
+//! applies at most one diff per layer.
+use crate::{Compression, DBColumn, Error, PartialBeaconState, StoreItem};
+use serde_derive::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::str::FromStr;
+use types::{EthSpec, SharedList, Slot};
+
+/// The spacing of the layers used for new databases: 2^13, 2^16, 2^18 and 2^21 slots.
+pub const DEFAULT_HIERARCHY_EXPONENTS: [u8; 4] = [13, 16, 18, 21];
+
+/// The compression applied to the contents of a diff, which are mostly zeroes.
+const DIFF_COMPRESSION: Compression = Compression::Zstd { level: 1 };
+
+/// The layout of the restore points in the freezer database, fixed when the database is created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct HierarchyConfig {
+    /// The base 2 logarithms of the spacing of each layer, in slots, in increasing order.
+    ///
+    /// The restore points which don't lie on any layer form an implicit bottom layer. If there
+    /// are no layers, every restore point is a full snapshot.
+    pub exponents: Vec<u8>,
+}
+
+impl Default for HierarchyConfig {
+    fn default() -> Self {
+        Self {
+            exponents: DEFAULT_HIERARCHY_EXPONENTS.to_vec(),
+        }
+    }
+}
+
+impl HierarchyConfig {
+    /// The layout of databases created before diffs were introduced, in which every restore
+    /// point is a full snapshot.
+    pub fn snapshots_only() -> Self {
+        Self { exponents: vec![] }
+    }
+
+    /// Check that the layers are in increasing order, and that each of them lies on restore
+    /// points.
+    pub fn validate(&self, slots_per_restore_point: u64) -> Result<(), String> {
+        if self.exponents.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(format!("Hierarchy exponents must be increasing: {}", self));
+        }
+        if let Some(&exponent) = self.exponents.last() {
+            if exponent >= 64 {
+                return Err(format!("Hierarchy exponent {} is too large", exponent));
+            }
+        }
+        if let Some(&exponent) = self.exponents.first() {
+            if (1u64 << exponent) % slots_per_restore_point != 0 {
+                return Err(format!(
+                    "Hierarchy layer spacing 2^{} is not a multiple of the slots per restore \
+                     point ({})",
+                    exponent, slots_per_restore_point
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the slot of the restore point that the restore point at `slot` is stored relative
+    /// to, or `None` if it is stored as a full snapshot.
+    pub fn diff_base_slot(&self, slot: Slot) -> Option<Slot> {
+        let spacings = self
+            .exponents
+            .iter()
+            .map(|exponent| 1u64 << exponent)
+            .collect::<Vec<_>>();
+        let base_layer = spacings
+            .iter()
+            .rposition(|spacing| slot % *spacing == 0)
+            .map_or(0, |layer| layer + 1);
+
+        // The top layer is stored in full, and has no base.
+        let base_spacing = spacings.get(base_layer)?;
+        Some(slot / *base_spacing * *base_spacing)
+    }
+}
+
+impl FromStr for HierarchyConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let exponents = s
+            .split(',')
+            .map(|exponent| {
+                exponent
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid hierarchy exponent: {}", exponent))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { exponents })
+    }
+}
+
+impl fmt::Display for HierarchyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let exponents = self
+            .exponents
+            .iter()
+            .map(|exponent| exponent.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", exponents.join(","))
+    }
+}
+
+impl StoreItem for HierarchyConfig {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// The difference between the partial states of two restore points.
+///
+/// Validators and balances are only ever appended, so they are XORed with those of the base
+/// state, leaving zeroes wherever they are unchanged. The remaining fields are small and stored
+/// in full.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct HDiff {
+    validators: Vec<u8>,
+    balances: Vec<u8>,
+    state: Vec<u8>,
+}
+
+impl HDiff {
+    /// Compute the diff which transforms `base` into `target`.
+    pub fn compute<E: EthSpec>(
+        base: &PartialBeaconState<E>,
+        target: &PartialBeaconState<E>,
+    ) -> Result<Self, Error> {
+        let validators = xor(
+            &base.validators.as_ssz_bytes(),
+            &target.validators.as_ssz_bytes(),
+        );
+        let balances = xor(
+            &base.balances.as_ssz_bytes(),
+            &target.balances.as_ssz_bytes(),
+        );
+
+        let mut state = target.clone();
+        state.validators = SharedList::default();
+        state.balances = SharedList::default();
+
+        Ok(Self {
+            validators: DIFF_COMPRESSION.compress(&validators)?,
+            balances: DIFF_COMPRESSION.compress(&balances)?,
+            state: DIFF_COMPRESSION.compress(&state.as_ssz_bytes())?,
+        })
+    }
+
+    /// Apply the diff to `base`, returning the target state.
+    pub fn apply<E: EthSpec>(
+        &self,
+        base: &PartialBeaconState<E>,
+    ) -> Result<PartialBeaconState<E>, Error> {
+        let validators = xor(
+            &base.validators.as_ssz_bytes(),
+            &DIFF_COMPRESSION.decompress(&self.validators)?,
+        );
+        let balances = xor(
+            &base.balances.as_ssz_bytes(),
+            &DIFF_COMPRESSION.decompress(&self.balances)?,
+        );
+
+        let mut state =
+            PartialBeaconState::from_ssz_bytes(&DIFF_COMPRESSION.decompress(&self.state)?)?;
+        state.validators = SharedList::from_ssz_bytes(&validators)?;
+        state.balances = SharedList::from_ssz_bytes(&balances)?;
+        Ok(state)
+    }
+}
+
+impl StoreItem for HDiff {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconStateDiff
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// XOR the bytes of `target` with those of `base`, returning a value of the same length as
+/// `target`. This is its own inverse.
+fn xor(base: &[u8], target: &[u8]) -> Vec<u8> {
+    target
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ base.get(i).copied().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(config: &HierarchyConfig, slots: &[u64]) -> Vec<Option<u64>> {
+        slots
+            .iter()
+            .map(|slot| {
+                config
+                    .diff_base_slot(Slot::new(*slot))
+                    .map(|base| base.as_u64())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diff_base_slot() {
+        let config = HierarchyConfig {
+            exponents: vec![3, 5, 7],
+        };
+        assert_eq!(
+            slots(&config, &[0, 4, 8, 36, 40, 64, 96, 128, 132, 160]),
+            vec![
+                None,
+                Some(0),
+                Some(0),
+                Some(32),
+                Some(32),
+                Some(0),
+                Some(0),
+                None,
+                Some(128),
+                Some(128)
+            ]
+        );
+
+        assert_eq!(
+            slots(&HierarchyConfig::snapshots_only(), &[0, 64, 128]),
+            vec![None, None, None]
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert!(HierarchyConfig::default().validate(2048).is_ok());
+        assert!(HierarchyConfig::default().validate(8192).is_ok());
+        assert!(HierarchyConfig::snapshots_only().validate(2048).is_ok());
+        assert!(HierarchyConfig {
+            exponents: vec![5, 5]
+        }
+        .validate(32)
+        .is_err());
+        assert!(HierarchyConfig {
+            exponents: vec![5, 9]
+        }
+        .validate(64)
+        .is_err());
+    }
+
+    #[test]
+    fn parse() {
+        let config: HierarchyConfig = "13,16, 18,21".parse().unwrap();
+        assert_eq!(config, HierarchyConfig::default());
+        assert_eq!(config.to_string(), "13,16,18,21");
+        assert!("13,x".parse::<HierarchyConfig>().is_err());
+    }
+}
@@ -1,23 +1,26 @@
+use crate::backend::BeaconNodeBackend;
 use crate::chunked_vector::{
     store_updated_vector, BlockRoots, HistoricalRoots, RandaoMixes, StateRoots,
 };
+use crate::compression::{Compression, CompressionStats};
 use crate::config::{OnDiskStoreConfig, StoreConfig};
 use crate::forwards_iter::HybridForwardsBlockRootsIterator;
+use crate::hdiff::{HDiff, HierarchyConfig};
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use crate::iter::{ParentRootBlockIterator, StateRootsIterator};
 use crate::leveldb_store::BytesKey;
-use crate::leveldb_store::LevelDB;
 use crate::memory_store::MemoryStore;
 use crate::metadata::{
-    CompactionTimestamp, PruningCheckpoint, SchemaVersion, COMPACTION_TIMESTAMP_KEY, CONFIG_KEY,
-    CURRENT_SCHEMA_VERSION, PRUNING_CHECKPOINT_KEY, SCHEMA_VERSION_KEY, SPLIT_KEY,
+    AnchorSlot, CompactionTimestamp, HistoricStateLimits, PruningCheckpoint, SchemaVersion,
+    ANCHOR_SLOT_KEY, COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, CURRENT_SCHEMA_VERSION,
+    FREEZER_COMPRESSION_KEY, HIERARCHY_CONFIG_KEY, HISTORIC_STATES_KEY, PRUNING_CHECKPOINT_KEY,
+    RESTORE_POINT_MIGRATION_KEY, SCHEMA_VERSION_KEY, SPLIT_KEY,
 };
 use crate::metrics;
 use crate::{
-    get_key_for_col, DBColumn, Error, ItemStore, KeyValueStoreOp, PartialBeaconState, StoreItem,
-    StoreOp,
+    get_key_for_col, DBColumn, Error, ItemStore, KeyValueStore, KeyValueStoreOp,
+    PartialBeaconState, SchemaMigrationPlan, StoreItem, StoreOp,
 };
-use leveldb::iterator::LevelDBIterator;
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
 use slog::{debug, error, info, trace, warn, Logger};
@@ -28,12 +31,19 @@ use state_processing::{
     SlotProcessingError,
 };
 use std::convert::TryInto;
+use std::hash::Hash;
 use std::marker::PhantomData;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use types::*;
 
+/// The maximum number of restore points to delete in a single write when pruning historic states.
+const PRUNE_HISTORIC_STATES_BATCH_SIZE: usize = 256;
+
+/// The maximum number of entries to delete in a single write when pruning history.
+const PRUNE_HISTORY_BATCH_SIZE: usize = 1024;
+
 /// Defines how blocks should be replayed on states.
 #[derive(PartialEq)]
 pub enum BlockReplay {
@@ -55,6 +65,10 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     /// States with slots less than `split.slot` are in the cold DB, while states with slots
     /// greater than or equal are in the hot DB.
     split: RwLock<Split>,
+    /// The limits of the restore points stored in the freezer database.
+    historic_states: RwLock<HistoricStateLimits>,
+    /// The layout of the restore points in the freezer database.
+    hierarchy: HierarchyConfig,
     config: StoreConfig,
     /// Cold database containing compact historical data.
     pub cold_db: Cold,
@@ -64,6 +78,9 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     pub hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// LRU cache of the partial states of restore points, keyed by slot. Updated whenever a
+    /// restore point is loaded, including as the base of a diff.
+    historic_state_cache: Mutex<LruCache<Slot, PartialBeaconState<E>>>,
     /// Chain spec.
     spec: ChainSpec,
     /// Logger.
@@ -103,6 +120,14 @@ pub enum HotColdDBError {
         slots_per_epoch: u64,
     },
     RestorePointBlockHashError(BeaconStateError),
+    /// The state at this slot has been pruned, or not yet reconstructed.
+    HistoricStateUnavailable(Slot),
+    /// Historic states must be fully reconstructed or pruned before the restore points can be
+    /// migrated.
+    HistoricStatesIncomplete(HistoricStateLimits),
+    InvalidHierarchy(String),
+    /// The split point can't be moved to the given state.
+    InvalidSplit(String),
     IterationError {
         unexpected_key: BytesKey,
     },
@@ -115,23 +140,30 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
         log: Logger,
     ) -> Result<HotColdDB<E, MemoryStore<E>, MemoryStore<E>>, Error> {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
+        let hierarchy = config.hierarchy.clone().unwrap_or_default();
+        Self::verify_hierarchy(&hierarchy, config.slots_per_restore_point)?;
 
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
+            historic_states: RwLock::new(HistoricStateLimits::default()),
+            hierarchy,
             cold_db: MemoryStore::open(),
             hot_db: MemoryStore::open(),
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            historic_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
             _phantom: PhantomData,
         };
 
+        db.init_historic_state_limits()?;
+
         Ok(db)
     }
 }
 
-impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
+impl<E: EthSpec> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>> {
     /// Open a new or existing database, with the given paths to the hot and cold DBs.
     ///
     /// The `slots_per_restore_point` parameter must be a divisor of `SLOTS_PER_HISTORICAL_ROOT`.
@@ -141,14 +173,62 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         config: StoreConfig,
         spec: ChainSpec,
         log: Logger,
-    ) -> Result<HotColdDB<E, LevelDB<E>, LevelDB<E>>, Error> {
+    ) -> Result<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>, Error> {
+        Self::open_with_migration_check(hot_path, cold_path, config, spec, log, |_, _| Ok(()))
+    }
+
+    /// Open a database as with `open`, calling `check_migration` with the plan of any automatic
+    /// schema migration before it runs.
+    ///
+    /// The migration happens before anything else is written to an existing database, so an
+    /// error from `check_migration` leaves the database as it was found.
+    pub fn open_with_migration_check<F>(
+        hot_path: &Path,
+        cold_path: &Path,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+        check_migration: F,
+    ) -> Result<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>, Error>
+    where
+        F: FnOnce(&Self, &SchemaMigrationPlan) -> Result<(), Error>,
+    {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
 
+        let hot_db = if config.read_only {
+            BeaconNodeBackend::open_read_only(hot_path, Compression::None)?
+        } else {
+            BeaconNodeBackend::open_or_migrate(
+                config.backend,
+                hot_path,
+                Compression::None,
+                config.migrate_backend,
+                &log,
+            )?
+        };
+        let freezer_compression = Self::load_or_init_freezer_compression(&hot_db, &config, &log)?;
+        let hierarchy = Self::load_or_init_hierarchy(&hot_db, &config, &log)?;
+        Self::verify_hierarchy(&hierarchy, config.slots_per_restore_point)?;
+        let cold_db = if config.read_only {
+            BeaconNodeBackend::open_read_only(cold_path, freezer_compression)?
+        } else {
+            BeaconNodeBackend::open_or_migrate(
+                config.backend,
+                cold_path,
+                freezer_compression,
+                config.migrate_backend,
+                &log,
+            )?
+        };
+
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            historic_states: RwLock::new(HistoricStateLimits::default()),
+            hierarchy,
+            cold_db,
+            hot_db,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            historic_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
@@ -156,24 +236,45 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         };
 
         // Ensure that the schema version of the on-disk database matches the software.
-        // If the version is mismatched, an automatic migration will be attempted.
+        // If the version is mismatched, an automatic migration will be attempted, unless the
+        // caller intends to migrate the database explicitly. A read-only database can't be
+        // migrated, so it must already have the current version unless the caller intends to
+        // inspect the schema itself.
         if let Some(schema_version) = db.load_schema_version()? {
-            debug!(
-                db.log,
-                "Attempting schema migration";
-                "from_version" => schema_version.as_u64(),
-                "to_version" => CURRENT_SCHEMA_VERSION.as_u64(),
-            );
-            db.migrate_schema(schema_version, CURRENT_SCHEMA_VERSION)?;
+            if db.config.read_only {
+                if db.config.migrate_schema && schema_version != CURRENT_SCHEMA_VERSION {
+                    return Err(HotColdDBError::UnsupportedSchemaVersion {
+                        target_version: CURRENT_SCHEMA_VERSION,
+                        current_version: schema_version,
+                    }
+                    .into());
+                }
+            } else if db.config.migrate_schema && schema_version != CURRENT_SCHEMA_VERSION {
+                check_migration(&db, &db.plan_schema_migration(CURRENT_SCHEMA_VERSION)?)?;
+                db.migrate_schema(schema_version, CURRENT_SCHEMA_VERSION)?;
+            }
         } else {
             db.store_schema_version(CURRENT_SCHEMA_VERSION)?;
         }
 
+        // Complete any restore point migration which was interrupted before its new frequency
+        // was recorded, so that the config on disk matches the layout of the freezer DB.
+        let disk_config = match db.load_restore_point_migration()? {
+            Some(migrated_config) if db.config.read_only => Some(migrated_config),
+            Some(_) => {
+                db.finish_restore_point_migration()?;
+                db.load_config()?
+            }
+            None => db.load_config()?,
+        };
+
         // Ensure that any on-disk config is compatible with the supplied config.
-        if let Some(disk_config) = db.load_config()? {
+        if let Some(disk_config) = disk_config {
             db.config.check_compatibility(&disk_config)?;
         }
-        db.store_config()?;
+        if !db.config.read_only {
+            db.store_config()?;
+        }
 
         // Load the previous split slot from the database (if any). This ensures we can
         // stop and restart correctly.
@@ -188,6 +289,14 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
             *db.split.write() = split;
         }
 
+        // Apply the historic state policy, which may have changed since the last start-up.
+        db.init_historic_state_limits()?;
+
+        // A read-only database is left exactly as it was found.
+        if db.config.read_only {
+            return Ok(db);
+        }
+
         // Run a garbage collection pass.
         db.remove_garbage()?;
 
@@ -201,27 +310,81 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         Ok(db)
     }
 
+    /// Return the compression of the freezer DB.
+    ///
+    /// The compression of a new database is taken from `config` and recorded in `hot_db`, while
+    /// an existing database keeps the compression it was created with.
+    fn load_or_init_freezer_compression(
+        hot_db: &BeaconNodeBackend<E>,
+        config: &StoreConfig,
+        log: &Logger,
+    ) -> Result<Compression, Error> {
+        let is_new = hot_db.get::<SchemaVersion>(&SCHEMA_VERSION_KEY)?.is_none();
+        if is_new {
+            let compression = config.freezer_compression.unwrap_or_default();
+            hot_db.put(&FREEZER_COMPRESSION_KEY, &compression)?;
+            return Ok(compression);
+        }
+
+        // Databases created before compression was configurable are uncompressed.
+        let on_disk = hot_db
+            .get::<Compression>(&FREEZER_COMPRESSION_KEY)?
+            .unwrap_or_default();
+        match config.freezer_compression {
+            Some(configured) if configured != on_disk => {
+                warn!(
+                    log,
+                    "Ignoring freezer compression for existing database";
+                    "info" => "compression can only be set when the database is created",
+                    "configured" => %configured,
+                    "on_disk" => %on_disk,
+                );
+            }
+            _ => (),
+        }
+        Ok(on_disk)
+    }
+
+    /// Return the layout of the restore points in the freezer DB.
+    ///
+    /// As for the freezer compression, the layout of a new database is taken from `config` and
+    /// recorded in `hot_db`, while an existing database keeps the layout it was created with.
+    fn load_or_init_hierarchy(
+        hot_db: &BeaconNodeBackend<E>,
+        config: &StoreConfig,
+        log: &Logger,
+    ) -> Result<HierarchyConfig, Error> {
+        let is_new = hot_db.get::<SchemaVersion>(&SCHEMA_VERSION_KEY)?.is_none();
+        if is_new {
+            let hierarchy = config.hierarchy.clone().unwrap_or_default();
+            hot_db.put(&HIERARCHY_CONFIG_KEY, &hierarchy)?;
+            return Ok(hierarchy);
+        }
+
+        // Databases created before diffs were introduced only store full snapshots.
+        let on_disk = hot_db
+            .get::<HierarchyConfig>(&HIERARCHY_CONFIG_KEY)?
+            .unwrap_or_else(HierarchyConfig::snapshots_only);
+        match &config.hierarchy {
+            Some(configured) if *configured != on_disk => {
+                warn!(
+                    log,
+                    "Ignoring hierarchy exponents for existing database";
+                    "info" => "the hierarchy can only be set when the database is created",
+                    "configured" => %configured,
+                    "on_disk" => %on_disk,
+                );
+            }
+            _ => (),
+        }
+        Ok(on_disk)
+    }
+
     /// Return an iterator over the state roots of all temporary states.
     pub fn iter_temporary_state_roots<'a>(
         &'a self,
     ) -> impl Iterator<Item = Result<Hash256, Error>> + 'a {
-        let column = DBColumn::BeaconStateTemporary;
-        let start_key =
-            BytesKey::from_vec(get_key_for_col(column.into(), Hash256::zero().as_bytes()));
-
-        let keys_iter = self.hot_db.keys_iter();
-        keys_iter.seek(&start_key);
-
-        keys_iter
-            .take_while(move |key| key.matches_column(column))
-            .map(move |bytes_key| {
-                bytes_key.remove_column(column).ok_or_else(|| {
-                    HotColdDBError::IterationError {
-                        unexpected_key: bytes_key,
-                    }
-                    .into()
-                })
-            })
+        self.hot_db.iter_column_keys(DBColumn::BeaconStateTemporary)
     }
 }
 
@@ -232,11 +395,18 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         block_root: &Hash256,
         block: SignedBeaconBlock<E>,
     ) -> Result<(), Error> {
+        let _timer = metrics::start_operation_timer("put_block", DBColumn::BeaconBlock);
+
         // Store on disk.
         self.hot_db.put(block_root, &block)?;
 
         // Update cache.
-        self.block_cache.lock().put(*block_root, block);
+        put_in_cache(
+            &mut self.block_cache.lock(),
+            *block_root,
+            block,
+            &metrics::BEACON_BLOCK_CACHE_EVICTION_COUNT,
+        );
 
         Ok(())
     }
@@ -244,18 +414,25 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     /// Fetch a block from the store.
     pub fn get_block(&self, block_root: &Hash256) -> Result<Option<SignedBeaconBlock<E>>, Error> {
         metrics::inc_counter(&metrics::BEACON_BLOCK_GET_COUNT);
+        let _timer = metrics::start_operation_timer("get_block", DBColumn::BeaconBlock);
 
         // Check the cache.
         if let Some(block) = self.block_cache.lock().get(block_root) {
             metrics::inc_counter(&metrics::BEACON_BLOCK_CACHE_HIT_COUNT);
             return Ok(Some(block.clone()));
         }
+        metrics::inc_counter(&metrics::BEACON_BLOCK_CACHE_MISS_COUNT);
 
         // Fetch from database.
         match self.hot_db.get::<SignedBeaconBlock<E>>(block_root)? {
             Some(block) => {
                 // Add to cache.
-                self.block_cache.lock().put(*block_root, block.clone());
+                put_in_cache(
+                    &mut self.block_cache.lock(),
+                    *block_root,
+                    block.clone(),
+                    &metrics::BEACON_BLOCK_CACHE_EVICTION_COUNT,
+                );
                 Ok(Some(block))
             }
             None => Ok(None),
@@ -480,7 +657,12 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         for op in &batch {
             match op {
                 StoreOp::PutBlock(block_root, block) => {
-                    guard.put(*block_root, (**block).clone());
+                    put_in_cache(
+                        &mut guard,
+                        *block_root,
+                        (**block).clone(),
+                        &metrics::BEACON_BLOCK_CACHE_EVICTION_COUNT,
+                    );
                 }
 
                 StoreOp::PutState(_, _) => (),
@@ -540,6 +722,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         block_replay: BlockReplay,
     ) -> Result<Option<BeaconState<E>>, Error> {
         metrics::inc_counter(&metrics::BEACON_STATE_HOT_GET_COUNT);
+        let _timer = metrics::start_operation_timer("load_hot_state", DBColumn::BeaconState);
 
         // If the state is marked as temporary, do not return it. It will become visible
         // only once its transaction commits and deletes its temporary flag.
@@ -561,10 +744,20 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             // Optimization to avoid even *thinking* about replaying blocks if we're already
             // on an epoch boundary.
             let state = if slot % E::slots_per_epoch() == 0 {
+                metrics::observe_vec(
+                    &metrics::BEACON_STATE_REPLAYED_BLOCKS,
+                    &["load_hot_state"],
+                    0.0,
+                );
                 boundary_state
             } else {
                 let blocks =
                     self.load_blocks_to_replay(boundary_state.slot, slot, latest_block_root)?;
+                metrics::observe_vec(
+                    &metrics::BEACON_STATE_REPLAYED_BLOCKS,
+                    &["load_hot_state"],
+                    blocks.len() as f64,
+                );
                 self.replay_blocks(boundary_state, blocks, slot, block_replay)?
             };
 
@@ -601,25 +794,87 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             "state_root" => format!("{:?}", state_root)
         );
 
-        // 1. Convert to PartialBeaconState and store that in the DB.
-        let partial_state = PartialBeaconState::from_state_forgetful(state);
-        let op = partial_state.as_kv_store_op(*state_root);
-        ops.push(op);
-
-        // 2. Store updated vector entries.
+        // 1. Store updated vector entries.
         let db = &self.cold_db;
         store_updated_vector(BlockRoots, db, state, &self.spec, ops)?;
         store_updated_vector(StateRoots, db, state, &self.spec, ops)?;
         store_updated_vector(HistoricalRoots, db, state, &self.spec, ops)?;
         store_updated_vector(RandaoMixes, db, state, &self.spec, ops)?;
 
-        // 3. Store restore point.
+        // 2. Store the restore point, unless historic states are being pruned.
+        if self
+            .get_historic_state_limits()
+            .contains_restore_point(state.slot)
+        {
+            self.store_restore_point(state_root, state, ops)?;
+        }
+
+        Ok(())
+    }
+
+    /// Store the partial state of a restore point, and its state root.
+    ///
+    /// The vector entries must be stored separately.
+    fn store_restore_point(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        self.store_restore_point_state(state_root, state, ops)?;
+
         let restore_point_index = state.slot.as_u64() / self.config.slots_per_restore_point;
         self.store_restore_point_hash(restore_point_index, *state_root, ops);
+        Ok(())
+    }
 
+    /// Store the partial state of a restore point under its state root.
+    ///
+    /// The partial state is stored as a diff if the hierarchy places it below another restore
+    /// point which is stored, and in full otherwise.
+    fn store_restore_point_state(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        // Convert to PartialBeaconState and store that in the DB.
+        let partial_state = PartialBeaconState::from_state_forgetful(state);
+        match self.stored_diff_base_slot(state.slot)? {
+            Some(base_slot) => {
+                let base_state = self.load_partial_restore_point(base_slot)?;
+                let diff = HDiff::compute(&base_state, &partial_state)?;
+                ops.push(diff.as_kv_store_op(*state_root));
+            }
+            None => ops.push(partial_state.as_kv_store_op(*state_root)),
+        }
         Ok(())
     }
 
+    /// Return the slot of the restore point that a new restore point at `slot` should be stored
+    /// relative to, or `None` if it should be stored in full.
+    ///
+    /// Diffs are only made against restore points which are stored and will be kept, so that
+    /// databases started from a checkpoint or with pruned historic states remain readable.
+    fn stored_diff_base_slot(&self, slot: Slot) -> Result<Option<Slot>, Error> {
+        match self.hierarchy.diff_base_slot(slot) {
+            Some(base_slot)
+                if self
+                    .get_historic_state_limits()
+                    .contains_restore_point(base_slot) =>
+            {
+                let key = Self::restore_point_key(
+                    base_slot.as_u64() / self.config.slots_per_restore_point,
+                );
+                let is_stored = self
+                    .cold_db
+                    .key_exists(DBColumn::BeaconRestorePoint.into(), key.as_bytes())?;
+                Ok(Some(base_slot).filter(|_| is_stored))
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Try to load a pre-finalization state from the freezer database.
     ///
     /// Return `None` if no state with `state_root` lies in the freezer.
@@ -634,7 +889,15 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     ///
     /// Will reconstruct the state if it lies between restore points.
     pub fn load_cold_state_by_slot(&self, slot: Slot) -> Result<BeaconState<E>, Error> {
+        self.check_historic_state_available(slot)?;
+        let _timer = metrics::start_operation_timer("load_cold_state", DBColumn::BeaconState);
+
         if slot % self.config.slots_per_restore_point == 0 {
+            metrics::observe_vec(
+                &metrics::BEACON_STATE_REPLAYED_BLOCKS,
+                &["load_cold_state"],
+                0.0,
+            );
             let restore_point_idx = slot.as_u64() / self.config.slots_per_restore_point;
             self.load_restore_point_by_index(restore_point_idx)
         } else {
@@ -642,29 +905,96 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         }
     }
 
-    /// Load a restore point state by its `state_root`.
-    fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
-        let mut partial_state: PartialBeaconState<E> = self
-            .cold_db
-            .get(state_root)?
-            .ok_or_else(|| HotColdDBError::MissingRestorePoint(*state_root))?;
+    /// Check that the restore points required to load the frozen state at `slot` are stored.
+    fn check_historic_state_available(&self, slot: Slot) -> Result<(), HotColdDBError> {
+        let limits = self.get_historic_state_limits();
+        let low_restore_point_slot =
+            slot / self.config.slots_per_restore_point * self.config.slots_per_restore_point;
+        let high_restore_point_slot = low_restore_point_slot + self.config.slots_per_restore_point;
 
-        // Fill in the fields of the partial state.
-        partial_state.load_block_roots(&self.cold_db, &self.spec)?;
-        partial_state.load_state_roots(&self.cold_db, &self.spec)?;
-        partial_state.load_historical_roots(&self.cold_db, &self.spec)?;
-        partial_state.load_randao_mixes(&self.cold_db, &self.spec)?;
+        // Intermediate states close to the split are replayed from the split state, rather than
+        // from a high restore point.
+        let available = limits.contains_restore_point(low_restore_point_slot)
+            && (slot == low_restore_point_slot
+                || limits.contains_restore_point(high_restore_point_slot)
+                || high_restore_point_slot >= self.get_split_slot());
 
-        Ok(partial_state.try_into()?)
+        if available {
+            Ok(())
+        } else {
+            Err(HotColdDBError::HistoricStateUnavailable(slot))
+        }
+    }
+
+    /// Load the partial state of the restore point at `slot`, applying diffs to the restore
+    /// points above it in the hierarchy as necessary.
+    fn load_partial_restore_point(&self, slot: Slot) -> Result<PartialBeaconState<E>, Error> {
+        if let Some(partial_state) = self.historic_state_cache.lock().get(&slot) {
+            metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_HIT_COUNT);
+            return Ok(partial_state.clone());
+        }
+        metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_MISS_COUNT);
+
+        let state_root =
+            self.load_restore_point_hash(slot.as_u64() / self.config.slots_per_restore_point)?;
+        let partial_state = if let Some(partial_state) =
+            self.read_freezer_item::<PartialBeaconState<E>>(&state_root)?
+        {
+            partial_state
+        } else {
+            let diff: HDiff = self
+                .read_freezer_item(&state_root)?
+                .ok_or(HotColdDBError::MissingRestorePoint(state_root))?;
+            let base_slot = self
+                .hierarchy
+                .diff_base_slot(slot)
+                .ok_or(HotColdDBError::MissingRestorePoint(state_root))?;
+            diff.apply(&self.load_partial_restore_point(base_slot)?)?
+        };
+
+        put_in_cache(
+            &mut self.historic_state_cache.lock(),
+            slot,
+            partial_state.clone(),
+            &metrics::HISTORIC_STATE_CACHE_EVICTION_COUNT,
+        );
+        Ok(partial_state)
+    }
+
+    /// Remove all restore points from the historic state cache.
+    pub(crate) fn clear_historic_state_cache(&self) {
+        self.historic_state_cache.lock().clear();
     }
 
     /// Load a restore point state by its `restore_point_index`.
-    fn load_restore_point_by_index(
+    pub(crate) fn load_restore_point_by_index(
         &self,
         restore_point_index: u64,
     ) -> Result<BeaconState<E>, Error> {
-        let state_root = self.load_restore_point_hash(restore_point_index)?;
-        self.load_restore_point(&state_root)
+        let slot = Slot::new(restore_point_index * self.config.slots_per_restore_point);
+        let mut partial_state = self.load_partial_restore_point(slot)?;
+
+        // Fill in the fields of the partial state.
+        {
+            let _timer = metrics::start_operation_timer("freezer_read", DBColumn::BeaconBlockRoots);
+            partial_state.load_block_roots(&self.cold_db, &self.spec)?;
+        }
+        {
+            let _timer = metrics::start_operation_timer("freezer_read", DBColumn::BeaconStateRoots);
+            partial_state.load_state_roots(&self.cold_db, &self.spec)?;
+        }
+        {
+            let _timer =
+                metrics::start_operation_timer("freezer_read", DBColumn::BeaconHistoricalRoots);
+            partial_state.load_historical_roots(&self.cold_db, &self.spec)?;
+        }
+        {
+            let _timer =
+                metrics::start_operation_timer("freezer_read", DBColumn::BeaconRandaoMixes);
+            partial_state.load_randao_mixes(&self.cold_db, &self.spec)?;
+        }
+
+        Ok(partial_state.try_into()?)
     }
 
     /// Load a frozen state that lies between restore points.
@@ -694,6 +1024,11 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             slot,
             self.get_high_restore_point_block_root(&high_restore_point, slot)?,
         )?;
+        metrics::observe_vec(
+            &metrics::BEACON_STATE_REPLAYED_BLOCKS,
+            &["load_cold_state"],
+            blocks.len() as f64,
+        );
 
         // 3. Replay the blocks on top of the low restore point.
         self.replay_blocks(low_restore_point, blocks, slot, BlockReplay::Accurate)
@@ -815,19 +1150,762 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(state)
     }
 
+    /// Return statistics about the compression of values written to the freezer DB since it
+    /// was opened, or `None` if the freezer DB does not support compression.
+    pub fn freezer_compression_stats(&self) -> Option<CompressionStats> {
+        self.cold_db.compression_stats()
+    }
+
+    /// Copy a consistent snapshot of the hot and freezer databases into new databases at
+    /// `hot_path` and `cold_path`, while the store remains in use.
+    ///
+    /// The hot DB is copied first. States are only deleted from the hot DB once they have been
+    /// written to the freezer, so the freezer copy contains every state missing from the hot DB
+    /// copy. Any states present in both are overwritten when they are next migrated.
+    pub fn backup(&self, hot_path: &Path, cold_path: &Path) -> Result<BackupSummary, Error> {
+        for path in &[hot_path, cold_path] {
+            if path.exists() {
+                return Err(Error::DBError {
+                    message: format!("Backup destination {} already exists", path.display()),
+                });
+            }
+        }
+
+        info!(
+            self.log,
+            "Starting database backup";
+            "hot_path" => %hot_path.display(),
+            "cold_path" => %cold_path.display(),
+        );
+
+        let hot_db_entries = self.hot_db.backup(hot_path)?;
+        let cold_db_entries = self.cold_db.backup(cold_path).map_err(|e| {
+            let _ = std::fs::remove_dir_all(hot_path);
+            e
+        })?;
+
+        info!(
+            self.log,
+            "Database backup complete";
+            "hot_db_entries" => hot_db_entries,
+            "cold_db_entries" => cold_db_entries,
+        );
+
+        Ok(BackupSummary {
+            hot_db_entries,
+            cold_db_entries,
+        })
+    }
+
     /// Fetch a copy of the current split slot from memory.
     pub fn get_split_slot(&self) -> Slot {
         self.split.read().slot
     }
 
+    /// Initialise the split point of a new database to the state at `anchor_slot`, whose latest
+    /// block is at `anchor_block_slot`.
+    ///
+    /// This is used when starting the chain from a checkpoint state rather than genesis. States
+    /// and blocks prior to the anchor (except genesis) are not available in such a database.
+    pub fn init_anchor(
+        &self,
+        anchor_slot: Slot,
+        anchor_state_root: Hash256,
+        anchor_block_slot: Slot,
+    ) -> Result<(), Error> {
+        let mut split_guard = self.split.write();
+        let split = Split {
+            slot: anchor_slot,
+            state_root: anchor_state_root,
+        };
+        self.store_anchor_slot(anchor_block_slot)?;
+        self.hot_db.put_sync(&SPLIT_KEY, &split)?;
+        *split_guard = split;
+        Ok(())
+    }
+
+    /// Load the slot of the oldest block other than genesis, if the database was started from a
+    /// checkpoint or its history has been pruned.
+    pub fn load_anchor_slot(&self) -> Result<Option<Slot>, Error> {
+        Ok(self
+            .hot_db
+            .get::<AnchorSlot>(&ANCHOR_SLOT_KEY)?
+            .map(|anchor_slot| anchor_slot.0))
+    }
+
+    /// Record the slot of the oldest block other than genesis.
+    fn store_anchor_slot(&self, anchor_block_slot: Slot) -> Result<(), Error> {
+        self.hot_db
+            .put_sync(&ANCHOR_SLOT_KEY, &AnchorSlot(anchor_block_slot))
+    }
+
+    /// Describe the split point of the database and the anchor block of the split state.
+    pub fn get_anchor_info(&self) -> Result<AnchorInfo, Error> {
+        let split = *self.split.read();
+        let anchor_block_root = self
+            .load_hot_state_summary(&split.state_root)?
+            .map(|summary| summary.latest_block_root());
+        let anchor_block_slot = match anchor_block_root {
+            Some(block_root) => self.get_block(&block_root)?.map(|block| block.slot()),
+            None => None,
+        };
+
+        Ok(AnchorInfo {
+            split_slot: split.slot,
+            split_state_root: split.state_root,
+            split_state_stored: anchor_block_root.is_some(),
+            anchor_block_root,
+            anchor_block_slot,
+            latest_restore_point_slot: self.get_latest_restore_point_slot(),
+            historic_state_limits: self.get_historic_state_limits(),
+        })
+    }
+
+    /// Check that the split point could be moved back to the state at `slot` with root
+    /// `state_root`, without changing anything.
+    ///
+    /// The state must be a full state in the hot database, on an epoch boundary, and no earlier
+    /// than the latest restore point before the current split. The freezer then holds every state
+    /// prior to it, and the next migration can freeze the states between it and the current split
+    /// again without needing any hot states which have already been deleted.
+    pub fn check_split(&self, slot: Slot, state_root: Hash256) -> Result<(), Error> {
+        let invalid = |reason: String| Err(HotColdDBError::InvalidSplit(reason).into());
+        let current_split_slot = self.get_split_slot();
+
+        if slot > current_split_slot {
+            return invalid(format!(
+                "slot {} is later than the current split slot {}",
+                slot, current_split_slot
+            ));
+        }
+        if slot % E::slots_per_epoch() != 0 {
+            return invalid(format!("slot {} is not on an epoch boundary", slot));
+        }
+        let latest_restore_point_slot = self.get_latest_restore_point_slot();
+        if slot < latest_restore_point_slot {
+            return invalid(format!(
+                "slot {} precedes the latest restore point at slot {}",
+                slot, latest_restore_point_slot
+            ));
+        }
+        match self.load_hot_state_summary(&state_root)? {
+            Some(summary) if summary.slot() == slot => (),
+            Some(summary) => {
+                return invalid(format!(
+                    "state {:?} is at slot {}, not {}",
+                    state_root,
+                    summary.slot(),
+                    slot
+                ))
+            }
+            None => {
+                return invalid(format!(
+                    "state {:?} is not stored in the hot database",
+                    state_root
+                ))
+            }
+        }
+        if get_full_state(&self.hot_db, &state_root)?.is_none() {
+            return invalid(format!("full state {:?} is missing", state_root));
+        }
+
+        Ok(())
+    }
+
+    /// Move the split point back to the state at `slot` with root `state_root`, after checking
+    /// it with `check_split`.
+    ///
+    /// This is a recovery tool for a database whose split state has been lost or corrupted while
+    /// an earlier state survives in the hot database. It must not be used while a beacon node has
+    /// the database open.
+    pub fn set_split(&self, slot: Slot, state_root: Hash256) -> Result<(), Error> {
+        self.check_split(slot, state_root)?;
+
+        let mut split_guard = self.split.write();
+        let split = Split { slot, state_root };
+        self.hot_db.put_sync(&SPLIT_KEY, &split)?;
+        *split_guard = split;
+
+        info!(
+            self.log,
+            "Moved the split point";
+            "slot" => slot,
+            "state_root" => ?state_root,
+        );
+        Ok(())
+    }
+
     /// Fetch the slot of the most recently stored restore point.
     pub fn get_latest_restore_point_slot(&self) -> Slot {
         (self.get_split_slot() - 1) / self.config.slots_per_restore_point
             * self.config.slots_per_restore_point
     }
 
+    /// Return the configuration of the store.
+    pub fn get_config(&self) -> &StoreConfig {
+        &self.config
+    }
+
+    /// Return `true` if the store was opened read-only, in which case all writes fail.
+    pub fn is_read_only(&self) -> bool {
+        self.config.read_only
+    }
+
+    /// Fetch a copy of the current historic state limits from memory.
+    pub fn get_historic_state_limits(&self) -> HistoricStateLimits {
+        *self.historic_states.read()
+    }
+
+    /// Load the historic state limits from disk, and update them for the configured policy.
+    ///
+    /// Enabling pruning stops restore points from being stored, and the existing ones are deleted
+    /// by `prune_historic_states`. Disabling it resumes storing restore points from the next one
+    /// to be frozen, leaving a gap which remains unavailable until it is reconstructed.
+    fn init_historic_state_limits(&self) -> Result<(), Error> {
+        let on_disk = self
+            .hot_db
+            .get::<HistoricStateLimits>(&HISTORIC_STATES_KEY)?;
+        let mut limits = on_disk.unwrap_or_default();
+
+        // The policy of a read-only database can't be changed.
+        if self.config.read_only {
+            *self.historic_states.write() = limits;
+            return Ok(());
+        }
+
+        if self.config.prune_historic_states == Some(true) && !limits.is_pruned() {
+            info!(
+                self.log,
+                "Enabling historic state pruning";
+                "info" => "restore points other than genesis will be deleted"
+            );
+            limits = HistoricStateLimits::pruned();
+        } else if self.config.prune_historic_states == Some(false) && limits.is_pruned() {
+            let slots_per_restore_point = self.config.slots_per_restore_point;
+            let state_upper_limit = (self.get_split_slot() + slots_per_restore_point - 1)
+                / slots_per_restore_point
+                * slots_per_restore_point;
+            info!(
+                self.log,
+                "Disabling historic state pruning";
+                "states_stored_from_slot" => state_upper_limit,
+            );
+            limits = HistoricStateLimits {
+                state_lower_limit: Slot::new(0),
+                state_upper_limit,
+            };
+        }
+
+        if on_disk != Some(limits) {
+            self.hot_db.put_sync(&HISTORIC_STATES_KEY, &limits)?;
+        }
+        *self.historic_states.write() = limits;
+
+        Ok(())
+    }
+
+    /// Update the historic state limits in memory and on disk.
+    fn store_historic_state_limits(&self, limits: HistoricStateLimits) -> Result<(), Error> {
+        let mut historic_states = self.historic_states.write();
+        self.hot_db.put_sync(&HISTORIC_STATES_KEY, &limits)?;
+        *historic_states = limits;
+        Ok(())
+    }
+
+    /// Delete the restore points which lie outside the historic state limits.
+    ///
+    /// The vector entries and cold state summaries are kept, so that the block roots and state
+    /// roots of pruned states remain available, and the states themselves can be reconstructed.
+    pub fn prune_historic_states(&self) -> Result<(), Error> {
+        let limits = self.get_historic_state_limits();
+        if limits.is_complete() {
+            return Ok(());
+        }
+
+        let latest_restore_point_index =
+            self.get_latest_restore_point_slot().as_u64() / self.config.slots_per_restore_point;
+
+        let mut ops = vec![];
+        let mut num_pruned = 0;
+        for restore_point_index in 1..=latest_restore_point_index {
+            let slot = Slot::new(restore_point_index * self.config.slots_per_restore_point);
+            if limits.contains_restore_point(slot) {
+                continue;
+            }
+
+            if self.delete_restore_point_ops(restore_point_index, &mut ops)? {
+                num_pruned += 1;
+            }
+
+            if ops.len() >= PRUNE_HISTORIC_STATES_BATCH_SIZE * 3 {
+                self.cold_db.do_atomically(std::mem::take(&mut ops))?;
+            }
+        }
+        self.cold_db.do_atomically(ops)?;
+        self.clear_historic_state_cache();
+
+        if num_pruned > 0 {
+            info!(
+                self.log,
+                "Pruned historic states";
+                "restore_points_deleted" => num_pruned
+            );
+            self.cold_db.compact()?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the blocks and frozen states which precede the split, other than those of genesis.
+    ///
+    /// This leaves the database as if it had been started from a checkpoint at the split, so that
+    /// an archive node can become a lean node without re-syncing. The vector entries of the
+    /// freezer are kept, as they are small and those around the split are still required. If
+    /// interrupted, pruning can be resumed by running it again.
+    pub fn prune_history(&self) -> Result<HistoryPruningSummary, Error> {
+        let split = *self.split.read();
+        let mut summary = HistoryPruningSummary::default();
+        if split.slot == 0 {
+            return Ok(summary);
+        }
+
+        // The block of the split state is the oldest block which is kept, other than genesis.
+        let anchor_block_root = self
+            .load_hot_state_summary(&split.state_root)?
+            .ok_or(HotColdDBError::MissingHotStateSummary(split.state_root))?
+            .latest_block_root();
+        let anchor_slot = self
+            .get_block(&anchor_block_root)?
+            .ok_or(Error::BlockNotFound(anchor_block_root))?
+            .slot();
+
+        info!(
+            self.log,
+            "Pruning history";
+            "anchor_slot" => anchor_slot,
+            "split_slot" => split.slot,
+        );
+
+        // The anchor is recorded first, so that an interrupted pruning doesn't leave any missing
+        // blocks unexplained.
+        self.store_anchor_slot(anchor_slot)?;
+
+        // 1. Delete the blocks.
+        let block_roots = self
+            .hot_db
+            .iter_column_keys(DBColumn::BeaconBlock)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut ops = vec![];
+        for block_root in block_roots {
+            let slot = self
+                .hot_db
+                .get::<SignedBeaconBlock<E>>(&block_root)?
+                .ok_or(Error::BlockNotFound(block_root))?
+                .slot();
+            if slot > 0 && slot < anchor_slot {
+                ops.push(StoreOp::DeleteBlock(block_root));
+                summary.blocks_deleted += 1;
+            }
+
+            if ops.len() >= PRUNE_HISTORY_BATCH_SIZE {
+                self.do_atomically(std::mem::take(&mut ops))?;
+            }
+        }
+        self.do_atomically(ops)?;
+
+        // 2. Delete the restore points.
+        let latest_restore_point_index =
+            self.get_latest_restore_point_slot().as_u64() / self.config.slots_per_restore_point;
+        let mut ops = vec![];
+        for restore_point_index in 1..=latest_restore_point_index {
+            if self.delete_restore_point_ops(restore_point_index, &mut ops)? {
+                summary.restore_points_deleted += 1;
+            }
+
+            if ops.len() >= PRUNE_HISTORY_BATCH_SIZE {
+                self.cold_db.do_atomically(std::mem::take(&mut ops))?;
+            }
+        }
+        self.cold_db.do_atomically(ops)?;
+        self.clear_historic_state_cache();
+
+        // 3. Delete the summaries of frozen states, so that their roots are no longer recognised.
+        let state_roots = self
+            .cold_db
+            .iter_column_keys(DBColumn::BeaconStateSummary)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut ops = vec![];
+        for state_root in state_roots {
+            if self.load_cold_state_slot(&state_root)? != Some(Slot::new(0)) {
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconStateSummary.into(),
+                    state_root.as_bytes(),
+                )));
+                summary.state_summaries_deleted += 1;
+            }
+
+            if ops.len() >= PRUNE_HISTORY_BATCH_SIZE {
+                self.cold_db.do_atomically(std::mem::take(&mut ops))?;
+            }
+        }
+        self.cold_db.do_atomically(ops)?;
+
+        // The pruned states can no longer be reconstructed, so there is no gap left to fill.
+        if !self.get_historic_state_limits().is_pruned() {
+            self.store_historic_state_limits(HistoricStateLimits::default())?;
+        }
+
+        info!(
+            self.log,
+            "Pruned history";
+            "blocks_deleted" => summary.blocks_deleted,
+            "restore_points_deleted" => summary.restore_points_deleted,
+            "state_summaries_deleted" => summary.state_summaries_deleted,
+        );
+        self.hot_db.compact()?;
+        self.cold_db.compact()?;
+
+        Ok(summary)
+    }
+
+    /// Push the operations to delete the restore point at `restore_point_index` to `ops`.
+    ///
+    /// Return `false` if the restore point is not stored.
+    fn delete_restore_point_ops(
+        &self,
+        restore_point_index: u64,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<bool, Error> {
+        let key = Self::restore_point_key(restore_point_index);
+        let state_root = match self.cold_db.get(&key)? {
+            Some(RestorePointHash { state_root }) => state_root,
+            None => return Ok(false),
+        };
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconState.into(),
+            state_root.as_bytes(),
+        )));
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconStateDiff.into(),
+            state_root.as_bytes(),
+        )));
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconRestorePoint.into(),
+            key.as_bytes(),
+        )));
+        Ok(true)
+    }
+
+    /// Reconstruct up to `max_restore_points` of the restore points between the historic state
+    /// limits, by replaying blocks forwards from the lower limit.
+    ///
+    /// The lower limit is persisted after each restore point, so that reconstruction resumes
+    /// where it left off after a restart. Only frozen states can be reconstructed, so it may need
+    /// to wait for the split to advance before it completes.
+    ///
+    /// Return `true` if further restore points can be reconstructed immediately.
+    pub fn reconstruct_historic_states(
+        self: &Arc<Self>,
+        max_restore_points: usize,
+    ) -> Result<bool, Error> {
+        let mut limits = self.get_historic_state_limits();
+        if limits.is_complete() || limits.is_pruned() {
+            return Ok(false);
+        }
+
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let latest_restore_point_slot = self.get_latest_restore_point_slot();
+        let next_slot = limits.state_lower_limit + slots_per_restore_point;
+        if next_slot > latest_restore_point_slot && next_slot < limits.state_upper_limit {
+            return Ok(false);
+        }
+
+        debug!(
+            self.log,
+            "Reconstructing historic states";
+            "lower_limit" => limits.state_lower_limit,
+            "upper_limit" => limits.state_upper_limit,
+        );
+
+        // The block roots of frozen states are read from the freezer DB, falling back to the
+        // split state for the slots after the latest restore point.
+        let split = *self.split.read();
+        let split_state = self.get_state(&split.state_root, Some(split.slot))?.ok_or(
+            HotColdDBError::MissingSplitState(split.state_root, split.slot),
+        )?;
+        let split_block_root = split_state.get_latest_block_root(split.state_root);
+        let block_roots = Self::forwards_block_roots_iterator(
+            self.clone(),
+            next_slot,
+            split_state,
+            split_block_root,
+            &self.spec,
+        )?;
+
+        let mut state = self.load_restore_point_by_index(
+            limits.state_lower_limit.as_u64() / slots_per_restore_point,
+        )?;
+        let mut num_reconstructed = 0;
+
+        for result in block_roots {
+            let (block_root, slot) = result?;
+            if slot % slots_per_restore_point != 0 {
+                continue;
+            }
+            if slot >= limits.state_upper_limit || slot > latest_restore_point_slot {
+                break;
+            }
+            if num_reconstructed == max_restore_points {
+                return Ok(true);
+            }
+
+            let blocks = self.load_blocks_to_replay(state.slot, slot, block_root)?;
+            state = self.replay_blocks(state, blocks, slot, BlockReplay::Accurate)?;
+            let state_root = state.update_tree_hash_cache()?;
+
+            let mut ops = vec![];
+            self.store_restore_point(&state_root, &state, &mut ops)?;
+            self.cold_db.do_atomically(ops)?;
+
+            limits.state_lower_limit = slot;
+            self.store_historic_state_limits(limits)?;
+            num_reconstructed += 1;
+        }
+
+        if limits.state_lower_limit + slots_per_restore_point >= limits.state_upper_limit {
+            limits.state_lower_limit = limits.state_upper_limit;
+            self.store_historic_state_limits(limits)?;
+            info!(
+                self.log,
+                "Historic state reconstruction complete";
+                "upper_limit" => limits.state_upper_limit,
+            );
+        } else if num_reconstructed > 0 {
+            info!(
+                self.log,
+                "Reconstructed historic states";
+                "states_available_to_slot" => limits.state_lower_limit,
+                "upper_limit" => limits.state_upper_limit,
+            );
+        }
+
+        Ok(false)
+    }
+
+    /// Rewrite the restore points of the freezer database to be `slots_per_restore_point` apart,
+    /// and record the new frequency on disk.
+    ///
+    /// The database must be re-opened with the new frequency afterwards.
+    pub fn migrate_slots_per_restore_point(
+        self: &Arc<Self>,
+        slots_per_restore_point: u64,
+    ) -> Result<(), Error> {
+        self.migrate_restore_point_layout(slots_per_restore_point)?;
+        self.finish_restore_point_migration()
+    }
+
+    /// Rewrite the restore points of the freezer database to be `slots_per_restore_point` apart,
+    /// the first part of `migrate_slots_per_restore_point`.
+    ///
+    /// Restore points which are missing from the new layout are created by replaying blocks
+    /// forwards from the existing ones, and those which are not part of it are deleted. The
+    /// restore points of the hierarchy's layers are common to both layouts, so every diff keeps
+    /// its base. The restore point index is switched over in a single batch once all of the new
+    /// states are stored, so the database keeps its old layout if the migration is interrupted
+    /// before then.
+    ///
+    /// The same batch records the new frequency in the freezer database, and it is copied to the
+    /// config of the hot database by `finish_restore_point_migration`. That happens when the
+    /// database is next opened if the migration is interrupted in between.
+    pub fn migrate_restore_point_layout(
+        self: &Arc<Self>,
+        slots_per_restore_point: u64,
+    ) -> Result<(), Error> {
+        Self::verify_slots_per_restore_point(slots_per_restore_point)?;
+        Self::verify_hierarchy(&self.hierarchy, slots_per_restore_point)?;
+
+        let old_slots_per_restore_point = self.config.slots_per_restore_point;
+        if slots_per_restore_point == old_slots_per_restore_point {
+            return Ok(());
+        }
+
+        let limits = self.get_historic_state_limits();
+        if !limits.is_complete() && !limits.is_pruned() {
+            return Err(HotColdDBError::HistoricStatesIncomplete(limits).into());
+        }
+
+        let split = *self.split.read();
+        let latest_restore_point_slot = if split.slot > 0 {
+            (split.slot - 1) / slots_per_restore_point * slots_per_restore_point
+        } else {
+            Slot::new(0)
+        };
+        let old_latest_restore_point_index =
+            self.get_latest_restore_point_slot().as_u64() / old_slots_per_restore_point;
+
+        // The state roots of the restore points in the new layout. Databases started from a
+        // checkpoint have no restore points prior to their anchor, other than genesis.
+        let mut state_roots = vec![];
+        let mut first_stored_slot = None;
+        for old_index in 0..=old_latest_restore_point_index {
+            let key = Self::restore_point_key(old_index);
+            if let Some(RestorePointHash { state_root }) = self.cold_db.get(&key)? {
+                let slot = Slot::new(old_index * old_slots_per_restore_point);
+                if slot > 0 && first_stored_slot.is_none() {
+                    first_stored_slot = Some(slot);
+                }
+                if slot % slots_per_restore_point == 0 && slot <= latest_restore_point_slot {
+                    state_roots.push((slot, state_root));
+                }
+            }
+        }
+
+        info!(
+            self.log,
+            "Migrating restore points";
+            "from_slots_per_restore_point" => old_slots_per_restore_point,
+            "to_slots_per_restore_point" => slots_per_restore_point,
+        );
+
+        // 1. Create the restore points which are new to the layout, reading the block roots
+        // from the freezer DB and then the split state, as for reconstruction.
+        let mut num_created = 0;
+        if let Some(start_slot) =
+            first_stored_slot.filter(|_| slots_per_restore_point < old_slots_per_restore_point)
+        {
+            let split_state = self.get_state(&split.state_root, Some(split.slot))?.ok_or(
+                HotColdDBError::MissingSplitState(split.state_root, split.slot),
+            )?;
+            let split_block_root = split_state.get_latest_block_root(split.state_root);
+            let block_roots = Self::forwards_block_roots_iterator(
+                self.clone(),
+                start_slot,
+                split_state,
+                split_block_root,
+                &self.spec,
+            )?;
+
+            let mut state: Option<BeaconState<E>> = None;
+            for result in block_roots {
+                let (block_root, slot) = result?;
+                if slot > latest_restore_point_slot {
+                    break;
+                }
+                if slot % slots_per_restore_point != 0
+                    || slot % old_slots_per_restore_point == 0
+                    || !limits.contains_restore_point(slot)
+                {
+                    continue;
+                }
+
+                // Replay from the previous new restore point if it lies in the same interval of
+                // the old layout, and from the old restore point below it otherwise.
+                let old_index = slot.as_u64() / old_slots_per_restore_point;
+                let old_slot = Slot::new(old_index * old_slots_per_restore_point);
+                let base_state = match state.take() {
+                    Some(state) if state.slot >= old_slot => state,
+                    _ => self.load_restore_point_by_index(old_index)?,
+                };
+
+                let blocks = self.load_blocks_to_replay(base_state.slot, slot, block_root)?;
+                let mut new_state =
+                    self.replay_blocks(base_state, blocks, slot, BlockReplay::Accurate)?;
+                let state_root = new_state.update_tree_hash_cache()?;
+
+                let mut ops = vec![];
+                self.store_restore_point_state(&state_root, &new_state, &mut ops)?;
+                self.cold_db.do_atomically(ops)?;
+
+                debug!(
+                    self.log,
+                    "Created restore point";
+                    "slot" => slot,
+                    "latest_restore_point_slot" => latest_restore_point_slot,
+                );
+                state_roots.push((slot, state_root));
+                state = Some(new_state);
+                num_created += 1;
+            }
+        }
+
+        // 2. Switch the restore point index over to the new layout, deleting the states of the
+        // restore points which are not part of it.
+        let mut ops = vec![];
+        let mut num_deleted = 0;
+        for old_index in 0..=old_latest_restore_point_index {
+            let key = Self::restore_point_key(old_index);
+            if let Some(RestorePointHash { state_root }) = self.cold_db.get(&key)? {
+                if old_index * old_slots_per_restore_point % slots_per_restore_point != 0 {
+                    ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        DBColumn::BeaconState.into(),
+                        state_root.as_bytes(),
+                    )));
+                    ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        DBColumn::BeaconStateDiff.into(),
+                        state_root.as_bytes(),
+                    )));
+                    num_deleted += 1;
+                }
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconRestorePoint.into(),
+                    key.as_bytes(),
+                )));
+            }
+        }
+        for (slot, state_root) in state_roots {
+            self.store_restore_point_hash(
+                slot.as_u64() / slots_per_restore_point,
+                state_root,
+                &mut ops,
+            );
+        }
+        let config = OnDiskStoreConfig {
+            slots_per_restore_point,
+            ..self.config.as_disk_config()
+        };
+        ops.push(config.as_kv_store_op(RESTORE_POINT_MIGRATION_KEY));
+        self.cold_db.do_atomically(ops)?;
+        self.clear_historic_state_cache();
+
+        info!(
+            self.log,
+            "Migrated restore points";
+            "slots_per_restore_point" => slots_per_restore_point,
+            "restore_points_created" => num_created,
+            "restore_points_deleted" => num_deleted,
+        );
+
+        if num_deleted > 0 {
+            self.cold_db.compact()?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the frequency of a migrated restore point layout to the config of the hot database,
+    /// which makes the new layout visible. Does nothing unless a migration is in progress.
+    pub fn finish_restore_point_migration(&self) -> Result<(), Error> {
+        if let Some(config) = self.load_restore_point_migration()? {
+            self.hot_db.put_sync(&CONFIG_KEY, &config)?;
+            self.cold_db
+                .delete::<OnDiskStoreConfig>(&RESTORE_POINT_MIGRATION_KEY)?;
+            info!(
+                self.log,
+                "Finished restore point migration";
+                "slots_per_restore_point" => config.slots_per_restore_point,
+            );
+        }
+        Ok(())
+    }
+
+    /// Load the config of a restore point layout which has been migrated, but not yet recorded
+    /// in the hot database.
+    fn load_restore_point_migration(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
+        self.cold_db.get(&RESTORE_POINT_MIGRATION_KEY)
+    }
+
     /// Load the database schema version from disk.
-    fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
+    pub(crate) fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
         self.hot_db.get(&SCHEMA_VERSION_KEY)
     }
 
@@ -852,10 +1930,12 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load the state root of a restore point.
-    fn load_restore_point_hash(&self, restore_point_index: u64) -> Result<Hash256, Error> {
+    pub(crate) fn load_restore_point_hash(
+        &self,
+        restore_point_index: u64,
+    ) -> Result<Hash256, Error> {
         let key = Self::restore_point_key(restore_point_index);
-        self.cold_db
-            .get(&key)?
+        self.read_freezer_item(&key)?
             .map(|r: RestorePointHash| r.state_root)
             .ok_or_else(|| HotColdDBError::MissingRestorePointHash(restore_point_index).into())
     }
@@ -880,11 +1960,16 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     /// Load a frozen state's slot, given its root.
     fn load_cold_state_slot(&self, state_root: &Hash256) -> Result<Option<Slot>, Error> {
         Ok(self
-            .cold_db
-            .get(state_root)?
+            .read_freezer_item(state_root)?
             .map(|s: ColdStateSummary| s.slot))
     }
 
+    /// Read an item from the freezer database, timing the read by the item's column.
+    fn read_freezer_item<I: StoreItem>(&self, key: &Hash256) -> Result<Option<I>, Error> {
+        let _timer = metrics::start_operation_timer("freezer_read", I::db_column());
+        self.cold_db.get(key)
+    }
+
     /// Load a hot state's summary, given its root.
     pub fn load_hot_state_summary(
         &self,
@@ -934,6 +2019,16 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         }
     }
 
+    /// Verify that the layers of `hierarchy` lie on restore points.
+    fn verify_hierarchy(
+        hierarchy: &HierarchyConfig,
+        slots_per_restore_point: u64,
+    ) -> Result<(), HotColdDBError> {
+        hierarchy
+            .validate(slots_per_restore_point)
+            .map_err(HotColdDBError::InvalidHierarchy)
+    }
+
     /// Run a compaction pass to free up space used by deleted states.
     pub fn compact(&self) -> Result<(), Error> {
         self.hot_db.compact()?;
@@ -975,6 +2070,20 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 }
 
+/// Insert an entry into an LRU cache, incrementing `evictions` if another entry is evicted to make
+/// room for it.
+fn put_in_cache<K: Hash + Eq, V>(
+    cache: &mut LruCache<K, V>,
+    key: K,
+    value: V,
+    evictions: &metrics::Result<metrics::IntCounter>,
+) {
+    if cache.cap() > 0 && cache.len() == cache.cap() && !cache.contains(&key) {
+        metrics::inc_counter(evictions);
+    }
+    cache.put(key, value);
+}
+
 /// Advance the split point of the store, moving new finalized states to the freezer.
 pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     store: Arc<HotColdDB<E, Hot, Cold>>,
@@ -1005,6 +2114,12 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     }
 
     let mut hot_db_ops: Vec<StoreOp<E>> = Vec::new();
+    let mut cold_db_ops: Vec<KeyValueStoreOp> = Vec::new();
+    let mut restore_points = vec![];
+
+    // Large writes stall the block imports which are writing to the databases at the same time,
+    // so states are copied and deleted in batches.
+    let batch_size = store.config.migration_batch_size.max(1);
 
     // 1. Copy all of the states between the head and the split slot, from the hot DB
     // to the cold DB.
@@ -1015,27 +2130,37 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     }) {
         let (state_root, slot) = maybe_pair?;
 
-        let mut cold_db_ops: Vec<KeyValueStoreOp> = Vec::new();
-
         if slot % store.config.slots_per_restore_point == 0 {
-            let state: BeaconState<E> = get_full_state(&store.hot_db, &state_root)?
-                .ok_or_else(|| HotColdDBError::MissingStateToFreeze(state_root))?;
-
-            store.store_cold_state(&state_root, &state, &mut cold_db_ops)?;
+            restore_points.push(state_root);
         }
 
         // Store a pointer from this state root to its slot, so we can later reconstruct states
         // from their state root alone.
         let cold_state_summary = ColdStateSummary { slot };
-        let op = cold_state_summary.as_kv_store_op(state_root);
-        cold_db_ops.push(op);
+        cold_db_ops.push(cold_state_summary.as_kv_store_op(state_root));
+        if cold_db_ops.len() >= batch_size {
+            store
+                .cold_db
+                .do_atomically(std::mem::take(&mut cold_db_ops))?;
+        }
+
+        // Delete the old summary, and the full state if we lie on an epoch boundary.
+        hot_db_ops.push(StoreOp::DeleteState(state_root, Some(slot)));
+    }
+
+    store.cold_db.do_atomically(cold_db_ops)?;
+
+    // Restore points are stored in ascending order of slot, as they may be stored as diffs
+    // against earlier restore points.
+    for state_root in restore_points.into_iter().rev() {
+        let state: BeaconState<E> = get_full_state(&store.hot_db, &state_root)?
+            .ok_or_else(|| HotColdDBError::MissingStateToFreeze(state_root))?;
 
         // There are data dependencies between calls to `store_cold_state()` that prevent us from
         // doing one big call to `store.cold_db.do_atomically()` at end of the loop.
+        let mut cold_db_ops: Vec<KeyValueStoreOp> = Vec::new();
+        store.store_cold_state(&state_root, &state, &mut cold_db_ops)?;
         store.cold_db.do_atomically(cold_db_ops)?;
-
-        // Delete the old summary, and the full state if we lie on an epoch boundary.
-        hot_db_ops.push(StoreOp::DeleteState(state_root, Some(slot)));
     }
 
     // Warning: Critical section.  We have to take care not to put any of the two databases in an
@@ -1086,8 +2211,12 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         *split_guard = split;
     }
 
-    // Delete the states from the hot database if we got this far.
-    store.do_atomically(hot_db_ops)?;
+    // Delete the states from the hot database if we got this far. States left behind by a crash
+    // between batches lie before the split, and are removed by garbage collection.
+    while !hot_db_ops.is_empty() {
+        let rest = hot_db_ops.split_off(batch_size.min(hot_db_ops.len()));
+        store.do_atomically(std::mem::replace(&mut hot_db_ops, rest))?;
+    }
 
     debug!(
         store.log,
@@ -1098,6 +2227,39 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     Ok(())
 }
 
+/// The number of entries copied into each database by `HotColdDB::backup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupSummary {
+    pub hot_db_entries: usize,
+    pub cold_db_entries: usize,
+}
+
+/// The number of entries deleted by `HotColdDB::prune_history`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryPruningSummary {
+    pub blocks_deleted: usize,
+    pub restore_points_deleted: usize,
+    pub state_summaries_deleted: usize,
+}
+
+/// The split point of the database and the anchor of the chain it holds, as described by
+/// `HotColdDB::get_anchor_info`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnchorInfo {
+    /// States prior to this slot are frozen.
+    pub split_slot: Slot,
+    pub split_state_root: Hash256,
+    /// Whether the split state is stored in the hot database, as it should be.
+    pub split_state_stored: bool,
+    /// The latest block applied to the split state, which is the oldest block that the hot
+    /// database refers to, or `None` if the split state is missing.
+    pub anchor_block_root: Option<Hash256>,
+    /// The slot of the anchor block, or `None` if it is missing.
+    pub anchor_block_slot: Option<Slot>,
+    pub latest_restore_point_slot: Slot,
+    pub historic_state_limits: HistoricStateLimits,
+}
+
 /// Struct for storing the split slot and state root in the database.
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
 pub struct Split {
@@ -1125,8 +2287,8 @@ impl StoreItem for Split {
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
 pub struct HotStateSummary {
     slot: Slot,
-    latest_block_root: Hash256,
-    epoch_boundary_state_root: Hash256,
+    pub(crate) latest_block_root: Hash256,
+    pub(crate) epoch_boundary_state_root: Hash256,
 }
 
 impl StoreItem for HotStateSummary {
@@ -1144,6 +2306,16 @@ impl StoreItem for HotStateSummary {
 }
 
 impl HotStateSummary {
+    /// The slot of the state.
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    /// The root of the latest block applied to the state.
+    pub fn latest_block_root(&self) -> Hash256 {
+        self.latest_block_root
+    }
+
     /// Construct a new summary of the given state.
     pub fn new<E: EthSpec>(state_root: &Hash256, state: &BeaconState<E>) -> Result<Self, Error> {
         // Fill in the state root on the latest block header if necessary (this happens on all
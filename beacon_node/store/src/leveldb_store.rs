@@ -1,4 +1,5 @@
 use super::*;
+use crate::hot_cold_store::HotColdDBError;
 use crate::metrics;
 use db_key::Key;
 use leveldb::compaction::Compaction;
@@ -6,7 +7,7 @@ use leveldb::database::batch::{Batch, Writebatch};
 use leveldb::database::kv::KV;
 use leveldb::database::Database;
 use leveldb::error::Error as LevelDBError;
-use leveldb::iterator::{Iterable, KeyIterator};
+use leveldb::iterator::{Iterable, KeyIterator, LevelDBIterator};
 use leveldb::options::{Options, ReadOptions, WriteOptions};
 use parking_lot::{Mutex, MutexGuard};
 use std::marker::PhantomData;
@@ -75,6 +76,17 @@ impl<E: EthSpec> LevelDB<E> {
     pub fn keys_iter(&self) -> KeyIterator<BytesKey> {
         self.db.keys_iter(self.read_options())
     }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    pub fn for_each_entry(
+        &self,
+        mut f: impl FnMut(Vec<u8>, Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        for (key, value) in self.db.iter(self.read_options()) {
+            f(key.key, value)?;
+        }
+        Ok(())
+    }
 }
 
 impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
@@ -174,6 +186,30 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
         }
         Ok(())
     }
+
+    fn iter_column_keys<'a>(
+        &'a self,
+        column: DBColumn,
+    ) -> Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a> {
+        let start_key =
+            BytesKey::from_vec(get_key_for_col(column.into(), Hash256::zero().as_bytes()));
+
+        let keys_iter = self.keys_iter();
+        keys_iter.seek(&start_key);
+
+        Box::new(
+            keys_iter
+                .take_while(move |key| key.matches_column(column))
+                .map(move |bytes_key| {
+                    bytes_key.remove_column(column).ok_or_else(|| {
+                        HotColdDBError::IterationError {
+                            unexpected_key: bytes_key,
+                        }
+                        .into()
+                    })
+                }),
+        )
+    }
 }
 
 impl<E: EthSpec> ItemStore<E> for LevelDB<E> {}
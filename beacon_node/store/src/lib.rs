@@ -2,7 +2,7 @@
 //!
 //! Provides the following stores:
 //!
-//! - `HotColdDB`: an on-disk store backed by leveldb. Used in production.
+//! - `HotColdDB`: an on-disk store backed by leveldb or redb. Used in production.
 //! - `MemoryStore`: an in-memory store backed by a hash-map. Used for testing.
 //!
 //! Provides a simple API for storing/retrieving all types that sometimes needs type-hints. See
@@ -10,12 +10,16 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod backend;
 pub mod chunked_iter;
 pub mod chunked_vector;
+mod compression;
 pub mod config;
+mod era;
 pub mod errors;
 mod forwards_iter;
 mod garbage_collection;
+mod hdiff;
 pub mod hot_cold_store;
 mod impls;
 mod leveldb_store;
@@ -23,19 +27,37 @@ mod memory_store;
 mod metadata;
 mod metrics;
 mod partial_beacon_state;
+#[cfg(feature = "redb")]
+mod redb_store;
 mod schema_change;
+mod stats;
+mod verify;
 
 pub mod iter;
 
-pub use self::config::StoreConfig;
-pub use self::hot_cold_store::{BlockReplay, HotColdDB, HotStateSummary, Split};
+pub use self::backend::BeaconNodeBackend;
+pub use self::compression::{Compression, CompressionStats};
+pub use self::config::{DatabaseBackend, StoreConfig};
+pub use self::era::Era;
+pub use self::hdiff::{HDiff, HierarchyConfig};
+pub use self::hot_cold_store::{
+    AnchorInfo, BackupSummary, BlockReplay, HistoryPruningSummary, HotColdDB, HotStateSummary,
+    Split,
+};
 pub use self::leveldb_store::LevelDB;
 pub use self::memory_store::MemoryStore;
+pub use self::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION};
 pub use self::partial_beacon_state::PartialBeaconState;
+#[cfg(feature = "redb")]
+pub use self::redb_store::Redb;
+pub use self::schema_change::{SchemaMigration, SchemaMigrationPlan, SchemaMigrationStep};
+pub use self::stats::{ColumnStats, DatabaseGrowth, DatabaseStats};
+pub use self::verify::{Inconsistency, VerificationReport};
 pub use errors::Error;
 pub use impls::beacon_state::StorageContainer as BeaconStateStorageContainer;
 pub use metrics::scrape_for_metrics;
 use parking_lot::MutexGuard;
+use std::path::Path;
 pub use types::*;
 
 pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
@@ -70,6 +92,26 @@ pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
 
     /// Compact the database, freeing space used by deleted items.
     fn compact(&self) -> Result<(), Error>;
+
+    /// Return an iterator over the keys of all entries in `column`, in key order, with the column
+    /// prefix removed.
+    fn iter_column_keys<'a>(
+        &'a self,
+        column: DBColumn,
+    ) -> Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a>;
+
+    /// Return statistics about the compression of values written to the database, if any.
+    fn compression_stats(&self) -> Option<CompressionStats> {
+        None
+    }
+
+    /// Copy a consistent snapshot of the database into a new database at `dest`, returning the
+    /// number of entries copied.
+    fn backup(&self, _dest: &Path) -> Result<usize, Error> {
+        Err(Error::DBError {
+            message: "Backups are not supported by this database".into(),
+        })
+    }
 }
 
 pub fn get_key_for_col(column: &str, key: &[u8]) -> Vec<u8> {
@@ -155,6 +197,8 @@ pub enum DBColumn {
     ForkChoice,
     /// For the table mapping restore point numbers to state roots.
     BeaconRestorePoint,
+    /// For restore points stored as diffs against an earlier restore point.
+    BeaconStateDiff,
     /// For the mapping from state roots to their slots or summaries.
     BeaconStateSummary,
     /// For the list of temporary states stored during block import,
@@ -165,6 +209,8 @@ pub enum DBColumn {
     BeaconHistoricalRoots,
     BeaconRandaoMixes,
     DhtEnrs,
+    /// For the validator public key cache, keyed by validator index.
+    PubkeyCache,
 }
 
 impl Into<&'static str> for DBColumn {
@@ -179,6 +225,7 @@ impl Into<&'static str> for DBColumn {
             DBColumn::Eth1Cache => "etc",
             DBColumn::ForkChoice => "frk",
             DBColumn::BeaconRestorePoint => "brp",
+            DBColumn::BeaconStateDiff => "bsd",
             DBColumn::BeaconStateSummary => "bss",
             DBColumn::BeaconStateTemporary => "bst",
             DBColumn::BeaconBlockRoots => "bbr",
@@ -186,11 +233,33 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconHistoricalRoots => "bhr",
             DBColumn::BeaconRandaoMixes => "brm",
             DBColumn::DhtEnrs => "dht",
+            DBColumn::PubkeyCache => "pkc",
         }
     }
 }
 
 impl DBColumn {
+    /// Every column, in the order they are declared.
+    pub const ALL: &'static [DBColumn] = &[
+        DBColumn::BeaconMeta,
+        DBColumn::BeaconBlock,
+        DBColumn::BeaconState,
+        DBColumn::BeaconChain,
+        DBColumn::OpPool,
+        DBColumn::Eth1Cache,
+        DBColumn::ForkChoice,
+        DBColumn::BeaconRestorePoint,
+        DBColumn::BeaconStateDiff,
+        DBColumn::BeaconStateSummary,
+        DBColumn::BeaconStateTemporary,
+        DBColumn::BeaconBlockRoots,
+        DBColumn::BeaconStateRoots,
+        DBColumn::BeaconHistoricalRoots,
+        DBColumn::BeaconRandaoMixes,
+        DBColumn::DhtEnrs,
+        DBColumn::PubkeyCache,
+    ];
+
     pub fn as_str(self) -> &'static str {
         self.into()
     }
@@ -275,6 +344,16 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    #[cfg(feature = "redb")]
+    fn redb() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let store = Redb::open(&path).unwrap();
+
+        test_impl(store);
+    }
+
     #[test]
     fn memorydb() {
         let store = MemoryStore::open();
@@ -298,4 +377,28 @@ mod tests {
 
         assert_eq!(store.exists::<StorableThing>(&key).unwrap(), false);
     }
+
+    #[test]
+    fn memorydb_fail_writes() {
+        let store = MemoryStore::<MinimalEthSpec>::open();
+        let key = Hash256::random();
+        let item = StorableThing { a: 1, b: 42 };
+        store.put(&key, &item).unwrap();
+
+        store.set_fail_writes(true);
+        let batch = vec![
+            StorableThing { a: 2, b: 43 }.as_kv_store_op(key),
+            item.as_kv_store_op(Hash256::random()),
+        ];
+        assert!(store.do_atomically(batch).is_err());
+        assert!(store.delete::<StorableThing>(&key).is_err());
+        assert_eq!(
+            store.get(&key).unwrap(),
+            Some(StorableThing { a: 1, b: 42 })
+        );
+
+        store.set_fail_writes(false);
+        store.delete::<StorableThing>(&key).unwrap();
+        assert_eq!(store.exists::<StorableThing>(&key).unwrap(), false);
+    }
 }
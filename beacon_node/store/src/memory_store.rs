@@ -1,7 +1,10 @@
-use super::{Error, ItemStore, KeyValueStore, KeyValueStoreOp};
+use super::{DBColumn, Error, ItemStore, KeyValueStore, KeyValueStoreOp};
+use crate::hot_cold_store::HotColdDBError;
+use crate::leveldb_store::BytesKey;
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
 use types::*;
 
 type DBHashMap = HashMap<Vec<u8>, Vec<u8>>;
@@ -10,6 +13,8 @@ type DBHashMap = HashMap<Vec<u8>, Vec<u8>>;
 pub struct MemoryStore<E: EthSpec> {
     db: RwLock<DBHashMap>,
     transaction_mutex: Mutex<()>,
+    /// Fail all writes, to simulate a disk failure.
+    fail_writes: AtomicBool,
     _phantom: PhantomData<E>,
 }
 
@@ -19,10 +24,46 @@ impl<E: EthSpec> MemoryStore<E> {
         Self {
             db: RwLock::new(HashMap::new()),
             transaction_mutex: Mutex::new(()),
+            fail_writes: AtomicBool::new(false),
             _phantom: PhantomData,
         }
     }
 
+    /// Make all subsequent writes fail (or succeed again), leaving the database unchanged.
+    ///
+    /// This is used to test the recovery from a write which doesn't reach the disk, e.g. due to a
+    /// crash or a full disk.
+    pub fn set_fail_writes(&self, fail_writes: bool) {
+        self.fail_writes.store(fail_writes, Ordering::SeqCst);
+    }
+
+    fn check_write(&self) -> Result<(), Error> {
+        if self.fail_writes.load(Ordering::SeqCst) {
+            Err(Error::DBError {
+                message: "Injected write failure".to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    ///
+    /// Writes to the database are blocked until `f` has been called with every pair.
+    pub fn for_each_entry(
+        &self,
+        mut f: impl FnMut(Vec<u8>, Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let db = self.db.read();
+        let mut entries = db.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+
+        for (key, value) in entries {
+            f(key.clone(), value.clone())?;
+        }
+        Ok(())
+    }
+
     fn get_key_for_col(col: &str, key: &[u8]) -> Vec<u8> {
         let mut col = col.as_bytes().to_vec();
         col.append(&mut key.to_vec());
@@ -39,6 +80,7 @@ impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
 
     /// Puts a key in the database.
     fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.check_write()?;
         let column_key = Self::get_key_for_col(col, key);
         self.db.write().insert(column_key, val.to_vec());
         Ok(())
@@ -61,20 +103,24 @@ impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
 
     /// Delete some key from the database.
     fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        self.check_write()?;
         let column_key = Self::get_key_for_col(col, key);
         self.db.write().remove(&column_key);
         Ok(())
     }
 
+    /// The whole batch is applied under a single lock, so that it is atomic for readers.
     fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        self.check_write()?;
+        let mut db = self.db.write();
         for op in batch {
             match op {
                 KeyValueStoreOp::PutKeyValue(key, value) => {
-                    self.db.write().insert(key, value);
+                    db.insert(key, value);
                 }
 
                 KeyValueStoreOp::DeleteKey(hash) => {
-                    self.db.write().remove(&hash);
+                    db.remove(&hash);
                 }
             }
         }
@@ -88,6 +134,31 @@ impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
     fn compact(&self) -> Result<(), Error> {
         Ok(())
     }
+
+    /// The keys are collected up-front, so the database isn't locked during iteration.
+    fn iter_column_keys<'a>(
+        &'a self,
+        column: DBColumn,
+    ) -> Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a> {
+        let mut keys = self
+            .db
+            .read()
+            .keys()
+            .filter(|key| key.starts_with(column.as_bytes()))
+            .cloned()
+            .collect::<Vec<_>>();
+        keys.sort();
+
+        Box::new(keys.into_iter().map(move |key| {
+            let bytes_key = BytesKey::from_vec(key);
+            bytes_key.remove_column(column).ok_or_else(|| {
+                HotColdDBError::IterationError {
+                    unexpected_key: bytes_key,
+                }
+                .into()
+            })
+        }))
+    }
 }
 
 impl<E: EthSpec> ItemStore<E> for MemoryStore<E> {}
@@ -1,6 +1,7 @@
 use crate::{DBColumn, Error, StoreItem};
 use ssz::{Decode, Encode};
-use types::{Checkpoint, Hash256};
+use ssz_derive::{Decode, Encode};
+use types::{Checkpoint, Hash256, Slot};
 
 pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(2);
 
@@ -12,6 +13,11 @@ pub const CONFIG_KEY: Hash256 = Hash256::repeat_byte(1);
 pub const SPLIT_KEY: Hash256 = Hash256::repeat_byte(2);
 pub const PRUNING_CHECKPOINT_KEY: Hash256 = Hash256::repeat_byte(3);
 pub const COMPACTION_TIMESTAMP_KEY: Hash256 = Hash256::repeat_byte(4);
+pub const FREEZER_COMPRESSION_KEY: Hash256 = Hash256::repeat_byte(5);
+pub const HISTORIC_STATES_KEY: Hash256 = Hash256::repeat_byte(6);
+pub const HIERARCHY_CONFIG_KEY: Hash256 = Hash256::repeat_byte(7);
+pub const RESTORE_POINT_MIGRATION_KEY: Hash256 = Hash256::repeat_byte(8);
+pub const ANCHOR_SLOT_KEY: Hash256 = Hash256::repeat_byte(9);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SchemaVersion(pub u64);
@@ -76,3 +82,82 @@ impl StoreItem for CompactionTimestamp {
         Ok(CompactionTimestamp(u64::from_ssz_bytes(bytes)?))
     }
 }
+
+/// The slot of the oldest block other than genesis, in a database which was started from a
+/// checkpoint or whose history has been pruned. The parent of this block is not stored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnchorSlot(pub Slot);
+
+impl StoreItem for AnchorSlot {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(AnchorSlot(Slot::from_ssz_bytes(bytes)?))
+    }
+}
+
+/// The range of slots for which restore points are kept in the freezer database.
+///
+/// Restore points are stored for slots less than or equal to `state_lower_limit`, and for slots
+/// greater than or equal to `state_upper_limit`. Historic states between the two are unavailable
+/// until they are reconstructed, which advances the lower limit towards the upper limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct HistoricStateLimits {
+    pub state_lower_limit: Slot,
+    pub state_upper_limit: Slot,
+}
+
+impl Default for HistoricStateLimits {
+    /// All restore points are kept.
+    fn default() -> Self {
+        Self {
+            state_lower_limit: Slot::new(0),
+            state_upper_limit: Slot::new(0),
+        }
+    }
+}
+
+impl HistoricStateLimits {
+    /// Only the genesis restore point is kept.
+    pub fn pruned() -> Self {
+        Self {
+            state_lower_limit: Slot::new(0),
+            state_upper_limit: Slot::max_value(),
+        }
+    }
+
+    /// Return `true` if restore points are no longer being stored.
+    pub fn is_pruned(&self) -> bool {
+        self.state_upper_limit == Slot::max_value()
+    }
+
+    /// Return `true` if there are no unavailable historic states.
+    pub fn is_complete(&self) -> bool {
+        self.state_lower_limit >= self.state_upper_limit
+    }
+
+    /// Return `true` if the restore point at `slot` should be kept.
+    pub fn contains_restore_point(&self, slot: Slot) -> bool {
+        slot <= self.state_lower_limit || slot >= self.state_upper_limit
+    }
+}
+
+impl StoreItem for HistoricStateLimits {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
@@ -1,5 +1,6 @@
 pub use lighthouse_metrics::{set_gauge, try_create_int_gauge, *};
 
+use crate::DBColumn;
 use directory::size_of_dir;
 use std::path::Path;
 
@@ -43,6 +44,32 @@ lazy_static! {
         "store_disk_db_delete_count_total",
         "Total number of deletions from the hot on-disk DB"
     );
+    pub static ref DISK_DB_COMPRESSION_INPUT_BYTES: Result<IntCounter> = try_create_int_counter(
+        "store_disk_db_compression_input_bytes_total",
+        "Number of bytes passed to compression before being written to an on-disk DB"
+    );
+    pub static ref DISK_DB_COMPRESSION_OUTPUT_BYTES: Result<IntCounter> = try_create_int_counter(
+        "store_disk_db_compression_output_bytes_total",
+        "Number of compressed bytes written to an on-disk DB"
+    );
+    /*
+     * Store operations
+     */
+    pub static ref STORE_OPERATION_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "store_operation_seconds",
+        "Time taken by a store operation, labelled by the column it reads or writes",
+        &["operation", "column"]
+    );
+    pub static ref BEACON_STATE_REPLAYED_BLOCKS: Result<HistogramVec> =
+        try_create_histogram_vec_with_buckets(
+            "store_beacon_state_replayed_blocks",
+            "Number of blocks loaded to be replayed when loading a beacon state",
+            vec![
+                0.0, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0, 1024.0, 2048.0,
+                4096.0, 8192.0
+            ],
+            &["operation"]
+        );
     /*
      * Beacon State
      */
@@ -58,6 +85,18 @@ lazy_static! {
         "store_beacon_state_cache_hit_total",
         "Number of hits to the store's state cache"
     );
+    pub static ref HISTORIC_STATE_CACHE_HIT_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_cache_hit_total",
+        "Number of hits to the store's historic state cache"
+    );
+    pub static ref HISTORIC_STATE_CACHE_MISS_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_cache_miss_total",
+        "Number of misses to the store's historic state cache"
+    );
+    pub static ref HISTORIC_STATE_CACHE_EVICTION_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_cache_eviction_total",
+        "Number of restore points evicted from the store's full historic state cache"
+    );
     pub static ref BEACON_STATE_CACHE_CLONE_TIME: Result<Histogram> = try_create_histogram(
         "store_beacon_state_cache_clone_time",
         "Time to load a beacon block from the block cache"
@@ -101,6 +140,14 @@ lazy_static! {
         "store_beacon_block_cache_hit_total",
         "Number of hits to the store's block cache"
     );
+    pub static ref BEACON_BLOCK_CACHE_MISS_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_block_cache_miss_total",
+        "Number of misses to the store's block cache"
+    );
+    pub static ref BEACON_BLOCK_CACHE_EVICTION_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_block_cache_eviction_total",
+        "Number of blocks evicted from the store's full block cache"
+    );
     pub static ref BEACON_BLOCK_READ_TIMES: Result<Histogram> = try_create_histogram(
         "store_beacon_block_read_overhead_seconds",
         "Overhead on reading a beacon block from the DB (e.g., decoding)"
@@ -134,3 +181,8 @@ pub fn scrape_for_metrics(db_path: &Path, freezer_db_path: &Path) {
     let freezer_db_size = size_of_dir(freezer_db_path);
     set_gauge(&FREEZER_DB_SIZE, freezer_db_size as i64);
 }
+
+/// Starts a timer for `operation` on `column`, which is observed when it is dropped.
+pub fn start_operation_timer(operation: &str, column: DBColumn) -> Option<HistogramTimer> {
+    start_timer_vec(&STORE_OPERATION_TIMES, &[operation, column.as_str()])
+}
@@ -43,8 +43,8 @@ where
     pub eth1_deposit_index: u64,
 
     // Registry
-    pub validators: VariableList<Validator, T::ValidatorRegistryLimit>,
-    pub balances: VariableList<u64, T::ValidatorRegistryLimit>,
+    pub validators: SharedList<Validator, T::ValidatorRegistryLimit>,
+    pub balances: SharedList<u64, T::ValidatorRegistryLimit>,
 
     // Shuffling
     /// Randao value from the current slot, for patching into the per-epoch randao vector.
@@ -222,6 +222,7 @@ impl<E: EthSpec> TryInto<BeaconState<E>> for PartialBeaconState<E> {
             committee_caches: <_>::default(),
             pubkey_cache: <_>::default(),
             exit_cache: <_>::default(),
+            progressive_balances_cache: <_>::default(),
             tree_hash_cache: <_>::default(),
         })
     }
@@ -0,0 +1,226 @@
+use super::*;
+use crate::backend::REDB_FILENAME;
+use crate::hot_cold_store::HotColdDBError;
+use crate::leveldb_store::BytesKey;
+use crate::metrics;
+use parking_lot::{Mutex, MutexGuard};
+use redb::{Database, Durability, ReadableTable, TableDefinition};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// All columns share a single table, with keys prefixed by their column (as for `LevelDB`).
+const TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("lighthouse");
+
+/// A wrapped redb database.
+pub struct Redb<E: EthSpec> {
+    db: Database,
+    /// A mutex to synchronise sensitive read-write transactions.
+    transaction_mutex: Mutex<()>,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: EthSpec> Redb<E> {
+    /// Open a database in the directory at `path`, creating a new database if one does not
+    /// already exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        std::fs::create_dir_all(path).map_err(|e| Error::DBError {
+            message: format!("Unable to create {}: {:?}", path.display(), e),
+        })?;
+
+        let db = Database::create(path.join(REDB_FILENAME)).map_err(redb::Error::from)?;
+
+        // Create the table up-front so that read transactions can always open it.
+        let tx = db.begin_write().map_err(redb::Error::from)?;
+        tx.open_table(TABLE).map_err(redb::Error::from)?;
+        tx.commit().map_err(redb::Error::from)?;
+
+        Ok(Self {
+            db,
+            transaction_mutex: Mutex::new(()),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Apply `ops` in a single write transaction with the given `durability`.
+    fn write(&self, ops: Vec<KeyValueStoreOp>, durability: Durability) -> Result<(), redb::Error> {
+        let mut tx = self.db.begin_write()?;
+        tx.set_durability(durability);
+        {
+            let mut table = tx.open_table(TABLE)?;
+            for op in ops {
+                match op {
+                    KeyValueStoreOp::PutKeyValue(key, value) => {
+                        table.insert(key.as_slice(), value.as_slice())?;
+                    }
+
+                    KeyValueStoreOp::DeleteKey(key) => {
+                        table.remove(key.as_slice())?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn put_bytes_with_durability(
+        &self,
+        col: &str,
+        key: &[u8],
+        val: &[u8],
+        durability: Durability,
+    ) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+        metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, val.len() as i64);
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        self.write(
+            vec![KeyValueStoreOp::PutKeyValue(column_key, val.to_vec())],
+            durability,
+        )
+        .map_err(Into::into)
+        .map(|()| {
+            metrics::stop_timer(timer);
+        })
+    }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    pub fn for_each_entry(
+        &self,
+        mut f: impl FnMut(Vec<u8>, Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let tx = self.db.begin_read().map_err(redb::Error::from)?;
+        let table = tx.open_table(TABLE).map_err(redb::Error::from)?;
+        for entry in table.iter().map_err(redb::Error::from)? {
+            let (key, value) = entry.map_err(redb::Error::from)?;
+            f(key.value().to_vec(), value.value().to_vec())?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for Redb<E> {
+    /// Store some `value` in `column`, indexed with `key`.
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.put_bytes_with_durability(col, key, val, Durability::Eventual)
+    }
+
+    fn put_bytes_sync(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.put_bytes_with_durability(col, key, val, Durability::Immediate)
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        self.put_bytes_sync("sync", b"sync", b"sync")
+    }
+
+    /// Retrieve some bytes in `column` with `key`.
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_READ_COUNT);
+        let timer = metrics::start_timer(&metrics::DISK_DB_READ_TIMES);
+
+        (|| -> Result<Option<Vec<u8>>, redb::Error> {
+            let tx = self.db.begin_read()?;
+            let table = tx.open_table(TABLE)?;
+            let value = table.get(column_key.as_slice())?;
+            Ok(value.map(|value| value.value().to_vec()))
+        })()
+        .map_err(Into::into)
+        .map(|opt| {
+            opt.map(|bytes| {
+                metrics::inc_counter_by(&metrics::DISK_DB_READ_BYTES, bytes.len() as i64);
+                metrics::stop_timer(timer);
+                bytes
+            })
+        })
+    }
+
+    /// Return `true` if `key` exists in `column`.
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_EXISTS_COUNT);
+
+        (|| -> Result<bool, redb::Error> {
+            let tx = self.db.begin_read()?;
+            let table = tx.open_table(TABLE)?;
+            let exists = table.get(column_key.as_slice())?.is_some();
+            Ok(exists)
+        })()
+        .map_err(Into::into)
+    }
+
+    /// Removes `key` from `column`.
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+
+        self.write(
+            vec![KeyValueStoreOp::DeleteKey(column_key)],
+            Durability::Eventual,
+        )
+        .map_err(Into::into)
+    }
+
+    fn do_atomically(&self, ops_batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        self.write(ops_batch, Durability::Eventual)
+            .map_err(Into::into)
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        self.transaction_mutex.lock()
+    }
+
+    /// No-op: redb re-uses the pages freed by deletions, so there is no write amplification to
+    /// reclaim as there is with LevelDB.
+    fn compact(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The keys are collected up-front, as they cannot outlive the read transaction.
+    fn iter_column_keys<'a>(
+        &'a self,
+        column: DBColumn,
+    ) -> Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a> {
+        let keys = (|| -> Result<Vec<Vec<u8>>, redb::Error> {
+            let tx = self.db.begin_read()?;
+            let table = tx.open_table(TABLE)?;
+            let mut keys = vec![];
+            for entry in table.range::<&[u8]>(column.as_bytes()..)? {
+                let (key, _) = entry?;
+                if !key.value().starts_with(column.as_bytes()) {
+                    break;
+                }
+                keys.push(key.value().to_vec());
+            }
+            Ok(keys)
+        })();
+
+        match keys {
+            Ok(keys) => Box::new(keys.into_iter().map(move |key| {
+                let bytes_key = BytesKey::from_vec(key);
+                bytes_key.remove_column(column).ok_or_else(|| {
+                    HotColdDBError::IterationError {
+                        unexpected_key: bytes_key,
+                    }
+                    .into()
+                })
+            })),
+            Err(e) => Box::new(std::iter::once(Err(e.into()))),
+        }
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for Redb<E> {}
+
+impl From<redb::Error> for Error {
+    fn from(e: redb::Error) -> Error {
+        Error::DBError {
+            message: format!("{:?}", e),
+        }
+    }
+}
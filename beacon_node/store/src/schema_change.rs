@@ -1,32 +1,230 @@
 //! Utilities for managing database schema changes.
+//!
+//! Adjacent schema versions are connected by migrations, which may exist in either direction. A
+//! database is migrated between two versions by applying each migration between them in turn,
+//! recording the schema version after each one so that an interrupted migration can be resumed.
 use crate::hot_cold_store::{HotColdDB, HotColdDBError};
 use crate::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION};
-use crate::{Error, ItemStore};
+use crate::{BeaconNodeBackend, DBColumn, Error, KeyValueStore};
+use slog::info;
+use std::time::{Duration, Instant};
 use types::EthSpec;
 
-impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+/// A rough rate at which migrations rewrite entries, used to estimate their duration.
+const ESTIMATED_ENTRIES_PER_SECOND: u64 = 10_000;
+/// A rough upper bound on the disk space taken by each entry a migration rewrites or deletes,
+/// until the database is compacted.
+const ESTIMATED_BYTES_PER_ENTRY: u64 = 1024;
+
+/// A migration between two adjacent schema versions.
+#[derive(Debug, PartialEq)]
+pub struct SchemaMigration {
+    pub from: SchemaVersion,
+    pub to: SchemaVersion,
+    /// A summary of the change, for operators.
+    pub description: &'static str,
+    /// The hot database columns whose entries are rewritten or deleted by the migration.
+    pub columns: &'static [DBColumn],
+}
+
+/// All the supported migrations. There is no way to migrate between versions which aren't
+/// connected by these, e.g. to downgrade past a change which can't be undone.
+pub const SCHEMA_MIGRATIONS: &[SchemaMigration] = &[
+    // Migration from v0.3.0 to v0.3.x, adding the temporary states column. Nothing actually needs
+    // to be done.
+    SchemaMigration {
+        from: SchemaVersion(1),
+        to: SchemaVersion(2),
+        description: "Add the temporary states column",
+        columns: &[],
+    },
+    // v0.3.0 doesn't garbage collect temporary states, so they're deleted before going back.
+    SchemaMigration {
+        from: SchemaVersion(2),
+        to: SchemaVersion(1),
+        description: "Delete temporary states, which v1 can't garbage collect",
+        columns: &[DBColumn::BeaconStateTemporary],
+    },
+];
+
+/// Return the migrations to apply, in order, to migrate from one schema version to another.
+pub fn schema_migration_path(
+    from: SchemaVersion,
+    to: SchemaVersion,
+) -> Result<Vec<&'static SchemaMigration>, Error> {
+    let unsupported = || HotColdDBError::UnsupportedSchemaVersion {
+        target_version: to,
+        current_version: from,
+    };
+
+    let mut path = vec![];
+    let mut version = from;
+    while version != to {
+        let next = if version < to {
+            SchemaVersion(version.as_u64() + 1)
+        } else {
+            SchemaVersion(version.as_u64() - 1)
+        };
+        let migration = SCHEMA_MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == version && migration.to == next)
+            .ok_or_else(unsupported)?;
+        path.push(migration);
+        version = next;
+    }
+    Ok(path)
+}
+
+/// A migration and the number of entries it will rewrite or delete.
+#[derive(Debug, PartialEq)]
+pub struct SchemaMigrationStep {
+    pub migration: &'static SchemaMigration,
+    pub entries: u64,
+}
+
+impl SchemaMigrationStep {
+    /// A rough estimate of the time that the step will take.
+    pub fn estimated_duration(&self) -> Duration {
+        Duration::from_secs(self.entries / ESTIMATED_ENTRIES_PER_SECOND)
+    }
+
+    /// A rough estimate of the free disk space that the step requires.
+    pub fn estimated_bytes(&self) -> u64 {
+        self.entries * ESTIMATED_BYTES_PER_ENTRY
+    }
+}
+
+/// The steps of a migration between two schema versions, which may be empty.
+#[derive(Debug, PartialEq)]
+pub struct SchemaMigrationPlan {
+    pub from: SchemaVersion,
+    pub to: SchemaVersion,
+    pub steps: Vec<SchemaMigrationStep>,
+}
+
+impl SchemaMigrationPlan {
+    /// A rough estimate of the time that the whole migration will take.
+    pub fn estimated_duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .map(SchemaMigrationStep::estimated_duration)
+            .sum()
+    }
+
+    /// A rough estimate of the free disk space that the whole migration requires.
+    pub fn estimated_bytes(&self) -> u64 {
+        self.steps
+            .iter()
+            .map(SchemaMigrationStep::estimated_bytes)
+            .sum()
+    }
+}
+
+impl<E> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>
 where
     E: EthSpec,
-    Hot: ItemStore<E>,
-    Cold: ItemStore<E>,
 {
+    /// Return the schema version of the database, which is current for a new database.
+    pub fn schema_version(&self) -> Result<SchemaVersion, Error> {
+        Ok(self
+            .load_schema_version()?
+            .unwrap_or(CURRENT_SCHEMA_VERSION))
+    }
+
+    /// Work out the migrations required to migrate the database to schema version `to`, and how
+    /// many entries each would touch, without changing anything.
+    pub fn plan_schema_migration(&self, to: SchemaVersion) -> Result<SchemaMigrationPlan, Error> {
+        let from = self.schema_version()?;
+        let steps = schema_migration_path(from, to)?
+            .into_iter()
+            .map(|migration| {
+                let entries = migration
+                    .columns
+                    .iter()
+                    .map(|column| self.hot_db.iter_column_keys(*column).count() as u64)
+                    .sum();
+                SchemaMigrationStep { migration, entries }
+            })
+            .collect();
+        Ok(SchemaMigrationPlan { from, to, steps })
+    }
+
     /// Migrate the database from one schema version to another, applying all requisite mutations.
     pub fn migrate_schema(&self, from: SchemaVersion, to: SchemaVersion) -> Result<(), Error> {
-        match (from, to) {
-            // Migration from v0.3.0 to v0.3.x, adding the temporary states column.
-            // Nothing actually needs to be done, but once a DB uses v2 it shouldn't go back.
-            (SchemaVersion(1), SchemaVersion(2)) => {
-                self.store_schema_version(to)?;
-                Ok(())
-            }
-            // Migrating from the current schema version to iself is always OK, a no-op.
-            (_, _) if from == to && to == CURRENT_SCHEMA_VERSION => Ok(()),
-            // Anything else is an error.
-            (_, _) => Err(HotColdDBError::UnsupportedSchemaVersion {
+        if from == to {
+            return Ok(());
+        }
+
+        let plan = self.plan_schema_migration(to)?;
+        if plan.from != from {
+            return Err(HotColdDBError::UnsupportedSchemaVersion {
                 target_version: to,
-                current_version: from,
+                current_version: plan.from,
+            }
+            .into());
+        }
+        info!(
+            self.log,
+            "Migrating database schema";
+            "from_version" => from.as_u64(),
+            "to_version" => to.as_u64(),
+            "estimated_duration_secs" => plan.estimated_duration().as_secs(),
+        );
+
+        for (i, step) in plan.steps.iter().enumerate() {
+            let migration = step.migration;
+            info!(
+                self.log,
+                "Applying schema migration";
+                "step" => format!("{}/{}", i + 1, plan.steps.len()),
+                "description" => migration.description,
+                "entries" => step.entries,
+                "estimated_duration_secs" => step.estimated_duration().as_secs(),
+            );
+            let start = Instant::now();
+
+            match (migration.from, migration.to) {
+                (SchemaVersion(1), SchemaVersion(2)) => (),
+                (SchemaVersion(2), SchemaVersion(1)) => self.delete_temp_states()?,
+                (_, _) => {
+                    return Err(HotColdDBError::UnsupportedSchemaVersion {
+                        target_version: migration.to,
+                        current_version: migration.from,
+                    }
+                    .into())
+                }
             }
-            .into()),
+            self.store_schema_version(migration.to)?;
+
+            info!(
+                self.log,
+                "Applied schema migration";
+                "to_version" => migration.to.as_u64(),
+                "duration_secs" => start.elapsed().as_secs(),
+            );
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(path: Vec<&SchemaMigration>) -> Vec<(u64, u64)> {
+        path.iter()
+            .map(|migration| (migration.from.as_u64(), migration.to.as_u64()))
+            .collect()
+    }
+
+    #[test]
+    fn migration_path() {
+        let path = |from, to| schema_migration_path(SchemaVersion(from), SchemaVersion(to));
+        assert_eq!(versions(path(1, 2).unwrap()), vec![(1, 2)]);
+        assert_eq!(versions(path(2, 1).unwrap()), vec![(2, 1)]);
+        assert!(path(2, 2).unwrap().is_empty());
+        assert!(path(0, 2).is_err());
+        assert!(path(2, 3).is_err());
     }
 }
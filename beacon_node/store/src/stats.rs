@@ -0,0 +1,170 @@
+//! Statistics about the contents of each column of the hot and freezer databases, and estimates
+//! of their growth.
+use crate::hot_cold_store::HotColdDB;
+use crate::{BeaconNodeBackend, DBColumn, Error, ItemStore};
+use std::collections::BTreeMap;
+use types::EthSpec;
+
+/// A rough upper bound on the size of a stored block.
+const ESTIMATED_BLOCK_BYTES: u64 = 128 * 1024;
+/// A rough estimate of the size of the freezer's entries for each slot: its block root and state
+/// root, and the summary of its state.
+const ESTIMATED_FROZEN_SLOT_BYTES: u64 = 128;
+
+/// The number and size of the entries with a common key prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// The column of the entries, or `None` if their prefix doesn't belong to a known column.
+    pub column: Option<DBColumn>,
+    /// The key prefix shared by the entries.
+    pub prefix: String,
+    pub entries: u64,
+    pub key_bytes: u64,
+    /// The size of the values as they are stored, after any compression.
+    pub value_bytes: u64,
+}
+
+impl ColumnStats {
+    /// The total size of the keys and values, which approximates the space used on disk.
+    pub fn total_bytes(&self) -> u64 {
+        self.key_bytes + self.value_bytes
+    }
+}
+
+/// The column statistics of the hot and freezer databases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseStats {
+    pub hot: Vec<ColumnStats>,
+    pub cold: Vec<ColumnStats>,
+}
+
+impl<E: EthSpec> BeaconNodeBackend<E> {
+    /// Count the entries in each column by reading every key-value pair, in order of key prefix.
+    pub fn column_stats(&self) -> Result<Vec<ColumnStats>, Error> {
+        let mut stats = BTreeMap::new();
+
+        self.for_each_entry(|key, value| {
+            let column = DBColumn::ALL
+                .iter()
+                .copied()
+                .find(|column| key.starts_with(column.as_bytes()));
+            let prefix_len = column.map_or(3, |column| column.as_bytes().len());
+            let prefix = String::from_utf8_lossy(&key[..prefix_len.min(key.len())]).into_owned();
+
+            let column_stats = stats.entry(prefix.clone()).or_insert_with(|| ColumnStats {
+                column,
+                prefix,
+                entries: 0,
+                key_bytes: 0,
+                value_bytes: 0,
+            });
+            column_stats.entries += 1;
+            column_stats.key_bytes += key.len() as u64;
+            column_stats.value_bytes += value.len() as u64;
+            Ok(())
+        })?;
+
+        Ok(stats.into_iter().map(|(_, stats)| stats).collect())
+    }
+}
+
+impl<E> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>
+where
+    E: EthSpec,
+{
+    /// Count the entries in each column of the hot and freezer databases.
+    ///
+    /// Every key-value pair is read, so this can take some time on a large database.
+    pub fn stats(&self) -> Result<DatabaseStats, Error> {
+        Ok(DatabaseStats {
+            hot: self.hot_db.column_stats()?,
+            cold: self.cold_db.column_stats()?,
+        })
+    }
+}
+
+/// The estimated growth of the hot and freezer databases, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatabaseGrowth {
+    pub hot_bytes: u64,
+    pub cold_bytes: u64,
+}
+
+impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold> {
+    /// Estimate how much the databases will grow as `slots` more slots are imported and
+    /// finalized, given the size of a full state in bytes.
+    ///
+    /// Blocks are kept in the hot database, and the freezer gains a restore point every
+    /// `slots_per_restore_point` slots unless historic states are pruned. Restore points are
+    /// assumed to be stored in full, so the estimate is generous for a database which stores
+    /// them as diffs.
+    pub fn estimate_growth(&self, slots: u64, state_bytes: u64) -> DatabaseGrowth {
+        let restore_points = if self.get_historic_state_limits().is_pruned() {
+            0
+        } else {
+            slots / self.get_config().slots_per_restore_point
+        };
+
+        DatabaseGrowth {
+            hot_bytes: slots * ESTIMATED_BLOCK_BYTES,
+            cold_bytes: slots * ESTIMATED_FROZEN_SLOT_BYTES + restore_points * state_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compression, DatabaseBackend, KeyValueStore, MemoryStore, StoreConfig};
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use std::path::Path;
+    use types::{ChainSpec, MinimalEthSpec};
+
+    #[test]
+    fn column_stats() {
+        let db = BeaconNodeBackend::<MinimalEthSpec>::open(
+            DatabaseBackend::Memory,
+            Path::new(""),
+            Compression::None,
+        )
+        .unwrap();
+        for i in 0..3u8 {
+            db.put_bytes(DBColumn::BeaconBlock.as_str(), &[i; 32], &[0; 100])
+                .unwrap();
+        }
+        db.put_bytes(DBColumn::BeaconMeta.as_str(), &[0; 32], &[0; 10])
+            .unwrap();
+        db.put_bytes("xyz", b"key", &[0; 5]).unwrap();
+
+        let stats = db.column_stats().unwrap();
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stats| (stats.column, stats.prefix.as_str(), stats.entries))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(DBColumn::BeaconBlock), "blk", 3),
+                (Some(DBColumn::BeaconMeta), "bma", 1),
+                (None, "xyz", 1),
+            ]
+        );
+        assert_eq!(stats[0].key_bytes, 3 * 35);
+        assert_eq!(stats[0].value_bytes, 300);
+        assert_eq!(stats[2].total_bytes(), 6 + 5);
+    }
+
+    #[test]
+    fn estimate_growth() {
+        let log = NullLoggerBuilder.build().unwrap();
+        let store: HotColdDB<MinimalEthSpec, MemoryStore<_>, MemoryStore<_>> =
+            HotColdDB::open_ephemeral(StoreConfig::default(), ChainSpec::minimal(), log).unwrap();
+
+        // The default for tests is a restore point every 64 slots.
+        let growth = store.estimate_growth(200, 1000);
+        assert_eq!(growth.hot_bytes, 200 * ESTIMATED_BLOCK_BYTES);
+        assert_eq!(
+            growth.cold_bytes,
+            200 * ESTIMATED_FROZEN_SLOT_BYTES + 3 * 1000
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Offline verification of the integrity of an on-disk database.
+use crate::hot_cold_store::{HotColdDB, HotStateSummary};
+use crate::impls::beacon_state::get_full_state;
+use crate::{BeaconNodeBackend, DBColumn, Error, ItemStore, KeyValueStore};
+use slog::info;
+use std::fmt;
+use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// A problem with the contents of the database found during verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    /// A block could not be read or decoded.
+    UnreadableBlock { block_root: Hash256, error: String },
+    /// A block is stored under a key which is not its root.
+    BlockRootMismatch {
+        block_root: Hash256,
+        computed_root: Hash256,
+    },
+    /// The parent of a block is missing, leaving a gap in the chain.
+    MissingParentBlock {
+        block_root: Hash256,
+        slot: Slot,
+        parent_root: Hash256,
+    },
+    /// A hot state could not be read or decoded.
+    UnreadableState { state_root: Hash256, error: String },
+    /// A hot state is stored under a key which is not its root.
+    StateRootMismatch {
+        state_root: Hash256,
+        computed_root: Hash256,
+    },
+    /// A hot state summary could not be read or decoded.
+    UnreadableStateSummary { state_root: Hash256, error: String },
+    /// The epoch boundary state that a hot state is replayed from is missing.
+    MissingEpochBoundaryState {
+        state_root: Hash256,
+        epoch_boundary_state_root: Hash256,
+    },
+    /// The latest block applied to a hot state is missing.
+    MissingLatestBlock {
+        state_root: Hash256,
+        latest_block_root: Hash256,
+    },
+    /// The state root of a restore point is missing or unreadable.
+    MissingRestorePointHash { slot: Slot },
+    /// A restore point state is missing.
+    MissingRestorePoint { slot: Slot, state_root: Hash256 },
+    /// A restore point state could not be read or decoded.
+    UnreadableRestorePoint {
+        slot: Slot,
+        state_root: Hash256,
+        error: String,
+    },
+    /// A restore point state does not have the root that it is stored under.
+    RestorePointRootMismatch {
+        slot: Slot,
+        state_root: Hash256,
+        computed_root: Hash256,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inconsistency::UnreadableBlock { block_root, error } => {
+                write!(f, "block {:?} is unreadable: {}", block_root, error)
+            }
+            Inconsistency::BlockRootMismatch {
+                block_root,
+                computed_root,
+            } => write!(
+                f,
+                "block stored as {:?} has root {:?}",
+                block_root, computed_root
+            ),
+            Inconsistency::MissingParentBlock {
+                block_root,
+                slot,
+                parent_root,
+            } => write!(
+                f,
+                "block {:?} at slot {} is missing its parent {:?}",
+                block_root, slot, parent_root
+            ),
+            Inconsistency::UnreadableState { state_root, error } => {
+                write!(f, "state {:?} is unreadable: {}", state_root, error)
+            }
+            Inconsistency::StateRootMismatch {
+                state_root,
+                computed_root,
+            } => write!(
+                f,
+                "state stored as {:?} has root {:?}",
+                state_root, computed_root
+            ),
+            Inconsistency::UnreadableStateSummary { state_root, error } => write!(
+                f,
+                "summary of state {:?} is unreadable: {}",
+                state_root, error
+            ),
+            Inconsistency::MissingEpochBoundaryState {
+                state_root,
+                epoch_boundary_state_root,
+            } => write!(
+                f,
+                "state {:?} is missing its epoch boundary state {:?}",
+                state_root, epoch_boundary_state_root
+            ),
+            Inconsistency::MissingLatestBlock {
+                state_root,
+                latest_block_root,
+            } => write!(
+                f,
+                "state {:?} is missing its latest block {:?}",
+                state_root, latest_block_root
+            ),
+            Inconsistency::MissingRestorePointHash { slot } => {
+                write!(
+                    f,
+                    "restore point at slot {} has no readable state root",
+                    slot
+                )
+            }
+            Inconsistency::MissingRestorePoint { slot, state_root } => write!(
+                f,
+                "restore point at slot {} is missing its state {:?}",
+                slot, state_root
+            ),
+            Inconsistency::UnreadableRestorePoint {
+                slot,
+                state_root,
+                error,
+            } => write!(
+                f,
+                "restore point {:?} at slot {} is unreadable: {}",
+                state_root, slot, error
+            ),
+            Inconsistency::RestorePointRootMismatch {
+                slot,
+                state_root,
+                computed_root,
+            } => write!(
+                f,
+                "restore point at slot {} stored as {:?} has root {:?}",
+                slot, state_root, computed_root
+            ),
+        }
+    }
+}
+
+/// The outcome of verifying a database.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationReport {
+    /// The number of blocks checked, including the genesis block alias.
+    pub blocks: usize,
+    /// The number of full states in the hot database checked.
+    pub hot_states: usize,
+    /// The number of hot state summaries checked.
+    pub hot_state_summaries: usize,
+    /// The number of restore points checked.
+    pub restore_points: usize,
+    /// The number of restore points skipped because they have been pruned, or precede the
+    /// oldest block.
+    pub skipped_restore_points: usize,
+    /// The slot of the oldest block other than genesis, whose parent is not stored, if the
+    /// database was started from a checkpoint or its history has been pruned.
+    pub anchor_slot: Option<Slot>,
+    /// The problems found.
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl VerificationReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+impl<E> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>
+where
+    E: EthSpec,
+{
+    /// Check the contents of the database, reporting any entries which are unreadable, do not
+    /// match the root they are stored under, or are missing.
+    ///
+    /// Every state is decoded and hashed, so this is slow on large databases. It is intended to
+    /// be run while the beacon node is stopped.
+    pub fn verify(&self) -> Result<VerificationReport, Error> {
+        let mut report = VerificationReport::default();
+
+        info!(self.log, "Verifying blocks");
+        self.verify_blocks(&mut report)?;
+
+        info!(self.log, "Verifying hot states");
+        self.verify_hot_states(&mut report)?;
+
+        info!(self.log, "Verifying restore points");
+        self.verify_restore_points(&mut report)?;
+
+        Ok(report)
+    }
+
+    /// Check that every block decodes, matches its key, and has a stored parent.
+    ///
+    /// A database started from a checkpoint lacks the ancestors of its anchor block, so the
+    /// parent of the block at the anchor slot recorded for the database may be missing. Any other
+    /// missing parent is an inconsistency.
+    fn verify_blocks(&self, report: &mut VerificationReport) -> Result<(), Error> {
+        report.anchor_slot = self.load_anchor_slot()?;
+
+        for block_root in self.hot_db.iter_column_keys(DBColumn::BeaconBlock) {
+            let block_root = block_root?;
+            report.blocks += 1;
+
+            let block = match self.hot_db.get::<SignedBeaconBlock<E>>(&block_root) {
+                Ok(Some(block)) => block,
+                Ok(None) => continue,
+                Err(e) => {
+                    report.inconsistencies.push(Inconsistency::UnreadableBlock {
+                        block_root,
+                        error: format!("{:?}", e),
+                    });
+                    continue;
+                }
+            };
+
+            // The genesis block is also stored under the zero hash.
+            let computed_root = block.canonical_root();
+            let is_genesis_alias = block_root == Hash256::zero() && block.message.slot == 0;
+            if computed_root != block_root && !is_genesis_alias {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::BlockRootMismatch {
+                        block_root,
+                        computed_root,
+                    });
+            }
+
+            let parent_root = block.message.parent_root;
+            let slot = block.message.slot;
+            if slot > 0
+                && Some(slot) != report.anchor_slot
+                && !self
+                    .hot_db
+                    .key_exists(DBColumn::BeaconBlock.into(), parent_root.as_bytes())?
+            {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingParentBlock {
+                        block_root,
+                        slot,
+                        parent_root,
+                    });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that every full state in the hot database decodes and matches its key, and that the
+    /// entries referenced by every hot state summary are stored.
+    fn verify_hot_states(&self, report: &mut VerificationReport) -> Result<(), Error> {
+        for state_root in self.hot_db.iter_column_keys(DBColumn::BeaconState) {
+            let state_root = state_root?;
+            report.hot_states += 1;
+
+            match get_full_state::<_, E>(&self.hot_db, &state_root) {
+                Ok(Some(state)) => {
+                    let computed_root = state.canonical_root();
+                    if computed_root != state_root {
+                        report
+                            .inconsistencies
+                            .push(Inconsistency::StateRootMismatch {
+                                state_root,
+                                computed_root,
+                            });
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => report.inconsistencies.push(Inconsistency::UnreadableState {
+                    state_root,
+                    error: format!("{:?}", e),
+                }),
+            }
+        }
+
+        for state_root in self.hot_db.iter_column_keys(DBColumn::BeaconStateSummary) {
+            let state_root = state_root?;
+            report.hot_state_summaries += 1;
+
+            let summary = match self.hot_db.get::<HotStateSummary>(&state_root) {
+                Ok(Some(summary)) => summary,
+                Ok(None) => continue,
+                Err(e) => {
+                    report
+                        .inconsistencies
+                        .push(Inconsistency::UnreadableStateSummary {
+                            state_root,
+                            error: format!("{:?}", e),
+                        });
+                    continue;
+                }
+            };
+
+            if !self.hot_db.key_exists(
+                DBColumn::BeaconState.into(),
+                summary.epoch_boundary_state_root.as_bytes(),
+            )? {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingEpochBoundaryState {
+                        state_root,
+                        epoch_boundary_state_root: summary.epoch_boundary_state_root,
+                    });
+            }
+
+            if !self.hot_db.key_exists(
+                DBColumn::BeaconBlock.into(),
+                summary.latest_block_root.as_bytes(),
+            )? {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingLatestBlock {
+                        state_root,
+                        latest_block_root: summary.latest_block_root,
+                    });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that every restore point within the historic state limits is stored, decodes and
+    /// matches its state root.
+    fn verify_restore_points(&self, report: &mut VerificationReport) -> Result<(), Error> {
+        if self.get_split_slot() == 0 {
+            return Ok(());
+        }
+
+        // Read every restore point from disk, rather than from memory.
+        self.clear_historic_state_cache();
+
+        let slots_per_restore_point = self.get_config().slots_per_restore_point;
+        let limits = self.get_historic_state_limits();
+        let latest_restore_point_index =
+            self.get_latest_restore_point_slot().as_u64() / slots_per_restore_point;
+
+        for restore_point_index in 0..=latest_restore_point_index {
+            let slot = Slot::new(restore_point_index * slots_per_restore_point);
+
+            if !limits.contains_restore_point(slot)
+                || report.anchor_slot.map_or(false, |anchor| slot < anchor)
+            {
+                report.skipped_restore_points += 1;
+                continue;
+            }
+            report.restore_points += 1;
+
+            let state_root = match self.load_restore_point_hash(restore_point_index) {
+                Ok(state_root) => state_root,
+                Err(_) => {
+                    report
+                        .inconsistencies
+                        .push(Inconsistency::MissingRestorePointHash { slot });
+                    continue;
+                }
+            };
+
+            let is_stored = self
+                .cold_db
+                .key_exists(DBColumn::BeaconState.into(), state_root.as_bytes())?
+                || self
+                    .cold_db
+                    .key_exists(DBColumn::BeaconStateDiff.into(), state_root.as_bytes())?;
+            if !is_stored {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingRestorePoint { slot, state_root });
+                continue;
+            }
+
+            match self.load_restore_point_by_index(restore_point_index) {
+                Ok(state) => {
+                    let computed_root = state.canonical_root();
+                    if computed_root != state_root {
+                        report
+                            .inconsistencies
+                            .push(Inconsistency::RestorePointRootMismatch {
+                                slot,
+                                state_root,
+                                computed_root,
+                            });
+                    }
+                }
+                Err(e) => report
+                    .inconsistencies
+                    .push(Inconsistency::UnreadableRestorePoint {
+                        slot,
+                        state_root,
+                        error: format!("{:?}", e),
+                    }),
+            }
+        }
+
+        Ok(())
+    }
+}
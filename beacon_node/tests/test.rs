@@ -3,13 +3,18 @@
 //TODO: Drop compat library once reqwest and other libraries update to tokio 0.3
 
 use beacon_chain::StateSkipConfig;
+use beacon_node::{cli_app, get_config, ClientConfig, ClientGenesis};
+use clap::{App, Arg};
+use environment::null_logger;
+use eth2_testnet_config::{Eth2TestnetConfig, GenesisStateUrl};
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     eth2::types::StateId,
     testing_client_config, LocalBeaconNode,
 };
+use tempfile::tempdir;
 use tokio_compat_02::FutureExt;
-use types::{EthSpec, MinimalEthSpec, Slot};
+use types::{EthSpec, Hash256, MinimalEthSpec, Slot};
 
 fn env_builder() -> EnvironmentBuilder<MinimalEthSpec> {
     EnvironmentBuilder::minimal()
@@ -68,3 +73,193 @@ fn http_server_genesis_state() {
 
     env.fire_signal();
 }
+
+/// Returns the config of a beacon node on the medalla network run with the beacon node `args`.
+fn config_from_args(args: &[&str]) -> Result<ClientConfig, String> {
+    config_from_global_args(&["--network=medalla"], args)
+}
+
+/// Returns the config of a beacon node run with the top-level `global_args` and the beacon node
+/// `args`.
+fn config_from_global_args(global_args: &[&str], args: &[&str]) -> Result<ClientConfig, String> {
+    let datadir = tempdir().expect("should create temp dir");
+    let app = App::new("lighthouse")
+        .arg(
+            Arg::with_name("datadir")
+                .long("datadir")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("network")
+                .long("network")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("testnet-dir")
+                .long("testnet-dir")
+                .takes_value(true)
+                .global(true),
+        )
+        .subcommand(cli_app());
+    let datadir_arg = format!("--datadir={}", datadir.path().display());
+    let matches = app
+        .get_matches_from_safe(
+            ["lighthouse", datadir_arg.as_str()]
+                .iter()
+                .chain(global_args)
+                .chain(&["beacon_node"])
+                .chain(args),
+        )
+        .map_err(|e| e.message)?;
+
+    get_config::<MinimalEthSpec>(
+        matches
+            .subcommand_matches("beacon_node")
+            .expect("should match beacon_node"),
+        &MinimalEthSpec::default_spec(),
+        null_logger()?,
+    )
+}
+
+#[test]
+fn snapshot_cache_size_flag() {
+    assert_eq!(
+        config_from_args(&[]).unwrap().chain.snapshot_cache_size,
+        ClientConfig::default().chain.snapshot_cache_size,
+        "should use the default size without the flag"
+    );
+    assert_eq!(
+        config_from_args(&["--snapshot-cache-size", "16"])
+            .unwrap()
+            .chain
+            .snapshot_cache_size,
+        16
+    );
+    assert!(
+        config_from_args(&["--snapshot-cache-size", "many"]).is_err(),
+        "should reject a size which isn't an integer"
+    );
+}
+
+#[test]
+fn enr_ipv6_port_and_field_flags() {
+    let config = config_from_args(&[
+        "--enr-udp6-port",
+        "9001",
+        "--enr-tcp6-port",
+        "9002",
+        "--enr-field",
+        "client=lighthouse",
+        "--enr-field",
+        "raw=0x0102",
+    ])
+    .unwrap();
+
+    assert_eq!(config.network.enr_udp6_port, Some(9001));
+    assert_eq!(config.network.enr_tcp6_port, Some(9002));
+    assert_eq!(
+        config.network.enr_custom_fields,
+        vec![
+            ("client".to_string(), b"lighthouse".to_vec()),
+            ("raw".to_string(), vec![1, 2]),
+        ]
+    );
+
+    for invalid_field in &["udp=9000", "eth2=0x00", "no-value", "=value", "raw=0xzz"] {
+        assert!(
+            config_from_args(&["--enr-field", *invalid_field]).is_err(),
+            "should reject the ENR field {}",
+            invalid_field
+        );
+    }
+}
+
+#[test]
+fn checkpoint_sync_url_flag() {
+    let config = config_from_args(&["--checkpoint-sync-url", "http://localhost:5052"]).unwrap();
+
+    assert!(
+        matches!(
+            config.genesis,
+            ClientGenesis::CheckpointSyncUrl { ref url, .. } if url == "http://localhost:5052"
+        ),
+        "should sync from the checkpoint of the remote node"
+    );
+}
+
+#[test]
+fn checkpoint_file_flags() {
+    let dir = tempdir().expect("should create temp dir");
+    let state_path = dir.path().join("state.ssz");
+    let block_path = dir.path().join("block.ssz");
+    std::fs::write(&state_path, &[1, 2, 3]).unwrap();
+    std::fs::write(&block_path, &[4, 5]).unwrap();
+    let state_path = state_path.to_str().unwrap();
+    let block_path = block_path.to_str().unwrap();
+
+    let config = config_from_args(&[
+        "--checkpoint-state",
+        state_path,
+        "--checkpoint-block",
+        block_path,
+    ])
+    .unwrap();
+    assert!(
+        matches!(
+            config.genesis,
+            ClientGenesis::CheckpointSyncFiles {
+                ref anchor_state_bytes,
+                ref anchor_block_bytes,
+                ..
+            } if anchor_state_bytes == &[1, 2, 3] && anchor_block_bytes == &[4, 5]
+        ),
+        "should sync from the checkpoint files"
+    );
+
+    assert!(
+        config_from_args(&["--checkpoint-state", state_path]).is_err(),
+        "should require the checkpoint block"
+    );
+    assert!(
+        config_from_args(&[
+            "--checkpoint-state",
+            state_path,
+            "--checkpoint-block",
+            "/nonexistent/block.ssz"
+        ])
+        .is_err(),
+        "should fail to read a missing file"
+    );
+}
+
+#[test]
+fn genesis_state_url_from_testnet_dir() {
+    let dir = tempdir().expect("should create temp dir");
+    let testnet_dir = dir.path().join("testnet");
+    let genesis_state_url = GenesisStateUrl {
+        url: "https://example.com/genesis.ssz".to_string(),
+        checksum: Hash256::repeat_byte(42),
+    };
+    Eth2TestnetConfig {
+        deposit_contract_deploy_block: 0,
+        boot_enr: None,
+        genesis_state_bytes: None,
+        genesis_state_url: Some(genesis_state_url.clone()),
+        yaml_config: None,
+    }
+    .write_to_file(testnet_dir.clone(), false)
+    .expect("should write testnet dir");
+
+    let testnet_dir_arg = format!("--testnet-dir={}", testnet_dir.display());
+    let config = config_from_global_args(&[testnet_dir_arg.as_str()], &[]).unwrap();
+
+    assert_eq!(
+        config.genesis,
+        ClientGenesis::GenesisStateUrl {
+            url: genesis_state_url.url,
+            checksum: genesis_state_url.checksum,
+        }
+    );
+}
@@ -4,11 +4,12 @@
 
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use futures::stream::StreamExt;
-use slog::info;
+use slog::{error, info};
 use slot_clock::SlotClock;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{interval_at, Instant};
+use types::EthSpec;
 
 /// Spawns a timer service which periodically executes tasks for the beacon chain
 pub fn spawn_timer<T: BeaconChainTypes>(
@@ -25,9 +26,57 @@ pub fn spawn_timer<T: BeaconChainTypes>(
 
     // Warning: `interval_at` panics if `milliseconds_per_slot` = 0.
     let mut interval = interval_at(start_instant, Duration::from_millis(milliseconds_per_slot));
+    let background_executor = executor.clone();
+    let background_log = log.clone();
     let timer_future = async move {
+        let mut gc_finalized_epoch = None;
         while interval.next().await.is_some() {
             beacon_chain.per_slot_task();
+
+            // Prepare the shufflings of the new epoch in the background, before they're needed
+            // by duty requests and the epoch's first attestations.
+            if let Some(slot) = beacon_chain.slot_clock.now() {
+                if slot % T::EthSpec::slots_per_epoch() == 0 {
+                    let chain = beacon_chain.clone();
+                    let log = background_log.clone();
+                    background_executor.spawn_blocking(
+                        move || {
+                            let epoch = slot.epoch(T::EthSpec::slots_per_epoch());
+                            if let Err(e) = chain.precompute_epoch_shufflings(epoch) {
+                                error!(
+                                    log,
+                                    "Failed to precompute shufflings";
+                                    "epoch" => epoch,
+                                    "error" => format!("{:?}", e)
+                                );
+                            }
+                        },
+                        "shuffling_precompute",
+                    );
+                }
+            }
+
+            // Garbage collect orphaned states after each finalization, and on the first slot
+            // after start-up to clean up after an unclean shutdown. A read-only database is left
+            // alone.
+            let finalized_epoch = beacon_chain.fork_choice.read().finalized_checkpoint().epoch;
+            if gc_finalized_epoch != Some(finalized_epoch) && !beacon_chain.store.is_read_only() {
+                gc_finalized_epoch = Some(finalized_epoch);
+                let chain = beacon_chain.clone();
+                let log = background_log.clone();
+                background_executor.spawn_blocking(
+                    move || {
+                        if let Err(e) = chain.garbage_collect_orphaned_states() {
+                            error!(
+                                log,
+                                "Failed to garbage collect orphaned states";
+                                "error" => format!("{:?}", e)
+                            );
+                        }
+                    },
+                    "state_gc",
+                );
+            }
         }
     };
 
@@ -11,20 +11,36 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         This is the recommended way to provide a network boot-node since it has a reduced attack \
         surface compared to a full beacon node.")
         .settings(&[clap::AppSettings::ColoredHelp])
+        .arg(
+            Arg::with_name("positional-enr-address")
+                .value_name("IP-ADDRESS")
+                .help("The external IP address/ DNS address to broadcast to other peers on how to reach this node. \
+                Takes precedence over --enr-address.")
+                .index(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("positional-port")
+                .value_name("PORT")
+                .help("The UDP port to listen on. Takes precedence over --port.")
+                .index(2)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("enr-address")
+                .long("enr-address")
                 .value_name("IP-ADDRESS")
                 .help("The external IP address/ DNS address to broadcast to other peers on how to reach this node. \
                 If a DNS address is provided, the enr-address is set to the IP address it resolves to and \
-                does not auto-update based on PONG responses in discovery.")
-                .required(true)
+                does not auto-update based on PONG responses in discovery. Required unless given as the \
+                first positional argument.")
                 .takes_value(true),
         )
         .arg(
             Arg::with_name("port")
+                .long("port")
                 .value_name("PORT")
-                .help("The UDP port to listen on.")
-                .default_value("9000")
+                .help("The UDP port to listen on. Defaults to 9000.")
                 .takes_value(true),
         )
         .arg(
@@ -26,6 +26,14 @@ impl<T: EthSpec> TryFrom<&ArgMatches<'_>> for BootNodeConfig<T> {
     type Error = String;
 
     fn try_from(matches: &ArgMatches<'_>) -> Result<Self, Self::Error> {
+        // The ENR address is not required by the CLI, so that it can also be supplied by the
+        // environment or a config file.
+        if !matches.is_present("positional-enr-address") && !matches.is_present("enr-address") {
+            return Err(
+                "The boot node requires an ENR address, supply one with --enr-address".into(),
+            );
+        }
+
         let data_dir = get_data_dir(matches);
 
         // Try and grab testnet config from input CLI params
@@ -67,13 +75,7 @@ impl<T: EthSpec> TryFrom<&ArgMatches<'_>> for BootNodeConfig<T> {
         set_network_config(&mut network_config, matches, &data_dir, &logger, true)?;
         // default to the standard port
         if !matches.is_present("enr-udp-port") {
-            network_config.enr_udp_port = Some(
-                matches
-                    .value_of("port")
-                    .expect("Value required")
-                    .parse()
-                    .map_err(|_| "Invalid port number")?,
-            );
+            network_config.enr_udp_port = Some(network_config.discovery_port);
         }
 
         let private_key = load_private_key(&network_config, &logger);
@@ -176,6 +176,22 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET beacon/deposit_snapshot`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_deposit_snapshot(
+        &self,
+    ) -> Result<Option<GenericResponse<DepositTreeSnapshot>>, Error> {
+        let mut path = self.eth_path()?;
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("beacon")
+            .push("deposit_snapshot");
+
+        self.get_opt(path).await
+    }
+
     /// `GET beacon/states/{state_id}/root`
     ///
     /// Returns `Ok(None)` on a 404 error.
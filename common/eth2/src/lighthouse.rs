@@ -2,7 +2,7 @@
 
 use crate::{
     ok_or_error,
-    types::{BeaconState, Epoch, EthSpec, GenericResponse, ValidatorId},
+    types::{BeaconState, Epoch, EthSpec, GenericResponse, Slot, ValidatorId},
     BeaconNodeHttpClient, DepositData, Error, Eth1Data, Hash256, StateId, StatusCode,
 };
 use proto_array::core::ProtoArray;
@@ -10,8 +10,9 @@ use reqwest::IntoUrl;
 use serde::{Deserialize, Serialize};
 use ssz::Decode;
 use ssz_derive::{Decode, Encode};
+use std::path::{Path, PathBuf};
 
-pub use eth2_libp2p::{types::SyncState, PeerInfo};
+pub use eth2_libp2p::{types::SyncState, DiscoveryInfo, PeerInfo};
 
 /// Information returned by `peers` and `connected_peers`.
 // TODO: this should be deserializable..
@@ -24,6 +25,114 @@ pub struct Peer<T: EthSpec> {
     pub peer_info: PeerInfo<T>,
 }
 
+/// A peer which is currently banned, returned by `lighthouse/peers/banned`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    /// The Peer's ID
+    pub peer_id: String,
+    /// True if the ban was issued via `lighthouse/peers/{peer_id}/ban`, rather than as a result
+    /// of the peer's score.
+    pub manual: bool,
+    /// The number of seconds until a manual ban expires. `None` for permanent manual bans and for
+    /// score bans, which are lifted once the peer's score has decayed.
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Query parameters for `lighthouse/peers/{peer_id}/ban`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BanPeerQuery {
+    /// The number of seconds to ban the peer for. The ban is permanent if omitted.
+    pub duration_secs: Option<u64>,
+}
+
+/// Information about the database, returned by `lighthouse/database/info`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseInfo {
+    /// The slot at which states are divided between the hot and freezer databases.
+    pub split_slot: Slot,
+    /// The compression of the freezer database, or `None` if it does not support compression.
+    pub freezer_compression: Option<CompressionInfo>,
+}
+
+/// The compression of a database, along with the bytes written through it since the node
+/// started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionInfo {
+    /// The codec, one of `none`, `snappy` or `zstd`.
+    pub codec: String,
+    /// The compression level, for codecs which have one.
+    pub level: Option<i32>,
+    /// The number of bytes passed to compression.
+    pub uncompressed_bytes: u64,
+    /// The number of bytes produced by compression.
+    pub compressed_bytes: u64,
+    /// `uncompressed_bytes / compressed_bytes`, if any bytes have been compressed.
+    pub ratio: Option<f64>,
+}
+
+/// The request body of `lighthouse/database/backup`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseBackupRequest {
+    /// The directory to write the backup to, on the beacon node's filesystem. Must be absolute and
+    /// must not already exist.
+    pub path: PathBuf,
+}
+
+/// A completed backup, returned by `lighthouse/database/backup`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseBackup {
+    /// The number of entries copied from the hot database.
+    pub hot_db_entries: usize,
+    /// The number of entries copied from the freezer database.
+    pub freezer_db_entries: usize,
+}
+
+/// A completed garbage collection of orphaned states, returned by `lighthouse/database/gc`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseGc {
+    /// The number of states deleted from the hot database.
+    pub states_deleted: usize,
+    /// The number of bytes of state data deleted from the hot database.
+    pub bytes_reclaimed: u64,
+}
+
+/// The progress of the slasher, returned by `lighthouse/slasher/status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlasherStatus {
+    /// The size of the slasher database at the end of the last update, in bytes.
+    pub database_size: u64,
+    /// The epoch of the last update, or `None` if there hasn't been one yet.
+    pub current_epoch: Option<Epoch>,
+    /// The oldest epoch retained by the slasher as of the last update.
+    pub oldest_epoch: Option<Epoch>,
+    /// The number of epochs of history kept, which is reduced if the database is full.
+    pub history_length: usize,
+    /// Whether updates are paused by `lighthouse/slasher/pause`.
+    pub paused: bool,
+    /// The number of attestations waiting to be processed.
+    pub attestation_queue_len: usize,
+    /// The number of blocks waiting to be processed.
+    pub block_queue_len: usize,
+    /// The number of attester slashings found since the node started.
+    pub attester_slashings_found: u64,
+    /// The number of proposer slashings found since the node started.
+    pub proposer_slashings_found: u64,
+    /// Processing counts for the most recent epochs, oldest first.
+    pub epochs: Vec<SlasherEpochStats>,
+}
+
+/// The attestations and blocks processed by the slasher during an epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlasherEpochStats {
+    pub epoch: Epoch,
+    /// The number of attestations stored in the slasher database.
+    pub attestations_processed: u64,
+    /// The number of attestations discarded for being too old or invalid.
+    pub attestations_dropped: u64,
+    /// The number of block headers stored in the slasher database.
+    pub blocks_processed: u64,
+}
+
 /// The results of validators voting during an epoch.
 ///
 /// Provides information about the current and previous epochs.
@@ -146,6 +255,53 @@ impl Health {
     }
 }
 
+/// Reports on the disk space of the filesystem holding a path.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MountInfo {
+    /// The ID of the device holding the path, which is shared by all paths on the filesystem.
+    pub device: u64,
+    /// Total size of the filesystem, in bytes.
+    pub total: u64,
+    /// Space used on the filesystem, in bytes.
+    pub used: u64,
+    /// Space available to unprivileged users, in bytes.
+    pub avail: u64,
+    /// Percentage of the filesystem used.
+    pub used_pct: f32,
+}
+
+impl MountInfo {
+    #[cfg(not(target_os = "linux"))]
+    pub fn for_path<P: AsRef<Path>>(_path: P) -> Result<Self, String> {
+        Err("Mount info is only available on Linux".into())
+    }
+
+    /// Observe the filesystem holding `path`, or its nearest existing ancestor if `path` hasn't
+    /// been created yet.
+    #[cfg(target_os = "linux")]
+    pub fn for_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        use std::os::unix::fs::MetadataExt;
+
+        let path = path
+            .as_ref()
+            .ancestors()
+            .find(|path| path.exists())
+            .ok_or_else(|| format!("Unable to find {:?}", path.as_ref()))?;
+        let usage = psutil::disk::disk_usage(path)
+            .map_err(|e| format!("Unable to get disk usage of {:?}: {:?}", path, e))?;
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| format!("Unable to get metadata of {:?}: {:?}", path, e))?;
+
+        Ok(Self {
+            device: metadata.dev(),
+            total: usage.total(),
+            used: usage.used(),
+            avail: usage.free(),
+            used_pct: usage.percent(),
+        })
+    }
+}
+
 /// Indicates how up-to-date the Eth1 caches are.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Eth1SyncStatusData {
@@ -236,14 +392,78 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/discovery`
+    pub async fn get_lighthouse_discovery(&self) -> Result<GenericResponse<DiscoveryInfo>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("discovery");
+
+        self.get(path).await
+    }
+
     /*
      * Note:
      *
-     * The `lighthouse/peers` endpoints do not have functions here. We are yet to implement
+     * The `lighthouse/peers` and `lighthouse/peers/connected` endpoints do not have functions here. We are yet to implement
      * `Deserialize` on the `PeerInfo` struct since it contains use of `Instant`. This could be
      * fairly simply achieved, if desired.
      */
 
+    /// `GET lighthouse/peers/banned`
+    pub async fn get_lighthouse_peers_banned(
+        &self,
+    ) -> Result<GenericResponse<Vec<BannedPeer>>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("banned");
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/peers/{peer_id}/ban`
+    pub async fn post_lighthouse_peers_ban(
+        &self,
+        peer_id: &str,
+        duration_secs: Option<u64>,
+    ) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push(peer_id)
+            .push("ban");
+
+        if let Some(duration_secs) = duration_secs {
+            path.query_pairs_mut()
+                .append_pair("duration_secs", &duration_secs.to_string());
+        }
+
+        self.post(path, &()).await
+    }
+
+    /// `POST lighthouse/peers/{peer_id}/unban`
+    pub async fn post_lighthouse_peers_unban(&self, peer_id: &str) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push(peer_id)
+            .push("unban");
+
+        self.post(path, &()).await
+    }
+
     /// `GET lighthouse/proto_array`
     pub async fn get_lighthouse_proto_array(&self) -> Result<GenericResponse<ProtoArray>, Error> {
         let mut path = self.server.clone();
@@ -336,6 +556,50 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/database/info`
+    pub async fn get_lighthouse_database_info(
+        &self,
+    ) -> Result<GenericResponse<DatabaseInfo>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("info");
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/database/backup`
+    pub async fn post_lighthouse_database_backup(
+        &self,
+        request: &DatabaseBackupRequest,
+    ) -> Result<GenericResponse<DatabaseBackup>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("backup");
+
+        self.post_with_response(path, request).await
+    }
+
+    /// `POST lighthouse/database/gc`
+    pub async fn post_lighthouse_database_gc(&self) -> Result<GenericResponse<DatabaseGc>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("gc");
+
+        self.post_with_response(path, &()).await
+    }
+
     /// `GET lighthouse/beacon/states/{state_id}/ssz`
     pub async fn get_lighthouse_beacon_states_ssz<E: EthSpec>(
         &self,
@@ -357,6 +621,47 @@ impl BeaconNodeHttpClient {
             .transpose()
     }
 
+    /// `GET lighthouse/slasher/status`
+    pub async fn get_lighthouse_slasher_status(
+        &self,
+    ) -> Result<GenericResponse<SlasherStatus>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("slasher")
+            .push("status");
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/slasher/pause`
+    pub async fn post_lighthouse_slasher_pause(&self) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("slasher")
+            .push("pause");
+
+        self.post(path, &()).await
+    }
+
+    /// `POST lighthouse/slasher/resume`
+    pub async fn post_lighthouse_slasher_resume(&self) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("slasher")
+            .push("resume");
+
+        self.post(path, &()).await
+    }
+
     /// `GET lighthouse/staking`
     pub async fn get_lighthouse_staking(&self) -> Result<bool, Error> {
         let mut path = self.server.clone();
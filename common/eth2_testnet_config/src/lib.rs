@@ -10,16 +10,18 @@
 use eth2_config::{testnets_dir, *};
 
 use enr::{CombinedKey, Enr};
+use serde_derive::{Deserialize, Serialize};
 use ssz::Decode;
 use std::fs::{create_dir_all, File};
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use types::{BeaconState, EthSpec, EthSpecId, YamlConfig};
+use types::{BeaconState, EthSpec, EthSpecId, Hash256, YamlConfig};
 
 pub const ADDRESS_FILE: &str = "deposit_contract.txt";
 pub const DEPLOY_BLOCK_FILE: &str = "deploy_block.txt";
 pub const BOOT_ENR_FILE: &str = "boot_enr.yaml";
 pub const GENESIS_STATE_FILE: &str = "genesis.ssz";
+pub const GENESIS_STATE_URL_FILE: &str = "genesis_state_url.yaml";
 pub const YAML_CONFIG_FILE: &str = "config.yaml";
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -57,6 +59,15 @@ const TOLEDO: HardcodedNet = define_net!(toledo, include_toledo_file);
 const HARDCODED_NETS: &[HardcodedNet] = &[ALTONA, MEDALLA, SPADINA, PYRMONT, MAINNET, TOLEDO];
 pub const DEFAULT_HARDCODED_TESTNET: &str = "mainnet";
 
+/// A location from which the genesis state can be downloaded, instead of being distributed with
+/// the testnet.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GenesisStateUrl {
+    pub url: String,
+    /// The SHA256 hash of the SSZ-encoded genesis state, which the download must match.
+    pub checksum: Hash256,
+}
+
 /// Specifies an Eth2 testnet.
 ///
 /// See the crate-level documentation for more details.
@@ -67,6 +78,8 @@ pub struct Eth2TestnetConfig {
     pub deposit_contract_deploy_block: u64,
     pub boot_enr: Option<Vec<Enr<CombinedKey>>>,
     pub genesis_state_bytes: Option<Vec<u8>>,
+    /// Where to download the genesis state from, if it is not included in `genesis_state_bytes`.
+    pub genesis_state_url: Option<GenesisStateUrl>,
     pub yaml_config: Option<YamlConfig>,
 }
 
@@ -92,6 +105,7 @@ impl Eth2TestnetConfig {
             ),
             genesis_state_bytes: Some(net.genesis_state_bytes.to_vec())
                 .filter(|bytes| !bytes.is_empty()),
+            genesis_state_url: None,
             yaml_config: Some(
                 serde_yaml::from_reader(net.yaml_config)
                     .map_err(|e| format!("Unable to parse yaml config: {:?}", e))?,
@@ -178,6 +192,10 @@ impl Eth2TestnetConfig {
             write_to_yaml_file!(YAML_CONFIG_FILE, yaml_config);
         }
 
+        if let Some(genesis_state_url) = &self.genesis_state_url {
+            write_to_yaml_file!(GENESIS_STATE_URL_FILE, genesis_state_url);
+        }
+
         // The genesis state is a special case because it uses SSZ, not YAML.
         if let Some(genesis_state_bytes) = &self.genesis_state_bytes {
             let file = base_dir.join(GENESIS_STATE_FILE);
@@ -218,6 +236,7 @@ impl Eth2TestnetConfig {
         let deposit_contract_deploy_block = load_from_file!(DEPLOY_BLOCK_FILE);
         let boot_enr = optional_load_from_file!(BOOT_ENR_FILE);
         let yaml_config = optional_load_from_file!(YAML_CONFIG_FILE);
+        let genesis_state_url = optional_load_from_file!(GENESIS_STATE_URL_FILE);
 
         // The genesis state is a special case because it uses SSZ, not YAML.
         let genesis_file_path = base_dir.join(GENESIS_STATE_FILE);
@@ -239,6 +258,7 @@ impl Eth2TestnetConfig {
             deposit_contract_deploy_block,
             boot_enr,
             genesis_state_bytes,
+            genesis_state_url,
             yaml_config,
         })
     }
@@ -301,13 +321,20 @@ mod tests {
         let genesis_state = Some(BeaconState::new(42, eth1_data, spec));
         let yaml_config = Some(YamlConfig::from_spec::<E>(spec));
 
-        do_test::<E>(boot_enr, genesis_state, yaml_config);
-        do_test::<E>(None, None, None);
+        let genesis_state_url = Some(GenesisStateUrl {
+            url: "https://example.com/genesis.ssz".to_string(),
+            checksum: Hash256::repeat_byte(42),
+        });
+
+        do_test::<E>(boot_enr, genesis_state, None, yaml_config);
+        do_test::<E>(None, None, genesis_state_url, None);
+        do_test::<E>(None, None, None, None);
     }
 
     fn do_test<E: EthSpec>(
         boot_enr: Option<Vec<Enr<CombinedKey>>>,
         genesis_state: Option<BeaconState<E>>,
+        genesis_state_url: Option<GenesisStateUrl>,
         yaml_config: Option<YamlConfig>,
     ) {
         let temp_dir = TempDir::new("eth2_testnet_test").expect("should create temp dir");
@@ -318,6 +345,7 @@ mod tests {
             deposit_contract_deploy_block,
             boot_enr,
             genesis_state_bytes: genesis_state.as_ref().map(Encode::as_ssz_bytes),
+            genesis_state_url,
             yaml_config,
         };
 
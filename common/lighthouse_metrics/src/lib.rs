@@ -54,13 +54,13 @@
 //! }
 //! ```
 
-use prometheus::{HistogramOpts, HistogramTimer, Opts};
+use prometheus::{HistogramOpts, Opts};
 use std::time::Duration;
 
 use prometheus::core::{Atomic, GenericGauge, GenericGaugeVec};
 pub use prometheus::{
-    Encoder, Gauge, GaugeVec, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
-    IntGaugeVec, Result, TextEncoder,
+    Encoder, Gauge, GaugeVec, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, Result, TextEncoder,
 };
 
 /// Collect all the metrics for reporting.
@@ -117,6 +117,20 @@ pub fn try_create_histogram_vec(
     Ok(histogram_vec)
 }
 
+/// Attempts to create a `HistogramVec` with custom `buckets`, returning `Err` if the registry does
+/// not accept the counter (potentially due to naming conflict).
+pub fn try_create_histogram_vec_with_buckets(
+    name: &str,
+    help: &str,
+    buckets: Vec<f64>,
+    label_names: &[&str],
+) -> Result<HistogramVec> {
+    let opts = HistogramOpts::new(name, help).buckets(buckets);
+    let histogram_vec = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram_vec.clone()))?;
+    Ok(histogram_vec)
+}
+
 /// Attempts to create a `IntGaugeVec`, returning `Err` if the registry does not accept the gauge
 /// (potentially due to naming conflict).
 pub fn try_create_int_gauge_vec(
@@ -263,6 +277,13 @@ pub fn observe_timer_vec(vec: &Result<HistogramVec>, name: &[&str], duration: Du
     }
 }
 
+/// Observes `value` on the histogram in `vec` with the given `name`.
+pub fn observe_vec(vec: &Result<HistogramVec>, name: &[&str], value: f64) {
+    if let Some(h) = get_histogram(vec, name) {
+        h.observe(value)
+    }
+}
+
 /// Stops a timer created with `start_timer(..)`.
 pub fn stop_timer(timer: Option<HistogramTimer>) {
     if let Some(t) = timer {
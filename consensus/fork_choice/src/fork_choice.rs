@@ -2,9 +2,11 @@ use std::marker::PhantomData;
 
 use proto_array::{Block as ProtoBlock, ProtoArrayForkChoice};
 use ssz_derive::{Decode, Encode};
+use state_processing::per_epoch_processing::weigh_justification_and_finalization;
+use state_processing::EpochProcessingError;
 use types::{
-    BeaconBlock, BeaconState, BeaconStateError, Checkpoint, Epoch, EthSpec, Hash256,
-    IndexedAttestation, RelativeEpoch, ShufflingId, Slot,
+    BeaconBlock, BeaconState, BeaconStateError, ChainSpec, Checkpoint, CloneConfig, Epoch, EthSpec,
+    Hash256, IndexedAttestation, RelativeEpoch, ShufflingId, Slot,
 };
 
 use crate::ForkChoiceStore;
@@ -31,6 +33,7 @@ pub enum Error<T> {
         time: Slot,
     },
     BeaconStateError(BeaconStateError),
+    UnrealizedCheckpointProcessing(EpochProcessingError),
     AttemptToRevertJustification {
         store: Slot,
         state: Slot,
@@ -125,13 +128,59 @@ fn compute_start_slot_at_epoch<E: EthSpec>(epoch: Epoch) -> Slot {
     epoch.start_slot(E::slots_per_epoch())
 }
 
+/// Calculate the checkpoints that would be justified and finalized if the epoch of `state` were
+/// processed now, i.e. its unrealized justified and finalized checkpoints.
+///
+/// ## Specification
+///
+/// Equivalent to the state processing in:
+///
+/// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#compute_pulled_up_tip
+fn compute_unrealized_checkpoints<E: EthSpec>(
+    state: &BeaconState<E>,
+    spec: &ChainSpec,
+) -> Result<(Checkpoint, Checkpoint), EpochProcessingError> {
+    let mut state = state.clone_with(CloneConfig {
+        committee_caches: true,
+        progressive_balances_cache: true,
+        ..CloneConfig::none()
+    });
+
+    // The cache is normally carried through block processing, so it only needs to be built here
+    // if the caller didn't provide it.
+    if !state
+        .progressive_balances_cache
+        .is_initialized_at(state.current_epoch())
+    {
+        state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+        state.build_committee_cache(RelativeEpoch::Current, spec)?;
+        state.build_progressive_balances_cache(spec)?;
+    }
+
+    let balances = &state.progressive_balances_cache;
+    let total_active_balance = balances.current_epoch_total_active_balance()?;
+    let previous_target_balance = balances.previous_epoch_target_attesting_balance()?;
+    let current_target_balance = balances.current_epoch_target_attesting_balance()?;
+    weigh_justification_and_finalization(
+        &mut state,
+        total_active_balance,
+        previous_target_balance,
+        current_target_balance,
+    )?;
+
+    Ok((
+        state.current_justified_checkpoint,
+        state.finalized_checkpoint,
+    ))
+}
+
 /// Called whenever the current time increases.
 ///
 /// ## Specification
 ///
 /// Equivalent to:
 ///
-/// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#on_tick
+/// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#on_tick
 fn on_tick<T, E>(store: &mut T, time: Slot) -> Result<(), Error<T::Error>>
 where
     T: ForkChoiceStore<E>,
@@ -160,6 +209,29 @@ where
             .map_err(Error::ForkChoiceStoreError)?;
     }
 
+    // Pull up the justification and finalization of the previous epoch.
+    //
+    // This is `update_checkpoints` from the spec, where `should_update_justified_checkpoint` is
+    // always true since we are at the start of an epoch.
+    let unrealized_justified_checkpoint = *store.unrealized_justified_checkpoint();
+    let unrealized_finalized_checkpoint = *store.unrealized_finalized_checkpoint();
+
+    if unrealized_justified_checkpoint.epoch > store.justified_checkpoint().epoch {
+        if unrealized_justified_checkpoint.epoch > store.best_justified_checkpoint().epoch {
+            store.set_best_justified_checkpoint(unrealized_justified_checkpoint);
+        }
+        store
+            .set_justified_checkpoint(unrealized_justified_checkpoint)
+            .map_err(Error::ForkChoiceStoreError)?;
+    }
+
+    if unrealized_finalized_checkpoint.epoch > store.finalized_checkpoint().epoch {
+        store.set_finalized_checkpoint(unrealized_finalized_checkpoint);
+        store
+            .set_justified_checkpoint(unrealized_justified_checkpoint)
+            .map_err(Error::ForkChoiceStoreError)?;
+    }
+
     Ok(())
 }
 
@@ -253,7 +325,7 @@ where
             ShufflingId::new(genesis_block_root, genesis_state, RelativeEpoch::Next)
                 .map_err(Error::BeaconStateError)?;
 
-        let proto_array = ProtoArrayForkChoice::new(
+        let proto_array = ProtoArrayForkChoice::new::<E>(
             finalized_block_slot,
             finalized_block_state_root,
             fc_store.justified_checkpoint().epoch,
@@ -341,37 +413,38 @@ where
     ///
     /// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#get_head
     pub fn get_head(&mut self, current_slot: Slot) -> Result<Hash256, Error<T::Error>> {
-        self.update_time(current_slot)?;
+        let current_slot = self.update_time(current_slot)?;
 
         let store = &mut self.fc_store;
 
         self.proto_array
-            .find_head(
+            .find_head::<E>(
                 store.justified_checkpoint().epoch,
                 store.justified_checkpoint().root,
                 store.finalized_checkpoint().epoch,
                 store.justified_balances(),
+                current_slot,
             )
             .map_err(Into::into)
     }
 
-    /// Returns `true` if the given `store` should be updated to set
-    /// `state.current_justified_checkpoint` its `justified_checkpoint`.
+    /// Returns `true` if the given `store` should be updated to set `new_justified_checkpoint` as
+    /// its `justified_checkpoint`, where `new_justified_checkpoint` has been justified by `state`
+    /// (perhaps only once its epoch is processed).
     ///
     /// ## Specification
     ///
     /// Is equivalent to:
     ///
-    /// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#should_update_justified_checkpoint
+    /// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#should_update_justified_checkpoint
     fn should_update_justified_checkpoint(
         &mut self,
         current_slot: Slot,
+        new_justified_checkpoint: &Checkpoint,
         state: &BeaconState<E>,
     ) -> Result<bool, Error<T::Error>> {
         self.update_time(current_slot)?;
 
-        let new_justified_checkpoint = &state.current_justified_checkpoint;
-
         if compute_slots_since_epoch_start::<E>(self.fc_store.get_current_slot())
             < SAFE_SLOTS_TO_UPDATE_JUSTIFIED
         {
@@ -406,6 +479,64 @@ where
         Ok(true)
     }
 
+    /// Update the justified and finalized checkpoints of the store with checkpoints that have been
+    /// justified and finalized by `state` (perhaps only once its epoch is processed).
+    ///
+    /// ## Specification
+    ///
+    /// Is equivalent to:
+    ///
+    /// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#update_checkpoints
+    fn update_checkpoints(
+        &mut self,
+        current_slot: Slot,
+        state: &BeaconState<E>,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+    ) -> Result<(), Error<T::Error>> {
+        // Update justified checkpoint.
+        if justified_checkpoint.epoch > self.fc_store.justified_checkpoint().epoch {
+            if justified_checkpoint.epoch > self.fc_store.best_justified_checkpoint().epoch {
+                self.fc_store
+                    .set_best_justified_checkpoint(justified_checkpoint);
+            }
+            if self.should_update_justified_checkpoint(
+                current_slot,
+                &justified_checkpoint,
+                state,
+            )? {
+                self.fc_store
+                    .set_justified_checkpoint(justified_checkpoint)
+                    .map_err(Error::UnableToSetJustifiedCheckpoint)?;
+            }
+        }
+
+        // Update finalized checkpoint.
+        if finalized_checkpoint.epoch > self.fc_store.finalized_checkpoint().epoch {
+            self.fc_store.set_finalized_checkpoint(finalized_checkpoint);
+            let finalized_slot =
+                compute_start_slot_at_epoch::<E>(self.fc_store.finalized_checkpoint().epoch);
+
+            // Note: the `if` statement here is not part of the specification, but I claim that it
+            // is an optimization and equivalent to the specification. See this PR for more
+            // information:
+            //
+            // https://github.com/ethereum/eth2.0-specs/pull/1880
+            if *self.fc_store.justified_checkpoint() != justified_checkpoint
+                && (justified_checkpoint.epoch > self.fc_store.justified_checkpoint().epoch
+                    || self
+                        .get_ancestor(self.fc_store.justified_checkpoint().root, finalized_slot)?
+                        != Some(self.fc_store.finalized_checkpoint().root))
+            {
+                self.fc_store
+                    .set_justified_checkpoint(justified_checkpoint)
+                    .map_err(Error::UnableToSetJustifiedCheckpoint)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Add `block` to the fork choice DAG.
     ///
     /// - `block_root` is the root of `block.
@@ -415,7 +546,7 @@ where
     ///
     /// Approximates:
     ///
-    /// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#on_block
+    /// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#on_block
     ///
     /// It only approximates the specification since it does not run the `state_transition` check.
     /// That should have already been called upstream and it's too expensive to call again.
@@ -430,6 +561,7 @@ where
         block: &BeaconBlock<E>,
         block_root: Hash256,
         state: &BeaconState<E>,
+        spec: &ChainSpec,
     ) -> Result<(), Error<T::Error>> {
         let current_slot = self.update_time(current_slot)?;
 
@@ -480,45 +612,13 @@ where
             }));
         }
 
-        // Update justified checkpoint.
-        if state.current_justified_checkpoint.epoch > self.fc_store.justified_checkpoint().epoch {
-            if state.current_justified_checkpoint.epoch
-                > self.fc_store.best_justified_checkpoint().epoch
-            {
-                self.fc_store
-                    .set_best_justified_checkpoint(state.current_justified_checkpoint);
-            }
-            if self.should_update_justified_checkpoint(current_slot, state)? {
-                self.fc_store
-                    .set_justified_checkpoint(state.current_justified_checkpoint)
-                    .map_err(Error::UnableToSetJustifiedCheckpoint)?;
-            }
-        }
-
-        // Update finalized checkpoint.
-        if state.finalized_checkpoint.epoch > self.fc_store.finalized_checkpoint().epoch {
-            self.fc_store
-                .set_finalized_checkpoint(state.finalized_checkpoint);
-            let finalized_slot =
-                compute_start_slot_at_epoch::<E>(self.fc_store.finalized_checkpoint().epoch);
-
-            // Note: the `if` statement here is not part of the specification, but I claim that it
-            // is an optimization and equivalent to the specification. See this PR for more
-            // information:
-            //
-            // https://github.com/ethereum/eth2.0-specs/pull/1880
-            if *self.fc_store.justified_checkpoint() != state.current_justified_checkpoint
-                && (state.current_justified_checkpoint.epoch
-                    > self.fc_store.justified_checkpoint().epoch
-                    || self
-                        .get_ancestor(self.fc_store.justified_checkpoint().root, finalized_slot)?
-                        != Some(self.fc_store.finalized_checkpoint().root))
-            {
-                self.fc_store
-                    .set_justified_checkpoint(state.current_justified_checkpoint)
-                    .map_err(Error::UnableToSetJustifiedCheckpoint)?;
-            }
-        }
+        // Update justified and finalized checkpoints.
+        self.update_checkpoints(
+            current_slot,
+            state,
+            state.current_justified_checkpoint,
+            state.finalized_checkpoint,
+        )?;
 
         let target_slot = block
             .slot
@@ -536,21 +636,59 @@ where
             .on_verified_block(block, block_root, state)
             .map_err(Error::AfterBlockFailed)?;
 
+        let (unrealized_justified_checkpoint, unrealized_finalized_checkpoint) =
+            compute_unrealized_checkpoints(state, spec)
+                .map_err(Error::UnrealizedCheckpointProcessing)?;
+
         // This does not apply a vote to the block, it just makes fork choice aware of the block so
         // it can still be identified as the head even if it doesn't have any votes.
-        self.proto_array.process_block(ProtoBlock {
-            slot: block.slot,
-            root: block_root,
-            parent_root: Some(block.parent_root),
-            target_root,
-            current_epoch_shuffling_id: ShufflingId::new(block_root, state, RelativeEpoch::Current)
+        self.proto_array.process_block::<E>(
+            ProtoBlock {
+                slot: block.slot,
+                root: block_root,
+                parent_root: Some(block.parent_root),
+                target_root,
+                current_epoch_shuffling_id: ShufflingId::new(
+                    block_root,
+                    state,
+                    RelativeEpoch::Current,
+                )
                 .map_err(Error::BeaconStateError)?,
-            next_epoch_shuffling_id: ShufflingId::new(block_root, state, RelativeEpoch::Next)
-                .map_err(Error::BeaconStateError)?,
-            state_root: block.state_root,
-            justified_epoch: state.current_justified_checkpoint.epoch,
-            finalized_epoch: state.finalized_checkpoint.epoch,
-        })?;
+                next_epoch_shuffling_id: ShufflingId::new(block_root, state, RelativeEpoch::Next)
+                    .map_err(Error::BeaconStateError)?,
+                state_root: block.state_root,
+                justified_epoch: state.current_justified_checkpoint.epoch,
+                finalized_epoch: state.finalized_checkpoint.epoch,
+                unrealized_justified_checkpoint: Some(unrealized_justified_checkpoint),
+                unrealized_finalized_checkpoint: Some(unrealized_finalized_checkpoint),
+            },
+            current_slot,
+        )?;
+
+        // Update the unrealized checkpoints of the store.
+        if unrealized_justified_checkpoint.epoch
+            > self.fc_store.unrealized_justified_checkpoint().epoch
+        {
+            self.fc_store
+                .set_unrealized_justified_checkpoint(unrealized_justified_checkpoint);
+        }
+        if unrealized_finalized_checkpoint.epoch
+            > self.fc_store.unrealized_finalized_checkpoint().epoch
+        {
+            self.fc_store
+                .set_unrealized_finalized_checkpoint(unrealized_finalized_checkpoint);
+        }
+
+        // If the block is from a prior epoch, its unrealized checkpoints would already have been
+        // realized at the epoch boundary, so pull them up now.
+        if block.slot.epoch(E::slots_per_epoch()) < current_slot.epoch(E::slots_per_epoch()) {
+            self.update_checkpoints(
+                current_slot,
+                state,
+                unrealized_justified_checkpoint,
+                unrealized_finalized_checkpoint,
+            )?;
+        }
 
         Ok(())
     }
@@ -266,7 +266,13 @@ impl ForkChoiceTest {
             .chain
             .fork_choice
             .write()
-            .on_block(current_slot, &block.message, block.canonical_root(), &state)
+            .on_block(
+                current_slot,
+                &block.message,
+                block.canonical_root(),
+                &state,
+                &self.harness.spec,
+            )
             .unwrap();
         self
     }
@@ -300,7 +306,13 @@ impl ForkChoiceTest {
             .chain
             .fork_choice
             .write()
-            .on_block(current_slot, &block.message, block.canonical_root(), &state)
+            .on_block(
+                current_slot,
+                &block.message,
+                block.canonical_root(),
+                &state,
+                &self.harness.spec,
+            )
             .err()
             .expect("on_block did not return an error");
         comparison_func(err);
@@ -981,6 +993,7 @@ fn weak_subjectivity_fail_on_startup() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(Checkpoint { epoch, root }),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config);
@@ -994,6 +1007,7 @@ fn weak_subjectivity_pass_on_startup() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(Checkpoint { epoch, root }),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config)
@@ -1019,6 +1033,7 @@ fn weak_subjectivity_check_passes() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1049,6 +1064,7 @@ fn weak_subjectivity_check_fails_early_epoch() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1078,6 +1094,7 @@ fn weak_subjectivity_check_fails_late_epoch() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1107,6 +1124,7 @@ fn weak_subjectivity_check_fails_incorrect_root() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1143,6 +1161,7 @@ fn weak_subjectivity_check_epoch_boundary_is_skip_slot() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     // recreate the chain exactly
@@ -1184,6 +1203,7 @@ fn weak_subjectivity_check_epoch_boundary_is_skip_slot_failure() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        ..ChainConfig::default()
     };
 
     // recreate the chain exactly
@@ -27,6 +27,10 @@ pub enum MerkleTree {
     ///
     /// It represents a Merkle tree of 2^depth zero leaves.
     Zero(usize),
+    /// Full subtree whose leaves have been pruned, known only by its root hash.
+    ///
+    /// No proofs can be generated for the leaves of a finalized subtree.
+    Finalized(H256),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -39,6 +43,8 @@ pub enum MerkleTreeError {
     Invalid,
     // Incorrect Depth provided
     DepthTooSmall,
+    // Depth exceeds the maximum supported depth
+    DepthTooLarge,
     // Overflow occurred
     ArithError,
 }
@@ -89,6 +95,7 @@ impl MerkleTree {
 
         match self {
             Leaf(_) => return Err(MerkleTreeError::LeafReached),
+            Finalized(_) => return Err(MerkleTreeError::MerkleTreeFull),
             Zero(_) => {
                 *self = MerkleTree::create(&[elem], depth);
             }
@@ -97,9 +104,11 @@ impl MerkleTree {
                 let right: &mut MerkleTree = &mut *right;
                 match (&*left, &*right) {
                     // Tree is full
-                    (Leaf(_), Leaf(_)) => return Err(MerkleTreeError::MerkleTreeFull),
+                    (Leaf(_), Leaf(_)) | (Finalized(_), Leaf(_)) | (Finalized(_), Finalized(_)) => {
+                        return Err(MerkleTreeError::MerkleTreeFull)
+                    }
                     // There is a right node so insert in right node
-                    (Node(_, _, _), Node(_, _, _)) => {
+                    (Node(_, _, _), Node(_, _, _)) | (Finalized(_), Node(_, _, _)) => {
                         if let Err(e) = right.push_leaf(elem, depth - 1) {
                             return Err(e);
                         }
@@ -108,8 +117,8 @@ impl MerkleTree {
                     (Zero(_), Zero(_)) => {
                         *left = MerkleTree::create(&[elem], depth - 1);
                     }
-                    // Leaf on left branch and zero on right branch, insert on right side
-                    (Leaf(_), Zero(_)) => {
+                    // Full left branch and zero on right branch, insert on right side
+                    (Leaf(_), Zero(_)) | (Finalized(_), Zero(_)) => {
                         *right = MerkleTree::create(&[elem], depth - 1);
                     }
                     // Try inserting on the left node -> if it fails because it is full, insert in right side.
@@ -141,6 +150,7 @@ impl MerkleTree {
         match *self {
             MerkleTree::Leaf(h) => h,
             MerkleTree::Node(h, _, _) => h,
+            MerkleTree::Finalized(h) => h,
             MerkleTree::Zero(depth) => H256::from_slice(&ZERO_HASHES[depth]),
         }
     }
@@ -148,7 +158,7 @@ impl MerkleTree {
     /// Get a reference to the left and right subtrees if they exist.
     pub fn left_and_right_branches(&self) -> Option<(&Self, &Self)> {
         match *self {
-            MerkleTree::Leaf(_) | MerkleTree::Zero(0) => None,
+            MerkleTree::Leaf(_) | MerkleTree::Zero(0) | MerkleTree::Finalized(_) => None,
             MerkleTree::Node(_, ref l, ref r) => Some((l, r)),
             MerkleTree::Zero(depth) => Some((&ZERO_NODES[depth - 1], &ZERO_NODES[depth - 1])),
         }
@@ -163,13 +173,16 @@ impl MerkleTree {
     ///
     /// The Merkle proof is in "bottom-up" order, starting with a leaf node
     /// and moving up the tree. Its length will be exactly equal to `depth`.
+    ///
+    /// Panics if `index` lies within a finalized subtree.
     pub fn generate_proof(&self, index: usize, depth: usize) -> (H256, Vec<H256>) {
         let mut proof = vec![];
         let mut current_node = self;
         let mut current_depth = depth;
         while current_depth > 0 {
             let ith_bit = (index >> (current_depth - 1)) & 0x01;
-            // Note: unwrap is safe because leaves are only ever constructed at depth == 0.
+            // Note: unwrap is safe because leaves are only ever constructed at depth == 0, and
+            // callers don't request proofs within finalized subtrees.
             let (left, right) = current_node.left_and_right_branches().unwrap();
 
             // Go right, include the left branch in the proof.
@@ -191,6 +204,114 @@ impl MerkleTree {
 
         (current_node.hash(), proof)
     }
+
+    /// Create a Merkle tree of `leaf_count` leaves from the roots of its finalized subtrees.
+    ///
+    /// `finalized` must hold the roots of the largest full subtrees covering the first
+    /// `leaf_count` leaves, from left to right, as returned by `get_finalized_hashes`. Leaves can
+    /// be pushed onto the resulting tree, but no proofs can be generated for the finalized leaves.
+    pub fn from_finalized_snapshot(
+        finalized: &[H256],
+        leaf_count: usize,
+        depth: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        if depth > MAX_TREE_DEPTH {
+            return Err(MerkleTreeError::DepthTooLarge);
+        }
+        if leaf_count > 2usize.pow(depth as u32) {
+            return Err(MerkleTreeError::MerkleTreeFull);
+        }
+        if finalized.len() != leaf_count.count_ones() as usize {
+            return Err(MerkleTreeError::Invalid);
+        }
+        Self::from_finalized_subtrees(finalized, leaf_count, depth)
+    }
+
+    fn from_finalized_subtrees(
+        finalized: &[H256],
+        leaf_count: usize,
+        depth: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        use MerkleTree::*;
+
+        if leaf_count == 0 {
+            return Ok(Zero(depth));
+        }
+        if leaf_count == 2usize.pow(depth as u32) {
+            return finalized
+                .first()
+                .map(|hash| Finalized(*hash))
+                .ok_or(MerkleTreeError::Invalid);
+        }
+
+        let subtree_capacity = 2usize.pow(depth as u32 - 1);
+        let (left_subtree, right_subtree) = if leaf_count <= subtree_capacity {
+            (
+                Self::from_finalized_subtrees(finalized, leaf_count, depth - 1)?,
+                Zero(depth - 1),
+            )
+        } else {
+            let (left_finalized, right_finalized) = finalized.split_at(1);
+            (
+                Self::from_finalized_subtrees(left_finalized, subtree_capacity, depth - 1)?,
+                Self::from_finalized_subtrees(
+                    right_finalized,
+                    leaf_count - subtree_capacity,
+                    depth - 1,
+                )?,
+            )
+        };
+        let hash = H256::from_slice(&hash32_concat(
+            left_subtree.hash().as_bytes(),
+            right_subtree.hash().as_bytes(),
+        ));
+
+        Ok(Node(hash, Box::new(left_subtree), Box::new(right_subtree)))
+    }
+
+    /// Return the roots of the largest full subtrees covering the first `leaf_count` leaves, from
+    /// left to right.
+    ///
+    /// These are all that's needed to recompute the root of the tree of those leaves, or to push
+    /// further leaves onto it. `leaf_count` must not exceed the number of leaves in the tree.
+    pub fn get_finalized_hashes(
+        &self,
+        leaf_count: usize,
+        depth: usize,
+    ) -> Result<Vec<H256>, MerkleTreeError> {
+        if leaf_count > 2usize.pow(depth as u32) {
+            return Err(MerkleTreeError::MerkleTreeFull);
+        }
+        let mut finalized = vec![];
+        self.append_finalized_hashes(leaf_count, depth, &mut finalized)?;
+        Ok(finalized)
+    }
+
+    fn append_finalized_hashes(
+        &self,
+        leaf_count: usize,
+        depth: usize,
+        finalized: &mut Vec<H256>,
+    ) -> Result<(), MerkleTreeError> {
+        if leaf_count == 0 {
+            return Ok(());
+        }
+        if leaf_count == 2usize.pow(depth as u32) {
+            finalized.push(self.hash());
+            return Ok(());
+        }
+
+        let (left, right) = self
+            .left_and_right_branches()
+            .ok_or(MerkleTreeError::Invalid)?;
+        let subtree_capacity = 2usize.pow(depth as u32 - 1);
+        if leaf_count <= subtree_capacity {
+            left.append_finalized_hashes(leaf_count, depth - 1, finalized)
+        } else {
+            left.append_finalized_hashes(subtree_capacity, depth - 1, finalized)?;
+            right.append_finalized_hashes(leaf_count - subtree_capacity, depth - 1, finalized)
+        }
+    }
 }
 
 /// Verify a proof that `leaf` exists at `index` in a Merkle tree rooted at `root`.
@@ -283,6 +404,73 @@ mod tests {
         TestResult::from_bool(proofs_ok)
     }
 
+    /// Check that a MerkleTree restored from the finalized hashes of a prefix of its leaves has
+    /// the same root, and that pushing the remaining leaves yields valid proofs for them.
+    #[quickcheck]
+    fn quickcheck_finalized_snapshot(
+        int_leaves: Vec<u64>,
+        finalized_count: usize,
+        depth: usize,
+    ) -> TestResult {
+        if depth == 0
+            || depth > MAX_TREE_DEPTH
+            || int_leaves.len() > 2usize.pow(depth as u32)
+            || finalized_count > int_leaves.len()
+        {
+            return TestResult::discard();
+        }
+
+        let leaves: Vec<_> = int_leaves.into_iter().map(H256::from_low_u64_be).collect();
+        let full_tree = MerkleTree::create(&leaves, depth);
+        let partial_tree = MerkleTree::create(&leaves[..finalized_count], depth);
+
+        let finalized = full_tree
+            .get_finalized_hashes(finalized_count, depth)
+            .expect("should get finalized hashes");
+        let mut merkle_tree =
+            MerkleTree::from_finalized_snapshot(&finalized, finalized_count, depth)
+                .expect("should restore from snapshot");
+        if merkle_tree.hash() != partial_tree.hash() {
+            return TestResult::failed();
+        }
+
+        let proofs_ok = leaves
+            .into_iter()
+            .enumerate()
+            .skip(finalized_count)
+            .all(|(i, leaf)| {
+                assert_eq!(merkle_tree.push_leaf(leaf, depth), Ok(()));
+                let (stored_leaf, branch) = merkle_tree.generate_proof(i, depth);
+                stored_leaf == leaf
+                    && verify_merkle_proof(leaf, &branch, depth, i, merkle_tree.hash())
+            });
+
+        TestResult::from_bool(proofs_ok && merkle_tree.hash() == full_tree.hash())
+    }
+
+    #[test]
+    fn finalized_snapshot_invalid() {
+        let leaves: Vec<_> = (0..5).map(H256::from_low_u64_be).collect();
+        let tree = MerkleTree::create(&leaves, 3);
+        let finalized = tree.get_finalized_hashes(5, 3).unwrap();
+        assert_eq!(finalized.len(), 2);
+
+        // Too few or too many finalized hashes for the leaf count.
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&finalized[..1], 5, 3),
+            Err(MerkleTreeError::Invalid)
+        );
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&finalized, 4, 3),
+            Err(MerkleTreeError::Invalid)
+        );
+        // More leaves than the tree can hold.
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&finalized, 9, 3),
+            Err(MerkleTreeError::MerkleTreeFull)
+        );
+    }
+
     #[test]
     fn sparse_zero_correct() {
         let depth = 2;
@@ -4,7 +4,7 @@ mod votes;
 
 use crate::proto_array_fork_choice::{Block, ProtoArrayForkChoice};
 use serde_derive::{Deserialize, Serialize};
-use types::{Epoch, Hash256, ShufflingId, Slot};
+use types::{Epoch, Hash256, MainnetEthSpec, ShufflingId, Slot};
 
 pub use ffg_updates::*;
 pub use no_votes::*;
@@ -56,7 +56,7 @@ pub struct ForkChoiceTestDefinition {
 impl ForkChoiceTestDefinition {
     pub fn run(self) {
         let junk_shuffling_id = ShufflingId::from_components(Epoch::new(0), Hash256::zero());
-        let mut fork_choice = ProtoArrayForkChoice::new(
+        let mut fork_choice = ProtoArrayForkChoice::new::<MainnetEthSpec>(
             self.finalized_block_slot,
             Hash256::zero(),
             self.justified_epoch,
@@ -67,6 +67,9 @@ impl ForkChoiceTestDefinition {
         )
         .expect("should create fork choice struct");
 
+        // The clock is advanced to the latest block as blocks are processed.
+        let mut current_slot = self.finalized_block_slot;
+
         for (op_index, op) in self.operations.into_iter().enumerate() {
             match op.clone() {
                 Operation::FindHead {
@@ -77,11 +80,12 @@ impl ForkChoiceTestDefinition {
                     expected_head,
                 } => {
                     let head = fork_choice
-                        .find_head(
+                        .find_head::<MainnetEthSpec>(
                             justified_epoch,
                             justified_root,
                             finalized_epoch,
                             &justified_state_balances,
+                            current_slot,
                         )
                         .unwrap_or_else(|_| {
                             panic!("find_head op at index {} returned error", op_index)
@@ -100,11 +104,12 @@ impl ForkChoiceTestDefinition {
                     finalized_epoch,
                     justified_state_balances,
                 } => {
-                    let result = fork_choice.find_head(
+                    let result = fork_choice.find_head::<MainnetEthSpec>(
                         justified_epoch,
                         justified_root,
                         finalized_epoch,
                         &justified_state_balances,
+                        current_slot,
                     );
 
                     assert!(
@@ -138,13 +143,18 @@ impl ForkChoiceTestDefinition {
                         ),
                         justified_epoch,
                         finalized_epoch,
+                        unrealized_justified_checkpoint: None,
+                        unrealized_finalized_checkpoint: None,
                     };
-                    fork_choice.process_block(block).unwrap_or_else(|e| {
-                        panic!(
-                            "process_block op at index {} returned error: {:?}",
-                            op_index, e
-                        )
-                    });
+                    current_slot = std::cmp::max(current_slot, slot);
+                    fork_choice
+                        .process_block::<MainnetEthSpec>(block, current_slot)
+                        .unwrap_or_else(|e| {
+                            panic!(
+                                "process_block op at index {} returned error: {:?}",
+                                op_index, e
+                            )
+                        });
                     check_bytes_round_trip(&fork_choice);
                 }
                 Operation::ProcessAttestation {
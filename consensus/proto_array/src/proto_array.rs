@@ -2,7 +2,7 @@ use crate::{error::Error, Block};
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use std::collections::HashMap;
-use types::{Epoch, Hash256, ShufflingId, Slot};
+use types::{Checkpoint, Epoch, EthSpec, Hash256, ShufflingId, Slot};
 
 #[derive(Clone, PartialEq, Debug, Encode, Decode, Serialize, Deserialize)]
 pub struct ProtoNode {
@@ -24,6 +24,17 @@ pub struct ProtoNode {
     pub parent: Option<usize>,
     pub justified_epoch: Epoch,
     pub finalized_epoch: Epoch,
+    /// The checkpoints that would be justified and finalized if the epoch of this block were
+    /// processed now, i.e. the "pulled-up" checkpoints from the spec.
+    ///
+    /// These are not persisted to disk; nodes loaded from disk fall back to their realized
+    /// checkpoints until they are pruned.
+    #[ssz(skip_serializing)]
+    #[ssz(skip_deserializing)]
+    pub unrealized_justified_checkpoint: Option<Checkpoint>,
+    #[ssz(skip_serializing)]
+    #[ssz(skip_deserializing)]
+    pub unrealized_finalized_checkpoint: Option<Checkpoint>,
     weight: u64,
     best_child: Option<usize>,
     best_descendant: Option<usize>,
@@ -54,11 +65,12 @@ impl ProtoArray {
     /// - Compare the current node with the parents best-child, updating it if the current node
     /// should become the best child.
     /// - If required, update the parents best-descendant with the current node or its best-descendant.
-    pub fn apply_score_changes(
+    pub fn apply_score_changes<E: EthSpec>(
         &mut self,
         mut deltas: Vec<i64>,
         justified_epoch: Epoch,
         finalized_epoch: Epoch,
+        current_slot: Slot,
     ) -> Result<(), Error> {
         if deltas.len() != self.indices.len() {
             return Err(Error::InvalidDeltaLen {
@@ -122,7 +134,11 @@ impl ProtoArray {
                 // Back-propagate the nodes delta to its parent.
                 *parent_delta += node_delta;
 
-                self.maybe_update_best_child_and_descendant(parent_index, node_index)?;
+                self.maybe_update_best_child_and_descendant::<E>(
+                    parent_index,
+                    node_index,
+                    current_slot,
+                )?;
             }
         }
 
@@ -132,7 +148,7 @@ impl ProtoArray {
     /// Register a block with the fork choice.
     ///
     /// It is only sane to supply a `None` parent for the genesis block.
-    pub fn on_block(&mut self, block: Block) -> Result<(), Error> {
+    pub fn on_block<E: EthSpec>(&mut self, block: Block, current_slot: Slot) -> Result<(), Error> {
         // If the block is already known, simply ignore it.
         if self.indices.contains_key(&block.root) {
             return Ok(());
@@ -152,6 +168,8 @@ impl ProtoArray {
                 .and_then(|parent| self.indices.get(&parent).copied()),
             justified_epoch: block.justified_epoch,
             finalized_epoch: block.finalized_epoch,
+            unrealized_justified_checkpoint: block.unrealized_justified_checkpoint,
+            unrealized_finalized_checkpoint: block.unrealized_finalized_checkpoint,
             weight: 0,
             best_child: None,
             best_descendant: None,
@@ -161,7 +179,11 @@ impl ProtoArray {
         self.nodes.push(node.clone());
 
         if let Some(parent_index) = node.parent {
-            self.maybe_update_best_child_and_descendant(parent_index, node_index)?;
+            self.maybe_update_best_child_and_descendant::<E>(
+                parent_index,
+                node_index,
+                current_slot,
+            )?;
         }
 
         Ok(())
@@ -175,7 +197,11 @@ impl ProtoArray {
     /// been called without a subsequent `Self::apply_score_changes` call. This is because
     /// `on_new_block` does not attempt to walk backwards through the tree and update the
     /// best-child/best-descendant links.
-    pub fn find_head(&self, justified_root: &Hash256) -> Result<Hash256, Error> {
+    pub fn find_head<E: EthSpec>(
+        &self,
+        justified_root: &Hash256,
+        current_slot: Slot,
+    ) -> Result<Hash256, Error> {
         let justified_index = self
             .indices
             .get(justified_root)
@@ -197,7 +223,7 @@ impl ProtoArray {
             .ok_or_else(|| Error::InvalidBestDescendant(best_descendant_index))?;
 
         // Perform a sanity check that the node is indeed valid to be the head.
-        if !self.node_is_viable_for_head(&best_node) {
+        if !self.node_is_viable_for_head::<E>(&best_node, current_slot) {
             return Err(Error::InvalidBestNode {
                 start_root: *justified_root,
                 justified_epoch: self.justified_epoch,
@@ -293,10 +319,11 @@ impl ProtoArray {
     ///     best-descendant.
     /// - The child is not the best child but becomes the best child.
     /// - The child is not the best child and does not become the best child.
-    fn maybe_update_best_child_and_descendant(
+    fn maybe_update_best_child_and_descendant<E: EthSpec>(
         &mut self,
         parent_index: usize,
         child_index: usize,
+        current_slot: Slot,
     ) -> Result<(), Error> {
         let child = self
             .nodes
@@ -308,7 +335,8 @@ impl ProtoArray {
             .get(parent_index)
             .ok_or_else(|| Error::InvalidNodeIndex(parent_index))?;
 
-        let child_leads_to_viable_head = self.node_leads_to_viable_head(&child)?;
+        let child_leads_to_viable_head =
+            self.node_leads_to_viable_head::<E>(&child, current_slot)?;
 
         // These three variables are aliases to the three options that we may set the
         // `parent.best_child` and `parent.best_descendant` to.
@@ -338,7 +366,7 @@ impl ProtoArray {
                         .ok_or_else(|| Error::InvalidBestDescendant(best_child_index))?;
 
                     let best_child_leads_to_viable_head =
-                        self.node_leads_to_viable_head(&best_child)?;
+                        self.node_leads_to_viable_head::<E>(&best_child, current_slot)?;
 
                     if child_leads_to_viable_head && !best_child_leads_to_viable_head {
                         // The child leads to a viable head, but the current best-child doesn't.
@@ -383,7 +411,11 @@ impl ProtoArray {
 
     /// Indicates if the node itself is viable for the head, or if it's best descendant is viable
     /// for the head.
-    fn node_leads_to_viable_head(&self, node: &ProtoNode) -> Result<bool, Error> {
+    fn node_leads_to_viable_head<E: EthSpec>(
+        &self,
+        node: &ProtoNode,
+        current_slot: Slot,
+    ) -> Result<bool, Error> {
         let best_descendant_is_viable_for_head =
             if let Some(best_descendant_index) = node.best_descendant {
                 let best_descendant = self
@@ -391,24 +423,38 @@ impl ProtoArray {
                     .get(best_descendant_index)
                     .ok_or_else(|| Error::InvalidBestDescendant(best_descendant_index))?;
 
-                self.node_is_viable_for_head(best_descendant)
+                self.node_is_viable_for_head::<E>(best_descendant, current_slot)
             } else {
                 false
             };
 
-        Ok(best_descendant_is_viable_for_head || self.node_is_viable_for_head(node))
+        Ok(best_descendant_is_viable_for_head
+            || self.node_is_viable_for_head::<E>(node, current_slot))
     }
 
     /// This is the equivalent to the `filter_block_tree` function in the eth2 spec:
     ///
-    /// https://github.com/ethereum/eth2.0-specs/blob/v0.10.0/specs/phase0/fork-choice.md#filter_block_tree
+    /// https://github.com/ethereum/consensus-specs/blob/v1.2.0/specs/phase0/fork-choice.md#filter_block_tree
     ///
     /// Any node that has a different finalized or justified epoch should not be viable for the
-    /// head.
-    fn node_is_viable_for_head(&self, node: &ProtoNode) -> bool {
-        (node.justified_epoch == self.justified_epoch || self.justified_epoch == Epoch::new(0))
-            && (node.finalized_epoch == self.finalized_epoch
-                || self.finalized_epoch == Epoch::new(0))
+    /// head. Nodes from prior epochs are judged by their unrealized checkpoints (if known), since
+    /// those would have been realized at the epoch boundary.
+    fn node_is_viable_for_head<E: EthSpec>(&self, node: &ProtoNode, current_slot: Slot) -> bool {
+        let node_epoch = node.slot.epoch(E::slots_per_epoch());
+        let current_epoch = current_slot.epoch(E::slots_per_epoch());
+
+        let (justified_epoch, finalized_epoch) = match (
+            node.unrealized_justified_checkpoint,
+            node.unrealized_finalized_checkpoint,
+        ) {
+            (Some(justified), Some(finalized)) if node_epoch < current_epoch => {
+                (justified.epoch, finalized.epoch)
+            }
+            _ => (node.justified_epoch, node.finalized_epoch),
+        };
+
+        (justified_epoch == self.justified_epoch || self.justified_epoch == Epoch::new(0))
+            && (finalized_epoch == self.finalized_epoch || self.finalized_epoch == Epoch::new(0))
     }
 
     /// Return a reverse iterator over the nodes which comprise the chain ending at `block_root`.
@@ -4,7 +4,7 @@ use crate::ssz_container::SszContainer;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use std::collections::HashMap;
-use types::{Epoch, Hash256, ShufflingId, Slot};
+use types::{Checkpoint, Epoch, EthSpec, Hash256, ShufflingId, Slot};
 
 pub const DEFAULT_PRUNE_THRESHOLD: usize = 256;
 
@@ -29,6 +29,8 @@ pub struct Block {
     pub next_epoch_shuffling_id: ShufflingId,
     pub justified_epoch: Epoch,
     pub finalized_epoch: Epoch,
+    pub unrealized_justified_checkpoint: Option<Checkpoint>,
+    pub unrealized_finalized_checkpoint: Option<Checkpoint>,
 }
 
 /// A Vec-wrapper which will grow to match any request.
@@ -66,7 +68,7 @@ pub struct ProtoArrayForkChoice {
 }
 
 impl ProtoArrayForkChoice {
-    pub fn new(
+    pub fn new<E: EthSpec>(
         finalized_block_slot: Slot,
         finalized_block_state_root: Hash256,
         justified_epoch: Epoch,
@@ -95,10 +97,12 @@ impl ProtoArrayForkChoice {
             next_epoch_shuffling_id,
             justified_epoch,
             finalized_epoch,
+            unrealized_justified_checkpoint: None,
+            unrealized_finalized_checkpoint: None,
         };
 
         proto_array
-            .on_block(block)
+            .on_block::<E>(block, finalized_block_slot)
             .map_err(|e| format!("Failed to add finalized block to proto_array: {:?}", e))?;
 
         Ok(Self {
@@ -124,22 +128,27 @@ impl ProtoArrayForkChoice {
         Ok(())
     }
 
-    pub fn process_block(&mut self, block: Block) -> Result<(), String> {
+    pub fn process_block<E: EthSpec>(
+        &mut self,
+        block: Block,
+        current_slot: Slot,
+    ) -> Result<(), String> {
         if block.parent_root.is_none() {
             return Err("Missing parent root".to_string());
         }
 
         self.proto_array
-            .on_block(block)
+            .on_block::<E>(block, current_slot)
             .map_err(|e| format!("process_block_error: {:?}", e))
     }
 
-    pub fn find_head(
+    pub fn find_head<E: EthSpec>(
         &mut self,
         justified_epoch: Epoch,
         justified_root: Hash256,
         finalized_epoch: Epoch,
         justified_state_balances: &[u64],
+        current_slot: Slot,
     ) -> Result<Hash256, String> {
         let old_balances = &mut self.balances;
 
@@ -154,13 +163,13 @@ impl ProtoArrayForkChoice {
         .map_err(|e| format!("find_head compute_deltas failed: {:?}", e))?;
 
         self.proto_array
-            .apply_score_changes(deltas, justified_epoch, finalized_epoch)
+            .apply_score_changes::<E>(deltas, justified_epoch, finalized_epoch, current_slot)
             .map_err(|e| format!("find_head apply_score_changes failed: {:?}", e))?;
 
         *old_balances = new_balances.to_vec();
 
         self.proto_array
-            .find_head(&justified_root)
+            .find_head::<E>(&justified_root, current_slot)
             .map_err(|e| format!("find_head failed: {:?}", e))
     }
 
@@ -204,6 +213,8 @@ impl ProtoArrayForkChoice {
             next_epoch_shuffling_id: block.next_epoch_shuffling_id.clone(),
             justified_epoch: block.justified_epoch,
             finalized_epoch: block.finalized_epoch,
+            unrealized_justified_checkpoint: block.unrealized_justified_checkpoint,
+            unrealized_finalized_checkpoint: block.unrealized_finalized_checkpoint,
         })
     }
 
@@ -333,6 +344,7 @@ fn compute_deltas(
 #[cfg(test)]
 mod test_compute_deltas {
     use super::*;
+    use types::MainnetEthSpec;
 
     /// Gives a hash that is not the zero hash (unless i is `usize::max_value)`.
     fn hash_from_index(i: usize) -> Hash256 {
@@ -351,7 +363,7 @@ mod test_compute_deltas {
         let unknown = Hash256::from_low_u64_be(4);
         let junk_shuffling_id = ShufflingId::from_components(Epoch::new(0), Hash256::zero());
 
-        let mut fc = ProtoArrayForkChoice::new(
+        let mut fc = ProtoArrayForkChoice::new::<MainnetEthSpec>(
             genesis_slot,
             state_root,
             genesis_epoch,
@@ -364,32 +376,42 @@ mod test_compute_deltas {
 
         // Add block that is a finalized descendant.
         fc.proto_array
-            .on_block(Block {
-                slot: genesis_slot + 1,
-                root: finalized_desc,
-                parent_root: Some(finalized_root),
-                state_root,
-                target_root: finalized_root,
-                current_epoch_shuffling_id: junk_shuffling_id.clone(),
-                next_epoch_shuffling_id: junk_shuffling_id.clone(),
-                justified_epoch: genesis_epoch,
-                finalized_epoch: genesis_epoch,
-            })
+            .on_block::<MainnetEthSpec>(
+                Block {
+                    slot: genesis_slot + 1,
+                    root: finalized_desc,
+                    parent_root: Some(finalized_root),
+                    state_root,
+                    target_root: finalized_root,
+                    current_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    next_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    justified_epoch: genesis_epoch,
+                    finalized_epoch: genesis_epoch,
+                    unrealized_justified_checkpoint: None,
+                    unrealized_finalized_checkpoint: None,
+                },
+                genesis_slot + 1,
+            )
             .unwrap();
 
         // Add block that is *not* a finalized descendant.
         fc.proto_array
-            .on_block(Block {
-                slot: genesis_slot + 1,
-                root: not_finalized_desc,
-                parent_root: None,
-                state_root,
-                target_root: finalized_root,
-                current_epoch_shuffling_id: junk_shuffling_id.clone(),
-                next_epoch_shuffling_id: junk_shuffling_id.clone(),
-                justified_epoch: genesis_epoch,
-                finalized_epoch: genesis_epoch,
-            })
+            .on_block::<MainnetEthSpec>(
+                Block {
+                    slot: genesis_slot + 1,
+                    root: not_finalized_desc,
+                    parent_root: None,
+                    state_root,
+                    target_root: finalized_root,
+                    current_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    next_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    justified_epoch: genesis_epoch,
+                    finalized_epoch: genesis_epoch,
+                    unrealized_justified_checkpoint: None,
+                    unrealized_finalized_checkpoint: None,
+                },
+                genesis_slot + 1,
+            )
             .unwrap();
 
         assert!(!fc.is_descendant(unknown, unknown));
@@ -413,6 +435,81 @@ mod test_compute_deltas {
         assert!(!fc.is_descendant(not_finalized_desc, unknown));
     }
 
+    #[test]
+    fn unrealized_justification() {
+        type E = MainnetEthSpec;
+
+        let genesis_slot = Slot::new(0);
+        let genesis_epoch = Epoch::new(0);
+        let justified_epoch = Epoch::new(1);
+
+        let state_root = Hash256::from_low_u64_be(0);
+        let finalized_root = Hash256::from_low_u64_be(1);
+        let pulled_up = Hash256::from_low_u64_be(2);
+        let not_pulled_up = Hash256::from_low_u64_be(3);
+        let junk_shuffling_id = ShufflingId::from_components(Epoch::new(0), Hash256::zero());
+
+        let mut fc = ProtoArrayForkChoice::new::<E>(
+            genesis_slot,
+            state_root,
+            justified_epoch,
+            genesis_epoch,
+            finalized_root,
+            junk_shuffling_id.clone(),
+            junk_shuffling_id.clone(),
+        )
+        .unwrap();
+
+        // Two blocks which haven't realized the justification of epoch 1, of which only one
+        // contains enough attestations to do so at the end of the epoch.
+        for (root, unrealized_justified_epoch) in &[(pulled_up, 1), (not_pulled_up, 0)] {
+            let slot = genesis_slot + 1;
+            fc.process_block::<E>(
+                Block {
+                    slot,
+                    root: *root,
+                    parent_root: Some(finalized_root),
+                    state_root,
+                    target_root: finalized_root,
+                    current_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    next_epoch_shuffling_id: junk_shuffling_id.clone(),
+                    justified_epoch: genesis_epoch,
+                    finalized_epoch: genesis_epoch,
+                    unrealized_justified_checkpoint: Some(Checkpoint {
+                        epoch: Epoch::new(*unrealized_justified_epoch),
+                        root: finalized_root,
+                    }),
+                    unrealized_finalized_checkpoint: Some(Checkpoint {
+                        epoch: genesis_epoch,
+                        root: finalized_root,
+                    }),
+                },
+                slot,
+            )
+            .unwrap();
+        }
+
+        let mut find_head = |current_slot| {
+            fc.find_head::<E>(
+                justified_epoch,
+                finalized_root,
+                genesis_epoch,
+                &[],
+                current_slot,
+            )
+            .unwrap()
+        };
+
+        // During their own epoch, neither block is viable.
+        assert_eq!(find_head(Slot::new(2)), finalized_root);
+
+        // Once their epoch has passed, the block which justifies epoch 1 is pulled up.
+        assert_eq!(
+            find_head(justified_epoch.start_slot(E::slots_per_epoch())),
+            pulled_up
+        );
+    }
+
     #[test]
     fn zero_hash() {
         let validator_count: usize = 16;
@@ -21,6 +21,34 @@ impl DepositDataTree {
         }
     }
 
+    /// Create a Merkle tree of `mix_in_length` deposits from the roots of its finalized subtrees,
+    /// as held by a `DepositTreeSnapshot`.
+    ///
+    /// Further deposits can be pushed, but no proofs can be generated for the finalized deposits.
+    pub fn from_finalized_snapshot(
+        finalized: &[Hash256],
+        mix_in_length: usize,
+        depth: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        Ok(Self {
+            tree: MerkleTree::from_finalized_snapshot(finalized, mix_in_length, depth)?,
+            mix_in_length,
+            depth,
+        })
+    }
+
+    /// Return the roots of the finalized subtrees of the first `deposit_count` deposits, from
+    /// which the tree of those deposits can be restored.
+    pub fn get_finalized_hashes(
+        &self,
+        deposit_count: usize,
+    ) -> Result<Vec<Hash256>, MerkleTreeError> {
+        if deposit_count > self.mix_in_length {
+            return Err(MerkleTreeError::Invalid);
+        }
+        self.tree.get_finalized_hashes(deposit_count, self.depth)
+    }
+
     /// Returns 32 bytes representing the "mix in length" for the merkle root of this tree.
     fn length_bytes(&self) -> Vec<u8> {
         int_to_bytes32(self.mix_in_length as u64)
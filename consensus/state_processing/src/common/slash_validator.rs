@@ -26,6 +26,11 @@ pub fn slash_validator<T: EthSpec>(
         epoch.safe_add(T::EpochsPerSlashingsVector::to_u64())?,
     );
     let validator_effective_balance = state.get_effective_balance(slashed_index, spec)?;
+    if state.progressive_balances_cache.is_initialized_at(epoch) {
+        state
+            .progressive_balances_cache
+            .on_slashing(slashed_index, validator_effective_balance)?;
+    }
     state.set_slashings(
         epoch,
         state
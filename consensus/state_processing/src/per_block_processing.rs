@@ -1,4 +1,6 @@
-use crate::common::{increase_balance, initiate_validator_exit, slash_validator};
+use crate::common::{
+    get_attesting_indices, increase_balance, initiate_validator_exit, slash_validator,
+};
 use errors::{BlockOperationError, BlockProcessingError, HeaderInvalid, IntoWithIndex};
 use rayon::prelude::*;
 use safe_arith::{ArithError, SafeArith};
@@ -365,6 +367,13 @@ pub fn process_attestations<T: EthSpec>(
         verify_attestation_for_block_inclusion(state, attestation, verify_signatures, spec)
             .map_err(|e| e.into_with_index(i))?;
 
+        if state
+            .progressive_balances_cache
+            .is_initialized_at(state.current_epoch())
+        {
+            update_progressive_balances_cache(state, attestation)?;
+        }
+
         let pending_attestation = PendingAttestation {
             aggregation_bits: attestation.aggregation_bits.clone(),
             data: attestation.data.clone(),
@@ -384,6 +393,39 @@ pub fn process_attestations<T: EthSpec>(
     Ok(())
 }
 
+/// Counts the unslashed attesters of `attestation` towards the progressive balances cache of
+/// `state`, if the attestation is for the correct target.
+fn update_progressive_balances_cache<T: EthSpec>(
+    state: &mut BeaconState<T>,
+    attestation: &Attestation<T>,
+) -> Result<(), BlockProcessingError> {
+    let target = attestation.data.target;
+    if target.root != *state.get_block_root_at_epoch(target.epoch)? {
+        return Ok(());
+    }
+
+    let committee = state.get_beacon_committee(attestation.data.slot, attestation.data.index)?;
+    let attesting_indices =
+        get_attesting_indices::<T>(committee.committee, &attestation.aggregation_bits)?;
+
+    for validator_index in attesting_indices {
+        let validator = state
+            .validators
+            .get(validator_index)
+            .ok_or(BeaconStateError::UnknownValidator(validator_index as u64))?;
+        if !validator.slashed {
+            let effective_balance = validator.effective_balance;
+            state.progressive_balances_cache.on_target_attestation(
+                target.epoch,
+                validator_index,
+                effective_balance,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Validates each `Deposit` and updates the state, short-circuiting on an invalid object.
 ///
 /// Returns `Ok(())` if the validation and state updates completed successfully, otherwise returns
@@ -2,6 +2,7 @@
 
 use super::block_processing_builder::BlockProcessingBuilder;
 use super::errors::*;
+use crate::per_epoch_processing::ValidatorStatuses;
 use crate::{per_block_processing, BlockSignatureStrategy};
 use types::test_utils::{
     AttestationTestTask, AttesterSlashingTestTask, DepositTestTask, ProposerSlashingTestTask,
@@ -332,6 +333,47 @@ fn valid_attestations() {
     assert_eq!(result, Ok(()));
 }
 
+#[test]
+fn progressive_balances_match_validator_statuses() {
+    let spec = MainnetEthSpec::default_spec();
+    let builder = get_builder(&spec, EPOCH_OFFSET, VALIDATOR_COUNT);
+    let test_task = AttestationTestTask::Valid;
+    let (block, mut state) = builder.build_with_n_attestations(test_task, 4, None, None, &spec);
+
+    per_block_processing(
+        &mut state,
+        &block,
+        None,
+        BlockSignatureStrategy::VerifyIndividual,
+        &spec,
+    )
+    .expect("block should process");
+
+    let mut validator_statuses = ValidatorStatuses::new(&state, &spec).unwrap();
+    validator_statuses
+        .process_attestations(&state, &spec)
+        .unwrap();
+    let total_balances = &validator_statuses.total_balances;
+    let cache = &state.progressive_balances_cache;
+
+    assert_eq!(
+        cache.current_epoch_total_active_balance(),
+        Ok(total_balances.current_epoch())
+    );
+    assert_eq!(
+        cache.previous_epoch_target_attesting_balance(),
+        Ok(total_balances.previous_epoch_target_attesters())
+    );
+    assert_eq!(
+        cache.current_epoch_target_attesting_balance(),
+        Ok(total_balances.current_epoch_target_attesters())
+    );
+    assert_eq!(
+        ProgressiveBalancesCache::new(&state, &spec).as_ref(),
+        Ok(cache)
+    );
+}
+
 #[test]
 fn invalid_attestation_no_committee_for_index() {
     let spec = MainnetEthSpec::default_spec();
@@ -1,4 +1,5 @@
 use errors::EpochProcessingError as Error;
+use rayon::prelude::*;
 use safe_arith::SafeArith;
 use tree_hash::TreeHash;
 use types::*;
@@ -60,6 +61,11 @@ pub fn per_epoch_processing<T: EthSpec>(
     // Final updates.
     process_final_updates(state, spec)?;
 
+    // Carry the current epoch's target attesters into the next epoch as its previous epoch.
+    state
+        .progressive_balances_cache
+        .on_epoch_transition(&state.validators)?;
+
     // Rotate the epoch caches to suit the epoch transition.
     state.advance_caches();
 
@@ -79,10 +85,28 @@ pub fn per_epoch_processing<T: EthSpec>(
 /// - `finalized_root`
 ///
 /// Spec v0.12.1
-#[allow(clippy::if_same_then_else)] // For readability and consistency with spec.
 pub fn process_justification_and_finalization<T: EthSpec>(
     state: &mut BeaconState<T>,
     total_balances: &TotalBalances,
+) -> Result<(), Error> {
+    weigh_justification_and_finalization(
+        state,
+        total_balances.current_epoch(),
+        total_balances.previous_epoch_target_attesters(),
+        total_balances.current_epoch_target_attesters(),
+    )
+}
+
+/// Apply `process_justification_and_finalization` using the given balances, rather than those of a
+/// `TotalBalances`.
+///
+/// This allows the balances to be provided by the state's `ProgressiveBalancesCache`.
+#[allow(clippy::if_same_then_else)] // For readability and consistency with spec.
+pub fn weigh_justification_and_finalization<T: EthSpec>(
+    state: &mut BeaconState<T>,
+    total_active_balance: u64,
+    previous_target_balance: u64,
+    current_target_balance: u64,
 ) -> Result<(), Error> {
     if state.current_epoch() <= T::genesis_epoch().safe_add(1)? {
         return Ok(());
@@ -98,11 +122,7 @@ pub fn process_justification_and_finalization<T: EthSpec>(
     state.previous_justified_checkpoint = state.current_justified_checkpoint;
     state.justification_bits.shift_up(1)?;
 
-    if total_balances
-        .previous_epoch_target_attesters()
-        .safe_mul(3)?
-        >= total_balances.current_epoch().safe_mul(2)?
-    {
+    if previous_target_balance.safe_mul(3)? >= total_active_balance.safe_mul(2)? {
         state.current_justified_checkpoint = Checkpoint {
             epoch: previous_epoch,
             root: *state.get_block_root_at_epoch(previous_epoch)?,
@@ -110,11 +130,7 @@ pub fn process_justification_and_finalization<T: EthSpec>(
         state.justification_bits.set(1, true)?;
     }
     // If the current epoch gets justified, fill the last bit.
-    if total_balances
-        .current_epoch_target_attesters()
-        .safe_mul(3)?
-        >= total_balances.current_epoch().safe_mul(2)?
-    {
+    if current_target_balance.safe_mul(3)? >= total_active_balance.safe_mul(2)? {
         state.current_justified_checkpoint = Checkpoint {
             epoch: current_epoch,
             root: *state.get_block_root_at_epoch(current_epoch)?,
@@ -178,19 +194,27 @@ pub fn process_final_updates<T: EthSpec>(
         .safe_div(spec.hysteresis_quotient)?;
     let downward_threshold = hysteresis_increment.safe_mul(spec.hysteresis_downward_multiplier)?;
     let upward_threshold = hysteresis_increment.safe_mul(spec.hysteresis_upward_multiplier)?;
-    for (index, validator) in state.validators.iter_mut().enumerate() {
-        let balance = state.balances[index];
-
-        if balance.safe_add(downward_threshold)? < validator.effective_balance
-            || validator.effective_balance.safe_add(upward_threshold)? < balance
-        {
-            validator.effective_balance = std::cmp::min(
-                balance.safe_sub(balance.safe_rem(spec.effective_balance_increment)?)?,
-                spec.max_effective_balance,
-            );
-        }
+    if state.balances.len() < state.validators.len() {
+        return Err(BeaconStateError::UnknownValidator(state.balances.len() as u64).into());
     }
 
+    // Each validator's update depends only upon its own balance, so they're done in parallel.
+    state
+        .validators
+        .par_iter_mut()
+        .zip(state.balances.par_iter())
+        .try_for_each(|(validator, &balance)| -> Result<(), Error> {
+            if balance.safe_add(downward_threshold)? < validator.effective_balance
+                || validator.effective_balance.safe_add(upward_threshold)? < balance
+            {
+                validator.effective_balance = std::cmp::min(
+                    balance.safe_sub(balance.safe_rem(spec.effective_balance_increment)?)?,
+                    spec.max_effective_balance,
+                );
+            }
+            Ok(())
+        })?;
+
     // Reset slashings
     state.set_slashings(next_epoch, 0)?;
 
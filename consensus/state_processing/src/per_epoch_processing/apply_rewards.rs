@@ -1,6 +1,7 @@
 use super::super::common::get_base_reward;
 use super::validator_statuses::{TotalBalances, ValidatorStatus, ValidatorStatuses};
 use super::Error;
+use rayon::prelude::*;
 use safe_arith::SafeArith;
 
 use types::*;
@@ -76,37 +77,52 @@ fn get_attestation_deltas<T: EthSpec>(
         .safe_sub(state.finalized_checkpoint.epoch)?
         .as_u64();
 
-    let mut deltas = vec![Delta::default(); state.validators.len()];
-
     let total_balances = &validator_statuses.total_balances;
 
+    // Compute the deltas of each validator in parallel.
+    //
     // Filter out ineligible validators. All sub-functions of the spec do this except for
     // `get_inclusion_delay_deltas`. It's safe to do so here because any validator that is in the
     // unslashed indices of the matching source attestations is active, and therefore eligible.
-    for (index, validator) in validator_statuses
+    let validator_deltas = validator_statuses
         .statuses
-        .iter()
+        .par_iter()
         .enumerate()
-        .filter(|(_, validator)| is_eligible_validator(validator))
-    {
-        let base_reward = get_base_reward(state, index, total_balances.current_epoch(), spec)?;
-
-        let source_delta =
-            get_source_delta(validator, base_reward, total_balances, finality_delay, spec)?;
-        let target_delta =
-            get_target_delta(validator, base_reward, total_balances, finality_delay, spec)?;
-        let head_delta =
-            get_head_delta(validator, base_reward, total_balances, finality_delay, spec)?;
-        let (inclusion_delay_delta, proposer_delta) =
-            get_inclusion_delay_delta(validator, base_reward, spec)?;
-        let inactivity_penalty_delta =
-            get_inactivity_penalty_delta(validator, base_reward, finality_delay, spec)?;
-
-        deltas[index].combine(source_delta)?;
-        deltas[index].combine(target_delta)?;
-        deltas[index].combine(head_delta)?;
-        deltas[index].combine(inclusion_delay_delta)?;
-        deltas[index].combine(inactivity_penalty_delta)?;
+        .map(|(index, validator)| -> Result<_, Error> {
+            if !is_eligible_validator(validator) {
+                return Ok((Delta::default(), None));
+            }
+
+            let base_reward = get_base_reward(state, index, total_balances.current_epoch(), spec)?;
+
+            let source_delta =
+                get_source_delta(validator, base_reward, total_balances, finality_delay, spec)?;
+            let target_delta =
+                get_target_delta(validator, base_reward, total_balances, finality_delay, spec)?;
+            let head_delta =
+                get_head_delta(validator, base_reward, total_balances, finality_delay, spec)?;
+            let (inclusion_delay_delta, proposer_delta) =
+                get_inclusion_delay_delta(validator, base_reward, spec)?;
+            let inactivity_penalty_delta =
+                get_inactivity_penalty_delta(validator, base_reward, finality_delay, spec)?;
+
+            let mut delta = Delta::default();
+            delta.combine(source_delta)?;
+            delta.combine(target_delta)?;
+            delta.combine(head_delta)?;
+            delta.combine(inclusion_delay_delta)?;
+            delta.combine(inactivity_penalty_delta)?;
+
+            Ok((delta, proposer_delta))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // Combine the deltas in series, in validator index order, so that the result doesn't depend
+    // upon the order in which the parallel computations complete.
+    let mut deltas = vec![Delta::default(); state.validators.len()];
+
+    for (index, (delta, proposer_delta)) in validator_deltas.into_iter().enumerate() {
+        deltas[index].combine(delta)?;
 
         if let Some((proposer_index, proposer_delta)) = proposer_delta {
             if proposer_index >= deltas.len() {
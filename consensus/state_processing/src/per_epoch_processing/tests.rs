@@ -21,3 +21,29 @@ fn runs_without_error() {
 
     per_epoch_processing(&mut state, &spec).unwrap();
 }
+
+#[test]
+fn parallel_processing_is_deterministic() {
+    let spec = MinimalEthSpec::default_spec();
+
+    let mut builder: TestingBeaconStateBuilder<MinimalEthSpec> =
+        TestingBeaconStateBuilder::from_deterministic_keypairs(64, &spec);
+
+    let target_slot =
+        (MinimalEthSpec::genesis_epoch() + 4).end_slot(MinimalEthSpec::slots_per_epoch());
+    builder.teleport_to_slot(target_slot);
+
+    let (state, _keypairs) = builder.build();
+
+    let process_with_threads = |num_threads| {
+        let mut state = state.clone();
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap()
+            .install(|| per_epoch_processing(&mut state, &spec).unwrap());
+        state
+    };
+
+    assert_eq!(process_with_threads(1), process_with_threads(4));
+}
@@ -1,5 +1,5 @@
 use crate::Hash256;
-use eth2_hashing::{Context, SHA256};
+use eth2_hashing::{Context, Sha256Context};
 use std::mem;
 
 const SEED_SIZE: usize = 32;
@@ -31,9 +31,9 @@ impl Buf {
     /// Returns the new pivot. It is "raw" because it has not modulo the list size (this must be
     /// done by the caller).
     fn raw_pivot(&self) -> u64 {
-        let mut context = Context::new(&SHA256);
+        let mut context = Context::new();
         context.update(&self.0[0..PIVOT_VIEW_SIZE]);
-        let digest = context.finish();
+        let digest = context.finalize();
 
         let mut bytes = [0; mem::size_of::<u64>()];
         bytes[..].copy_from_slice(&digest.as_ref()[0..mem::size_of::<u64>()]);
@@ -47,9 +47,9 @@ impl Buf {
 
     /// Hash the entire buffer.
     fn hash(&self) -> Hash256 {
-        let mut context = Context::new(&SHA256);
+        let mut context = Context::new();
         context.update(&self.0[..]);
-        let digest = context.finish();
+        let digest = context.finalize();
         Hash256::from_slice(digest.as_ref())
     }
 }
@@ -7,7 +7,7 @@ pub use merkle_hasher::{Error, MerkleHasher};
 pub use merkleize_padded::merkleize_padded;
 pub use merkleize_standard::merkleize_standard;
 
-use eth2_hashing::{Context, SHA256};
+use eth2_hashing::{Context, Sha256Context};
 use eth2_hashing::{ZERO_HASHES, ZERO_HASHES_MAX_INDEX};
 
 pub const BYTES_PER_CHUNK: usize = 32;
@@ -39,9 +39,9 @@ pub fn merkle_root(bytes: &[u8], minimum_leaf_count: usize) -> Hash256 {
         let mut leaves = [0; HASHSIZE * 2];
         leaves[0..bytes.len()].copy_from_slice(bytes);
 
-        let mut context = Context::new(&SHA256);
+        let mut context = Context::new();
         context.update(&leaves);
-        let digest = context.finish();
+        let digest = context.finalize();
 
         Hash256::from_slice(digest.as_ref())
     } else {
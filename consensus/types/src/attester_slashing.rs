@@ -3,6 +3,7 @@ use crate::{test_utils::TestRandom, EthSpec, IndexedAttestation};
 use derivative::Derivative;
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
+use std::collections::HashSet;
 use test_random_derive::TestRandom;
 use tree_hash_derive::TreeHash;
 
@@ -18,10 +19,40 @@ pub struct AttesterSlashing<T: EthSpec> {
     pub attestation_2: IndexedAttestation<T>,
 }
 
+impl<T: EthSpec> AttesterSlashing<T> {
+    /// The indices of the validators which signed both attestations, in the order of
+    /// `attestation_1`.
+    pub fn intersecting_indices(&self) -> Vec<u64> {
+        let attestation_2_indices = self
+            .attestation_2
+            .attesting_indices
+            .iter()
+            .collect::<HashSet<_>>();
+        self.attestation_1
+            .attesting_indices
+            .iter()
+            .filter(|index| attestation_2_indices.contains(index))
+            .copied()
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{SeedableRng, XorShiftRng};
     use crate::*;
 
+    #[test]
+    pub fn test_intersecting_indices() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut slashing = AttesterSlashing::<MainnetEthSpec>::random_for_test(&mut rng);
+
+        slashing.attestation_1.attesting_indices = vec![1, 3, 5, 7].into();
+        slashing.attestation_2.attesting_indices = vec![2, 3, 4, 7].into();
+
+        assert_eq!(slashing.intersecting_indices(), vec![3, 7]);
+    }
+
     ssz_and_tree_hash_tests!(AttesterSlashing<MainnetEthSpec>);
 }
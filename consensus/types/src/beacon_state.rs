@@ -23,12 +23,14 @@ use tree_hash_derive::TreeHash;
 pub use self::committee_cache::CommitteeCache;
 pub use clone_config::CloneConfig;
 pub use eth_spec::*;
+pub use progressive_balances_cache::ProgressiveBalancesCache;
 pub use tree_hash_cache::BeaconTreeHashCache;
 
 #[macro_use]
 mod committee_cache;
 mod clone_config;
 mod exit_cache;
+mod progressive_balances_cache;
 mod pubkey_cache;
 mod tests;
 mod tree_hash_cache;
@@ -67,6 +69,7 @@ pub enum Error {
     CurrentCommitteeCacheUninitialized,
     RelativeEpochError(RelativeEpochError),
     ExitCacheUninitialized,
+    ProgressiveBalancesCacheUninitialized,
     CommitteeCacheUninitialized(Option<RelativeEpoch>),
     SszTypesError(ssz_types::Error),
     TreeHashCacheNotInitialized,
@@ -179,10 +182,10 @@ where
 
     // Registry
     #[compare_fields(as_slice)]
-    pub validators: VariableList<Validator, T::ValidatorRegistryLimit>,
+    pub validators: SharedList<Validator, T::ValidatorRegistryLimit>,
     #[compare_fields(as_slice)]
-    #[serde(with = "ssz_types::serde_utils::quoted_u64_var_list")]
-    pub balances: VariableList<u64, T::ValidatorRegistryLimit>,
+    #[serde(with = "crate::shared_list::quoted_u64_shared_list")]
+    pub balances: SharedList<u64, T::ValidatorRegistryLimit>,
 
     // Randomness
     pub randao_mixes: FixedVector<Hash256, T::EpochsPerHistoricalVector>,
@@ -226,6 +229,12 @@ where
     #[ssz(skip_deserializing)]
     #[tree_hash(skip_hashing)]
     #[test_random(default)]
+    pub progressive_balances_cache: ProgressiveBalancesCache,
+    #[serde(skip_serializing, skip_deserializing)]
+    #[ssz(skip_serializing)]
+    #[ssz(skip_deserializing)]
+    #[tree_hash(skip_hashing)]
+    #[test_random(default)]
     pub tree_hash_cache: Option<BeaconTreeHashCache<T>>,
 }
 
@@ -259,8 +268,8 @@ impl<T: EthSpec> BeaconState<T> {
             eth1_deposit_index: 0,
 
             // Validator registry
-            validators: VariableList::empty(), // Set later.
-            balances: VariableList::empty(),   // Set later.
+            validators: SharedList::default(), // Set later.
+            balances: SharedList::default(),   // Set later.
 
             // Randomness
             randao_mixes: FixedVector::from_elem(Hash256::zero()),
@@ -286,6 +295,7 @@ impl<T: EthSpec> BeaconState<T> {
             ],
             pubkey_cache: PubkeyCache::default(),
             exit_cache: ExitCache::default(),
+            progressive_balances_cache: ProgressiveBalancesCache::default(),
             tree_hash_cache: None,
         }
     }
@@ -895,6 +905,7 @@ impl<T: EthSpec> BeaconState<T> {
         self.build_all_committee_caches(spec)?;
         self.update_pubkey_cache()?;
         self.exit_cache.build(&self.validators, spec)?;
+        self.build_progressive_balances_cache(spec)?;
 
         Ok(())
     }
@@ -915,6 +926,20 @@ impl<T: EthSpec> BeaconState<T> {
         self.drop_pubkey_cache();
         self.drop_tree_hash_cache();
         self.exit_cache = ExitCache::default();
+        self.progressive_balances_cache = ProgressiveBalancesCache::default();
+    }
+
+    /// Build the progressive balances cache for the current epoch, if it needs to be built.
+    ///
+    /// Requires the previous and current epoch committee caches.
+    pub fn build_progressive_balances_cache(&mut self, spec: &ChainSpec) -> Result<(), Error> {
+        if !self
+            .progressive_balances_cache
+            .is_initialized_at(self.current_epoch())
+        {
+            self.progressive_balances_cache = ProgressiveBalancesCache::new(self, spec)?;
+        }
+        Ok(())
     }
 
     /// Returns `true` if the committee cache for `relative_epoch` is built and ready to use.
@@ -954,6 +979,23 @@ impl<T: EthSpec> BeaconState<T> {
         Ok(())
     }
 
+    /// Replace the committee cache for `relative_epoch` with one built elsewhere, e.g., from
+    /// another state with the same shuffling.
+    ///
+    /// Returns an error if `committee_cache` is not initialized at `relative_epoch`.
+    pub fn set_committee_cache(
+        &mut self,
+        relative_epoch: RelativeEpoch,
+        committee_cache: CommitteeCache,
+    ) -> Result<(), Error> {
+        if !committee_cache.is_initialized_at(relative_epoch.into_epoch(self.current_epoch())) {
+            return Err(Error::CommitteeCacheUninitialized(Some(relative_epoch)));
+        }
+
+        self.committee_caches[Self::committee_cache_index(relative_epoch)] = committee_cache;
+        Ok(())
+    }
+
     /// Advances the cache for this state into the next epoch.
     ///
     /// This should be used if the `slot` of this state is advanced beyond an epoch boundary.
@@ -1120,6 +1162,11 @@ impl<T: EthSpec> BeaconState<T> {
             } else {
                 ExitCache::default()
             },
+            progressive_balances_cache: if config.progressive_balances_cache {
+                self.progressive_balances_cache.clone()
+            } else {
+                ProgressiveBalancesCache::default()
+            },
             tree_hash_cache: if config.tree_hash_cache {
                 self.tree_hash_cache.clone()
             } else {
@@ -1196,8 +1243,8 @@ impl<T: EthSpec> arbitrary::Arbitrary for BeaconState<T> {
             eth1_data: Eth1Data::arbitrary(u)?,
             eth1_data_votes: <VariableList<Eth1Data, T::SlotsPerEth1VotingPeriod>>::arbitrary(u)?,
             eth1_deposit_index: u64::arbitrary(u)?,
-            validators: <VariableList<Validator, T::ValidatorRegistryLimit>>::arbitrary(u)?,
-            balances: <VariableList<u64, T::ValidatorRegistryLimit>>::arbitrary(u)?,
+            validators: <VariableList<Validator, T::ValidatorRegistryLimit>>::arbitrary(u)?.into(),
+            balances: <VariableList<u64, T::ValidatorRegistryLimit>>::arbitrary(u)?.into(),
             randao_mixes: <FixedVector<Hash256, T::EpochsPerHistoricalVector>>::arbitrary(u)?,
             slashings: <FixedVector<u64, T::EpochsPerSlashingsVector>>::arbitrary(u)?,
             previous_epoch_attestations: <VariableList<
@@ -1219,6 +1266,7 @@ impl<T: EthSpec> arbitrary::Arbitrary for BeaconState<T> {
             ],
             pubkey_cache: PubkeyCache::arbitrary(u)?,
             exit_cache: ExitCache::arbitrary(u)?,
+            progressive_balances_cache: ProgressiveBalancesCache::arbitrary(u)?,
             tree_hash_cache: None,
         })
     }
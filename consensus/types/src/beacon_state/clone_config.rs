@@ -4,6 +4,7 @@ pub struct CloneConfig {
     pub committee_caches: bool,
     pub pubkey_cache: bool,
     pub exit_cache: bool,
+    pub progressive_balances_cache: bool,
     pub tree_hash_cache: bool,
 }
 
@@ -13,6 +14,7 @@ impl CloneConfig {
             committee_caches: true,
             pubkey_cache: true,
             exit_cache: true,
+            progressive_balances_cache: true,
             tree_hash_cache: true,
         }
     }
@@ -39,5 +41,6 @@ mod test {
         assert!(!CloneConfig::none().tree_hash_cache);
         assert!(CloneConfig::committee_caches_only().committee_caches);
         assert!(!CloneConfig::committee_caches_only().exit_cache);
+        assert!(!CloneConfig::committee_caches_only().progressive_balances_cache);
     }
 }
@@ -0,0 +1,253 @@
+use super::{BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec, RelativeEpoch, Validator};
+use safe_arith::SafeArith;
+use std::mem;
+
+/// Running totals of the balances used to justify checkpoints, kept up to date as blocks are
+/// applied to the state.
+///
+/// Each validator's effective balance is counted the first time it is seen in an attestation to
+/// the correct target during the previous or current epoch, and removed again if it is slashed.
+/// This means the justification weights for a state are available at any slot, without walking
+/// every pending attestation and its committee as `ValidatorStatuses` does.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProgressiveBalancesCache {
+    /// The current epoch of the state when the cache was last built or rotated.
+    initialized_epoch: Option<Epoch>,
+    /// The effective balance increment from the spec.
+    effective_balance_increment: u64,
+    /// The total effective balance of all active validators during the _current_ epoch.
+    current_epoch_total_active_balance: u64,
+    /// The total effective balance of all unslashed validators who attested to the correct target
+    /// during the _previous_ epoch.
+    previous_epoch_target_attesting_balance: u64,
+    /// The total effective balance of all unslashed validators who attested to the correct target
+    /// during the _current_ epoch.
+    current_epoch_target_attesting_balance: u64,
+    /// Flags for the validators counted in `previous_epoch_target_attesting_balance`.
+    previous_epoch_target_attesters: Vec<bool>,
+    /// Flags for the validators counted in `current_epoch_target_attesting_balance`.
+    current_epoch_target_attesters: Vec<bool>,
+}
+
+// Generate a safe accessor for a balance in the cache, as per spec `get_total_balance`.
+macro_rules! balance_accessor {
+    ($field_name:ident) => {
+        pub fn $field_name(&self) -> Result<u64, BeaconStateError> {
+            self.check_initialized()?;
+            Ok(std::cmp::max(
+                self.effective_balance_increment,
+                self.$field_name,
+            ))
+        }
+    };
+}
+
+impl ProgressiveBalancesCache {
+    /// Build a new cache from the pending attestations of `state`.
+    ///
+    /// The previous and current epoch committee caches of `state` must be built.
+    pub fn new<T: EthSpec>(
+        state: &BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> Result<Self, BeaconStateError> {
+        let current_epoch = state.current_epoch();
+
+        let mut cache = Self {
+            initialized_epoch: Some(current_epoch),
+            effective_balance_increment: spec.effective_balance_increment,
+            current_epoch_total_active_balance: total_active_balance(
+                &state.validators,
+                current_epoch,
+            )?,
+            previous_epoch_target_attesting_balance: 0,
+            current_epoch_target_attesting_balance: 0,
+            previous_epoch_target_attesters: vec![false; state.validators.len()],
+            current_epoch_target_attesters: vec![false; state.validators.len()],
+        };
+
+        for attestation in state
+            .previous_epoch_attestations
+            .iter()
+            .chain(state.current_epoch_attestations.iter())
+        {
+            let target = attestation.data.target;
+            if target.root != *state.get_block_root_at_epoch(target.epoch)? {
+                continue;
+            }
+
+            let committee =
+                state.get_beacon_committee(attestation.data.slot, attestation.data.index)?;
+            if attestation.aggregation_bits.len() != committee.committee.len() {
+                return Err(BeaconStateError::InvalidBitfield);
+            }
+
+            for (i, &validator_index) in committee.committee.iter().enumerate() {
+                if !attestation.aggregation_bits.get(i)? {
+                    continue;
+                }
+
+                let validator = state
+                    .validators
+                    .get(validator_index)
+                    .ok_or(BeaconStateError::UnknownValidator(validator_index as u64))?;
+                if !validator.slashed {
+                    cache.on_target_attestation(
+                        target.epoch,
+                        validator_index,
+                        validator.effective_balance,
+                    )?;
+                }
+            }
+        }
+
+        Ok(cache)
+    }
+
+    /// Returns `true` if the cache has been built for a state whose current epoch is `epoch`.
+    pub fn is_initialized_at(&self, epoch: Epoch) -> bool {
+        self.initialized_epoch == Some(epoch)
+    }
+
+    /// Check that the cache is initialized and return an error if it is not.
+    pub fn check_initialized(&self) -> Result<(), BeaconStateError> {
+        if self.initialized_epoch.is_some() {
+            Ok(())
+        } else {
+            Err(BeaconStateError::ProgressiveBalancesCacheUninitialized)
+        }
+    }
+
+    /// Record an attestation by `validator_index` to the correct target of `target_epoch`.
+    ///
+    /// The validator's effective balance is only counted once per epoch, no matter how many times
+    /// its attestation is included.
+    pub fn on_target_attestation(
+        &mut self,
+        target_epoch: Epoch,
+        validator_index: usize,
+        effective_balance: u64,
+    ) -> Result<(), BeaconStateError> {
+        let current_epoch = self
+            .initialized_epoch
+            .ok_or(BeaconStateError::ProgressiveBalancesCacheUninitialized)?;
+
+        let (attesters, balance) = match RelativeEpoch::from_epoch(current_epoch, target_epoch)? {
+            RelativeEpoch::Previous => (
+                &mut self.previous_epoch_target_attesters,
+                &mut self.previous_epoch_target_attesting_balance,
+            ),
+            RelativeEpoch::Current => (
+                &mut self.current_epoch_target_attesters,
+                &mut self.current_epoch_target_attesting_balance,
+            ),
+            RelativeEpoch::Next => return Err(BeaconStateError::EpochOutOfBounds),
+        };
+
+        // Validators may have been added by deposits since the cache was built.
+        if validator_index >= attesters.len() {
+            attesters.resize(validator_index.safe_add(1)?, false);
+        }
+
+        if !attesters[validator_index] {
+            attesters[validator_index] = true;
+            balance.safe_add_assign(effective_balance)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a newly slashed validator from the target-attesting balances.
+    pub fn on_slashing(
+        &mut self,
+        validator_index: usize,
+        effective_balance: u64,
+    ) -> Result<(), BeaconStateError> {
+        self.check_initialized()?;
+
+        uncount_attester(
+            &mut self.previous_epoch_target_attesters,
+            &mut self.previous_epoch_target_attesting_balance,
+            validator_index,
+            effective_balance,
+        )?;
+        uncount_attester(
+            &mut self.current_epoch_target_attesters,
+            &mut self.current_epoch_target_attesting_balance,
+            validator_index,
+            effective_balance,
+        )
+    }
+
+    /// Rotate the current epoch into the previous epoch at the end of epoch processing.
+    ///
+    /// Must be called after the effective balances of `validators` have been updated for the next
+    /// epoch. Does nothing if the cache is not initialized.
+    pub fn on_epoch_transition(
+        &mut self,
+        validators: &[Validator],
+    ) -> Result<(), BeaconStateError> {
+        let next_epoch = match self.initialized_epoch {
+            Some(epoch) => epoch.safe_add(1)?,
+            None => return Ok(()),
+        };
+
+        self.previous_epoch_target_attesters = mem::replace(
+            &mut self.current_epoch_target_attesters,
+            vec![false; validators.len()],
+        );
+
+        // Effective balances may have changed, so the previous epoch's total is re-summed over
+        // only those validators which attested.
+        self.previous_epoch_target_attesting_balance = 0;
+        for (validator, _) in validators
+            .iter()
+            .zip(self.previous_epoch_target_attesters.iter())
+            .filter(|(_, attested)| **attested)
+        {
+            self.previous_epoch_target_attesting_balance
+                .safe_add_assign(validator.effective_balance)?;
+        }
+        self.current_epoch_target_attesting_balance = 0;
+        self.current_epoch_total_active_balance = total_active_balance(validators, next_epoch)?;
+        self.initialized_epoch = Some(next_epoch);
+
+        Ok(())
+    }
+
+    balance_accessor!(current_epoch_total_active_balance);
+    balance_accessor!(previous_epoch_target_attesting_balance);
+    balance_accessor!(current_epoch_target_attesting_balance);
+}
+
+/// Remove `validator_index` from `attesters`, subtracting its balance if it was counted.
+fn uncount_attester(
+    attesters: &mut [bool],
+    balance: &mut u64,
+    validator_index: usize,
+    effective_balance: u64,
+) -> Result<(), BeaconStateError> {
+    if let Some(attested) = attesters.get_mut(validator_index) {
+        if *attested {
+            *attested = false;
+            balance.safe_sub_assign(effective_balance)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the sum of the effective balances of the validators active at `epoch`.
+fn total_active_balance(validators: &[Validator], epoch: Epoch) -> Result<u64, BeaconStateError> {
+    validators
+        .iter()
+        .filter(|validator| validator.is_active_at(epoch))
+        .try_fold(0_u64, |acc, validator| {
+            Ok(acc.safe_add(validator.effective_balance)?)
+        })
+}
+
+#[cfg(feature = "arbitrary-fuzz")]
+impl arbitrary::Arbitrary for ProgressiveBalancesCache {
+    fn arbitrary(_u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::default())
+    }
+}
@@ -164,6 +164,17 @@ fn test_clone_config<E: EthSpec>(base_state: &BeaconState<E>, clone_config: Clon
             .check_initialized()
             .expect_err("exit cache doesn't exist");
     }
+    if clone_config.progressive_balances_cache {
+        state
+            .progressive_balances_cache
+            .check_initialized()
+            .expect("progressive balances cache exists");
+    } else {
+        state
+            .progressive_balances_cache
+            .check_initialized()
+            .expect_err("progressive balances cache doesn't exist");
+    }
     if clone_config.tree_hash_cache {
         assert!(state.tree_hash_cache.is_some());
     } else {
@@ -184,12 +195,13 @@ fn clone_config() {
         .update_tree_hash_cache()
         .expect("should update tree hash cache");
 
-    let num_caches = 4;
+    let num_caches = 5;
     let all_configs = (0..2u8.pow(num_caches)).map(|i| CloneConfig {
         committee_caches: (i & 1) != 0,
         pubkey_cache: ((i >> 1) & 1) != 0,
         exit_cache: ((i >> 2) & 1) != 0,
         tree_hash_cache: ((i >> 3) & 1) != 0,
+        progressive_balances_cache: ((i >> 4) & 1) != 0,
     });
 
     for config in all_configs {
@@ -197,6 +209,25 @@ fn clone_config() {
     }
 }
 
+#[test]
+fn clone_shares_registry() {
+    let spec = MinimalEthSpec::default_spec();
+    let builder: TestingBeaconStateBuilder<MinimalEthSpec> =
+        TestingBeaconStateBuilder::from_deterministic_keypairs(16, &spec);
+    let (state, _keypairs) = builder.build();
+
+    let mut clone = state.clone_with(CloneConfig::none());
+    assert!(clone.validators.ptr_eq(&state.validators));
+    assert!(clone.balances.ptr_eq(&state.balances));
+
+    clone.balances[0] = 0;
+    clone.validators[1].slashed = true;
+    assert!(!clone.validators.ptr_eq(&state.validators));
+    assert!(!clone.balances.ptr_eq(&state.balances));
+    assert_ne!(state.balances[0], 0);
+    assert!(!state.validators[1].slashed);
+}
+
 #[test]
 fn tree_hash_cache() {
     use crate::test_utils::{SeedableRng, TestRandom, XorShiftRng};
@@ -123,6 +123,10 @@ pub struct ChainSpec {
     pub attestation_subnet_count: u64,
     pub random_subnets_per_validator: u64,
     pub epochs_per_random_subnet_subscription: u64,
+    pub subnets_per_node: u8,
+    pub epochs_per_subnet_subscription: u64,
+    pub attestation_subnet_extra_bits: u8,
+    pub attestation_subnet_prefix_bits: u8,
 }
 
 impl ChainSpec {
@@ -339,6 +343,10 @@ impl ChainSpec {
             maximum_gossip_clock_disparity_millis: 500,
             target_aggregators_per_committee: 16,
             epochs_per_random_subnet_subscription: 256,
+            subnets_per_node: 2,
+            epochs_per_subnet_subscription: 256,
+            attestation_subnet_extra_bits: 0,
+            attestation_subnet_prefix_bits: 6,
         }
     }
 
@@ -793,6 +801,10 @@ impl YamlConfig {
             attestation_propagation_slot_range: chain_spec.attestation_propagation_slot_range,
             maximum_gossip_clock_disparity_millis: chain_spec.maximum_gossip_clock_disparity_millis,
             attestation_subnet_count: chain_spec.attestation_subnet_count,
+            subnets_per_node: chain_spec.subnets_per_node,
+            epochs_per_subnet_subscription: chain_spec.epochs_per_subnet_subscription,
+            attestation_subnet_extra_bits: chain_spec.attestation_subnet_extra_bits,
+            attestation_subnet_prefix_bits: chain_spec.attestation_subnet_prefix_bits,
             /*
              * Constants, not configurable.
              */
@@ -0,0 +1,150 @@
+use crate::test_utils::TestRandom;
+use crate::{BeaconState, EthSpec, Hash256, DEPOSIT_TREE_DEPTH};
+use eth2_hashing::{hash32_concat, ZERO_HASHES};
+use int_to_bytes::int_to_bytes32;
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+
+/// A compact representation of the deposit contract tree at a given deposit count, from which the
+/// tree can be restored without the logs of the deposits it contains.
+///
+/// See EIP-4881.
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode, TestRandom)]
+pub struct DepositTreeSnapshot {
+    /// The roots of the largest full subtrees covering the deposits, from left to right.
+    pub finalized: Vec<Hash256>,
+    pub deposit_root: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub deposit_count: u64,
+    /// The hash of the eth1 block at which the deposit contract held `deposit_count` deposits.
+    pub execution_block_hash: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub execution_block_height: u64,
+}
+
+impl DepositTreeSnapshot {
+    /// Compute the deposit root from `finalized` and `deposit_count`.
+    ///
+    /// Returns `None` if there isn't exactly one finalized hash per full subtree.
+    pub fn calculate_root(&self) -> Option<Hash256> {
+        if self.finalized.len() != self.deposit_count.count_ones() as usize {
+            return None;
+        }
+
+        let mut size = self.deposit_count;
+        let mut index = self.finalized.len();
+        let mut root = Hash256::from_slice(&ZERO_HASHES[0]);
+        for zero_hash in ZERO_HASHES.iter().take(DEPOSIT_TREE_DEPTH) {
+            root = if size & 1 == 1 {
+                index -= 1;
+                Hash256::from_slice(&hash32_concat(
+                    self.finalized[index].as_bytes(),
+                    root.as_bytes(),
+                ))
+            } else {
+                Hash256::from_slice(&hash32_concat(root.as_bytes(), zero_hash))
+            };
+            size >>= 1;
+        }
+        if size != 0 {
+            return None;
+        }
+
+        Some(Hash256::from_slice(&hash32_concat(
+            root.as_bytes(),
+            &int_to_bytes32(self.deposit_count),
+        )))
+    }
+
+    /// Returns `true` if `deposit_root` matches the root computed from `finalized`.
+    pub fn is_valid(&self) -> bool {
+        self.calculate_root() == Some(self.deposit_root)
+    }
+
+    /// Returns `true` if the snapshot is valid, matches the `eth1_data` of `state` and contains no
+    /// deposits which are pending in `state`.
+    ///
+    /// Proofs can't be generated for the deposits covered by a snapshot, so a snapshot which
+    /// includes deposits that are yet to be processed would prevent blocks from being produced.
+    pub fn matches_state<E: EthSpec>(&self, state: &BeaconState<E>) -> bool {
+        self.is_valid()
+            && self.deposit_root == state.eth1_data.deposit_root
+            && self.deposit_count == state.eth1_data.deposit_count
+            && self.execution_block_hash == state.eth1_data.block_hash
+            && state.eth1_deposit_index >= self.deposit_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Eth1Data, MinimalEthSpec};
+    use merkle_proof::MerkleTree;
+
+    ssz_tests!(DepositTreeSnapshot);
+
+    #[test]
+    fn calculate_root() {
+        let leaves: Vec<_> = (0..21).map(Hash256::from_low_u64_be).collect();
+
+        for deposit_count in 0..=leaves.len() {
+            let tree = MerkleTree::create(&leaves[..deposit_count], DEPOSIT_TREE_DEPTH);
+            let finalized = tree
+                .get_finalized_hashes(deposit_count, DEPOSIT_TREE_DEPTH)
+                .unwrap();
+            let deposit_root = Hash256::from_slice(&hash32_concat(
+                tree.hash().as_bytes(),
+                &int_to_bytes32(deposit_count as u64),
+            ));
+
+            let mut snapshot = DepositTreeSnapshot {
+                finalized,
+                deposit_root,
+                deposit_count: deposit_count as u64,
+                execution_block_hash: Hash256::zero(),
+                execution_block_height: 0,
+            };
+            assert!(snapshot.is_valid(), "deposit count {}", deposit_count);
+
+            snapshot.deposit_count += 1;
+            assert!(!snapshot.is_valid(), "deposit count {}", deposit_count);
+        }
+    }
+
+    #[test]
+    fn matches_state() {
+        let leaves: Vec<_> = (0..5).map(Hash256::from_low_u64_be).collect();
+        let tree = MerkleTree::create(&leaves, DEPOSIT_TREE_DEPTH);
+        let snapshot = DepositTreeSnapshot {
+            finalized: tree
+                .get_finalized_hashes(leaves.len(), DEPOSIT_TREE_DEPTH)
+                .unwrap(),
+            deposit_root: Hash256::from_slice(&hash32_concat(
+                tree.hash().as_bytes(),
+                &int_to_bytes32(leaves.len() as u64),
+            )),
+            deposit_count: leaves.len() as u64,
+            execution_block_hash: Hash256::repeat_byte(1),
+            execution_block_height: 42,
+        };
+        let eth1_data = Eth1Data {
+            deposit_root: snapshot.deposit_root,
+            deposit_count: snapshot.deposit_count,
+            block_hash: snapshot.execution_block_hash,
+        };
+        let mut state =
+            BeaconState::<MinimalEthSpec>::new(0, eth1_data, &MinimalEthSpec::default_spec());
+
+        // The last two deposits are pending.
+        state.eth1_deposit_index = snapshot.deposit_count - 2;
+        assert!(!snapshot.matches_state(&state));
+
+        state.eth1_deposit_index = snapshot.deposit_count;
+        assert!(snapshot.matches_state(&state));
+
+        state.eth1_data.block_hash = Hash256::repeat_byte(2);
+        assert!(!snapshot.matches_state(&state));
+    }
+}
@@ -0,0 +1,230 @@
+//! A `VariableList` which is shared between clones until one of them mutates it.
+//!
+//! The validator registry and balances are by far the largest fields of a `BeaconState`, yet they
+//! only change at epoch boundaries and on the occasional deposit or slashing. Holding them in a
+//! `SharedList` means that the many states kept around for forks and caches share one copy of
+//! each list, rather than each cloning the whole registry.
+use crate::test_utils::TestRandom;
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ssz::{Decode, DecodeError, Encode};
+use ssz_types::{typenum::Unsigned, VariableList};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tree_hash::{Hash256, TreeHash, TreeHashType};
+
+/// A copy-on-write wrapper around a `VariableList`.
+///
+/// Cloning is cheap: the clone shares the underlying list. Any mutable access (via `DerefMut`)
+/// copies the list first if it is shared, so mutations are never visible to other clones.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SharedList<T, N>(Arc<VariableList<T, N>>);
+
+impl<T, N: Unsigned> SharedList<T, N> {
+    /// Returns `true` if `self` and `other` point to the same underlying list.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: Clone, N: Unsigned> SharedList<T, N> {
+    /// Returns the inner list, cloning it only if it is shared.
+    pub fn into_inner(self) -> VariableList<T, N> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|list| (*list).clone())
+    }
+}
+
+impl<T, N: Unsigned> Default for SharedList<T, N> {
+    fn default() -> Self {
+        Self(Arc::new(VariableList::empty()))
+    }
+}
+
+impl<T, N: Unsigned> Deref for SharedList<T, N> {
+    type Target = VariableList<T, N>;
+
+    fn deref(&self) -> &VariableList<T, N> {
+        &self.0
+    }
+}
+
+impl<T: Clone, N: Unsigned> DerefMut for SharedList<T, N> {
+    fn deref_mut(&mut self) -> &mut VariableList<T, N> {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl<T, N: Unsigned> From<VariableList<T, N>> for SharedList<T, N> {
+    fn from(list: VariableList<T, N>) -> Self {
+        Self(Arc::new(list))
+    }
+}
+
+impl<T, N: Unsigned> From<Vec<T>> for SharedList<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        VariableList::<T, N>::from(vec).into()
+    }
+}
+
+impl<T: Clone, N: Unsigned> From<SharedList<T, N>> for Vec<T> {
+    fn from(list: SharedList<T, N>) -> Vec<T> {
+        list.into_inner().into()
+    }
+}
+
+impl<'a, T, N: Unsigned> IntoIterator for &'a SharedList<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, N: Unsigned> TreeHash for SharedList<T, N>
+where
+    T: TreeHash,
+{
+    fn tree_hash_type() -> TreeHashType {
+        VariableList::<T, N>::tree_hash_type()
+    }
+
+    fn tree_hash_packed_encoding(&self) -> Vec<u8> {
+        self.0.tree_hash_packed_encoding()
+    }
+
+    fn tree_hash_packing_factor() -> usize {
+        VariableList::<T, N>::tree_hash_packing_factor()
+    }
+
+    fn tree_hash_root(&self) -> Hash256 {
+        self.0.tree_hash_root()
+    }
+}
+
+impl<T, N: Unsigned> Encode for SharedList<T, N>
+where
+    T: Encode,
+{
+    fn is_ssz_fixed_len() -> bool {
+        <VariableList<T, N> as Encode>::is_ssz_fixed_len()
+    }
+
+    fn ssz_fixed_len() -> usize {
+        <VariableList<T, N> as Encode>::ssz_fixed_len()
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        self.0.ssz_bytes_len()
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        self.0.ssz_append(buf)
+    }
+}
+
+impl<T, N: Unsigned> Decode for SharedList<T, N>
+where
+    T: Decode,
+{
+    fn is_ssz_fixed_len() -> bool {
+        <VariableList<T, N> as Decode>::is_ssz_fixed_len()
+    }
+
+    fn ssz_fixed_len() -> usize {
+        <VariableList<T, N> as Decode>::ssz_fixed_len()
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        VariableList::<T, N>::from_ssz_bytes(bytes).map(Self::from)
+    }
+}
+
+impl<T, N: Unsigned> Serialize for SharedList<T, N>
+where
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T, N: Unsigned> Deserialize<'de> for SharedList<T, N>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        VariableList::<T, N>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl<T: TestRandom, N: Unsigned> TestRandom for SharedList<T, N> {
+    fn random_for_test(rng: &mut impl RngCore) -> Self {
+        VariableList::<T, N>::random_for_test(rng).into()
+    }
+}
+
+/// Formats `SharedList<u64, N>` using quotes, like `ssz_types::serde_utils::quoted_u64_var_list`.
+pub mod quoted_u64_shared_list {
+    use super::SharedList;
+    use serde::Deserializer;
+    use ssz_types::{serde_utils::quoted_u64_var_list, typenum::Unsigned, VariableList};
+
+    pub use quoted_u64_var_list::serialize;
+
+    pub fn deserialize<'de, D, N>(deserializer: D) -> Result<SharedList<u64, N>, D::Error>
+    where
+        D: Deserializer<'de>,
+        N: Unsigned,
+    {
+        let list: VariableList<u64, N> = quoted_u64_var_list::deserialize(deserializer)?;
+        Ok(list.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssz_types::typenum::U8;
+
+    type List = SharedList<u64, U8>;
+
+    #[test]
+    fn clones_share_until_mutated() {
+        let a = List::from(vec![1, 2, 3]);
+        let mut b = a.clone();
+        assert!(a.ptr_eq(&b));
+
+        b[0] = 42;
+        assert!(!a.ptr_eq(&b));
+        assert_eq!(&a[..], &[1, 2, 3]);
+        assert_eq!(&b[..], &[42, 2, 3]);
+
+        let c = b.clone();
+        b.push(4).unwrap();
+        assert_eq!(&c[..], &[42, 2, 3]);
+        assert_eq!(&b[..], &[42, 2, 3, 4]);
+    }
+
+    #[test]
+    fn unshared_mutation_does_not_copy() {
+        let mut a = List::from(vec![1, 2, 3]);
+        let before = &a[0] as *const u64;
+        a[1] = 0;
+        assert_eq!(&a[0] as *const u64, before);
+    }
+
+    #[test]
+    fn encoding_matches_variable_list() {
+        let list: VariableList<u64, U8> = vec![5, 6, 7].into();
+        let shared = List::from(list.clone());
+
+        assert_eq!(shared.as_ssz_bytes(), list.as_ssz_bytes());
+        assert_eq!(shared.tree_hash_root(), list.tree_hash_root());
+        assert_eq!(List::from_ssz_bytes(&list.as_ssz_bytes()).unwrap(), shared);
+        assert_eq!(
+            serde_json::to_string(&shared).unwrap(),
+            serde_json::to_string(&list).unwrap()
+        );
+    }
+}
@@ -1,10 +1,17 @@
 //! Identifies each shard by an integer identifier.
-use crate::{AttestationData, ChainSpec, CommitteeIndex, EthSpec, Slot};
+use crate::{AttestationData, ChainSpec, CommitteeIndex, Epoch, EthSpec, Slot, Uint256};
+use eth2_hashing::hash;
+use int_to_bytes::int_to_bytes8;
 use safe_arith::{ArithError, SafeArith};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
+use swap_or_not_shuffle::compute_shuffled_index;
 
 const MAX_SUBNET_ID: usize = 64;
+/// The number of bits in a discv5 `NodeId`.
+const NODE_ID_BITS: u64 = 256;
+/// `compute_shuffled_index` only supports lists of up to `2**24` elements.
+const MAX_SUBNET_PREFIX_BITS: u64 = 24;
 
 lazy_static! {
     static ref SUBNET_ID_TO_STRING: Vec<String> = {
@@ -71,6 +78,58 @@ impl SubnetId {
             .safe_rem(spec.attestation_subnet_count)?
             .into())
     }
+
+    /// Computes the set of long-lived subnets a node with `node_id` should be subscribed to
+    /// during `epoch`.
+    ///
+    /// Returns the subnets along with the first epoch at which the set of subnets will change.
+    /// Nodes are expected to re-compute their subscriptions at that epoch.
+    pub fn compute_subnets_for_epoch<T: EthSpec>(
+        node_id: Uint256,
+        epoch: Epoch,
+        spec: &ChainSpec,
+    ) -> Result<(impl Iterator<Item = SubnetId>, Epoch), &'static str> {
+        let prefix_bits = spec.attestation_subnet_prefix_bits as u64;
+        if prefix_bits == 0 || prefix_bits > MAX_SUBNET_PREFIX_BITS {
+            return Err("Invalid attestation subnet prefix bits");
+        }
+        let epochs_per_subscription = spec.epochs_per_subnet_subscription;
+        if epochs_per_subscription == 0 {
+            return Err("Invalid epochs per subnet subscription");
+        }
+
+        let node_id_prefix = (node_id >> (NODE_ID_BITS - prefix_bits) as usize).as_usize();
+        let node_offset = (node_id % Uint256::from(epochs_per_subscription)).as_u64();
+
+        let subscription_period = epoch
+            .as_u64()
+            .safe_add(node_offset)
+            .map_err(|_| "Epoch overflow")?
+            .safe_div(epochs_per_subscription)
+            .map_err(|_| "Division by zero")?;
+        let permutation_seed = hash(&int_to_bytes8(subscription_period));
+
+        let permutated_prefix = compute_shuffled_index(
+            node_id_prefix,
+            1 << prefix_bits,
+            &permutation_seed,
+            spec.shuffle_round_count,
+        )
+        .ok_or("Unable to shuffle node id prefix")? as u64;
+
+        // The subscriptions change once `epoch + node_offset` reaches the next multiple of
+        // `epochs_per_subscription`.
+        let valid_until_epoch = subscription_period
+            .saturating_add(1)
+            .saturating_mul(epochs_per_subscription)
+            .saturating_sub(node_offset);
+
+        let subnet_count = spec.attestation_subnet_count;
+        let subnets = (0..spec.subnets_per_node as u64)
+            .map(move |index| SubnetId::new((permutated_prefix + index) % subnet_count));
+
+        Ok((subnets, Epoch::new(valid_until_epoch)))
+    }
 }
 
 impl Deref for SubnetId {
@@ -110,3 +169,54 @@ impl AsRef<str> for SubnetId {
         subnet_id_to_string(self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainnetEthSpec;
+
+    fn compute_subnets(node_id: Uint256, epoch: u64, spec: &ChainSpec) -> (Vec<SubnetId>, Epoch) {
+        let (subnets, valid_until) =
+            SubnetId::compute_subnets_for_epoch::<MainnetEthSpec>(node_id, Epoch::new(epoch), spec)
+                .expect("should compute subnets");
+        (subnets.collect(), valid_until)
+    }
+
+    #[test]
+    fn compute_subnets_for_epoch_is_stable_until_next_period() {
+        let spec = ChainSpec::mainnet();
+
+        for seed in 0..32u8 {
+            let node_id = Uint256::from_big_endian(&[seed.wrapping_mul(7); 32]);
+            let epoch = 1_000 + seed as u64;
+
+            let (subnets, valid_until) = compute_subnets(node_id, epoch, &spec);
+
+            assert_eq!(subnets.len(), spec.subnets_per_node as usize);
+            assert!(subnets
+                .iter()
+                .all(|subnet| **subnet < spec.attestation_subnet_count));
+            assert!(valid_until.as_u64() > epoch);
+            assert!(valid_until.as_u64() <= epoch + spec.epochs_per_subnet_subscription);
+
+            // The subnets must not change before `valid_until`.
+            let (subnets_before_change, _) =
+                compute_subnets(node_id, valid_until.as_u64() - 1, &spec);
+            assert_eq!(subnets, subnets_before_change);
+        }
+    }
+
+    #[test]
+    fn compute_subnets_for_epoch_invalid_spec() {
+        let spec = ChainSpec {
+            epochs_per_subnet_subscription: 0,
+            ..ChainSpec::mainnet()
+        };
+        assert!(SubnetId::compute_subnets_for_epoch::<MainnetEthSpec>(
+            Uint256::zero(),
+            Epoch::new(0),
+            &spec
+        )
+        .is_err());
+    }
+}
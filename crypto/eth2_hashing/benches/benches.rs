@@ -0,0 +1,28 @@
+use criterion::Criterion;
+use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use eth2_hashing::{DynamicImpl, RingImpl, Sha256, Sha2CrateImpl};
+
+const INPUT_SIZES: &[usize] = &[32, 64, 1024, 4096];
+
+fn hashing(c: &mut Criterion) {
+    for &size in INPUT_SIZES {
+        c.bench(
+            &format!("sha256 {} bytes", size),
+            Benchmark::new("sha2", move |b| {
+                let input = vec![42; size];
+                b.iter(|| black_box(Sha2CrateImpl.hash_fixed(&input)))
+            })
+            .with_function("ring", move |b| {
+                let input = vec![42; size];
+                b.iter(|| black_box(RingImpl.hash_fixed(&input)))
+            })
+            .with_function("dynamic", move |b| {
+                let input = vec![42; size];
+                b.iter(|| black_box(DynamicImpl::best().hash_fixed(&input)))
+            }),
+        );
+    }
+}
+
+criterion_group!(benches, hashing);
+criterion_main!(benches);
@@ -1,29 +1,39 @@
-//! Provides a simple hash function utilizing `ring::digest::SHA256`.
+//! Optimized SHA256 for use in Ethereum 2.0.
 //!
-//! The purpose of this crate is to provide an abstraction to whatever hash function Ethereum
-//! 2.0 is using. The hash function has been subject to change during the specification process, so
-//! defining it once in this crate makes it easy to replace.
-
-#[cfg(not(target_arch = "wasm32"))]
-pub use ring::digest::{digest, Context, Digest, SHA256};
+//! The initial purpose of this crate was to provide an abstraction over the hash function used in
+//! Ethereum 2.0. The hash function changed during the specification process, so defining it once in
+//! this crate made it easy to replace.
+//!
+//! Now this crate serves primarily as a wrapper over two SHA256 crates: `sha2` and `ring`, which it
+//! switches between at runtime based upon the features of the executing CPU:
+//!
+//! - `sha2` is used on x86_64 CPUs with the SHA extensions (SHA-NI) and on aarch64 CPUs with the
+//!   ARMv8 cryptography extensions, which it uses via intrinsics and assembly respectively.
+//! - `ring` is used everywhere else, where its assembly (with AVX2/AVX/SSSE3 or NEON code paths
+//!   selected by `ring` at runtime) is faster than `sha2`.
+//!
+//! The `detect-cpufeatures` feature enables the runtime detection. Without it, `ring` is always
+//! used (except on `wasm32`, where only `sha2` is available).
 
-#[cfg(target_arch = "wasm32")]
-use sha2::{Digest, Sha256};
+pub use self::DynamicContext as Context;
+use sha2::Digest;
 
 #[cfg(feature = "zero_hash_cache")]
 use lazy_static::lazy_static;
 
-/// Returns the digest of `input`.
-///
-/// Uses `ring::digest::SHA256`.
-pub fn hash(input: &[u8]) -> Vec<u8> {
-    #[cfg(not(target_arch = "wasm32"))]
-    let h = digest(&SHA256, input).as_ref().into();
+/// Length of a SHA256 hash in bytes.
+pub const HASH_LEN: usize = 32;
 
-    #[cfg(target_arch = "wasm32")]
-    let h = Sha256::digest(input).as_ref().into();
+/// Returns the digest of `input` using the best available implementation.
+pub fn hash(input: &[u8]) -> Vec<u8> {
+    DynamicImpl::best().hash(input)
+}
 
-    h
+/// Hash function returning a fixed-size array (to save on allocations).
+///
+/// Uses the best available implementation based on CPU features.
+pub fn hash_fixed(input: &[u8]) -> [u8; HASH_LEN] {
+    DynamicImpl::best().hash_fixed(input)
 }
 
 /// Compute the hash of two slices concatenated.
@@ -31,31 +41,215 @@ pub fn hash(input: &[u8]) -> Vec<u8> {
 /// # Panics
 ///
 /// Will panic if either `h1` or `h2` are not 32 bytes in length.
-#[cfg(not(target_arch = "wasm32"))]
 pub fn hash32_concat(h1: &[u8], h2: &[u8]) -> [u8; 32] {
-    let mut context = Context::new(&SHA256);
+    let mut context = Context::new();
     context.update(h1);
     context.update(h2);
+    context.finalize()
+}
+
+/// Context trait for abstracting over implementation contexts.
+pub trait Sha256Context {
+    fn new() -> Self;
+
+    fn update(&mut self, bytes: &[u8]);
 
-    let mut output = [0; 32];
-    output[..].copy_from_slice(context.finish().as_ref());
-    output
+    fn finalize(self) -> [u8; HASH_LEN];
 }
 
-/// Compute the hash of two slices concatenated.
+/// Top-level trait implemented by both `sha2` and `ring` implementations.
+pub trait Sha256 {
+    type Context: Sha256Context;
+
+    fn hash(&self, input: &[u8]) -> Vec<u8>;
+
+    fn hash_fixed(&self, input: &[u8]) -> [u8; HASH_LEN];
+}
+
+/// Implementation of SHA256 using the `sha2` crate (fastest on CPUs with SHA extensions).
+pub struct Sha2CrateImpl;
+
+impl Sha256Context for sha2::Sha256 {
+    fn new() -> Self {
+        sha2::Digest::new()
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        sha2::Digest::update(self, bytes)
+    }
+
+    fn finalize(self) -> [u8; HASH_LEN] {
+        sha2::Digest::finalize(self).into()
+    }
+}
+
+impl Sha256 for Sha2CrateImpl {
+    type Context = sha2::Sha256;
+
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        Self::Context::digest(input).into_iter().collect()
+    }
+
+    fn hash_fixed(&self, input: &[u8]) -> [u8; HASH_LEN] {
+        Self::Context::digest(input).into()
+    }
+}
+
+/// Implementation of SHA256 using the `ring` crate (fastest on CPUs without SHA extensions).
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RingImpl;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Sha256Context for ring::digest::Context {
+    fn new() -> Self {
+        Self::new(&ring::digest::SHA256)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finalize(self) -> [u8; HASH_LEN] {
+        let mut output = [0; HASH_LEN];
+        output.copy_from_slice(self.finish().as_ref());
+        output
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Sha256 for RingImpl {
+    type Context = ring::digest::Context;
+
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        ring::digest::digest(&ring::digest::SHA256, input)
+            .as_ref()
+            .into()
+    }
+
+    fn hash_fixed(&self, input: &[u8]) -> [u8; HASH_LEN] {
+        let mut ctxt = Self::Context::new(&ring::digest::SHA256);
+        ctxt.update(input);
+        ctxt.finalize()
+    }
+}
+
+/// Default dynamic implementation that switches between available implementations.
+pub enum DynamicImpl {
+    Sha2,
+    #[cfg(not(target_arch = "wasm32"))]
+    Ring,
+}
+
+// Runtime latches for detecting the availability of SHA extensions.
+#[cfg(all(feature = "detect-cpufeatures", target_arch = "x86_64"))]
+cpufeatures::new!(x86_sha_extensions, "sha", "sse2", "ssse3", "sse4.1");
+
+#[cfg(all(feature = "detect-cpufeatures", target_arch = "aarch64"))]
+cpufeatures::new!(aarch64_sha2, "sha2");
+
+/// Returns `true` if the executing CPU has extensions which `sha2` uses to accelerate SHA256.
 ///
-/// # Panics
+/// The result of the detection is cached after the first call.
+#[cfg(all(feature = "detect-cpufeatures", target_arch = "x86_64"))]
+#[inline(always)]
+pub fn have_sha_extensions() -> bool {
+    x86_sha_extensions::get()
+}
+
+/// Returns `true` if the executing CPU has extensions which `sha2` uses to accelerate SHA256.
 ///
-/// Will panic if either `h1` or `h2` are not 32 bytes in length.
-#[cfg(target_arch = "wasm32")]
-pub fn hash32_concat(h1: &[u8], h2: &[u8]) -> [u8; 32] {
-    let mut preimage = [0; 64];
-    preimage[0..32].copy_from_slice(h1);
-    preimage[32..64].copy_from_slice(h2);
+/// The result of the detection is cached after the first call.
+#[cfg(all(feature = "detect-cpufeatures", target_arch = "aarch64"))]
+#[inline(always)]
+pub fn have_sha_extensions() -> bool {
+    aarch64_sha2::get()
+}
 
-    let mut output = [0; 32];
-    output[..].copy_from_slice(&hash(&preimage));
-    output
+/// Returns `true` if the executing CPU has extensions which `sha2` uses to accelerate SHA256.
+///
+/// Always `false` when the CPU features are not detected.
+#[cfg(not(all(
+    feature = "detect-cpufeatures",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[inline(always)]
+pub fn have_sha_extensions() -> bool {
+    false
+}
+
+impl DynamicImpl {
+    /// Choose the best available implementation based on the currently executing CPU.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline(always)]
+    pub fn best() -> Self {
+        if have_sha_extensions() {
+            Self::Sha2
+        } else {
+            Self::Ring
+        }
+    }
+
+    /// Choose the best available implementation, which is always `sha2` on `wasm32`.
+    #[cfg(target_arch = "wasm32")]
+    #[inline(always)]
+    pub fn best() -> Self {
+        Self::Sha2
+    }
+}
+
+impl Sha256 for DynamicImpl {
+    type Context = DynamicContext;
+
+    #[inline(always)]
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha2 => Sha2CrateImpl.hash(input),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Ring => RingImpl.hash(input),
+        }
+    }
+
+    #[inline(always)]
+    fn hash_fixed(&self, input: &[u8]) -> [u8; HASH_LEN] {
+        match self {
+            Self::Sha2 => Sha2CrateImpl.hash_fixed(input),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Ring => RingImpl.hash_fixed(input),
+        }
+    }
+}
+
+/// Context encapsulating all implementation contexts.
+pub enum DynamicContext {
+    Sha2(sha2::Sha256),
+    #[cfg(not(target_arch = "wasm32"))]
+    Ring(ring::digest::Context),
+}
+
+impl Sha256Context for DynamicContext {
+    fn new() -> Self {
+        match DynamicImpl::best() {
+            DynamicImpl::Sha2 => Self::Sha2(Sha256Context::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            DynamicImpl::Ring => Self::Ring(Sha256Context::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha2(ctxt) => Sha256Context::update(ctxt, bytes),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Ring(ctxt) => Sha256Context::update(ctxt, bytes),
+        }
+    }
+
+    fn finalize(self) -> [u8; HASH_LEN] {
+        match self {
+            Self::Sha2(ctxt) => Sha256Context::finalize(ctxt),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Ring(ctxt) => Sha256Context::finalize(ctxt),
+        }
+    }
 }
 
 /// The max index that can be used with `ZERO_HASHES`.
@@ -95,6 +289,26 @@ mod tests {
         assert_eq!(expected, output);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn implementations_agree() {
+        for len in &[0, 1, 32, 55, 56, 64, 65, 1024] {
+            let input = (0..*len).map(|i| i as u8).collect::<Vec<u8>>();
+
+            let ring_hash = RingImpl.hash_fixed(&input);
+            assert_eq!(Sha2CrateImpl.hash_fixed(&input), ring_hash);
+            assert_eq!(RingImpl.hash(&input), ring_hash.to_vec());
+            assert_eq!(Sha2CrateImpl.hash(&input), ring_hash.to_vec());
+            assert_eq!(hash_fixed(&input), ring_hash);
+
+            let (left, right) = input.split_at(*len / 2);
+            let mut context = Context::new();
+            context.update(left);
+            context.update(right);
+            assert_eq!(context.finalize(), ring_hash);
+        }
+    }
+
     #[cfg(feature = "zero_hash_cache")]
     mod zero_hash {
         use super::*;
@@ -0,0 +1,118 @@
+use crate::{open_store, open_store_read_only};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use std::str::FromStr;
+use types::{EthSpec, Hash256, Slot};
+
+pub const CMD: &str = "anchor";
+pub const SET_SPLIT_SLOT_FLAG: &str = "set-split-slot";
+pub const SET_SPLIT_STATE_ROOT_FLAG: &str = "set-split-state-root";
+pub const DRY_RUN_FLAG: &str = "dry-run";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Displays the split point of the database of a stopped beacon node, before which all \
+            states are frozen, and the anchor block of the split state. The split can be moved \
+            back to an earlier state in the hot database to recover a database whose split \
+            state has been lost, after checking that the move leaves the database consistent.",
+        )
+        .arg(
+            Arg::with_name(SET_SPLIT_SLOT_FLAG)
+                .long(SET_SPLIT_SLOT_FLAG)
+                .value_name("SLOT")
+                .help("The slot of the state to move the split point to.")
+                .requires(SET_SPLIT_STATE_ROOT_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(SET_SPLIT_STATE_ROOT_FLAG)
+                .long(SET_SPLIT_STATE_ROOT_FLAG)
+                .value_name("ROOT")
+                .help("The root of the state to move the split point to.")
+                .requires(SET_SPLIT_SLOT_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(DRY_RUN_FLAG)
+                .long(DRY_RUN_FLAG)
+                .help("Check that the split point can be moved, without changing the database.")
+                .requires(SET_SPLIT_SLOT_FLAG),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let new_split = match matches.value_of(SET_SPLIT_STATE_ROOT_FLAG) {
+        Some(state_root) => {
+            let slot = clap_utils::parse_required(matches, SET_SPLIT_SLOT_FLAG)?;
+            Some((Slot::new(slot), parse_root(state_root)?))
+        }
+        None => None,
+    };
+    let dry_run = matches.is_present(DRY_RUN_FLAG);
+
+    // The database is only opened for writing if the split point is to be moved.
+    let store = if new_split.is_some() && !dry_run {
+        open_store(matches, &env)?
+    } else {
+        open_store_read_only(matches, &env)?
+    };
+
+    let info = store
+        .get_anchor_info()
+        .map_err(|e| format!("Unable to read the split point: {:?}", e))?;
+    let limits = info.historic_state_limits;
+    println!("Split slot:                {}", info.split_slot);
+    println!("Split state root:          {:?}", info.split_state_root);
+    println!("Split state stored:        {}", info.split_state_stored);
+    println!(
+        "Anchor block root:         {}",
+        info.anchor_block_root
+            .map_or_else(|| "missing".to_string(), |root| format!("{:?}", root))
+    );
+    println!(
+        "Anchor block slot:         {}",
+        info.anchor_block_slot
+            .map_or_else(|| "missing".to_string(), |slot| slot.to_string())
+    );
+    println!(
+        "Latest restore point slot: {}",
+        info.latest_restore_point_slot
+    );
+    println!(
+        "Historic state limits:     {}..{}",
+        limits.state_lower_limit, limits.state_upper_limit
+    );
+
+    let (slot, state_root) = match new_split {
+        Some(new_split) => new_split,
+        None => return Ok(()),
+    };
+    println!();
+
+    if dry_run {
+        store
+            .check_split(slot, state_root)
+            .map_err(|e| format!("Unable to move the split point: {:?}", e))?;
+        eprintln!(
+            "Dry run, the split point can be moved to slot {}, but the database was not changed",
+            slot
+        );
+        return Ok(());
+    }
+
+    store
+        .set_split(slot, state_root)
+        .map_err(|e| format!("Unable to move the split point: {:?}", e))?;
+    eprintln!("Done, the split point was moved to slot {}", slot);
+    Ok(())
+}
+
+/// Parse a `0x`-prefixed hex root.
+fn parse_root(value: &str) -> Result<Hash256, String> {
+    if value.starts_with("0x") {
+        Hash256::from_str(&value[2..]).map_err(|e| format!("Invalid root {}: {:?}", value, e))
+    } else {
+        Err(format!("Invalid root {}: must start with 0x", value))
+    }
+}
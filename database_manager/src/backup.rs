@@ -0,0 +1,70 @@
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use eth2::{lighthouse::DatabaseBackupRequest, BeaconNodeHttpClient, Url};
+use std::path::PathBuf;
+use tokio_compat_02::FutureExt;
+use types::EthSpec;
+
+pub const CMD: &str = "backup";
+pub const DEST_ARG: &str = "DEST";
+pub const BEACON_SERVER_FLAG: &str = "beacon-node";
+
+pub const DEFAULT_BEACON_NODE: &str = "http://localhost:5052/";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Writes a consistent copy of the hot and freezer databases of a running beacon node \
+            to DEST. The beacon node must be on the same machine, and must be started with \
+            --http-allow-database-backup.",
+        )
+        .arg(
+            Arg::with_name(DEST_ARG)
+                .value_name("DEST")
+                .help("The directory to create the backup in. It must not already exist.")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name(BEACON_SERVER_FLAG)
+                .long(BEACON_SERVER_FLAG)
+                .value_name("NETWORK_ADDRESS")
+                .help("Address to a beacon node HTTP API")
+                .default_value(&DEFAULT_BEACON_NODE)
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let dest: PathBuf = clap_utils::parse_required(matches, DEST_ARG)?;
+    // The path is resolved by the beacon node, so it must not depend on our working directory.
+    let dest = if dest.is_absolute() {
+        dest
+    } else {
+        std::env::current_dir()
+            .map_err(|e| format!("Unable to read the current directory: {:?}", e))?
+            .join(dest)
+    };
+
+    let server_url: String = clap_utils::parse_required(matches, BEACON_SERVER_FLAG)?;
+    let client = BeaconNodeHttpClient::new(
+        Url::parse(&server_url)
+            .map_err(|e| format!("Failed to parse beacon http server: {:?}", e))?,
+    );
+
+    eprintln!("Backing up the beacon node database to {:?}", dest);
+
+    let request = DatabaseBackupRequest { path: dest };
+    let backup = env
+        .runtime()
+        .block_on(client.post_lighthouse_database_backup(&request).compat())
+        .map_err(|e| format!("Database backup failed: {:?}", e))?
+        .data;
+
+    eprintln!(
+        "Backup complete: {} hot database entries, {} freezer database entries",
+        backup.hot_db_entries, backup.freezer_db_entries
+    );
+
+    Ok(())
+}
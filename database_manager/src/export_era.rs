@@ -0,0 +1,96 @@
+use crate::open_store_read_only;
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use std::fs;
+use std::path::{Path, PathBuf};
+use store::Era;
+use types::{EthSpec, Unsigned};
+
+pub const CMD: &str = "export-era";
+pub const DIR_ARG: &str = "DIR";
+pub const FROM_FLAG: &str = "from";
+pub const TO_FLAG: &str = "to";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Writes era files of the finalized chain from the database of a stopped beacon node. \
+            Each era holds the blocks of one historical root and the state at its end, and is \
+            verified against its historical root before it is written. Eras whose files already \
+            exist are skipped, so an interrupted export can be resumed.",
+        )
+        .arg(
+            Arg::with_name(DIR_ARG)
+                .value_name("DIR")
+                .help("The directory to write the era files to.")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name(FROM_FLAG)
+                .long(FROM_FLAG)
+                .value_name("ERA")
+                .help("The first era to export.")
+                .default_value("0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(TO_FLAG)
+                .long(TO_FLAG)
+                .value_name("ERA")
+                .help("The last era to export. [default: the latest finalized era]")
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let dir: PathBuf = clap_utils::parse_required(matches, DIR_ARG)?;
+    let from: u64 = clap_utils::parse_required(matches, FROM_FLAG)?;
+    let config_name = directory::get_testnet_name(matches);
+    let store = open_store_read_only(matches, &env)?;
+
+    let slots_per_era = T::SlotsPerHistoricalRoot::to_u64();
+    let latest_era = store.get_split_slot().as_u64().saturating_sub(1) / slots_per_era;
+    let to = clap_utils::parse_optional(matches, TO_FLAG)?.unwrap_or(latest_era);
+    if to > latest_era {
+        return Err(format!(
+            "Era {} is not finalized, the latest finalized era is {}",
+            to, latest_era
+        ));
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create {}: {:?}", dir.display(), e))?;
+
+    for number in from..=to {
+        let era = store
+            .load_era(number)
+            .map_err(|e| format!("Unable to load era {}: {:?}", number, e))?;
+        era.verify()
+            .map_err(|e| format!("Era {} failed verification: {:?}", number, e))?;
+
+        let file_name = era
+            .file_name(&config_name)
+            .map_err(|e| format!("Unable to name era {}: {:?}", number, e))?;
+        let path = dir.join(&file_name);
+        if path.exists() {
+            eprintln!("Skipping {}, which already exists", file_name);
+            continue;
+        }
+
+        write_era(&era, &path)?;
+        eprintln!("Wrote {} ({} blocks)", file_name, era.blocks.len());
+    }
+
+    Ok(())
+}
+
+/// Write `era` to `path` via a temporary file, so that an incomplete file is never left at `path`.
+fn write_era<T: EthSpec>(era: &Era<T>, path: &Path) -> Result<(), String> {
+    let bytes = era
+        .to_bytes()
+        .map_err(|e| format!("Unable to encode era {}: {:?}", era.number(), e))?;
+    let temp_path = path.with_extension("era.tmp");
+    fs::write(&temp_path, &bytes)
+        .map_err(|e| format!("Unable to write {}: {:?}", temp_path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Unable to write {}: {:?}", path.display(), e))
+}
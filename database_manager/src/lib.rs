@@ -0,0 +1,163 @@
+pub mod anchor;
+pub mod backup;
+pub mod export_era;
+pub mod migrate_restore_points;
+pub mod migrate_schema;
+pub mod prune_history;
+pub mod stats;
+pub mod verify;
+pub mod verify_era;
+
+use beacon_node::{get_data_dir, ClientConfig};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use std::path::PathBuf;
+use store::config::DEFAULT_SLOTS_PER_RESTORE_POINT;
+use store::{BeaconNodeBackend, DatabaseBackend, HotColdDB, StoreConfig};
+use types::EthSpec;
+
+pub const CMD: &str = "database_manager";
+pub const FREEZER_DIR_FLAG: &str = "freezer-dir";
+pub const SLOTS_PER_RESTORE_POINT_FLAG: &str = "slots-per-restore-point";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .visible_aliases(&["db", "database"])
+        .about("Utilities for managing the database of a beacon node.")
+        .arg(
+            Arg::with_name(FREEZER_DIR_FLAG)
+                .long(FREEZER_DIR_FLAG)
+                .value_name("DIR")
+                .help("Data directory for the freezer database, if it is not in the datadir.")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name(SLOTS_PER_RESTORE_POINT_FLAG)
+                .long(SLOTS_PER_RESTORE_POINT_FLAG)
+                .value_name("SLOT_COUNT")
+                .help(
+                    "The slots per restore point that the database was created with. \
+                    [default: 2048 (mainnet) or 64 (minimal)]",
+                )
+                .takes_value(true)
+                .global(true),
+        )
+        .subcommand(anchor::cli_app())
+        .subcommand(backup::cli_app())
+        .subcommand(export_era::cli_app())
+        .subcommand(migrate_restore_points::cli_app())
+        .subcommand(migrate_schema::cli_app())
+        .subcommand(prune_history::cli_app())
+        .subcommand(stats::cli_app())
+        .subcommand(verify::cli_app())
+        .subcommand(verify_era::cli_app())
+}
+
+/// Run the database manager, returning an error if the operation did not succeed.
+pub fn run<T: EthSpec>(matches: &ArgMatches<'_>, env: Environment<T>) -> Result<(), String> {
+    match matches.subcommand() {
+        (anchor::CMD, Some(matches)) => anchor::cli_run(matches, env)?,
+        (backup::CMD, Some(matches)) => backup::cli_run(matches, env)?,
+        (export_era::CMD, Some(matches)) => export_era::cli_run(matches, env)?,
+        (migrate_restore_points::CMD, Some(matches)) => {
+            migrate_restore_points::cli_run(matches, env)?
+        }
+        (migrate_schema::CMD, Some(matches)) => migrate_schema::cli_run(matches, env)?,
+        (prune_history::CMD, Some(matches)) => prune_history::cli_run(matches, env)?,
+        (stats::CMD, Some(matches)) => stats::cli_run(matches, env)?,
+        (verify::CMD, Some(matches)) => verify::cli_run(matches, env)?,
+        (verify_era::CMD, Some(matches)) => verify_era::cli_run::<T>(matches)?,
+        (unknown, _) => {
+            return Err(format!(
+                "{} is not a valid {} command. See --help.",
+                unknown, CMD
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Open the database of a stopped beacon node, using the same paths as the beacon node.
+///
+/// The backend and compression of the database are detected, and its historic state policy is
+/// left unchanged.
+pub fn open_store<T: EthSpec>(
+    matches: &ArgMatches,
+    env: &Environment<T>,
+) -> Result<HotColdDB<T, BeaconNodeBackend<T>, BeaconNodeBackend<T>>, String> {
+    open_store_inner(matches, env, true, false)
+}
+
+/// Open the database as `open_store` does, but leave its schema version unchanged rather than
+/// migrating it to the current version. With `read_only`, the database is opened without write
+/// access, whatever its schema version.
+pub fn open_store_without_schema_migration<T: EthSpec>(
+    matches: &ArgMatches,
+    env: &Environment<T>,
+    read_only: bool,
+) -> Result<HotColdDB<T, BeaconNodeBackend<T>, BeaconNodeBackend<T>>, String> {
+    open_store_inner(matches, env, false, read_only)
+}
+
+/// Open the database as `open_store` does, but without write access, for commands which only
+/// read it. The database must already have the current schema version.
+pub fn open_store_read_only<T: EthSpec>(
+    matches: &ArgMatches,
+    env: &Environment<T>,
+) -> Result<HotColdDB<T, BeaconNodeBackend<T>, BeaconNodeBackend<T>>, String> {
+    open_store_inner(matches, env, true, true)
+}
+
+fn open_store_inner<T: EthSpec>(
+    matches: &ArgMatches,
+    env: &Environment<T>,
+    migrate_schema: bool,
+    read_only: bool,
+) -> Result<HotColdDB<T, BeaconNodeBackend<T>, BeaconNodeBackend<T>>, String> {
+    let client_config = ClientConfig {
+        data_dir: get_data_dir(matches),
+        freezer_db_path: matches.value_of(FREEZER_DIR_FLAG).map(PathBuf::from),
+        ..ClientConfig::default()
+    };
+    let hot_path = client_config
+        .get_db_path()
+        .ok_or("Unable to locate the database")?;
+    let cold_path = client_config
+        .get_freezer_db_path()
+        .ok_or("Unable to locate the freezer database")?;
+
+    let backend = DatabaseBackend::detect(&hot_path)
+        .ok_or_else(|| format!("No database found at {}", hot_path.display()))?;
+    let slots_per_restore_point =
+        clap_utils::parse_optional(matches, SLOTS_PER_RESTORE_POINT_FLAG)?.unwrap_or_else(|| {
+            std::cmp::min(
+                T::slots_per_historical_root() as u64,
+                DEFAULT_SLOTS_PER_RESTORE_POINT,
+            )
+        });
+    let store_config = StoreConfig {
+        slots_per_restore_point,
+        backend,
+        migrate_schema,
+        prune_historic_states: None,
+        read_only,
+        ..StoreConfig::default()
+    };
+
+    HotColdDB::open(
+        &hot_path,
+        &cold_path,
+        store_config,
+        env.eth2_config().spec.clone(),
+        env.log().clone(),
+    )
+    .map_err(|e| {
+        format!(
+            "Unable to open the database at {} (the beacon node must be stopped): {:?}",
+            hot_path.display(),
+            e
+        )
+    })
+}
@@ -0,0 +1,60 @@
+use crate::{open_store, SLOTS_PER_RESTORE_POINT_FLAG};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use std::sync::Arc;
+use types::EthSpec;
+
+pub const CMD: &str = "migrate-restore-points";
+pub const TO_FLAG: &str = "to";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Rewrites the freezer database of a stopped beacon node to store a restore point \
+            every N slots, without re-syncing. Restore points are created by replaying blocks, so \
+            decreasing the frequency is quick but increasing it can take several hours. Historic \
+            state reconstruction must be complete, or historic states pruned. If the migration is \
+            interrupted, the database keeps its old layout or is switched to the new one when it \
+            is next opened.",
+        )
+        .arg(
+            Arg::with_name(TO_FLAG)
+                .long(TO_FLAG)
+                .value_name("SLOT_COUNT")
+                .help(
+                    "The new number of slots per restore point. It must divide the slots per \
+                    historical root, and be a multiple of the slots per epoch.",
+                )
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let slots_per_restore_point: u64 = clap_utils::parse_required(matches, TO_FLAG)?;
+    let store = Arc::new(open_store(matches, &env)?);
+
+    let old_slots_per_restore_point = store.get_config().slots_per_restore_point;
+    if slots_per_restore_point == old_slots_per_restore_point {
+        eprintln!(
+            "The database already has {} slots per restore point",
+            slots_per_restore_point
+        );
+        return Ok(());
+    }
+
+    eprintln!(
+        "Migrating from {} to {} slots per restore point, this may take a while",
+        old_slots_per_restore_point, slots_per_restore_point
+    );
+
+    store
+        .migrate_slots_per_restore_point(slots_per_restore_point)
+        .map_err(|e| format!("Unable to migrate the restore points: {:?}", e))?;
+
+    eprintln!(
+        "Done. Start the beacon node with --{} {}",
+        SLOTS_PER_RESTORE_POINT_FLAG, slots_per_restore_point
+    );
+    Ok(())
+}
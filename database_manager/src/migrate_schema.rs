@@ -0,0 +1,80 @@
+use crate::open_store_without_schema_migration;
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use store::{SchemaVersion, CURRENT_SCHEMA_VERSION};
+use types::EthSpec;
+
+pub const CMD: &str = "migrate-schema";
+pub const TO_FLAG: &str = "to";
+pub const DRY_RUN_FLAG: &str = "dry-run";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Migrates the database of a stopped beacon node to another schema version. The beacon \
+            node migrates its database to the current version when it starts, so this is mainly \
+            useful to preview that migration with --dry-run, or to downgrade the database before \
+            running an older release.",
+        )
+        .arg(
+            Arg::with_name(TO_FLAG)
+                .long(TO_FLAG)
+                .value_name("VERSION")
+                .help("The schema version to migrate to. [default: the current version]")
+                .takes_value(true),
+        )
+        .arg(Arg::with_name(DRY_RUN_FLAG).long(DRY_RUN_FLAG).help(
+            "Print the migrations that would be applied, and an estimate of how long \
+                    they would take, without changing the database.",
+        ))
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let to = clap_utils::parse_optional(matches, TO_FLAG)?
+        .map(SchemaVersion)
+        .unwrap_or(CURRENT_SCHEMA_VERSION);
+    let dry_run = matches.is_present(DRY_RUN_FLAG);
+    // A dry run opens the database read-only, so that it isn't written to at all.
+    let store = open_store_without_schema_migration(matches, &env, dry_run)?;
+
+    let plan = store
+        .plan_schema_migration(to)
+        .map_err(|e| format!("Unable to plan the schema migration: {:?}", e))?;
+    if plan.steps.is_empty() {
+        eprintln!("The database already has schema version {}", to.as_u64());
+        return Ok(());
+    }
+
+    eprintln!(
+        "Migrating from schema version {} to {}:",
+        plan.from.as_u64(),
+        plan.to.as_u64()
+    );
+    for step in &plan.steps {
+        eprintln!(
+            "  v{} -> v{}: {} ({} entries, about {}s)",
+            step.migration.from.as_u64(),
+            step.migration.to.as_u64(),
+            step.migration.description,
+            step.entries,
+            step.estimated_duration().as_secs()
+        );
+    }
+    eprintln!(
+        "Estimated duration: {}s, disk space: {} MiB",
+        plan.estimated_duration().as_secs(),
+        plan.estimated_bytes() / (1024 * 1024)
+    );
+
+    if dry_run {
+        eprintln!("Dry run, the database was not changed");
+        return Ok(());
+    }
+
+    store
+        .migrate_schema(plan.from, plan.to)
+        .map_err(|e| format!("Unable to migrate the schema: {:?}", e))?;
+
+    eprintln!("Done");
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::open_store;
+use clap::{App, ArgMatches};
+use environment::Environment;
+use types::EthSpec;
+
+pub const CMD: &str = "prune-history";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD).about(
+        "Deletes the blocks and historic states which precede the latest finalized checkpoint \
+        from the database of a stopped beacon node, leaving it as if it had been synced from that \
+        checkpoint. This can't be undone, and the history can only be recovered by re-syncing.",
+    )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let store = open_store(matches, &env)?;
+
+    eprintln!(
+        "Pruning history prior to slot {}, this may take a while",
+        store.get_split_slot()
+    );
+
+    let summary = store
+        .prune_history()
+        .map_err(|e| format!("Unable to prune history: {:?}", e))?;
+
+    eprintln!(
+        "Done: {} blocks, {} restore points and {} state summaries deleted",
+        summary.blocks_deleted, summary.restore_points_deleted, summary.state_summaries_deleted
+    );
+    Ok(())
+}
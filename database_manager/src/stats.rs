@@ -0,0 +1,56 @@
+use crate::open_store_read_only;
+use clap::{App, ArgMatches};
+use environment::Environment;
+use store::ColumnStats;
+use types::EthSpec;
+
+pub const CMD: &str = "stats";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD).about(
+        "Reports the number of entries and approximate size of each column of the hot and \
+        freezer databases of a stopped beacon node. Every entry is read, so this can take some \
+        time on a large database.",
+    )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let store = open_store_read_only(matches, &env)?;
+
+    let stats = store
+        .stats()
+        .map_err(|e| format!("Unable to read the database: {:?}", e))?;
+
+    print_stats("Hot database", stats.hot);
+    println!();
+    print_stats("Freezer database", stats.cold);
+    Ok(())
+}
+
+/// Print a table of `stats`, largest first.
+fn print_stats(title: &str, mut stats: Vec<ColumnStats>) {
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.total_bytes()));
+    let total_bytes = stats.iter().map(ColumnStats::total_bytes).sum::<u64>();
+
+    println!("{} ({}):", title, format_size(total_bytes));
+    println!(
+        "{:<24} {:<6} {:>12} {:>12}",
+        "Column", "Prefix", "Entries", "Size"
+    );
+    for column_stats in &stats {
+        let name = column_stats
+            .column
+            .map_or_else(|| "Unknown".to_string(), |column| format!("{:?}", column));
+        println!(
+            "{:<24} {:<6} {:>12} {:>12}",
+            name,
+            column_stats.prefix,
+            column_stats.entries,
+            format_size(column_stats.total_bytes())
+        );
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
@@ -0,0 +1,48 @@
+use crate::open_store_read_only;
+use clap::{App, ArgMatches};
+use environment::Environment;
+use types::EthSpec;
+
+pub const CMD: &str = "verify";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD).about(
+        "Checks that the blocks, states and restore points in the database of a stopped beacon \
+        node are readable, match the roots they are stored under, and have no gaps. Every state \
+        is decoded and hashed, so this can take several hours on a large database.",
+    )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let store = open_store_read_only(matches, &env)?;
+
+    eprintln!("Verifying the database, this may take a while");
+
+    let report = store
+        .verify()
+        .map_err(|e| format!("Database verification failed: {:?}", e))?;
+
+    eprintln!("Blocks: {}", report.blocks);
+    eprintln!("Hot states: {}", report.hot_states);
+    eprintln!("Hot state summaries: {}", report.hot_state_summaries);
+    eprintln!(
+        "Restore points: {} ({} skipped)",
+        report.restore_points, report.skipped_restore_points
+    );
+    if let Some(anchor_slot) = report.anchor_slot {
+        eprintln!("Oldest block (checkpoint anchor) at slot {}", anchor_slot);
+    }
+
+    if report.is_consistent() {
+        eprintln!("No inconsistencies found");
+        Ok(())
+    } else {
+        for inconsistency in &report.inconsistencies {
+            eprintln!("Inconsistency: {}", inconsistency);
+        }
+        Err(format!(
+            "Found {} inconsistencies in the database",
+            report.inconsistencies.len()
+        ))
+    }
+}
@@ -0,0 +1,67 @@
+use clap::{App, Arg, ArgMatches};
+use std::fs;
+use std::path::PathBuf;
+use store::Era;
+use types::EthSpec;
+
+pub const CMD: &str = "verify-era";
+pub const FILES_ARG: &str = "FILES";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Checks that era files hold the canonical blocks of their era, by verifying them \
+            against the historical root in the state at the end of each era. The database is not \
+            used, so the roots of the states must be checked separately against a trusted source.",
+        )
+        .arg(
+            Arg::with_name(FILES_ARG)
+                .value_name("FILES")
+                .help("The era files to verify.")
+                .required(true)
+                .multiple(true)
+                .index(1),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
+    let paths = matches
+        .values_of(FILES_ARG)
+        .ok_or("No era files supplied")?
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    let mut num_invalid = 0;
+    for path in &paths {
+        let result = fs::read(path)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|bytes| Era::<T>::from_bytes(&bytes).map_err(|e| format!("{:?}", e)))
+            .and_then(|era| {
+                era.verify().map_err(|e| format!("{:?}", e))?;
+                Ok(era)
+            });
+
+        match result {
+            Ok(era) => eprintln!(
+                "{}: era {} is valid, state root {:?}",
+                path.display(),
+                era.number(),
+                era.state.canonical_root()
+            ),
+            Err(e) => {
+                eprintln!("{}: invalid: {}", path.display(), e);
+                num_invalid += 1;
+            }
+        }
+    }
+
+    if num_invalid == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} era files are invalid",
+            num_invalid,
+            paths.len()
+        ))
+    }
+}
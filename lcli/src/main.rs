@@ -7,9 +7,12 @@ mod eth1_genesis;
 mod generate_bootnode_enr;
 mod insecure_validators;
 mod interop_genesis;
+mod mnemonic_validators;
 mod new_testnet;
 mod parse_hex;
+mod parse_ssz;
 mod refund_deposit_contract;
+mod replay_states;
 mod skip_slots;
 mod transition_blocks;
 
@@ -93,7 +96,10 @@ fn main() {
                         .value_name("BEACON_STATE")
                         .takes_value(true)
                         .required(true)
-                        .help("Path to a SSZ file of the pre-state."),
+                        .help(
+                            "Path to a SSZ file of the pre-state, or with --beacon-url the ID of \
+                            the state to download, e.g. head, finalized, a slot or a state root.",
+                        ),
                 )
                 .arg(
                     Arg::with_name("slots")
@@ -102,6 +108,34 @@ fn main() {
                         .required(true)
                         .help("Number of slots to skip before outputting a state.."),
                 )
+                .arg(
+                    Arg::with_name("beacon-url")
+                        .long("beacon-url")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .help("The HTTP API of a beacon node to download the pre-state from."),
+                )
+                .arg(
+                    Arg::with_name("runs")
+                        .long("runs")
+                        .value_name("N")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("Run the transition this many times, printing the timings of each run."),
+                )
+                .arg(
+                    Arg::with_name("caches")
+                        .long("caches")
+                        .value_name("CACHES")
+                        .takes_value(true)
+                        .default_value("all")
+                        .help(
+                            "The caches of the pre-state to retain for each run, as a \
+                            comma-separated list of committee, pubkey, exit, \
+                            progressive-balances and tree-hash, or all or none. Caches which are \
+                            not retained are rebuilt during every run.",
+                        ),
+                )
                 .arg(
                     Arg::with_name("output")
                         .value_name("SSZ_FILE")
@@ -119,14 +153,57 @@ fn main() {
                         .value_name("BEACON_STATE")
                         .takes_value(true)
                         .required(true)
-                        .help("Path to a SSZ file of the pre-state."),
+                        .help(
+                            "Path to a SSZ file of the pre-state, or with --beacon-url the ID of \
+                            the state to download, e.g. head, finalized, a slot or a state root.",
+                        ),
                 )
                 .arg(
                     Arg::with_name("block")
                         .value_name("BEACON_BLOCK")
                         .takes_value(true)
                         .required(true)
-                        .help("Path to a SSZ file of the block to apply to pre-state."),
+                        .help(
+                            "Path to a SSZ file of the block to apply to pre-state, or with \
+                            --beacon-url the ID of the block to download.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("beacon-url")
+                        .long("beacon-url")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .help("The HTTP API of a beacon node to download the pre-state and block from."),
+                )
+                .arg(
+                    Arg::with_name("runs")
+                        .long("runs")
+                        .value_name("N")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("Run the transition this many times, printing the timings of each run."),
+                )
+                .arg(
+                    Arg::with_name("caches")
+                        .long("caches")
+                        .value_name("CACHES")
+                        .takes_value(true)
+                        .default_value("all")
+                        .help(
+                            "The caches of the pre-state to retain for each run, as a \
+                            comma-separated list of committee, pubkey, exit, \
+                            progressive-balances and tree-hash, or all or none. Caches which are \
+                            not retained are rebuilt during every run.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("signature-strategy")
+                        .long("signature-strategy")
+                        .value_name("STRATEGY")
+                        .takes_value(true)
+                        .possible_values(&["none", "individual", "bulk"])
+                        .default_value("individual")
+                        .help("How to verify the signatures of the block."),
                 )
                 .arg(
                     Arg::with_name("output")
@@ -137,6 +214,161 @@ fn main() {
                         .help("Path to output a SSZ file."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("replay-states")
+                .about(
+                    "Replays a range of blocks against a pre-state, printing the time taken by \
+                    each step of the state transition as CSV. Blocks are loaded from a directory \
+                    of SSZ files, a beacon node API or the database of a stopped beacon node.",
+                )
+                .arg(
+                    Arg::with_name("pre-state")
+                        .long("pre-state")
+                        .value_name("BEACON_STATE")
+                        .takes_value(true)
+                        .help(
+                            "Path to a SSZ file of the pre-state. Required with --blocks-dir, \
+                            otherwise the state at --start-slot is loaded from the source.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("blocks-dir")
+                        .long("blocks-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .conflicts_with_all(&["beacon-url", "datadir"])
+                        .help("Path to a directory of SSZ files of the blocks to replay."),
+                )
+                .arg(
+                    Arg::with_name("beacon-url")
+                        .long("beacon-url")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .conflicts_with("datadir")
+                        .help("The HTTP API of a beacon node to download the blocks from."),
+                )
+                .arg(
+                    Arg::with_name("datadir")
+                        .long("datadir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help(
+                            "The data directory of a stopped beacon node to load the blocks \
+                            from. Only finalized blocks can be loaded.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name(database_manager::FREEZER_DIR_FLAG)
+                        .long(database_manager::FREEZER_DIR_FLAG)
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .requires("datadir")
+                        .help("Data directory for the freezer database, if it is not in the datadir."),
+                )
+                .arg(
+                    Arg::with_name(database_manager::SLOTS_PER_RESTORE_POINT_FLAG)
+                        .long(database_manager::SLOTS_PER_RESTORE_POINT_FLAG)
+                        .value_name("SLOT_COUNT")
+                        .takes_value(true)
+                        .requires("datadir")
+                        .help(
+                            "The slots per restore point that the database was created with. \
+                            [default: 2048 (mainnet) or 64 (minimal)]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("start-slot")
+                        .long("start-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .help("The slot of the pre-state, when loading from an API or database."),
+                )
+                .arg(
+                    Arg::with_name("end-slot")
+                        .long("end-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .help("The slot of the last block, when loading from an API or database."),
+                )
+                .arg(
+                    Arg::with_name("caches")
+                        .long("caches")
+                        .value_name("CACHES")
+                        .takes_value(true)
+                        .default_value("all")
+                        .help(
+                            "The caches to retain between blocks, as a comma-separated list of \
+                            committee, pubkey, exit, progressive-balances and tree-hash, or all \
+                            or none. Caches which are not retained are rebuilt for every block.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("signature-strategy")
+                        .long("signature-strategy")
+                        .value_name("STRATEGY")
+                        .takes_value(true)
+                        .possible_values(&["none", "individual", "bulk"])
+                        .default_value("bulk")
+                        .help("How to verify the signatures of each block."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("SSZ_FILE")
+                        .takes_value(true)
+                        .help("Path to output a SSZ file of the post-state."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("ssz")
+                .about("Inspects and compares SSZ files of consensus containers")
+                .subcommand(
+                    SubCommand::with_name("inspect")
+                        .about("Prints a container as YAML, along with its tree hash root")
+                        .arg(
+                            Arg::with_name("type")
+                                .value_name("TYPE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("The name of the container type, e.g. BeaconState or SignedBeaconBlock"),
+                        )
+                        .arg(
+                            Arg::with_name("path")
+                                .value_name("SSZ_FILE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Path to a SSZ file of the container."),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about(
+                            "Prints every field which differs between two containers of the same \
+                            type, including the elements of lists",
+                        )
+                        .arg(
+                            Arg::with_name("type")
+                                .value_name("TYPE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("The name of the container type, e.g. BeaconState or SignedBeaconBlock"),
+                        )
+                        .arg(
+                            Arg::with_name("path-a")
+                                .value_name("SSZ_FILE_A")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Path to a SSZ file of the first container."),
+                        )
+                        .arg(
+                            Arg::with_name("path-b")
+                                .value_name("SSZ_FILE_B")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Path to a SSZ file of the second container."),
+                        ),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("pretty-hex")
                 .about("Parses SSZ encoded as ASCII 0x-prefixed hex")
@@ -394,6 +626,31 @@ fn main() {
                         .help("The block the deposit contract was deployed. Setting this is a huge
                               optimization for nodes, please do it."),
                 )
+                .arg(
+                    Arg::with_name("boot-enr")
+                        .long("boot-enr")
+                        .value_name("ENRS")
+                        .takes_value(true)
+                        .help("A comma-separated list of the ENRs of the testnet's boot nodes, as \
+                              created by generate-bootnode-enr."),
+                )
+                .arg(
+                    Arg::with_name("interop-genesis-state")
+                        .long("interop-genesis-state")
+                        .takes_value(false)
+                        .requires("validator-count")
+                        .help("Also write a genesis state of insecure, deterministic validators \
+                              with a genesis time of --min-genesis-time, instead of waiting for \
+                              eth1 deposits."),
+                )
+                .arg(
+                    Arg::with_name("validator-count")
+                        .long("validator-count")
+                        .value_name("INTEGER")
+                        .takes_value(true)
+                        .requires("interop-genesis-state")
+                        .help("The number of validators in the interop genesis state."),
+                )
         )
         .subcommand(
             SubCommand::with_name("check-deposit-data")
@@ -492,6 +749,86 @@ fn main() {
                         .help("The directory for storing secrets."),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("mnemonic-validators")
+                .about(
+                    "Produces the validator directories of a devnet from one or more mnemonics, \
+                    split across its nodes, along with a deposit_data-*.json file of all their \
+                    deposits. The deposits are signed for the config in --testnet-dir, if \
+                    supplied.",
+                )
+                .arg(
+                    Arg::with_name("mnemonic")
+                        .long("mnemonic")
+                        .value_name("MNEMONIC_PATH")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(true)
+                        .help("A file containing a mnemonic. May be supplied multiple times."),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .value_name("COUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The number of validators to derive from each mnemonic."),
+                )
+                .arg(
+                    Arg::with_name("first-index")
+                        .long("first-index")
+                        .value_name("INDEX")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("The EIP-2334 key index of the first validator of each mnemonic."),
+                )
+                .arg(
+                    Arg::with_name("nodes")
+                        .long("nodes")
+                        .value_name("NODES")
+                        .takes_value(true)
+                        .help(
+                            "The number of nodes to split the validators evenly between. \
+                            Defaults to 1.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("validators-per-node")
+                        .long("validators-per-node")
+                        .value_name("COUNTS")
+                        .takes_value(true)
+                        .conflicts_with("nodes")
+                        .help(
+                            "A comma-separated list of the number of validators of each node, \
+                            adding up to the number of validators generated. Validators are \
+                            assigned to nodes in order of mnemonic, then index.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("deposit-gwei")
+                        .long("deposit-gwei")
+                        .value_name("GWEI")
+                        .takes_value(true)
+                        .help("The deposit amount of each validator. Defaults to MAX_EFFECTIVE_BALANCE."),
+                )
+                .arg(
+                    Arg::with_name("network-name")
+                        .long("network-name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .default_value("custom")
+                        .help("The network name recorded in the deposit data."),
+                )
+                .arg(
+                    Arg::with_name("output-dir")
+                        .long("output-dir")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The directory to create the node directories and deposit data in."),
+                )
+        )
         .get_matches();
 
     let result = matches
@@ -564,14 +901,19 @@ fn run<T: EthSpec>(
             info!("Genesis state YAML file created. Exiting successfully.");
             Ok(())
         }
-        ("transition-blocks", Some(matches)) => run_transition_blocks::<T>(matches)
+        ("transition-blocks", Some(matches)) => run_transition_blocks::<T>(env, matches)
             .map_err(|e| format!("Failed to transition blocks: {}", e)),
         ("skip-slots", Some(matches)) => {
-            skip_slots::run::<T>(matches).map_err(|e| format!("Failed to skip slots: {}", e))
+            skip_slots::run::<T>(env, matches).map_err(|e| format!("Failed to skip slots: {}", e))
         }
+        ("replay-states", Some(matches)) => replay_states::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run replay-states command: {}", e)),
         ("pretty-hex", Some(matches)) => {
             run_parse_hex::<T>(matches).map_err(|e| format!("Failed to pretty print hex: {}", e))
         }
+        ("ssz", Some(matches)) => {
+            parse_ssz::run::<T>(matches).map_err(|e| format!("Failed to run ssz command: {}", e))
+        }
         ("deploy-deposit-contract", Some(matches)) => {
             deploy_deposit_contract::run::<T>(env, matches)
                 .map_err(|e| format!("Failed to run deploy-deposit-contract command: {}", e))
@@ -594,6 +936,8 @@ fn run<T: EthSpec>(
             .map_err(|e| format!("Failed to run generate-bootnode-enr command: {}", e)),
         ("insecure-validators", Some(matches)) => insecure_validators::run(matches)
             .map_err(|e| format!("Failed to run insecure-validators command: {}", e)),
+        ("mnemonic-validators", Some(matches)) => mnemonic_validators::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run mnemonic-validators command: {}", e)),
         (other, _) => Err(format!("Unknown subcommand {}. See --help.", other)),
     }
 }
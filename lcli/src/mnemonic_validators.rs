@@ -0,0 +1,157 @@
+//! Produce the validators of a devnet from one or more mnemonics, split across its nodes.
+//!
+//! Each node gets a `node_{i}` directory containing `validators` and `secrets` directories in the
+//! layout used by `lighthouse vc`, and the deposits of every validator are written to a single
+//! `deposit_data-*.json` file.
+use account_manager::common::{read_mnemonic_from_cli, validator_keystores_from_seed};
+use account_manager::validator::deposit_data::{write_deposit_data_file, DepositDataJson};
+use account_utils::random_password;
+use clap::ArgMatches;
+use environment::Environment;
+use eth2_testnet_config::Eth2TestnetConfig;
+use eth2_wallet::bip39::Seed;
+use std::fs;
+use std::path::PathBuf;
+use types::EthSpec;
+use validator_dir::Builder as ValidatorBuilder;
+
+pub fn run<T: EthSpec>(env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let mnemonic_paths = matches
+        .values_of("mnemonic")
+        .ok_or_else(|| "No mnemonics supplied".to_string())?
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    let count: u32 = clap_utils::parse_required(matches, "count")?;
+    let first_index: u32 = clap_utils::parse_required(matches, "first-index")?;
+    let end_index = first_index.checked_add(count).ok_or_else(|| {
+        format!(
+            "first-index {} and count {} exceed the largest key index {}",
+            first_index,
+            count,
+            u32::max_value()
+        )
+    })?;
+    let output_dir: PathBuf = clap_utils::parse_required(matches, "output-dir")?;
+    let network_name: String = clap_utils::parse_required(matches, "network-name")?;
+    let testnet_dir: Option<PathBuf> = clap_utils::parse_optional(matches, "testnet-dir")?;
+
+    let spec = if let Some(testnet_dir) = testnet_dir {
+        Eth2TestnetConfig::load(testnet_dir)?
+            .yaml_config
+            .as_ref()
+            .ok_or_else(|| "The testnet directory must contain a spec config".to_string())?
+            .apply_to_chain_spec::<T>(&env.core_context().eth2_config.spec)
+            .ok_or_else(|| {
+                format!(
+                    "The loaded config is not compatible with the {} spec",
+                    &env.core_context().eth2_config.eth_spec_id
+                )
+            })?
+    } else {
+        env.core_context().eth2_config.spec
+    };
+    let deposit_gwei: u64 =
+        clap_utils::parse_optional(matches, "deposit-gwei")?.unwrap_or(spec.max_effective_balance);
+
+    let total = mnemonic_paths.len() * count as usize;
+    let validators_per_node = match matches.value_of("validators-per-node") {
+        Some(split) => {
+            let split = split
+                .split(',')
+                .map(|n| {
+                    n.parse::<usize>()
+                        .map_err(|e| format!("Invalid validators-per-node {}: {}", n, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if split.iter().sum::<usize>() != total {
+                return Err(format!(
+                    "validators-per-node must add up to the {} validators generated",
+                    total
+                ));
+            }
+            split
+        }
+        None => {
+            let nodes: usize = clap_utils::parse_optional(matches, "nodes")?.unwrap_or(1);
+            if nodes == 0 {
+                return Err("nodes must be at least 1".to_string());
+            }
+            // Split the validators evenly, giving any remainder to the first nodes.
+            (0..nodes)
+                .map(|i| total / nodes + if i < total % nodes { 1 } else { 0 })
+                .collect()
+        }
+    };
+
+    if output_dir.exists() {
+        return Err(format!(
+            "{:?} already exists, will not overwrite",
+            output_dir
+        ));
+    }
+
+    // The node of each validator, in the order they are generated.
+    let mut nodes = validators_per_node
+        .iter()
+        .enumerate()
+        .flat_map(|(node, &n)| std::iter::repeat(node).take(n));
+
+    let mut deposits = Vec::with_capacity(total);
+    for (m, mnemonic_path) in mnemonic_paths.into_iter().enumerate() {
+        let mnemonic = read_mnemonic_from_cli(Some(mnemonic_path), false)?;
+        let seed = Seed::new(&mnemonic, "");
+
+        for index in first_index..end_index {
+            let node = nodes
+                .next()
+                .ok_or_else(|| "More validators than nodes can hold".to_string())?;
+            let node_dir = output_dir.join(format!("node_{}", node));
+            let validators_dir = node_dir.join("validators");
+            let secrets_dir = node_dir.join("secrets");
+            for dir in &[&validators_dir, &secrets_dir] {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Unable to create {:?}: {:?}", dir, e))?;
+            }
+
+            let voting_password = random_password();
+            let withdrawal_password = random_password();
+            let keystores = validator_keystores_from_seed(
+                seed.as_bytes(),
+                index,
+                voting_password.as_bytes(),
+                withdrawal_password.as_bytes(),
+            )?;
+            let voting_pubkey = keystores.voting.pubkey().to_string();
+
+            let validator = ValidatorBuilder::new(validators_dir)
+                .password_dir(secrets_dir)
+                .voting_keystore(keystores.voting, voting_password.as_bytes())
+                .withdrawal_keystore(keystores.withdrawal, withdrawal_password.as_bytes())
+                .create_eth1_tx_data(deposit_gwei, &spec)
+                .store_withdrawal_keystore(false)
+                .build()
+                .map_err(|e| format!("Unable to build validator: {:?}", e))?;
+
+            let deposit_data = validator
+                .eth1_deposit_data()
+                .map_err(|e| format!("Unable to read deposit data: {:?}", e))?
+                .ok_or_else(|| format!("No deposit data for validator 0x{}", voting_pubkey))?;
+            deposits.push(DepositDataJson::new(&deposit_data, &spec, &network_name));
+
+            println!(
+                "Validator {}/{}\tmnemonic {}\tindex {}\tnode_{}\t0x{}",
+                deposits.len(),
+                total,
+                m,
+                index,
+                node,
+                voting_pubkey
+            );
+        }
+    }
+
+    let path = write_deposit_data_file(&output_dir, &deposits)?;
+    println!("Deposit data written to {:?}", path);
+
+    Ok(())
+}
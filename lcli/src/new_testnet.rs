@@ -2,9 +2,13 @@ use clap::ArgMatches;
 use clap_utils::{
     parse_optional, parse_path_with_default_in_home_dir, parse_required, parse_ssz_optional,
 };
+use eth2_libp2p::Enr;
 use eth2_testnet_config::Eth2TestnetConfig;
+use genesis::interop_genesis_state;
+use ssz::Encode;
 use std::path::PathBuf;
-use types::{Address, EthSpec, YamlConfig};
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::{test_utils::generate_deterministic_keypairs, Address, EthSpec, YamlConfig};
 
 pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
     let testnet_dir_path = parse_path_with_default_in_home_dir(
@@ -53,10 +57,43 @@ pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
         spec.genesis_fork_version = v;
     }
 
+    let boot_enr = matches
+        .value_of("boot-enr")
+        .map(|enrs| {
+            enrs.split(',')
+                .map(|enr| {
+                    enr.parse::<Enr>()
+                        .map_err(|e| format!("Invalid boot ENR {}: {}", enr, e))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let genesis_state_bytes = if matches.is_present("interop-genesis-state") {
+        let validator_count: usize = parse_required(matches, "validator-count")?;
+        let genesis_time = if let Some(genesis_time) = parse_optional(matches, "min-genesis-time")?
+        {
+            genesis_time
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| format!("Unable to get time: {:?}", e))?
+                .as_secs()
+        };
+
+        let keypairs = generate_deterministic_keypairs(validator_count);
+        let genesis_state = interop_genesis_state::<T>(&keypairs, genesis_time, &spec)?;
+        Some(genesis_state.as_ssz_bytes())
+    } else {
+        None
+    };
+
     let testnet = Eth2TestnetConfig {
         deposit_contract_deploy_block,
-        boot_enr: Some(vec![]),
-        genesis_state_bytes: None,
+        boot_enr: Some(boot_enr),
+        genesis_state_bytes,
+        genesis_state_url: None,
         yaml_config: Some(YamlConfig::from_spec::<T>(&spec)),
     };
 
@@ -1,40 +1,185 @@
-use crate::helpers::parse_path;
+//! Inspect SSZ files of consensus containers, and compare two of them field by field.
+use crate::transition_blocks::load_from_ssz;
 use clap::ArgMatches;
 use serde::Serialize;
+use serde_yaml::Value;
 use ssz::Decode;
-use std::fs::File;
-use std::io::Read;
-use types::{EthSpec, SignedBeaconBlock};
+use std::path::PathBuf;
+use tree_hash::TreeHash;
+use types::{
+    AggregateAndProof, Attestation, AttestationData, AttesterSlashing, BeaconBlock,
+    BeaconBlockBody, BeaconBlockHeader, BeaconState, Checkpoint, Deposit, DepositData,
+    DepositMessage, Eth1Data, EthSpec, Fork, HistoricalBatch, IndexedAttestation,
+    PendingAttestation, ProposerSlashing, SignedAggregateAndProof, SignedBeaconBlock,
+    SignedBeaconBlockHeader, SignedVoluntaryExit, Validator, VoluntaryExit,
+};
+
+/// Call the generic function `$f` with the container type named `$type_str`.
+macro_rules! with_type {
+    ($type_str: expr, $E: ident, $f: ident ( $($arg: expr),* )) => {
+        match $type_str {
+            "AggregateAndProof" => $f::<AggregateAndProof<$E>>($($arg),*),
+            "Attestation" => $f::<Attestation<$E>>($($arg),*),
+            "AttestationData" => $f::<AttestationData>($($arg),*),
+            "AttesterSlashing" => $f::<AttesterSlashing<$E>>($($arg),*),
+            "BeaconBlock" => $f::<BeaconBlock<$E>>($($arg),*),
+            "BeaconBlockBody" => $f::<BeaconBlockBody<$E>>($($arg),*),
+            "BeaconBlockHeader" => $f::<BeaconBlockHeader>($($arg),*),
+            "BeaconState" => $f::<BeaconState<$E>>($($arg),*),
+            "Checkpoint" => $f::<Checkpoint>($($arg),*),
+            "Deposit" => $f::<Deposit>($($arg),*),
+            "DepositData" => $f::<DepositData>($($arg),*),
+            "DepositMessage" => $f::<DepositMessage>($($arg),*),
+            "Eth1Data" => $f::<Eth1Data>($($arg),*),
+            "Fork" => $f::<Fork>($($arg),*),
+            "HistoricalBatch" => $f::<HistoricalBatch<$E>>($($arg),*),
+            "IndexedAttestation" => $f::<IndexedAttestation<$E>>($($arg),*),
+            "PendingAttestation" => $f::<PendingAttestation<$E>>($($arg),*),
+            "ProposerSlashing" => $f::<ProposerSlashing>($($arg),*),
+            "SignedAggregateAndProof" => $f::<SignedAggregateAndProof<$E>>($($arg),*),
+            "SignedBeaconBlock" => $f::<SignedBeaconBlock<$E>>($($arg),*),
+            "SignedBeaconBlockHeader" => $f::<SignedBeaconBlockHeader>($($arg),*),
+            "SignedVoluntaryExit" => $f::<SignedVoluntaryExit>($($arg),*),
+            "Validator" => $f::<Validator>($($arg),*),
+            "VoluntaryExit" => $f::<VoluntaryExit>($($arg),*),
+            other => Err(format!("Unknown type: {}", other)),
+        }
+    };
+}
 
 pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
-    let type_str = matches
-        .value_of("type")
-        .ok_or_else(|| "No type supplied".to_string())?;
-    let path = parse_path(matches, "path")?;
+    match matches.subcommand() {
+        ("inspect", Some(matches)) => {
+            let type_str = type_str(matches)?;
+            let path: PathBuf = clap_utils::parse_required(matches, "path")?;
 
-    info!("Type: {:?}", type_str);
+            info!("Using {} spec", T::spec_name());
+            info!("Type: {:?}", type_str);
 
-    let mut bytes = vec![];
-    let mut file = File::open(&path).map_err(|e| format!("Unable to open {:?}: {}", path, e))?;
-    file.read_to_end(&mut bytes)
-        .map_err(|e| format!("Unable to read {:?}: {}", path, e))?;
+            with_type!(type_str, T, inspect(path))
+        }
+        ("diff", Some(matches)) => {
+            let type_str = type_str(matches)?;
+            let path_a: PathBuf = clap_utils::parse_required(matches, "path-a")?;
+            let path_b: PathBuf = clap_utils::parse_required(matches, "path-b")?;
 
-    match type_str {
-        "SignedBeaconBlock" => decode_and_print::<SignedBeaconBlock<T>>(&bytes)?,
-        other => return Err(format!("Unknown type: {}", other)),
-    };
+            info!("Using {} spec", T::spec_name());
+            info!("Type: {:?}", type_str);
 
-    Ok(())
+            with_type!(type_str, T, diff(path_a, path_b))
+        }
+        (other, _) => Err(format!("Unknown subcommand {}. See --help.", other)),
+    }
 }
 
-fn decode_and_print<T: Decode + Serialize>(bytes: &[u8]) -> Result<(), String> {
-    let item = T::from_ssz_bytes(&bytes).map_err(|e| format!("Ssz decode failed: {:?}", e))?;
+fn type_str<'a>(matches: &'a ArgMatches) -> Result<&'a str, String> {
+    matches
+        .value_of("type")
+        .ok_or_else(|| "No type supplied".to_string())
+}
+
+/// Print the item in `path` as YAML, followed by its tree hash root.
+fn inspect<T: Decode + Serialize + TreeHash>(path: PathBuf) -> Result<(), String> {
+    let item: T = load_from_ssz(path)?;
 
     println!(
         "{}",
         serde_yaml::to_string(&item)
             .map_err(|e| format!("Unable to write object to YAML: {:?}", e))?
     );
+    println!("tree_hash_root: {:?}", item.tree_hash_root());
+
+    Ok(())
+}
+
+/// Print every field which differs between the items in `path_a` and `path_b`, descending into
+/// nested containers and lists.
+fn diff<T: Decode + Serialize + TreeHash>(path_a: PathBuf, path_b: PathBuf) -> Result<(), String> {
+    let item_a: T = load_from_ssz(path_a)?;
+    let item_b: T = load_from_ssz(path_b)?;
+
+    let root_a = item_a.tree_hash_root();
+    let root_b = item_b.tree_hash_root();
+    if root_a == root_b {
+        println!("Identical, tree_hash_root: {:?}", root_a);
+        return Ok(());
+    }
+    println!("tree_hash_root: {:?} -> {:?}", root_a, root_b);
+
+    let to_value = |item: &T| {
+        serde_yaml::to_value(item).map_err(|e| format!("Unable to convert object to YAML: {:?}", e))
+    };
+    let mut differences = vec![];
+    diff_values(
+        "",
+        &to_value(&item_a)?,
+        &to_value(&item_b)?,
+        &mut differences,
+    );
+
+    for difference in &differences {
+        println!("{}", difference);
+    }
+    info!("{} differences", differences.len());
 
     Ok(())
 }
+
+/// Append a line to `differences` for every value which differs between `a` and `b`, where `path`
+/// locates them within the outermost container.
+fn diff_values(path: &str, a: &Value, b: &Value, differences: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Mapping(map_a), Value::Mapping(map_b)) => {
+            for (key, value_a) in map_a {
+                let field = format!("{}.{}", path, value_summary(key));
+                match map_b.get(key) {
+                    Some(value_b) => diff_values(&field, value_a, value_b, differences),
+                    None => differences.push(format!("{}: removed", field)),
+                }
+            }
+            for key in map_b.iter().map(|(key, _)| key) {
+                if !map_a.contains_key(key) {
+                    differences.push(format!("{}.{}: added", path, value_summary(key)));
+                }
+            }
+        }
+        (Value::Sequence(list_a), Value::Sequence(list_b)) => {
+            if list_a.len() != list_b.len() {
+                differences.push(format!(
+                    "{}: length {} -> {}",
+                    path,
+                    list_a.len(),
+                    list_b.len()
+                ));
+            }
+            for (i, (value_a, value_b)) in list_a.iter().zip(list_b).enumerate() {
+                diff_values(&format!("{}[{}]", path, i), value_a, value_b, differences);
+            }
+            for (i, value_b) in list_b.iter().enumerate().skip(list_a.len()) {
+                differences.push(format!("{}[{}]: added {}", path, i, value_summary(value_b)));
+            }
+            for i in list_b.len()..list_a.len() {
+                differences.push(format!("{}[{}]: removed", path, i));
+            }
+        }
+        (a, b) if a != b => differences.push(format!(
+            "{}: {} -> {}",
+            path,
+            value_summary(a),
+            value_summary(b)
+        )),
+        _ => (),
+    }
+}
+
+/// A short description of `value`, which is printed in full unless it is a container or list.
+fn value_summary(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Sequence(list) => format!("<list of {}>", list.len()),
+        Value::Mapping(_) => "<container>".to_string(),
+    }
+}
@@ -0,0 +1,330 @@
+//! Replay a range of blocks against a starting state, timing each step of the state transition.
+//!
+//! Blocks are read from a directory of SSZ files, a beacon node API or the database of a stopped
+//! beacon node. Caches which are not selected are dropped before every block, so their cost of
+//! being rebuilt is included in the timings.
+use crate::transition_blocks::load_from_ssz;
+use clap::ArgMatches;
+use environment::Environment;
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient, Url,
+};
+use ssz::Encode;
+use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use store::HotColdDB;
+use tokio_compat_02::FutureExt;
+use types::{BeaconState, CloneConfig, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The names accepted by `--caches`, in the order they are listed in the help text.
+const CACHE_NAMES: &[&str] = &[
+    "committee",
+    "pubkey",
+    "exit",
+    "progressive-balances",
+    "tree-hash",
+];
+
+pub fn run<T: EthSpec>(env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let spec = env.eth2_config().spec.clone();
+    let caches = parse_caches(
+        matches
+            .value_of("caches")
+            .ok_or_else(|| "No caches supplied".to_string())?,
+    )?;
+    let signature_strategy = match matches.value_of("signature-strategy") {
+        Some("none") => BlockSignatureStrategy::NoVerification,
+        Some("individual") => BlockSignatureStrategy::VerifyIndividual,
+        Some("bulk") => BlockSignatureStrategy::VerifyBulk,
+        _ => return Err("Invalid signature strategy".to_string()),
+    };
+    let pre_state_path: Option<PathBuf> = clap_utils::parse_optional(matches, "pre-state")?;
+    let output_path: Option<PathBuf> = clap_utils::parse_optional(matches, "output")?;
+
+    info!("Using {} spec", T::spec_name());
+    info!("Retaining caches: {:?}", caches);
+
+    let (pre_state, blocks) = if let Some(blocks_dir) = matches.value_of("blocks-dir") {
+        let pre_state_path = pre_state_path
+            .ok_or_else(|| "A pre-state is required when loading blocks from a directory")?;
+        let pre_state = load_from_ssz(pre_state_path)?;
+        let blocks = load_blocks_dir::<T>(PathBuf::from(blocks_dir))?;
+        (pre_state, blocks)
+    } else {
+        let start_slot: Slot = clap_utils::parse_required(matches, "start-slot")?;
+        let end_slot: Slot = clap_utils::parse_required(matches, "end-slot")?;
+        if end_slot <= start_slot {
+            return Err(format!(
+                "End slot {} must be after start slot {}",
+                end_slot, start_slot
+            ));
+        }
+
+        let (pre_state, blocks) = if matches.is_present("beacon-url") {
+            load_from_api::<T>(&env, matches, start_slot, end_slot)?
+        } else if matches.is_present("datadir") {
+            load_from_db::<T>(&env, matches, start_slot, end_slot)?
+        } else {
+            return Err("One of --blocks-dir, --beacon-url or --datadir is required".to_string());
+        };
+
+        match pre_state_path {
+            Some(path) => (load_from_ssz(path)?, blocks),
+            None => (pre_state, blocks),
+        }
+    };
+
+    info!(
+        "Replaying {} blocks from state at slot {}",
+        blocks.len(),
+        pre_state.slot
+    );
+
+    let mut state = pre_state;
+    let mut total = Duration::default();
+
+    println!("slot,skipped_slots,slots_ms,caches_ms,block_ms,state_root_ms,total_ms");
+
+    for block in &blocks {
+        if block.slot() <= state.slot {
+            return Err(format!(
+                "Block at slot {} does not descend from state at slot {}",
+                block.slot(),
+                state.slot
+            ));
+        }
+        let skipped_slots = block.slot() - state.slot - 1;
+
+        // Drop the caches which should not be retained between blocks.
+        state = state.clone_with(caches);
+
+        let timer = Instant::now();
+        while state.slot < block.slot() {
+            per_slot_processing(&mut state, None, &spec)
+                .map_err(|e| format!("Failed to advance to slot {}: {:?}", state.slot + 1, e))?;
+        }
+        let slots_time = timer.elapsed();
+
+        let timer = Instant::now();
+        state
+            .build_all_caches(&spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+        let caches_time = timer.elapsed();
+
+        let timer = Instant::now();
+        per_block_processing(&mut state, block, None, signature_strategy, &spec).map_err(|e| {
+            format!(
+                "State transition failed for block at slot {}: {:?}",
+                block.slot(),
+                e
+            )
+        })?;
+        let block_time = timer.elapsed();
+
+        let timer = Instant::now();
+        let state_root = state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+        let state_root_time = timer.elapsed();
+
+        if state_root != block.state_root() {
+            return Err(format!(
+                "State root mismatch at slot {}: block has {:?}, replayed state has {:?}",
+                block.slot(),
+                block.state_root(),
+                state_root
+            ));
+        }
+
+        let block_total = slots_time + caches_time + block_time + state_root_time;
+        total += block_total;
+
+        println!(
+            "{},{},{},{},{},{},{}",
+            block.slot(),
+            skipped_slots,
+            as_millis(slots_time),
+            as_millis(caches_time),
+            as_millis(block_time),
+            as_millis(state_root_time),
+            as_millis(block_total)
+        );
+    }
+
+    info!(
+        "Replayed {} blocks in {:.3}ms",
+        blocks.len(),
+        as_millis(total)
+    );
+
+    if let Some(output_path) = output_path {
+        fs::write(&output_path, state.as_ssz_bytes())
+            .map_err(|e| format!("Unable to write to output file: {:?}", e))?;
+        info!("Wrote post-state to {:?}", output_path);
+    }
+
+    Ok(())
+}
+
+/// Parse a comma-separated list of `CACHE_NAMES`, or `all` or `none`.
+pub fn parse_caches(value: &str) -> Result<CloneConfig, String> {
+    match value {
+        "all" => return Ok(CloneConfig::all()),
+        "none" => return Ok(CloneConfig::none()),
+        _ => (),
+    }
+
+    let mut config = CloneConfig::none();
+    for name in value.split(',').map(str::trim) {
+        match name {
+            "committee" => config.committee_caches = true,
+            "pubkey" => config.pubkey_cache = true,
+            "exit" => config.exit_cache = true,
+            "progressive-balances" => config.progressive_balances_cache = true,
+            "tree-hash" => config.tree_hash_cache = true,
+            other => {
+                return Err(format!(
+                    "Unknown cache {}, expected one of: {}",
+                    other,
+                    CACHE_NAMES.join(", ")
+                ))
+            }
+        }
+    }
+    Ok(config)
+}
+
+/// Load every SSZ block in `dir`, in slot order.
+fn load_blocks_dir<T: EthSpec>(dir: PathBuf) -> Result<Vec<SignedBeaconBlock<T>>, String> {
+    let mut blocks = fs::read_dir(&dir)
+        .map_err(|e| format!("Unable to read blocks directory {:?}: {:?}", dir, e))?
+        .map(|entry| {
+            let path = entry
+                .map_err(|e| format!("Unable to read blocks directory {:?}: {:?}", dir, e))?
+                .path();
+            load_from_ssz::<SignedBeaconBlock<T>>(path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    blocks.sort_by_key(|block| block.slot());
+    Ok(blocks)
+}
+
+/// Download the state at `start_slot` and the blocks after it, up to and including `end_slot`.
+fn load_from_api<T: EthSpec>(
+    env: &Environment<T>,
+    matches: &ArgMatches,
+    start_slot: Slot,
+    end_slot: Slot,
+) -> Result<(BeaconState<T>, Vec<SignedBeaconBlock<T>>), String> {
+    let server_url: String = clap_utils::parse_required(matches, "beacon-url")?;
+    let client = BeaconNodeHttpClient::new(
+        Url::parse(&server_url).map_err(|e| format!("Failed to parse beacon url: {:?}", e))?,
+    );
+
+    env.runtime().block_on(
+        async {
+            info!("Downloading state at slot {}", start_slot);
+            let pre_state = client
+                .get_debug_beacon_states::<T>(StateId::Slot(start_slot))
+                .await
+                .map_err(|e| format!("Failed to download state: {:?}", e))?
+                .ok_or_else(|| format!("No state at slot {}", start_slot))?
+                .data;
+
+            info!("Downloading blocks up to slot {}", end_slot);
+            let mut blocks = vec![];
+            for slot in (start_slot.as_u64() + 1..=end_slot.as_u64()).map(Slot::new) {
+                let block = client
+                    .get_beacon_blocks::<T>(BlockId::Slot(slot))
+                    .await
+                    .map_err(|e| format!("Failed to download block at slot {}: {:?}", slot, e))?;
+
+                // Skipped slots have no block.
+                if let Some(response) = block {
+                    if response.data.slot() == slot {
+                        blocks.push(response.data);
+                    }
+                }
+            }
+
+            Ok((pre_state, blocks))
+        }
+        .compat(),
+    )
+}
+
+/// Load the finalized state at `start_slot` and the blocks after it, up to and including
+/// `end_slot`, from the database of a stopped beacon node.
+fn load_from_db<T: EthSpec>(
+    env: &Environment<T>,
+    matches: &ArgMatches,
+    start_slot: Slot,
+    end_slot: Slot,
+) -> Result<(BeaconState<T>, Vec<SignedBeaconBlock<T>>), String> {
+    let store = Arc::new(database_manager::open_store_read_only(matches, env)?);
+
+    // The block roots up to and including `end_slot` are iterated forwards from the freezer
+    // database, ending at the state after it.
+    let split_slot = store.get_split_slot();
+    if end_slot + 1 >= split_slot {
+        return Err(format!(
+            "End slot {} must be before the last finalized slot {}",
+            end_slot, split_slot
+        ));
+    }
+
+    info!("Loading state at slot {}", start_slot);
+    let pre_state = store
+        .load_cold_state_by_slot(start_slot)
+        .map_err(|e| format!("Unable to load state at slot {}: {:?}", start_slot, e))?;
+    let end_state = store
+        .load_cold_state_by_slot(end_slot + 1)
+        .map_err(|e| format!("Unable to load state at slot {}: {:?}", end_slot + 1, e))?;
+
+    // The latest block header of a state lacks its state root if the block is at the state's slot.
+    let mut end_block_header = end_state.latest_block_header.clone();
+    if end_block_header.state_root == Hash256::zero() {
+        end_block_header.state_root = end_state.canonical_root();
+    }
+    let end_block_root = end_block_header.canonical_root();
+
+    info!("Loading blocks up to slot {}", end_slot);
+    let block_roots = HotColdDB::forwards_block_roots_iterator(
+        store.clone(),
+        start_slot,
+        end_state,
+        end_block_root,
+        &env.eth2_config().spec,
+    )
+    .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?;
+
+    let mut blocks = vec![];
+    let mut previous_root = None;
+    for result in block_roots {
+        let (root, slot) = result.map_err(|e| format!("Unable to read block root: {:?}", e))?;
+        if slot > end_slot {
+            break;
+        }
+
+        // The block at the start slot is already applied to the pre-state, and skipped slots
+        // repeat the root of the previous block.
+        if slot > start_slot && previous_root != Some(root) {
+            let block = store
+                .get_block(&root)
+                .map_err(|e| format!("Unable to load block {:?}: {:?}", root, e))?
+                .ok_or_else(|| format!("Missing block {:?} at slot {}", root, slot))?;
+            blocks.push(block);
+        }
+        previous_root = Some(root);
+    }
+
+    Ok((pre_state, blocks))
+}
+
+pub fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000.0
+}
@@ -1,18 +1,30 @@
+//! Advance a state through some number of skip slots, optionally repeating the transition to
+//! profile it.
+//!
+//! The pre-state is read from an SSZ file, or downloaded from a beacon node API. The time taken by
+//! each stage of the transition is printed as CSV for every run.
+use crate::replay_states::{as_millis, parse_caches};
 use crate::transition_blocks::load_from_ssz;
 use clap::ArgMatches;
+use environment::Environment;
+use eth2::{types::StateId, BeaconNodeHttpClient, Url};
 use ssz::Encode;
 use state_processing::per_slot_processing;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio_compat_02::FutureExt;
 use types::{BeaconState, EthSpec};
 
-pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
-    let pre_state_path = matches
-        .value_of("pre-state")
-        .ok_or_else(|| "No pre-state file supplied".to_string())?
-        .parse::<PathBuf>()
-        .map_err(|e| format!("Failed to parse pre-state path: {}", e))?;
+pub fn run<T: EthSpec>(env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let spec = &env.eth2_config().spec.clone();
+    let runs: usize = clap_utils::parse_required(matches, "runs")?;
+    let caches = parse_caches(
+        matches
+            .value_of("caches")
+            .ok_or_else(|| "No caches supplied".to_string())?,
+    )?;
 
     let slots = matches
         .value_of("slots")
@@ -27,29 +39,109 @@ pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
         .map_err(|e| format!("Failed to parse output path: {}", e))?;
 
     info!("Using {} spec", T::spec_name());
-    info!("Pre-state path: {:?}", pre_state_path);
     info!("Slots: {:?}", slots);
 
-    let mut state: BeaconState<T> = load_from_ssz(pre_state_path)?;
-
-    let spec = &T::default_spec();
+    let mut pre_state: BeaconState<T> = if let Some(server_url) = matches.value_of("beacon-url") {
+        let state_id: StateId = clap_utils::parse_required(matches, "pre-state")?;
+        info!("Beacon node: {}", server_url);
+        info!("State: {}", state_id);
+        load_from_api(&env, server_url, state_id)?
+    } else {
+        let pre_state_path = matches
+            .value_of("pre-state")
+            .ok_or_else(|| "No pre-state file supplied".to_string())?
+            .parse::<PathBuf>()
+            .map_err(|e| format!("Failed to parse pre-state path: {}", e))?;
+        info!("Pre-state path: {:?}", pre_state_path);
+        load_from_ssz(pre_state_path)?
+    };
 
-    state
+    pre_state
         .build_all_caches(spec)
         .map_err(|e| format!("Unable to build caches: {:?}", e))?;
 
-    // Transition the parent state to the block slot.
-    for i in 0..slots {
-        per_slot_processing(&mut state, None, spec)
-            .map_err(|e| format!("Failed to advance slot on iteration {}: {:?}", i, e))?;
+    println!("run,caches_ms,slots_ms,mean_slot_ms,state_root_ms,total_ms");
+
+    let mut state = None;
+    let mut total = Duration::default();
+    for run in 0..runs {
+        let mut run_state = pre_state.clone_with(caches);
+
+        let timer = Instant::now();
+        run_state
+            .build_all_caches(spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+        let caches_time = timer.elapsed();
+
+        // Transition the parent state to the block slot.
+        let timer = Instant::now();
+        for i in 0..slots {
+            per_slot_processing(&mut run_state, None, spec)
+                .map_err(|e| format!("Failed to advance slot on iteration {}: {:?}", i, e))?;
+        }
+        let slots_time = timer.elapsed();
+
+        let timer = Instant::now();
+        run_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+        let state_root_time = timer.elapsed();
+
+        let run_total = caches_time + slots_time + state_root_time;
+        total += run_total;
+
+        println!(
+            "{},{},{},{},{},{}",
+            run,
+            as_millis(caches_time),
+            as_millis(slots_time),
+            as_millis(slots_time) / std::cmp::max(slots, 1) as f64,
+            as_millis(state_root_time),
+            as_millis(run_total)
+        );
+        state = Some(run_state);
+    }
+
+    if runs > 0 {
+        info!(
+            "Mean time to skip {} slots over {} runs: {:.3}ms",
+            slots,
+            runs,
+            as_millis(total) / runs as f64
+        );
     }
 
-    let mut output_file =
-        File::create(output_path).map_err(|e| format!("Unable to create output file: {:?}", e))?;
+    if let Some(state) = state {
+        let mut output_file = File::create(output_path)
+            .map_err(|e| format!("Unable to create output file: {:?}", e))?;
 
-    output_file
-        .write_all(&state.as_ssz_bytes())
-        .map_err(|e| format!("Unable to write to output file: {:?}", e))?;
+        output_file
+            .write_all(&state.as_ssz_bytes())
+            .map_err(|e| format!("Unable to write to output file: {:?}", e))?;
+    }
 
     Ok(())
 }
+
+/// Download the state identified by `state_id`.
+fn load_from_api<T: EthSpec>(
+    env: &Environment<T>,
+    server_url: &str,
+    state_id: StateId,
+) -> Result<BeaconState<T>, String> {
+    let client = BeaconNodeHttpClient::new(
+        Url::parse(server_url).map_err(|e| format!("Failed to parse beacon url: {:?}", e))?,
+    );
+
+    env.runtime().block_on(
+        async {
+            Ok(client
+                .get_debug_beacon_states::<T>(state_id)
+                .await
+                .map_err(|e| format!("Failed to download state: {:?}", e))?
+                .ok_or_else(|| format!("No state {}", state_id))?
+                .data)
+        }
+        .compat(),
+    )
+}
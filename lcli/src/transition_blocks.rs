@@ -1,23 +1,40 @@
+//! Apply a block to a pre-state, optionally repeating the transition to profile it.
+//!
+//! The pre-state and block are read from SSZ files, or downloaded from a beacon node API by their
+//! IDs. The time taken by each stage of the transition is printed as CSV for every run.
+use crate::replay_states::{as_millis, parse_caches};
 use clap::ArgMatches;
+use environment::Environment;
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient, Url,
+};
 use ssz::{Decode, Encode};
 use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::PathBuf;
-use types::{BeaconState, EthSpec, SignedBeaconBlock};
-
-pub fn run_transition_blocks<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
-    let pre_state_path = matches
-        .value_of("pre-state")
-        .ok_or_else(|| "No pre-state file supplied".to_string())?
-        .parse::<PathBuf>()
-        .map_err(|e| format!("Failed to parse pre-state path: {}", e))?;
-
-    let block_path = matches
-        .value_of("block")
-        .ok_or_else(|| "No block file supplied".to_string())?
-        .parse::<PathBuf>()
-        .map_err(|e| format!("Failed to parse block path: {}", e))?;
+use std::time::{Duration, Instant};
+use tokio_compat_02::FutureExt;
+use types::{BeaconState, ChainSpec, CloneConfig, EthSpec, SignedBeaconBlock};
+
+pub fn run_transition_blocks<T: EthSpec>(
+    env: Environment<T>,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let spec = env.eth2_config().spec.clone();
+    let runs: usize = clap_utils::parse_required(matches, "runs")?;
+    let caches = parse_caches(
+        matches
+            .value_of("caches")
+            .ok_or_else(|| "No caches supplied".to_string())?,
+    )?;
+    let signature_strategy = match matches.value_of("signature-strategy") {
+        Some("none") => BlockSignatureStrategy::NoVerification,
+        Some("individual") => BlockSignatureStrategy::VerifyIndividual,
+        Some("bulk") => BlockSignatureStrategy::VerifyBulk,
+        _ => return Err("Invalid signature strategy".to_string()),
+    };
 
     let output_path = matches
         .value_of("output")
@@ -26,54 +43,175 @@ pub fn run_transition_blocks<T: EthSpec>(matches: &ArgMatches) -> Result<(), Str
         .map_err(|e| format!("Failed to parse output path: {}", e))?;
 
     info!("Using {} spec", T::spec_name());
-    info!("Pre-state path: {:?}", pre_state_path);
-    info!("Block path: {:?}", block_path);
 
-    let pre_state: BeaconState<T> = load_from_ssz(pre_state_path)?;
-    let block: SignedBeaconBlock<T> = load_from_ssz(block_path)?;
+    let (pre_state, block) = if let Some(server_url) = matches.value_of("beacon-url") {
+        let state_id: StateId = clap_utils::parse_required(matches, "pre-state")?;
+        let block_id: BlockId = clap_utils::parse_required(matches, "block")?;
+        info!("Beacon node: {}", server_url);
+        info!("Pre-state: {}", state_id);
+        info!("Block: {}", block_id);
+        load_from_api::<T>(&env, server_url, state_id, block_id)?
+    } else {
+        let pre_state_path = matches
+            .value_of("pre-state")
+            .ok_or_else(|| "No pre-state file supplied".to_string())?
+            .parse::<PathBuf>()
+            .map_err(|e| format!("Failed to parse pre-state path: {}", e))?;
+
+        let block_path = matches
+            .value_of("block")
+            .ok_or_else(|| "No block file supplied".to_string())?
+            .parse::<PathBuf>()
+            .map_err(|e| format!("Failed to parse block path: {}", e))?;
+
+        info!("Pre-state path: {:?}", pre_state_path);
+        info!("Block path: {:?}", block_path);
+
+        (load_from_ssz(pre_state_path)?, load_from_ssz(block_path)?)
+    };
+
+    let mut pre_state: BeaconState<T> = pre_state;
+    pre_state
+        .build_all_caches(&spec)
+        .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+
+    println!("run,slots_ms,caches_ms,block_ms,state_root_ms,total_ms");
 
-    let post_state = do_transition(pre_state, block)?;
+    let mut post_state = None;
+    let mut total = Duration::default();
+    for run in 0..runs {
+        let (state, timings) =
+            do_transition(&pre_state, &block, caches, signature_strategy, &spec)?;
+        total += timings.total();
+        println!("{},{}", run, timings.to_csv());
+        post_state = Some(state);
+    }
+
+    if runs > 0 {
+        info!(
+            "Mean transition time over {} runs: {:.3}ms",
+            runs,
+            as_millis(total) / runs as f64
+        );
+    }
 
-    let mut output_file =
-        File::create(output_path).map_err(|e| format!("Unable to create output file: {:?}", e))?;
+    if let Some(post_state) = post_state {
+        let mut output_file = File::create(output_path)
+            .map_err(|e| format!("Unable to create output file: {:?}", e))?;
 
-    output_file
-        .write_all(&post_state.as_ssz_bytes())
-        .map_err(|e| format!("Unable to write to output file: {:?}", e))?;
+        output_file
+            .write_all(&post_state.as_ssz_bytes())
+            .map_err(|e| format!("Unable to write to output file: {:?}", e))?;
+    }
 
     Ok(())
 }
 
-fn do_transition<T: EthSpec>(
-    mut pre_state: BeaconState<T>,
-    block: SignedBeaconBlock<T>,
-) -> Result<BeaconState<T>, String> {
-    let spec = &T::default_spec();
+/// The time taken by each stage of a state transition.
+#[derive(Default)]
+pub struct Timings {
+    pub slots: Duration,
+    pub caches: Duration,
+    pub block: Duration,
+    pub state_root: Duration,
+}
 
-    pre_state
-        .build_all_caches(spec)
-        .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.slots + self.caches + self.block + self.state_root
+    }
+
+    /// The timings in milliseconds, followed by their total.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            as_millis(self.slots),
+            as_millis(self.caches),
+            as_millis(self.block),
+            as_millis(self.state_root),
+            as_millis(self.total())
+        )
+    }
+}
+
+/// Apply `block` to a copy of `pre_state` which retains only the `caches`, timing each stage.
+fn do_transition<T: EthSpec>(
+    pre_state: &BeaconState<T>,
+    block: &SignedBeaconBlock<T>,
+    caches: CloneConfig,
+    signature_strategy: BlockSignatureStrategy,
+    spec: &ChainSpec,
+) -> Result<(BeaconState<T>, Timings), String> {
+    let mut state = pre_state.clone_with(caches);
+    let mut timings = Timings::default();
 
     // Transition the parent state to the block slot.
-    for i in pre_state.slot.as_u64()..block.slot().as_u64() {
-        per_slot_processing(&mut pre_state, None, spec)
+    let timer = Instant::now();
+    for i in state.slot.as_u64()..block.slot().as_u64() {
+        per_slot_processing(&mut state, None, spec)
             .map_err(|e| format!("Failed to advance slot on iteration {}: {:?}", i, e))?;
     }
+    timings.slots = timer.elapsed();
 
-    pre_state
+    let timer = Instant::now();
+    state
         .build_all_caches(spec)
         .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+    timings.caches = timer.elapsed();
+
+    let timer = Instant::now();
+    per_block_processing(&mut state, block, None, signature_strategy, spec)
+        .map_err(|e| format!("State transition failed: {:?}", e))?;
+    timings.block = timer.elapsed();
+
+    let timer = Instant::now();
+    let state_root = state
+        .update_tree_hash_cache()
+        .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+    timings.state_root = timer.elapsed();
+
+    if state_root != block.state_root() {
+        warn!(
+            "State root mismatch: block has {:?}, post-state has {:?}",
+            block.state_root(),
+            state_root
+        );
+    }
 
-    per_block_processing(
-        &mut pre_state,
-        &block,
-        None,
-        BlockSignatureStrategy::VerifyIndividual,
-        spec,
-    )
-    .map_err(|e| format!("State transition failed: {:?}", e))?;
+    Ok((state, timings))
+}
 
-    Ok(pre_state)
+/// Download the state identified by `state_id` and the block identified by `block_id`.
+fn load_from_api<T: EthSpec>(
+    env: &Environment<T>,
+    server_url: &str,
+    state_id: StateId,
+    block_id: BlockId,
+) -> Result<(BeaconState<T>, SignedBeaconBlock<T>), String> {
+    let client = BeaconNodeHttpClient::new(
+        Url::parse(server_url).map_err(|e| format!("Failed to parse beacon url: {:?}", e))?,
+    );
+
+    env.runtime().block_on(
+        async {
+            let pre_state = client
+                .get_debug_beacon_states::<T>(state_id)
+                .await
+                .map_err(|e| format!("Failed to download pre-state: {:?}", e))?
+                .ok_or_else(|| format!("No state {}", state_id))?
+                .data;
+
+            let block = client
+                .get_beacon_blocks::<T>(block_id)
+                .await
+                .map_err(|e| format!("Failed to download block: {:?}", e))?
+                .ok_or_else(|| format!("No block {}", block_id))?
+                .data;
+
+            Ok((pre_state, block))
+        }
+        .compat(),
+    )
 }
 
 pub fn load_from_ssz<T: Decode>(path: PathBuf) -> Result<T, String> {
@@ -0,0 +1,223 @@
+//! Loads the values of CLI flags from a TOML or YAML file supplied with `--config-file`.
+//!
+//! The file maps the long names of flags to their values, e.g.:
+//!
+//! ```toml
+//! datadir = "/var/lib/lighthouse"
+//! http = true
+//! http-port = 5052
+//! boot-nodes = ["enr:-abc", "enr:-def"]
+//! ```
+//!
+//! These values are turned into CLI arguments and appended to those supplied by the user, for
+//! every flag which was not already supplied.
+use crate::env_vars::{flag_kind, FlagKind};
+use clap::App;
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const CONFIG_FILE_FLAG: &str = "config-file";
+
+/// The subcommands which accept a `--config-file`.
+pub const CONFIG_FILE_SUBCOMMANDS: &[&str] = &["beacon_node", "validator_client", "boot_node"];
+
+/// The value of a single flag in a config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigValue {
+    /// For a flag which takes no value, `true` supplies it and `false` omits it.
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    /// Supplied as a single comma-separated value.
+    List(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    /// Returns the CLI arguments which supply this value for the flag `name` of the given `kind`.
+    fn to_args(&self, name: &str, kind: FlagKind) -> Result<Vec<String>, String> {
+        match (kind, self) {
+            (FlagKind::Switch, ConfigValue::Bool(true)) => Ok(vec![format!("--{}", name)]),
+            (FlagKind::Switch, ConfigValue::Bool(false)) => Ok(vec![]),
+            (FlagKind::Switch, _) => Err(format!("{} must be true or false", name)),
+            (FlagKind::Value, value) => Ok(vec![format!("--{}={}", name, value.to_value(name)?)]),
+            (FlagKind::Unknown, _) => Err(format!("{} is not a flag of the subcommand", name)),
+        }
+    }
+
+    fn to_value(&self, name: &str) -> Result<String, String> {
+        match self {
+            ConfigValue::Bool(b) => Ok(b.to_string()),
+            ConfigValue::Integer(i) => Ok(i.to_string()),
+            ConfigValue::Float(f) => Ok(f.to_string()),
+            ConfigValue::String(s) => Ok(s.clone()),
+            ConfigValue::List(list) => list
+                .iter()
+                .map(|value| match value {
+                    ConfigValue::List(_) => Err(format!("{} cannot contain nested lists", name)),
+                    value => value.to_value(name),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|values| values.join(",")),
+        }
+    }
+}
+
+/// Reads the flags of a config file, as YAML if its extension is `.yaml` or `.yml`, otherwise as
+/// TOML.
+pub fn load_config_file(path: &Path) -> Result<BTreeMap<String, ConfigValue>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read config file {:?}: {:?}", path, e))?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)
+            .map_err(|e| format!("Unable to parse YAML config file {:?}: {}", path, e)),
+        _ => toml::from_str(&contents)
+            .map_err(|e| format!("Unable to parse TOML config file {:?}: {}", path, e)),
+    }
+}
+
+/// Returns the CLI arguments which supply the flags of `config` to the nested `subcommands` of
+/// `app`, skipping those for which `is_supplied` returns `true`.
+pub fn config_file_args<F>(
+    config: &BTreeMap<String, ConfigValue>,
+    app: &App,
+    subcommands: &[&str],
+    is_supplied: F,
+) -> Result<Vec<String>, String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut args = vec![];
+    for (name, value) in config {
+        if name == CONFIG_FILE_FLAG {
+            return Err(format!(
+                "A config file cannot contain --{}",
+                CONFIG_FILE_FLAG
+            ));
+        }
+        if !is_supplied(name) {
+            args.extend(value.to_args(name, flag_kind(app, subcommands, name))?);
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, SubCommand};
+
+    const TOML: &str = r#"
+        datadir = "/tmp/lighthouse"
+        http = true
+        staking = false
+        http-port = 5052
+        target-peers = 5.5
+        boot-nodes = ["enr:-a", "enr:-b"]
+        slasher-broadcast = false
+    "#;
+
+    const YAML: &str = r#"
+        datadir: /tmp/lighthouse
+        http: true
+        staking: false
+        http-port: 5052
+        target-peers: 5.5
+        boot-nodes:
+          - enr:-a
+          - enr:-b
+        slasher-broadcast: false
+    "#;
+
+    fn value(name: &'static str) -> Arg<'static, 'static> {
+        Arg::with_name(name).long(name).takes_value(true)
+    }
+
+    fn app() -> App<'static, 'static> {
+        App::new("lighthouse")
+            .arg(value("logfile"))
+            .arg(value("datadir").global(true))
+            .arg(value("config-file").global(true))
+            .subcommand(
+                SubCommand::with_name("beacon_node")
+                    .arg(Arg::with_name("http").long("http"))
+                    .arg(Arg::with_name("staking").long("staking"))
+                    .arg(value("http-port"))
+                    .arg(value("target-peers"))
+                    .arg(value("boot-nodes"))
+                    .arg(value("slasher-broadcast")),
+            )
+    }
+
+    fn args(config: &BTreeMap<String, ConfigValue>) -> Result<Vec<String>, String> {
+        config_file_args(config, &app(), &["beacon_node"], |_| false)
+    }
+
+    fn expected_args() -> Vec<String> {
+        vec![
+            "--boot-nodes=enr:-a,enr:-b",
+            "--datadir=/tmp/lighthouse",
+            "--http",
+            "--http-port=5052",
+            "--slasher-broadcast=false",
+            "--target-peers=5.5",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+
+    #[test]
+    fn toml_and_yaml_args() {
+        let toml: BTreeMap<String, ConfigValue> = toml::from_str(TOML).unwrap();
+        let yaml: BTreeMap<String, ConfigValue> = serde_yaml::from_str(YAML).unwrap();
+
+        assert_eq!(toml, yaml);
+        assert_eq!(args(&toml), Ok(expected_args()));
+    }
+
+    #[test]
+    fn supplied_flags_are_skipped() {
+        let config: BTreeMap<String, ConfigValue> = toml::from_str(TOML).unwrap();
+        let args = config_file_args(&config, &app(), &["beacon_node"], |name| {
+            name == "datadir" || name == "http"
+        })
+        .unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "--boot-nodes=enr:-a,enr:-b",
+                "--http-port=5052",
+                "--slasher-broadcast=false",
+                "--target-peers=5.5"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let nested: BTreeMap<String, ConfigValue> =
+            toml::from_str("boot-nodes = [[\"enr:-a\"]]").unwrap();
+        assert!(args(&nested).is_err());
+
+        let recursive: BTreeMap<String, ConfigValue> =
+            toml::from_str("config-file = \"other.toml\"").unwrap();
+        assert!(args(&recursive).is_err());
+
+        let invalid_switch: BTreeMap<String, ConfigValue> = toml::from_str("http = 1").unwrap();
+        assert!(args(&invalid_switch).is_err());
+
+        let top_level: BTreeMap<String, ConfigValue> =
+            toml::from_str("logfile = \"/tmp/lighthouse.log\"").unwrap();
+        assert!(args(&top_level).is_err());
+
+        assert!(
+            toml::from_str::<BTreeMap<String, ConfigValue>>("[beacon_node]\nhttp = true").is_err()
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Supplies CLI flags from `LIGHTHOUSE_*` environment variables.
+//!
+//! The name of the variable for a flag is its long name in upper case, with `-` replaced by `_`
+//! and prefixed with `LIGHTHOUSE_`, e.g. `LIGHTHOUSE_HTTP_PORT=5052` for `--http-port 5052`. A
+//! flag which takes no value is supplied by setting its variable to `true`.
+use clap::{App, ErrorKind};
+use std::ffi::OsString;
+use std::iter;
+
+pub const ENV_VAR_PREFIX: &str = "LIGHTHOUSE_";
+
+/// A flag supplied by an environment variable.
+#[derive(Debug, PartialEq)]
+pub struct EnvFlag {
+    /// The long name of the flag.
+    pub name: String,
+    /// The CLI arguments which supply the flag, empty if the flag is disabled.
+    pub args: Vec<String>,
+    /// Whether the flag belongs to the top-level command rather than the subcommand, in which
+    /// case its arguments must precede the subcommand.
+    pub top_level: bool,
+}
+
+/// The kind of flag named by an environment variable or config file key.
+#[derive(Debug, PartialEq)]
+pub(crate) enum FlagKind {
+    /// A flag which takes no value.
+    Switch,
+    /// A flag which takes a value.
+    Value,
+    /// Not a flag of the subcommand being run.
+    Unknown,
+}
+
+/// Returns the name of the environment variable for the flag `name`.
+pub fn env_var_name(name: &str) -> String {
+    format!(
+        "{}{}",
+        ENV_VAR_PREFIX,
+        name.to_uppercase().replace('-', "_")
+    )
+}
+
+/// Returns the name of the flag for the environment variable `var`, if it has the
+/// `LIGHTHOUSE_` prefix.
+pub fn flag_name(var: &str) -> Option<String> {
+    if var.starts_with(ENV_VAR_PREFIX) && var.len() > ENV_VAR_PREFIX.len() {
+        Some(var[ENV_VAR_PREFIX.len()..].to_lowercase().replace('_', "-"))
+    } else {
+        None
+    }
+}
+
+/// Returns the flags supplied by the `LIGHTHOUSE_*` variables of `vars` for the nested
+/// `subcommands` of `app`, skipping those for which `is_supplied` returns `true`.
+///
+/// Variables which don't name a flag of the subcommand or the top-level command are ignored, so
+/// that the same environment can be used to run several subcommands.
+pub fn env_flags<V, F>(
+    app: &App,
+    subcommands: &[&str],
+    vars: V,
+    is_supplied: F,
+) -> Result<Vec<EnvFlag>, String>
+where
+    V: IntoIterator<Item = (OsString, OsString)>,
+    F: Fn(&str) -> bool,
+{
+    let mut flags = vec![];
+    for (var, value) in vars {
+        let var = match var.into_string() {
+            Ok(var) => var,
+            Err(_) => continue,
+        };
+        let name = match flag_name(&var) {
+            Some(name) if !is_supplied(&name) => name,
+            _ => continue,
+        };
+        let value = value
+            .into_string()
+            .map_err(|_| format!("{} is not valid unicode", var))?;
+
+        let (kind, top_level) = match flag_kind(app, subcommands, &name) {
+            FlagKind::Unknown if !subcommands.is_empty() => (flag_kind(app, &[], &name), true),
+            kind => (kind, false),
+        };
+        let args = match kind {
+            FlagKind::Switch => match value.to_lowercase().as_str() {
+                "true" => vec![format!("--{}", name)],
+                "false" => vec![],
+                _ => return Err(format!("{} must be true or false", var)),
+            },
+            FlagKind::Value => vec![format!("--{}={}", name, value)],
+            FlagKind::Unknown => continue,
+        };
+        flags.push(EnvFlag {
+            name,
+            args,
+            top_level,
+        });
+    }
+
+    // Environment variables are unordered, so sort them for consistent error messages.
+    flags.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(flags)
+}
+
+/// Determines the kind of the flag `name` by supplying it to the subcommand without a value.
+pub(crate) fn flag_kind(app: &App, subcommands: &[&str], name: &str) -> FlagKind {
+    let flag = format!("--{}", name);
+    let args = iter::once(app.get_name())
+        .chain(subcommands.iter().copied())
+        .chain(iter::once(flag.as_str()));
+
+    match app.clone().get_matches_from_safe(args) {
+        Err(e) if e.kind == ErrorKind::EmptyValue => FlagKind::Value,
+        Err(e)
+            if e.kind == ErrorKind::UnknownArgument
+                || e.kind == ErrorKind::HelpDisplayed
+                || e.kind == ErrorKind::VersionDisplayed =>
+        {
+            FlagKind::Unknown
+        }
+        _ => FlagKind::Switch,
+    }
+}
+
+/// Returns the name by which clap knows the flag with the long name `long`, which may differ from
+/// it (e.g. `enr-udp-port` for `--enr-port`), or `long` itself if the flag is unknown.
+///
+/// For a flag which takes a value the name is found by supplying it without one, otherwise by
+/// supplying it twice.
+pub fn arg_name(app: &App, subcommands: &[&str], long: &str) -> String {
+    let flag = format!("--{}", long);
+    let args = |count| {
+        iter::once(app.get_name())
+            .chain(subcommands.iter().copied())
+            .chain(iter::repeat(flag.as_str()).take(count))
+    };
+
+    let result = app
+        .clone()
+        .get_matches_from_safe(args(1))
+        .and_then(|_| app.clone().get_matches_from_safe(args(2)));
+    match result {
+        Err(e)
+            if e.kind == ErrorKind::EmptyValue || e.kind == ErrorKind::UnexpectedMultipleUsage =>
+        {
+            e.info
+                .and_then(|info| info.into_iter().next())
+                .unwrap_or_else(|| long.to_string())
+        }
+        _ if !subcommands.is_empty() => arg_name(app, &[], long),
+        _ => long.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, SubCommand};
+
+    fn app() -> App<'static, 'static> {
+        App::new("lighthouse")
+            .arg(Arg::with_name("logfile").long("logfile").takes_value(true))
+            .arg(
+                Arg::with_name("datadir")
+                    .long("datadir")
+                    .takes_value(true)
+                    .global(true),
+            )
+            .subcommand(
+                SubCommand::with_name("beacon_node")
+                    .arg(Arg::with_name("http").long("http"))
+                    .arg(
+                        Arg::with_name("http-port")
+                            .long("http-port")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("enr-udp-port")
+                            .long("enr-port")
+                            .takes_value(true),
+                    )
+                    .arg(Arg::with_name("auto-update").long("enable-auto-update")),
+            )
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        vars.iter()
+            .map(|(var, value)| (var.into(), value.into()))
+            .collect()
+    }
+
+    fn flag(name: &str, args: &[&str], top_level: bool) -> EnvFlag {
+        EnvFlag {
+            name: name.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            top_level,
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(env_var_name("http-port"), "LIGHTHOUSE_HTTP_PORT");
+        assert_eq!(
+            flag_name("LIGHTHOUSE_HTTP_PORT"),
+            Some("http-port".to_string())
+        );
+        assert_eq!(flag_name("LIGHTHOUSE_"), None);
+        assert_eq!(flag_name("HTTP_PORT"), None);
+    }
+
+    #[test]
+    fn flags_from_vars() {
+        let vars = vars(&[
+            ("LIGHTHOUSE_HTTP_PORT", "5052"),
+            ("LIGHTHOUSE_HTTP", "true"),
+            ("LIGHTHOUSE_DATADIR", "/tmp/lighthouse"),
+            ("LIGHTHOUSE_LOGFILE", "/tmp/lighthouse.log"),
+            ("LIGHTHOUSE_GRAFFITI", "unknown"),
+            ("HOME", "/root"),
+        ]);
+
+        assert_eq!(
+            env_flags(&app(), &["beacon_node"], vars, |_| false),
+            Ok(vec![
+                flag("datadir", &["--datadir=/tmp/lighthouse"], false),
+                flag("http", &["--http"], false),
+                flag("http-port", &["--http-port=5052"], false),
+                flag("logfile", &["--logfile=/tmp/lighthouse.log"], true),
+            ])
+        );
+    }
+
+    #[test]
+    fn supplied_and_disabled_flags() {
+        let vars = vars(&[
+            ("LIGHTHOUSE_HTTP_PORT", "5052"),
+            ("LIGHTHOUSE_HTTP", "false"),
+        ]);
+
+        assert_eq!(
+            env_flags(&app(), &["beacon_node"], vars, |name| name == "http-port"),
+            Ok(vec![flag("http", &[], false)])
+        );
+    }
+
+    #[test]
+    fn invalid_switch() {
+        let vars = vars(&[("LIGHTHOUSE_HTTP", "1")]);
+
+        assert!(env_flags(&app(), &["beacon_node"], vars, |_| false).is_err());
+    }
+
+    #[test]
+    fn arg_names() {
+        let app = app();
+        let subcommands = &["beacon_node"];
+
+        assert_eq!(arg_name(&app, subcommands, "http-port"), "http-port");
+        assert_eq!(arg_name(&app, subcommands, "enr-port"), "enr-udp-port");
+        assert_eq!(
+            arg_name(&app, subcommands, "enable-auto-update"),
+            "auto-update"
+        );
+        assert_eq!(arg_name(&app, subcommands, "logfile"), "logfile");
+        assert_eq!(arg_name(&app, subcommands, "graffiti"), "graffiti");
+    }
+}
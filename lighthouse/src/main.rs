@@ -1,10 +1,16 @@
+mod config_file;
+mod env_vars;
+
 use beacon_node::ProductionBeaconNode;
 use clap::{App, Arg, ArgMatches};
+use config_file::{CONFIG_FILE_FLAG, CONFIG_FILE_SUBCOMMANDS};
 use env_logger::{Builder, Env};
+use env_vars::ENV_VAR_PREFIX;
 use environment::EnvironmentBuilder;
 use eth2_testnet_config::{Eth2TestnetConfig, DEFAULT_HARDCODED_TESTNET};
 use lighthouse_version::VERSION;
 use slog::{crit, info, warn};
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::process::exit;
 use tokio_compat_02::FutureExt;
@@ -26,22 +32,19 @@ fn bls_library_name() -> &'static str {
 }
 
 fn main() {
+    let version = VERSION.replace("Lighthouse/", "");
+    let long_version = format!("{}\nBLS Library: {}", version, bls_library_name());
+
     // Parse the CLI parameters.
-    let matches = App::new("Lighthouse")
-        .version(VERSION.replace("Lighthouse/", "").as_str())
+    let app = App::new("Lighthouse")
+        .version(version.as_str())
         .author("Sigma Prime <contact@sigmaprime.io>")
         .setting(clap::AppSettings::ColoredHelp)
         .about(
             "Ethereum 2.0 client by Sigma Prime. Provides a full-featured beacon \
              node, a validator client and utilities for managing validator accounts.",
         )
-        .long_version(
-            format!(
-                "{}\n\
-                 BLS Library: {}",
-                 VERSION.replace("Lighthouse/", ""), bls_library_name()
-            ).as_str()
-        )
+        .long_version(long_version.as_str())
         .arg(
             Arg::with_name("spec")
                 .short("s")
@@ -123,12 +126,31 @@ fn main() {
                 .global(true)
 
         )
+        .arg(
+            Arg::with_name(CONFIG_FILE_FLAG)
+                .long(CONFIG_FILE_FLAG)
+                .value_name("FILE")
+                .help("Path to a TOML or YAML file of flag values for the beacon_node, \
+                    validator_client or boot_node subcommands. Flags supplied on the command line \
+                    take precedence over those in the file.")
+                .takes_value(true)
+                .global(true)
+        )
         .subcommand(beacon_node::cli_app())
         .subcommand(boot_node::cli_app())
         .subcommand(validator_client::cli_app())
         .subcommand(account_manager::cli_app())
+        .subcommand(database_manager::cli_app())
         .subcommand(remote_signer::cli_app())
-        .get_matches();
+        .subcommand(slasher_service::cli_app());
+
+    let matches = match parse_cli_args(&app) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1)
+        }
+    };
 
     // Debugging output for libp2p and external crates.
     if matches.is_present("env_log") {
@@ -174,6 +196,118 @@ fn main() {
     }
 }
 
+/// Parses the CLI parameters, supplemented by the `LIGHTHOUSE_*` environment variables and then
+/// the `--config-file` for the flags which were not supplied on the command line.
+fn parse_cli_args<'a>(app: &App<'a, '_>) -> Result<ArgMatches<'a>, String> {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    let matches = app.clone().get_matches_from(args.clone());
+
+    let subcommands = subcommand_names(&matches);
+    let config_file_supported = subcommands.first().map_or(false, |subcommand| {
+        CONFIG_FILE_SUBCOMMANDS.contains(subcommand)
+    });
+    let env_flags = env_vars::env_flags(app, &subcommands, std::env::vars_os(), |name| {
+        is_supplied(&matches, &env_vars::arg_name(app, &subcommands, name))
+    })?
+    .into_iter()
+    .filter(|flag| flag.name != CONFIG_FILE_FLAG || config_file_supported)
+    .collect::<Vec<_>>();
+
+    let matches = if env_flags.is_empty() {
+        matches
+    } else {
+        let (top_level, subcommand): (Vec<_>, Vec<_>) =
+            env_flags.iter().partition(|flag| flag.top_level);
+        let top_level_args = top_level
+            .into_iter()
+            .flat_map(|flag| flag.args.iter().map(Into::into));
+        let position = args.len().min(1);
+        args.splice(position..position, top_level_args);
+        args.extend(
+            subcommand
+                .into_iter()
+                .flat_map(|flag| flag.args.iter().map(Into::into)),
+        );
+        app.clone()
+            .get_matches_from_safe(args.clone())
+            .map_err(|e| {
+                format!(
+                    "Invalid {}* environment variable: {}",
+                    ENV_VAR_PREFIX, e.message
+                )
+            })?
+    };
+
+    apply_config_file(app, args, matches, |name| {
+        env_flags.iter().any(|flag| flag.name == name)
+    })
+}
+
+/// If a `--config-file` was supplied, parses `args` again with the flags of the file appended to
+/// them, skipping those supplied in `args` or for which `skip` returns `true`.
+fn apply_config_file<'a, F>(
+    app: &App<'a, '_>,
+    args: Vec<OsString>,
+    matches: ArgMatches<'a>,
+    skip: F,
+) -> Result<ArgMatches<'a>, String>
+where
+    F: Fn(&str) -> bool,
+{
+    let (subcommand, sub_matches) = match matches.subcommand() {
+        (name, Some(sub_matches)) => (name, sub_matches),
+        (_, None) => return Ok(matches),
+    };
+    let path = match clap_utils::parse_optional::<PathBuf>(sub_matches, CONFIG_FILE_FLAG)? {
+        Some(path) => path,
+        None => return Ok(matches),
+    };
+    if !CONFIG_FILE_SUBCOMMANDS.contains(&subcommand) {
+        return Err(format!(
+            "--{} is only supported by the {} subcommands",
+            CONFIG_FILE_FLAG,
+            CONFIG_FILE_SUBCOMMANDS.join(", ")
+        ));
+    }
+
+    let config = config_file::load_config_file(&path)?;
+    let subcommands = subcommand_names(&matches);
+    let file_args = config_file::config_file_args(&config, app, &subcommands, |name| {
+        skip(name) || is_supplied(&matches, &env_vars::arg_name(app, &subcommands, name))
+    })
+    .map_err(|e| format!("Invalid config file {:?}: {}", path, e))?;
+
+    let args = args
+        .into_iter()
+        .chain(file_args.into_iter().map(Into::into));
+    app.clone()
+        .get_matches_from_safe(args)
+        .map_err(|e| format!("Invalid config file {:?}: {}", path, e.message))
+}
+
+/// Returns the names of the nested subcommands in `matches`, outermost first.
+fn subcommand_names<'m>(matches: &'m ArgMatches) -> Vec<&'m str> {
+    let mut names = vec![];
+    let mut matches = matches;
+    while let (name, Some(sub_matches)) = matches.subcommand() {
+        names.push(name);
+        matches = sub_matches;
+    }
+    names
+}
+
+/// Returns `true` if the argument `name` was supplied to the command or any of its subcommands.
+///
+/// Note that `name` is the name of the argument, which may differ from its long flag, see
+/// `env_vars::arg_name`.
+fn is_supplied(matches: &ArgMatches, name: &str) -> bool {
+    matches.occurrences_of(name) > 0
+        || match matches.subcommand() {
+            (_, Some(sub_matches)) => is_supplied(sub_matches, name),
+            (_, None) => false,
+        }
+}
+
 fn load_testnet_config(matches: &ArgMatches) -> Result<Eth2TestnetConfig, String> {
     if matches.is_present("testnet-dir") {
         clap_utils::parse_testnet_dir(matches, "testnet-dir")?
@@ -264,6 +398,21 @@ fn run<E: EthSpec>(
         return Ok(());
     };
 
+    if let Some(sub_matches) = matches.subcommand_matches("database_manager") {
+        database_manager::run(sub_matches, environment)?;
+
+        // Exit as soon as database manager returns control.
+        return Ok(());
+    };
+
+    if let Some(sub_matches) = matches.subcommand_matches(slasher_service::CMD) {
+        // Exit as soon as a one-off subcommand of the slasher returns control, only the slasher
+        // itself runs until shutdown.
+        if slasher_service::run_subcommand(&mut environment, sub_matches)? {
+            return Ok(());
+        }
+    };
+
     info!(log, "Lighthouse started"; "version" => VERSION);
     info!(
         log,
@@ -332,6 +481,16 @@ fn run<E: EthSpec>(
                     .try_send("Failed to start remote signer");
             }
         }
+        ("slasher", Some(matches)) => {
+            if let Err(e) = slasher_service::run(&mut environment, matches) {
+                crit!(log, "Failed to start slasher"; "reason" => e);
+                let _ = environment
+                    .core_context()
+                    .executor
+                    .shutdown_sender()
+                    .try_send("Failed to start slasher");
+            }
+        }
         _ => {
             crit!(log, "No subcommand supplied. See --help .");
             return Err("No subcommand supplied.".into());
@@ -3,7 +3,9 @@
 use account_manager::{
     validator::{
         create::*,
+        deposit_data::{read_deposit_data_file, DepositDataJson},
         import::{self, CMD as IMPORT_CMD},
+        recover::{self, CMD as RECOVER_CMD, MNEMONIC_FLAG as MNEMONIC_PATH_FLAG},
         CMD as VALIDATOR_CMD,
     },
     wallet::{
@@ -404,6 +406,221 @@ fn validator_create() {
     assert_eq!(dir_validator_count(validator_dir.path()), 6);
 }
 
+/// Create the validators with the key indices in `index_range` from the mnemonic of `wallet`,
+/// writing their deposits to `deposit_data_dir`.
+fn create_from_mnemonic<P: AsRef<Path>>(
+    wallet: &TestWallet,
+    validator_dir: P,
+    secrets_dir: P,
+    deposit_data_dir: P,
+    index_range: &str,
+) -> Result<Output, String> {
+    output_result(
+        validator_cmd()
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.as_ref().as_os_str())
+            .arg(CREATE_CMD)
+            .arg(format!("--{}", SECRETS_DIR_FLAG))
+            .arg(secrets_dir.as_ref().as_os_str())
+            .arg(format!("--{}", FROM_MNEMONIC_FLAG))
+            .arg(format!("--{}", INDEX_RANGE_FLAG))
+            .arg(index_range)
+            .arg(format!("--{}", MNEMONIC_PATH_FLAG))
+            .arg(wallet.mnemonic_path().into_os_string())
+            .arg(format!("--{}", DEPOSIT_DATA_DIR_FLAG))
+            .arg(deposit_data_dir.as_ref().as_os_str()),
+    )
+}
+
+/// Returns the deposits of the single `deposit_data-*.json` file in `dir`, if any.
+fn read_deposit_data_dir<P: AsRef<Path>>(dir: P) -> Option<Vec<DepositDataJson>> {
+    let paths = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("deposit_data-")
+        })
+        .collect::<Vec<_>>();
+    assert!(paths.len() <= 1, "at most one deposit data file");
+    paths
+        .first()
+        .map(|path| read_deposit_data_file(path).unwrap())
+}
+
+#[test]
+fn validator_create_from_mnemonic() {
+    let base_dir = tempdir().unwrap();
+    let validator_dir = tempdir().unwrap();
+    let secrets_dir = tempdir().unwrap();
+
+    let wallet = TestWallet::new(base_dir.path(), "wally");
+    wallet.create_expect_success();
+
+    // Create the validators with indices 1 and 2.
+    let deposit_data_dir = tempdir().unwrap();
+    let output = create_from_mnemonic(
+        &wallet,
+        validator_dir.path(),
+        secrets_dir.path(),
+        deposit_data_dir.path(),
+        "1..3",
+    )
+    .unwrap();
+    let stdout = from_utf8(&output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("\tIndex: 1\t"));
+    assert!(stdout.contains("\tIndex: 2\t"));
+
+    assert_eq!(dir_validator_count(validator_dir.path()), 2);
+    let deposits = read_deposit_data_dir(deposit_data_dir.path()).unwrap();
+    assert_eq!(deposits.len(), 2);
+    for deposit in &deposits {
+        let pubkey = format!("0x{}", deposit.pubkey);
+        assert!(stdout.contains(&pubkey));
+        assert!(validator_dir.path().join(&pubkey).exists());
+        assert!(secrets_dir.path().join(&pubkey).exists());
+    }
+    check_slashing_protection(
+        &validator_dir,
+        deposits
+            .iter()
+            .map(|deposit| format!("0x{}", deposit.pubkey).parse().unwrap()),
+    );
+
+    // Empty and malformed ranges are rejected without creating anything.
+    for index_range in &["3..3", "3..1", "3", "a..b"] {
+        let deposit_data_dir = tempdir().unwrap();
+        create_from_mnemonic(
+            &wallet,
+            validator_dir.path(),
+            secrets_dir.path(),
+            deposit_data_dir.path(),
+            index_range,
+        )
+        .unwrap_err();
+        assert_eq!(read_deposit_data_dir(deposit_data_dir.path()), None);
+    }
+    assert_eq!(dir_validator_count(validator_dir.path()), 2);
+
+    // The validator with index 1 already exists, so only the one with index 0 is created, and its
+    // deposit is still written.
+    let deposit_data_dir = tempdir().unwrap();
+    let err = create_from_mnemonic(
+        &wallet,
+        validator_dir.path(),
+        secrets_dir.path(),
+        deposit_data_dir.path(),
+        "0..3",
+    )
+    .unwrap_err();
+    assert!(err.contains("Created 1 of 3 validators"), "{}", err);
+
+    assert_eq!(dir_validator_count(validator_dir.path()), 3);
+    let partial_deposits = read_deposit_data_dir(deposit_data_dir.path()).unwrap();
+    assert_eq!(partial_deposits.len(), 1);
+    assert!(!deposits.contains(&partial_deposits[0]));
+    assert!(validator_dir
+        .path()
+        .join(format!("0x{}", partial_deposits[0].pubkey))
+        .exists());
+}
+
+/// Recover `count` validators from the mnemonic of `wallet`, starting at `first_index`, entering
+/// `confirmation` when asked to confirm the recovery.
+fn recover_validators<P: AsRef<Path>>(
+    wallet: &TestWallet,
+    validator_dir: P,
+    secrets_dir: P,
+    first_index: u32,
+    count: u32,
+    confirmation: &str,
+) -> Result<Output, String> {
+    let mut child = validator_cmd()
+        .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+        .arg(validator_dir.as_ref().as_os_str())
+        .arg(RECOVER_CMD)
+        .arg(format!("--{}", STDIN_INPUTS_FLAG)) // Using tty does not work well with tests.
+        .arg(format!("--{}", SECRETS_DIR_FLAG))
+        .arg(secrets_dir.as_ref().as_os_str())
+        .arg(format!("--{}", recover::FIRST_INDEX_FLAG))
+        .arg(first_index.to_string())
+        .arg(format!("--{}", COUNT_FLAG))
+        .arg(count.to_string())
+        .arg(format!("--{}", MNEMONIC_PATH_FLAG))
+        .arg(wallet.mnemonic_path().into_os_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The command may exit before reading the confirmation, closing its stdin.
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("{}\n", confirmation).as_bytes());
+
+    let output = child.wait_with_output().unwrap();
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(from_utf8(&output.stderr)
+            .expect("stderr is not utf8")
+            .to_string())
+    }
+}
+
+#[test]
+fn validator_recover() {
+    let base_dir = tempdir().unwrap();
+    let validator_dir = tempdir().unwrap();
+    let secrets_dir = tempdir().unwrap();
+
+    let wallet = TestWallet::new(base_dir.path(), "wally");
+    wallet.create_expect_success();
+
+    // Nothing is recovered without the confirmation phrase.
+    let err = recover_validators(
+        &wallet,
+        validator_dir.path(),
+        secrets_dir.path(),
+        0,
+        2,
+        "I understand",
+    )
+    .unwrap_err();
+    assert!(err.contains("Key recovery cancelled"), "{}", err);
+    assert_eq!(dir_validator_count(validator_dir.path()), 0);
+
+    recover_validators(
+        &wallet,
+        validator_dir.path(),
+        secrets_dir.path(),
+        0,
+        2,
+        recover::CONFIRMATION_PHRASE,
+    )
+    .unwrap();
+    assert_eq!(dir_validator_count(validator_dir.path()), 2);
+
+    // A range of indices beyond the largest key index is rejected.
+    let err = recover_validators(
+        &wallet,
+        validator_dir.path(),
+        secrets_dir.path(),
+        u32::max_value(),
+        2,
+        recover::CONFIRMATION_PHRASE,
+    )
+    .unwrap_err();
+    assert!(err.contains("exceed the largest key index"), "{}", err);
+    assert_eq!(dir_validator_count(validator_dir.path()), 2);
+}
+
 #[test]
 fn validator_import_launchpad() {
     const PASSWORD: &str = "cats";
@@ -7,15 +7,6 @@
 
 source ./vars.env
 
-lcli \
-	--spec mainnet \
-	new-testnet \
-	--deposit-contract-address 1234567890123456789012345678901234567890 \
-	--testnet-dir $TESTNET_DIR \
-	--min-genesis-active-validator-count $VALIDATOR_COUNT \
-	--force
-
-echo Specification generated at $TESTNET_DIR.
 echo "Generating $VALIDATOR_COUNT validators concurrently... (this may take a while)"
 
 lcli \
@@ -29,8 +20,12 @@ echo "Building genesis state... (this might take a while)"
 
 lcli \
 	--spec mainnet \
-	interop-genesis \
+	new-testnet \
+	--deposit-contract-address 1234567890123456789012345678901234567890 \
 	--testnet-dir $TESTNET_DIR \
-	$VALIDATOR_COUNT
+	--min-genesis-active-validator-count $VALIDATOR_COUNT \
+	--interop-genesis-state \
+	--validator-count $VALIDATOR_COUNT \
+	--force
 
-echo Created genesis state in $TESTNET_DIR
+echo Specification and genesis state generated at $TESTNET_DIR.
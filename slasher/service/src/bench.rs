@@ -0,0 +1,264 @@
+//! Measures the throughput of the slasher for a given configuration, by replaying an epoch of
+//! attestations captured from a beacon node.
+use crate::feed::get_committees;
+use beacon_node::get_slasher_config;
+use clap::ArgMatches;
+use directory::size_of_dir;
+use environment::Environment;
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient,
+};
+use slasher::Slasher;
+use slog::{info, o};
+use ssz::{Decode, Encode};
+use state_processing::common::get_indexed_attestation;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tokio_compat_02::FutureExt;
+use types::{Epoch, EthSpec, IndexedAttestation, Slot};
+
+pub const CAPTURE_CMD: &str = "capture";
+pub const BENCH_CMD: &str = "bench";
+
+/// Download the attestations of an epoch which were included in canonical blocks, and write them
+/// to a file as an SSZ list of indexed attestations.
+pub fn capture<E: EthSpec>(
+    environment: &mut Environment<E>,
+    client: BeaconNodeHttpClient,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let log = environment.core_context().log().clone();
+    let epoch = Epoch::new(clap_utils::parse_required(matches, "epoch")?);
+    let output: PathBuf = clap_utils::parse_required(matches, "output")?;
+
+    let attestations = environment
+        .runtime()
+        .block_on(download_epoch::<E>(&client, epoch).compat())?;
+
+    fs::write(&output, attestations.as_ssz_bytes())
+        .map_err(|e| format!("Unable to write {:?}: {:?}", output, e))?;
+
+    info!(
+        log,
+        "Captured attestations";
+        "epoch" => epoch,
+        "num_attestations" => attestations.len(),
+        "output" => format!("{:?}", output),
+    );
+
+    Ok(())
+}
+
+/// Attestations can be included up to the end of the epoch after their own, so the blocks of both
+/// epochs are searched.
+async fn download_epoch<E: EthSpec>(
+    client: &BeaconNodeHttpClient,
+    epoch: Epoch,
+) -> Result<Vec<IndexedAttestation<E>>, String> {
+    let start_slot = epoch.start_slot(E::slots_per_epoch());
+    let committees = get_committees(client, StateId::Slot(start_slot), epoch).await?;
+
+    let mut indexed_attestations = vec![];
+    for slot in (0..2 * E::slots_per_epoch()).map(|i| start_slot + i) {
+        let root = client
+            .get_beacon_headers(Some(slot), None)
+            .await
+            .map_err(|e| format!("Unable to download block headers: {:?}", e))?
+            .map_or_else(Vec::new, |response| response.data)
+            .into_iter()
+            .find(|header| header.header.message.slot == slot)
+            .map(|header| header.root);
+
+        let attestations = if let Some(root) = root {
+            client
+                .get_beacon_blocks_attestations::<E>(BlockId::Root(root))
+                .await
+                .map_err(|e| format!("Unable to download block attestations: {:?}", e))?
+                .map_or_else(Vec::new, |response| response.data)
+        } else {
+            continue;
+        };
+
+        for attestation in attestations
+            .iter()
+            .filter(|attestation| attestation.data.target.epoch == epoch)
+        {
+            let committee = committees
+                .get(&(attestation.data.slot, attestation.data.index))
+                .ok_or_else(|| {
+                    format!(
+                        "No committee {} at slot {}",
+                        attestation.data.index, attestation.data.slot
+                    )
+                })?;
+            indexed_attestations.push(
+                get_indexed_attestation(committee, attestation)
+                    .map_err(|e| format!("Invalid attestation: {:?}", e))?,
+            );
+        }
+    }
+
+    Ok(indexed_attestations)
+}
+
+/// Replay a captured epoch of attestations into an empty slasher, running an update for every
+/// `--slasher-update-period` worth of slots, and report the time spent processing them.
+pub fn bench<E: EthSpec>(
+    environment: &mut Environment<E>,
+    slasher_matches: &ArgMatches,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let context = environment.core_context();
+    let log = context.log().clone();
+    let spec = context.eth2_config().spec.clone();
+
+    let input: PathBuf = clap_utils::parse_required(matches, "attestations")?;
+    let repeat: u64 = clap_utils::parse_required(matches, "repeat")?;
+
+    let bytes = fs::read(&input).map_err(|e| format!("Unable to read {:?}: {:?}", input, e))?;
+    let attestations = Vec::<IndexedAttestation<E>>::from_ssz_bytes(&bytes)
+        .map_err(|e| format!("Unable to decode {:?}: {:?}", input, e))?;
+
+    // Use a temporary database unless a directory is given, so that each run starts empty. A
+    // given directory must not already hold a database, which the benchmark would write into.
+    let tempdir = TempDir::new().map_err(|e| format!("Unable to create temp dir: {:?}", e))?;
+    let config = get_slasher_config(slasher_matches, tempdir.path())?;
+    let database_path = config.database_path.clone();
+    if fs::read_dir(&database_path).map_or(false, |mut entries| entries.next().is_some()) {
+        return Err(format!(
+            "Refusing to benchmark into the non-empty directory {:?}, the benchmark requires \
+            an empty database",
+            database_path
+        ));
+    }
+
+    let slots_per_update =
+        std::cmp::max(1, config.update_period * 1000 / spec.milliseconds_per_slot);
+
+    info!(
+        log,
+        "Starting slasher benchmark";
+        "num_attestations" => attestations.len(),
+        "repeat" => repeat,
+        "slots_per_update" => slots_per_update,
+        "chunk_size" => config.chunk_size,
+        "validator_chunk_size" => config.validator_chunk_size,
+        "backend" => format!("{:?}", config.backend),
+    );
+
+    let slasher = Slasher::<E>::open(config, log.new(o!("service" => "slasher")))
+        .map_err(|e| format!("Unable to open slasher database: {:?}", e))?;
+
+    // Group the attestations into batches by the slot of the update which would process them.
+    let mut batches = BTreeMap::<Slot, Vec<IndexedAttestation<E>>>::new();
+    for offset in 0..repeat {
+        for attestation in &attestations {
+            let attestation = shift_epochs(attestation, offset);
+            let update_slot = Slot::new(
+                (attestation.data.slot.as_u64() / slots_per_update + 1) * slots_per_update,
+            );
+            batches.entry(update_slot).or_default().push(attestation);
+        }
+    }
+
+    let mut total_time = Duration::default();
+    let mut max_time = Duration::default();
+    let mut num_slashings = 0;
+    let num_updates = batches.len();
+    for (update_slot, batch) in batches {
+        for attestation in batch {
+            slasher.accept_attestation(attestation);
+        }
+
+        let timer = Instant::now();
+        slasher
+            .process_queued(update_slot.epoch(E::slots_per_epoch()))
+            .map_err(|e| format!("Slasher update failed: {:?}", e))?;
+        let elapsed = timer.elapsed();
+
+        total_time += elapsed;
+        max_time = std::cmp::max(max_time, elapsed);
+        num_slashings += slasher.get_attester_slashings().len();
+    }
+
+    let num_attestations = attestations.len() as u64 * repeat;
+    let attestations_per_sec = num_attestations as f64 / total_time.as_secs_f64();
+    info!(
+        log,
+        "Finished slasher benchmark";
+        "num_attestations" => num_attestations,
+        "num_updates" => num_updates,
+        "total_time" => format!("{:?}", total_time),
+        "max_update_time" => format!("{:?}", max_time),
+        "attestations_per_sec" => format!("{:.0}", attestations_per_sec),
+        "database_size" => size_of_dir(&database_path),
+        "num_slashings" => num_slashings,
+    );
+
+    Ok(())
+}
+
+/// Move `attestation` forwards by `offset` epochs, so that a captured epoch can be replayed
+/// several times without the copies slashing one another.
+fn shift_epochs<E: EthSpec>(
+    attestation: &IndexedAttestation<E>,
+    offset: u64,
+) -> IndexedAttestation<E> {
+    let mut attestation = attestation.clone();
+    attestation.data.slot += offset * E::slots_per_epoch();
+    attestation.data.source.epoch += offset;
+    attestation.data.target.epoch += offset;
+    attestation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use environment::EnvironmentBuilder;
+    use tempfile::tempdir;
+    use types::MinimalEthSpec;
+
+    #[test]
+    fn refuses_existing_database() {
+        let mut env = EnvironmentBuilder::minimal()
+            .multi_threaded_tokio_runtime()
+            .unwrap()
+            .null_logger()
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("epoch.ssz");
+        fs::write(
+            &input,
+            Vec::<IndexedAttestation<MinimalEthSpec>>::new().as_ssz_bytes(),
+        )
+        .unwrap();
+        let slasher_dir = dir.path().join("slasher_db");
+        fs::create_dir(&slasher_dir).unwrap();
+        fs::write(slasher_dir.join("data.mdb"), b"not a benchmark").unwrap();
+
+        let matches = crate::cli::cli_app().get_matches_from(vec![
+            "slasher",
+            "--slasher-dir",
+            slasher_dir.to_str().unwrap(),
+            BENCH_CMD,
+            "--attestations",
+            input.to_str().unwrap(),
+        ]);
+        let bench_matches = matches.subcommand_matches(BENCH_CMD).unwrap();
+
+        assert!(bench(&mut env, &matches, bench_matches)
+            .unwrap_err()
+            .contains("non-empty directory"));
+        assert_eq!(
+            fs::read(slasher_dir.join("data.mdb")).unwrap(),
+            b"not a benchmark"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::bench::{BENCH_CMD, CAPTURE_CMD};
+use crate::export::{EXPORT_CMD, IMPORT_CMD};
+use crate::migrate::MIGRATE_CMD;
+use crate::CMD;
+use clap::{App, Arg, SubCommand};
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .author("Sigma Prime <contact@sigmaprime.io>")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Runs the slasher as a separate process, so that its workload can be kept off the \
+            beacon node's machine. The blocks and attestations of each slot are downloaded from \
+            the beacon node's HTTP API, and any slashings found are submitted back to the beacon \
+            node to be included in blocks and published on gossip.",
+        )
+        .arg(
+            Arg::with_name("beacon-node")
+                .long("beacon-node")
+                .value_name("NETWORK_ADDRESS")
+                .help("Address to a beacon node HTTP API")
+                .default_value("http://localhost:5052/")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-dir")
+                .long("slasher-dir")
+                .value_name("PATH")
+                .help("Set the slasher's database directory. [default: DATADIR/beacon/slasher_db]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-update-period")
+                .long("slasher-update-period")
+                .value_name("SECONDS")
+                .help("Configure how often the slasher runs batch processing.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-history-length")
+                .long("slasher-history-length")
+                .value_name("EPOCHS")
+                .help(
+                    "Configure how many epochs of history the slasher keeps. Immutable after \
+                    initialization.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-max-db-size")
+                .long("slasher-max-db-size")
+                .value_name("GIGABYTES")
+                .help(
+                    "Maximum size of the database used by the slasher. If it is reached, the \
+                    slasher prunes its oldest epochs, keeping less history than \
+                    --slasher-history-length.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-chunk-size")
+                .long("slasher-chunk-size")
+                .value_name("EPOCHS")
+                .help("Number of epochs per validator per chunk stored on disk.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-validator-chunk-size")
+                .long("slasher-validator-chunk-size")
+                .value_name("NUM_VALIDATORS")
+                .help("Number of validators per chunk stored on disk.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-backend")
+                .long("slasher-backend")
+                .value_name("BACKEND")
+                .help("Set the key-value store used by the slasher's database. [default: lmdb]")
+                .possible_values(&["lmdb", "redb"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slasher-memory-budget")
+                .long("slasher-memory-budget")
+                .value_name("MEGABYTES")
+                .help(
+                    "Limit the memory used by the slasher's queues of attestations and block \
+                    headers. Attestations beyond the limit of each batch are deferred to the next \
+                    batch, and once the queues are full new attestations are dropped. \
+                    [default: unlimited]",
+                )
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name(CAPTURE_CMD)
+                .about(
+                    "Downloads the attestations of an epoch from the beacon node's canonical \
+                    chain, for replaying with the bench subcommand.",
+                )
+                .arg(
+                    Arg::with_name("epoch")
+                        .long("epoch")
+                        .value_name("EPOCH")
+                        .help("The epoch whose attestations are captured.")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("The file to write the attestations to, as SSZ.")
+                        .required(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(BENCH_CMD)
+                .about(
+                    "Replays a captured epoch of attestations into an empty slasher database and \
+                    reports the time taken to process them. The --slasher-* flags set the \
+                    configuration under test.",
+                )
+                .arg(
+                    Arg::with_name("attestations")
+                        .long("attestations")
+                        .value_name("FILE")
+                        .help("A file of attestations written by the capture subcommand.")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("repeat")
+                        .long("repeat")
+                        .value_name("EPOCHS")
+                        .help(
+                            "Replay the attestations this many times, each copy one epoch after \
+                            the last.",
+                        )
+                        .default_value("1")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(EXPORT_CMD)
+                .about(
+                    "Writes the slasher database to a file, for importing on another machine. \
+                    The slasher or beacon node using the database must be stopped first.",
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("The file to write the export to.")
+                        .required(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(IMPORT_CMD)
+                .about(
+                    "Reads a file written by the export subcommand into a new slasher database. \
+                    The chunk sizes and history length must match those of the exported \
+                    database, but the backend may differ.",
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("The file to read the export from.")
+                        .required(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(MIGRATE_CMD)
+                .about(
+                    "Upgrades the slasher database to the schema version of this release. The \
+                    slasher or beacon node using the database must be stopped first, and won't \
+                    open a database with an older schema until it has been migrated.",
+                )
+                .arg(Arg::with_name("dry-run").long("dry-run").help(
+                    "Print the migrations that would be applied, the amount of data they \
+                    would rewrite and an estimate of how long they would take, without \
+                    changing the database.",
+                )),
+        )
+}
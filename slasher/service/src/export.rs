@@ -0,0 +1,77 @@
+//! Copies the slasher database between machines, via a file which is independent of the backend.
+use beacon_node::{get_data_dir, get_slasher_config};
+use clap::ArgMatches;
+use environment::Environment;
+use slasher::SlasherDB;
+use slog::info;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use types::EthSpec;
+
+pub const EXPORT_CMD: &str = "export";
+pub const IMPORT_CMD: &str = "import";
+
+/// Write the slasher database to a file. The slasher must not be running.
+pub fn export<E: EthSpec>(
+    environment: &mut Environment<E>,
+    slasher_matches: &ArgMatches,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let log = environment.core_context().log().clone();
+    let config = get_slasher_config(slasher_matches, &get_data_dir(slasher_matches))?;
+    let output: PathBuf = clap_utils::parse_required(matches, "output")?;
+
+    if !config.database_path.exists() {
+        return Err(format!("No slasher database at {:?}", config.database_path));
+    }
+
+    let db = SlasherDB::<E>::open(Arc::new(config))
+        .map_err(|e| format!("Unable to open slasher database: {:?}", e))?;
+    let file =
+        File::create(&output).map_err(|e| format!("Unable to create {:?}: {:?}", output, e))?;
+    let num_records = db
+        .export(BufWriter::new(file))
+        .map_err(|e| format!("Unable to export slasher database: {:?}", e))?;
+
+    info!(
+        log,
+        "Exported slasher database";
+        "num_records" => num_records,
+        "output" => format!("{:?}", output),
+    );
+
+    Ok(())
+}
+
+/// Read a file written by `export` into a new slasher database.
+pub fn import<E: EthSpec>(
+    environment: &mut Environment<E>,
+    slasher_matches: &ArgMatches,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let log = environment.core_context().log().clone();
+    let config = get_slasher_config(slasher_matches, &get_data_dir(slasher_matches))?;
+    let input: PathBuf = clap_utils::parse_required(matches, "input")?;
+
+    config
+        .validate()
+        .map_err(|e| format!("Invalid slasher config: {:?}", e))?;
+
+    let file = File::open(&input).map_err(|e| format!("Unable to open {:?}: {:?}", input, e))?;
+    let db = SlasherDB::<E>::open(Arc::new(config))
+        .map_err(|e| format!("Unable to open slasher database: {:?}", e))?;
+    let num_records = db
+        .import(BufReader::new(file))
+        .map_err(|e| format!("Unable to import slasher database: {:?}", e))?;
+
+    info!(
+        log,
+        "Imported slasher database";
+        "num_records" => num_records,
+        "input" => format!("{:?}", input),
+    );
+
+    Ok(())
+}
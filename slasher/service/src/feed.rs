@@ -0,0 +1,644 @@
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient, StatusCode,
+};
+use futures::StreamExt;
+use slasher::Slasher;
+use slog::{debug, info, warn, Logger};
+use slot_clock::SlotClock;
+use state_processing::common::get_indexed_attestation;
+use std::cmp::max;
+use std::collections::HashMap;
+use std::sync::Arc;
+use task_executor::TaskExecutor;
+use tokio::time::{interval_at, Duration, Instant};
+use types::{
+    Attestation, AttesterSlashing, Epoch, EthSpec, ProposerSlashing, SignedBeaconBlockHeader, Slot,
+};
+
+/// The number of epochs of committees to keep, counting back from the current epoch.
+const COMMITTEE_EPOCHS: u64 = 3;
+
+/// The validator indices of each committee of an epoch, keyed by slot and committee index.
+pub type Committees = HashMap<(Slot, u64), Vec<usize>>;
+
+/// Downloads blocks and attestations from a beacon node for the slasher, and submits the
+/// slashings it finds.
+pub struct BeaconNodeFeed<E: EthSpec> {
+    slasher: Arc<Slasher<E>>,
+    client: BeaconNodeHttpClient,
+    /// The next slot to download, or `None` if none have been downloaded yet.
+    next_slot: Option<Slot>,
+    committees: HashMap<Epoch, Committees>,
+    /// Slashings which could not be submitted yet, and will be retried.
+    attester_slashings: Vec<AttesterSlashing<E>>,
+    proposer_slashings: Vec<ProposerSlashing>,
+    log: Logger,
+}
+
+impl<E: EthSpec> BeaconNodeFeed<E> {
+    pub fn new(slasher: Arc<Slasher<E>>, client: BeaconNodeHttpClient, log: Logger) -> Self {
+        Self {
+            slasher,
+            client,
+            next_slot: None,
+            committees: HashMap::new(),
+            attester_slashings: vec![],
+            proposer_slashings: vec![],
+            log,
+        }
+    }
+
+    /// Download the previous slot a third of the way through each slot, once its aggregate
+    /// attestations have been published.
+    pub fn start<C: SlotClock + 'static>(
+        mut self,
+        slot_clock: C,
+        slot_duration: Duration,
+        executor: &TaskExecutor,
+    ) -> Result<(), String> {
+        let duration_to_next_slot = slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        let mut interval = interval_at(
+            Instant::now() + duration_to_next_slot + slot_duration / 3,
+            slot_duration,
+        );
+
+        executor.spawn(
+            async move {
+                while interval.next().await.is_some() {
+                    if let Some(current_slot) = slot_clock.now() {
+                        self.on_slot(current_slot).await;
+                    }
+                }
+            },
+            "slasher_feed",
+        );
+
+        Ok(())
+    }
+
+    async fn on_slot(&mut self, current_slot: Slot) {
+        if current_slot == 0 {
+            return;
+        }
+        let previous_slot = current_slot - 1;
+
+        // Catch up on slots which were missed while the beacon node was unavailable, for up to
+        // an epoch.
+        let start_slot = self.next_slot.map_or(previous_slot, |next_slot| {
+            max(
+                next_slot,
+                previous_slot.saturating_sub(E::slots_per_epoch()),
+            )
+        });
+        for slot in (start_slot.as_u64()..=previous_slot.as_u64()).map(Slot::new) {
+            if let Err(e) = self.download_slot(slot).await {
+                warn!(
+                    self.log,
+                    "Unable to download slot from beacon node";
+                    "slot" => slot,
+                    "error" => e,
+                );
+                break;
+            }
+            self.next_slot = Some(slot + 1);
+        }
+
+        let current_epoch = current_slot.epoch(E::slots_per_epoch());
+        self.committees
+            .retain(|epoch, _| *epoch + COMMITTEE_EPOCHS > current_epoch);
+
+        self.submit_slashings().await;
+    }
+
+    /// Queue the canonical block at `slot` and the attestations of `slot` for the slasher.
+    async fn download_slot(&mut self, slot: Slot) -> Result<(), String> {
+        // Only canonical blocks are available from the API, so double proposals are only found if
+        // each of the blocks is canonical at some point.
+        let headers = self
+            .client
+            .get_beacon_headers(Some(slot), None)
+            .await
+            .map_err(|e| format!("Unable to download block headers: {:?}", e))?
+            .map_or_else(Vec::new, |response| response.data)
+            .into_iter()
+            // Skipped slots return the header of the previous block.
+            .filter(|header| header.header.message.slot == slot)
+            .collect::<Vec<_>>();
+
+        let mut attestations = self
+            .client
+            .get_beacon_pool_attestations::<E>(Some(slot), None)
+            .await
+            .map_err(|e| format!("Unable to download pool attestations: {:?}", e))?
+            .data;
+
+        for header in headers {
+            attestations.extend(
+                self.client
+                    .get_beacon_blocks_attestations::<E>(BlockId::Root(header.root))
+                    .await
+                    .map_err(|e| format!("Unable to download block attestations: {:?}", e))?
+                    .map_or_else(Vec::new, |response| response.data),
+            );
+
+            let signature = header
+                .header
+                .signature
+                .decompress()
+                .map_err(|e| format!("Invalid block signature: {:?}", e))?;
+            self.slasher.accept_block_header(SignedBeaconBlockHeader {
+                message: header.header.message,
+                signature,
+            });
+        }
+
+        debug!(
+            self.log,
+            "Downloaded slot for slasher";
+            "slot" => slot,
+            "num_attestations" => attestations.len(),
+        );
+
+        for attestation in attestations {
+            self.accept_attestation(&attestation).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Queue `attestation` for the slasher.
+    ///
+    /// Only fails if the committees can't be downloaded. Attestations which can't be indexed are
+    /// skipped, so that they don't stop the slot from being downloaded.
+    async fn accept_attestation(&mut self, attestation: &Attestation<E>) -> Result<(), String> {
+        let slot = attestation.data.slot;
+        let epoch = slot.epoch(E::slots_per_epoch());
+
+        if !self.committees.contains_key(&epoch) {
+            let committees = get_committees(&self.client, StateId::Head, epoch).await?;
+            self.committees.insert(epoch, committees);
+        }
+
+        let committee = match self
+            .committees
+            .get(&epoch)
+            .and_then(|committees| committees.get(&(slot, attestation.data.index)))
+        {
+            Some(committee) => committee,
+            None => {
+                warn!(
+                    self.log,
+                    "Skipping attestation with unknown committee";
+                    "slot" => slot,
+                    "index" => attestation.data.index,
+                );
+                return Ok(());
+            }
+        };
+
+        match get_indexed_attestation(committee, attestation) {
+            Ok(indexed_attestation) => self.slasher.accept_attestation(indexed_attestation),
+            Err(e) => warn!(
+                self.log,
+                "Skipping invalid attestation";
+                "slot" => slot,
+                "index" => attestation.data.index,
+                "error" => format!("{:?}", e),
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Submit the slashings found by the slasher to the beacon node.
+    ///
+    /// Slashings which the beacon node can't be reached for are kept to be retried.
+    async fn submit_slashings(&mut self) {
+        self.attester_slashings
+            .extend(self.slasher.get_attester_slashings());
+        self.proposer_slashings
+            .extend(self.slasher.get_proposer_slashings());
+
+        let mut retry_attester_slashings = vec![];
+        for slashing in self.attester_slashings.drain(..) {
+            match self
+                .client
+                .post_beacon_pool_attester_slashings(&slashing)
+                .await
+            {
+                Ok(()) => info!(
+                    self.log,
+                    "Submitted attester slashing";
+                    "epoch" => slashing.attestation_1.data.target.epoch,
+                ),
+                Err(e) if e.status() == Some(StatusCode::BAD_REQUEST) => warn!(
+                    self.log,
+                    "Attester slashing rejected by beacon node";
+                    "error" => format!("{:?}", e),
+                ),
+                Err(e) => {
+                    warn!(
+                        self.log,
+                        "Unable to submit attester slashing";
+                        "error" => format!("{:?}", e),
+                    );
+                    retry_attester_slashings.push(slashing);
+                }
+            }
+        }
+        self.attester_slashings = retry_attester_slashings;
+
+        let mut retry_proposer_slashings = vec![];
+        for slashing in self.proposer_slashings.drain(..) {
+            match self
+                .client
+                .post_beacon_pool_proposer_slashings(&slashing)
+                .await
+            {
+                Ok(()) => info!(
+                    self.log,
+                    "Submitted proposer slashing";
+                    "validator_index" => slashing.signed_header_1.message.proposer_index,
+                    "slot" => slashing.signed_header_1.message.slot,
+                ),
+                Err(e) if e.status() == Some(StatusCode::BAD_REQUEST) => warn!(
+                    self.log,
+                    "Proposer slashing rejected by beacon node";
+                    "error" => format!("{:?}", e),
+                ),
+                Err(e) => {
+                    warn!(
+                        self.log,
+                        "Unable to submit proposer slashing";
+                        "error" => format!("{:?}", e),
+                    );
+                    retry_proposer_slashings.push(slashing);
+                }
+            }
+        }
+        self.proposer_slashings = retry_proposer_slashings;
+    }
+}
+
+/// Download the committees of `epoch` from the beacon node, as computed from the state `state_id`.
+pub async fn get_committees(
+    client: &BeaconNodeHttpClient,
+    state_id: StateId,
+    epoch: Epoch,
+) -> Result<Committees, String> {
+    Ok(client
+        .get_beacon_states_committees(state_id, None, None, Some(epoch))
+        .await
+        .map_err(|e| format!("Unable to download committees: {:?}", e))?
+        .ok_or_else(|| format!("No committees for epoch {}", epoch))?
+        .data
+        .into_iter()
+        .map(|committee| {
+            let validators = committee
+                .validators
+                .into_iter()
+                .map(|index| index as usize)
+                .collect();
+            ((committee.slot, committee.index), validators)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use environment::{Environment, EnvironmentBuilder};
+    use eth2::types::{BlockHeaderAndSignature, BlockHeaderData, CommitteeData, GenericResponse};
+    use eth2::Url;
+    use slasher::{
+        test_utils::{block, indexed_att, logger, E},
+        Config,
+    };
+    use std::collections::{HashSet, VecDeque};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread;
+    use tempfile::{tempdir, TempDir};
+    use tokio_compat_02::FutureExt;
+    use types::{
+        AggregateSignature, AttestationData, BitList, Checkpoint, Hash256, Keypair, MinimalEthSpec,
+        SignatureBytes,
+    };
+
+    const HEADERS_PATH: &str = "/eth/v1/beacon/headers?slot=";
+    const POOL_ATTESTATIONS_PATH: &str = "/eth/v1/beacon/pool/attestations?slot=";
+    const COMMITTEES_PATH: &str = "/eth/v1/beacon/states/head/committees?epoch=";
+    const ATTESTER_SLASHINGS_PATH: &str = "/eth/v1/beacon/pool/attester_slashings";
+    const PROPOSER_SLASHINGS_PATH: &str = "/eth/v1/beacon/pool/proposer_slashings";
+
+    /// Serve requests with the status and body returned by `handler` for their method and path,
+    /// returning the URL of the server and a record of the requests, as `METHOD path`.
+    fn serve<F>(handler: F) -> (Url, Arc<Mutex<Vec<String>>>)
+    where
+        F: Fn(&str, &str) -> (u16, String) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let requests_clone = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().to_string();
+                requests_clone
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", method, path));
+
+                let (status, body) = handler(&method, &path);
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\n\
+                    Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn json<T: serde::Serialize + serde::de::DeserializeOwned>(data: T) -> (u16, String) {
+        (
+            200,
+            serde_json::to_string(&GenericResponse { data }).unwrap(),
+        )
+    }
+
+    fn not_found() -> (u16, String) {
+        (404, String::new())
+    }
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .multi_threaded_tokio_runtime()
+            .unwrap()
+            .null_logger()
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn feed(url: Url) -> (BeaconNodeFeed<E>, TempDir) {
+        let dir = tempdir().unwrap();
+        let slasher = Slasher::open(Config::new(dir.path().into()), logger()).unwrap();
+        let feed = BeaconNodeFeed::new(Arc::new(slasher), BeaconNodeHttpClient::new(url), logger());
+        (feed, dir)
+    }
+
+    fn on_slot(env: &Environment<MinimalEthSpec>, feed: &mut BeaconNodeFeed<E>, slot: u64) {
+        env.runtime()
+            .block_on(feed.on_slot(Slot::new(slot)).compat());
+    }
+
+    /// Take the slots whose block headers have been requested.
+    fn requested_slots(requests: &Mutex<Vec<String>>) -> Vec<u64> {
+        requests
+            .lock()
+            .unwrap()
+            .drain(..)
+            .filter_map(|request| {
+                request
+                    .strip_prefix("GET ")
+                    .and_then(|path| path.strip_prefix(HEADERS_PATH))
+                    .map(|slot| slot.parse().unwrap())
+            })
+            .collect()
+    }
+
+    fn attestation(slot: u64, index: u64) -> Attestation<E> {
+        let mut aggregation_bits = BitList::with_capacity(2).unwrap();
+        aggregation_bits.set(0, true).unwrap();
+        Attestation {
+            aggregation_bits,
+            data: AttestationData {
+                slot: Slot::new(slot),
+                index,
+                beacon_block_root: Hash256::zero(),
+                source: Checkpoint::default(),
+                target: Checkpoint::default(),
+            },
+            signature: AggregateSignature::empty(),
+        }
+    }
+
+    #[test]
+    fn downloads_each_slot_once() {
+        let (url, requests) = serve(|_, path| {
+            if path.starts_with(POOL_ATTESTATIONS_PATH) {
+                json(Vec::<Attestation<E>>::new())
+            } else {
+                not_found()
+            }
+        });
+        let (mut feed, _dir) = feed(url);
+        let env = environment();
+
+        on_slot(&env, &mut feed, 0);
+        assert_eq!(requested_slots(&requests), Vec::<u64>::new());
+        assert_eq!(feed.next_slot, None);
+
+        // The first slot only downloads the previous slot.
+        on_slot(&env, &mut feed, 10);
+        assert_eq!(requested_slots(&requests), vec![9]);
+        assert_eq!(feed.next_slot, Some(Slot::new(10)));
+
+        on_slot(&env, &mut feed, 10);
+        assert_eq!(requested_slots(&requests), Vec::<u64>::new());
+
+        // Missed slots are caught up on.
+        on_slot(&env, &mut feed, 13);
+        assert_eq!(requested_slots(&requests), vec![10, 11, 12]);
+
+        // For up to an epoch.
+        let current_slot = 4 * E::slots_per_epoch();
+        on_slot(&env, &mut feed, current_slot);
+        let expected_slots =
+            (current_slot - 1 - E::slots_per_epoch()..current_slot).collect::<Vec<_>>();
+        assert_eq!(requested_slots(&requests), expected_slots);
+        assert_eq!(feed.next_slot, Some(Slot::new(current_slot)));
+    }
+
+    #[test]
+    fn retries_slot_after_error() {
+        let failing_slots = Arc::new(Mutex::new(HashSet::new()));
+        failing_slots.lock().unwrap().insert(11_u64);
+        let failing_slots_clone = failing_slots.clone();
+
+        let (url, requests) = serve(move |_, path| match path.strip_prefix(HEADERS_PATH) {
+            Some(slot)
+                if failing_slots_clone
+                    .lock()
+                    .unwrap()
+                    .contains(&slot.parse::<u64>().unwrap()) =>
+            {
+                (500, String::new())
+            }
+            Some(_) => not_found(),
+            None => json(Vec::<Attestation<E>>::new()),
+        });
+        let (mut feed, _dir) = feed(url);
+        let env = environment();
+
+        on_slot(&env, &mut feed, 10);
+        assert_eq!(requested_slots(&requests), vec![9]);
+
+        // Downloads stop at the failed slot.
+        on_slot(&env, &mut feed, 14);
+        assert_eq!(requested_slots(&requests), vec![10, 11]);
+        assert_eq!(feed.next_slot, Some(Slot::new(11)));
+
+        // And resume from it once the beacon node recovers.
+        failing_slots.lock().unwrap().clear();
+        on_slot(&env, &mut feed, 14);
+        assert_eq!(requested_slots(&requests), vec![11, 12, 13]);
+        assert_eq!(feed.next_slot, Some(Slot::new(14)));
+    }
+
+    #[test]
+    fn accepts_blocks_and_attestations() {
+        let keypair = Keypair::random();
+        let header = block(9, 1, 0);
+        let header_data = BlockHeaderData {
+            root: Hash256::repeat_byte(9),
+            canonical: true,
+            header: BlockHeaderAndSignature {
+                message: header.message.clone(),
+                signature: SignatureBytes::from(keypair.sk.sign(Hash256::zero())),
+            },
+        };
+
+        let (url, requests) = serve(move |_, path| {
+            if let Some(slot) = path.strip_prefix(HEADERS_PATH) {
+                // Slot 10 is skipped, so its header is that of slot 9.
+                match slot {
+                    "9" | "10" => json(vec![header_data.clone()]),
+                    _ => not_found(),
+                }
+            } else if let Some(slot) = path.strip_prefix(POOL_ATTESTATIONS_PATH) {
+                match slot {
+                    "9" => json(vec![attestation(9, 0)]),
+                    "10" => json(vec![attestation(10, 0)]),
+                    // There is no committee with the first index.
+                    "11" => json(vec![attestation(11, 1), attestation(11, 0)]),
+                    _ => json(Vec::<Attestation<E>>::new()),
+                }
+            } else if path.starts_with(COMMITTEES_PATH) {
+                json(
+                    (9..12)
+                        .map(|slot| CommitteeData {
+                            index: 0,
+                            slot: Slot::new(slot),
+                            validators: vec![3, 4],
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                // The attestations of the block.
+                json(Vec::<Attestation<E>>::new())
+            }
+        });
+        let (mut feed, _dir) = feed(url);
+        let env = environment();
+
+        on_slot(&env, &mut feed, 10);
+        let status = feed.slasher.status();
+        assert_eq!(status.block_queue_len, 1);
+        assert_eq!(status.attestation_queue_len, 1);
+
+        on_slot(&env, &mut feed, 11);
+        let status = feed.slasher.status();
+        assert_eq!(status.block_queue_len, 1);
+        assert_eq!(status.attestation_queue_len, 2);
+
+        // The attestation with an unknown committee is skipped, without holding up the rest of
+        // the slot.
+        on_slot(&env, &mut feed, 12);
+        assert_eq!(feed.next_slot, Some(Slot::new(12)));
+        assert_eq!(feed.slasher.status().attestation_queue_len, 3);
+
+        // The committees of each epoch are only downloaded once.
+        let committee_requests = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.contains(COMMITTEES_PATH))
+            .count();
+        assert_eq!(committee_requests, 1);
+        assert!(feed.committees.contains_key(&Epoch::new(0)));
+
+        // And are dropped once they are too old.
+        on_slot(&env, &mut feed, COMMITTEE_EPOCHS * E::slots_per_epoch());
+        assert!(feed.committees.is_empty());
+    }
+
+    #[test]
+    fn retries_unsubmitted_slashings() {
+        let statuses = Arc::new(Mutex::new(VecDeque::from(vec![
+            // The first attempt fails, the second is rejected.
+            (ATTESTER_SLASHINGS_PATH, 500),
+            (PROPOSER_SLASHINGS_PATH, 503),
+            (ATTESTER_SLASHINGS_PATH, 400),
+            (PROPOSER_SLASHINGS_PATH, 200),
+        ])));
+        let statuses_clone = statuses.clone();
+        let (url, requests) = serve(move |method, path| {
+            let (expected_path, status) = statuses_clone.lock().unwrap().pop_front().unwrap();
+            assert_eq!(method, "POST");
+            assert_eq!(path, expected_path);
+            (status, String::new())
+        });
+        let (mut feed, _dir) = feed(url);
+        let env = environment();
+
+        feed.slasher.accept_block_header(block(1, 0, 0));
+        feed.slasher.accept_block_header(block(1, 0, 1));
+        feed.slasher.accept_attestation(indexed_att(&[0], 0, 1, 0));
+        feed.slasher.accept_attestation(indexed_att(&[0], 0, 1, 1));
+        feed.slasher.process_queued(Epoch::new(1)).unwrap();
+
+        env.runtime().block_on(feed.submit_slashings().compat());
+        assert_eq!(feed.attester_slashings.len(), 1);
+        assert_eq!(feed.proposer_slashings.len(), 1);
+
+        env.runtime().block_on(feed.submit_slashings().compat());
+        assert!(feed.attester_slashings.is_empty());
+        assert!(feed.proposer_slashings.is_empty());
+
+        assert!(statuses.lock().unwrap().is_empty());
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,116 @@
+//! Runs the slasher as a standalone process, which is fed by the HTTP API of a beacon node.
+//!
+//! Each slot, the blocks and attestations of the previous slot are downloaded from the beacon
+//! node and queued for the slasher. Slashings found by the slasher are submitted back to the
+//! beacon node, which adds them to its op pool and publishes them on gossip.
+//!
+//! The `capture` and `bench` subcommands measure the throughput of a slasher configuration
+//! against a real epoch of attestations, the `export` and `import` subcommands copy the slasher
+//! database to another machine, and the `migrate` subcommand upgrades its schema.
+mod bench;
+mod cli;
+mod export;
+mod feed;
+mod migrate;
+
+use beacon_node::{get_data_dir, get_slasher_config};
+use bench::{BENCH_CMD, CAPTURE_CMD};
+use clap::ArgMatches;
+use environment::Environment;
+use eth2::{BeaconNodeHttpClient, Url};
+use export::{EXPORT_CMD, IMPORT_CMD};
+use feed::BeaconNodeFeed;
+use migrate::MIGRATE_CMD;
+use slasher::{Slasher, SlasherServer};
+use slog::{info, o};
+use slot_clock::{SlotClock, SystemTimeSlotClock};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_compat_02::FutureExt;
+use types::EthSpec;
+
+pub use cli::cli_app;
+
+pub const CMD: &str = "slasher";
+
+/// Run one of the `capture`, `bench`, `export`, `import` or `migrate` subcommands, returning once
+/// it has completed.
+///
+/// Returns `Ok(false)` without doing anything if no subcommand was supplied, in which case the
+/// slasher should be started with `run`.
+pub fn run_subcommand<E: EthSpec>(
+    environment: &mut Environment<E>,
+    matches: &ArgMatches,
+) -> Result<bool, String> {
+    match matches.subcommand() {
+        (BENCH_CMD, Some(bench_matches)) => bench::bench(environment, matches, bench_matches)?,
+        (EXPORT_CMD, Some(export_matches)) => export::export(environment, matches, export_matches)?,
+        (IMPORT_CMD, Some(import_matches)) => export::import(environment, matches, import_matches)?,
+        (MIGRATE_CMD, Some(migrate_matches)) => {
+            migrate::migrate(environment, matches, migrate_matches)?
+        }
+        (CAPTURE_CMD, Some(capture_matches)) => {
+            bench::capture(environment, beacon_node_client(matches)?, capture_matches)?
+        }
+        (name, _) if !name.is_empty() => return Err(format!("Unknown subcommand: {}", name)),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Start the slasher and the tasks which connect it to the beacon node, returning once they are
+/// running.
+pub fn run<E: EthSpec>(
+    environment: &mut Environment<E>,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let context = environment.core_context();
+    let log = context.log().clone();
+    let spec = context.eth2_config().spec.clone();
+
+    let config = get_slasher_config(matches, &get_data_dir(matches))?;
+    let beacon_node_url: String = clap_utils::parse_required(matches, "beacon-node")?;
+    let client = beacon_node_client(matches)?;
+
+    info!(
+        log,
+        "Connecting to beacon node";
+        "address" => &beacon_node_url,
+    );
+    let genesis = environment
+        .runtime()
+        .block_on(client.get_beacon_genesis().compat())
+        .map_err(|e| format!("Unable to read genesis from beacon node: {:?}", e))?
+        .data;
+
+    let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+    let slot_clock = SystemTimeSlotClock::new(
+        spec.genesis_slot,
+        Duration::from_secs(genesis.genesis_time),
+        slot_duration,
+    );
+
+    let slasher = Arc::new(
+        Slasher::open(config, log.new(o!("service" => "slasher")))
+            .map_err(|e| format!("Unable to open slasher database: {:?}", e))?,
+    );
+
+    BeaconNodeFeed::new(slasher.clone(), client, log).start(
+        slot_clock.clone(),
+        slot_duration,
+        &context.executor,
+    )?;
+
+    // Slashings are collected by the feed, so there's nothing to do after each update.
+    SlasherServer::run(slasher, slot_clock, || (), &context.executor);
+
+    Ok(())
+}
+
+fn beacon_node_client(matches: &ArgMatches) -> Result<BeaconNodeHttpClient, String> {
+    let beacon_node_url: String = clap_utils::parse_required(matches, "beacon-node")?;
+    Ok(BeaconNodeHttpClient::new(
+        Url::parse(&beacon_node_url)
+            .map_err(|e| format!("Unable to parse beacon node URL: {:?}", e))?,
+    ))
+}
@@ -0,0 +1,97 @@
+//! Upgrades the slasher database to the current schema version while the slasher is stopped, so
+//! that a long migration never happens silently during beacon node startup.
+use beacon_node::{get_data_dir, get_slasher_config};
+use clap::ArgMatches;
+use environment::Environment;
+use slasher::SlasherDB;
+use slog::info;
+use std::sync::Arc;
+use std::time::Instant;
+use types::EthSpec;
+
+pub const MIGRATE_CMD: &str = "migrate";
+
+const MEGABYTE: u64 = 1 << 20;
+
+/// Migrate the slasher database, or with `--dry-run` only report the migrations required.
+pub fn migrate<E: EthSpec>(
+    environment: &mut Environment<E>,
+    slasher_matches: &ArgMatches,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let log = environment.core_context().log().clone();
+    let config = get_slasher_config(slasher_matches, &get_data_dir(slasher_matches))?;
+    let dry_run = matches.is_present("dry-run");
+
+    if !config.database_path.exists() {
+        return Err(format!("No slasher database at {:?}", config.database_path));
+    }
+
+    let db = SlasherDB::<E>::open_without_schema_migration(Arc::new(config))
+        .map_err(|e| format!("Unable to open slasher database: {:?}", e))?;
+    let plan = db
+        .plan_schema_migration()
+        .map_err(|e| format!("Unable to plan the schema migration: {:?}", e))?;
+
+    if plan.steps.is_empty() {
+        info!(
+            log,
+            "Slasher database is up to date";
+            "schema_version" => plan.to,
+        );
+        return Ok(());
+    }
+
+    info!(
+        log,
+        "Slasher schema migration required";
+        "from_version" => plan.from,
+        "to_version" => plan.to,
+        "data_rewritten_mb" => plan.bytes() / MEGABYTE,
+        "estimated_duration_secs" => plan.estimated_duration().as_secs(),
+    );
+    for step in &plan.steps {
+        info!(
+            log,
+            "Pending schema migration";
+            "version" => format!("v{} -> v{}", step.migration.from, step.migration.to),
+            "description" => step.migration.description,
+            "records" => step.records,
+            "estimated_duration_secs" => step.estimated_duration().as_secs(),
+        );
+    }
+
+    if dry_run {
+        info!(log, "Dry run, the slasher database was not changed");
+        return Ok(());
+    }
+
+    for (i, step) in plan.steps.iter().enumerate() {
+        info!(
+            log,
+            "Applying schema migration";
+            "step" => format!("{}/{}", i + 1, plan.steps.len()),
+            "description" => step.migration.description,
+            "records" => step.records,
+        );
+        let start = Instant::now();
+
+        db.apply_schema_migration(step.migration)
+            .map_err(|e| format!("Unable to migrate the schema: {:?}", e))?;
+
+        info!(
+            log,
+            "Applied schema migration";
+            "to_version" => step.migration.to,
+            "duration_secs" => start.elapsed().as_secs(),
+        );
+    }
+
+    info!(
+        log,
+        "Migrated slasher database";
+        "schema_version" => plan.to,
+    );
+
+    Ok(())
+}
@@ -1,13 +1,14 @@
+use crate::backend::{Database, RwTransaction};
 use crate::metrics::{self, SLASHER_COMPRESSION_RATIO, SLASHER_NUM_CHUNKS_UPDATED};
 use crate::{AttesterRecord, AttesterSlashingStatus, Config, Error, SlasherDB};
 use flate2::bufread::{ZlibDecoder, ZlibEncoder};
-use lmdb::{RwTransaction, Transaction};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::{btree_map::Entry, BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::io::Read;
 use std::iter::Extend;
 use std::sync::Arc;
+use std::time::Instant;
 use types::{AttesterSlashing, Epoch, EthSpec, IndexedAttestation};
 
 pub const MAX_DISTANCE: u16 = u16::MAX;
@@ -146,7 +147,7 @@ pub trait TargetArrayChunk: Sized + serde::Serialize + serde::de::DeserializeOwn
 
     fn next_start_epoch(start_epoch: Epoch, config: &Config) -> Epoch;
 
-    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> lmdb::Database;
+    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> Database;
 
     fn load<E: EthSpec>(
         db: &SlasherDB<E>,
@@ -156,13 +157,12 @@ pub trait TargetArrayChunk: Sized + serde::Serialize + serde::de::DeserializeOwn
         config: &Config,
     ) -> Result<Option<Self>, Error> {
         let disk_key = config.disk_key(validator_chunk_index, chunk_index);
-        let chunk_bytes = match txn.get(Self::select_db(db), &disk_key.to_be_bytes()) {
-            Ok(chunk_bytes) => chunk_bytes,
-            Err(lmdb::Error::NotFound) => return Ok(None),
-            Err(e) => return Err(e.into()),
+        let chunk_bytes = match txn.get(Self::select_db(db), &disk_key.to_be_bytes())? {
+            Some(chunk_bytes) => chunk_bytes,
+            None => return Ok(None),
         };
 
-        let chunk = bincode::deserialize_from(ZlibDecoder::new(chunk_bytes))?;
+        let chunk = bincode::deserialize_from(ZlibDecoder::new(chunk_bytes.as_ref()))?;
 
         Ok(Some(chunk))
     }
@@ -188,7 +188,6 @@ pub trait TargetArrayChunk: Sized + serde::Serialize + serde::de::DeserializeOwn
             Self::select_db(db),
             &disk_key.to_be_bytes(),
             &compressed_value,
-            SlasherDB::<E>::write_flags(),
         )?;
         Ok(())
     }
@@ -292,7 +291,7 @@ impl TargetArrayChunk for MinTargetChunk {
         start_epoch / chunk_size * chunk_size - 1
     }
 
-    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> lmdb::Database {
+    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> Database {
         db.min_targets_db
     }
 }
@@ -391,7 +390,7 @@ impl TargetArrayChunk for MaxTargetChunk {
         (start_epoch / chunk_size + 1) * chunk_size
     }
 
-    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> lmdb::Database {
+    fn select_db<E: EthSpec>(db: &SlasherDB<E>) -> Database {
         db.max_targets_db
     }
 }
@@ -486,7 +485,7 @@ pub fn update<E: EthSpec>(
     db: &SlasherDB<E>,
     txn: &mut RwTransaction<'_>,
     validator_chunk_index: usize,
-    batch: Vec<Arc<(IndexedAttestation<E>, AttesterRecord)>>,
+    batch: Vec<Arc<(IndexedAttestation<E>, AttesterRecord, Instant)>>,
     current_epoch: Epoch,
     config: &Config,
 ) -> Result<HashSet<AttesterSlashing<E>>, Error> {
@@ -573,7 +572,10 @@ pub fn update_array<E: EthSpec, T: TargetArrayChunk>(
     db: &SlasherDB<E>,
     txn: &mut RwTransaction<'_>,
     validator_chunk_index: usize,
-    chunk_attestations: &BTreeMap<usize, Vec<Arc<(IndexedAttestation<E>, AttesterRecord)>>>,
+    chunk_attestations: &BTreeMap<
+        usize,
+        Vec<Arc<(IndexedAttestation<E>, AttesterRecord, Instant)>>,
+    >,
     current_epoch: Epoch,
     config: &Config,
 ) -> Result<HashSet<AttesterSlashing<E>>, Error> {
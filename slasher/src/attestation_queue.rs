@@ -1,7 +1,12 @@
 use crate::{AttesterRecord, Config};
 use parking_lot::Mutex;
+use ssz::Encode;
+use std::cmp::max;
 use std::collections::BTreeSet;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use types::{EthSpec, IndexedAttestation};
 
 /// Staging area for attestations received from the network.
@@ -11,6 +16,8 @@ use types::{EthSpec, IndexedAttestation};
 pub struct AttestationQueue<E: EthSpec> {
     /// All attestations (unique) for storage on disk.
     pub queue: Mutex<AttestationBatch<E>>,
+    /// The memory used by the attestations in `queue`, only updated while it is locked.
+    bytes: AtomicUsize,
 }
 
 /// Attestations grouped by validator index range.
@@ -20,9 +27,11 @@ pub struct GroupedAttestations<E: EthSpec> {
 }
 
 /// A queue of attestations for a range of validator indices.
+///
+/// Each attestation is kept with its `AttesterRecord` and the time at which it was queued.
 #[derive(Debug, Default)]
 pub struct AttestationBatch<E: EthSpec> {
-    pub attestations: Vec<Arc<(IndexedAttestation<E>, AttesterRecord)>>,
+    pub attestations: Vec<Arc<(IndexedAttestation<E>, AttesterRecord, Instant)>>,
 }
 
 impl<E: EthSpec> AttestationBatch<E> {
@@ -34,6 +43,14 @@ impl<E: EthSpec> AttestationBatch<E> {
         self.attestations.is_empty()
     }
 
+    /// The memory used by the attestations of the batch, as counted by the queue.
+    fn bytes(&self) -> usize {
+        self.attestations
+            .iter()
+            .map(|attestation| AttestationQueue::<E>::attestation_bytes(&attestation.0))
+            .sum()
+    }
+
     /// Group the attestations by validator index.
     pub fn group_by_validator_index(self, config: &Config) -> GroupedAttestations<E> {
         let mut grouped_attestations = GroupedAttestations { subqueues: vec![] };
@@ -66,21 +83,66 @@ impl<E: EthSpec> AttestationBatch<E> {
 }
 
 impl<E: EthSpec> AttestationQueue<E> {
+    /// The memory used by `attestation` while it is queued: its SSZ size, which covers the
+    /// attesting indices on the heap, plus the size of its entry in the queue.
+    pub fn attestation_bytes(attestation: &IndexedAttestation<E>) -> usize {
+        attestation.ssz_bytes_len() + size_of::<(IndexedAttestation<E>, AttesterRecord, Instant)>()
+    }
+
     /// Add an attestation to the queue.
     pub fn queue(&self, attestation: IndexedAttestation<E>) {
+        let bytes = Self::attestation_bytes(&attestation);
         let attester_record = AttesterRecord::from(attestation.clone());
-        self.queue
-            .lock()
+        let mut queue = self.queue.lock();
+        queue
             .attestations
-            .push(Arc::new((attestation, attester_record)));
+            .push(Arc::new((attestation, attester_record, Instant::now())));
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
     }
 
-    pub fn dequeue(&self) -> AttestationBatch<E> {
-        std::mem::take(&mut self.queue.lock())
+    /// Remove the oldest attestations from the queue, for as long as they fit within `max_bytes`,
+    /// or all of them if `None`.
+    ///
+    /// At least one attestation is removed if the queue isn't empty, however large it is.
+    pub fn dequeue(&self, max_bytes: Option<usize>) -> AttestationBatch<E> {
+        let mut queue = self.queue.lock();
+        let len = max_bytes.map_or(queue.len(), |max_bytes| {
+            let mut bytes = 0;
+            let len = queue
+                .attestations
+                .iter()
+                .take_while(|attestation| {
+                    bytes += Self::attestation_bytes(&attestation.0);
+                    bytes <= max_bytes
+                })
+                .count();
+            max(len, 1)
+        });
+
+        let batch = if queue.len() > len {
+            let newer = queue.attestations.split_off(len);
+            AttestationBatch {
+                attestations: std::mem::replace(&mut queue.attestations, newer),
+            }
+        } else {
+            std::mem::take(&mut *queue)
+        };
+        self.bytes.fetch_sub(batch.bytes(), Ordering::Relaxed);
+        batch
     }
 
+    /// Return attestations to the front of the queue, ahead of those queued since `dequeue`.
     pub fn requeue(&self, batch: AttestationBatch<E>) {
-        self.queue.lock().attestations.extend(batch.attestations);
+        let bytes = batch.bytes();
+        let mut queue = self.queue.lock();
+        let newer = std::mem::replace(&mut queue.attestations, batch.attestations);
+        queue.attestations.extend(newer);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// The memory used by the queued attestations.
+    pub fn bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
     }
 
     pub fn len(&self) -> usize {
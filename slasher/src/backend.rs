@@ -0,0 +1,437 @@
+//! Run-time selection of the key-value store that backs the `SlasherDB`.
+//!
+//! Each backend is wrapped by the enums in this module, which present a common interface of
+//! tables and read-write transactions to the rest of the slasher. A database written by one
+//! backend cannot be read by another, so the backend of an existing database is detected when it
+//! is opened.
+use crate::config::DatabaseBackend;
+use crate::utils::TxnOptional;
+use crate::{Config, Error};
+use lmdb::{Cursor, DatabaseFlags, Transaction, WriteFlags};
+#[cfg(feature = "redb")]
+use redb::{ReadableTable, TableDefinition};
+use std::borrow::Cow;
+use std::fmt;
+#[cfg(feature = "redb")]
+use std::ops::Bound;
+use std::path::Path;
+
+/// The name of the redb database file within the database directory.
+pub const REDB_FILENAME: &str = "slasher.redb";
+
+/// The name of the data file which LMDB creates within the database directory.
+const LMDB_DATA_FILENAME: &str = "data.mdb";
+
+const MEGABYTE: usize = 1 << 20;
+
+impl DatabaseBackend {
+    /// Return the backend of the database in the directory at `path`, or `None` if there is no
+    /// database there.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if path.join(REDB_FILENAME).exists() {
+            Some(DatabaseBackend::Redb)
+        } else if path.join(LMDB_DATA_FILENAME).exists() {
+            Some(DatabaseBackend::Lmdb)
+        } else {
+            None
+        }
+    }
+}
+
+/// An open database, which holds all of the slasher's tables.
+pub enum Environment {
+    Lmdb(lmdb::Environment),
+    #[cfg(feature = "redb")]
+    Redb(redb::Database),
+}
+
+/// A handle to a table within an `Environment`.
+#[derive(Debug, Clone, Copy)]
+pub enum Database {
+    Lmdb(lmdb::Database),
+    /// redb tables are opened by name within each transaction.
+    #[cfg(feature = "redb")]
+    Redb(&'static str),
+}
+
+/// A read-write transaction, whose changes are discarded unless it is committed.
+#[derive(Debug)]
+pub struct RwTransaction<'env> {
+    txn: RwTxn<'env>,
+    /// The number of bytes of keys and values written by `put`.
+    bytes_written: u64,
+}
+
+enum RwTxn<'env> {
+    Lmdb(lmdb::RwTransaction<'env>),
+    #[cfg(feature = "redb")]
+    Redb(redb::WriteTransaction<'env>),
+}
+
+impl Environment {
+    /// Open the database in `config.database_path` with the configured backend, creating it if
+    /// it does not already exist.
+    ///
+    /// LMDB is limited to `max_dbs` tables, and to `config.max_db_size_mbs` on disk.
+    pub fn open(config: &Config, max_dbs: u32) -> Result<Self, Error> {
+        std::fs::create_dir_all(&config.database_path)?;
+
+        match config.backend {
+            DatabaseBackend::Lmdb => {
+                let env = lmdb::Environment::new()
+                    .set_max_dbs(max_dbs)
+                    .set_map_size(config.max_db_size_mbs * MEGABYTE)
+                    .open_with_permissions(&config.database_path, 0o600)?;
+                Ok(Environment::Lmdb(env))
+            }
+            #[cfg(feature = "redb")]
+            DatabaseBackend::Redb => {
+                let db = redb::Database::create(config.database_path.join(REDB_FILENAME))
+                    .map_err(redb::Error::from)?;
+                Ok(Environment::Redb(db))
+            }
+            #[cfg(not(feature = "redb"))]
+            DatabaseBackend::Redb => Err(Error::DatabaseBackendNotCompiled(DatabaseBackend::Redb)),
+        }
+    }
+
+    /// Open the table called `name`, creating it if it does not already exist.
+    pub fn create_db(&self, name: &'static str) -> Result<Database, Error> {
+        match self {
+            Environment::Lmdb(env) => Ok(Database::Lmdb(
+                env.create_db(Some(name), DatabaseFlags::default())?,
+            )),
+            #[cfg(feature = "redb")]
+            Environment::Redb(db) => {
+                // Create the table up-front so that it exists for every later transaction.
+                let txn = db.begin_write().map_err(redb::Error::from)?;
+                txn.open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+                txn.commit().map_err(redb::Error::from)?;
+                Ok(Database::Redb(name))
+            }
+        }
+    }
+
+    pub fn begin_rw_txn(&self) -> Result<RwTransaction<'_>, Error> {
+        let txn = match self {
+            Environment::Lmdb(env) => RwTxn::Lmdb(env.begin_rw_txn()?),
+            #[cfg(feature = "redb")]
+            Environment::Redb(db) => RwTxn::Redb(db.begin_write().map_err(redb::Error::from)?),
+        };
+        Ok(RwTransaction {
+            txn,
+            bytes_written: 0,
+        })
+    }
+}
+
+impl<'env> RwTransaction<'env> {
+    /// The number of bytes written to the database by this transaction so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Retrieve the value of `key` in `db`, if it exists.
+    pub fn get<K: AsRef<[u8]>>(
+        &self,
+        db: Database,
+        key: &K,
+    ) -> Result<Option<Cow<'_, [u8]>>, Error> {
+        match (&self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                Ok(txn.get(db, key).optional()?.map(Cow::Borrowed))
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+                let value = table.get(key.as_ref()).map_err(redb::Error::from)?;
+                Ok(value.map(|value| Cow::Owned(value.value().to_vec())))
+            }
+            #[cfg(feature = "redb")]
+            _ => Err(Error::DatabaseHandleMismatch),
+        }
+    }
+
+    /// Store `value` under `key` in `db`, replacing any existing value.
+    pub fn put<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &mut self,
+        db: Database,
+        key: &K,
+        value: &V,
+    ) -> Result<(), Error> {
+        self.bytes_written += (key.as_ref().len() + value.as_ref().len()) as u64;
+        match (&mut self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                txn.put(db, key, value, WriteFlags::default())?;
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let mut table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+                table
+                    .insert(key.as_ref(), value.as_ref())
+                    .map_err(redb::Error::from)?;
+            }
+            #[cfg(feature = "redb")]
+            _ => return Err(Error::DatabaseHandleMismatch),
+        }
+        Ok(())
+    }
+
+    /// Call `f` with the key and value of each entry of `db`, in key order, for as long as it
+    /// returns `true`.
+    pub fn for_each(
+        &self,
+        db: Database,
+        mut f: impl FnMut(&[u8], &[u8]) -> Result<bool, Error>,
+    ) -> Result<(), Error> {
+        match (&self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                let mut cursor = txn.open_ro_cursor(db)?;
+
+                let mut entry = cursor.get(None, None, lmdb_sys::MDB_FIRST).optional()?;
+                while let Some((key_bytes, value_bytes)) = entry {
+                    if !f(key_bytes.ok_or(Error::MissingKey)?, value_bytes)? {
+                        break;
+                    }
+                    entry = cursor.get(None, None, lmdb_sys::MDB_NEXT).optional()?;
+                }
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+
+                for entry in table.iter().map_err(redb::Error::from)? {
+                    let (key, value) = entry.map_err(redb::Error::from)?;
+                    if !f(key.value(), value.value())? {
+                        break;
+                    }
+                }
+            }
+            #[cfg(feature = "redb")]
+            _ => return Err(Error::DatabaseHandleMismatch),
+        }
+        Ok(())
+    }
+
+    /// Remove `key` from `db`, if it exists.
+    pub fn delete<K: AsRef<[u8]>>(&mut self, db: Database, key: &K) -> Result<(), Error> {
+        match (&mut self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                txn.del(db, key, None).optional()?;
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let mut table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+                table.remove(key.as_ref()).map_err(redb::Error::from)?;
+            }
+            #[cfg(feature = "redb")]
+            _ => return Err(Error::DatabaseHandleMismatch),
+        }
+        Ok(())
+    }
+
+    /// Replace the value of each of the next `limit` entries of `db` after the key `start_after`
+    /// (or from the start of `db`) with the value returned by `f`, if it returns one.
+    ///
+    /// Returns the key of the last entry visited if `limit` was reached, from which the next call
+    /// continues, or `None` once the end of `db` has been reached.
+    pub fn update_each(
+        &mut self,
+        db: Database,
+        start_after: Option<&[u8]>,
+        limit: usize,
+        mut f: impl FnMut(&[u8], &[u8]) -> Result<Option<Vec<u8>>, Error>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut bytes_written = 0;
+        let mut last_key = None;
+        match (&mut self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                let mut cursor = txn.open_rw_cursor(db)?;
+
+                let mut entry = match start_after {
+                    Some(start_after) => {
+                        match cursor
+                            .get(Some(start_after), None, lmdb_sys::MDB_SET_RANGE)
+                            .optional()?
+                        {
+                            Some((Some(key_bytes), _)) if key_bytes == start_after => {
+                                cursor.get(None, None, lmdb_sys::MDB_NEXT).optional()?
+                            }
+                            entry => entry,
+                        }
+                    }
+                    None => cursor.get(None, None, lmdb_sys::MDB_FIRST).optional()?,
+                };
+
+                // Values are replaced in place, so that the chunk is never held in memory.
+                let mut count = 0;
+                while let Some((key_bytes, value_bytes)) = entry {
+                    let key = key_bytes.ok_or(Error::MissingKey)?.to_vec();
+                    if let Some(value) = f(&key, value_bytes)? {
+                        bytes_written += (key.len() + value.len()) as u64;
+                        cursor.put(&key, &value, WriteFlags::CURRENT)?;
+                    }
+
+                    count += 1;
+                    if count == limit {
+                        last_key = Some(key);
+                        break;
+                    }
+                    entry = cursor.get(None, None, lmdb_sys::MDB_NEXT).optional()?;
+                }
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let mut table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+
+                // The new values of the chunk are collected first, as the table can't be modified
+                // while iterating.
+                let mut updates = vec![];
+                {
+                    let entries = match start_after {
+                        Some(start_after) => {
+                            table.range::<&[u8]>((Bound::Excluded(start_after), Bound::Unbounded))
+                        }
+                        None => table.iter(),
+                    }
+                    .map_err(redb::Error::from)?;
+
+                    for (count, entry) in entries.enumerate() {
+                        let (key, value) = entry.map_err(redb::Error::from)?;
+                        if let Some(value) = f(key.value(), value.value())? {
+                            updates.push((key.value().to_vec(), value));
+                        }
+                        if count + 1 == limit {
+                            last_key = Some(key.value().to_vec());
+                            break;
+                        }
+                    }
+                }
+
+                for (key, value) in updates {
+                    bytes_written += (key.len() + value.len()) as u64;
+                    table
+                        .insert(key.as_slice(), value.as_slice())
+                        .map_err(redb::Error::from)?;
+                }
+            }
+            #[cfg(feature = "redb")]
+            _ => return Err(Error::DatabaseHandleMismatch),
+        }
+        self.bytes_written += bytes_written;
+        Ok(last_key)
+    }
+
+    /// Delete entries from the start of `db`, in key order, for as long as `predicate` returns
+    /// `true` for their keys.
+    pub fn delete_while(
+        &mut self,
+        db: Database,
+        mut predicate: impl FnMut(&[u8]) -> Result<bool, Error>,
+    ) -> Result<(), Error> {
+        match (&mut self.txn, db) {
+            (RwTxn::Lmdb(txn), Database::Lmdb(db)) => {
+                let mut cursor = txn.open_rw_cursor(db)?;
+
+                // Position cursor at first key, bailing out if the database is empty.
+                if cursor
+                    .get(None, None, lmdb_sys::MDB_FIRST)
+                    .optional()?
+                    .is_none()
+                {
+                    return Ok(());
+                }
+
+                loop {
+                    let key_bytes = cursor
+                        .get(None, None, lmdb_sys::MDB_GET_CURRENT)?
+                        .0
+                        .ok_or(Error::MissingKey)?;
+
+                    if !predicate(key_bytes)? {
+                        break;
+                    }
+                    cursor.del(WriteFlags::default())?;
+
+                    // End the loop if there is no next entry.
+                    if cursor
+                        .get(None, None, lmdb_sys::MDB_NEXT)
+                        .optional()?
+                        .is_none()
+                    {
+                        break;
+                    }
+                }
+            }
+            #[cfg(feature = "redb")]
+            (RwTxn::Redb(txn), Database::Redb(name)) => {
+                let mut table = txn
+                    .open_table(table_definition(name))
+                    .map_err(redb::Error::from)?;
+
+                // The keys are collected first, as the table can't be modified while iterating.
+                let mut keys = vec![];
+                for entry in table.iter().map_err(redb::Error::from)? {
+                    let (key, _) = entry.map_err(redb::Error::from)?;
+                    if !predicate(key.value())? {
+                        break;
+                    }
+                    keys.push(key.value().to_vec());
+                }
+
+                for key in keys {
+                    table.remove(key.as_slice()).map_err(redb::Error::from)?;
+                }
+            }
+            #[cfg(feature = "redb")]
+            _ => return Err(Error::DatabaseHandleMismatch),
+        }
+        Ok(())
+    }
+
+    pub fn commit(self) -> Result<(), Error> {
+        match self.txn {
+            RwTxn::Lmdb(txn) => txn.commit()?,
+            #[cfg(feature = "redb")]
+            RwTxn::Redb(txn) => txn.commit().map_err(redb::Error::from)?,
+        }
+        Ok(())
+    }
+}
+
+/// All redb tables map byte-string keys to byte-string values, as LMDB does.
+#[cfg(feature = "redb")]
+fn table_definition(name: &str) -> TableDefinition<'_, &'static [u8], &'static [u8]> {
+    TableDefinition::new(name)
+}
+
+impl fmt::Debug for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Environment::Lmdb(env) => f.debug_tuple("Lmdb").field(env).finish(),
+            #[cfg(feature = "redb")]
+            Environment::Redb(_) => f.debug_tuple("Redb").finish(),
+        }
+    }
+}
+
+impl<'env> fmt::Debug for RwTxn<'env> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RwTxn::Lmdb(_) => f.debug_tuple("Lmdb").finish(),
+            #[cfg(feature = "redb")]
+            RwTxn::Redb(_) => f.debug_tuple("Redb").finish(),
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use parking_lot::Mutex;
+use ssz::Encode;
+use std::mem::size_of;
 use types::SignedBeaconBlockHeader;
 
 #[derive(Debug, Default)]
@@ -7,6 +9,12 @@ pub struct BlockQueue {
 }
 
 impl BlockQueue {
+    /// The memory used by `block_header` while it is queued: its SSZ size plus the size of its
+    /// entry in the queue.
+    pub fn block_header_bytes(block_header: &SignedBeaconBlockHeader) -> usize {
+        block_header.ssz_bytes_len() + size_of::<SignedBeaconBlockHeader>()
+    }
+
     pub fn queue(&self, block_header: SignedBeaconBlockHeader) {
         self.blocks.lock().push(block_header)
     }
@@ -16,6 +24,15 @@ impl BlockQueue {
         std::mem::replace(&mut *blocks, vec![])
     }
 
+    /// The memory used by the queued block headers.
+    pub fn bytes(&self) -> usize {
+        self.blocks
+            .lock()
+            .iter()
+            .map(Self::block_header_bytes)
+            .sum()
+    }
+
     pub fn len(&self) -> usize {
         self.blocks.lock().len()
     }
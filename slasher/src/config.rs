@@ -1,6 +1,8 @@
 use crate::Error;
 use serde_derive::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 use types::{Epoch, EthSpec, IndexedAttestation};
 
 pub const DEFAULT_CHUNK_SIZE: usize = 16;
@@ -8,6 +10,9 @@ pub const DEFAULT_VALIDATOR_CHUNK_SIZE: usize = 256;
 pub const DEFAULT_HISTORY_LENGTH: usize = 4096;
 pub const DEFAULT_UPDATE_PERIOD: u64 = 12;
 pub const DEFAULT_MAX_DB_SIZE: usize = 256 * 1024; // 256 GiB
+pub const DEFAULT_BROADCAST: bool = true;
+
+const MEGABYTE: usize = 1 << 20;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -20,6 +25,64 @@ pub struct Config {
     pub update_period: u64,
     /// Maximum size of the LMDB database in megabytes.
     pub max_db_size_mbs: usize,
+    /// The key-value store to keep the database in.
+    ///
+    /// Not stored with the rest of the config in the database, as it doesn't change the meaning
+    /// of the data and the backend of an existing database is detected instead.
+    #[serde(skip)]
+    pub backend: DatabaseBackend,
+    /// Whether to publish the slashings found on gossip as soon as they are found, rather than
+    /// waiting to include them in a block.
+    ///
+    /// Not stored in the database, as it has no effect on the data.
+    #[serde(skip)]
+    pub broadcast: bool,
+    /// The epoch to supply historical blocks and attestations from when the slasher starts, if
+    /// any.
+    ///
+    /// Not stored in the database, as it has no effect on the data.
+    #[serde(skip)]
+    pub backfill_epoch: Option<Epoch>,
+    /// The memory in megabytes which queued attestations and block headers may use, if limited.
+    ///
+    /// A quarter of the budget is for the batch of attestations being processed, and the rest for
+    /// the attestations and block headers waiting in the queues. Not stored in the database, as it
+    /// has no effect on the data.
+    #[serde(skip)]
+    pub memory_budget_mbs: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Lmdb,
+    Redb,
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self {
+        DatabaseBackend::Lmdb
+    }
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lmdb" => Ok(DatabaseBackend::Lmdb),
+            "redb" => Ok(DatabaseBackend::Redb),
+            other => Err(format!("Unknown slasher backend: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseBackend::Lmdb => write!(f, "lmdb"),
+            DatabaseBackend::Redb => write!(f, "redb"),
+        }
+    }
 }
 
 impl Config {
@@ -31,6 +94,10 @@ impl Config {
             history_length: DEFAULT_HISTORY_LENGTH,
             update_period: DEFAULT_UPDATE_PERIOD,
             max_db_size_mbs: DEFAULT_MAX_DB_SIZE,
+            backend: DatabaseBackend::default(),
+            broadcast: DEFAULT_BROADCAST,
+            backfill_epoch: None,
+            memory_budget_mbs: None,
         }
     }
 
@@ -39,6 +106,7 @@ impl Config {
             || self.validator_chunk_size == 0
             || self.history_length == 0
             || self.max_db_size_mbs == 0
+            || self.memory_budget_mbs == Some(0)
         {
             Err(Error::ConfigInvalidZeroParameter {
                 config: self.clone(),
@@ -53,6 +121,18 @@ impl Config {
         }
     }
 
+    /// The most memory in bytes which the attestations and block headers waiting to be processed
+    /// may use, beyond which new attestations are dropped.
+    pub fn max_queued_bytes(&self) -> Option<usize> {
+        self.memory_budget_mbs.map(|budget| 3 * budget * MEGABYTE / 4)
+    }
+
+    /// The most memory in bytes which the attestations processed in one batch may use, beyond
+    /// which attestations are deferred to the next batch.
+    pub fn max_batch_bytes(&self) -> Option<usize> {
+        self.memory_budget_mbs.map(|budget| budget * MEGABYTE / 4)
+    }
+
     pub fn is_compatible(&self, other: &Config) -> bool {
         self.chunk_size == other.chunk_size
             && self.validator_chunk_size == other.validator_chunk_size
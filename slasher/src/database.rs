@@ -1,12 +1,17 @@
+use crate::backend::{Database, Environment, RwTransaction};
 use crate::{
-    utils::{TxnMapFull, TxnOptional},
-    AttesterRecord, AttesterSlashingStatus, Config, Error, ProposerSlashingStatus,
+    utils::TxnMapFull, AttesterRecord, AttesterSlashingStatus, Config, DatabaseBackend, Error,
+    ProposerSlashingStatus,
 };
 use byteorder::{BigEndian, ByteOrder};
-use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
+use std::fmt;
+use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 use types::{
     Epoch, EthSpec, Hash256, IndexedAttestation, ProposerSlashing, SignedBeaconBlockHeader, Slot,
 };
@@ -38,12 +43,124 @@ const LMDB_MAX_DBS: u32 = 7;
 const METADATA_VERSION_KEY: &[u8] = &[0];
 /// Constant key under which the slasher configuration is stored in the `metadata_db`.
 const METADATA_CONFIG_KEY: &[u8] = &[1];
+/// Constant key under which the progress of an unfinished migration is stored in the
+/// `metadata_db`.
+const METADATA_MIGRATION_PROGRESS_KEY: &[u8] = &[2];
 
 const ATTESTER_KEY_SIZE: usize = 16;
 const PROPOSER_KEY_SIZE: usize = 16;
 const CURRENT_EPOCH_KEY_SIZE: usize = 8;
 const INDEXED_ATTESTATION_KEY_SIZE: usize = 40;
-const MEGABYTE: usize = 1 << 20;
+
+/// The first item of an export, describing the database it was taken from.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportHeader {
+    schema_version: u64,
+    config: Config,
+}
+
+/// A single entry of an exported table.
+///
+/// Records are written as `Some(record)`, followed by `None` to mark the end of the export.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportRecord {
+    table: String,
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+/// A rough rate at which migrations rewrite records, used to estimate their duration.
+const ESTIMATED_RECORDS_PER_SECOND: u64 = 50_000;
+
+/// The number of records rewritten by each transaction of a migration, which bounds the memory
+/// used by the migration of a large table.
+pub const MIGRATION_CHUNK_SIZE: usize = 1_000;
+
+/// How far an unfinished migration has got, committed along with each chunk of records.
+#[derive(Debug, Serialize, Deserialize)]
+struct MigrationProgress {
+    /// The schema version that the migration is to.
+    to: u64,
+    /// The index of the table being rewritten, within the `tables` of the migration.
+    table: usize,
+    /// The key of the last record rewritten in that table, if any.
+    last_key: Option<Vec<u8>>,
+}
+
+/// Returns the new value of a record from its key and old value, or `None` to leave it unchanged.
+pub type RewriteRecord = fn(&[u8], &[u8]) -> Result<Option<Vec<u8>>, Error>;
+
+/// A migration from one schema version to the next.
+pub struct SchemaMigration {
+    pub from: u64,
+    pub to: u64,
+    /// A summary of the change, for operators.
+    pub description: &'static str,
+    /// The tables whose records are rewritten by the migration.
+    pub tables: &'static [&'static str],
+    /// Applied to every record of `tables`.
+    pub rewrite: RewriteRecord,
+}
+
+impl PartialEq for SchemaMigration {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from && self.to == other.to
+    }
+}
+
+impl fmt::Debug for SchemaMigration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SchemaMigration")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("description", &self.description)
+            .field("tables", &self.tables)
+            .finish()
+    }
+}
+
+/// All the supported migrations, which are applied by `lighthouse slasher migrate` and never when
+/// the database is opened.
+pub const SCHEMA_MIGRATIONS: &[SchemaMigration] = &[];
+
+/// A migration and the records it will rewrite.
+#[derive(Debug, PartialEq)]
+pub struct SchemaMigrationStep {
+    pub migration: &'static SchemaMigration,
+    pub records: u64,
+    /// The total size of the keys and values of those records.
+    pub bytes: u64,
+}
+
+impl SchemaMigrationStep {
+    /// A rough estimate of the time that the step will take.
+    pub fn estimated_duration(&self) -> Duration {
+        Duration::from_secs(self.records / ESTIMATED_RECORDS_PER_SECOND)
+    }
+}
+
+/// The steps of a migration to the current schema version, which may be empty.
+#[derive(Debug, PartialEq)]
+pub struct SchemaMigrationPlan {
+    pub from: u64,
+    pub to: u64,
+    pub steps: Vec<SchemaMigrationStep>,
+}
+
+impl SchemaMigrationPlan {
+    /// A rough estimate of the time that the whole migration will take.
+    pub fn estimated_duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .map(SchemaMigrationStep::estimated_duration)
+            .sum()
+    }
+
+    /// The total size of the records which the migration will rewrite.
+    pub fn bytes(&self) -> u64 {
+        self.steps.iter().map(|step| step.bytes).sum()
+    }
+}
 
 #[derive(Debug)]
 pub struct SlasherDB<E: EthSpec> {
@@ -180,37 +297,16 @@ impl AsRef<[u8]> for IndexedAttestationKey {
 
 impl<E: EthSpec> SlasherDB<E> {
     pub fn open(config: Arc<Config>) -> Result<Self, Error> {
-        std::fs::create_dir_all(&config.database_path)?;
-        let env = Environment::new()
-            .set_max_dbs(LMDB_MAX_DBS)
-            .set_map_size(config.max_db_size_mbs * MEGABYTE)
-            .open_with_permissions(&config.database_path, 0o600)?;
-        let indexed_attestation_db =
-            env.create_db(Some(INDEXED_ATTESTATION_DB), Self::db_flags())?;
-        let attesters_db = env.create_db(Some(ATTESTERS_DB), Self::db_flags())?;
-        let min_targets_db = env.create_db(Some(MIN_TARGETS_DB), Self::db_flags())?;
-        let max_targets_db = env.create_db(Some(MAX_TARGETS_DB), Self::db_flags())?;
-        let current_epochs_db = env.create_db(Some(CURRENT_EPOCHS_DB), Self::db_flags())?;
-        let proposers_db = env.create_db(Some(PROPOSERS_DB), Self::db_flags())?;
-        let metadata_db = env.create_db(Some(METADATA_DB), Self::db_flags())?;
-
-        let db = Self {
-            env,
-            indexed_attestation_db,
-            attesters_db,
-            min_targets_db,
-            max_targets_db,
-            current_epochs_db,
-            proposers_db,
-            metadata_db,
-            config,
-            _phantom: PhantomData,
-        };
-
+        let db = Self::open_without_schema_migration(config)?;
         let mut txn = db.begin_rw_txn()?;
 
         if let Some(schema_version) = db.load_schema_version(&mut txn)? {
-            if schema_version != CURRENT_SCHEMA_VERSION {
+            if schema_version < CURRENT_SCHEMA_VERSION {
+                return Err(Error::SchemaMigrationRequired {
+                    database_schema_version: schema_version,
+                    software_schema_version: CURRENT_SCHEMA_VERSION,
+                });
+            } else if schema_version > CURRENT_SCHEMA_VERSION {
                 return Err(Error::IncompatibleSchemaVersion {
                     database_schema_version: schema_version,
                     software_schema_version: CURRENT_SCHEMA_VERSION,
@@ -233,12 +329,39 @@ impl<E: EthSpec> SlasherDB<E> {
         Ok(db)
     }
 
-    pub fn db_flags() -> DatabaseFlags {
-        DatabaseFlags::default()
-    }
+    /// Open the database without checking its schema version or config, so that it may be
+    /// migrated by `apply_schema_migration`.
+    pub fn open_without_schema_migration(config: Arc<Config>) -> Result<Self, Error> {
+        if let Some(on_disk) = DatabaseBackend::detect(&config.database_path) {
+            if on_disk != config.backend {
+                return Err(Error::DatabaseBackendMismatch {
+                    on_disk,
+                    config: config.backend,
+                });
+            }
+        }
+
+        let env = Environment::open(&config, LMDB_MAX_DBS)?;
+        let indexed_attestation_db = env.create_db(INDEXED_ATTESTATION_DB)?;
+        let attesters_db = env.create_db(ATTESTERS_DB)?;
+        let min_targets_db = env.create_db(MIN_TARGETS_DB)?;
+        let max_targets_db = env.create_db(MAX_TARGETS_DB)?;
+        let current_epochs_db = env.create_db(CURRENT_EPOCHS_DB)?;
+        let proposers_db = env.create_db(PROPOSERS_DB)?;
+        let metadata_db = env.create_db(METADATA_DB)?;
 
-    pub fn write_flags() -> WriteFlags {
-        WriteFlags::default()
+        Ok(Self {
+            env,
+            indexed_attestation_db,
+            attesters_db,
+            min_targets_db,
+            max_targets_db,
+            current_epochs_db,
+            proposers_db,
+            metadata_db,
+            config,
+            _phantom: PhantomData,
+        })
     }
 
     pub fn begin_rw_txn(&self) -> Result<RwTransaction<'_>, Error> {
@@ -247,9 +370,8 @@ impl<E: EthSpec> SlasherDB<E> {
 
     pub fn load_schema_version(&self, txn: &mut RwTransaction<'_>) -> Result<Option<u64>, Error> {
         Ok(txn
-            .get(self.metadata_db, &METADATA_VERSION_KEY)
-            .optional()?
-            .map(bincode::deserialize)
+            .get(self.metadata_db, &METADATA_VERSION_KEY)?
+            .map(|bytes| bincode::deserialize(&bytes))
             .transpose()?)
     }
 
@@ -258,16 +380,14 @@ impl<E: EthSpec> SlasherDB<E> {
             self.metadata_db,
             &METADATA_VERSION_KEY,
             &bincode::serialize(&CURRENT_SCHEMA_VERSION)?,
-            Self::write_flags(),
         )?;
         Ok(())
     }
 
     pub fn load_config(&self, txn: &mut RwTransaction<'_>) -> Result<Option<Config>, Error> {
         Ok(txn
-            .get(self.metadata_db, &METADATA_CONFIG_KEY)
-            .optional()?
-            .map(bincode::deserialize)
+            .get(self.metadata_db, &METADATA_CONFIG_KEY)?
+            .map(|bytes| bincode::deserialize(&bytes))
             .transpose()?)
     }
 
@@ -276,7 +396,6 @@ impl<E: EthSpec> SlasherDB<E> {
             self.metadata_db,
             &METADATA_CONFIG_KEY,
             &bincode::serialize(self.config.as_ref())?,
-            Self::write_flags(),
         )?;
         Ok(())
     }
@@ -290,9 +409,8 @@ impl<E: EthSpec> SlasherDB<E> {
             .get(
                 self.current_epochs_db,
                 &CurrentEpochKey::new(validator_index),
-            )
-            .optional()?
-            .map(Epoch::from_ssz_bytes)
+            )?
+            .map(|bytes| Epoch::from_ssz_bytes(&bytes))
             .transpose()?)
     }
 
@@ -306,7 +424,6 @@ impl<E: EthSpec> SlasherDB<E> {
             self.current_epochs_db,
             &CurrentEpochKey::new(validator_index),
             &current_epoch.as_ssz_bytes(),
-            Self::write_flags(),
         )?;
         Ok(())
     }
@@ -323,12 +440,7 @@ impl<E: EthSpec> SlasherDB<E> {
         );
         let data = indexed_attestation.as_ssz_bytes();
 
-        txn.put(
-            self.indexed_attestation_db,
-            &key,
-            &data,
-            Self::write_flags(),
-        )?;
+        txn.put(self.indexed_attestation_db, &key, &data)?;
         Ok(())
     }
 
@@ -339,13 +451,12 @@ impl<E: EthSpec> SlasherDB<E> {
         indexed_attestation_hash: Hash256,
     ) -> Result<IndexedAttestation<E>, Error> {
         let key = IndexedAttestationKey::new(target_epoch, indexed_attestation_hash);
-        let bytes = txn
-            .get(self.indexed_attestation_db, &key)
-            .optional()?
-            .ok_or_else(|| Error::MissingIndexedAttestation {
+        let bytes = txn.get(self.indexed_attestation_db, &key)?.ok_or_else(|| {
+            Error::MissingIndexedAttestation {
                 root: indexed_attestation_hash,
-            })?;
-        Ok(IndexedAttestation::from_ssz_bytes(bytes)?)
+            }
+        })?;
+        Ok(IndexedAttestation::from_ssz_bytes(&bytes)?)
     }
 
     pub fn check_and_update_attester_record(
@@ -386,7 +497,6 @@ impl<E: EthSpec> SlasherDB<E> {
                 self.attesters_db,
                 &AttesterKey::new(validator_index, target_epoch),
                 &record.as_ssz_bytes(),
-                Self::write_flags(),
             )?;
             Ok(AttesterSlashingStatus::NotSlashable)
         }
@@ -415,9 +525,8 @@ impl<E: EthSpec> SlasherDB<E> {
     ) -> Result<Option<AttesterRecord>, Error> {
         let attester_key = AttesterKey::new(validator_index, target);
         Ok(txn
-            .get(self.attesters_db, &attester_key)
-            .optional()?
-            .map(AttesterRecord::from_ssz_bytes)
+            .get(self.attesters_db, &attester_key)?
+            .map(|bytes| AttesterRecord::from_ssz_bytes(&bytes))
             .transpose()?)
     }
 
@@ -429,9 +538,8 @@ impl<E: EthSpec> SlasherDB<E> {
     ) -> Result<Option<SignedBeaconBlockHeader>, Error> {
         let proposer_key = ProposerKey::new(proposer_index, slot);
         Ok(txn
-            .get(self.proposers_db, &proposer_key)
-            .optional()?
-            .map(SignedBeaconBlockHeader::from_ssz_bytes)
+            .get(self.proposers_db, &proposer_key)?
+            .map(|bytes| SignedBeaconBlockHeader::from_ssz_bytes(&bytes))
             .transpose()?)
     }
 
@@ -459,16 +567,19 @@ impl<E: EthSpec> SlasherDB<E> {
                 self.proposers_db,
                 &ProposerKey::new(proposer_index, slot),
                 &block_header.as_ssz_bytes(),
-                Self::write_flags(),
             )?;
             Ok(ProposerSlashingStatus::NotSlashable)
         }
     }
 
-    /// Attempt to prune the database, deleting old blocks and attestations.
-    pub fn prune(&self, current_epoch: Epoch) -> Result<(), Error> {
+    /// Attempt to prune the database, deleting blocks and attestations which are older than the
+    /// most recent `history_length` epochs.
+    ///
+    /// The `history_length` may be shorter than the configured history length, to free space.
+    pub fn prune(&self, current_epoch: Epoch, history_length: usize) -> Result<(), Error> {
         let mut txn = self.begin_rw_txn()?;
-        self.try_prune(current_epoch, &mut txn).allow_map_full()?;
+        self.try_prune(current_epoch, history_length, &mut txn)
+            .allow_map_full()?;
         txn.commit()?;
         Ok(())
     }
@@ -479,151 +590,285 @@ impl<E: EthSpec> SlasherDB<E> {
     pub fn try_prune(
         &self,
         current_epoch: Epoch,
+        history_length: usize,
         txn: &mut RwTransaction<'_>,
     ) -> Result<(), Error> {
-        self.prune_proposers(current_epoch, txn)?;
-        self.prune_attesters(current_epoch, txn)?;
-        self.prune_indexed_attestations(current_epoch, txn)?;
-        Ok(())
-    }
-
-    fn prune_proposers(
-        &self,
-        current_epoch: Epoch,
-        txn: &mut RwTransaction<'_>,
-    ) -> Result<(), Error> {
-        let min_slot = current_epoch
+        let min_epoch = current_epoch
             .saturating_add(1u64)
-            .saturating_sub(self.config.history_length)
-            .start_slot(E::slots_per_epoch());
-
-        let mut cursor = txn.open_rw_cursor(self.proposers_db)?;
+            .saturating_sub(history_length as u64);
 
-        // Position cursor at first key, bailing out if the database is empty.
-        if cursor
-            .get(None, None, lmdb_sys::MDB_FIRST)
-            .optional()?
-            .is_none()
-        {
-            return Ok(());
-        }
+        self.prune_proposers(min_epoch, txn)?;
+        self.prune_attesters(min_epoch, txn)?;
+        self.prune_indexed_attestations(min_epoch, txn)?;
+        Ok(())
+    }
 
-        loop {
-            let key_bytes = cursor
-                .get(None, None, lmdb_sys::MDB_GET_CURRENT)?
-                .0
-                .ok_or_else(|| Error::MissingProposerKey)?;
+    fn prune_proposers(&self, min_epoch: Epoch, txn: &mut RwTransaction<'_>) -> Result<(), Error> {
+        let min_slot = min_epoch.start_slot(E::slots_per_epoch());
 
+        txn.delete_while(self.proposers_db, |key_bytes| {
             let (slot, _) = ProposerKey::parse(key_bytes)?;
-            if slot < min_slot {
-                cursor.del(Self::write_flags())?;
-
-                // End the loop if there is no next entry.
-                if cursor
-                    .get(None, None, lmdb_sys::MDB_NEXT)
-                    .optional()?
-                    .is_none()
-                {
-                    break;
-                }
-            } else {
-                break;
-            }
-        }
+            Ok(slot < min_slot)
+        })
+    }
 
-        Ok(())
+    fn prune_attesters(&self, min_epoch: Epoch, txn: &mut RwTransaction<'_>) -> Result<(), Error> {
+        txn.delete_while(self.attesters_db, |key_bytes| {
+            let (target_epoch, _) = AttesterKey::parse(key_bytes)?;
+            Ok(target_epoch < min_epoch)
+        })
     }
 
-    fn prune_attesters(
+    fn prune_indexed_attestations(
         &self,
-        current_epoch: Epoch,
+        min_epoch: Epoch,
         txn: &mut RwTransaction<'_>,
     ) -> Result<(), Error> {
-        let min_epoch = current_epoch
-            .saturating_add(1u64)
-            .saturating_sub(self.config.history_length as u64);
+        txn.delete_while(self.indexed_attestation_db, |key_bytes| {
+            let (target_epoch, _) = IndexedAttestationKey::parse(key_bytes)?;
+            Ok(target_epoch < min_epoch)
+        })
+    }
 
-        let mut cursor = txn.open_rw_cursor(self.attesters_db)?;
+    /// The tables which are copied by an export, which is all of them apart from the metadata.
+    fn export_tables(&self) -> [(&'static str, Database); 6] {
+        [
+            (INDEXED_ATTESTATION_DB, self.indexed_attestation_db),
+            (ATTESTERS_DB, self.attesters_db),
+            (MIN_TARGETS_DB, self.min_targets_db),
+            (MAX_TARGETS_DB, self.max_targets_db),
+            (CURRENT_EPOCHS_DB, self.current_epochs_db),
+            (PROPOSERS_DB, self.proposers_db),
+        ]
+    }
 
-        // Position cursor at first key, bailing out if the database is empty.
-        if cursor
-            .get(None, None, lmdb_sys::MDB_FIRST)
-            .optional()?
-            .is_none()
-        {
-            return Ok(());
+    /// Write the contents of the database to `writer` as a compressed stream, returning the number
+    /// of records written.
+    ///
+    /// Records are exported as raw keys and values, so an export may be imported into a database
+    /// using a different backend.
+    pub fn export(&self, writer: impl Write) -> Result<u64, Error> {
+        let mut encoder = ZlibEncoder::new(writer, Compression::default());
+
+        let header = ExportHeader {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            config: (*self.config).clone(),
+        };
+        bincode::serialize_into(&mut encoder, &header)?;
+
+        // The transaction is only read from, and is aborted when dropped.
+        let txn = self.begin_rw_txn()?;
+        let mut num_records = 0;
+        for (table, db) in self.export_tables().iter() {
+            txn.for_each(*db, |key, value| {
+                let record = ExportRecord {
+                    table: table.to_string(),
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                };
+                bincode::serialize_into(&mut encoder, &Some(record))?;
+                num_records += 1;
+                Ok(true)
+            })?;
         }
+        bincode::serialize_into(&mut encoder, &None::<ExportRecord>)?;
+        encoder.finish()?.flush()?;
 
-        loop {
-            let key_bytes = cursor
-                .get(None, None, lmdb_sys::MDB_GET_CURRENT)?
-                .0
-                .ok_or_else(|| Error::MissingAttesterKey)?;
+        Ok(num_records)
+    }
 
-            let (target_epoch, _) = AttesterKey::parse(key_bytes)?;
+    /// Read an export written by `export` into this database, returning the number of records
+    /// imported.
+    ///
+    /// The database must be empty, and its config compatible with that of the exporting database.
+    /// Nothing is written unless the whole export is read successfully.
+    pub fn import(&self, reader: impl Read) -> Result<u64, Error> {
+        let mut decoder = ZlibDecoder::new(reader);
+
+        let header: ExportHeader = bincode::deserialize_from(&mut decoder)?;
+        if header.schema_version != CURRENT_SCHEMA_VERSION {
+            return Err(Error::IncompatibleSchemaVersion {
+                database_schema_version: header.schema_version,
+                software_schema_version: CURRENT_SCHEMA_VERSION,
+            });
+        }
+        if !self.config.is_compatible(&header.config) {
+            return Err(Error::ImportConfigIncompatible {
+                export_config: header.config,
+                config: (*self.config).clone(),
+            });
+        }
 
-            if target_epoch < min_epoch {
-                cursor.del(Self::write_flags())?;
+        let tables = self.export_tables();
+        let mut txn = self.begin_rw_txn()?;
 
-                // End the loop if there is no next entry.
-                if cursor
-                    .get(None, None, lmdb_sys::MDB_NEXT)
-                    .optional()?
-                    .is_none()
-                {
-                    break;
-                }
-            } else {
-                break;
+        for (_, db) in tables.iter() {
+            let mut is_empty = true;
+            txn.for_each(*db, |_, _| {
+                is_empty = false;
+                Ok(false)
+            })?;
+            if !is_empty {
+                return Err(Error::ImportDatabaseNotEmpty);
             }
         }
 
-        Ok(())
-    }
-
-    fn prune_indexed_attestations(
-        &self,
-        current_epoch: Epoch,
-        txn: &mut RwTransaction<'_>,
-    ) -> Result<(), Error> {
-        let min_epoch = current_epoch
-            .saturating_add(1u64)
-            .saturating_sub(self.config.history_length as u64);
-
-        let mut cursor = txn.open_rw_cursor(self.indexed_attestation_db)?;
-
-        // Position cursor at first key, bailing out if the database is empty.
-        if cursor
-            .get(None, None, lmdb_sys::MDB_FIRST)
-            .optional()?
-            .is_none()
+        let mut num_records = 0;
+        while let Some(record) = bincode::deserialize_from::<_, Option<ExportRecord>>(&mut decoder)?
         {
-            return Ok(());
+            let db = tables
+                .iter()
+                .find(|(table, _)| *table == record.table)
+                .map(|(_, db)| *db)
+                .ok_or(Error::ImportUnknownTable {
+                    table: record.table,
+                })?;
+            txn.put(db, &record.key, &record.value)?;
+            num_records += 1;
         }
+        txn.commit()?;
 
-        loop {
-            let key_bytes = cursor
-                .get(None, None, lmdb_sys::MDB_GET_CURRENT)?
-                .0
-                .ok_or_else(|| Error::MissingAttesterKey)?;
+        Ok(num_records)
+    }
+    /// Return the schema version of the database, which is current for a new database.
+    pub fn schema_version(&self) -> Result<u64, Error> {
+        let mut txn = self.begin_rw_txn()?;
+        Ok(self
+            .load_schema_version(&mut txn)?
+            .unwrap_or(CURRENT_SCHEMA_VERSION))
+    }
+
+    /// Work out the migrations required to bring the database to the current schema version, and
+    /// how many records each would touch, without changing anything.
+    pub fn plan_schema_migration(&self) -> Result<SchemaMigrationPlan, Error> {
+        let from = self.schema_version()?;
+        let to = CURRENT_SCHEMA_VERSION;
+        if from > to {
+            return Err(Error::IncompatibleSchemaVersion {
+                database_schema_version: from,
+                software_schema_version: to,
+            });
+        }
 
-            let (target_epoch, _) = IndexedAttestationKey::parse(key_bytes)?;
+        // The transaction is only read from, and is aborted when dropped.
+        let txn = self.begin_rw_txn()?;
+        let steps = (from..to)
+            .map(|version| {
+                let migration = SCHEMA_MIGRATIONS
+                    .iter()
+                    .find(|migration| migration.from == version)
+                    .ok_or(Error::IncompatibleSchemaVersion {
+                        database_schema_version: from,
+                        software_schema_version: to,
+                    })?;
+
+                let mut records = 0;
+                let mut bytes = 0;
+                for table in migration.tables {
+                    txn.for_each(self.table(table)?, |key, value| {
+                        records += 1;
+                        bytes += (key.len() + value.len()) as u64;
+                        Ok(true)
+                    })?;
+                }
+                Ok(SchemaMigrationStep {
+                    migration,
+                    records,
+                    bytes,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
 
-            if target_epoch < min_epoch {
-                cursor.del(Self::write_flags())?;
+        Ok(SchemaMigrationPlan { from, to, steps })
+    }
 
-                if cursor
-                    .get(None, None, lmdb_sys::MDB_NEXT)
-                    .optional()?
-                    .is_none()
-                {
+    /// Apply a single step of a migration.
+    ///
+    /// The records are rewritten in chunks of `MIGRATION_CHUNK_SIZE`, one transaction per chunk,
+    /// with the progress made committed alongside each chunk. The new schema version is only
+    /// recorded once every record has been rewritten, and an interrupted migration resumes from
+    /// the last chunk committed.
+    pub fn apply_schema_migration(&self, migration: &SchemaMigration) -> Result<(), Error> {
+        let mut txn = self.begin_rw_txn()?;
+        let schema_version = self
+            .load_schema_version(&mut txn)?
+            .unwrap_or(CURRENT_SCHEMA_VERSION);
+        if schema_version != migration.from {
+            return Err(Error::IncompatibleSchemaVersion {
+                database_schema_version: schema_version,
+                software_schema_version: migration.to,
+            });
+        }
+        let progress = self
+            .load_migration_progress(&mut txn)?
+            .filter(|progress| progress.to == migration.to);
+        drop(txn);
+
+        let (first_table, mut start_after) =
+            progress.map_or((0, None), |progress| (progress.table, progress.last_key));
+        for (table_index, table) in migration.tables.iter().enumerate().skip(first_table) {
+            let db = self.table(table)?;
+            loop {
+                let mut txn = self.begin_rw_txn()?;
+                let last_key = txn.update_each(
+                    db,
+                    start_after.as_deref(),
+                    MIGRATION_CHUNK_SIZE,
+                    migration.rewrite,
+                )?;
+                // Move on to the next table once the end of this one has been reached.
+                let progress = MigrationProgress {
+                    to: migration.to,
+                    table: if last_key.is_some() {
+                        table_index
+                    } else {
+                        table_index + 1
+                    },
+                    last_key,
+                };
+                txn.put(
+                    self.metadata_db,
+                    &METADATA_MIGRATION_PROGRESS_KEY,
+                    &bincode::serialize(&progress)?,
+                )?;
+                txn.commit()?;
+
+                start_after = progress.last_key;
+                if start_after.is_none() {
                     break;
                 }
-            } else {
-                break;
             }
         }
 
+        let mut txn = self.begin_rw_txn()?;
+        txn.delete(self.metadata_db, &METADATA_MIGRATION_PROGRESS_KEY)?;
+        txn.put(
+            self.metadata_db,
+            &METADATA_VERSION_KEY,
+            &bincode::serialize(&migration.to)?,
+        )?;
+        txn.commit()?;
         Ok(())
     }
+
+    fn load_migration_progress(
+        &self,
+        txn: &mut RwTransaction<'_>,
+    ) -> Result<Option<MigrationProgress>, Error> {
+        Ok(txn
+            .get(self.metadata_db, &METADATA_MIGRATION_PROGRESS_KEY)?
+            .map(|bytes| bincode::deserialize(&bytes))
+            .transpose()?)
+    }
+
+    /// Look up one of the tables of an export by name.
+    fn table(&self, name: &str) -> Result<Database, Error> {
+        self.export_tables()
+            .iter()
+            .find(|(table, _)| *table == name)
+            .map(|(_, db)| *db)
+            .ok_or_else(|| Error::UnknownTable {
+                table: name.to_string(),
+            })
+    }
 }
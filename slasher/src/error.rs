@@ -1,10 +1,12 @@
-use crate::Config;
+use crate::{config::DatabaseBackend, Config};
 use std::io;
 use types::{Epoch, Hash256};
 
 #[derive(Debug)]
 pub enum Error {
     DatabaseError(lmdb::Error),
+    #[cfg(feature = "redb")]
+    RedbError(redb::Error),
     DatabaseIOError(io::Error),
     SszDecodeError(ssz::DecodeError),
     BincodeError(bincode::Error),
@@ -14,6 +16,12 @@ pub enum Error {
         database_schema_version: u64,
         software_schema_version: u64,
     },
+    /// The database was written with an older schema, and must be upgraded with
+    /// `lighthouse slasher migrate` before it can be opened.
+    SchemaMigrationRequired {
+        database_schema_version: u64,
+        software_schema_version: u64,
+    },
     ConfigInvalidChunkSize {
         chunk_size: usize,
         history_length: usize,
@@ -25,6 +33,28 @@ pub enum Error {
         on_disk_config: Config,
         config: Config,
     },
+    /// The database on disk was written by a different backend to the one configured.
+    DatabaseBackendMismatch {
+        on_disk: DatabaseBackend,
+        config: DatabaseBackend,
+    },
+    /// The configured backend was not compiled in, as it is behind a cargo feature.
+    DatabaseBackendNotCompiled(DatabaseBackend),
+    /// A table of one backend was used with a transaction of another.
+    DatabaseHandleMismatch,
+    /// An export was imported into a database which already contains records.
+    ImportDatabaseNotEmpty,
+    /// An export was written with a config which is incompatible with the importing database.
+    ImportConfigIncompatible {
+        export_config: Config,
+        config: Config,
+    },
+    ImportUnknownTable {
+        table: String,
+    },
+    UnknownTable {
+        table: String,
+    },
     DistanceTooLarge,
     DistanceCalculationOverflow,
     /// Missing an attester record that we expected to exist.
@@ -47,12 +77,20 @@ pub enum Error {
     MissingIndexedAttestation {
         root: Hash256,
     },
+    MissingKey,
     MissingAttesterKey,
     MissingProposerKey,
     MissingIndexedAttestationKey,
     AttesterRecordInconsistentRoot,
 }
 
+impl Error {
+    /// Return `true` if the error was caused by the database reaching its maximum size.
+    pub fn is_database_full(&self) -> bool {
+        matches!(self, Error::DatabaseError(lmdb::Error::MapFull))
+    }
+}
+
 impl From<lmdb::Error> for Error {
     fn from(e: lmdb::Error) -> Self {
         match e {
@@ -62,6 +100,13 @@ impl From<lmdb::Error> for Error {
     }
 }
 
+#[cfg(feature = "redb")]
+impl From<redb::Error> for Error {
+    fn from(e: redb::Error) -> Self {
+        Error::RedbError(e)
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         Error::DatabaseIOError(e)
@@ -3,6 +3,7 @@
 mod array;
 mod attestation_queue;
 mod attester_record;
+mod backend;
 mod block_queue;
 pub mod config;
 mod database;
@@ -10,6 +11,7 @@ mod error;
 mod metrics;
 mod slasher;
 mod slasher_server;
+mod status;
 pub mod test_utils;
 mod utils;
 
@@ -17,10 +19,14 @@ pub use crate::slasher::Slasher;
 pub use attestation_queue::{AttestationBatch, AttestationQueue};
 pub use attester_record::AttesterRecord;
 pub use block_queue::BlockQueue;
-pub use config::Config;
-pub use database::SlasherDB;
+pub use config::{Config, DatabaseBackend};
+pub use database::{
+    RewriteRecord, SchemaMigration, SchemaMigrationPlan, SchemaMigrationStep, SlasherDB,
+    MIGRATION_CHUNK_SIZE, SCHEMA_MIGRATIONS,
+};
 pub use error::Error;
 pub use slasher_server::SlasherServer;
+pub use status::{EpochStats, SlasherStatus};
 
 use types::{AttesterSlashing, EthSpec, IndexedAttestation, ProposerSlashing};
 
@@ -4,7 +4,11 @@ pub use lighthouse_metrics::*;
 lazy_static! {
     pub static ref SLASHER_DATABASE_SIZE: Result<IntGauge> = try_create_int_gauge(
         "slasher_database_size",
-        "Size of the LMDB database backing the slasher, in bytes"
+        "Size of the database backing the slasher, in bytes"
+    );
+    pub static ref SLASHER_HISTORY_LENGTH: Result<IntGauge> = try_create_int_gauge(
+        "slasher_history_length",
+        "Number of epochs of history kept, which is reduced if the database is full"
     );
     pub static ref SLASHER_RUN_TIME: Result<Histogram> = try_create_histogram(
         "slasher_process_batch_time",
@@ -35,4 +39,35 @@ lazy_static! {
         "slasher_compression_ratio",
         "Compression ratio for min-max array chunks (higher is better)"
     );
+    pub static ref SLASHER_ATTESTATIONS_DROPPED_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "slasher_attestations_dropped_total",
+        "Total number of attestations dropped for being too old or too far in the future"
+    );
+    pub static ref SLASHER_ATTESTATIONS_REJECTED_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "slasher_attestations_rejected_total",
+        "Total number of attestations rejected because the queue reached its memory budget"
+    );
+    pub static ref SLASHER_ATTESTATIONS_DEFERRED_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "slasher_attestations_deferred_total",
+        "Total number of times an attestation was deferred to a later batch"
+    );
+    pub static ref SLASHER_CHUNK_UPDATE_TIME: Result<Histogram> = try_create_histogram(
+        "slasher_chunk_update_time",
+        "Time taken to update the min-max target arrays for a chunk of validators"
+    );
+    pub static ref SLASHER_BYTES_WRITTEN_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "slasher_bytes_written_total",
+        "Total number of bytes of keys and values written to the database"
+    );
+    pub static ref SLASHER_WRITE_AMPLIFICATION: Result<Gauge> = try_create_float_gauge(
+        "slasher_write_amplification",
+        "Bytes written to the database per byte of blocks and attestations in the last batch"
+    );
+    pub static ref SLASHER_DETECTION_LATENCY: Result<HistogramVec> =
+        try_create_histogram_vec_with_buckets(
+            "slasher_detection_latency",
+            "Time from receiving an attestation to producing a slashing which includes it",
+            vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0],
+            &["kind"],
+        );
 }
@@ -1,20 +1,40 @@
+use crate::backend::RwTransaction;
 use crate::metrics::{
-    self, SLASHER_NUM_ATTESTATIONS_DEFERRED, SLASHER_NUM_ATTESTATIONS_DROPPED,
-    SLASHER_NUM_ATTESTATIONS_VALID, SLASHER_NUM_BLOCKS_PROCESSED,
+    self, SLASHER_ATTESTATIONS_DEFERRED_TOTAL, SLASHER_ATTESTATIONS_DROPPED_TOTAL,
+    SLASHER_ATTESTATIONS_REJECTED_TOTAL, SLASHER_BYTES_WRITTEN_TOTAL, SLASHER_CHUNK_UPDATE_TIME,
+    SLASHER_DETECTION_LATENCY, SLASHER_HISTORY_LENGTH, SLASHER_NUM_ATTESTATIONS_DEFERRED,
+    SLASHER_NUM_ATTESTATIONS_DROPPED, SLASHER_NUM_ATTESTATIONS_VALID, SLASHER_NUM_BLOCKS_PROCESSED,
+    SLASHER_WRITE_AMPLIFICATION,
 };
+use crate::status::ProcessingStats;
 use crate::{
     array, AttestationBatch, AttestationQueue, AttesterRecord, BlockQueue, Config, Error,
-    ProposerSlashingStatus, SlasherDB,
+    ProposerSlashingStatus, SlasherDB, SlasherStatus,
 };
-use lmdb::{RwTransaction, Transaction};
 use parking_lot::Mutex;
-use slog::{debug, error, info, Logger};
+use slog::{debug, error, info, warn, Logger};
+use ssz::Encode;
+use std::cmp::max;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use types::{
     AttesterSlashing, Epoch, EthSpec, IndexedAttestation, ProposerSlashing, SignedBeaconBlockHeader,
 };
 
+/// The fewest epochs of history that the slasher will prune down to when its database is full.
+const MIN_HISTORY_LENGTH: usize = 2;
+
+/// The fraction of the history which is dropped each time the database is found to be full.
+const HISTORY_REDUCTION_DIVISOR: usize = 4;
+
+const MEGABYTE: u64 = 1 << 20;
+
+/// The most attestations queued while the slasher is paused, or fewer if the memory budget is
+/// reached first.
+const PAUSED_MAX_QUEUED_ATTESTATIONS: usize = 1 << 18;
+
 #[derive(Debug)]
 pub struct Slasher<E: EthSpec> {
     db: SlasherDB<E>,
@@ -23,6 +43,17 @@ pub struct Slasher<E: EthSpec> {
     attester_slashings: Mutex<HashSet<AttesterSlashing<E>>>,
     proposer_slashings: Mutex<HashSet<ProposerSlashing>>,
     config: Arc<Config>,
+    /// The number of epochs of history currently kept, which is reduced below the configured
+    /// history length if the database reaches its maximum size.
+    history_length: AtomicUsize,
+    /// The size of the database after the previous update, in bytes.
+    last_database_size: AtomicU64,
+    /// The number of attestations rejected since the previous update, because the queue was full.
+    attestations_rejected: AtomicU64,
+    /// Whether updates are suspended, leaving blocks and attestations in the queues.
+    paused: AtomicBool,
+    /// Counts of the work done by each update, for the `status`.
+    stats: Mutex<ProcessingStats>,
     pub(crate) log: Logger,
 }
 
@@ -35,6 +66,8 @@ impl<E: EthSpec> Slasher<E> {
         let proposer_slashings = Mutex::new(HashSet::new());
         let attestation_queue = AttestationQueue::default();
         let block_queue = BlockQueue::default();
+        let history_length = AtomicUsize::new(config.history_length);
+        metrics::set_gauge(&SLASHER_HISTORY_LENGTH, config.history_length as i64);
         Ok(Self {
             db,
             attester_slashings,
@@ -42,6 +75,11 @@ impl<E: EthSpec> Slasher<E> {
             attestation_queue,
             block_queue,
             config,
+            history_length,
+            last_database_size: AtomicU64::new(0),
+            attestations_rejected: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+            stats: Mutex::new(ProcessingStats::default()),
             log,
         })
     }
@@ -60,31 +98,150 @@ impl<E: EthSpec> Slasher<E> {
         &self.config
     }
 
+    /// The number of epochs of history currently kept by the slasher.
+    ///
+    /// This is less than the configured history length if the database has reached its maximum
+    /// size.
+    pub fn history_length(&self) -> usize {
+        self.history_length.load(Ordering::Relaxed)
+    }
+
+    /// Suspend updates, so that blocks and attestations are queued but not processed.
+    ///
+    /// While paused the attestation queue is bounded, and attestations received once it is full
+    /// are dropped.
+    pub fn pause(&self) {
+        if !self.paused.swap(true, Ordering::Relaxed) {
+            info!(self.log, "Slasher paused");
+        }
+    }
+
+    /// Resume updates, processing everything queued while paused in the next update.
+    pub fn resume(&self) {
+        if self.paused.swap(false, Ordering::Relaxed) {
+            info!(
+                self.log,
+                "Slasher resumed";
+                "attestation_queue_len" => self.attestation_queue.len(),
+                "block_queue_len" => self.block_queue.len(),
+            );
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Whether there is no room in the queue for `attestation`, either because the memory budget
+    /// would be exceeded or because the slasher is paused and has queued as many as it may.
+    fn attestation_queue_is_full(&self, attestation: &IndexedAttestation<E>) -> bool {
+        let over_budget = self
+            .config
+            .max_queued_bytes()
+            .map_or(false, |max_queued_bytes| {
+                self.attestation_queue.bytes()
+                    + self.block_queue.bytes()
+                    + AttestationQueue::attestation_bytes(attestation)
+                    > max_queued_bytes
+            });
+        over_budget
+            || (self.is_paused() && self.attestation_queue.len() >= PAUSED_MAX_QUEUED_ATTESTATIONS)
+    }
+
+    /// Return a summary of the slasher's progress.
+    pub fn status(&self) -> SlasherStatus {
+        let history_length = self.history_length();
+        let stats = self.stats.lock();
+        let current_epoch = stats.current_epoch();
+
+        SlasherStatus {
+            database_size: self.last_database_size.load(Ordering::Relaxed),
+            current_epoch,
+            oldest_epoch: current_epoch
+                .map(|epoch| (epoch + 1).saturating_sub(history_length as u64)),
+            history_length,
+            paused: self.is_paused(),
+            attestation_queue_len: self.attestation_queue.len(),
+            block_queue_len: self.block_queue.len(),
+            attester_slashings_found: stats.attester_slashings_found,
+            proposer_slashings_found: stats.proposer_slashings_found,
+            epochs: stats.epochs.iter().copied().collect(),
+        }
+    }
+
     /// Accept an attestation from the network and queue it for processing.
+    ///
+    /// The attestation is dropped if the queues have reached the limit set by the memory budget,
+    /// or the limit while paused.
     pub fn accept_attestation(&self, attestation: IndexedAttestation<E>) {
+        if self.attestation_queue_is_full(&attestation) {
+            self.attestations_rejected.fetch_add(1, Ordering::Relaxed);
+            metrics::inc_counter(&SLASHER_ATTESTATIONS_REJECTED_TOTAL);
+            return;
+        }
         self.attestation_queue.queue(attestation);
     }
 
     /// Accept a block from the network and queue it for processing.
+    ///
+    /// Block headers count towards the memory budget, but are never dropped, as there is at most
+    /// one per slot from each proposer.
     pub fn accept_block_header(&self, block_header: SignedBeaconBlockHeader) {
         self.block_queue.queue(block_header);
     }
 
     /// Apply queued blocks and attestations to the on-disk database, and detect slashings!
+    ///
+    /// If the database is full then the history kept is reduced, so that the next pruning frees
+    /// up space.
     pub fn process_queued(&self, current_epoch: Epoch) -> Result<(), Error> {
+        let result = self.try_process_queued(current_epoch);
+        if let Err(e) = &result {
+            if e.is_database_full() {
+                self.reduce_history_length();
+            }
+        }
+        result
+    }
+
+    fn try_process_queued(&self, current_epoch: Epoch) -> Result<(), Error> {
         let mut txn = self.db.begin_rw_txn()?;
-        self.process_blocks(&mut txn)?;
-        self.process_attestations(current_epoch, &mut txn)?;
+        let block_bytes = self.process_blocks(current_epoch, &mut txn)?;
+        let attestation_bytes = self.process_attestations(current_epoch, &mut txn)?;
+
+        let bytes_written = txn.bytes_written();
         txn.commit()?;
+
+        metrics::inc_counter_by(&SLASHER_BYTES_WRITTEN_TOTAL, bytes_written as i64);
+        let bytes_ingested = block_bytes + attestation_bytes;
+        if bytes_ingested > 0 {
+            metrics::set_float_gauge(
+                &SLASHER_WRITE_AMPLIFICATION,
+                bytes_written as f64 / bytes_ingested as f64,
+            );
+        }
         Ok(())
     }
 
     /// Apply queued blocks to the on-disk database.
-    pub fn process_blocks(&self, txn: &mut RwTransaction<'_>) -> Result<(), Error> {
+    ///
+    /// Returns the number of SSZ bytes of block headers processed.
+    pub fn process_blocks(
+        &self,
+        current_epoch: Epoch,
+        txn: &mut RwTransaction<'_>,
+    ) -> Result<u64, Error> {
         let blocks = self.block_queue.dequeue();
+        let num_bytes = blocks
+            .iter()
+            .map(|block| block.ssz_bytes_len() as u64)
+            .sum();
         let mut slashings = vec![];
 
         metrics::set_gauge(&SLASHER_NUM_BLOCKS_PROCESSED, blocks.len() as i64);
+        self.stats.lock().update_epoch(current_epoch, |stats| {
+            stats.blocks_processed += blocks.len() as u64;
+        });
 
         for block in blocks {
             if let ProposerSlashingStatus::DoubleVote(slashing) =
@@ -100,25 +257,42 @@ impl<E: EthSpec> Slasher<E> {
                 "Found {} new proposer slashings!",
                 slashings.len(),
             );
+            self.stats.lock().proposer_slashings_found += slashings.len() as u64;
             self.proposer_slashings.lock().extend(slashings);
         }
 
-        Ok(())
+        Ok(num_bytes)
     }
 
     /// Apply queued attestations to the on-disk database.
+    ///
+    /// Returns the number of SSZ bytes of valid attestations processed.
     pub fn process_attestations(
         &self,
         current_epoch: Epoch,
         txn: &mut RwTransaction<'_>,
-    ) -> Result<(), Error> {
-        let snapshot = self.attestation_queue.dequeue();
+    ) -> Result<u64, Error> {
+        // Attestations beyond the memory budget of a batch are left in the queue for the next.
+        let snapshot = self
+            .attestation_queue
+            .dequeue(self.config.max_batch_bytes());
+        let num_left = self.attestation_queue.len();
 
         // Filter attestations for relevance.
         let (snapshot, deferred, num_dropped) = self.validate(snapshot, current_epoch);
-        let num_deferred = deferred.len();
+        let num_deferred = deferred.len() + num_left;
         self.attestation_queue.requeue(deferred);
 
+        let num_rejected = self.attestations_rejected.swap(0, Ordering::Relaxed);
+        if num_rejected > 0 {
+            warn!(
+                self.log,
+                "Slasher attestation queue full";
+                "msg" => "attestations were dropped, consider increasing --slasher-memory-budget",
+                "num_rejected" => num_rejected,
+            );
+        }
+
         // Insert attestations into database.
         debug!(
             self.log,
@@ -130,8 +304,16 @@ impl<E: EthSpec> Slasher<E> {
         metrics::set_gauge(&SLASHER_NUM_ATTESTATIONS_VALID, snapshot.len() as i64);
         metrics::set_gauge(&SLASHER_NUM_ATTESTATIONS_DEFERRED, num_deferred as i64);
         metrics::set_gauge(&SLASHER_NUM_ATTESTATIONS_DROPPED, num_dropped as i64);
+        metrics::inc_counter_by(&SLASHER_ATTESTATIONS_DEFERRED_TOTAL, num_deferred as i64);
+        metrics::inc_counter_by(&SLASHER_ATTESTATIONS_DROPPED_TOTAL, num_dropped as i64);
+        self.stats.lock().update_epoch(current_epoch, |stats| {
+            stats.attestations_processed += snapshot.len() as u64;
+            stats.attestations_dropped += num_dropped as u64;
+        });
 
+        let mut num_bytes = 0;
         for attestation in snapshot.attestations.iter() {
+            num_bytes += attestation.0.ssz_bytes_len() as u64;
             self.db.store_indexed_attestation(
                 txn,
                 attestation.1.indexed_attestation_hash,
@@ -144,7 +326,7 @@ impl<E: EthSpec> Slasher<E> {
         for (subqueue_id, subqueue) in grouped_attestations.subqueues.into_iter().enumerate() {
             self.process_batch(txn, subqueue_id, subqueue.attestations, current_epoch)?;
         }
-        Ok(())
+        Ok(num_bytes)
     }
 
     /// Process a batch of attestations for a range of validator indices.
@@ -152,7 +334,7 @@ impl<E: EthSpec> Slasher<E> {
         &self,
         txn: &mut RwTransaction<'_>,
         subqueue_id: usize,
-        batch: Vec<Arc<(IndexedAttestation<E>, AttesterRecord)>>,
+        batch: Vec<Arc<(IndexedAttestation<E>, AttesterRecord, Instant)>>,
         current_epoch: Epoch,
     ) -> Result<(), Error> {
         // First, check for double votes.
@@ -165,7 +347,13 @@ impl<E: EthSpec> Slasher<E> {
                             "Found {} new double-vote slashings!",
                             slashings.len()
                         );
+                        observe_detection_latency(
+                            std::slice::from_ref(attestation),
+                            &slashings,
+                            "double_vote",
+                        );
                     }
+                    self.stats.lock().attester_slashings_found += slashings.len() as u64;
                     self.attester_slashings.lock().extend(slashings);
                 }
                 Err(e) => {
@@ -180,14 +368,18 @@ impl<E: EthSpec> Slasher<E> {
         }
 
         // Then check for surrounds using the min-max arrays.
-        match array::update(
+        let timer = metrics::start_timer(&SLASHER_CHUNK_UPDATE_TIME);
+        let result = array::update(
             &self.db,
             txn,
             subqueue_id,
-            batch,
+            batch.clone(),
             current_epoch,
             &self.config,
-        ) {
+        );
+        metrics::stop_timer(timer);
+
+        match result {
             Ok(slashings) => {
                 if !slashings.is_empty() {
                     info!(
@@ -195,7 +387,9 @@ impl<E: EthSpec> Slasher<E> {
                         "Found {} new surround slashings!",
                         slashings.len()
                     );
+                    observe_detection_latency(&batch, &slashings, "surround");
                 }
+                self.stats.lock().attester_slashings_found += slashings.len() as u64;
                 self.attester_slashings.lock().extend(slashings);
             }
             Err(e) => {
@@ -267,7 +461,7 @@ impl<E: EthSpec> Slasher<E> {
             let source_epoch = attestation.data.source.epoch;
 
             if source_epoch > target_epoch
-                || source_epoch + self.config.history_length as u64 <= current_epoch
+                || source_epoch + self.history_length() as u64 <= current_epoch
             {
                 drop_count += 1;
                 continue;
@@ -294,6 +488,70 @@ impl<E: EthSpec> Slasher<E> {
 
     /// Prune unnecessary attestations and blocks from the on-disk database.
     pub fn prune_database(&self, current_epoch: Epoch) -> Result<(), Error> {
-        self.db.prune(current_epoch)
+        self.db.prune(current_epoch, self.history_length())
+    }
+
+    /// Reduce the history kept if the database has grown beyond its maximum size, given that it
+    /// now occupies `database_size` bytes.
+    ///
+    /// Space freed by pruning is re-used without shrinking the files of the database, so the
+    /// history is only reduced while the database is over its maximum size *and* still growing.
+    pub fn check_database_size(&self, database_size: u64) {
+        let max_database_size = self.config.max_db_size_mbs as u64 * MEGABYTE;
+        let last_database_size = self
+            .last_database_size
+            .swap(database_size, Ordering::Relaxed);
+
+        if database_size > max_database_size && database_size > last_database_size {
+            self.reduce_history_length();
+        }
+    }
+
+    /// Drop the oldest epochs from the history kept, so that they are deleted by the next pruning.
+    fn reduce_history_length(&self) {
+        let history_length = self.history_length();
+        let new_history_length = max(
+            history_length - history_length / HISTORY_REDUCTION_DIVISOR,
+            MIN_HISTORY_LENGTH,
+        );
+        if new_history_length == history_length {
+            error!(
+                self.log,
+                "Slasher database is full";
+                "history_length" => history_length,
+                "msg" => "increase --slasher-max-db-size",
+            );
+            return;
+        }
+
+        self.history_length
+            .store(new_history_length, Ordering::Relaxed);
+        metrics::set_gauge(&SLASHER_HISTORY_LENGTH, new_history_length as i64);
+        warn!(
+            self.log,
+            "Slasher database is full, reducing history";
+            "history_length" => new_history_length,
+            "configured_history_length" => self.config.history_length,
+            "msg" => "increase --slasher-max-db-size or reduce --slasher-history-length",
+        );
+    }
+}
+
+/// Record the time since each attestation of `batch` which is part of a slashing was received.
+fn observe_detection_latency<E: EthSpec>(
+    batch: &[Arc<(IndexedAttestation<E>, AttesterRecord, Instant)>],
+    slashings: &HashSet<AttesterSlashing<E>>,
+    kind: &str,
+) {
+    for attestation in batch {
+        if slashings.iter().any(|slashing| {
+            slashing.attestation_1 == attestation.0 || slashing.attestation_2 == attestation.0
+        }) {
+            metrics::observe_vec(
+                &SLASHER_DETECTION_LATENCY,
+                &[kind],
+                attestation.2.elapsed().as_secs_f64(),
+            );
+        }
     }
 }
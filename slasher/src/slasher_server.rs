@@ -14,9 +14,12 @@ use types::EthSpec;
 pub struct SlasherServer;
 
 impl SlasherServer {
-    pub fn run<E: EthSpec, C: SlotClock + 'static>(
+    /// Start the slasher, calling `on_update` after each batch of blocks and attestations is
+    /// processed.
+    pub fn run<E: EthSpec, C: SlotClock + 'static, F: Fn() + Send + 'static>(
         slasher: Arc<Slasher<E>>,
         slot_clock: C,
+        on_update: F,
         executor: &TaskExecutor,
     ) {
         info!(slasher.log, "Starting slasher to detect misbehaviour");
@@ -50,6 +53,16 @@ impl SlasherServer {
         executor.spawn_blocking(
             move || {
                 while let Ok(current_epoch) = receiver.recv() {
+                    if slasher.is_paused() {
+                        debug!(
+                            slasher.log,
+                            "Slasher paused, skipping update";
+                            "epoch" => current_epoch,
+                            "num_attestations" => slasher.attestation_queue.len(),
+                        );
+                        continue;
+                    }
+
                     let t = Instant::now();
                     let num_attestations = slasher.attestation_queue.len();
                     let num_blocks = slasher.block_queue.len();
@@ -65,8 +78,10 @@ impl SlasherServer {
                     }
                     drop(batch_timer);
 
+                    on_update();
+
                     // Prune the database, even in the case where batch processing failed.
-                    // If the LMDB database is full then pruning could help to free it up.
+                    // If the database is full then pruning with the reduced history frees it up.
                     if let Err(e) = slasher.prune_database(current_epoch) {
                         error!(
                             slasher.log,
@@ -87,6 +102,7 @@ impl SlasherServer {
 
                     let database_size = size_of_dir(&slasher.config().database_path);
                     metrics::set_gauge(&SLASHER_DATABASE_SIZE, database_size as i64);
+                    slasher.check_database_size(database_size);
                 }
             },
             "slasher_server_process_queued",
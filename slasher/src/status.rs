@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use types::Epoch;
+
+/// The number of recent epochs for which processing counts are kept.
+const STATUS_EPOCHS: usize = 8;
+
+/// A summary of the slasher's progress, used to check that it is keeping up with the network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlasherStatus {
+    /// The size of the database at the end of the last update, in bytes.
+    pub database_size: u64,
+    /// The epoch of the last update, or `None` if there hasn't been one.
+    pub current_epoch: Option<Epoch>,
+    /// The oldest epoch retained by pruning as of the last update.
+    pub oldest_epoch: Option<Epoch>,
+    /// The number of epochs of history currently kept.
+    pub history_length: usize,
+    /// Whether updates are paused.
+    pub paused: bool,
+    /// The attestations waiting to be processed.
+    pub attestation_queue_len: usize,
+    /// The blocks waiting to be processed.
+    pub block_queue_len: usize,
+    /// The number of attester slashings found since the slasher started.
+    pub attester_slashings_found: u64,
+    /// The number of proposer slashings found since the slasher started.
+    pub proposer_slashings_found: u64,
+    /// Processing counts for the most recent epochs, oldest first.
+    pub epochs: Vec<EpochStats>,
+}
+
+/// The number of attestations and blocks processed by updates during an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EpochStats {
+    pub epoch: Epoch,
+    /// Attestations stored in the database.
+    pub attestations_processed: u64,
+    /// Attestations discarded for being too old or invalid.
+    pub attestations_dropped: u64,
+    /// Block headers stored in the database.
+    pub blocks_processed: u64,
+}
+
+/// Running counts which are reported in the `SlasherStatus`.
+#[derive(Debug, Default)]
+pub struct ProcessingStats {
+    pub epochs: VecDeque<EpochStats>,
+    pub attester_slashings_found: u64,
+    pub proposer_slashings_found: u64,
+}
+
+impl ProcessingStats {
+    /// Add to the counts for `epoch`, starting a new entry if it is later than the last one.
+    pub fn update_epoch(&mut self, epoch: Epoch, update: impl FnOnce(&mut EpochStats)) {
+        // Updates never run for an earlier epoch, so the last entry is always current.
+        if self
+            .current_epoch()
+            .map_or(true, |current_epoch| current_epoch < epoch)
+        {
+            if self.epochs.len() == STATUS_EPOCHS {
+                self.epochs.pop_front();
+            }
+            self.epochs.push_back(EpochStats {
+                epoch,
+                ..EpochStats::default()
+            });
+        }
+
+        if let Some(stats) = self.epochs.back_mut() {
+            update(stats);
+        }
+    }
+
+    /// The epoch of the most recent update.
+    pub fn current_epoch(&self) -> Option<Epoch> {
+        self.epochs.back().map(|stats| stats.epoch)
+    }
+}
@@ -24,7 +24,7 @@ impl<T> TxnMapFull<T, Error> for Result<T, Error> {
     fn allow_map_full(self) -> Result<Option<T>, Error> {
         match self {
             Ok(x) => Ok(Some(x)),
-            Err(Error::DatabaseError(lmdb::Error::MapFull)) => Ok(None),
+            Err(e) if e.is_database_full() => Ok(None),
             Err(e) => Err(e),
         }
     }
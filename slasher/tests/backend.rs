@@ -0,0 +1,79 @@
+#![cfg(feature = "redb")]
+
+use maplit::hashset;
+use slasher::{
+    test_utils::{att_slashing, block as test_block, indexed_att, logger, E},
+    Config, DatabaseBackend, Error, Slasher,
+};
+use tempdir::TempDir;
+use types::Epoch;
+
+fn redb_config(tempdir: &TempDir) -> Config {
+    let mut config = Config::new(tempdir.path().into());
+    config.backend = DatabaseBackend::Redb;
+    config
+}
+
+#[test]
+fn redb_attester_slashings() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let slasher = Slasher::<E>::open(redb_config(&tempdir), logger()).unwrap();
+    let current_epoch = Epoch::new(3);
+
+    let v = vec![0, 1, 2];
+    let double_1 = indexed_att(&v, 0, 1, 0);
+    let double_2 = indexed_att(&v, 0, 1, 1);
+    let surrounded = indexed_att(&v, 1, 2, 0);
+    let surrounding = indexed_att(&v, 0, 3, 0);
+
+    for attestation in &[&double_1, &surrounded] {
+        slasher.accept_attestation((*attestation).clone());
+    }
+    slasher.process_queued(current_epoch).unwrap();
+    for attestation in &[&double_2, &surrounding] {
+        slasher.accept_attestation((*attestation).clone());
+    }
+    slasher.process_queued(current_epoch).unwrap();
+
+    assert_eq!(
+        slasher.get_attester_slashings(),
+        hashset![
+            att_slashing(&double_1, &double_2),
+            att_slashing(&surrounding, &surrounded)
+        ]
+    );
+
+    slasher.prune_database(current_epoch).unwrap();
+}
+
+#[test]
+fn redb_proposer_slashings_persist() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let current_epoch = Epoch::new(0);
+
+    {
+        let slasher = Slasher::<E>::open(redb_config(&tempdir), logger()).unwrap();
+        slasher.accept_block_header(test_block(1, 7, 0));
+        slasher.process_queued(current_epoch).unwrap();
+    }
+
+    // The first block should be read back from disk by a new slasher.
+    let slasher = Slasher::<E>::open(redb_config(&tempdir), logger()).unwrap();
+    slasher.accept_block_header(test_block(1, 7, 1));
+    slasher.process_queued(current_epoch).unwrap();
+    assert_eq!(slasher.get_proposer_slashings().len(), 1);
+}
+
+#[test]
+fn backend_mismatch() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    Slasher::<E>::open(Config::new(tempdir.path().into()), logger()).unwrap();
+
+    match Slasher::<E>::open(redb_config(&tempdir), logger()) {
+        Err(Error::DatabaseBackendMismatch { on_disk, config }) => {
+            assert_eq!(on_disk, DatabaseBackend::Lmdb);
+            assert_eq!(config, DatabaseBackend::Redb);
+        }
+        other => panic!("expected backend mismatch, got {:?}", other.map(|_| ())),
+    }
+}
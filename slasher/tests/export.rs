@@ -0,0 +1,96 @@
+use maplit::hashset;
+use slasher::{
+    test_utils::{att_slashing, block as test_block, indexed_att, logger, E},
+    Config, DatabaseBackend, Error, Slasher, SlasherDB,
+};
+use std::sync::Arc;
+use tempdir::TempDir;
+use types::Epoch;
+
+/// The backend imported into, which differs from that of the export when redb is available.
+#[cfg(feature = "redb")]
+const IMPORT_BACKEND: DatabaseBackend = DatabaseBackend::Redb;
+#[cfg(not(feature = "redb"))]
+const IMPORT_BACKEND: DatabaseBackend = DatabaseBackend::Lmdb;
+
+/// Export the database at `config`, which must not be open.
+fn export(config: Config) -> Vec<u8> {
+    let db = SlasherDB::<E>::open(Arc::new(config)).unwrap();
+    let mut export = vec![];
+    db.export(&mut export).unwrap();
+    export
+}
+
+#[test]
+fn export_import_across_backends() {
+    let lmdb_dir = TempDir::new("slasher").unwrap();
+    let import_dir = TempDir::new("slasher").unwrap();
+    let lmdb_config = Config::new(lmdb_dir.path().into());
+    let mut import_config = Config::new(import_dir.path().into());
+    import_config.backend = IMPORT_BACKEND;
+    let current_epoch = Epoch::new(3);
+
+    let v = vec![0, 1, 2];
+    let surrounded = indexed_att(&v, 1, 2, 0);
+    let surrounding = indexed_att(&v, 0, 3, 0);
+
+    {
+        let slasher = Slasher::<E>::open(lmdb_config.clone(), logger()).unwrap();
+        slasher.accept_attestation(surrounded.clone());
+        slasher.accept_block_header(test_block(1, 7, 0));
+        slasher.process_queued(current_epoch).unwrap();
+    }
+
+    let export = export(lmdb_config);
+    {
+        let db = SlasherDB::<E>::open(Arc::new(import_config.clone())).unwrap();
+        assert!(db.import(&export[..]).unwrap() > 0);
+    }
+
+    // The imported history should be used to detect slashings.
+    let slasher = Slasher::<E>::open(import_config, logger()).unwrap();
+    slasher.accept_attestation(surrounding.clone());
+    slasher.accept_block_header(test_block(1, 7, 1));
+    slasher.process_queued(current_epoch).unwrap();
+
+    assert_eq!(
+        slasher.get_attester_slashings(),
+        hashset![att_slashing(&surrounding, &surrounded)]
+    );
+    assert_eq!(slasher.get_proposer_slashings().len(), 1);
+}
+
+#[test]
+fn import_into_non_empty() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+
+    {
+        let slasher = Slasher::<E>::open(config.clone(), logger()).unwrap();
+        slasher.accept_attestation(indexed_att(&[0], 0, 1, 0));
+        slasher.process_queued(Epoch::new(1)).unwrap();
+    }
+
+    let export = export(config.clone());
+    let db = SlasherDB::<E>::open(Arc::new(config)).unwrap();
+    assert!(matches!(
+        db.import(&export[..]),
+        Err(Error::ImportDatabaseNotEmpty)
+    ));
+}
+
+#[test]
+fn import_incompatible_config() {
+    let export_dir = TempDir::new("slasher").unwrap();
+    let import_dir = TempDir::new("slasher").unwrap();
+    let export_config = Config::new(export_dir.path().into());
+    let mut import_config = Config::new(import_dir.path().into());
+    import_config.validator_chunk_size *= 2;
+
+    let export = export(export_config);
+    let db = SlasherDB::<E>::open(Arc::new(import_config)).unwrap();
+    assert!(matches!(
+        db.import(&export[..]),
+        Err(Error::ImportConfigIncompatible { .. })
+    ));
+}
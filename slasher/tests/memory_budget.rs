@@ -0,0 +1,70 @@
+use slasher::{
+    test_utils::{block, indexed_att, logger, E},
+    AttestationQueue, BlockQueue, Config, Slasher,
+};
+use tempdir::TempDir;
+use types::Epoch;
+
+#[test]
+fn memory_budget_defers_and_rejects() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let mut config = Config::new(tempdir.path().into());
+    config.memory_budget_mbs = Some(1);
+
+    // Each of the attestations has a single attester, so they all use the same memory.
+    let attestation_bytes = AttestationQueue::attestation_bytes(&indexed_att(&[0], 0, 1, 0));
+    let max_queued = config.max_queued_bytes().unwrap() / attestation_bytes;
+    let max_batch = config.max_batch_bytes().unwrap() / attestation_bytes;
+    assert!(max_batch < max_queued);
+
+    let slasher = Slasher::<E>::open(config, logger()).unwrap();
+    for i in 0..max_queued as u64 + 10 {
+        slasher.accept_attestation(indexed_att(&[i], 0, 1, 0));
+    }
+
+    // Attestations beyond the queue's budget are rejected.
+    assert_eq!(slasher.status().attestation_queue_len, max_queued);
+
+    // Those beyond the batch's budget are left for the next batch.
+    let current_epoch = Epoch::new(1);
+    slasher.process_queued(current_epoch).unwrap();
+    assert_eq!(
+        slasher.status().attestation_queue_len,
+        max_queued - max_batch
+    );
+
+    while slasher.status().attestation_queue_len > 0 {
+        slasher.process_queued(current_epoch).unwrap();
+    }
+    assert_eq!(
+        slasher.status().epochs[0].attestations_processed,
+        max_queued as u64
+    );
+}
+
+#[test]
+fn memory_budget_counts_attesters_and_block_headers() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let mut config = Config::new(tempdir.path().into());
+    config.memory_budget_mbs = Some(1);
+    let max_queued_bytes = config.max_queued_bytes().unwrap();
+
+    let slasher = Slasher::<E>::open(config, logger()).unwrap();
+    let block_header = block(1, 0, 0);
+    let block_header_bytes = BlockQueue::block_header_bytes(&block_header);
+    slasher.accept_block_header(block_header);
+
+    // Attestations with more attesters use more of the budget.
+    let attesters = (0..64).collect::<Vec<u64>>();
+    let attestation_bytes = AttestationQueue::attestation_bytes(&indexed_att(&attesters, 0, 1, 0));
+    assert!(attestation_bytes > AttestationQueue::attestation_bytes(&indexed_att(&[0], 0, 1, 0)));
+
+    for i in 0..(max_queued_bytes / attestation_bytes) as u64 + 10 {
+        slasher.accept_attestation(indexed_att(&attesters, 0, 1, i));
+    }
+    assert_eq!(
+        slasher.status().attestation_queue_len,
+        (max_queued_bytes - block_header_bytes) / attestation_bytes
+    );
+    assert_eq!(slasher.status().block_queue_len, 1);
+}
@@ -0,0 +1,123 @@
+use slasher::{
+    test_utils::{indexed_att, logger, E},
+    Config, Error, SchemaMigration, Slasher, SlasherDB, MIGRATION_CHUNK_SIZE,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempdir::TempDir;
+use types::Epoch;
+
+#[test]
+fn current_schema_needs_no_migration() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+
+    {
+        let slasher = Slasher::<E>::open(config.clone(), logger()).unwrap();
+        slasher.accept_attestation(indexed_att(&[0], 0, 1, 0));
+        slasher.process_queued(Epoch::new(1)).unwrap();
+    }
+
+    let db = SlasherDB::<E>::open_without_schema_migration(Arc::new(config)).unwrap();
+    let plan = db.plan_schema_migration().unwrap();
+    assert_eq!(plan.from, plan.to);
+    assert!(plan.steps.is_empty());
+    assert_eq!(plan.bytes(), 0);
+}
+
+static RECORDS_REWRITTEN: AtomicUsize = AtomicUsize::new(0);
+
+fn count_rewrite(_key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    RECORDS_REWRITTEN.fetch_add(1, Ordering::SeqCst);
+    Ok(Some(value.to_vec()))
+}
+
+#[test]
+fn migration_rewrites_records_and_schema_version() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+
+    {
+        let slasher = Slasher::<E>::open(config.clone(), logger()).unwrap();
+        slasher.accept_attestation(indexed_att(&[0], 0, 1, 0));
+        slasher.accept_attestation(indexed_att(&[1], 0, 1, 1));
+        slasher.process_queued(Epoch::new(1)).unwrap();
+    }
+
+    let db = SlasherDB::<E>::open_without_schema_migration(Arc::new(config.clone())).unwrap();
+    let from = db.schema_version().unwrap();
+    let migration = SchemaMigration {
+        from,
+        to: from + 1,
+        description: "Rewrite indexed attestations",
+        tables: &["indexed_attestations"],
+        rewrite: count_rewrite,
+    };
+
+    db.apply_schema_migration(&migration).unwrap();
+    assert_eq!(RECORDS_REWRITTEN.load(Ordering::SeqCst), 2);
+    assert_eq!(db.schema_version().unwrap(), from + 1);
+
+    // The migration can't be applied again, and the software no longer opens the database.
+    assert!(db.apply_schema_migration(&migration).is_err());
+    drop(db);
+    assert!(matches!(
+        SlasherDB::<E>::open(Arc::new(config)),
+        Err(Error::IncompatibleSchemaVersion { .. })
+    ));
+}
+
+static ATTESTERS_REWRITTEN: AtomicUsize = AtomicUsize::new(0);
+static FAIL_AFTER: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Fails once `FAIL_AFTER` records have been rewritten, to interrupt a migration.
+fn interruptible_rewrite(_key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    if ATTESTERS_REWRITTEN.load(Ordering::SeqCst) == FAIL_AFTER.load(Ordering::SeqCst) {
+        return Err(Error::MissingKey);
+    }
+    ATTESTERS_REWRITTEN.fetch_add(1, Ordering::SeqCst);
+    Ok(Some(value.to_vec()))
+}
+
+#[test]
+fn interrupted_migration_resumes_from_last_chunk() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+
+    // One attester record per validator, spanning several chunks.
+    let num_records = 3 * MIGRATION_CHUNK_SIZE;
+    {
+        let slasher = Slasher::<E>::open(config.clone(), logger()).unwrap();
+        let validators = (0..num_records as u64).collect::<Vec<_>>();
+        for indices in validators.chunks(num_records / 2) {
+            slasher.accept_attestation(indexed_att(indices, 0, 1, 0));
+        }
+        slasher.process_queued(Epoch::new(1)).unwrap();
+    }
+
+    let db = SlasherDB::<E>::open_without_schema_migration(Arc::new(config)).unwrap();
+    let from = db.schema_version().unwrap();
+    let migration = SchemaMigration {
+        from,
+        to: from + 1,
+        description: "Rewrite attester records",
+        tables: &["attesters"],
+        rewrite: interruptible_rewrite,
+    };
+
+    // Fail halfway through the third chunk, leaving the first two chunks committed.
+    let committed = 2 * MIGRATION_CHUNK_SIZE;
+    let failed = MIGRATION_CHUNK_SIZE / 2;
+    FAIL_AFTER.store(committed + failed, Ordering::SeqCst);
+    assert!(db.apply_schema_migration(&migration).is_err());
+    assert_eq!(db.schema_version().unwrap(), from);
+
+    // Resuming rewrites only the records after the last committed chunk.
+    FAIL_AFTER.store(usize::MAX, Ordering::SeqCst);
+    db.apply_schema_migration(&migration).unwrap();
+    assert_eq!(
+        ATTESTERS_REWRITTEN.load(Ordering::SeqCst),
+        committed + failed + (num_records - committed)
+    );
+    assert_eq!(db.schema_version().unwrap(), from + 1);
+}
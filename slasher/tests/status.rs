@@ -0,0 +1,80 @@
+use slasher::{
+    test_utils::{block as test_block, indexed_att, logger, E},
+    Config, EpochStats, Slasher,
+};
+use tempdir::TempDir;
+use types::Epoch;
+
+#[test]
+fn status_counts() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+    let history_length = config.history_length;
+    let slasher = Slasher::<E>::open(config, logger()).unwrap();
+
+    let status = slasher.status();
+    assert_eq!(status.current_epoch, None);
+    assert_eq!(status.oldest_epoch, None);
+    assert!(status.epochs.is_empty());
+
+    let v = vec![0, 1];
+    slasher.accept_attestation(indexed_att(&v, 0, 1, 0));
+    slasher.accept_attestation(indexed_att(&v, 0, 1, 1));
+    slasher.accept_block_header(test_block(8, 3, 0));
+    slasher.accept_block_header(test_block(8, 3, 1));
+
+    let status = slasher.status();
+    assert_eq!(status.attestation_queue_len, 2);
+    assert_eq!(status.block_queue_len, 2);
+
+    let current_epoch = Epoch::new(1);
+    slasher.process_queued(current_epoch).unwrap();
+
+    // A future attestation is deferred rather than counted.
+    slasher.accept_attestation(indexed_att(&v, 1, 2, 0));
+    slasher.process_queued(current_epoch).unwrap();
+
+    let status = slasher.status();
+    assert_eq!(status.current_epoch, Some(current_epoch));
+    assert_eq!(status.oldest_epoch, Some(Epoch::new(0)));
+    assert_eq!(status.history_length, history_length);
+    assert_eq!(status.attestation_queue_len, 1);
+    assert_eq!(status.block_queue_len, 0);
+    assert_eq!(status.attester_slashings_found, 1);
+    assert_eq!(status.proposer_slashings_found, 1);
+    assert_eq!(
+        status.epochs,
+        vec![EpochStats {
+            epoch: current_epoch,
+            attestations_processed: 2,
+            attestations_dropped: 0,
+            blocks_processed: 2,
+        }]
+    );
+
+    slasher.process_queued(current_epoch + 1).unwrap();
+
+    let status = slasher.status();
+    assert_eq!(status.current_epoch, Some(current_epoch + 1));
+    assert_eq!(status.attestation_queue_len, 0);
+    assert_eq!(status.epochs.len(), 2);
+    assert_eq!(status.epochs[1].attestations_processed, 1);
+}
+
+#[test]
+fn pause_and_resume() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let config = Config::new(tempdir.path().into());
+    let slasher = Slasher::<E>::open(config, logger()).unwrap();
+    assert!(!slasher.status().paused);
+
+    // Attestations are still queued while paused.
+    slasher.pause();
+    slasher.accept_attestation(indexed_att(&[0], 0, 1, 0));
+    let status = slasher.status();
+    assert!(status.paused);
+    assert_eq!(status.attestation_queue_len, 1);
+
+    slasher.resume();
+    assert!(!slasher.status().paused);
+}
@@ -1,5 +1,5 @@
 use slasher::{
-    test_utils::{indexed_att, logger},
+    test_utils::{indexed_att, logger, E},
     Config, Error, Slasher,
 };
 use tempdir::TempDir;
@@ -87,3 +87,70 @@ fn pruning_with_map_full() {
         }
     }
 }
+
+// Test that a full database reduces the history kept, so that pruning frees space.
+#[test]
+fn map_full_reduces_history() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let mut config = Config::new(tempdir.path().into());
+    config.validator_chunk_size = 1;
+    config.chunk_size = 16;
+    config.history_length = 1024;
+    config.max_db_size_mbs = 1;
+
+    let slasher = Slasher::open(config.clone(), logger()).unwrap();
+
+    let v = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+    let mut current_epoch = Epoch::new(1);
+
+    loop {
+        slasher.accept_attestation(indexed_att(
+            v.clone(),
+            (current_epoch - 1).as_u64(),
+            current_epoch.as_u64(),
+            0,
+        ));
+        match slasher.process_queued(current_epoch) {
+            Ok(()) => current_epoch += 1,
+            Err(e) if e.is_database_full() => break,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    let history_length = slasher.history_length();
+    assert!(history_length < config.history_length);
+
+    // Attestations older than the reduced history are dropped, so this double vote is ignored.
+    slasher.prune_database(current_epoch).unwrap();
+    slasher.accept_attestation(indexed_att(
+        v.clone(),
+        current_epoch.as_u64() - history_length as u64,
+        current_epoch.as_u64() - 1,
+        1,
+    ));
+    slasher.process_queued(current_epoch).unwrap();
+    assert!(slasher.get_attester_slashings().is_empty());
+}
+
+#[test]
+fn growth_beyond_max_size_reduces_history() {
+    let tempdir = TempDir::new("slasher").unwrap();
+    let mut config = Config::new(tempdir.path().into());
+    config.max_db_size_mbs = 1;
+    let max_size = 1 << 20;
+
+    let slasher = Slasher::<E>::open(config.clone(), logger()).unwrap();
+
+    slasher.check_database_size(max_size / 2);
+    assert_eq!(slasher.history_length(), config.history_length);
+
+    // Over the limit and growing.
+    slasher.check_database_size(max_size + 1);
+    let history_length = slasher.history_length();
+    assert!(history_length < config.history_length);
+
+    // Over the limit, but not growing as freed space is re-used.
+    slasher.check_database_size(max_size + 1);
+    assert_eq!(slasher.history_length(), history_length);
+}
@@ -87,6 +87,9 @@ pub fn testing_client_config() -> ClientConfig {
     client_config.network.libp2p_port = 0;
     client_config.network.discovery_port = 0;
     client_config.network.upnp_enabled = false;
+    // All testing nodes share the loopback address.
+    client_config.network.max_peers_per_ip = 0;
+    client_config.network.max_peers_per_subnet = 0;
     client_config.http_api.enabled = true;
     client_config.http_api.listen_port = 0;
     client_config.websocket_server.enabled = true;